@@ -84,6 +84,11 @@ impl AuthorityVerifier {
         self.known_authorities.insert(authority_id, public_key);
     }
 
+    /// Whether `authority_id` has a registered public key
+    pub fn is_registered(&self, authority_id: &str) -> bool {
+        self.known_authorities.contains_key(authority_id)
+    }
+
     /// Verify a command signature
     ///
     /// # Arguments
@@ -77,6 +77,34 @@ pub struct GeoBoundary {
     pub vertices: Vec<Coordinate>,
 }
 
+impl GeoBoundary {
+    /// Check whether a point lies inside the boundary polygon
+    ///
+    /// Treats latitude and longitude as planar coordinates, which holds for
+    /// boundaries that do not span the antimeridian or a pole. A boundary
+    /// with fewer than three vertices contains nothing.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        if self.vertices.len() < 3 {
+            return false;
+        }
+        // Ray casting: count the edges a ray towards increasing longitude crosses
+        let mut inside = false;
+        let mut previous = &self.vertices[self.vertices.len() - 1];
+        for vertex in &self.vertices {
+            if (vertex.lat > lat) != (previous.lat > lat) {
+                let crossing_lon = vertex.lon
+                    + (lat - vertex.lat) / (previous.lat - vertex.lat)
+                        * (previous.lon - vertex.lon);
+                if lon < crossing_lon {
+                    inside = !inside;
+                }
+            }
+            previous = vertex;
+        }
+        inside
+    }
+}
+
 /// Mesh topology configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MeshTopology {
@@ -229,4 +257,33 @@ mod tests {
         let deserialized: SwarmCommand = serde_json::from_str(&json).unwrap();
         assert_eq!(cmd, deserialized);
     }
+
+    #[test]
+    fn test_geo_boundary_contains() {
+        let corner = |lat, lon| Coordinate {
+            lat,
+            lon,
+            alt: None,
+        };
+        // L-shaped boundary, so the notch is outside
+        let boundary = GeoBoundary {
+            vertices: vec![
+                corner(45.0, -122.0),
+                corner(45.0, -121.0),
+                corner(45.5, -121.0),
+                corner(45.5, -121.5),
+                corner(46.0, -121.5),
+                corner(46.0, -122.0),
+            ],
+        };
+        assert!(boundary.contains(45.25, -121.5));
+        assert!(boundary.contains(45.75, -121.75));
+        assert!(!boundary.contains(45.75, -121.25));
+        assert!(!boundary.contains(44.0, -121.5));
+
+        let degenerate = GeoBoundary {
+            vertices: vec![corner(45.0, -122.0), corner(46.0, -121.0)],
+        };
+        assert!(!degenerate.contains(45.5, -121.5));
+    }
 }
@@ -2,12 +2,36 @@
 //!
 //! This module provides command dispatch logic with fan-out for swarm operations
 //! and result aggregation.
+//!
+//! Commands reach units over a `UnitLink`, signed with the router's key so
+//! units can tell them from forgeries. Each delivery waits for the unit's
+//! signed ACK or NACK, checked against the key registered for the unit and
+//! bound to that dispatch, so an answer to an earlier one is refused; a
+//! unit that stays silent for the acknowledgement timeout is sent the command
+//! again, up to the attempt limit. Progress reports restart the timeout.
+//! Swarm commands fan out to all units at once and can be aborted while in
+//! flight.
+//!
+//! Every step of a delivery is recorded in the dispatcher's `CommandFeed`,
+//! which backs command status queries and watches.
 
 #![warn(missing_docs)]
 
 use crate::command_types::{SwarmCommand, UnitCommand};
+use crate::feeds::{CommandFeed, CommandState, CommandStatusEvent, LiveFeed};
+use crate::unit_link::{
+    CommandEnvelope, CommandPayload, LinkMessage, ReplyKind, ReplySink, UnitLink,
+};
+use aethercore_core::sync;
+use ed25519_dalek::SigningKey;
+use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+use tokio::sync::watch;
+use tracing::{debug, warn};
 
 /// Dispatcher errors
 #[derive(Debug, Error)]
@@ -41,6 +65,14 @@ pub enum DispatchError {
         /// Reason for data loss
         reason: String,
     },
+
+    /// A command with this identifier is already being dispatched
+    #[error("Command already in flight: {0}")]
+    DuplicateCommand(String),
+
+    /// No command with this identifier is being dispatched
+    #[error("Command not in flight: {0}")]
+    UnknownCommand(String),
 }
 
 /// Command dispatch result for a single unit
@@ -149,25 +181,106 @@ impl SwarmDispatchStatus {
     }
 }
 
+/// Swarm command being dispatched
+struct InFlight {
+    target_unit_ids: Vec<String>,
+    abort: watch::Sender<Option<String>>,
+}
+
 /// Command dispatcher for unit and swarm operations
-#[derive(Debug)]
 pub struct CommandDispatcher {
     /// Maximum batch size for swarm commands
     max_batch_size: usize,
     /// Integrity status tracker (optional - if None, integrity checks are disabled)
     integrity_tracker:
         Option<std::sync::Arc<std::sync::Mutex<aethercore_stream::StreamIntegrityTracker>>>,
+    /// Link commands are sent over (if None, every unit is unreachable)
+    link: Option<Arc<dyn UnitLink>>,
+    /// Key commands and aborts are signed with (if None, every dispatch fails)
+    router_key: Option<SigningKey>,
+    /// Routes unit replies back to waiting dispatches
+    replies: ReplySink,
+    /// Public keys unit replies are verified against
+    unit_keys: RwLock<HashMap<String, [u8; 32]>>,
+    /// How long to wait for a unit's answer before sending again
+    ack_timeout: Duration,
+    /// Deliveries of a command to one unit before giving up
+    max_attempts: u32,
+    /// Swarm commands being dispatched, by swarm command ID
+    in_flight: Mutex<HashMap<String, InFlight>>,
+    /// Delivery steps of recent commands
+    status_feed: Arc<LiveFeed<CommandFeed>>,
+}
+
+impl std::fmt::Debug for CommandDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandDispatcher")
+            .field("max_batch_size", &self.max_batch_size)
+            .field("has_link", &self.link.is_some())
+            .field("has_router_key", &self.router_key.is_some())
+            .field("ack_timeout", &self.ack_timeout)
+            .field("max_attempts", &self.max_attempts)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Removes a swarm command from the in-flight table when its dispatch ends
+struct InFlightGuard<'a> {
+    dispatcher: &'a CommandDispatcher,
+    swarm_command_id: String,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.dispatcher
+            .lock_in_flight()
+            .remove(&self.swarm_command_id);
+    }
+}
+
+fn current_timestamp_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64
+}
+
+/// Resolve with the abort reason once the command is aborted
+async fn aborted(abort: &mut Option<watch::Receiver<Option<String>>>) -> String {
+    if let Some(abort) = abort {
+        if let Ok(reason) = abort.wait_for(Option::is_some).await {
+            return reason.clone().unwrap_or_default();
+        }
+    }
+    std::future::pending().await
 }
 
 impl CommandDispatcher {
     /// Maximum allowed batch size (from spec: ≤ 100 units per batch)
     pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
 
+    /// Default time to wait for a unit's answer before sending again
+    pub const DEFAULT_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Default deliveries of a command to one unit before giving up
+    pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
     /// Create a new command dispatcher without integrity checking
+    ///
+    /// Until a link is attached with `with_unit_link` and a key set with
+    /// `with_router_key`, every dispatch fails.
     pub fn new() -> Self {
         Self {
             max_batch_size: Self::DEFAULT_MAX_BATCH_SIZE,
             integrity_tracker: None,
+            link: None,
+            router_key: None,
+            replies: ReplySink::new(),
+            unit_keys: RwLock::new(HashMap::new()),
+            ack_timeout: Self::DEFAULT_ACK_TIMEOUT,
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+            in_flight: Mutex::new(HashMap::new()),
+            status_feed: Arc::new(LiveFeed::new(CommandFeed::new())),
         }
     }
 
@@ -175,7 +288,7 @@ impl CommandDispatcher {
     pub fn with_max_batch_size(max_batch_size: usize) -> Self {
         Self {
             max_batch_size,
-            integrity_tracker: None,
+            ..Self::new()
         }
     }
 
@@ -184,11 +297,88 @@ impl CommandDispatcher {
         tracker: std::sync::Arc<std::sync::Mutex<aethercore_stream::StreamIntegrityTracker>>,
     ) -> Self {
         Self {
-            max_batch_size: Self::DEFAULT_MAX_BATCH_SIZE,
             integrity_tracker: Some(tracker),
+            ..Self::new()
         }
     }
 
+    /// Send commands over `link`
+    ///
+    /// The link must hand unit replies to this dispatcher's `reply_sink`.
+    pub fn with_unit_link(mut self, link: Arc<dyn UnitLink>) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    /// Sign every message sent to units with `key`
+    ///
+    /// Units are provisioned with its public key and drop messages that do
+    /// not verify against it.
+    pub fn with_router_key(mut self, key: SigningKey) -> Self {
+        self.router_key = Some(key);
+        self
+    }
+
+    /// Public key units verify the router's messages against
+    pub fn router_public_key(&self) -> Option<[u8; 32]> {
+        self.router_key
+            .as_ref()
+            .map(|key| key.verifying_key().to_bytes())
+    }
+
+    /// Wait `ack_timeout` for each answer and deliver each command at most
+    /// `max_attempts` times
+    pub fn with_retry_policy(mut self, ack_timeout: Duration, max_attempts: u32) -> Self {
+        self.ack_timeout = ack_timeout;
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sink unit links deliver replies into
+    pub fn reply_sink(&self) -> ReplySink {
+        self.replies.clone()
+    }
+
+    /// Feed the delivery steps of every command are recorded in
+    pub fn command_feed(&self) -> Arc<LiveFeed<CommandFeed>> {
+        self.status_feed.clone()
+    }
+
+    /// Register the key a unit signs its replies with
+    ///
+    /// Replies from units without a registered key are never accepted.
+    pub fn register_unit_key(&self, unit_id: &str, public_key: [u8; 32]) {
+        sync::write(&self.unit_keys).insert(unit_id.to_string(), public_key);
+    }
+
+    fn unit_key(&self, unit_id: &str) -> Option<[u8; 32]> {
+        sync::read(&self.unit_keys).get(unit_id).copied()
+    }
+
+    fn record(&self, command_id: &str, unit_id: Option<&str>, state: CommandState) {
+        self.status_feed.push(CommandStatusEvent {
+            command_id: command_id.to_string(),
+            unit_id: unit_id.map(str::to_string),
+            state,
+            timestamp_ns: current_timestamp_ns(),
+        });
+    }
+
+    fn record_finished(&self, command_id: &str, results: &[UnitDispatchResult]) {
+        self.record(
+            command_id,
+            None,
+            CommandState::Finished {
+                total_units: results.len(),
+                success_count: results.iter().filter(|r| r.is_success()).count(),
+            },
+        );
+    }
+
+    fn lock_in_flight(&self) -> MutexGuard<'_, HashMap<String, InFlight>> {
+        sync::lock(&self.in_flight)
+    }
+
     /// Check if a unit's integrity is compromised
     fn check_integrity(&self, unit_id: &str) -> Result<(), DispatchError> {
         if let Some(tracker) = &self.integrity_tracker {
@@ -215,29 +405,168 @@ impl CommandDispatcher {
         Ok(())
     }
 
+    /// Send a command to one unit and wait for its verdict
+    async fn deliver(
+        &self,
+        unit_id: &str,
+        command_id: &str,
+        payload: CommandPayload,
+        timestamp_ns: u64,
+        mut abort: Option<watch::Receiver<Option<String>>>,
+    ) -> UnitDispatchResult {
+        let failed = |reason: String| {
+            self.record(
+                command_id,
+                Some(unit_id),
+                CommandState::Failed {
+                    reason: reason.clone(),
+                },
+            );
+            UnitDispatchResult::Failed {
+                unit_id: unit_id.to_string(),
+                reason,
+                timestamp_ns: current_timestamp_ns(),
+            }
+        };
+        let Some(link) = &self.link else {
+            return failed("No unit link configured".to_string());
+        };
+        let Some(router_key) = &self.router_key else {
+            return failed("No router key configured".to_string());
+        };
+        let Some(public_key) = self.unit_key(unit_id) else {
+            return failed(format!("No key registered for unit {}", unit_id));
+        };
+        let Some(mut waiter) = self.replies.wait_for(command_id, unit_id) else {
+            return failed(format!("Command {} already in flight", command_id));
+        };
+
+        let dispatched_ns = current_timestamp_ns();
+        let mut link_error = None;
+        for attempt in 1..=self.max_attempts {
+            let message = LinkMessage::Command(CommandEnvelope::sign(
+                command_id,
+                unit_id,
+                payload.clone(),
+                attempt,
+                timestamp_ns,
+                dispatched_ns,
+                router_key,
+            ));
+            link_error = match link.send(unit_id, &message).await {
+                Ok(()) => {
+                    self.record(command_id, Some(unit_id), CommandState::Sent { attempt });
+                    None
+                }
+                Err(e) => {
+                    warn!(unit_id, command_id, attempt, "Command send failed: {}", e);
+                    Some(e.to_string())
+                }
+            };
+
+            // Wait for an answer, then send again
+            let mut deadline = tokio::time::Instant::now() + self.ack_timeout;
+            loop {
+                let reply = tokio::select! {
+                    reply = tokio::time::timeout_at(deadline, waiter.recv()) => reply,
+                    reason = aborted(&mut abort) => {
+                        self.record(
+                            command_id,
+                            Some(unit_id),
+                            CommandState::Aborted {
+                                reason: reason.clone(),
+                            },
+                        );
+                        return UnitDispatchResult::Failed {
+                            unit_id: unit_id.to_string(),
+                            reason: format!("Aborted: {}", reason),
+                            timestamp_ns: current_timestamp_ns(),
+                        };
+                    }
+                };
+                let Ok(reply) = reply else {
+                    break;
+                };
+                if !reply.verify(&public_key) {
+                    warn!(
+                        unit_id,
+                        command_id, "Dropping unit reply with bad signature"
+                    );
+                    continue;
+                }
+                if reply.dispatched_ns != dispatched_ns || reply.timestamp_ns < dispatched_ns {
+                    warn!(
+                        unit_id,
+                        command_id, "Dropping unit reply to an earlier dispatch"
+                    );
+                    continue;
+                }
+                match reply.kind {
+                    ReplyKind::Progress { percent } => {
+                        debug!(unit_id, command_id, percent, "Unit reported progress");
+                        self.record(
+                            command_id,
+                            Some(unit_id),
+                            CommandState::Progress { percent },
+                        );
+                        deadline = tokio::time::Instant::now() + self.ack_timeout;
+                    }
+                    ReplyKind::Ack => {
+                        self.record(command_id, Some(unit_id), CommandState::Acknowledged);
+                        return UnitDispatchResult::Success {
+                            unit_id: unit_id.to_string(),
+                            timestamp_ns: current_timestamp_ns(),
+                        };
+                    }
+                    ReplyKind::Nack { reason } => return failed(reason),
+                }
+            }
+        }
+
+        match link_error {
+            Some(reason) => failed(reason),
+            None => {
+                self.record(command_id, Some(unit_id), CommandState::TimedOut);
+                UnitDispatchResult::Timeout {
+                    unit_id: unit_id.to_string(),
+                    timestamp_ns: current_timestamp_ns(),
+                }
+            }
+        }
+    }
+
     /// Dispatch a command to a single unit
     ///
-    /// This is a placeholder that returns a mock result. In production, this would:
-    /// 1. Check integrity status
-    /// 2. Validate unit_id
-    /// 3. Send command via gRPC/message queue
-    /// 4. Wait for acknowledgment
-    /// 5. Return result
-    pub fn dispatch_unit_command(
+    /// Checks the unit's integrity, sends the command over the unit link and
+    /// waits for the unit to acknowledge it, sending it again if the unit
+    /// stays silent.
+    ///
+    /// # Arguments
+    /// * `command_id` - Unique identifier for the command
+    /// * `unit_id` - Target unit
+    /// * `command` - Unit command to execute
+    /// * `timestamp_ns` - Command timestamp
+    pub async fn dispatch_unit_command(
         &self,
+        command_id: &str,
         unit_id: &str,
-        _command: &UnitCommand,
+        command: &UnitCommand,
         timestamp_ns: u64,
     ) -> Result<UnitDispatchResult, DispatchError> {
         // Check integrity first - block compromised nodes
         self.check_integrity(unit_id)?;
 
-        // Placeholder: In production, this would dispatch to the actual unit
-        // For now, simulate success
-        Ok(UnitDispatchResult::Success {
-            unit_id: unit_id.to_string(),
-            timestamp_ns,
-        })
+        let result = self
+            .deliver(
+                unit_id,
+                command_id,
+                CommandPayload::Unit(command.clone()),
+                timestamp_ns,
+                None,
+            )
+            .await;
+        self.record_finished(command_id, std::slice::from_ref(&result));
+        Ok(result)
     }
 
     /// Fan out swarm command to multiple units
@@ -249,11 +578,12 @@ impl CommandDispatcher {
     /// * `timestamp_ns` - Command timestamp
     ///
     /// # Returns
-    /// Aggregated dispatch status
-    pub fn dispatch_swarm_command(
+    /// Aggregated dispatch status, once every unit has answered, timed out
+    /// or been aborted
+    pub async fn dispatch_swarm_command(
         &self,
         swarm_command_id: String,
-        _command: &SwarmCommand,
+        command: &SwarmCommand,
         target_unit_ids: &[String],
         timestamp_ns: u64,
     ) -> Result<SwarmDispatchStatus, DispatchError> {
@@ -265,54 +595,106 @@ impl CommandDispatcher {
             });
         }
 
-        // Convert swarm command to unit commands and dispatch
-        // Check integrity for each unit
-        let unit_results: Vec<UnitDispatchResult> = target_unit_ids
-            .iter()
-            .map(|unit_id| {
-                // Check integrity - if compromised, return failure
-                match self.check_integrity(unit_id) {
-                    Ok(_) => {
-                        // In production, this would convert the swarm command to appropriate unit command
-                        // and dispatch it. For now, simulate success.
-                        UnitDispatchResult::Success {
-                            unit_id: unit_id.clone(),
-                            timestamp_ns,
+        let (abort, abort_receiver) = watch::channel(None);
+        {
+            let mut in_flight = self.lock_in_flight();
+            if in_flight.contains_key(&swarm_command_id) {
+                return Err(DispatchError::DuplicateCommand(swarm_command_id));
+            }
+            in_flight.insert(
+                swarm_command_id.clone(),
+                InFlight {
+                    target_unit_ids: target_unit_ids.to_vec(),
+                    abort,
+                },
+            );
+        }
+        let _guard = InFlightGuard {
+            dispatcher: self,
+            swarm_command_id: swarm_command_id.clone(),
+        };
+
+        // Check integrity for each unit, then deliver to all at once
+        let unit_results: Vec<UnitDispatchResult> =
+            join_all(target_unit_ids.iter().map(|unit_id| {
+                let abort = abort_receiver.clone();
+                let swarm_command_id = &swarm_command_id;
+                async move {
+                    match self.check_integrity(unit_id) {
+                        Ok(_) => {
+                            self.deliver(
+                                unit_id,
+                                swarm_command_id,
+                                CommandPayload::Swarm(command.clone()),
+                                timestamp_ns,
+                                Some(abort),
+                            )
+                            .await
                         }
-                    }
-                    Err(DispatchError::DataLoss { unit_id, reason }) => {
-                        UnitDispatchResult::Failed {
-                            unit_id,
-                            reason,
-                            timestamp_ns,
+                        Err(e) => {
+                            let reason = match e {
+                                DispatchError::DataLoss { reason, .. } => reason,
+                                e => e.to_string(),
+                            };
+                            self.record(
+                                swarm_command_id,
+                                Some(unit_id),
+                                CommandState::Failed {
+                                    reason: reason.clone(),
+                                },
+                            );
+                            UnitDispatchResult::Failed {
+                                unit_id: unit_id.clone(),
+                                reason,
+                                timestamp_ns,
+                            }
                         }
                     }
-                    Err(e) => UnitDispatchResult::Failed {
-                        unit_id: unit_id.clone(),
-                        reason: e.to_string(),
-                        timestamp_ns,
-                    },
                 }
-            })
-            .collect();
+            }))
+            .await;
+        self.record_finished(&swarm_command_id, &unit_results);
 
         Ok(SwarmDispatchStatus::new(
             swarm_command_id,
             unit_results,
-            timestamp_ns,
+            current_timestamp_ns(),
         ))
     }
 
     /// Abort a swarm command
     ///
-    /// Sends abort signal to all units in the swarm operation
-    pub fn abort_swarm_command(
+    /// Sends an abort to every unit the command targets. Units that have not
+    /// acknowledged yet are reported as failed in the command's status.
+    ///
+    /// # Returns
+    /// Number of units the abort was sent to
+    pub async fn abort_swarm_command(
         &self,
-        _swarm_command_id: &str,
-        _target_unit_ids: &[String],
-    ) -> Result<(), DispatchError> {
-        // Placeholder: In production, this would send abort to all units
-        Ok(())
+        swarm_command_id: &str,
+        reason: &str,
+    ) -> Result<usize, DispatchError> {
+        let target_unit_ids = {
+            let in_flight = self.lock_in_flight();
+            let command = in_flight
+                .get(swarm_command_id)
+                .ok_or_else(|| DispatchError::UnknownCommand(swarm_command_id.to_string()))?;
+            command.abort.send_replace(Some(reason.to_string()));
+            command.target_unit_ids.clone()
+        };
+
+        if let (Some(link), Some(router_key)) = (&self.link, &self.router_key) {
+            join_all(target_unit_ids.iter().map(|unit_id| {
+                let message = LinkMessage::abort(swarm_command_id, unit_id, reason, router_key);
+                async move {
+                    if let Err(e) = link.send(unit_id, &message).await {
+                        warn!(unit_id, swarm_command_id, "Abort send failed: {}", e);
+                    }
+                }
+            }))
+            .await;
+        }
+        Ok(target_unit_ids.len())
     }
 }
 
@@ -326,6 +708,48 @@ impl Default for CommandDispatcher {
 mod tests {
     use super::*;
     use crate::command_types::Coordinate;
+    use crate::unit_link::{InMemoryUnitLink, UnitBehavior, UnitReply};
+    use ed25519_dalek::SigningKey;
+
+    fn unit_key(unit_id: &str) -> SigningKey {
+        SigningKey::from_bytes(blake3::hash(unit_id.as_bytes()).as_bytes())
+    }
+
+    fn router_key() -> SigningKey {
+        SigningKey::from_bytes(&[9u8; 32])
+    }
+
+    /// Attach an in-memory link with the given units, their keys registered
+    fn with_units(
+        dispatcher: CommandDispatcher,
+        units: &[(&str, UnitBehavior)],
+    ) -> (CommandDispatcher, Arc<InMemoryUnitLink>) {
+        let link = Arc::new(InMemoryUnitLink::new(
+            dispatcher.reply_sink(),
+            router_key().verifying_key().to_bytes(),
+        ));
+        for (unit_id, behavior) in units {
+            link.add_unit(unit_id, unit_key(unit_id), behavior.clone());
+            dispatcher.register_unit_key(unit_id, unit_key(unit_id).verifying_key().to_bytes());
+        }
+        let dispatcher = dispatcher
+            .with_unit_link(link.clone())
+            .with_router_key(router_key())
+            .with_retry_policy(Duration::from_millis(50), 3);
+        (dispatcher, link)
+    }
+
+    fn navigate_command() -> UnitCommand {
+        UnitCommand::Navigate {
+            waypoint: Coordinate {
+                lat: 0.0,
+                lon: 0.0,
+                alt: Some(100.0),
+            },
+            speed: Some(10.0),
+            altitude: None,
+        }
+    }
 
     #[test]
     fn test_unit_dispatch_result_success() {
@@ -365,8 +789,8 @@ mod tests {
         assert!(!status.all_success());
     }
 
-    #[test]
-    fn test_batch_size_limit() {
+    #[tokio::test]
+    async fn test_batch_size_limit() {
         let dispatcher = CommandDispatcher::new();
         let command = SwarmCommand::RecallAll {
             base_id: "BASE-1".to_string(),
@@ -375,8 +799,9 @@ mod tests {
         // Create too many units
         let unit_ids: Vec<String> = (0..101).map(|i| format!("unit-{}", i)).collect();
 
-        let result =
-            dispatcher.dispatch_swarm_command("swarm-1".to_string(), &command, &unit_ids, 1000);
+        let result = dispatcher
+            .dispatch_swarm_command("swarm-1".to_string(), &command, &unit_ids, 1000)
+            .await;
 
         assert!(matches!(
             result,
@@ -384,9 +809,10 @@ mod tests {
         ));
     }
 
-    #[test]
-    fn test_dispatch_unit_command() {
-        let dispatcher = CommandDispatcher::new();
+    #[tokio::test]
+    async fn test_dispatch_unit_command() {
+        let (dispatcher, link) =
+            with_units(CommandDispatcher::new(), &[("unit-1", UnitBehavior::Ack)]);
         let command = UnitCommand::Navigate {
             waypoint: Coordinate {
                 lat: 45.0,
@@ -397,13 +823,27 @@ mod tests {
             altitude: None,
         };
 
-        let result = dispatcher.dispatch_unit_command("unit-1", &command, 1000);
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &command, 1000)
+            .await;
         assert!(result.is_ok());
         assert!(result.unwrap().is_success());
+
+        let sent = link.sent();
+        assert_eq!(sent.len(), 1);
+        match &sent[0] {
+            (unit_id, LinkMessage::Command(envelope)) => {
+                assert_eq!(unit_id, "unit-1");
+                assert_eq!(envelope.command_id, "cmd-1");
+                assert_eq!(envelope.payload, CommandPayload::Unit(command));
+                assert_eq!(envelope.attempt, 1);
+            }
+            other => panic!("Expected command, got {:?}", other),
+        }
     }
 
-    #[test]
-    fn test_dispatch_unit_command_integrity_check() {
+    #[tokio::test]
+    async fn test_dispatch_unit_command_integrity_check() {
         use aethercore_stream::StreamIntegrityTracker;
         use std::sync::{Arc, Mutex};
 
@@ -414,30 +854,33 @@ mod tests {
             .record_broken_event("Test integrity violation".to_string());
 
         let tracker_arc = Arc::new(Mutex::new(tracker));
-        let dispatcher = CommandDispatcher::with_integrity_tracker(tracker_arc);
+        let (dispatcher, link) = with_units(
+            CommandDispatcher::with_integrity_tracker(tracker_arc),
+            &[
+                ("unit-compromised", UnitBehavior::Ack),
+                ("unit-ok", UnitBehavior::Ack),
+            ],
+        );
 
-        let command = UnitCommand::Navigate {
-            waypoint: Coordinate {
-                lat: 0.0,
-                lon: 0.0,
-                alt: Some(100.0),
-            },
-            speed: Some(10.0),
-            altitude: None,
-        };
+        let command = navigate_command();
 
-        // Compromised unit should fail
-        let result = dispatcher.dispatch_unit_command("unit-compromised", &command, 1000);
+        // Compromised unit should fail without being contacted
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-compromised", &command, 1000)
+            .await;
         assert!(result.is_err());
         assert!(matches!(result, Err(DispatchError::DataLoss { .. })));
+        assert!(link.sent().is_empty());
 
         // Non-compromised unit should succeed
-        let result = dispatcher.dispatch_unit_command("unit-ok", &command, 1000);
-        assert!(result.is_ok());
+        let result = dispatcher
+            .dispatch_unit_command("cmd-2", "unit-ok", &command, 1000)
+            .await;
+        assert!(result.unwrap().is_success());
     }
 
-    #[test]
-    fn test_dispatch_swarm_command_integrity_check() {
+    #[tokio::test]
+    async fn test_dispatch_swarm_command_integrity_check() {
         use aethercore_stream::StreamIntegrityTracker;
         use std::sync::{Arc, Mutex};
 
@@ -448,7 +891,14 @@ mod tests {
             .record_broken_event("Chain discontinuity".to_string());
 
         let tracker_arc = Arc::new(Mutex::new(tracker));
-        let dispatcher = CommandDispatcher::with_integrity_tracker(tracker_arc);
+        let (dispatcher, _link) = with_units(
+            CommandDispatcher::with_integrity_tracker(tracker_arc),
+            &[
+                ("unit-1", UnitBehavior::Ack),
+                ("unit-2", UnitBehavior::Ack),
+                ("unit-3", UnitBehavior::Ack),
+            ],
+        );
 
         let command = SwarmCommand::RecallAll {
             base_id: "BASE-1".to_string(),
@@ -462,6 +912,7 @@ mod tests {
 
         let status = dispatcher
             .dispatch_swarm_command("swarm-1".to_string(), &command, &target_units, 1000)
+            .await
             .unwrap();
 
         // Should have 2 successes and 1 failure
@@ -479,22 +930,318 @@ mod tests {
         assert_eq!(failed.unit_id(), "unit-2");
     }
 
-    #[test]
-    fn test_dispatch_without_integrity_tracker() {
+    #[tokio::test]
+    async fn test_dispatch_without_integrity_tracker() {
         // Dispatcher without integrity tracker should not block anything
+        let (dispatcher, _link) =
+            with_units(CommandDispatcher::new(), &[("any-unit", UnitBehavior::Ack)]);
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "any-unit", &navigate_command(), 1000)
+            .await;
+        assert!(result.unwrap().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_without_link_fails() {
         let dispatcher = CommandDispatcher::new();
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+        assert!(matches!(result, UnitDispatchResult::Failed { .. }));
+    }
 
-        let command = UnitCommand::Navigate {
-            waypoint: Coordinate {
-                lat: 0.0,
-                lon: 0.0,
-                alt: Some(100.0),
-            },
-            speed: Some(10.0),
-            altitude: None,
+    #[tokio::test]
+    async fn test_unit_answers_decide_result() {
+        let (dispatcher, _link) = with_units(
+            CommandDispatcher::new(),
+            &[
+                (
+                    "unit-progress",
+                    UnitBehavior::Progress(vec![25.0, 50.0, 75.0]),
+                ),
+                ("unit-nack", UnitBehavior::Nack("Low battery".to_string())),
+                ("unit-silent", UnitBehavior::Silent),
+            ],
+        );
+        let command = navigate_command();
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-progress", &command, 1000)
+            .await
+            .unwrap();
+        assert!(result.is_success());
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-nack", &command, 1000)
+            .await
+            .unwrap();
+        match result {
+            UnitDispatchResult::Failed { reason, .. } => assert_eq!(reason, "Low battery"),
+            other => panic!("Expected failure, got {:?}", other),
+        }
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-silent", &command, 1000)
+            .await
+            .unwrap();
+        assert!(matches!(result, UnitDispatchResult::Timeout { .. }));
+
+        // Unreachable units fail with the link's reason
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-missing", &command, 1000)
+            .await
+            .unwrap();
+        assert!(matches!(result, UnitDispatchResult::Failed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_steps_recorded() {
+        use crate::feeds::Feed;
+
+        let (dispatcher, _link) = with_units(
+            CommandDispatcher::new(),
+            &[("unit-1", UnitBehavior::Progress(vec![50.0]))],
+        );
+        dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+
+        let feed = dispatcher.command_feed();
+        let feed = feed.read();
+        let states: Vec<(Option<&str>, &CommandState)> = feed
+            .events_after(0)
+            .unwrap()
+            .into_iter()
+            .map(|(_, event)| (event.unit_id.as_deref(), &event.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (Some("unit-1"), &CommandState::Sent { attempt: 1 }),
+                (Some("unit-1"), &CommandState::Progress { percent: 50.0 }),
+                (Some("unit-1"), &CommandState::Acknowledged),
+                (
+                    None,
+                    &CommandState::Finished {
+                        total_units: 1,
+                        success_count: 1
+                    }
+                ),
+            ]
+        );
+        assert_eq!(feed.command_status("cmd-1").unwrap().status, "completed");
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_command_resent() {
+        let (dispatcher, link) = with_units(
+            CommandDispatcher::new(),
+            &[("unit-1", UnitBehavior::DropFirst(2))],
+        );
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+        assert!(result.is_success());
+        assert_eq!(link.executed("unit-1"), 1);
+
+        let attempts: Vec<u32> = link
+            .sent()
+            .iter()
+            .filter_map(|(_, message)| match message {
+                LinkMessage::Command(envelope) => Some(envelope.attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attempts, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_replies_with_wrong_key_ignored() {
+        let (dispatcher, _link) =
+            with_units(CommandDispatcher::new(), &[("unit-1", UnitBehavior::Ack)]);
+        // Another key registered for the unit: its genuine-looking ACKs are forgeries
+        dispatcher.register_unit_key("unit-1", unit_key("impostor").verifying_key().to_bytes());
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+        assert!(matches!(result, UnitDispatchResult::Timeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_replies_to_earlier_dispatch_ignored() {
+        let (dispatcher, link) = with_units(
+            CommandDispatcher::new(),
+            &[("unit-1", UnitBehavior::Silent)],
+        );
+        let key = unit_key("unit-1");
+        let command = navigate_command();
+
+        let dispatch = dispatcher.dispatch_unit_command("cmd-1", "unit-1", &command, 1000);
+        let replay = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let dispatched_ns = match &link.sent()[0] {
+                (_, LinkMessage::Command(envelope)) => envelope.dispatched_ns,
+                other => panic!("Expected command, got {:?}", other),
+            };
+            let sink = dispatcher.reply_sink();
+            // Genuine ACK from an earlier dispatch of the same command
+            assert!(sink.deliver(UnitReply::sign(
+                "cmd-1",
+                "unit-1",
+                ReplyKind::Ack,
+                dispatched_ns - 1,
+                current_timestamp_ns(),
+                &key,
+            )));
+            // Bound to this dispatch but dated before it
+            assert!(sink.deliver(UnitReply::sign(
+                "cmd-1",
+                "unit-1",
+                ReplyKind::Ack,
+                dispatched_ns,
+                dispatched_ns - 1,
+                &key,
+            )));
         };
+        let (result, ()) = tokio::join!(dispatch, replay);
+        assert!(matches!(
+            result.unwrap(),
+            UnitDispatchResult::Timeout { .. }
+        ));
+    }
 
-        let result = dispatcher.dispatch_unit_command("any-unit", &command, 1000);
-        assert!(result.is_ok());
+    #[tokio::test]
+    async fn test_commands_with_wrong_router_key_ignored() {
+        let (dispatcher, link) =
+            with_units(CommandDispatcher::new(), &[("unit-1", UnitBehavior::Ack)]);
+        // Signed by a key the unit was not provisioned with
+        let dispatcher = dispatcher.with_router_key(unit_key("impostor"));
+
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+        assert!(matches!(result, UnitDispatchResult::Timeout { .. }));
+        assert_eq!(link.executed("unit-1"), 0);
+
+        // Nothing is sent unsigned
+        let (mut dispatcher, link) =
+            with_units(CommandDispatcher::new(), &[("unit-1", UnitBehavior::Ack)]);
+        dispatcher.router_key = None;
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+        assert!(matches!(result, UnitDispatchResult::Failed { .. }));
+        assert!(link.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_envelope_replayed_to_other_unit_ignored() {
+        let (dispatcher, link) = with_units(
+            CommandDispatcher::new(),
+            &[("unit-1", UnitBehavior::Ack), ("unit-2", UnitBehavior::Ack)],
+        );
+        let result = dispatcher
+            .dispatch_unit_command("cmd-1", "unit-1", &navigate_command(), 1000)
+            .await
+            .unwrap();
+        assert!(result.is_success());
+
+        // A relay, or unit-1 itself, forwards the signed envelope to unit-2
+        let (_, envelope) = link.sent().remove(0);
+        link.send("unit-2", &envelope).await.unwrap();
+        assert_eq!(link.executed("unit-1"), 1);
+        assert_eq!(link.executed("unit-2"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_swarm_status_reflects_unit_outcomes() {
+        let (dispatcher, _link) = with_units(
+            CommandDispatcher::new(),
+            &[
+                ("unit-1", UnitBehavior::Ack),
+                ("unit-2", UnitBehavior::Nack("Obstacle".to_string())),
+                ("unit-3", UnitBehavior::Silent),
+            ],
+        );
+        let command = SwarmCommand::RecallAll {
+            base_id: "BASE-1".to_string(),
+        };
+        let target_units: Vec<String> = (1..=3).map(|i| format!("unit-{}", i)).collect();
+
+        let status = dispatcher
+            .dispatch_swarm_command("swarm-1".to_string(), &command, &target_units, 1000)
+            .await
+            .unwrap();
+        assert_eq!(status.success_count, 1);
+        assert_eq!(status.failure_count, 1);
+        assert_eq!(status.timeout_count, 1);
+        assert!(!dispatcher.lock_in_flight().contains_key("swarm-1"));
+    }
+
+    #[tokio::test]
+    async fn test_abort_swarm_command() {
+        let (dispatcher, link) = with_units(
+            CommandDispatcher::new(),
+            &[
+                ("unit-1", UnitBehavior::Hold),
+                ("unit-2", UnitBehavior::Ack),
+            ],
+        );
+        let dispatcher = dispatcher.with_retry_policy(Duration::from_secs(10), 3);
+        let command = SwarmCommand::RecallAll {
+            base_id: "BASE-1".to_string(),
+        };
+        let target_units = vec!["unit-1".to_string(), "unit-2".to_string()];
+
+        assert!(matches!(
+            dispatcher.abort_swarm_command("swarm-1", "Too late").await,
+            Err(DispatchError::UnknownCommand(_))
+        ));
+
+        let dispatch =
+            dispatcher.dispatch_swarm_command("swarm-1".to_string(), &command, &target_units, 1000);
+        let abort = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(matches!(
+                dispatcher
+                    .dispatch_swarm_command("swarm-1".to_string(), &command, &target_units, 1000)
+                    .await,
+                Err(DispatchError::DuplicateCommand(_))
+            ));
+            dispatcher
+                .abort_swarm_command("swarm-1", "Operator abort")
+                .await
+        };
+        let (status, aborted) = tokio::join!(dispatch, abort);
+        assert_eq!(aborted.unwrap(), 2);
+
+        // The unit that had already acknowledged keeps its success
+        let status = status.unwrap();
+        assert_eq!(status.success_count, 1);
+        match &status.unit_results[0] {
+            UnitDispatchResult::Failed {
+                unit_id, reason, ..
+            } => {
+                assert_eq!(unit_id, "unit-1");
+                assert_eq!(reason, "Aborted: Operator abort");
+            }
+            other => panic!("Expected abort, got {:?}", other),
+        }
+
+        let aborts = link
+            .sent()
+            .into_iter()
+            .filter(|(_, message)| matches!(message, LinkMessage::Abort { .. }))
+            .count();
+        assert_eq!(aborts, 2);
     }
 }
@@ -0,0 +1,261 @@
+//! Command status feed
+//!
+//! The dispatcher records each step of a command's delivery here: every
+//! send, progress report and final answer per unit, then a `Finished` event
+//! for the command as a whole once its dispatch ends.
+
+use super::{events_after, Feed};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Step in a command's delivery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CommandState {
+    /// Command sent to the unit
+    Sent {
+        /// Delivery attempt, starting at 1
+        attempt: u32,
+    },
+    /// Unit reported progress
+    Progress {
+        /// Completion percentage
+        percent: f32,
+    },
+    /// Unit acknowledged the command
+    Acknowledged,
+    /// Unit refused the command, or it could not be delivered
+    Failed {
+        /// Failure reason
+        reason: String,
+    },
+    /// Unit never answered
+    TimedOut,
+    /// Command aborted before the unit acknowledged it
+    Aborted {
+        /// Reason given by the operator
+        reason: String,
+    },
+    /// Dispatch ended for every target unit
+    Finished {
+        /// Units targeted
+        total_units: usize,
+        /// Units that acknowledged
+        success_count: usize,
+    },
+}
+
+impl CommandState {
+    /// Status name, as reported by the C2 API
+    pub fn status(&self) -> &'static str {
+        match self {
+            CommandState::Sent { .. } | CommandState::Progress { .. } => "executing",
+            CommandState::Acknowledged => "completed",
+            CommandState::Failed { .. } | CommandState::TimedOut => "failed",
+            CommandState::Aborted { .. } => "aborted",
+            CommandState::Finished {
+                total_units,
+                success_count,
+            } => {
+                if success_count == total_units {
+                    "completed"
+                } else {
+                    "failed"
+                }
+            }
+        }
+    }
+}
+
+/// Command status event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandStatusEvent {
+    /// Command identifier
+    pub command_id: String,
+
+    /// Unit the event concerns (None for the command as a whole)
+    pub unit_id: Option<String>,
+
+    /// New state
+    pub state: CommandState,
+
+    /// Timestamp (nanoseconds since epoch)
+    pub timestamp_ns: u64,
+}
+
+/// Status of a command, as reported by the C2 API
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandStatus<'a> {
+    /// "executing" until dispatch finishes, then "completed", "failed" or
+    /// "aborted"
+    pub status: &'static str,
+    /// Latest state per target unit
+    pub units: BTreeMap<&'a str, &'a CommandState>,
+}
+
+/// Feed of command status events for recent commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandFeed {
+    /// Recent events buffer
+    events: VecDeque<CommandStatusEvent>,
+
+    /// Maximum buffer size
+    max_buffer_size: usize,
+
+    /// Cursor of the newest event
+    cursor: u64,
+}
+
+impl CommandFeed {
+    /// Default maximum buffer size
+    pub const DEFAULT_MAX_BUFFER_SIZE: usize = 1000;
+
+    /// Create an empty command feed
+    pub fn new() -> Self {
+        Self::with_max_buffer_size(Self::DEFAULT_MAX_BUFFER_SIZE)
+    }
+
+    /// Create an empty command feed keeping at most `max_buffer_size` events
+    pub fn with_max_buffer_size(max_buffer_size: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            max_buffer_size,
+            cursor: 0,
+        }
+    }
+
+    /// Status of a command from its buffered events
+    ///
+    /// Returns `None` if no event for the command is buffered.
+    pub fn command_status(&self, command_id: &str) -> Option<CommandStatus<'_>> {
+        let mut found = false;
+        let mut finished = None;
+        let mut units = BTreeMap::new();
+        for event in self.events.iter().filter(|e| e.command_id == command_id) {
+            found = true;
+            match &event.unit_id {
+                Some(unit_id) => {
+                    units.insert(unit_id.as_str(), &event.state);
+                }
+                None => finished = Some(&event.state),
+            }
+        }
+        if !found {
+            return None;
+        }
+
+        let status = match finished {
+            None => "executing",
+            Some(_)
+                if units
+                    .values()
+                    .any(|state| matches!(state, CommandState::Aborted { .. })) =>
+            {
+                "aborted"
+            }
+            Some(state) => state.status(),
+        };
+        Some(CommandStatus { status, units })
+    }
+}
+
+impl Default for CommandFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Feed for CommandFeed {
+    type Event = CommandStatusEvent;
+
+    fn push(&mut self, event: CommandStatusEvent) {
+        self.cursor += 1;
+        self.events.push_back(event);
+
+        // Trim buffer if needed
+        while self.events.len() > self.max_buffer_size {
+            self.events.pop_front();
+        }
+    }
+
+    fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    fn events_after(&self, cursor: u64) -> Option<Vec<(u64, &CommandStatusEvent)>> {
+        events_after(&self.events, self.cursor, cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(command_id: &str, unit_id: Option<&str>, state: CommandState) -> CommandStatusEvent {
+        CommandStatusEvent {
+            command_id: command_id.to_string(),
+            unit_id: unit_id.map(str::to_string),
+            state,
+            timestamp_ns: 1000,
+        }
+    }
+
+    #[test]
+    fn test_command_status() {
+        let mut feed = CommandFeed::new();
+        assert!(feed.command_status("swarm-1").is_none());
+
+        feed.push(event(
+            "swarm-1",
+            Some("unit-1"),
+            CommandState::Sent { attempt: 1 },
+        ));
+        feed.push(event(
+            "swarm-1",
+            Some("unit-2"),
+            CommandState::Sent { attempt: 1 },
+        ));
+        feed.push(event("cmd-2", Some("unit-1"), CommandState::Acknowledged));
+        feed.push(event("swarm-1", Some("unit-1"), CommandState::Acknowledged));
+
+        let status = feed.command_status("swarm-1").unwrap();
+        assert_eq!(status.status, "executing");
+        assert_eq!(status.units["unit-1"], &CommandState::Acknowledged);
+        assert_eq!(status.units["unit-2"].status(), "executing");
+
+        feed.push(event("swarm-1", Some("unit-2"), CommandState::TimedOut));
+        feed.push(event(
+            "swarm-1",
+            None,
+            CommandState::Finished {
+                total_units: 2,
+                success_count: 1,
+            },
+        ));
+        let status = feed.command_status("swarm-1").unwrap();
+        assert_eq!(status.status, "failed");
+        assert_eq!(status.units.len(), 2);
+        assert_eq!(feed.command_status("cmd-2").unwrap().status, "executing");
+    }
+
+    #[test]
+    fn test_aborted_command_status() {
+        let mut feed = CommandFeed::new();
+        feed.push(event("swarm-1", Some("unit-1"), CommandState::Acknowledged));
+        feed.push(event(
+            "swarm-1",
+            Some("unit-2"),
+            CommandState::Aborted {
+                reason: "Operator abort".to_string(),
+            },
+        ));
+        feed.push(event(
+            "swarm-1",
+            None,
+            CommandState::Finished {
+                total_units: 2,
+                success_count: 1,
+            },
+        ));
+        assert_eq!(feed.command_status("swarm-1").unwrap().status, "aborted");
+    }
+}
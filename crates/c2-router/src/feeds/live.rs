@@ -0,0 +1,233 @@
+//! Feeds shared between publishers and streaming subscribers
+
+use super::{AlertFeed, Feed, FleetFeed, MissionFeed};
+use aethercore_core::sync;
+use futures_util::Stream;
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use thiserror::Error;
+use tokio::sync::watch;
+
+/// Feed subscription errors
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FeedError {
+    /// Events after the cursor are no longer buffered, or never existed
+    #[error("Cursor {0} is not available; resubscribe from cursor 0")]
+    CursorUnavailable(u64),
+}
+
+/// Feed that notifies subscribers of every event pushed
+#[derive(Debug)]
+pub struct LiveFeed<F> {
+    feed: RwLock<F>,
+    updates: watch::Sender<u64>,
+}
+
+impl<F: Feed + Send + Sync + 'static> LiveFeed<F> {
+    /// Wrap `feed`, keeping its buffered events
+    pub fn new(feed: F) -> Self {
+        let (updates, _) = watch::channel(feed.cursor());
+        Self {
+            feed: RwLock::new(feed),
+            updates,
+        }
+    }
+
+    /// Push an event and wake subscribers
+    pub fn push(&self, event: F::Event) {
+        let cursor = {
+            let mut feed = sync::write(&self.feed);
+            feed.push(event);
+            feed.cursor()
+        };
+        self.updates.send_replace(cursor);
+    }
+
+    /// Read access to the underlying feed
+    pub fn read(&self) -> RwLockReadGuard<'_, F> {
+        sync::read(&self.feed)
+    }
+
+    /// Stream the events after `cursor` that pass `filter`, then new ones as
+    /// they are pushed
+    ///
+    /// Each item carries the event's cursor. A subscriber that falls so far
+    /// behind that the feed drops events it has not seen receives
+    /// `CursorUnavailable`, and the stream ends.
+    pub fn subscribe<P>(
+        self: &Arc<Self>,
+        cursor: u64,
+        filter: P,
+    ) -> Result<impl Stream<Item = Result<(u64, F::Event), FeedError>> + Send + 'static, FeedError>
+    where
+        P: Fn(&F::Event) -> bool + Send + 'static,
+    {
+        if self.read().events_after(cursor).is_none() {
+            return Err(FeedError::CursorUnavailable(cursor));
+        }
+
+        let subscription = Subscription {
+            feed: self.clone(),
+            updates: self.updates.subscribe(),
+            cursor,
+            pending: VecDeque::new(),
+            filter,
+            ended: false,
+        };
+        Ok(futures_util::stream::unfold(
+            subscription,
+            |mut subscription| async move {
+                let item = subscription.next().await?;
+                Some((item, subscription))
+            },
+        ))
+    }
+}
+
+struct Subscription<F: Feed, P> {
+    feed: Arc<LiveFeed<F>>,
+    updates: watch::Receiver<u64>,
+    cursor: u64,
+    pending: VecDeque<(u64, F::Event)>,
+    filter: P,
+    ended: bool,
+}
+
+impl<F, P> Subscription<F, P>
+where
+    F: Feed + Send + Sync + 'static,
+    P: Fn(&F::Event) -> bool,
+{
+    async fn next(&mut self) -> Option<Result<(u64, F::Event), FeedError>> {
+        loop {
+            if self.ended {
+                return None;
+            }
+            if let Some(item) = self.pending.pop_front() {
+                return Some(Ok(item));
+            }
+
+            // Mark updates seen before reading, so a push in between wakes us
+            self.updates.borrow_and_update();
+            {
+                let feed = self.feed.read();
+                let Some(events) = feed.events_after(self.cursor) else {
+                    self.ended = true;
+                    return Some(Err(FeedError::CursorUnavailable(self.cursor)));
+                };
+                for (cursor, event) in events {
+                    self.cursor = cursor;
+                    if (self.filter)(event) {
+                        self.pending.push_back((cursor, event.clone()));
+                    }
+                }
+            }
+
+            if self.pending.is_empty() && self.updates.changed().await.is_err() {
+                return None;
+            }
+        }
+    }
+}
+
+/// Live fleet, mission and alert feeds
+#[derive(Debug)]
+pub struct FeedHub {
+    /// Fleet asset updates
+    pub fleet: Arc<LiveFeed<FleetFeed>>,
+    /// Mission updates
+    pub missions: Arc<LiveFeed<MissionFeed>>,
+    /// Alerts
+    pub alerts: Arc<LiveFeed<AlertFeed>>,
+}
+
+impl FeedHub {
+    /// Create empty feeds
+    pub fn new() -> Self {
+        Self {
+            fleet: Arc::new(LiveFeed::new(FleetFeed::new("fleet".to_string()))),
+            missions: Arc::new(LiveFeed::new(MissionFeed::new("missions".to_string()))),
+            alerts: Arc::new(LiveFeed::new(AlertFeed::new("alerts".to_string()))),
+        }
+    }
+}
+
+impl Default for FeedHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feeds::{FleetEvent, FleetEventType};
+    use futures_util::StreamExt;
+    use std::time::Duration;
+
+    fn fleet_event(asset_id: &str, timestamp: u64) -> FleetEvent {
+        FleetEvent {
+            asset_id: asset_id.to_string(),
+            event_type: FleetEventType::PositionUpdate,
+            timestamp,
+            latitude: Some(45.0),
+            longitude: Some(-122.0),
+            state: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_replays_then_follows() {
+        let feed = Arc::new(LiveFeed::new(FleetFeed::new("fleet".to_string())));
+        feed.push(fleet_event("asset-1", 1));
+        feed.push(fleet_event("asset-2", 2));
+        feed.push(fleet_event("asset-1", 3));
+
+        let stream = feed
+            .subscribe(1, |event: &FleetEvent| event.asset_id == "asset-1")
+            .unwrap();
+        futures_util::pin_mut!(stream);
+
+        let (cursor, event) = stream.next().await.unwrap().unwrap();
+        assert_eq!((cursor, event.timestamp), (3, 3));
+
+        let publisher = {
+            let feed = feed.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                feed.push(fleet_event("asset-2", 4));
+                feed.push(fleet_event("asset-1", 5));
+            })
+        };
+        let (cursor, event) = tokio::time::timeout(Duration::from_secs(1), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!((cursor, event.timestamp), (5, 5));
+        publisher.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_subscription_cursor_unavailable() {
+        let feed = Arc::new(LiveFeed::new(FleetFeed::new("fleet".to_string())));
+        assert!(matches!(
+            feed.subscribe(7, |_: &FleetEvent| true),
+            Err(FeedError::CursorUnavailable(7))
+        ));
+
+        // A subscriber overtaken by the buffer is told so
+        feed.push(fleet_event("asset-1", 1));
+        let stream = feed.subscribe(0, |_: &FleetEvent| true).unwrap();
+        futures_util::pin_mut!(stream);
+        assert_eq!(stream.next().await.unwrap().unwrap().0, 1);
+        for i in 2..=150 {
+            feed.push(fleet_event("asset-1", i));
+        }
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(FeedError::CursorUnavailable(1)))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+}
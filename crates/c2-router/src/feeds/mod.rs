@@ -2,10 +2,58 @@
 //!
 //! Provides real-time event feeds for the Tactical Glass C2 surface,
 //! enabling subscriptions to fleet, mission, and alert updates.
+//!
+//! Every event kept by a feed is numbered by a cursor, starting at 1. A
+//! `LiveFeed` streams the events after a given cursor and then new events as
+//! they are pushed, so a subscriber that reconnects can resume after the
+//! last cursor it saw for as long as the feed still buffers what followed.
+
+mod command;
+mod live;
+
+pub use command::{CommandFeed, CommandState, CommandStatus, CommandStatusEvent};
+pub use live::{FeedError, FeedHub, LiveFeed};
 
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
+/// Buffered event feed with cursors
+pub trait Feed {
+    /// Event type carried by the feed
+    type Event: Clone + Send + Sync + 'static;
+
+    /// Push a new event
+    fn push(&mut self, event: Self::Event);
+
+    /// Cursor of the newest event kept (0 before the first)
+    fn cursor(&self) -> u64;
+
+    /// Buffered events after `cursor`, oldest first, with their cursors
+    ///
+    /// Cursor 0 returns every buffered event. Returns `None` if events after
+    /// `cursor` were already dropped from the buffer, or if `cursor` is ahead
+    /// of the feed.
+    fn events_after(&self, cursor: u64) -> Option<Vec<(u64, &Self::Event)>>;
+}
+
+/// Events after `cursor` in a buffer whose newest event has cursor `newest`
+fn events_after<T>(events: &VecDeque<T>, newest: u64, cursor: u64) -> Option<Vec<(u64, &T)>> {
+    let oldest = newest + 1 - events.len() as u64;
+    let cursor = if cursor == 0 { oldest - 1 } else { cursor };
+    if cursor > newest || cursor + 1 < oldest {
+        return None;
+    }
+    let skip = (cursor + 1 - oldest) as usize;
+    Some(
+        events
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .map(|(i, event)| (oldest + i as u64, event))
+            .collect(),
+    )
+}
+
 /// Fleet feed for real-time fleet asset updates
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FleetFeed {
@@ -21,6 +69,10 @@ pub struct FleetFeed {
     /// Maximum buffer size
     max_buffer_size: usize,
 
+    /// Cursor of the newest event
+    #[serde(default)]
+    cursor: u64,
+
     /// Last update timestamp
     pub last_updated: u64,
 }
@@ -33,6 +85,7 @@ impl FleetFeed {
             subscribed_assets: Vec::new(),
             events: VecDeque::new(),
             max_buffer_size: 100,
+            cursor: 0,
             last_updated: 0,
         }
     }
@@ -49,6 +102,7 @@ impl FleetFeed {
 
     /// Push a new fleet event
     pub fn push_event(&mut self, event: FleetEvent) {
+        self.cursor += 1;
         self.last_updated = event.timestamp;
         self.events.push_back(event);
 
@@ -74,6 +128,22 @@ impl FleetFeed {
     }
 }
 
+impl Feed for FleetFeed {
+    type Event = FleetEvent;
+
+    fn push(&mut self, event: FleetEvent) {
+        self.push_event(event);
+    }
+
+    fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    fn events_after(&self, cursor: u64) -> Option<Vec<(u64, &FleetEvent)>> {
+        events_after(&self.events, self.cursor, cursor)
+    }
+}
+
 /// Fleet event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FleetEvent {
@@ -124,6 +194,10 @@ pub struct MissionFeed {
     /// Maximum buffer size
     max_buffer_size: usize,
 
+    /// Cursor of the newest event
+    #[serde(default)]
+    cursor: u64,
+
     /// Last update timestamp
     pub last_updated: u64,
 }
@@ -136,6 +210,7 @@ impl MissionFeed {
             subscribed_missions: Vec::new(),
             events: VecDeque::new(),
             max_buffer_size: 100,
+            cursor: 0,
             last_updated: 0,
         }
     }
@@ -152,6 +227,7 @@ impl MissionFeed {
 
     /// Push a new mission event
     pub fn push_event(&mut self, event: MissionEvent) {
+        self.cursor += 1;
         self.last_updated = event.timestamp;
         self.events.push_back(event);
 
@@ -177,6 +253,22 @@ impl MissionFeed {
     }
 }
 
+impl Feed for MissionFeed {
+    type Event = MissionEvent;
+
+    fn push(&mut self, event: MissionEvent) {
+        self.push_event(event);
+    }
+
+    fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    fn events_after(&self, cursor: u64) -> Option<Vec<(u64, &MissionEvent)>> {
+        events_after(&self.events, self.cursor, cursor)
+    }
+}
+
 /// Mission event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MissionEvent {
@@ -228,6 +320,10 @@ pub struct AlertFeed {
     /// Maximum buffer size
     max_buffer_size: usize,
 
+    /// Cursor of the newest event
+    #[serde(default)]
+    cursor: u64,
+
     /// Last update timestamp
     pub last_updated: u64,
 }
@@ -240,6 +336,7 @@ impl AlertFeed {
             min_severity: AlertSeverityFilter::Info,
             events: VecDeque::new(),
             max_buffer_size: 100,
+            cursor: 0,
             last_updated: 0,
         }
     }
@@ -253,6 +350,7 @@ impl AlertFeed {
     pub fn push_event(&mut self, event: AlertEvent) {
         // Filter by severity
         if event.severity as u8 >= self.min_severity as u8 {
+            self.cursor += 1;
             self.last_updated = event.timestamp;
             self.events.push_back(event);
 
@@ -279,6 +377,22 @@ impl AlertFeed {
     }
 }
 
+impl Feed for AlertFeed {
+    type Event = AlertEvent;
+
+    fn push(&mut self, event: AlertEvent) {
+        self.push_event(event);
+    }
+
+    fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    fn events_after(&self, cursor: u64) -> Option<Vec<(u64, &AlertEvent)>> {
+        events_after(&self.events, self.cursor, cursor)
+    }
+}
+
 /// Alert event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertEvent {
@@ -451,6 +565,85 @@ mod tests {
         assert_eq!(recent[0].alert_id, "alert-4");
     }
 
+    fn mission_event(mission_id: &str, timestamp: u64) -> MissionEvent {
+        MissionEvent {
+            mission_id: mission_id.to_string(),
+            event_type: MissionEventType::MissionStarted,
+            timestamp,
+            state: None,
+            assets: None,
+        }
+    }
+
+    #[test]
+    fn test_feed_cursors() {
+        let mut feed = MissionFeed::new("feed-001".to_string());
+        assert_eq!(feed.cursor(), 0);
+        assert!(feed.events_after(0).unwrap().is_empty());
+
+        for i in 1..=3 {
+            feed.push(mission_event(&format!("mission-{}", i), i));
+        }
+        assert_eq!(feed.cursor(), 3);
+
+        let events = feed.events_after(1).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, 2);
+        assert_eq!(events[0].1.mission_id, "mission-2");
+        assert!(feed.events_after(3).unwrap().is_empty());
+        assert_eq!(feed.events_after(0).unwrap().len(), 3);
+
+        // Cursors from elsewhere are unknown
+        assert!(feed.events_after(4).is_none());
+    }
+
+    #[test]
+    fn test_feed_cursor_expires_when_buffer_trimmed() {
+        let mut feed = MissionFeed::new("feed-001".to_string());
+        feed.max_buffer_size = 5;
+        for i in 1..=10 {
+            feed.push(mission_event("mission-1", i));
+        }
+
+        // Events 1-5 were dropped, so resuming after 4 would miss event 5
+        assert!(feed.events_after(4).is_none());
+        let events = feed.events_after(5).unwrap();
+        assert_eq!(events.first().unwrap().0, 6);
+        assert_eq!(feed.events_after(0).unwrap().len(), 5);
+
+        // Clearing keeps cursors increasing
+        feed.clear();
+        assert!(feed.events_after(9).is_none());
+        assert!(feed.events_after(10).unwrap().is_empty());
+        feed.push(mission_event("mission-1", 11));
+        assert_eq!(feed.events_after(10).unwrap()[0].0, 11);
+    }
+
+    #[test]
+    fn test_alert_cursor_counts_kept_events_only() {
+        let mut feed = AlertFeed::new("feed-001".to_string());
+        feed.set_min_severity(AlertSeverityFilter::Warning);
+        for (i, severity) in [
+            AlertSeverityFilter::Info,
+            AlertSeverityFilter::Critical,
+            AlertSeverityFilter::Info,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            feed.push(AlertEvent {
+                alert_id: format!("alert-{}", i),
+                severity,
+                category: "test".to_string(),
+                message: "Test".to_string(),
+                timestamp: i as u64,
+                source_asset: None,
+            });
+        }
+        assert_eq!(feed.cursor(), 1);
+        assert_eq!(feed.events_after(0).unwrap()[0].1.alert_id, "alert-1");
+    }
+
     #[test]
     fn test_feed_clear() {
         let mut feed = FleetFeed::new("feed-001".to_string());
@@ -12,6 +12,26 @@
 //! 4. **Quorum Validation**: Verify sufficient authority signatures for command scope
 //! 5. **Audit Logging**: Record every command attempt with outcome
 //!
+//! # Quorum
+//!
+//! Each entry of a request's `signatures` is a JSON-serialized
+//! `AuthoritySignature` over `QuorumGate::unit_command_hash` or
+//! `QuorumGate::swarm_command_hash`, both of which cover the requesting
+//! device and its nonce, so an approval is good for one request only. The
+//! command is classified by `QuorumGate` and must carry signatures from
+//! enough distinct registered authorities for its scope. The resulting `QuorumProof` is returned as
+//! `quorum_proof_json` and, on a server built `with_truth_chain`, recorded
+//! in the Truth-Chain before the command is dispatched.
+//!
+//! # Streams
+//!
+//! `WatchCommand` follows a command's delivery as recorded in the
+//! dispatcher's `CommandFeed`, ending with the update for the command as a
+//! whole. `SubscribeFleet`, `SubscribeMissions` and `SubscribeAlerts` follow
+//! the server's `FeedHub`, filtered by asset, mission or severity on the
+//! server. Every update carries its feed cursor; a client that reconnects
+//! with the last cursor it received resumes where it left off.
+//!
 //! # Trust Mesh Integration
 //!
 //! Commands are gated by trust level with explicit rejection semantics:
@@ -28,19 +48,56 @@
 //! - `x-device-id`: Unique device identifier from TPM
 //!
 //! The signature is verified against the device's registered public key in the identity registry.
+//! It covers `"{device_id}:{command_json}:{timestamp_ns}"`, with `":{nonce}"`
+//! appended for unit and swarm commands so a replayed command cannot swap its
+//! nonce.
+//!
+//! # Replay Protection
+//!
+//! Unit, swarm and schedule requests carry a `nonce` that must be unique per device
+//! within the replay window. A server built `with_replay_protector` on a protector from
+//! `ReplayProtector::open` keeps its nonces across restarts.
+//!
+//! # Scheduling
+//!
+//! On a server built `with_scheduler`, `ScheduleCommand` holds a unit or
+//! swarm command until its trigger fires. Scheduling takes the quorum the
+//! command itself would need, signed over `QuorumGate::schedule_hash`.
+//! Cancelling takes the same quorum over `QuorumGate::schedule_cancel_hash`;
+//! the device signature covers the schedule ID in place of the command JSON.
+//!
+//! # Mutual TLS
+//!
+//! With the `mtls` feature, a server configured via `with_peer_authorizer`
+//! also requires `x-device-id` to match the subject of the client certificate,
+//! so a device cannot act under another device's identity. Serve it with
+//! `aethercore_identity::mtls::incoming` to refuse revoked identities during
+//! the TLS handshake.
 
 #![warn(missing_docs)]
 
+use crate::authority::AuthoritySignature;
 use crate::command_types::{SwarmCommand, UnitCommand};
-use crate::dispatcher::CommandDispatcher;
+use crate::dispatcher::{CommandDispatcher, DispatchError, UnitDispatchResult};
+use crate::feeds::{
+    AlertEvent, AlertSeverityFilter, CommandStatusEvent, Feed, FeedError, FeedHub, FleetEvent,
+    LiveFeed, MissionEvent,
+};
+use crate::ledger::{CommandRecord, TruthChainRecorder};
 use crate::offline::OfflineMateriaBuffer;
-use crate::quorum::QuorumGate;
-use crate::replay_protection::ReplayProtector;
+use crate::quorum::{QuorumError, QuorumGate, QuorumProof};
+use crate::replay_protection::{ReplayError, ReplayProtector};
+use crate::scheduler::{
+    CommandSchedule, CommandScheduler, ScheduleStore, ScheduledCommand, SchedulerError,
+};
 use aethercore_identity::IdentityManager;
 use aethercore_trust_mesh::{NodeHealthComputer, TrustLevel, TrustScore, TrustScorer};
 use base64::engine::general_purpose;
 use base64::Engine as _;
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex, RwLock};
 use tonic::{Request, Response, Status};
 
@@ -57,19 +114,24 @@ pub mod c2_proto {
 
 pub use c2_proto::{
     c2_router_server::{C2Router, C2RouterServer},
-    AbortRequest, AbortResponse, CommandStatusRequest, CommandStatusResponse, OfflineGapRequest,
-    OfflineGapResponse, SwarmCommandRequest, SwarmCommandResponse, SyncAuthorizationRequest,
-    SyncAuthorizationResponse, UnitCommandRequest, UnitCommandResponse,
+    AbortRequest, AbortResponse, AlertSubscription, CancelScheduleRequest, CancelScheduleResponse,
+    CommandStatusRequest, CommandStatusResponse, CommandStatusUpdate, FeedUpdate,
+    FleetSubscription, MissionSubscription, OfflineGapRequest, OfflineGapResponse,
+    ScheduleCommandRequest, ScheduleCommandResponse, SwarmCommandRequest, SwarmCommandResponse,
+    SyncAuthorizationRequest, SyncAuthorizationResponse, UnitCommandRequest, UnitCommandResponse,
+    WatchCommandRequest,
 };
 
 const TRUST_THRESHOLD: f64 = 0.8;
 
+/// Server stream of `T` updates
+pub type UpdateStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
 /// C2 gRPC Server implementation with full security hardening
 pub struct C2GrpcServer {
     /// Command dispatcher for routing commands to the mesh
     dispatcher: Arc<CommandDispatcher>,
     /// Quorum gate for authority verification
-    #[allow(dead_code)]
     quorum_gate: Arc<QuorumGate>,
     /// Trust scorer for checking node trust levels
     trust_scorer: Arc<RwLock<TrustScorer>>,
@@ -81,6 +143,15 @@ pub struct C2GrpcServer {
     replay_protector: Arc<ReplayProtector>,
     /// Offline materia buffer for blackout resilience (optional)
     offline_buffer: Option<Arc<Mutex<OfflineMateriaBuffer>>>,
+    /// Truth-Chain recorder for authorized commands (optional)
+    truth_chain: Option<Arc<Mutex<TruthChainRecorder>>>,
+    /// Fleet, mission and alert feeds served to subscribers
+    feeds: Arc<FeedHub>,
+    /// Scheduler holding commands until their triggers fire (optional)
+    scheduler: Option<Arc<CommandScheduler>>,
+    /// mTLS peer authorizer binding `x-device-id` to the client certificate
+    #[cfg(feature = "mtls")]
+    peer_authorizer: Option<Arc<dyn aethercore_identity::mtls::PeerAuthorizer>>,
 }
 
 impl C2GrpcServer {
@@ -99,6 +170,11 @@ impl C2GrpcServer {
             identity_manager: Arc::new(RwLock::new(identity_manager)),
             replay_protector: Arc::new(ReplayProtector::new()),
             offline_buffer: None,
+            truth_chain: None,
+            feeds: Arc::new(FeedHub::new()),
+            scheduler: None,
+            #[cfg(feature = "mtls")]
+            peer_authorizer: None,
         }
     }
 
@@ -118,7 +194,74 @@ impl C2GrpcServer {
             identity_manager: Arc::new(RwLock::new(identity_manager)),
             replay_protector: Arc::new(ReplayProtector::new()),
             offline_buffer: Some(Arc::new(Mutex::new(offline_buffer))),
+            truth_chain: None,
+            feeds: Arc::new(FeedHub::new()),
+            scheduler: None,
+            #[cfg(feature = "mtls")]
+            peer_authorizer: None,
+        }
+    }
+
+    /// Record every authorized command and its quorum proof in `recorder`.
+    ///
+    /// Commands are refused if they cannot be recorded.
+    pub fn with_truth_chain(mut self, recorder: TruthChainRecorder) -> Self {
+        self.truth_chain = Some(Arc::new(Mutex::new(recorder)));
+        self
+    }
+
+    /// Check unit command nonces with `protector`, such as one opened on a
+    /// persistent store, instead of an in-memory protector.
+    pub fn with_replay_protector(mut self, protector: ReplayProtector) -> Self {
+        self.replay_protector = Arc::new(protector);
+        self
+    }
+
+    /// Serve subscriptions from `feeds` instead of feeds of its own.
+    pub fn with_feeds(mut self, feeds: Arc<FeedHub>) -> Self {
+        self.feeds = feeds;
+        self
+    }
+
+    /// Feeds served to subscribers, for publishers to push events into.
+    pub fn feeds(&self) -> Arc<FeedHub> {
+        self.feeds.clone()
+    }
+
+    /// Accept scheduled commands, held in `store` until their triggers fire.
+    ///
+    /// Firings are recorded in the Truth-Chain of a server already built
+    /// `with_truth_chain`.
+    pub fn with_scheduler(mut self, store: ScheduleStore) -> Result<Self, SchedulerError> {
+        let mut scheduler = CommandScheduler::new(self.dispatcher.clone(), store)?;
+        if let Some(truth_chain) = &self.truth_chain {
+            scheduler = scheduler.with_truth_chain(truth_chain.clone());
         }
+        self.scheduler = Some(Arc::new(scheduler));
+        Ok(self)
+    }
+
+    /// Scheduler of a server built `with_scheduler`, to `run` and to pass
+    /// unit observations to.
+    pub fn scheduler(&self) -> Option<Arc<CommandScheduler>> {
+        self.scheduler.clone()
+    }
+
+    /// Require `x-device-id` to match the mTLS client certificate.
+    #[cfg(feature = "mtls")]
+    pub fn with_peer_authorizer(
+        mut self,
+        authorizer: Arc<dyn aethercore_identity::mtls::PeerAuthorizer>,
+    ) -> Self {
+        self.peer_authorizer = Some(authorizer);
+        self
+    }
+
+    /// Revocation view of this server's identity registry, for building an
+    /// `IdentityPeerAuthorizer` that shares it.
+    #[cfg(feature = "mtls")]
+    pub fn identity_status(&self) -> Arc<dyn aethercore_identity::mtls::IdentityStatus> {
+        self.identity_manager.clone()
     }
 
     /// Recompute and persist trust score for a device using current integrity metrics.
@@ -243,6 +386,17 @@ impl C2GrpcServer {
             })?
             .to_string();
 
+        // Bind the claimed device ID to the mTLS client certificate
+        #[cfg(feature = "mtls")]
+        aethercore_identity::mtls::bind_device_id(
+            request,
+            self.peer_authorizer.as_ref(),
+            &device_id,
+        )
+        .inspect_err(|status| {
+            self.audit_log("AUTH_FAILED", &device_id, "None", status.message());
+        })?;
+
         // Extract signature from metadata
         let signature_b64 = request
             .metadata()
@@ -306,6 +460,7 @@ impl C2GrpcServer {
         signature_b64: &str,
         command_json: &str,
         timestamp_ns: u64,
+        nonce: Option<&str>,
     ) -> Result<(), Status> {
         let identity_mgr = self.identity_manager.read().map_err(|e| {
             self.audit_log(
@@ -361,7 +516,11 @@ impl C2GrpcServer {
             Status::unauthenticated("Invalid signature format")
         })?;
 
-        let message = format!("{}:{}:{}", device_id, command_json, timestamp_ns);
+        let mut message = format!("{}:{}:{}", device_id, command_json, timestamp_ns);
+        if let Some(nonce) = nonce {
+            message.push(':');
+            message.push_str(nonce);
+        }
 
         verifying_key
             .verify(message.as_bytes(), &signature)
@@ -377,6 +536,125 @@ impl C2GrpcServer {
             })
     }
 
+    /// Reject a request whose timestamp is stale or whose nonce was seen
+    fn check_replay(
+        &self,
+        device_id: &str,
+        target: &str,
+        timestamp_ns: u64,
+        nonce: &str,
+    ) -> Result<(), Status> {
+        self.replay_protector
+            .validate_command(device_id, timestamp_ns, nonce)
+            .map_err(|e| {
+                if let ReplayError::StorageError(_) = e {
+                    // Fail closed: a nonce that was not persisted could be replayed
+                    self.audit_log("REPLAY_CHECK_FAILED", device_id, target, &e.to_string());
+                    return Status::unavailable("Replay protection store unavailable");
+                }
+                let error_msg = format!("Replay attack detected: {}", e);
+                self.audit_log("REPLAY_ATTACK_DETECTED", device_id, target, &error_msg);
+                Status::permission_denied(error_msg)
+            })
+    }
+
+    /// Scheduler of a server built `with_scheduler`
+    fn require_scheduler(
+        &self,
+        action: &str,
+        device_id: &str,
+        target: &str,
+    ) -> Result<&CommandScheduler, Status> {
+        self.scheduler.as_deref().ok_or_else(|| {
+            self.audit_log(action, device_id, target, "Scheduling not enabled");
+            Status::unimplemented("Command scheduling is not enabled on this server")
+        })
+    }
+
+    /// Decode the JSON-serialized authority signatures carried by a request
+    fn decode_authority_signatures(
+        &self,
+        action: &str,
+        device_id: &str,
+        target: &str,
+        signatures: &[String],
+    ) -> Result<Vec<AuthoritySignature>, Status> {
+        if signatures.is_empty() {
+            self.audit_log(action, device_id, target, "No signatures provided");
+            return Err(Status::unauthenticated("No authority signatures provided"));
+        }
+
+        signatures
+            .iter()
+            .map(|signature| {
+                serde_json::from_str(signature).map_err(|e| {
+                    let reason = format!("Invalid authority signature: {}", e);
+                    self.audit_log(action, device_id, target, &reason);
+                    Status::invalid_argument(reason)
+                })
+            })
+            .collect()
+    }
+
+    /// Audit a failed quorum check and turn it into a rejection
+    fn quorum_rejected(
+        &self,
+        action: &str,
+        device_id: &str,
+        target: &str,
+        error: QuorumError,
+    ) -> Status {
+        let reason = format!("Quorum not met: {}", error);
+        self.audit_log(action, device_id, target, &reason);
+        Status::permission_denied(reason)
+    }
+
+    /// Serialize a quorum proof for the response
+    fn encode_quorum_proof(proof: &QuorumProof) -> Result<String, Status> {
+        serde_json::to_string(proof)
+            .map_err(|e| Status::internal(format!("Failed to encode quorum proof: {}", e)))
+    }
+
+    /// Append an authorized command to the Truth-Chain, if one is configured
+    ///
+    /// The ledger entry is signed with the requesting device's signature,
+    /// already verified by `verify_command_signature`.
+    fn record_authorized_command(
+        &self,
+        action: &str,
+        device_id: &str,
+        signature_b64: &str,
+        record: &CommandRecord,
+    ) -> Result<(), Status> {
+        let Some(truth_chain) = &self.truth_chain else {
+            return Ok(());
+        };
+
+        let signature = general_purpose::STANDARD
+            .decode(signature_b64)
+            .map_err(|_| Status::unauthenticated("Invalid signature encoding"))?;
+
+        let mut recorder = truth_chain.lock().map_err(|e| {
+            self.audit_log(
+                action,
+                device_id,
+                &record.command_id,
+                &format!("Lock error: {}", e),
+            );
+            Status::internal("Truth-Chain lock error")
+        })?;
+
+        recorder
+            .record_command(record, signature, device_id.to_string())
+            .map_err(|e| {
+                let reason = format!("Truth-Chain recording failed: {}", e);
+                self.audit_log(action, device_id, &record.command_id, &reason);
+                Status::internal(reason)
+            })?;
+
+        Ok(())
+    }
+
     /// Check trust score against threshold and quarantine status
     fn verify_trust_score(&self, device_id: &str) -> Result<(), Status> {
         let scorer = self.trust_scorer.read().map_err(|e| {
@@ -426,6 +704,41 @@ impl C2GrpcServer {
         Ok(())
     }
 
+    /// Stream the events of `feed` after `cursor` that pass `filter`
+    fn feed_updates<F, P>(
+        feed: &Arc<LiveFeed<F>>,
+        cursor: u64,
+        filter: P,
+    ) -> Result<UpdateStream<FeedUpdate>, Status>
+    where
+        F: Feed + Send + Sync + 'static,
+        F::Event: Serialize,
+        P: Fn(&F::Event) -> bool + Send + 'static,
+    {
+        let updates = feed.subscribe(cursor, filter).map_err(feed_error)?;
+        Ok(Box::pin(updates.map(|update| {
+            let (cursor, event) = update.map_err(feed_error)?;
+            let event_json = serde_json::to_string(&event)
+                .map_err(|e| Status::internal(format!("Failed to encode event: {}", e)))?;
+            Ok(FeedUpdate { cursor, event_json })
+        })))
+    }
+
+    /// Shared handling of feed subscription requests
+    fn subscribe_feed(
+        &self,
+        action: &str,
+        device_id: &str,
+        target: &str,
+        updates: Result<UpdateStream<FeedUpdate>, Status>,
+    ) -> Result<Response<UpdateStream<FeedUpdate>>, Status> {
+        let updates = updates.inspect_err(|e| {
+            self.audit_log(action, device_id, target, e.message());
+        })?;
+        self.audit_log(action, device_id, target, "SUCCESS");
+        Ok(Response::new(updates))
+    }
+
     /// Audit log helper - structured logging for all operations
     fn audit_log(&self, action: &str, operator: &str, target: &str, result: &str) {
         tracing::info!(
@@ -446,8 +759,26 @@ impl C2GrpcServer {
     }
 }
 
+fn feed_error(e: FeedError) -> Status {
+    Status::out_of_range(e.to_string())
+}
+
+/// Subscription target for the audit log
+fn subscription_target(ids: &[String]) -> String {
+    if ids.is_empty() {
+        "*".to_string()
+    } else {
+        ids.join(",")
+    }
+}
+
 #[tonic::async_trait]
 impl C2Router for C2GrpcServer {
+    type WatchCommandStream = UpdateStream<CommandStatusUpdate>;
+    type SubscribeFleetStream = UpdateStream<FeedUpdate>;
+    type SubscribeMissionsStream = UpdateStream<FeedUpdate>;
+    type SubscribeAlertsStream = UpdateStream<FeedUpdate>;
+
     async fn execute_unit_command(
         &self,
         request: Request<UnitCommandRequest>,
@@ -465,29 +796,22 @@ impl C2Router for C2GrpcServer {
             &signature_b64,
             &req.command_json,
             req.timestamp_ns,
+            Some(&req.nonce),
         )?;
 
         // Step 2: Replay Protection - Validate timestamp and nonce
-        // Extract nonce from signatures (first signature serves as nonce for now)
-        let nonce = req.signatures.first().ok_or_else(|| {
+        if req.nonce.is_empty() {
             self.audit_log(
                 "REPLAY_CHECK_FAILED",
                 &device_id,
                 unit_id,
-                "No signature/nonce provided",
+                "No nonce provided",
             );
-            Status::unauthenticated("No signature provided")
-        })?;
+            return Err(Status::invalid_argument("No nonce provided"));
+        }
 
         // Validate replay protection
-        if let Err(e) = self
-            .replay_protector
-            .validate_command(&device_id, req.timestamp_ns, nonce)
-        {
-            let error_msg = format!("Replay attack detected: {}", e);
-            self.audit_log("REPLAY_ATTACK_DETECTED", &device_id, unit_id, &error_msg);
-            return Err(Status::permission_denied(error_msg));
-        }
+        self.check_replay(&device_id, unit_id, req.timestamp_ns, &req.nonce)?;
 
         // Step 3: Trust Gating - Check trust score
         self.verify_trust_score(&device_id)?;
@@ -503,23 +827,52 @@ impl C2Router for C2GrpcServer {
             Status::invalid_argument(format!("Invalid command JSON: {}", e))
         })?;
 
-        // Step 5: Quorum verification would happen here
-        // In full implementation, verify signatures against command hash
-        // For now, we log the signature count
-        if req.signatures.is_empty() {
-            self.audit_log(
-                "EXECUTE_UNIT",
-                &device_id,
-                unit_id,
-                "No signatures provided",
-            );
-            return Err(Status::unauthenticated("No authority signatures provided"));
-        }
+        // Step 5: Quorum verification against the command hash
+        let authorities =
+            self.decode_authority_signatures("EXECUTE_UNIT", &device_id, unit_id, &req.signatures)?;
+        let scope = QuorumGate::classify_unit_command(&command);
+        let command_hash = QuorumGate::unit_command_hash(
+            &device_id,
+            unit_id,
+            &req.command_json,
+            req.timestamp_ns,
+            &req.nonce,
+        );
+        self.quorum_gate
+            .verify_unit_command(&command, &command_hash, &authorities)
+            .map_err(|e| self.quorum_rejected("EXECUTE_UNIT", &device_id, unit_id, e))?;
+        let proof = QuorumProof::new(
+            command_hash,
+            authorities,
+            scope,
+            Self::current_timestamp_ns(),
+        );
+        let quorum_proof_json = Self::encode_quorum_proof(&proof)?;
+
+        // Step 5a: Record the authorized command in the Truth-Chain
+        let command_id = hex::encode(command_hash);
+        let record = CommandRecord::new(
+            command_id.clone(),
+            "UnitCommand".to_string(),
+            serde_json::to_value(&command)
+                .map_err(|e| Status::internal(format!("Failed to encode command: {}", e)))?,
+            command_hash,
+            proof
+                .signatures
+                .iter()
+                .map(|sig| sig.authority_id.clone())
+                .collect(),
+            vec![unit_id.clone()],
+            req.timestamp_ns,
+        )
+        .with_quorum_proof(proof);
+        self.record_authorized_command("EXECUTE_UNIT", &device_id, &signature_b64, &record)?;
 
-        // Step 6: Dispatch command
+        // Step 6: Dispatch command and wait for the unit's answer
         let dispatch_result = self
             .dispatcher
-            .dispatch_unit_command(unit_id, &command, req.timestamp_ns)
+            .dispatch_unit_command(&command_id, unit_id, &command, req.timestamp_ns)
+            .await
             .map_err(|e| {
                 self.audit_log(
                     "EXECUTE_UNIT",
@@ -530,25 +883,34 @@ impl C2Router for C2GrpcServer {
                 Status::internal(format!("Command dispatch failed: {}", e))
             })?;
 
-        // Step 6: Audit log success
+        // Step 6: Audit log outcome
         let success = dispatch_result.is_success();
+        let message = match &dispatch_result {
+            UnitDispatchResult::Success { .. } => {
+                format!("Command acknowledged by unit {}", unit_id)
+            }
+            UnitDispatchResult::Failed { reason, .. } => {
+                format!("Command dispatch failed: {}", reason)
+            }
+            UnitDispatchResult::Timeout { .. } => {
+                format!("Command timed out waiting for unit {}", unit_id)
+            }
+        };
         self.audit_log(
             "EXECUTE_UNIT",
             &device_id,
             unit_id,
-            if success { "SUCCESS" } else { "FAILED" },
+            if success { "SUCCESS" } else { &message },
         );
 
         // Step 7: Return response
         let response = UnitCommandResponse {
             success,
             unit_id: unit_id.clone(),
-            message: if success {
-                format!("Command dispatched to unit {}", unit_id)
-            } else {
-                "Command dispatch failed".to_string()
-            },
+            message,
             timestamp_ns: Self::current_timestamp_ns(),
+            quorum_proof_json,
+            command_id,
         };
 
         Ok(Response::new(response))
@@ -571,8 +933,21 @@ impl C2Router for C2GrpcServer {
             &signature_b64,
             &req.command_json,
             req.timestamp_ns,
+            Some(&req.nonce),
         )?;
 
+        // Step 1b: Replay Protection - Validate timestamp and nonce
+        if req.nonce.is_empty() {
+            self.audit_log(
+                "REPLAY_CHECK_FAILED",
+                &device_id,
+                swarm_id,
+                "No nonce provided",
+            );
+            return Err(Status::invalid_argument("No nonce provided"));
+        }
+        self.check_replay(&device_id, swarm_id, req.timestamp_ns, &req.nonce)?;
+
         // Step 2: Trust Gating
         self.verify_trust_score(&device_id)?;
 
@@ -587,16 +962,51 @@ impl C2Router for C2GrpcServer {
             Status::invalid_argument(format!("Invalid command JSON: {}", e))
         })?;
 
-        // Step 4: Quorum verification
-        if req.signatures.is_empty() {
-            self.audit_log(
-                "EXECUTE_SWARM",
-                &device_id,
-                swarm_id,
-                "No signatures provided",
-            );
-            return Err(Status::unauthenticated("No authority signatures provided"));
-        }
+        // Step 4: Quorum verification against the command hash
+        let authorities = self.decode_authority_signatures(
+            "EXECUTE_SWARM",
+            &device_id,
+            swarm_id,
+            &req.signatures,
+        )?;
+        let unit_count = req.target_unit_ids.len();
+        let scope = QuorumGate::classify_swarm_command(&command, unit_count);
+        let command_hash = QuorumGate::swarm_command_hash(
+            &device_id,
+            swarm_id,
+            &req.target_unit_ids,
+            &req.command_json,
+            req.timestamp_ns,
+            &req.nonce,
+        );
+        self.quorum_gate
+            .verify_swarm_command(&command, unit_count, &command_hash, &authorities)
+            .map_err(|e| self.quorum_rejected("EXECUTE_SWARM", &device_id, swarm_id, e))?;
+        let proof = QuorumProof::new(
+            command_hash,
+            authorities,
+            scope,
+            Self::current_timestamp_ns(),
+        );
+        let quorum_proof_json = Self::encode_quorum_proof(&proof)?;
+
+        // Step 4a: Record the authorized command in the Truth-Chain
+        let record = CommandRecord::new(
+            swarm_id.clone(),
+            "SwarmCommand".to_string(),
+            serde_json::to_value(&command)
+                .map_err(|e| Status::internal(format!("Failed to encode command: {}", e)))?,
+            command_hash,
+            proof
+                .signatures
+                .iter()
+                .map(|sig| sig.authority_id.clone())
+                .collect(),
+            req.target_unit_ids.clone(),
+            req.timestamp_ns,
+        )
+        .with_quorum_proof(proof);
+        self.record_authorized_command("EXECUTE_SWARM", &device_id, &signature_b64, &record)?;
 
         // Step 5: Dispatch swarm command
         let dispatch_status = self
@@ -607,6 +1017,7 @@ impl C2Router for C2GrpcServer {
                 &req.target_unit_ids,
                 req.timestamp_ns,
             )
+            .await
             .map_err(|e| {
                 self.audit_log(
                     "EXECUTE_SWARM",
@@ -614,7 +1025,10 @@ impl C2Router for C2GrpcServer {
                     swarm_id,
                     &format!("Dispatch failed: {}", e),
                 );
-                Status::internal(format!("Swarm command dispatch failed: {}", e))
+                match e {
+                    DispatchError::DuplicateCommand(_) => Status::already_exists(e.to_string()),
+                    e => Status::internal(format!("Swarm command dispatch failed: {}", e)),
+                }
             })?;
 
         // Step 6: Audit log
@@ -637,6 +1051,7 @@ impl C2Router for C2GrpcServer {
             timeout_count: dispatch_status.timeout_count as u32,
             completion_percent: dispatch_status.completion_percent(),
             timestamp_ns: Self::current_timestamp_ns(),
+            quorum_proof_json,
         };
 
         Ok(Response::new(response))
@@ -654,14 +1069,24 @@ impl C2Router for C2GrpcServer {
 
         let req = request.into_inner();
 
+        // Look up the command among the dispatcher's recent commands
+        let command_feed = self.dispatcher.command_feed();
+        let command_feed = command_feed.read();
+        let status = command_feed
+            .command_status(&req.command_id)
+            .ok_or_else(|| {
+                self.audit_log("GET_STATUS", &device_id, &req.command_id, "Unknown command");
+                Status::not_found(format!("No recent status for command {}", req.command_id))
+            })?;
+        let details_json = serde_json::to_string(&status.units)
+            .map_err(|e| Status::internal(format!("Failed to encode status: {}", e)))?;
+
         self.audit_log("GET_STATUS", &device_id, &req.command_id, "SUCCESS");
 
-        // In production, look up actual command status from ledger/state store
-        // For now, return a placeholder response
         let response = CommandStatusResponse {
             command_id: req.command_id.clone(),
-            status: "completed".to_string(),
-            details_json: "{}".to_string(),
+            status: status.status.to_string(),
+            details_json,
             timestamp_ns: Self::current_timestamp_ns(),
         };
 
@@ -680,8 +1105,23 @@ impl C2Router for C2GrpcServer {
 
         let req = request.into_inner();
 
-        // Abort the swarm command
-        // In production, would send abort signals to all units
+        // Abort the swarm command on every unit it targets
+        let unit_count = self
+            .dispatcher
+            .abort_swarm_command(&req.swarm_command_id, &req.reason)
+            .await
+            .map_err(|e| {
+                self.audit_log(
+                    "ABORT_SWARM",
+                    &device_id,
+                    &req.swarm_command_id,
+                    &format!("Abort failed: {}", e),
+                );
+                match e {
+                    DispatchError::UnknownCommand(_) => Status::not_found(e.to_string()),
+                    e => Status::internal(format!("Swarm command abort failed: {}", e)),
+                }
+            })?;
         self.audit_log(
             "ABORT_SWARM",
             &device_id,
@@ -691,7 +1131,10 @@ impl C2Router for C2GrpcServer {
 
         let response = AbortResponse {
             success: true,
-            message: format!("Swarm command {} aborted", req.swarm_command_id),
+            message: format!(
+                "Swarm command {} aborted on {} units",
+                req.swarm_command_id, unit_count
+            ),
             timestamp_ns: Self::current_timestamp_ns(),
         };
 
@@ -950,72 +1393,545 @@ impl C2Router for C2GrpcServer {
 
         Ok(Response::new(response))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::authority::AuthorityVerifier;
-    use crate::offline::EncryptedPacket;
-    use aethercore_identity::{Attestation, PlatformIdentity};
-    use base64::engine::general_purpose;
-    use ed25519_dalek::{Signer, SigningKey};
-    use proptest::prelude::*;
-    use std::collections::HashMap;
-    use std::fs;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
-    use tonic::metadata::MetadataValue;
+    async fn watch_command(
+        &self,
+        request: Request<WatchCommandRequest>,
+    ) -> Result<Response<Self::WatchCommandStream>, Status> {
+        // Authentication
+        let (device_id, _signature_b64) = self.verify_request_metadata(&request)?;
 
-    fn create_test_server() -> C2GrpcServer {
-        let dispatcher = CommandDispatcher::new();
-        let verifier = AuthorityVerifier::new();
-        let quorum_gate = QuorumGate::new(verifier);
-        let trust_scorer = TrustScorer::new();
-        let identity_manager = IdentityManager::new();
+        // Trust gating
+        self.verify_trust_score(&device_id)?;
 
-        C2GrpcServer::new(dispatcher, quorum_gate, trust_scorer, identity_manager)
+        let req = request.into_inner();
+        if req.command_id.is_empty() {
+            return Err(Status::invalid_argument("command_id is required"));
+        }
+
+        let command_feed = self.dispatcher.command_feed();
+        let command_id = req.command_id.clone();
+        let updates = command_feed
+            .subscribe(req.cursor, move |event: &CommandStatusEvent| {
+                event.command_id == command_id
+            })
+            .map_err(|e| {
+                self.audit_log("WATCH_COMMAND", &device_id, &req.command_id, &e.to_string());
+                feed_error(e)
+            })?;
+        self.audit_log("WATCH_COMMAND", &device_id, &req.command_id, "SUCCESS");
+
+        let updates = updates.map(move |update| {
+            let (cursor, event) = update.map_err(feed_error)?;
+            let status = match &event.unit_id {
+                Some(_) => event.state.status(),
+                None => command_feed
+                    .read()
+                    .command_status(&event.command_id)
+                    .map_or(event.state.status(), |status| status.status),
+            };
+            Ok(CommandStatusUpdate {
+                cursor,
+                status: status.to_string(),
+                details_json: serde_json::to_string(&event.state)
+                    .map_err(|e| Status::internal(format!("Failed to encode status: {}", e)))?,
+                command_id: event.command_id,
+                unit_id: event.unit_id.unwrap_or_default(),
+                timestamp_ns: event.timestamp_ns,
+            })
+        });
+
+        // End the stream after the update on the command as a whole
+        let updates = futures_util::stream::unfold(
+            (Box::pin(updates), false),
+            |(mut updates, finished)| async move {
+                if finished {
+                    return None;
+                }
+                let update = updates.next().await?;
+                let finished = matches!(&update, Ok(update) if update.unit_id.is_empty());
+                Some((update, (updates, finished)))
+            },
+        );
+
+        Ok(Response::new(Box::pin(updates)))
     }
 
-    fn temp_db_path(prefix: &str) -> PathBuf {
-        let ts = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos();
-        std::env::temp_dir().join(format!("{}_{}_{}.db", prefix, std::process::id(), ts))
+    async fn subscribe_fleet(
+        &self,
+        request: Request<FleetSubscription>,
+    ) -> Result<Response<Self::SubscribeFleetStream>, Status> {
+        // Authentication
+        let (device_id, _signature_b64) = self.verify_request_metadata(&request)?;
+
+        // Trust gating
+        self.verify_trust_score(&device_id)?;
+
+        let req = request.into_inner();
+        let target = subscription_target(&req.asset_ids);
+        let asset_ids = req.asset_ids;
+        let updates =
+            Self::feed_updates(&self.feeds.fleet, req.cursor, move |event: &FleetEvent| {
+                asset_ids.is_empty() || asset_ids.contains(&event.asset_id)
+            });
+        self.subscribe_feed("SUBSCRIBE_FLEET", &device_id, &target, updates)
     }
 
-    fn create_test_offline_buffer(node_id: &str, broken_chain: bool) -> OfflineMateriaBuffer {
-        const HASH_LEN: usize = 32;
-        let storage_path = temp_db_path("c2_router_stream_verify");
-        let _ = fs::remove_file(&storage_path);
+    async fn subscribe_missions(
+        &self,
+        request: Request<MissionSubscription>,
+    ) -> Result<Response<Self::SubscribeMissionsStream>, Status> {
+        // Authentication
+        let (device_id, _signature_b64) = self.verify_request_metadata(&request)?;
 
-        let mut buffer =
-            OfflineMateriaBuffer::new(storage_path, node_id.to_string()).expect("buffer init");
-        buffer.enter_offline_mode().expect("enter offline mode");
+        // Trust gating
+        self.verify_trust_score(&device_id)?;
 
-        let event_hash_1 = vec![1u8; HASH_LEN];
-        let event_hash_2 = vec![2u8; HASH_LEN];
-        let prev_hash_2 = if broken_chain {
-            vec![9u8; HASH_LEN]
+        let req = request.into_inner();
+        let target = subscription_target(&req.mission_ids);
+        let mission_ids = req.mission_ids;
+        let updates = Self::feed_updates(
+            &self.feeds.missions,
+            req.cursor,
+            move |event: &MissionEvent| {
+                mission_ids.is_empty() || mission_ids.contains(&event.mission_id)
+            },
+        );
+        self.subscribe_feed("SUBSCRIBE_MISSIONS", &device_id, &target, updates)
+    }
+
+    async fn subscribe_alerts(
+        &self,
+        request: Request<AlertSubscription>,
+    ) -> Result<Response<Self::SubscribeAlertsStream>, Status> {
+        // Authentication
+        let (device_id, _signature_b64) = self.verify_request_metadata(&request)?;
+
+        // Trust gating
+        self.verify_trust_score(&device_id)?;
+
+        let req = request.into_inner();
+        let target = subscription_target(&req.asset_ids);
+        let min_severity = if req.min_severity.is_empty() {
+            AlertSeverityFilter::Info
         } else {
-            event_hash_1.clone()
+            serde_json::from_value(serde_json::Value::String(req.min_severity.clone())).map_err(
+                |_| {
+                    self.audit_log(
+                        "SUBSCRIBE_ALERTS",
+                        &device_id,
+                        &target,
+                        &format!("Unknown severity: {}", req.min_severity),
+                    );
+                    Status::invalid_argument(format!(
+                        "Unknown alert severity: {}",
+                        req.min_severity
+                    ))
+                },
+            )?
         };
+        let asset_ids = req.asset_ids;
+        let updates =
+            Self::feed_updates(&self.feeds.alerts, req.cursor, move |event: &AlertEvent| {
+                event.severity >= min_severity
+                    && (asset_ids.is_empty()
+                        || event
+                            .source_asset
+                            .as_ref()
+                            .is_some_and(|asset_id| asset_ids.contains(asset_id)))
+            });
+        self.subscribe_feed("SUBSCRIBE_ALERTS", &device_id, &target, updates)
+    }
 
-        buffer
-            .queue_signed_event(EncryptedPacket {
-                event_id: "evt-001".to_string(),
-                timestamp_ns: 1_000,
-                encrypted_payload: vec![1, 2, 3],
-                nonce: vec![4, 5, 6],
-                event_hash: event_hash_1,
-                prev_event_hash: vec![0u8; HASH_LEN],
-                signature: vec![0u8; 64],
-                public_key_id: "pub-1".to_string(),
-            })
-            .expect("queue event 1");
-
-        buffer
+    async fn schedule_command(
+        &self,
+        request: Request<ScheduleCommandRequest>,
+    ) -> Result<Response<ScheduleCommandResponse>, Status> {
+        // Step 1: Authentication
+        let (device_id, signature_b64) = self.verify_request_metadata(&request)?;
+
+        let req = request.into_inner();
+        let schedule_id = &req.schedule_id;
+
+        // Step 1a: Verify signature against registered key
+        self.verify_command_signature(
+            &device_id,
+            &signature_b64,
+            &req.schedule_json,
+            req.timestamp_ns,
+            Some(&req.nonce),
+        )?;
+
+        // Step 1b: Replay Protection - Validate timestamp and nonce
+        if req.nonce.is_empty() {
+            self.audit_log(
+                "REPLAY_CHECK_FAILED",
+                &device_id,
+                schedule_id,
+                "No nonce provided",
+            );
+            return Err(Status::invalid_argument("No nonce provided"));
+        }
+        self.check_replay(&device_id, schedule_id, req.timestamp_ns, &req.nonce)?;
+
+        // Step 2: Trust Gating
+        self.verify_trust_score(&device_id)?;
+        let scheduler = self.require_scheduler("SCHEDULE_COMMAND", &device_id, schedule_id)?;
+
+        // Step 3: Parse and validate the schedule
+        if schedule_id.is_empty() {
+            self.audit_log(
+                "SCHEDULE_COMMAND",
+                &device_id,
+                schedule_id,
+                "No schedule ID provided",
+            );
+            return Err(Status::invalid_argument("No schedule ID provided"));
+        }
+        let schedule: CommandSchedule = serde_json::from_str(&req.schedule_json).map_err(|e| {
+            self.audit_log(
+                "SCHEDULE_COMMAND",
+                &device_id,
+                schedule_id,
+                &format!("Invalid schedule JSON: {}", e),
+            );
+            Status::invalid_argument(format!("Invalid schedule JSON: {}", e))
+        })?;
+        schedule.validate().map_err(|e| {
+            self.audit_log("SCHEDULE_COMMAND", &device_id, schedule_id, &e.to_string());
+            Status::invalid_argument(e.to_string())
+        })?;
+        if scheduler.get(schedule_id).is_some() {
+            self.audit_log(
+                "SCHEDULE_COMMAND",
+                &device_id,
+                schedule_id,
+                "Schedule already pending",
+            );
+            return Err(Status::already_exists(format!(
+                "Schedule {} is already pending",
+                schedule_id
+            )));
+        }
+
+        // Step 4: Quorum verification against the schedule hash, at the
+        // quorum the command would need if executed directly
+        let authorities = self.decode_authority_signatures(
+            "SCHEDULE_COMMAND",
+            &device_id,
+            schedule_id,
+            &req.signatures,
+        )?;
+        let scope = schedule.action.scope();
+        let schedule_hash = QuorumGate::schedule_hash(
+            &device_id,
+            schedule_id,
+            &req.schedule_json,
+            req.timestamp_ns,
+            &req.nonce,
+        );
+        schedule
+            .action
+            .verify_quorum(&self.quorum_gate, &schedule_hash, &authorities)
+            .map_err(|e| self.quorum_rejected("SCHEDULE_COMMAND", &device_id, schedule_id, e))?;
+        let proof = QuorumProof::new(
+            schedule_hash,
+            authorities,
+            scope,
+            Self::current_timestamp_ns(),
+        );
+        let quorum_proof_json = Self::encode_quorum_proof(&proof)?;
+
+        // Step 5: Record the authorized schedule in the Truth-Chain
+        let record = CommandRecord::new(
+            schedule_id.clone(),
+            "ScheduledCommand".to_string(),
+            serde_json::to_value(&schedule)
+                .map_err(|e| Status::internal(format!("Failed to encode schedule: {}", e)))?,
+            schedule_hash,
+            proof
+                .signatures
+                .iter()
+                .map(|sig| sig.authority_id.clone())
+                .collect(),
+            schedule.action.target_units(),
+            req.timestamp_ns,
+        )
+        .with_quorum_proof(proof.clone());
+        self.record_authorized_command("SCHEDULE_COMMAND", &device_id, &signature_b64, &record)?;
+
+        // Step 6: Hold the command until its trigger fires
+        let device_signature = general_purpose::STANDARD
+            .decode(&signature_b64)
+            .map_err(|_| Status::unauthenticated("Invalid signature encoding"))?;
+        scheduler
+            .schedule(ScheduledCommand {
+                schedule_id: schedule_id.clone(),
+                schedule,
+                quorum_proof: proof,
+                device_id: device_id.clone(),
+                device_signature,
+                created_ns: Self::current_timestamp_ns(),
+            })
+            .map_err(|e| {
+                self.audit_log("SCHEDULE_COMMAND", &device_id, schedule_id, &e.to_string());
+                match e {
+                    SchedulerError::DuplicateSchedule(_) => Status::already_exists(e.to_string()),
+                    SchedulerError::InvalidSchedule(_) => Status::invalid_argument(e.to_string()),
+                    e => Status::internal(format!("Failed to store schedule: {}", e)),
+                }
+            })?;
+        self.audit_log("SCHEDULE_COMMAND", &device_id, schedule_id, "SUCCESS");
+
+        let response = ScheduleCommandResponse {
+            success: true,
+            schedule_id: schedule_id.clone(),
+            message: format!("Command scheduled as {}", schedule_id),
+            timestamp_ns: Self::current_timestamp_ns(),
+            quorum_proof_json,
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn cancel_scheduled_command(
+        &self,
+        request: Request<CancelScheduleRequest>,
+    ) -> Result<Response<CancelScheduleResponse>, Status> {
+        // Step 1: Authentication
+        let (device_id, signature_b64) = self.verify_request_metadata(&request)?;
+
+        let req = request.into_inner();
+        let schedule_id = &req.schedule_id;
+
+        // Step 1a: Verify signature against registered key, over the
+        // schedule ID in place of command JSON
+        self.verify_command_signature(
+            &device_id,
+            &signature_b64,
+            schedule_id,
+            req.timestamp_ns,
+            Some(&req.nonce),
+        )?;
+
+        // Step 1b: Replay Protection - Validate timestamp and nonce
+        if req.nonce.is_empty() {
+            self.audit_log(
+                "REPLAY_CHECK_FAILED",
+                &device_id,
+                schedule_id,
+                "No nonce provided",
+            );
+            return Err(Status::invalid_argument("No nonce provided"));
+        }
+        self.check_replay(&device_id, schedule_id, req.timestamp_ns, &req.nonce)?;
+
+        // Step 2: Trust Gating
+        self.verify_trust_score(&device_id)?;
+        let scheduler = self.require_scheduler("CANCEL_SCHEDULE", &device_id, schedule_id)?;
+        let scheduled = scheduler.get(schedule_id).ok_or_else(|| {
+            self.audit_log(
+                "CANCEL_SCHEDULE",
+                &device_id,
+                schedule_id,
+                "Unknown schedule",
+            );
+            Status::not_found(format!("No pending schedule {}", schedule_id))
+        })?;
+
+        // Step 3: Quorum verification - cancelling takes the quorum that
+        // scheduling did
+        let authorities = self.decode_authority_signatures(
+            "CANCEL_SCHEDULE",
+            &device_id,
+            schedule_id,
+            &req.signatures,
+        )?;
+        let action = &scheduled.schedule.action;
+        let cancel_hash = QuorumGate::schedule_cancel_hash(
+            &device_id,
+            &scheduled.quorum_proof.command_hash,
+            req.timestamp_ns,
+            &req.nonce,
+        );
+        action
+            .verify_quorum(&self.quorum_gate, &cancel_hash, &authorities)
+            .map_err(|e| self.quorum_rejected("CANCEL_SCHEDULE", &device_id, schedule_id, e))?;
+        let proof = QuorumProof::new(
+            cancel_hash,
+            authorities,
+            action.scope(),
+            Self::current_timestamp_ns(),
+        );
+        let quorum_proof_json = Self::encode_quorum_proof(&proof)?;
+
+        // Step 3a: Record the cancellation in the Truth-Chain
+        let record = CommandRecord::new(
+            format!("{}/cancelled", schedule_id),
+            "ScheduledCommandCancelled".to_string(),
+            serde_json::json!({
+                "schedule_id": schedule_id,
+                "reason": req.reason,
+            }),
+            cancel_hash,
+            proof
+                .signatures
+                .iter()
+                .map(|sig| sig.authority_id.clone())
+                .collect(),
+            action.target_units(),
+            req.timestamp_ns,
+        )
+        .with_quorum_proof(proof);
+        self.record_authorized_command("CANCEL_SCHEDULE", &device_id, &signature_b64, &record)?;
+
+        // Step 4: Cancel, unless the command fired in the meantime
+        scheduler.cancel(schedule_id).map_err(|e| {
+            self.audit_log("CANCEL_SCHEDULE", &device_id, schedule_id, &e.to_string());
+            match e {
+                SchedulerError::UnknownSchedule(_) => Status::not_found(e.to_string()),
+                e => Status::internal(format!("Failed to cancel schedule: {}", e)),
+            }
+        })?;
+        self.audit_log(
+            "CANCEL_SCHEDULE",
+            &device_id,
+            schedule_id,
+            &format!("Reason: {}", req.reason),
+        );
+
+        let response = CancelScheduleResponse {
+            success: true,
+            message: format!("Scheduled command {} cancelled", schedule_id),
+            timestamp_ns: Self::current_timestamp_ns(),
+            quorum_proof_json,
+        };
+
+        Ok(Response::new(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::AuthorityVerifier;
+    use crate::feeds::CommandState;
+    use crate::offline::EncryptedPacket;
+    use crate::unit_link::{InMemoryUnitLink, LinkMessage, UnitBehavior};
+    use aethercore_identity::{Attestation, PlatformIdentity};
+    use base64::engine::general_purpose;
+    use ed25519_dalek::{Signer, SigningKey};
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tonic::metadata::MetadataValue;
+
+    fn authority_keys() -> Vec<(&'static str, SigningKey)> {
+        vec![
+            ("operator-1", SigningKey::from_bytes(&[11u8; 32])),
+            ("coalition-1", SigningKey::from_bytes(&[12u8; 32])),
+        ]
+    }
+
+    fn create_test_quorum_gate() -> QuorumGate {
+        let mut verifier = AuthorityVerifier::new();
+        for (authority_id, key) in authority_keys() {
+            verifier.register_authority(authority_id.to_string(), key.verifying_key().to_bytes());
+        }
+        QuorumGate::new(verifier)
+    }
+
+    /// Signatures from the first `count` test authorities, as sent on the wire
+    fn sign_authorities(command_hash: &[u8; 32], count: usize) -> Vec<String> {
+        authority_keys()
+            .into_iter()
+            .take(count)
+            .map(|(authority_id, key)| {
+                let signature = AuthoritySignature::new(
+                    authority_id.to_string(),
+                    key.sign(command_hash).to_bytes().to_vec(),
+                    key.verifying_key().to_bytes(),
+                    1000,
+                );
+                serde_json::to_string(&signature).unwrap()
+            })
+            .collect()
+    }
+
+    /// Dispatcher linked to simulated units "unit-1" and "unit-2"
+    fn create_test_dispatcher(
+        behavior: UnitBehavior,
+    ) -> (CommandDispatcher, Arc<InMemoryUnitLink>) {
+        let router_key = SigningKey::from_bytes(&[20u8; 32]);
+        let dispatcher = CommandDispatcher::new();
+        let link = Arc::new(InMemoryUnitLink::new(
+            dispatcher.reply_sink(),
+            router_key.verifying_key().to_bytes(),
+        ));
+        for (unit_id, seed) in [("unit-1", 21u8), ("unit-2", 22u8)] {
+            let key = SigningKey::from_bytes(&[seed; 32]);
+            dispatcher.register_unit_key(unit_id, key.verifying_key().to_bytes());
+            link.add_unit(unit_id, key, behavior.clone());
+        }
+        (
+            dispatcher
+                .with_unit_link(link.clone())
+                .with_router_key(router_key),
+            link,
+        )
+    }
+
+    fn create_test_server() -> C2GrpcServer {
+        let (dispatcher, _link) = create_test_dispatcher(UnitBehavior::Ack);
+        let quorum_gate = create_test_quorum_gate();
+        let trust_scorer = TrustScorer::new();
+        let identity_manager = IdentityManager::new();
+
+        C2GrpcServer::new(dispatcher, quorum_gate, trust_scorer, identity_manager)
+    }
+
+    fn temp_db_path(prefix: &str) -> PathBuf {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        std::env::temp_dir().join(format!("{}_{}_{}.db", prefix, std::process::id(), ts))
+    }
+
+    fn create_test_offline_buffer(node_id: &str, broken_chain: bool) -> OfflineMateriaBuffer {
+        const HASH_LEN: usize = 32;
+        let storage_path = temp_db_path("c2_router_stream_verify");
+        let _ = fs::remove_file(&storage_path);
+
+        let mut buffer =
+            OfflineMateriaBuffer::new(storage_path, node_id.to_string()).expect("buffer init");
+        buffer.enter_offline_mode().expect("enter offline mode");
+
+        let event_hash_1 = vec![1u8; HASH_LEN];
+        let event_hash_2 = vec![2u8; HASH_LEN];
+        let prev_hash_2 = if broken_chain {
+            vec![9u8; HASH_LEN]
+        } else {
+            event_hash_1.clone()
+        };
+
+        buffer
+            .queue_signed_event(EncryptedPacket {
+                event_id: "evt-001".to_string(),
+                timestamp_ns: 1_000,
+                encrypted_payload: vec![1, 2, 3],
+                nonce: vec![4, 5, 6],
+                event_hash: event_hash_1,
+                prev_event_hash: vec![0u8; HASH_LEN],
+                signature: vec![0u8; 64],
+                public_key_id: "pub-1".to_string(),
+            })
+            .expect("queue event 1");
+
+        buffer
             .queue_signed_event(EncryptedPacket {
                 event_id: "evt-002".to_string(),
                 timestamp_ns: 2_000,
@@ -1038,7 +1954,7 @@ mod tests {
         device_id: &str,
         broken_chain: bool,
     ) -> C2GrpcServer {
-        let dispatcher = CommandDispatcher::new();
+        let (dispatcher, _link) = create_test_dispatcher(UnitBehavior::Ack);
         let verifier = AuthorityVerifier::new();
         let quorum_gate = QuorumGate::new(verifier);
         let trust_scorer = TrustScorer::new();
@@ -1080,13 +1996,24 @@ mod tests {
         }
     }
 
-    fn sign_metadata(device_id: &str, command_json: &str, timestamp_ns: u64) -> String {
+    fn sign_metadata(
+        device_id: &str,
+        command_json: &str,
+        timestamp_ns: u64,
+        nonce: &str,
+    ) -> String {
         let signing_key = test_signing_key();
-        let message = format!("{}:{}:{}", device_id, command_json, timestamp_ns);
+        let message = format!("{}:{}:{}:{}", device_id, command_json, timestamp_ns, nonce);
         let signature = signing_key.sign(message.as_bytes());
         general_purpose::STANDARD.encode(signature.to_bytes())
     }
 
+    /// Nonce not used by any other request in this test run
+    fn next_nonce() -> String {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        format!("nonce-{}", NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
     fn attach_signature_metadata<T>(
         request: &mut Request<T>,
         device_id: &str,
@@ -1113,14 +2040,23 @@ mod tests {
         command_json: &str,
     ) -> Request<UnitCommandRequest> {
         let timestamp_ns = C2GrpcServer::current_timestamp_ns();
+        let nonce = next_nonce();
+        let command_hash =
+            QuorumGate::unit_command_hash(device_id, unit_id, command_json, timestamp_ns, &nonce);
         let mut request = Request::new(UnitCommandRequest {
             unit_id: unit_id.to_string(),
             command_json: command_json.to_string(),
-            signatures: vec!["sig1".to_string()],
+            signatures: sign_authorities(&command_hash, 1),
             timestamp_ns,
+            nonce,
         });
 
-        let signature_b64 = sign_metadata(device_id, command_json, timestamp_ns);
+        let signature_b64 = sign_metadata(
+            device_id,
+            command_json,
+            timestamp_ns,
+            &request.get_ref().nonce,
+        );
         attach_signature_metadata(&mut request, device_id, &signature_b64);
         request
     }
@@ -1157,6 +2093,7 @@ mod tests {
             command_json: r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns: 1000,
+            nonce: "nonce-1".to_string(),
         });
 
         let result = server.execute_unit_command(request).await;
@@ -1175,6 +2112,7 @@ mod tests {
             command_json: r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns: 1000,
+            nonce: "nonce-1".to_string(),
         });
 
         request
@@ -1197,6 +2135,7 @@ mod tests {
             command_json: r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns: 1000,
+            nonce: "nonce-1".to_string(),
         });
 
         request
@@ -1233,9 +2172,10 @@ mod tests {
             command_json: command_json.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns,
+            nonce: "nonce-1".to_string(),
         });
 
-        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns);
+        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns, "nonce-1");
         attach_signature_metadata(&mut request, "device-1", &signature_b64);
 
         let result = server.execute_unit_command(request).await;
@@ -1275,9 +2215,10 @@ mod tests {
             command_json: command_json.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns,
+            nonce: "nonce-1".to_string(),
         });
 
-        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns);
+        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns, "nonce-1");
         attach_signature_metadata(&mut request, "device-1", &signature_b64);
 
         let result = server.execute_unit_command(request).await;
@@ -1422,22 +2363,17 @@ mod tests {
             .update_score("device-1", 0.0); // Score will be 1.0 (default)
 
         let command_json = r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#;
-        let timestamp_ns = C2GrpcServer::current_timestamp_ns();
-        let mut request = Request::new(UnitCommandRequest {
-            unit_id: "unit-1".to_string(),
-            command_json: command_json.to_string(),
-            signatures: vec!["sig1".to_string()],
-            timestamp_ns,
-        });
-
-        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns);
-        attach_signature_metadata(&mut request, "device-1", &signature_b64);
+        let request = create_signed_unit_command_request("device-1", "unit-1", command_json);
 
         let result = server.execute_unit_command(request).await;
         assert!(result.is_ok());
         let response = result.unwrap().into_inner();
         assert!(response.success);
         assert_eq!(response.unit_id, "unit-1");
+
+        let proof: QuorumProof = serde_json::from_str(&response.quorum_proof_json).unwrap();
+        assert_eq!(proof.scope, "single unit (normal)");
+        assert_eq!(proof.signatures.len(), 1);
     }
 
     #[tokio::test]
@@ -1462,16 +2398,33 @@ mod tests {
 
         let command_json = r#"{"RecallAll":{"base_id":"BASE-1"}}"#;
         let timestamp_ns = C2GrpcServer::current_timestamp_ns();
+        let target_unit_ids = vec!["unit-1".to_string(), "unit-2".to_string()];
+        let nonce = next_nonce();
+        let command_hash = QuorumGate::swarm_command_hash(
+            "device-1",
+            "swarm-1",
+            &target_unit_ids,
+            command_json,
+            timestamp_ns,
+            &nonce,
+        );
         let mut request = Request::new(SwarmCommandRequest {
             swarm_command_id: "swarm-1".to_string(),
-            target_unit_ids: vec!["unit-1".to_string(), "unit-2".to_string()],
+            target_unit_ids,
             command_json: command_json.to_string(),
-            signatures: vec!["sig1".to_string()],
+            signatures: sign_authorities(&command_hash, 2),
             timestamp_ns,
+            nonce,
         });
 
-        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns);
+        let signature_b64 = sign_metadata(
+            "device-1",
+            command_json,
+            timestamp_ns,
+            &request.get_ref().nonce,
+        );
         attach_signature_metadata(&mut request, "device-1", &signature_b64);
+        let captured = replay_of(&request);
 
         let result = server.execute_swarm_command(request).await;
         assert!(result.is_ok());
@@ -1479,49 +2432,559 @@ mod tests {
         assert_eq!(response.swarm_command_id, "swarm-1");
         assert_eq!(response.total_units, 2);
         assert_eq!(response.success_count, 2);
-    }
 
-    #[tokio::test]
-    async fn test_get_command_status() {
-        let server = create_test_server();
+        let proof: QuorumProof = serde_json::from_str(&response.quorum_proof_json).unwrap();
+        assert_eq!(proof.command_hash, command_hash);
+        assert_eq!(proof.scope, "swarm (<5 units)");
 
-        // Register the device
-        let identity = create_test_identity("device-1");
-        server
-            .identity_manager
-            .write()
-            .unwrap()
-            .register(identity)
-            .unwrap();
+        let err = server
+            .execute_swarm_command(replay_of(&captured))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("Duplicate nonce"));
 
-        // Set high trust score
+        // The nonce is signed, so a replay cannot swap in a fresh one
+        let mut swapped = replay_of(&captured);
+        swapped.get_mut().nonce = next_nonce();
+        let err = server.execute_swarm_command(swapped).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+
+    fn create_trusted_server() -> C2GrpcServer {
+        let server = create_test_server();
+        register_identity(&server, create_test_identity("device-1"));
         server
             .trust_scorer
             .write()
             .unwrap()
             .update_score("device-1", 0.0);
+        server
+    }
+
+    fn create_unit_request_with_signatures(
+        command_json: &str,
+        signatures: impl FnOnce(&[u8; 32]) -> Vec<String>,
+    ) -> Request<UnitCommandRequest> {
+        let timestamp_ns = C2GrpcServer::current_timestamp_ns();
+        let nonce = next_nonce();
+        let command_hash =
+            QuorumGate::unit_command_hash("device-1", "unit-1", command_json, timestamp_ns, &nonce);
+        let mut request = Request::new(UnitCommandRequest {
+            unit_id: "unit-1".to_string(),
+            command_json: command_json.to_string(),
+            signatures: signatures(&command_hash),
+            timestamp_ns,
+            nonce,
+        });
+        let signature_b64 = sign_metadata(
+            "device-1",
+            command_json,
+            timestamp_ns,
+            &request.get_ref().nonce,
+        );
+        attach_signature_metadata(&mut request, "device-1", &signature_b64);
+        request
+    }
+
+    #[tokio::test]
+    async fn test_critical_unit_command_requires_quorum() {
+        let server = create_trusted_server();
+        let command_json = r#"{"Reboot":{"delay_secs":10}}"#;
+
+        let request =
+            create_unit_request_with_signatures(command_json, |hash| sign_authorities(hash, 1));
+        let err = server.execute_unit_command(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("Quorum not met"));
+
+        // The same authority twice is still one authority
+        let request = create_unit_request_with_signatures(command_json, |hash| {
+            let signature = sign_authorities(hash, 1).remove(0);
+            vec![signature.clone(), signature]
+        });
+        let err = server.execute_unit_command(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        let request =
+            create_unit_request_with_signatures(command_json, |hash| sign_authorities(hash, 2));
+        let response = server
+            .execute_unit_command(request)
+            .await
+            .unwrap()
+            .into_inner();
+        let proof: QuorumProof = serde_json::from_str(&response.quorum_proof_json).unwrap();
+        assert_eq!(proof.scope, "single unit (critical)");
+        assert_eq!(proof.signatures.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_authority_signatures_bound_to_command() {
+        let server = create_trusted_server();
+        let command_json = r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#;
+
+        // Approval for another unit does not carry over
+        let request = create_unit_request_with_signatures(command_json, |_| {
+            let other_unit =
+                QuorumGate::unit_command_hash("device-1", "unit-2", command_json, 1, "nonce-0");
+            sign_authorities(&other_unit, 1)
+        });
+        let err = server.execute_unit_command(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("verification failed"));
+
+        let request =
+            create_unit_request_with_signatures(command_json, |_| vec!["sig1".to_string()]);
+        let err = server.execute_unit_command(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_authorized_commands_recorded_in_truth_chain() {
+        let ledger_path = temp_db_path("c2_router_truth_chain");
+        let recorder = TruthChainRecorder::new(ledger_path.clone(), "c2-1".to_string()).unwrap();
+        let server = create_trusted_server().with_truth_chain(recorder);
+        let command_json = r#"{"Reboot":{"delay_secs":10}}"#;
+
+        // Refused commands leave no trace
+        let request =
+            create_unit_request_with_signatures(command_json, |hash| sign_authorities(hash, 1));
+        assert!(server.execute_unit_command(request).await.is_err());
+        let truth_chain = server.truth_chain.as_ref().unwrap();
+        assert!(truth_chain.lock().unwrap().get_last_event_hash().is_none());
+
+        let request =
+            create_unit_request_with_signatures(command_json, |hash| sign_authorities(hash, 2));
+        assert!(server.execute_unit_command(request).await.is_ok());
+        assert!(truth_chain.lock().unwrap().get_last_event_hash().is_some());
+
+        let _ = fs::remove_file(&ledger_path);
+    }
+
+    /// Copy of `request` with the same message and metadata
+    fn replay_of<T: Clone>(request: &Request<T>) -> Request<T> {
+        Request::from_parts(
+            request.metadata().clone(),
+            tonic::Extensions::default(),
+            request.get_ref().clone(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_unit_command_replay_rejected() {
+        let store_path = temp_db_path("c2_router_replay");
+        let server = create_trusted_server()
+            .with_replay_protector(ReplayProtector::open(&store_path).unwrap());
+        let command_json = r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#;
+        let request = create_signed_unit_command_request("device-1", "unit-1", command_json);
+        let captured = replay_of(&request);
+        assert!(server.execute_unit_command(request).await.is_ok());
+
+        let err = server
+            .execute_unit_command(replay_of(&captured))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("Duplicate nonce"));
 
-        let mut request = Request::new(CommandStatusRequest {
-            command_id: "cmd-1".to_string(),
+        // The nonce is signed, so a replay cannot swap in a fresh one
+        let mut swapped = replay_of(&captured);
+        swapped.get_mut().nonce = next_nonce();
+        let err = server.execute_unit_command(swapped).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+        // Nor does a restart make the command acceptable again
+        drop(server);
+        let server = create_trusted_server()
+            .with_replay_protector(ReplayProtector::open(&store_path).unwrap());
+        let err = server.execute_unit_command(captured).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("predates last shutdown"));
+
+        let request = create_signed_unit_command_request("device-1", "unit-1", command_json);
+        assert!(server.execute_unit_command(request).await.is_ok());
+        let _ = fs::remove_file(&store_path);
+    }
+
+    fn create_schedule_request(
+        schedule_id: &str,
+        schedule_json: &str,
+        authority_count: usize,
+    ) -> Request<ScheduleCommandRequest> {
+        let timestamp_ns = C2GrpcServer::current_timestamp_ns();
+        let nonce = next_nonce();
+        let schedule_hash =
+            QuorumGate::schedule_hash("device-1", schedule_id, schedule_json, timestamp_ns, &nonce);
+        let mut request = Request::new(ScheduleCommandRequest {
+            schedule_id: schedule_id.to_string(),
+            schedule_json: schedule_json.to_string(),
+            signatures: sign_authorities(&schedule_hash, authority_count),
+            timestamp_ns,
+            nonce: nonce.clone(),
         });
+        let signature_b64 = sign_metadata("device-1", schedule_json, timestamp_ns, &nonce);
+        attach_signature_metadata(&mut request, "device-1", &signature_b64);
+        request
+    }
 
+    fn create_cancel_request(
+        schedule_id: &str,
+        schedule_hash: &[u8; 32],
+        authority_count: usize,
+    ) -> Request<CancelScheduleRequest> {
+        let timestamp_ns = C2GrpcServer::current_timestamp_ns();
+        let nonce = next_nonce();
+        let cancel_hash =
+            QuorumGate::schedule_cancel_hash("device-1", schedule_hash, timestamp_ns, &nonce);
+        let mut request = Request::new(CancelScheduleRequest {
+            schedule_id: schedule_id.to_string(),
+            reason: "Mission changed".to_string(),
+            signatures: sign_authorities(&cancel_hash, authority_count),
+            timestamp_ns,
+            nonce: nonce.clone(),
+        });
+        let signature_b64 = sign_metadata("device-1", schedule_id, timestamp_ns, &nonce);
+        attach_signature_metadata(&mut request, "device-1", &signature_b64);
+        request
+    }
+
+    #[tokio::test]
+    async fn test_schedule_and_cancel_require_quorum() {
+        let schedule_json = r#"{"trigger":{"At":{"time_ns":18446744073709551615}},"action":{"Unit":{"unit_id":"unit-1","command":{"Reboot":{"delay_secs":10}}}}}"#;
+
+        let err = create_trusted_server()
+            .schedule_command(create_schedule_request("sched-1", schedule_json, 2))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unimplemented);
+
+        let server = create_trusted_server()
+            .with_scheduler(ScheduleStore::in_memory().unwrap())
+            .unwrap();
+        let scheduler = server.scheduler().unwrap();
+
+        // Scheduling a reboot takes the quorum of a reboot
+        let err = server
+            .schedule_command(create_schedule_request("sched-0", schedule_json, 1))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(scheduler.pending().is_empty());
+
+        let request = create_schedule_request("sched-1", schedule_json, 2);
+        let captured = replay_of(&request);
+        let response = server.schedule_command(request).await.unwrap().into_inner();
+        let proof: QuorumProof = serde_json::from_str(&response.quorum_proof_json).unwrap();
+        assert_eq!(proof.scope, "single unit (critical)");
+        assert!(scheduler.get("sched-1").is_some());
+
+        // So does cancelling it
+        let err = server
+            .cancel_scheduled_command(create_cancel_request("sched-1", &proof.command_hash, 1))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(scheduler.get("sched-1").is_some());
+
+        let request = create_cancel_request("sched-1", &proof.command_hash, 2);
+        let captured_cancel = replay_of(&request);
+        server.cancel_scheduled_command(request).await.unwrap();
+        assert!(scheduler.pending().is_empty());
+
+        let err = server
+            .cancel_scheduled_command(create_cancel_request("sched-1", &proof.command_hash, 2))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        // Nor can the cancelled schedule or its cancellation be replayed
+        let err = server.schedule_command(captured).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("Duplicate nonce"));
+        let err = server
+            .cancel_scheduled_command(captured_cancel)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("Duplicate nonce"));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_command_firing_recorded() {
+        let ledger_path = temp_db_path("c2_router_schedule_truth_chain");
+        let recorder = TruthChainRecorder::new(ledger_path.clone(), "c2-1".to_string()).unwrap();
+        let server = create_trusted_server()
+            .with_truth_chain(recorder)
+            .with_scheduler(ScheduleStore::in_memory().unwrap())
+            .unwrap();
+        let schedule_json = r#"{"trigger":{"At":{"time_ns":1000}},"action":{"Unit":{"unit_id":"unit-1","command":{"Loiter":{"duration_secs":60,"radius_m":null}}}}}"#;
+        let err = server
+            .schedule_command(create_schedule_request("sched-1", "{}", 1))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        server
+            .schedule_command(create_schedule_request("sched-1", schedule_json, 1))
+            .await
+            .unwrap();
+        let truth_chain = server.truth_chain.as_ref().unwrap();
+        let scheduled_hash = truth_chain.lock().unwrap().get_last_event_hash();
+        assert!(scheduled_hash.is_some());
+
+        server
+            .scheduler()
+            .unwrap()
+            .tick(C2GrpcServer::current_timestamp_ns());
+        assert!(server.scheduler().unwrap().pending().is_empty());
+        assert_ne!(
+            truth_chain.lock().unwrap().get_last_event_hash(),
+            scheduled_hash
+        );
+        assert!(truth_chain.lock().unwrap().verify_chain().unwrap());
+        let _ = fs::remove_file(&ledger_path);
+    }
+
+    /// Request carrying `device-1`'s metadata
+    fn device_request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
         request
             .metadata_mut()
             .insert("x-device-id", MetadataValue::from_static("device-1"));
         request
             .metadata_mut()
             .insert("x-signature", MetadataValue::from_static("c2lnbmF0dXJl"));
+        request
+    }
 
-        let result = server.get_command_status(request).await;
+    /// Execute a navigate command on "unit-1" and return its command ID
+    async fn execute_navigate(server: &C2GrpcServer) -> String {
+        let command_json = r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#;
+        let request = create_signed_unit_command_request("device-1", "unit-1", command_json);
+        let response = server
+            .execute_unit_command(request)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.success);
+        response.command_id
+    }
+
+    #[tokio::test]
+    async fn test_get_command_status() {
+        let server = create_trusted_server();
+
+        let err = server
+            .get_command_status(device_request(CommandStatusRequest {
+                command_id: "cmd-1".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let command_id = execute_navigate(&server).await;
+        let result = server
+            .get_command_status(device_request(CommandStatusRequest {
+                command_id: command_id.clone(),
+            }))
+            .await;
         assert!(result.is_ok());
         let response = result.unwrap().into_inner();
-        assert_eq!(response.command_id, "cmd-1");
+        assert_eq!(response.command_id, command_id);
         assert_eq!(response.status, "completed");
+        let details: HashMap<String, CommandState> =
+            serde_json::from_str(&response.details_json).unwrap();
+        assert_eq!(details["unit-1"], CommandState::Acknowledged);
+    }
+
+    #[tokio::test]
+    async fn test_watch_command() {
+        let server = create_trusted_server();
+        let command_id = execute_navigate(&server).await;
+
+        let watch = |cursor| {
+            server.watch_command(device_request(WatchCommandRequest {
+                command_id: command_id.clone(),
+                cursor,
+            }))
+        };
+
+        // Replays the delivery and ends with the command as a whole
+        let updates: Vec<CommandStatusUpdate> = watch(0)
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        let statuses: Vec<(&str, &str)> = updates
+            .iter()
+            .map(|u| (u.unit_id.as_str(), u.status.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("unit-1", "executing"),
+                ("unit-1", "completed"),
+                ("", "completed")
+            ]
+        );
+
+        // Resumes after a cursor
+        let resumed: Vec<CommandStatusUpdate> = watch(updates[0].cursor)
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(resumed, updates[1..]);
+
+        let err = watch(100).await.err().unwrap();
+        assert_eq!(err.code(), tonic::Code::OutOfRange);
+    }
+
+    fn fleet_event(asset_id: &str, timestamp: u64) -> FleetEvent {
+        FleetEvent {
+            asset_id: asset_id.to_string(),
+            event_type: crate::feeds::FleetEventType::PositionUpdate,
+            timestamp,
+            latitude: Some(45.0),
+            longitude: Some(-122.0),
+            state: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_fleet_filters_and_resumes() {
+        let server = create_trusted_server();
+        let feeds = server.feeds();
+        for (i, asset_id) in ["asset-1", "asset-2", "asset-1"].into_iter().enumerate() {
+            feeds.fleet.push(fleet_event(asset_id, i as u64));
+        }
+
+        let subscribe = |cursor| {
+            server.subscribe_fleet(device_request(FleetSubscription {
+                asset_ids: vec!["asset-1".to_string()],
+                cursor,
+            }))
+        };
+        let mut updates = subscribe(0).await.unwrap().into_inner();
+        let first = updates.next().await.unwrap().unwrap();
+        assert_eq!(first.cursor, 1);
+        let second = updates.next().await.unwrap().unwrap();
+        assert_eq!(second.cursor, 3);
+
+        // Live events after the buffered ones
+        feeds.fleet.push(fleet_event("asset-2", 3));
+        feeds.fleet.push(fleet_event("asset-1", 4));
+        let live = updates.next().await.unwrap().unwrap();
+        assert_eq!(live.cursor, 5);
+        let event: FleetEvent = serde_json::from_str(&live.event_json).unwrap();
+        assert_eq!((event.asset_id.as_str(), event.timestamp), ("asset-1", 4));
+
+        // A reconnecting client resumes after its last cursor
+        let mut resumed = subscribe(first.cursor).await.unwrap().into_inner();
+        assert_eq!(resumed.next().await.unwrap().unwrap(), second);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_missions_by_id() {
+        let server = create_trusted_server();
+        let feeds = server.feeds();
+        for mission_id in ["mission-1", "mission-2"] {
+            feeds.missions.push(MissionEvent {
+                mission_id: mission_id.to_string(),
+                event_type: crate::feeds::MissionEventType::MissionStarted,
+                timestamp: 1000,
+                state: None,
+                assets: None,
+            });
+        }
+
+        let mut updates = server
+            .subscribe_missions(device_request(MissionSubscription {
+                mission_ids: vec!["mission-2".to_string()],
+                cursor: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let update = updates.next().await.unwrap().unwrap();
+        assert_eq!(update.cursor, 2);
+        let event: MissionEvent = serde_json::from_str(&update.event_json).unwrap();
+        assert_eq!(event.mission_id, "mission-2");
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_alerts_by_severity() {
+        let server = create_trusted_server();
+        let feeds = server.feeds();
+        for (alert_id, severity, source_asset) in [
+            ("alert-1", AlertSeverityFilter::Info, Some("asset-1")),
+            ("alert-2", AlertSeverityFilter::Critical, Some("asset-2")),
+            ("alert-3", AlertSeverityFilter::Warning, Some("asset-1")),
+            ("alert-4", AlertSeverityFilter::Critical, None),
+        ] {
+            feeds.alerts.push(AlertEvent {
+                alert_id: alert_id.to_string(),
+                severity,
+                category: "integrity".to_string(),
+                message: "Test".to_string(),
+                timestamp: 1000,
+                source_asset: source_asset.map(str::to_string),
+            });
+        }
+
+        let subscribe = |min_severity: &str, asset_ids: Vec<String>, cursor| {
+            server.subscribe_alerts(device_request(AlertSubscription {
+                min_severity: min_severity.to_string(),
+                asset_ids,
+                cursor,
+            }))
+        };
+        let alert_ids = |updates: Vec<Result<FeedUpdate, Status>>| -> Vec<String> {
+            updates
+                .into_iter()
+                .map(|u| {
+                    let event: AlertEvent = serde_json::from_str(&u.unwrap().event_json).unwrap();
+                    event.alert_id
+                })
+                .collect()
+        };
+
+        let updates = subscribe("Warning", Vec::new(), 0)
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            alert_ids(updates.take(3).collect().await),
+            vec!["alert-2", "alert-3", "alert-4"]
+        );
+        let updates = subscribe("", vec!["asset-1".to_string()], 0)
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            alert_ids(updates.take(2).collect().await),
+            vec!["alert-1", "alert-3"]
+        );
+
+        let err = subscribe("Severe", Vec::new(), 0).await.err().unwrap();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        let err = subscribe("Info", Vec::new(), 99).await.err().unwrap();
+        assert_eq!(err.code(), tonic::Code::OutOfRange);
     }
 
     #[tokio::test]
     async fn test_abort_swarm_command() {
-        let server = create_test_server();
+        let (dispatcher, link) = create_test_dispatcher(UnitBehavior::Hold);
+        let server = C2GrpcServer::new(
+            dispatcher,
+            create_test_quorum_gate(),
+            TrustScorer::new(),
+            IdentityManager::new(),
+        );
 
         // Register the device
         let identity = create_test_identity("device-1");
@@ -1539,23 +3002,59 @@ mod tests {
             .unwrap()
             .update_score("device-1", 0.0);
 
-        let mut request = Request::new(AbortRequest {
-            swarm_command_id: "swarm-1".to_string(),
-            reason: "Emergency abort".to_string(),
-        });
+        let abort_request = || {
+            let mut request = Request::new(AbortRequest {
+                swarm_command_id: "swarm-1".to_string(),
+                reason: "Emergency abort".to_string(),
+            });
+            request
+                .metadata_mut()
+                .insert("x-device-id", MetadataValue::from_static("device-1"));
+            request
+                .metadata_mut()
+                .insert("x-signature", MetadataValue::from_static("c2lnbmF0dXJl"));
+            request
+        };
 
-        request
-            .metadata_mut()
-            .insert("x-device-id", MetadataValue::from_static("device-1"));
-        request
-            .metadata_mut()
-            .insert("x-signature", MetadataValue::from_static("c2lnbmF0dXJl"));
+        // Nothing in flight yet
+        let err = server
+            .abort_swarm_command(abort_request())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let command = SwarmCommand::RecallAll {
+            base_id: "BASE-1".to_string(),
+        };
+        let target_unit_ids = vec!["unit-1".to_string(), "unit-2".to_string()];
+        let dispatch = server.dispatcher.dispatch_swarm_command(
+            "swarm-1".to_string(),
+            &command,
+            &target_unit_ids,
+            1000,
+        );
+        let abort = async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            server.abort_swarm_command(abort_request()).await
+        };
+        let (status, result) = tokio::join!(dispatch, abort);
 
-        let result = server.abort_swarm_command(request).await;
-        assert!(result.is_ok());
         let response = result.unwrap().into_inner();
         assert!(response.success);
-        assert!(response.message.contains("aborted"));
+        assert!(response.message.contains("aborted on 2 units"));
+        let status = status.unwrap();
+        assert_eq!(status.failure_count, 2);
+        assert!(status.unit_results.iter().all(|result| matches!(
+            result,
+            UnitDispatchResult::Failed { reason, .. } if reason == "Aborted: Emergency abort"
+        )));
+        assert_eq!(
+            link.sent()
+                .iter()
+                .filter(|(_, message)| matches!(message, LinkMessage::Abort { .. }))
+                .count(),
+            2
+        );
     }
 
     #[tokio::test]
@@ -1584,9 +3083,10 @@ mod tests {
             command_json: command_json.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns,
+            nonce: "nonce-1".to_string(),
         });
 
-        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns);
+        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns, "nonce-1");
         attach_signature_metadata(&mut request, "device-1", &signature_b64);
 
         let result = server.execute_unit_command(request).await;
@@ -1623,9 +3123,10 @@ mod tests {
             command_json: command_json.to_string(),
             signatures: vec!["sig1".to_string()],
             timestamp_ns,
+            nonce: "nonce-1".to_string(),
         });
 
-        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns);
+        let signature_b64 = sign_metadata("device-1", command_json, timestamp_ns, "nonce-1");
         attach_signature_metadata(&mut request, "device-1", &signature_b64);
 
         let result = server.execute_unit_command(request).await;
@@ -1657,14 +3158,54 @@ mod tests {
         let signature = signing_key.sign(message.as_bytes());
         let signature_b64 = general_purpose::STANDARD.encode(signature.to_bytes());
 
-        let result =
-            server.verify_command_signature(device_id, &signature_b64, command_json, timestamp_ns);
+        let result = server.verify_command_signature(
+            device_id,
+            &signature_b64,
+            command_json,
+            timestamp_ns,
+            None,
+        );
 
         if let Err(err) = result {
             panic!("Valid signature rejected: {}", err);
         }
     }
 
+    #[test]
+    fn signature_verification_binds_nonce() {
+        let server = create_test_server();
+        let device_id = "device-1";
+        let command_json = r#"{"cmd":"test"}"#;
+        let timestamp_ns = 42;
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = signing_key.verifying_key().to_bytes();
+
+        register_identity(&server, create_signing_identity(device_id, public_key));
+
+        let message = format!("{}:{}:{}:nonce-1", device_id, command_json, timestamp_ns);
+        let signature = signing_key.sign(message.as_bytes());
+        let signature_b64 = general_purpose::STANDARD.encode(signature.to_bytes());
+
+        assert!(server
+            .verify_command_signature(
+                device_id,
+                &signature_b64,
+                command_json,
+                timestamp_ns,
+                Some("nonce-1"),
+            )
+            .is_ok());
+        assert!(server
+            .verify_command_signature(
+                device_id,
+                &signature_b64,
+                command_json,
+                timestamp_ns,
+                Some("nonce-2"),
+            )
+            .is_err());
+    }
+
     #[test]
     fn signature_verification_rejects_invalid_base64() {
         let server = create_test_server();
@@ -1676,8 +3217,13 @@ mod tests {
 
         register_identity(&server, create_signing_identity(device_id, public_key));
 
-        let result =
-            server.verify_command_signature(device_id, "not-base64!", command_json, timestamp_ns);
+        let result = server.verify_command_signature(
+            device_id,
+            "not-base64!",
+            command_json,
+            timestamp_ns,
+            None,
+        );
 
         match result {
             Ok(_) => panic!("Invalid base64 signature was accepted"),
@@ -1709,8 +3255,13 @@ mod tests {
         let signature = attacker_key.sign(message.as_bytes());
         let signature_b64 = general_purpose::STANDARD.encode(signature.to_bytes());
 
-        let result =
-            server.verify_command_signature(device_id, &signature_b64, command_json, timestamp_ns);
+        let result = server.verify_command_signature(
+            device_id,
+            &signature_b64,
+            command_json,
+            timestamp_ns,
+            None,
+        );
 
         match result {
             Ok(_) => panic!("Mismatched signature was accepted"),
@@ -1747,6 +3298,7 @@ mod tests {
                 &signature_b64,
                 command_json,
                 timestamp_ns,
+                None,
             );
 
             prop_assert!(result.is_err());
@@ -1755,4 +3307,31 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "mtls")]
+    #[tokio::test]
+    async fn test_mtls_requires_client_certificate() {
+        struct AllowAll;
+
+        impl aethercore_identity::mtls::PeerAuthorizer for AllowAll {
+            fn authorize_peer(&self, _chain: &[&[u8]]) -> Result<String, String> {
+                Ok("device-1".to_string())
+            }
+        }
+
+        let server = create_test_server().with_peer_authorizer(Arc::new(AllowAll));
+        register_identity(&server, create_test_identity("device-1"));
+        server
+            .trust_scorer
+            .write()
+            .unwrap()
+            .update_score("device-1", 0.0);
+
+        let command_json = r#"{"Navigate":{"waypoint":{"lat":45.0,"lon":-122.0,"alt":100.0},"speed":10.0,"altitude":100.0}}"#;
+        let request = create_signed_unit_command_request("device-1", "unit-1", command_json);
+
+        let err = server.execute_unit_command(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        assert!(err.message().contains("Client certificate"));
+    }
 }
@@ -5,6 +5,7 @@
 
 #![warn(missing_docs)]
 
+use crate::quorum::QuorumProof;
 use aethercore_core::ledger::{EventLedger, LedgerError, SignedEvent};
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
@@ -44,6 +45,9 @@ pub struct CommandRecord {
     pub target_units: Vec<String>,
     /// Command timestamp
     pub timestamp_ns: u64,
+    /// Proof that the command met its quorum, if it went through the gate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quorum_proof: Option<QuorumProof>,
 }
 
 impl CommandRecord {
@@ -65,8 +69,15 @@ impl CommandRecord {
             authority_signatures,
             target_units,
             timestamp_ns,
+            quorum_proof: None,
         }
     }
+
+    /// Attach the quorum proof that authorized the command
+    pub fn with_quorum_proof(mut self, proof: QuorumProof) -> Self {
+        self.quorum_proof = Some(proof);
+        self
+    }
 }
 
 /// Truth-Chain recorder for command audit
@@ -5,9 +5,14 @@
 //! - Command type definitions for unit and swarm operations
 //! - Authority verification with Ed25519 signatures
 //! - Quorum-gated actuation based on command scope
-//! - Command dispatch with unit/swarm fan-out
+//! - Command dispatch with unit/swarm fan-out over unit links, with signed
+//!   acknowledgements, retries and abort
+//! - Replay protection with a nonce store that survives restarts
+//! - Scheduled commands fired by time, geofence entry, telemetry thresholds
+//!   or loss of link, held in a store that survives restarts
 //! - Truth-Chain Ledger integration for command audit
-//! - gRPC service interface (placeholder)
+//! - gRPC service interface, with command status and fleet, mission and
+//!   alert streams resumable from a cursor
 //!
 //! # Architecture
 //!
@@ -39,8 +44,12 @@
 //!     authority::AuthorityVerifier,
 //!     quorum::QuorumGate,
 //!     dispatcher::CommandDispatcher,
+//!     unit_link::MeshUnitLink,
 //! };
+//! use aethercore_mesh::UdpTransport;
+//! use std::sync::Arc;
 //!
+//! # async fn example(unit_public_key: [u8; 32], router_key: ed25519_dalek::SigningKey) {
 //! // Create command
 //! let command = UnitCommand::Navigate {
 //!     waypoint: Coordinate { lat: 45.0, lon: -122.0, alt: Some(100.0) },
@@ -52,9 +61,22 @@
 //! let verifier = AuthorityVerifier::new();
 //! let gate = QuorumGate::new(verifier);
 //!
-//! // Dispatch command
+//! // Reach units over the mesh
 //! let dispatcher = CommandDispatcher::new();
-//! let result = dispatcher.dispatch_unit_command("unit-1", &command, 1000);
+//! let transport = Arc::new(UdpTransport::bind("0.0.0.0:7500").await.unwrap());
+//! let link = Arc::new(MeshUnitLink::new(transport, dispatcher.reply_sink()));
+//! link.set_address("unit-1", "10.0.0.7:7500");
+//! dispatcher.register_unit_key("unit-1", unit_public_key);
+//! let dispatcher = dispatcher
+//!     .with_unit_link(link.clone())
+//!     .with_router_key(router_key);
+//! tokio::spawn(async move { link.run().await });
+//!
+//! // Dispatch command and wait for the unit's acknowledgement
+//! let result = dispatcher
+//!     .dispatch_unit_command("cmd-1", "unit-1", &command, 1000)
+//!     .await;
+//! # }
 //! ```
 
 #![warn(missing_docs)]
@@ -68,6 +90,8 @@ pub mod ledger;
 pub mod offline;
 pub mod quorum;
 pub mod replay_protection;
+pub mod scheduler;
+pub mod unit_link;
 
 // Re-export commonly used types
 pub use authority::{AuthorityError, AuthoritySignature, AuthorityVerifier};
@@ -76,11 +100,16 @@ pub use command_types::{
     SwarmCommand, UnitCommand,
 };
 pub use dispatcher::{CommandDispatcher, DispatchError, SwarmDispatchStatus, UnitDispatchResult};
-pub use feeds::{AlertFeed, FleetFeed, MissionFeed};
+pub use feeds::{
+    AlertFeed, CommandFeed, CommandState, CommandStatusEvent, Feed, FeedError, FeedHub, FleetFeed,
+    LiveFeed, MissionFeed,
+};
 pub use grpc::{
-    c2_proto, AbortRequest, AbortResponse, C2GrpcServer, C2Router, C2RouterServer,
-    CommandStatusRequest, CommandStatusResponse, SwarmCommandRequest, SwarmCommandResponse,
-    UnitCommandRequest, UnitCommandResponse,
+    c2_proto, AbortRequest, AbortResponse, AlertSubscription, C2GrpcServer, C2Router,
+    C2RouterServer, CancelScheduleRequest, CancelScheduleResponse, CommandStatusRequest,
+    CommandStatusResponse, CommandStatusUpdate, FeedUpdate, FleetSubscription, MissionSubscription,
+    ScheduleCommandRequest, ScheduleCommandResponse, SwarmCommandRequest, SwarmCommandResponse,
+    UnitCommandRequest, UnitCommandResponse, UpdateStream, WatchCommandRequest,
 };
 pub use ledger::{CommandRecord, RecorderError, TruthChainRecorder};
 pub use offline::{
@@ -88,3 +117,11 @@ pub use offline::{
 };
 pub use quorum::{CommandScope, QuorumError, QuorumGate, QuorumProof};
 pub use replay_protection::{ReplayError, ReplayProtector, ReplayResult};
+pub use scheduler::{
+    CommandSchedule, CommandScheduler, Comparison, Observation, ScheduleStore, ScheduledAction,
+    ScheduledCommand, SchedulerError, Trigger,
+};
+pub use unit_link::{
+    InMemoryUnitLink, LinkError, LinkMessage, MeshUnitLink, ReplyKind, ReplySink, UnitBehavior,
+    UnitLink, UnitReply,
+};
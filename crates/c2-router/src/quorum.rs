@@ -151,7 +151,101 @@ impl QuorumGate {
         self.verify_quorum(scope, command_hash, signatures)
     }
 
+    /// Hash authorities sign to approve a unit command
+    ///
+    /// Binds the target unit and request timestamp as well as the command,
+    /// so approval for one unit cannot be replayed against another. The
+    /// requesting device and its nonce tie the approval to a single request:
+    /// resubmitting it under a fresh nonce needs fresh signatures.
+    pub fn unit_command_hash(
+        device_id: &str,
+        unit_id: &str,
+        command_json: &str,
+        timestamp_ns: u64,
+        nonce: &str,
+    ) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"aethercore.c2.unit-command");
+        hash_field(&mut hasher, device_id.as_bytes());
+        hash_field(&mut hasher, unit_id.as_bytes());
+        hash_field(&mut hasher, command_json.as_bytes());
+        hasher.update(&timestamp_ns.to_le_bytes());
+        hash_field(&mut hasher, nonce.as_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Hash authorities sign to approve a swarm command
+    ///
+    /// Binds the full target list, which also decides the command's scope,
+    /// and like `unit_command_hash` the requesting device and its nonce.
+    pub fn swarm_command_hash(
+        device_id: &str,
+        swarm_command_id: &str,
+        target_unit_ids: &[String],
+        command_json: &str,
+        timestamp_ns: u64,
+        nonce: &str,
+    ) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"aethercore.c2.swarm-command");
+        hash_field(&mut hasher, device_id.as_bytes());
+        hash_field(&mut hasher, swarm_command_id.as_bytes());
+        hasher.update(&(target_unit_ids.len() as u64).to_le_bytes());
+        for unit_id in target_unit_ids {
+            hash_field(&mut hasher, unit_id.as_bytes());
+        }
+        hash_field(&mut hasher, command_json.as_bytes());
+        hasher.update(&timestamp_ns.to_le_bytes());
+        hash_field(&mut hasher, nonce.as_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Hash authorities sign to approve a scheduled command
+    ///
+    /// Binds the trigger along with the action, so approval for a command
+    /// held until one condition cannot be reused to fire it on another, and
+    /// like `unit_command_hash` the requesting device and its nonce.
+    pub fn schedule_hash(
+        device_id: &str,
+        schedule_id: &str,
+        schedule_json: &str,
+        timestamp_ns: u64,
+        nonce: &str,
+    ) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"aethercore.c2.schedule");
+        hash_field(&mut hasher, device_id.as_bytes());
+        hash_field(&mut hasher, schedule_id.as_bytes());
+        hash_field(&mut hasher, schedule_json.as_bytes());
+        hasher.update(&timestamp_ns.to_le_bytes());
+        hash_field(&mut hasher, nonce.as_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Hash authorities sign to cancel a scheduled command
+    ///
+    /// Binds the hash the schedule was approved under, so a cancellation
+    /// cannot be replayed against a later schedule reusing the same ID, and
+    /// the cancelling device and its nonce.
+    pub fn schedule_cancel_hash(
+        device_id: &str,
+        schedule_hash: &[u8; 32],
+        timestamp_ns: u64,
+        nonce: &str,
+    ) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"aethercore.c2.schedule-cancel");
+        hash_field(&mut hasher, device_id.as_bytes());
+        hasher.update(schedule_hash);
+        hasher.update(&timestamp_ns.to_le_bytes());
+        hash_field(&mut hasher, nonce.as_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
     /// Verify quorum for a given scope
+    ///
+    /// Only registered authorities count, each once, however many of its
+    /// signatures are presented.
     fn verify_quorum(
         &self,
         scope: CommandScope,
@@ -160,21 +254,36 @@ impl QuorumGate {
     ) -> Result<(), QuorumError> {
         let required = scope.required_signatures();
 
-        if signatures.len() < required {
+        if let Some(unknown) = signatures
+            .iter()
+            .find(|sig| !self.verifier.is_registered(&sig.authority_id))
+        {
+            return Err(AuthorityError::UnknownAuthority(unknown.authority_id.clone()).into());
+        }
+
+        // Verify all signatures
+        let mut authorities = self.verifier.verify_multiple(command_hash, signatures)?;
+        authorities.sort();
+        authorities.dedup();
+
+        if authorities.len() < required {
             return Err(QuorumError::InsufficientAuthority {
-                got: signatures.len(),
+                got: authorities.len(),
                 required,
                 operation: scope.operation_name().to_string(),
             });
         }
 
-        // Verify all signatures
-        self.verifier.verify_multiple(command_hash, signatures)?;
-
         Ok(())
     }
 }
 
+/// Length-prefix a variable field so adjacent fields cannot run together
+pub(crate) fn hash_field(hasher: &mut blake3::Hasher, field: &[u8]) {
+    hasher.update(&(field.len() as u64).to_le_bytes());
+    hasher.update(field);
+}
+
 /// Quorum proof for command authorization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuorumProof {
@@ -333,4 +442,93 @@ mod tests {
             .verify_unit_command(&command, &command_hash, &signatures)
             .is_ok());
     }
+
+    #[test]
+    fn test_duplicate_authority_counts_once() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let command = UnitCommand::Reboot { delay_secs: 10 };
+        let command_hash = QuorumGate::unit_command_hash("device-1", "unit-1", "{}", 1000, "n-1");
+
+        let mut verifier = AuthorityVerifier::new();
+        verifier.register_authority("operator-1".to_string(), key.verifying_key().to_bytes());
+        let gate = QuorumGate::new(verifier);
+
+        let signatures = vec![
+            create_test_signature(&key, &command_hash, "operator-1"),
+            create_test_signature(&key, &command_hash, "operator-1"),
+        ];
+        assert!(matches!(
+            gate.verify_unit_command(&command, &command_hash, &signatures),
+            Err(QuorumError::InsufficientAuthority { got: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_unregistered_authority_rejected() {
+        let key = SigningKey::from_bytes(&[2u8; 32]);
+        let command = UnitCommand::EmergencyStop {
+            reason: "Test emergency".to_string(),
+        };
+        let command_hash = QuorumGate::unit_command_hash("device-1", "unit-1", "{}", 1000, "n-1");
+        let signatures = vec![create_test_signature(&key, &command_hash, "self-signed")];
+
+        let gate = QuorumGate::new(AuthorityVerifier::new());
+        assert!(matches!(
+            gate.verify_unit_command(&command, &command_hash, &signatures),
+            Err(QuorumError::AuthorityFailed(
+                AuthorityError::UnknownAuthority(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_command_hash_binds_targets() {
+        let hash = QuorumGate::unit_command_hash("device-1", "unit-1", "{}", 1000, "n-1");
+        assert_ne!(
+            hash,
+            QuorumGate::unit_command_hash("device-1", "unit-2", "{}", 1000, "n-1")
+        );
+        assert_ne!(
+            hash,
+            QuorumGate::unit_command_hash("device-1", "unit-1", "{}", 1001, "n-1")
+        );
+        // Approval is for one request, not any resubmission of the command
+        assert_ne!(
+            hash,
+            QuorumGate::unit_command_hash("device-1", "unit-1", "{}", 1000, "n-2")
+        );
+        assert_ne!(
+            hash,
+            QuorumGate::unit_command_hash("device-2", "unit-1", "{}", 1000, "n-1")
+        );
+
+        let targets = vec!["unit-1".to_string(), "unit-2".to_string()];
+        let hash =
+            QuorumGate::swarm_command_hash("device-1", "swarm-1", &targets, "{}", 1000, "n-1");
+        assert_ne!(
+            hash,
+            QuorumGate::swarm_command_hash("device-1", "swarm-1", &targets[..1], "{}", 1000, "n-1")
+        );
+        // Field boundaries cannot be shifted between IDs
+        let merged = vec!["unit-1unit-2".to_string()];
+        assert_ne!(
+            hash,
+            QuorumGate::swarm_command_hash("device-1", "swarm-1", &merged, "{}", 1000, "n-1")
+        );
+
+        let hash = QuorumGate::schedule_hash("device-1", "sched-1", "{}", 1000, "n-1");
+        assert_ne!(
+            hash,
+            QuorumGate::schedule_hash("device-1", "sched-1", "{}", 1000, "n-2")
+        );
+        assert_ne!(
+            hash,
+            QuorumGate::schedule_hash("device-2", "sched-1", "{}", 1000, "n-1")
+        );
+        let cancel_hash = QuorumGate::schedule_cancel_hash("device-1", &hash, 1000, "n-1");
+        assert_ne!(
+            cancel_hash,
+            QuorumGate::schedule_cancel_hash("device-1", &hash, 1000, "n-2")
+        );
+    }
 }
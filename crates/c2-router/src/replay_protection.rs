@@ -2,11 +2,27 @@
 //!
 //! Implements nonce-based and timestamp-based replay protection for C2 commands.
 //! Enforces the fail-visible doctrine: suspected replay attacks are rejected immediately.
-
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+//!
+//! # Persistence
+//!
+//! A protector opened on a database file survives restarts. Each nonce is
+//! committed to the store before its command is accepted, so a crash cannot
+//! forget a nonce that was acted on. Nonces are grouped in time buckets by
+//! command timestamp, and whole buckets are pruned once they fall out of the
+//! retention window.
+//!
+//! On startup, commands timestamped at or before the previous run's last
+//! activity are refused outright. They were issued before the restart and
+//! are not expected to arrive after it.
+
+use aethercore_core::sync;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+use tracing::{info, warn};
 
 /// Maximum age of a command timestamp (5 minutes)
 const MAX_TIMESTAMP_AGE_SECS: u64 = 300;
@@ -17,9 +33,14 @@ const MAX_FUTURE_SKEW_SECS: u64 = 30;
 /// Time window to keep processed nonces in memory (10 minutes)
 const NONCE_RETENTION_SECS: u64 = 600;
 
+/// Width of the time buckets nonces are pruned by (1 minute)
+const NONCE_BUCKET_SECS: u64 = 60;
+
 /// Maximum number of nonces to track per device (prevents memory exhaustion)
 const MAX_NONCES_PER_DEVICE: usize = 1000;
 
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
 /// Replay protection error types
 #[derive(Debug, Error)]
 pub enum ReplayError {
@@ -41,6 +62,15 @@ pub enum ReplayError {
         u64,
     ),
 
+    /// Command was issued before the router last shut down
+    #[error("Command timestamp {0} predates last shutdown at {1}")]
+    PredatesShutdown(
+        /// Command timestamp (nanoseconds since epoch)
+        u64,
+        /// Last activity before the restart (nanoseconds since epoch)
+        u64,
+    ),
+
     /// Nonce was already observed for this device
     #[error("Duplicate nonce detected: {0}")]
     DuplicateNonce(
@@ -65,16 +95,60 @@ pub enum ReplayError {
         /// System time error details
         String,
     ),
+
+    /// Nonce store could not be read or written
+    #[error("Nonce store error: {0}")]
+    StorageError(
+        /// Database error details
+        #[from]
+        rusqlite::Error,
+    ),
 }
 
 /// Result type for replay protection operations
 pub type ReplayResult<T> = Result<T, ReplayError>;
 
-/// Nonce entry with timestamp for cleanup
-#[derive(Debug, Clone)]
-struct NonceEntry {
-    nonce: String,
-    timestamp_ns: u64,
+/// Nonces seen within the retention window
+#[derive(Debug, Default)]
+struct NonceWindow {
+    /// Bucket of each nonce, per device ID
+    devices: HashMap<String, HashMap<String, u64>>,
+    /// Device ID and nonce of each entry, per bucket
+    buckets: BTreeMap<u64, Vec<(String, String)>>,
+}
+
+impl NonceWindow {
+    fn insert(&mut self, device_id: &str, nonce: &str, bucket: u64) {
+        self.devices
+            .entry(device_id.to_string())
+            .or_default()
+            .insert(nonce.to_string(), bucket);
+        self.buckets
+            .entry(bucket)
+            .or_default()
+            .push((device_id.to_string(), nonce.to_string()));
+    }
+
+    /// Forget every bucket before `cutoff`; returns whether any were dropped
+    fn prune(&mut self, cutoff: u64) -> bool {
+        let kept = self.buckets.split_off(&cutoff);
+        let expired = std::mem::replace(&mut self.buckets, kept);
+        for (device_id, nonce) in expired.values().flatten() {
+            if let Some(nonces) = self.devices.get_mut(device_id) {
+                nonces.remove(nonce);
+                if nonces.is_empty() {
+                    self.devices.remove(device_id);
+                }
+            }
+        }
+        !expired.is_empty()
+    }
+}
+
+struct ProtectorState {
+    window: NonceWindow,
+    /// Durable nonce store (None for an in-memory protector)
+    store: Option<Connection>,
 }
 
 /// Replay protection tracker
@@ -82,16 +156,100 @@ struct NonceEntry {
 /// Tracks processed nonces per device to prevent replay attacks.
 /// Uses time-based cleanup to prevent memory exhaustion.
 pub struct ReplayProtector {
-    /// Nonces seen per device ID
-    nonces: Arc<RwLock<HashMap<String, Vec<NonceEntry>>>>,
+    state: Mutex<ProtectorState>,
+    /// Commands timestamped at or before this are refused
+    not_before_ns: u64,
 }
 
 impl ReplayProtector {
-    /// Create a new replay protector
+    /// Create a new in-memory replay protector
+    ///
+    /// Nothing is kept across restarts; use `open` where that matters.
     pub fn new() -> Self {
         Self {
-            nonces: Arc::new(RwLock::new(HashMap::new())),
+            state: Mutex::new(ProtectorState {
+                window: NonceWindow::default(),
+                store: None,
+            }),
+            not_before_ns: 0,
+        }
+    }
+
+    /// Open a replay protector backed by the SQLite database at `path`
+    ///
+    /// Nonces still within the retention window are reloaded, and commands
+    /// timestamped at or before the last activity recorded in the database
+    /// are refused from now on.
+    pub fn open(path: impl AsRef<Path>) -> ReplayResult<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+        )?;
+        // Full sync: an accepted nonce must survive power loss, not just a crash
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+             PRAGMA synchronous=FULL;
+             CREATE TABLE IF NOT EXISTS replay_nonces (
+                 device_id TEXT NOT NULL,
+                 nonce TEXT NOT NULL,
+                 timestamp_ns INTEGER NOT NULL,
+                 bucket INTEGER NOT NULL,
+                 PRIMARY KEY (device_id, nonce)
+             );
+             CREATE INDEX IF NOT EXISTS idx_replay_nonces_bucket
+                 ON replay_nonces(bucket);
+             CREATE TABLE IF NOT EXISTS replay_state (
+                 key TEXT PRIMARY KEY,
+                 value INTEGER NOT NULL
+             );",
+        )?;
+
+        let cutoff = Self::retention_cutoff(Self::now_ns()?);
+        conn.execute(
+            "DELETE FROM replay_nonces WHERE bucket < ?1",
+            params![cutoff as i64],
+        )?;
+
+        let mut window = NonceWindow::default();
+        {
+            let mut stmt = conn.prepare("SELECT device_id, nonce, bucket FROM replay_nonces")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)? as u64,
+                ))
+            })?;
+            for row in rows {
+                let (device_id, nonce, bucket) = row?;
+                window.insert(&device_id, &nonce, bucket);
+            }
         }
+
+        let not_before_ns = conn
+            .query_row(
+                "SELECT value FROM replay_state WHERE key = 'last_active_ns'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .map_or(0, |value| value as u64);
+
+        info!(
+            path = %path.display(),
+            nonces = window.buckets.values().map(Vec::len).sum::<usize>(),
+            not_before_ns,
+            "Replay protection store opened"
+        );
+
+        Ok(Self {
+            state: Mutex::new(ProtectorState {
+                window,
+                store: Some(conn),
+            }),
+            not_before_ns,
+        })
     }
 
     /// Validate command timestamp and nonce
@@ -99,9 +257,11 @@ impl ReplayProtector {
     /// Checks:
     /// 1. Timestamp is not too old (prevents replay of stale commands)
     /// 2. Timestamp is not too far in future (prevents time manipulation)
-    /// 3. Nonce has not been seen before (prevents exact replay)
+    /// 3. Timestamp is after the last shutdown (prevents replay across restarts)
+    /// 4. Nonce has not been seen before (prevents exact replay)
     ///
     /// Returns Ok if validation passes, Err for replay attack or invalid input.
+    /// A nonce that cannot be persisted fails the command.
     pub fn validate_command(
         &self,
         device_id: &str,
@@ -111,10 +271,18 @@ impl ReplayProtector {
         // Step 1: Validate timestamp freshness
         self.validate_timestamp(timestamp_ns)?;
 
-        // Step 2: Check for duplicate nonce
+        // Step 2: Refuse commands issued before the restart
+        if timestamp_ns <= self.not_before_ns {
+            return Err(ReplayError::PredatesShutdown(
+                timestamp_ns,
+                self.not_before_ns,
+            ));
+        }
+
+        // Step 3: Check for duplicate nonce
         self.check_and_record_nonce(device_id, timestamp_ns, nonce)?;
 
-        // Step 3: Cleanup old nonces (async cleanup to avoid blocking)
+        // Step 4: Drop buckets past the retention window
         self.cleanup_old_nonces();
 
         Ok(())
@@ -122,14 +290,11 @@ impl ReplayProtector {
 
     /// Validate that timestamp is within acceptable window
     fn validate_timestamp(&self, timestamp_ns: u64) -> ReplayResult<()> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| ReplayError::SystemTimeError(e.to_string()))?
-            .as_nanos() as u64;
+        let now = Self::now_ns()?;
 
         // Check if timestamp is too old
         if timestamp_ns < now {
-            let age_secs = (now - timestamp_ns) / 1_000_000_000;
+            let age_secs = (now - timestamp_ns) / NANOS_PER_SEC;
             if age_secs > MAX_TIMESTAMP_AGE_SECS {
                 return Err(ReplayError::TimestampTooOld(
                     age_secs,
@@ -138,7 +303,7 @@ impl ReplayProtector {
             }
         } else {
             // Check if timestamp is too far in future
-            let future_secs = (timestamp_ns - now) / 1_000_000_000;
+            let future_secs = (timestamp_ns - now) / NANOS_PER_SEC;
             if future_secs > MAX_FUTURE_SKEW_SECS {
                 return Err(ReplayError::TimestampTooFuture(
                     future_secs,
@@ -157,64 +322,99 @@ impl ReplayProtector {
         timestamp_ns: u64,
         nonce: &str,
     ) -> ReplayResult<()> {
-        let mut nonces = self
-            .nonces
-            .write()
-            .map_err(|e| ReplayError::InvalidTimestamp(format!("Lock error: {}", e)))?;
+        let mut state = self.lock();
 
-        let device_nonces = nonces.entry(device_id.to_string()).or_insert_with(Vec::new);
+        if let Some(device_nonces) = state.window.devices.get(device_id) {
+            // Check if nonce already exists
+            if device_nonces.contains_key(nonce) {
+                return Err(ReplayError::DuplicateNonce(nonce.to_string()));
+            }
 
-        // Check if nonce already exists
-        if device_nonces.iter().any(|entry| entry.nonce == nonce) {
-            return Err(ReplayError::DuplicateNonce(nonce.to_string()));
+            // Check nonce limit
+            if device_nonces.len() >= MAX_NONCES_PER_DEVICE {
+                return Err(ReplayError::NonceLimitExceeded);
+            }
         }
 
-        // Check nonce limit
-        if device_nonces.len() >= MAX_NONCES_PER_DEVICE {
-            return Err(ReplayError::NonceLimitExceeded);
+        // Persist before accepting, so the nonce outlives a crash
+        let bucket = timestamp_ns / (NONCE_BUCKET_SECS * NANOS_PER_SEC);
+        if let Some(store) = state.store.as_mut() {
+            let tx = store.transaction()?;
+            // A row only the store still has is an expired nonce it failed to prune
+            tx.execute(
+                "INSERT OR REPLACE INTO replay_nonces (device_id, nonce, timestamp_ns, bucket)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![device_id, nonce, timestamp_ns as i64, bucket as i64],
+            )?;
+            Self::write_last_active(&tx, Self::now_ns()?)?;
+            tx.commit()?;
         }
 
-        // Record the nonce
-        device_nonces.push(NonceEntry {
-            nonce: nonce.to_string(),
-            timestamp_ns,
-        });
-
+        state.window.insert(device_id, nonce, bucket);
         Ok(())
     }
 
     /// Clean up old nonces that are beyond the retention window
     fn cleanup_old_nonces(&self) {
-        if let Ok(mut nonces) = self.nonces.write() {
-            let cutoff_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|d| d.as_nanos() as u64 - (NONCE_RETENTION_SECS * 1_000_000_000))
-                .unwrap_or(0);
-
-            for (_, device_nonces) in nonces.iter_mut() {
-                device_nonces.retain(|entry| entry.timestamp_ns >= cutoff_time);
+        let Ok(now) = Self::now_ns() else {
+            return;
+        };
+        let cutoff = Self::retention_cutoff(now);
+
+        let mut state = self.lock();
+        if !state.window.prune(cutoff) {
+            return;
+        }
+        if let Some(store) = &state.store {
+            // Expired nonces left behind are deleted on the next prune or open
+            if let Err(e) = store.execute(
+                "DELETE FROM replay_nonces WHERE bucket < ?1",
+                params![cutoff as i64],
+            ) {
+                warn!("Failed to prune replay nonce store: {}", e);
             }
-
-            // Remove devices with no nonces
-            nonces.retain(|_, device_nonces| !device_nonces.is_empty());
         }
     }
 
     /// Get the number of tracked nonces for a device (for testing/monitoring)
     pub fn nonce_count(&self, device_id: &str) -> usize {
-        self.nonces
-            .read()
-            .ok()
-            .and_then(|nonces| nonces.get(device_id).map(|v| v.len()))
-            .unwrap_or(0)
+        self.lock()
+            .window
+            .devices
+            .get(device_id)
+            .map_or(0, HashMap::len)
     }
 
     /// Clear all tracked nonces (for testing)
     #[cfg(test)]
     pub fn clear(&self) {
-        if let Ok(mut nonces) = self.nonces.write() {
-            nonces.clear();
-        }
+        self.lock().window = NonceWindow::default();
+    }
+
+    /// First bucket still inside the retention window at `now_ns`
+    fn retention_cutoff(now_ns: u64) -> u64 {
+        now_ns.saturating_sub(NONCE_RETENTION_SECS * NANOS_PER_SEC)
+            / (NONCE_BUCKET_SECS * NANOS_PER_SEC)
+    }
+
+    fn write_last_active(conn: &Connection, now_ns: u64) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO replay_state (key, value) VALUES ('last_active_ns', ?1)
+             ON CONFLICT(key) DO UPDATE SET value = MAX(value, excluded.value)",
+            params![now_ns as i64],
+        )?;
+        Ok(())
+    }
+
+    fn now_ns() -> ReplayResult<u64> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .map_err(|e| ReplayError::SystemTimeError(e.to_string()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ProtectorState> {
+        sync::lock(&self.state)
     }
 }
 
@@ -224,6 +424,20 @@ impl Default for ReplayProtector {
     }
 }
 
+impl Drop for ReplayProtector {
+    /// Record the shutdown time, below which commands are refused on restart
+    fn drop(&mut self) {
+        let Ok(now) = Self::now_ns() else {
+            return;
+        };
+        if let Some(store) = &self.lock().store {
+            if let Err(e) = Self::write_last_active(store, now) {
+                warn!("Failed to record replay protection shutdown time: {}", e);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,6 +449,17 @@ mod tests {
             .as_nanos() as u64
     }
 
+    fn temp_store_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "{}_{}_{}.db",
+            name,
+            std::process::id(),
+            current_timestamp_ns()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
     #[test]
     fn test_valid_command_accepted() {
         let protector = ReplayProtector::new();
@@ -375,4 +600,75 @@ mod tests {
         let result = protector.validate_command(device_id, near_future, "nonce-002");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_nonces_survive_crash() {
+        let path = temp_store_path("test_replay_crash");
+        // Ahead of the clock, so it still postdates the last recorded activity
+        let timestamp = current_timestamp_ns() + 10 * NANOS_PER_SEC;
+
+        let protector = ReplayProtector::open(&path).unwrap();
+        protector
+            .validate_command("device-001", timestamp, "nonce-001")
+            .unwrap();
+        // Crash: no shutdown is recorded
+        std::mem::forget(protector);
+
+        let protector = ReplayProtector::open(&path).unwrap();
+        assert_eq!(protector.nonce_count("device-001"), 1);
+        let result = protector.validate_command("device-001", timestamp, "nonce-001");
+        assert!(matches!(result, Err(ReplayError::DuplicateNonce(_))));
+        assert!(protector
+            .validate_command("device-001", timestamp, "nonce-002")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_commands_before_shutdown_rejected() {
+        let path = temp_store_path("test_replay_shutdown");
+        let issued = current_timestamp_ns();
+        drop(ReplayProtector::open(&path).unwrap());
+
+        // Issued before the shutdown, never seen: refused all the same
+        let protector = ReplayProtector::open(&path).unwrap();
+        let result = protector.validate_command("device-001", issued, "nonce-001");
+        assert!(matches!(result, Err(ReplayError::PredatesShutdown(_, _))));
+        assert!(protector
+            .validate_command("device-001", current_timestamp_ns(), "nonce-001")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_expired_buckets_pruned() {
+        let path = temp_store_path("test_replay_prune");
+        let protector = ReplayProtector::open(&path).unwrap();
+        let now = current_timestamp_ns();
+        let bucket = now / (NONCE_BUCKET_SECS * NANOS_PER_SEC);
+        {
+            let mut state = protector.lock();
+            state.window.insert("device-001", "expired", bucket - 20);
+            state
+                .store
+                .as_ref()
+                .unwrap()
+                .execute(
+                    "INSERT INTO replay_nonces VALUES ('device-001', 'expired', 0, ?1)",
+                    params![(bucket - 20) as i64],
+                )
+                .unwrap();
+        }
+        protector
+            .validate_command("device-001", now, "nonce-001")
+            .unwrap();
+        assert_eq!(protector.nonce_count("device-001"), 1);
+
+        let stored: i64 = protector
+            .lock()
+            .store
+            .as_ref()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM replay_nonces", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, 1);
+    }
 }
@@ -0,0 +1,726 @@
+//! Scheduled and conditional command execution
+//!
+//! A scheduled command is an authorized unit or swarm command held until its
+//! `Trigger` fires: at a time, when a unit enters a geofence, when a unit's
+//! telemetry passes a threshold, or when a unit falls silent. Triggers on
+//! unit reports are checked as reports are passed to `observe`; time and
+//! link-loss triggers on every `tick`, which `run` calls periodically.
+//!
+//! Pending commands are kept in a `ScheduleStore` and survive restarts. A
+//! command fires at most once: it leaves the store, its firing is recorded
+//! in the Truth-Chain, and it is dispatched with its schedule ID as command
+//! ID, so its delivery can be followed like that of any other command.
+
+mod store;
+mod trigger;
+
+pub use store::ScheduleStore;
+pub use trigger::{Comparison, Observation, Trigger};
+
+use crate::authority::AuthoritySignature;
+use crate::command_types::{SwarmCommand, UnitCommand};
+use crate::dispatcher::CommandDispatcher;
+use crate::feeds::{CommandState, CommandStatusEvent};
+use crate::ledger::{CommandRecord, TruthChainRecorder};
+use crate::quorum::{CommandScope, QuorumError, QuorumGate, QuorumProof};
+use aethercore_core::sync;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+/// Scheduler errors
+#[derive(Debug, Error)]
+pub enum SchedulerError {
+    /// Schedule store operation failed
+    #[error("Database error: {0}")]
+    DatabaseError(
+        /// Database error details
+        #[from]
+        rusqlite::Error,
+    ),
+
+    /// Stored command could not be encoded or decoded
+    #[error("Serialization error: {0}")]
+    SerializationError(
+        /// Serialization error details
+        String,
+    ),
+
+    /// Schedule can never fire or targets nothing
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(
+        /// Reason the schedule is invalid
+        String,
+    ),
+
+    /// A command is already scheduled under this identifier
+    #[error("Schedule already pending: {0}")]
+    DuplicateSchedule(
+        /// Schedule identifier
+        String,
+    ),
+
+    /// No command is scheduled under this identifier
+    #[error("No pending schedule: {0}")]
+    UnknownSchedule(
+        /// Schedule identifier
+        String,
+    ),
+}
+
+/// Command a schedule dispatches when it fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScheduledAction {
+    /// Command for a single unit
+    Unit {
+        /// Target unit
+        unit_id: String,
+        /// Command to execute
+        command: UnitCommand,
+    },
+    /// Command for a swarm
+    Swarm {
+        /// Target units
+        target_unit_ids: Vec<String>,
+        /// Command to execute
+        command: SwarmCommand,
+    },
+}
+
+impl ScheduledAction {
+    /// Units the command is sent to
+    pub fn target_units(&self) -> Vec<String> {
+        match self {
+            ScheduledAction::Unit { unit_id, .. } => vec![unit_id.clone()],
+            ScheduledAction::Swarm {
+                target_unit_ids, ..
+            } => target_unit_ids.clone(),
+        }
+    }
+
+    /// Scope the command would have if executed directly
+    pub fn scope(&self) -> CommandScope {
+        match self {
+            ScheduledAction::Unit { command, .. } => QuorumGate::classify_unit_command(command),
+            ScheduledAction::Swarm {
+                target_unit_ids,
+                command,
+            } => QuorumGate::classify_swarm_command(command, target_unit_ids.len()),
+        }
+    }
+
+    /// Verify `signatures` over `hash` meet the quorum the command would
+    /// need if executed directly
+    pub fn verify_quorum(
+        &self,
+        gate: &QuorumGate,
+        hash: &[u8; 32],
+        signatures: &[AuthoritySignature],
+    ) -> Result<(), QuorumError> {
+        match self {
+            ScheduledAction::Unit { command, .. } => {
+                gate.verify_unit_command(command, hash, signatures)
+            }
+            ScheduledAction::Swarm {
+                target_unit_ids,
+                command,
+            } => gate.verify_swarm_command(command, target_unit_ids.len(), hash, signatures),
+        }
+    }
+}
+
+/// What to execute, and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandSchedule {
+    /// Condition that fires the command
+    pub trigger: Trigger,
+    /// Command to execute
+    pub action: ScheduledAction,
+}
+
+impl CommandSchedule {
+    /// Check that the schedule can fire and targets at least one unit
+    pub fn validate(&self) -> Result<(), SchedulerError> {
+        self.trigger
+            .validate()
+            .map_err(SchedulerError::InvalidSchedule)?;
+        let targets = self.action.target_units();
+        if targets.is_empty() || targets.iter().any(String::is_empty) {
+            return Err(SchedulerError::InvalidSchedule(
+                "Command must target at least one unit".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Authorized command awaiting its trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledCommand {
+    /// Schedule identifier, used as command ID once fired
+    pub schedule_id: String,
+    /// Trigger and command
+    pub schedule: CommandSchedule,
+    /// Proof the schedule met the command's quorum
+    pub quorum_proof: QuorumProof,
+    /// Device that scheduled the command
+    pub device_id: String,
+    /// Device signature over the schedule request, carried into the
+    /// Truth-Chain record of the firing
+    pub device_signature: Vec<u8>,
+    /// When the command was scheduled (nanoseconds since epoch)
+    pub created_ns: u64,
+}
+
+struct SchedulerState {
+    pending: BTreeMap<String, ScheduledCommand>,
+    /// When each unit was last heard from
+    last_contact: HashMap<String, u64>,
+    store: ScheduleStore,
+}
+
+/// Holds scheduled commands and dispatches them when their triggers fire
+pub struct CommandScheduler {
+    dispatcher: Arc<CommandDispatcher>,
+    truth_chain: Option<Arc<Mutex<TruthChainRecorder>>>,
+    state: Mutex<SchedulerState>,
+    /// Link-loss silences count from no earlier than this
+    started_ns: u64,
+}
+
+fn current_timestamp_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64
+}
+
+impl CommandScheduler {
+    /// Create a scheduler dispatching through `dispatcher`, resuming the
+    /// commands pending in `store`
+    pub fn new(
+        dispatcher: Arc<CommandDispatcher>,
+        store: ScheduleStore,
+    ) -> Result<Self, SchedulerError> {
+        let pending: BTreeMap<_, _> = store
+            .load()?
+            .into_iter()
+            .map(|command| (command.schedule_id.clone(), command))
+            .collect();
+        if !pending.is_empty() {
+            info!(pending = pending.len(), "Resuming scheduled commands");
+        }
+        Ok(Self {
+            dispatcher,
+            truth_chain: None,
+            state: Mutex::new(SchedulerState {
+                pending,
+                last_contact: HashMap::new(),
+                store,
+            }),
+            started_ns: current_timestamp_ns(),
+        })
+    }
+
+    /// Record every firing in `recorder`
+    ///
+    /// A command whose firing cannot be recorded is not dispatched.
+    pub fn with_truth_chain(mut self, recorder: Arc<Mutex<TruthChainRecorder>>) -> Self {
+        self.truth_chain = Some(recorder);
+        self
+    }
+
+    /// Hold an authorized command until its trigger fires
+    pub fn schedule(&self, command: ScheduledCommand) -> Result<(), SchedulerError> {
+        command.schedule.validate()?;
+        let mut state = self.lock();
+        if state.pending.contains_key(&command.schedule_id) {
+            return Err(SchedulerError::DuplicateSchedule(command.schedule_id));
+        }
+        state.store.insert(&command)?;
+        info!(
+            schedule_id = %command.schedule_id,
+            device_id = %command.device_id,
+            "Command scheduled"
+        );
+        state.pending.insert(command.schedule_id.clone(), command);
+        Ok(())
+    }
+
+    /// Pending command scheduled under `schedule_id`
+    pub fn get(&self, schedule_id: &str) -> Option<ScheduledCommand> {
+        self.lock().pending.get(schedule_id).cloned()
+    }
+
+    /// Every pending command, by schedule ID
+    pub fn pending(&self) -> Vec<ScheduledCommand> {
+        self.lock().pending.values().cloned().collect()
+    }
+
+    /// Drop a pending command before it fires
+    pub fn cancel(&self, schedule_id: &str) -> Result<ScheduledCommand, SchedulerError> {
+        let mut state = self.lock();
+        if !state.pending.contains_key(schedule_id) {
+            return Err(SchedulerError::UnknownSchedule(schedule_id.to_string()));
+        }
+        state.store.remove(schedule_id)?;
+        info!(schedule_id, "Scheduled command cancelled");
+        state
+            .pending
+            .remove(schedule_id)
+            .ok_or_else(|| SchedulerError::UnknownSchedule(schedule_id.to_string()))
+    }
+
+    /// Note a report from a unit and fire the commands it triggers
+    ///
+    /// Must be called within a Tokio runtime, which fired commands are
+    /// dispatched on.
+    pub fn observe(&self, observation: Observation) {
+        let now_ns = current_timestamp_ns();
+        let mut state = self.lock();
+        state
+            .last_contact
+            .insert(observation.unit_id().to_string(), now_ns);
+
+        let due: Vec<String> = state
+            .pending
+            .values()
+            .filter(|command| command.schedule.trigger.matches(&observation))
+            .map(|command| command.schedule_id.clone())
+            .collect();
+        for schedule_id in due {
+            self.fire(&mut state, &schedule_id, now_ns, Some(&observation));
+        }
+    }
+
+    /// Fire the time and link-loss triggers due at `now_ns`
+    ///
+    /// Must be called within a Tokio runtime, which fired commands are
+    /// dispatched on.
+    pub fn tick(&self, now_ns: u64) {
+        let mut state = self.lock();
+        let due: Vec<String> = state
+            .pending
+            .values()
+            .filter(|command| {
+                let trigger = &command.schedule.trigger;
+                let last_contact_ns = trigger
+                    .unit_id()
+                    .and_then(|unit_id| state.last_contact.get(unit_id))
+                    .copied()
+                    .unwrap_or(0)
+                    .max(command.created_ns)
+                    .max(self.started_ns);
+                trigger.is_due(now_ns, last_contact_ns)
+            })
+            .map(|command| command.schedule_id.clone())
+            .collect();
+        for schedule_id in due {
+            self.fire(&mut state, &schedule_id, now_ns, None);
+        }
+    }
+
+    /// Call `tick` every `interval`, forever
+    pub async fn run(&self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.tick(current_timestamp_ns());
+        }
+    }
+
+    /// Take a command off the schedule, record its firing and dispatch it
+    fn fire(
+        &self,
+        state: &mut SchedulerState,
+        schedule_id: &str,
+        now_ns: u64,
+        observation: Option<&Observation>,
+    ) {
+        // Leave the store first: a crash from here on loses the command
+        // rather than firing it again after the restart
+        if let Err(e) = state.store.remove(schedule_id) {
+            error!(
+                schedule_id,
+                "Scheduled command held, store update failed: {}", e
+            );
+            return;
+        }
+        let Some(command) = state.pending.remove(schedule_id) else {
+            return;
+        };
+        info!(schedule_id, "Scheduled command fired");
+
+        if let Err(reason) = self.record_firing(&command, now_ns, observation) {
+            error!(schedule_id, "Scheduled command not dispatched: {}", reason);
+            self.dispatcher.command_feed().push(CommandStatusEvent {
+                command_id: command.schedule_id,
+                unit_id: None,
+                state: CommandState::Failed { reason },
+                timestamp_ns: now_ns,
+            });
+            return;
+        }
+
+        tokio::spawn(dispatch(self.dispatcher.clone(), command, now_ns));
+    }
+
+    fn record_firing(
+        &self,
+        command: &ScheduledCommand,
+        fired_ns: u64,
+        observation: Option<&Observation>,
+    ) -> Result<(), String> {
+        let Some(truth_chain) = &self.truth_chain else {
+            return Ok(());
+        };
+
+        let proof = &command.quorum_proof;
+        let record = CommandRecord::new(
+            format!("{}/fired", command.schedule_id),
+            "ScheduledCommandFired".to_string(),
+            serde_json::json!({
+                "schedule_id": command.schedule_id,
+                "schedule": command.schedule,
+                "observation": observation,
+            }),
+            proof.command_hash,
+            proof
+                .signatures
+                .iter()
+                .map(|sig| sig.authority_id.clone())
+                .collect(),
+            command.schedule.action.target_units(),
+            fired_ns,
+        )
+        .with_quorum_proof(proof.clone());
+
+        let mut recorder = truth_chain
+            .lock()
+            .map_err(|e| format!("Truth-Chain lock error: {}", e))?;
+        recorder
+            .record_command(
+                &record,
+                command.device_signature.clone(),
+                command.device_id.clone(),
+            )
+            .map_err(|e| format!("Truth-Chain recording failed: {}", e))?;
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SchedulerState> {
+        sync::lock(&self.state)
+    }
+}
+
+/// Dispatch a fired command under its schedule ID
+async fn dispatch(
+    dispatcher: Arc<CommandDispatcher>,
+    command: ScheduledCommand,
+    timestamp_ns: u64,
+) {
+    let schedule_id = command.schedule_id;
+    match command.schedule.action {
+        ScheduledAction::Unit { unit_id, command } => {
+            match dispatcher
+                .dispatch_unit_command(&schedule_id, &unit_id, &command, timestamp_ns)
+                .await
+            {
+                Ok(result) => info!(
+                    schedule_id,
+                    unit_id,
+                    success = result.is_success(),
+                    "Scheduled unit command dispatched"
+                ),
+                Err(e) => warn!(
+                    schedule_id,
+                    unit_id, "Scheduled unit command dispatch failed: {}", e
+                ),
+            }
+        }
+        ScheduledAction::Swarm {
+            target_unit_ids,
+            command,
+        } => {
+            match dispatcher
+                .dispatch_swarm_command(
+                    schedule_id.clone(),
+                    &command,
+                    &target_unit_ids,
+                    timestamp_ns,
+                )
+                .await
+            {
+                Ok(status) => info!(
+                    schedule_id,
+                    success_count = status.success_count,
+                    total_units = status.total_units,
+                    "Scheduled swarm command dispatched"
+                ),
+                Err(e) => warn!(
+                    schedule_id,
+                    "Scheduled swarm command dispatch failed: {}", e
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_types::{Coordinate, GeoBoundary};
+    use crate::unit_link::{InMemoryUnitLink, UnitBehavior};
+    use ed25519_dalek::SigningKey;
+    use std::path::PathBuf;
+
+    const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+    fn create_test_dispatcher() -> (Arc<CommandDispatcher>, Arc<InMemoryUnitLink>) {
+        let router_key = SigningKey::from_bytes(&[20u8; 32]);
+        let dispatcher = CommandDispatcher::new();
+        let link = Arc::new(InMemoryUnitLink::new(
+            dispatcher.reply_sink(),
+            router_key.verifying_key().to_bytes(),
+        ));
+        for (unit_id, seed) in [("unit-1", 21u8), ("unit-2", 22u8)] {
+            let key = SigningKey::from_bytes(&[seed; 32]);
+            dispatcher.register_unit_key(unit_id, key.verifying_key().to_bytes());
+            link.add_unit(unit_id, key, UnitBehavior::Ack);
+        }
+        let dispatcher = dispatcher
+            .with_unit_link(link.clone())
+            .with_router_key(router_key);
+        (Arc::new(dispatcher), link)
+    }
+
+    fn temp_db_path(prefix: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "{}_{}_{}.db",
+            prefix,
+            std::process::id(),
+            current_timestamp_ns()
+        ))
+    }
+
+    fn scheduled(schedule_id: &str, trigger: Trigger) -> ScheduledCommand {
+        ScheduledCommand {
+            schedule_id: schedule_id.to_string(),
+            schedule: CommandSchedule {
+                trigger,
+                action: ScheduledAction::Unit {
+                    unit_id: "unit-1".to_string(),
+                    command: UnitCommand::Loiter {
+                        duration_secs: Some(60),
+                        radius_m: None,
+                    },
+                },
+            },
+            quorum_proof: QuorumProof::new([7u8; 32], vec![], CommandScope::SingleUnitNormal, 0),
+            device_id: "device-1".to_string(),
+            device_signature: vec![0u8; 64],
+            created_ns: current_timestamp_ns(),
+        }
+    }
+
+    /// Wait for the spawned dispatches to reach `unit_id` `count` times
+    async fn wait_executed(link: &InMemoryUnitLink, unit_id: &str, count: usize) {
+        for _ in 0..100 {
+            if link.executed(unit_id) >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(link.executed(unit_id), count);
+    }
+
+    #[tokio::test]
+    async fn test_time_trigger_fires_once() {
+        let ledger_path = temp_db_path("c2_router_schedule_ledger");
+        let recorder = TruthChainRecorder::new(ledger_path.clone(), "c2-1".to_string()).unwrap();
+        let truth_chain = Arc::new(Mutex::new(recorder));
+        let (dispatcher, link) = create_test_dispatcher();
+        let scheduler =
+            CommandScheduler::new(dispatcher.clone(), ScheduleStore::in_memory().unwrap())
+                .unwrap()
+                .with_truth_chain(truth_chain.clone());
+
+        let now = current_timestamp_ns();
+        let fire_at = now + 3600 * NANOS_PER_SEC;
+        scheduler
+            .schedule(scheduled("sched-1", Trigger::At { time_ns: fire_at }))
+            .unwrap();
+
+        scheduler.tick(now);
+        assert_eq!(scheduler.pending().len(), 1);
+        assert!(truth_chain.lock().unwrap().get_last_event_hash().is_none());
+
+        scheduler.tick(fire_at);
+        assert!(scheduler.pending().is_empty());
+        assert!(truth_chain.lock().unwrap().get_last_event_hash().is_some());
+        wait_executed(&link, "unit-1", 1).await;
+
+        scheduler.tick(fire_at + NANOS_PER_SEC);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(link.executed("unit-1"), 1);
+
+        // Delivery is reported under the schedule ID
+        let feed = dispatcher.command_feed();
+        assert_eq!(
+            feed.read().command_status("sched-1").unwrap().status,
+            "completed"
+        );
+        let _ = std::fs::remove_file(&ledger_path);
+    }
+
+    #[tokio::test]
+    async fn test_condition_triggers() {
+        let (dispatcher, link) = create_test_dispatcher();
+        let scheduler =
+            CommandScheduler::new(dispatcher, ScheduleStore::in_memory().unwrap()).unwrap();
+        let corner = |lat, lon| Coordinate {
+            lat,
+            lon,
+            alt: None,
+        };
+        scheduler
+            .schedule(scheduled(
+                "geofence",
+                Trigger::GeofenceEntry {
+                    unit_id: "unit-2".to_string(),
+                    boundary: GeoBoundary {
+                        vertices: vec![
+                            corner(45.0, -122.0),
+                            corner(45.0, -121.0),
+                            corner(46.0, -121.0),
+                            corner(46.0, -122.0),
+                        ],
+                    },
+                },
+            ))
+            .unwrap();
+        scheduler
+            .schedule(scheduled(
+                "low-fuel",
+                Trigger::TelemetryThreshold {
+                    unit_id: "unit-2".to_string(),
+                    metric: "fuel_percent".to_string(),
+                    comparison: Comparison::Below,
+                    threshold: 20.0,
+                },
+            ))
+            .unwrap();
+
+        let position = |lat, lon| Observation::Position {
+            unit_id: "unit-2".to_string(),
+            lat,
+            lon,
+        };
+        let fuel = |value| Observation::Telemetry {
+            unit_id: "unit-2".to_string(),
+            metric: "fuel_percent".to_string(),
+            value,
+        };
+
+        scheduler.observe(position(44.5, -121.5));
+        scheduler.observe(fuel(50.0));
+        assert_eq!(scheduler.pending().len(), 2);
+
+        scheduler.observe(position(45.5, -121.5));
+        assert!(scheduler.get("geofence").is_none());
+        assert!(scheduler.get("low-fuel").is_some());
+        wait_executed(&link, "unit-1", 1).await;
+
+        scheduler.observe(fuel(15.0));
+        assert!(scheduler.pending().is_empty());
+        wait_executed(&link, "unit-1", 2).await;
+    }
+
+    #[tokio::test]
+    async fn test_link_lost_trigger() {
+        let (dispatcher, link) = create_test_dispatcher();
+        let scheduler =
+            CommandScheduler::new(dispatcher, ScheduleStore::in_memory().unwrap()).unwrap();
+        scheduler
+            .schedule(scheduled(
+                "rtb",
+                Trigger::LinkLost {
+                    unit_id: "unit-2".to_string(),
+                    silence_secs: 60,
+                },
+            ))
+            .unwrap();
+
+        // Hearing from another unit does not count
+        scheduler.observe(Observation::Contact {
+            unit_id: "unit-1".to_string(),
+        });
+        let last_contact = current_timestamp_ns();
+        scheduler.observe(Observation::Contact {
+            unit_id: "unit-2".to_string(),
+        });
+        scheduler.tick(last_contact + 30 * NANOS_PER_SEC);
+        assert_eq!(scheduler.pending().len(), 1);
+
+        scheduler.tick(last_contact + 120 * NANOS_PER_SEC);
+        assert!(scheduler.pending().is_empty());
+        wait_executed(&link, "unit-1", 1).await;
+    }
+
+    #[tokio::test]
+    async fn test_schedules_survive_restart() {
+        let store_path = temp_db_path("c2_router_schedules");
+        let (dispatcher, _link) = create_test_dispatcher();
+        let far_future = Trigger::At { time_ns: u64::MAX };
+
+        let scheduler = CommandScheduler::new(
+            dispatcher.clone(),
+            ScheduleStore::open(&store_path).unwrap(),
+        )
+        .unwrap();
+        scheduler
+            .schedule(scheduled("sched-1", far_future.clone()))
+            .unwrap();
+        scheduler
+            .schedule(scheduled("sched-2", far_future.clone()))
+            .unwrap();
+        assert!(matches!(
+            scheduler.schedule(scheduled("sched-1", far_future.clone())),
+            Err(SchedulerError::DuplicateSchedule(_))
+        ));
+        assert!(matches!(
+            scheduler.schedule(scheduled(
+                "sched-3",
+                Trigger::LinkLost {
+                    unit_id: "unit-1".to_string(),
+                    silence_secs: 0,
+                },
+            )),
+            Err(SchedulerError::InvalidSchedule(_))
+        ));
+        drop(scheduler);
+
+        let scheduler = CommandScheduler::new(
+            dispatcher.clone(),
+            ScheduleStore::open(&store_path).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(scheduler.pending().len(), 2);
+        assert_eq!(scheduler.cancel("sched-1").unwrap().schedule_id, "sched-1");
+        assert!(matches!(
+            scheduler.cancel("sched-1"),
+            Err(SchedulerError::UnknownSchedule(_))
+        ));
+        drop(scheduler);
+
+        let scheduler =
+            CommandScheduler::new(dispatcher, ScheduleStore::open(&store_path).unwrap()).unwrap();
+        let pending = scheduler.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].schedule_id, "sched-2");
+        let _ = std::fs::remove_file(&store_path);
+    }
+}
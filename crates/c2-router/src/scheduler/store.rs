@@ -0,0 +1,79 @@
+//! Storage for scheduled commands awaiting their trigger
+
+use super::{ScheduledCommand, SchedulerError};
+use rusqlite::{params, Connection, OpenFlags};
+use std::path::Path;
+use tracing::info;
+
+/// SQLite table of pending scheduled commands
+///
+/// A command is removed from the store before it fires, so a restart never
+/// fires it a second time.
+pub struct ScheduleStore {
+    conn: Connection,
+}
+
+impl ScheduleStore {
+    /// Open the store at `path`, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SchedulerError> {
+        let path = path.as_ref();
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+        )?;
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=FULL;")?;
+        info!(path = %path.display(), "Schedule store opened");
+        Self::init(conn)
+    }
+
+    /// Store that forgets everything when dropped
+    pub fn in_memory() -> Result<Self, SchedulerError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, SchedulerError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS scheduled_commands (
+                schedule_id TEXT PRIMARY KEY,
+                command_json TEXT NOT NULL,
+                created_ns INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Every pending command, oldest first
+    pub(crate) fn load(&self) -> Result<Vec<ScheduledCommand>, SchedulerError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT command_json FROM scheduled_commands ORDER BY created_ns")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut commands = Vec::new();
+        for row in rows {
+            let command = serde_json::from_str(&row?)
+                .map_err(|e| SchedulerError::SerializationError(e.to_string()))?;
+            commands.push(command);
+        }
+        Ok(commands)
+    }
+
+    pub(crate) fn insert(&self, command: &ScheduledCommand) -> Result<(), SchedulerError> {
+        let command_json = serde_json::to_string(command)
+            .map_err(|e| SchedulerError::SerializationError(e.to_string()))?;
+        self.conn.execute(
+            "INSERT INTO scheduled_commands (schedule_id, command_json, created_ns)
+             VALUES (?1, ?2, ?3)",
+            params![command.schedule_id, command_json, command.created_ns as i64],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn remove(&self, schedule_id: &str) -> Result<(), SchedulerError> {
+        self.conn.execute(
+            "DELETE FROM scheduled_commands WHERE schedule_id = ?1",
+            params![schedule_id],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! Conditions scheduled commands wait for
+
+use crate::command_types::GeoBoundary;
+use serde::{Deserialize, Serialize};
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Side of a threshold a telemetry value must reach
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    /// Value above the threshold
+    Above,
+    /// Value below the threshold
+    Below,
+}
+
+/// Condition that fires a scheduled command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Trigger {
+    /// Fire at a time
+    At {
+        /// Firing time (nanoseconds since epoch)
+        time_ns: u64,
+    },
+    /// Fire when the unit reports a position inside the boundary
+    GeofenceEntry {
+        /// Unit watched
+        unit_id: String,
+        /// Boundary the unit must enter
+        boundary: GeoBoundary,
+    },
+    /// Fire when the unit reports a telemetry value past the threshold
+    TelemetryThreshold {
+        /// Unit watched
+        unit_id: String,
+        /// Telemetry metric name, such as "fuel_percent"
+        metric: String,
+        /// Side of the threshold that fires
+        comparison: Comparison,
+        /// Threshold value
+        threshold: f64,
+    },
+    /// Fire when nothing is heard from the unit for a while
+    LinkLost {
+        /// Unit watched
+        unit_id: String,
+        /// Silence that counts as lost link, in seconds
+        silence_secs: u64,
+    },
+}
+
+impl Trigger {
+    /// Check that the trigger can ever fire
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Trigger::At { .. } => Ok(()),
+            Trigger::GeofenceEntry { boundary, .. } if boundary.vertices.len() < 3 => {
+                Err("Geofence boundary needs at least 3 vertices".to_string())
+            }
+            Trigger::TelemetryThreshold { metric, .. } if metric.is_empty() => {
+                Err("Telemetry metric must not be empty".to_string())
+            }
+            Trigger::TelemetryThreshold { threshold, .. } if !threshold.is_finite() => {
+                Err("Telemetry threshold must be finite".to_string())
+            }
+            Trigger::LinkLost {
+                silence_secs: 0, ..
+            } => Err("Link loss silence must be at least 1 second".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Unit the trigger watches, if any
+    pub fn unit_id(&self) -> Option<&str> {
+        match self {
+            Trigger::At { .. } => None,
+            Trigger::GeofenceEntry { unit_id, .. }
+            | Trigger::TelemetryThreshold { unit_id, .. }
+            | Trigger::LinkLost { unit_id, .. } => Some(unit_id),
+        }
+    }
+
+    /// Whether an observation meets the trigger
+    pub fn matches(&self, observation: &Observation) -> bool {
+        match (self, observation) {
+            (
+                Trigger::GeofenceEntry { unit_id, boundary },
+                Observation::Position {
+                    unit_id: observed,
+                    lat,
+                    lon,
+                },
+            ) => unit_id == observed && boundary.contains(*lat, *lon),
+            (
+                Trigger::TelemetryThreshold {
+                    unit_id,
+                    metric,
+                    comparison,
+                    threshold,
+                },
+                Observation::Telemetry {
+                    unit_id: observed,
+                    metric: observed_metric,
+                    value,
+                },
+            ) => {
+                unit_id == observed
+                    && metric == observed_metric
+                    && match comparison {
+                        Comparison::Above => value > threshold,
+                        Comparison::Below => value < threshold,
+                    }
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the trigger is due at `now_ns`, for a watched unit last heard
+    /// from at `last_contact_ns`
+    pub fn is_due(&self, now_ns: u64, last_contact_ns: u64) -> bool {
+        match self {
+            Trigger::At { time_ns } => now_ns >= *time_ns,
+            Trigger::LinkLost { silence_secs, .. } => {
+                now_ns.saturating_sub(last_contact_ns) >= silence_secs * NANOS_PER_SEC
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Report about a unit, checked against pending triggers
+///
+/// Every observation also counts as contact with its unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Observation {
+    /// Unit reported its position
+    Position {
+        /// Reporting unit
+        unit_id: String,
+        /// Latitude in decimal degrees
+        lat: f64,
+        /// Longitude in decimal degrees
+        lon: f64,
+    },
+    /// Unit reported a telemetry value
+    Telemetry {
+        /// Reporting unit
+        unit_id: String,
+        /// Metric name
+        metric: String,
+        /// Reported value
+        value: f64,
+    },
+    /// Unit was heard from
+    Contact {
+        /// Unit heard from
+        unit_id: String,
+    },
+}
+
+impl Observation {
+    /// Unit the observation is about
+    pub fn unit_id(&self) -> &str {
+        match self {
+            Observation::Position { unit_id, .. }
+            | Observation::Telemetry { unit_id, .. }
+            | Observation::Contact { unit_id } => unit_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telemetry_threshold() {
+        let trigger = Trigger::TelemetryThreshold {
+            unit_id: "unit-1".to_string(),
+            metric: "fuel_percent".to_string(),
+            comparison: Comparison::Below,
+            threshold: 20.0,
+        };
+        let telemetry = |unit_id: &str, metric: &str, value| Observation::Telemetry {
+            unit_id: unit_id.to_string(),
+            metric: metric.to_string(),
+            value,
+        };
+
+        assert!(trigger.matches(&telemetry("unit-1", "fuel_percent", 15.0)));
+        assert!(!trigger.matches(&telemetry("unit-1", "fuel_percent", 20.0)));
+        assert!(!trigger.matches(&telemetry("unit-1", "battery_percent", 15.0)));
+        assert!(!trigger.matches(&telemetry("unit-2", "fuel_percent", 15.0)));
+        assert!(!trigger.is_due(u64::MAX, 0));
+    }
+
+    #[test]
+    fn test_validate() {
+        let link_lost = |silence_secs| Trigger::LinkLost {
+            unit_id: "unit-1".to_string(),
+            silence_secs,
+        };
+        assert!(link_lost(30).validate().is_ok());
+        assert!(link_lost(0).validate().is_err());
+        assert!(Trigger::GeofenceEntry {
+            unit_id: "unit-1".to_string(),
+            boundary: GeoBoundary { vertices: vec![] },
+        }
+        .validate()
+        .is_err());
+    }
+}
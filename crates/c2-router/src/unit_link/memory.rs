@@ -0,0 +1,155 @@
+//! In-memory unit link with simulated units
+//!
+//! Each simulated unit checks messages against the router's public key and
+//! its own ID, as a real unit must, then answers commands immediately according to its
+//! `UnitBehavior`, signing its replies with its own key. Everything sent
+//! over the link is kept for inspection.
+
+use super::{LinkError, LinkMessage, ReplyKind, ReplySink, UnitLink, UnitReply};
+use aethercore_core::sync::lock;
+use ed25519_dalek::SigningKey;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How a simulated unit answers commands
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitBehavior {
+    /// Acknowledge every command
+    Ack,
+    /// Report progress at each percentage, then acknowledge
+    Progress(Vec<f32>),
+    /// Refuse every command
+    Nack(String),
+    /// Ignore the first deliveries of each command, then acknowledge
+    DropFirst(u32),
+    /// Report that the command started, and never finish
+    Hold,
+    /// Never answer
+    Silent,
+}
+
+struct SimulatedUnit {
+    key: SigningKey,
+    behavior: UnitBehavior,
+    deliveries: HashMap<String, u32>,
+    executed: HashSet<String>,
+}
+
+/// Link to simulated units, for tests
+pub struct InMemoryUnitLink {
+    replies: ReplySink,
+    router_public_key: [u8; 32],
+    units: Mutex<HashMap<String, SimulatedUnit>>,
+    sent: Mutex<Vec<(String, LinkMessage)>>,
+}
+
+impl InMemoryUnitLink {
+    /// Create a link with no units, answering into `replies`
+    ///
+    /// Units ignore messages not signed with the key of `router_public_key`,
+    /// or not addressed to them.
+    pub fn new(replies: ReplySink, router_public_key: [u8; 32]) -> Self {
+        Self {
+            replies,
+            router_public_key,
+            units: Mutex::new(HashMap::new()),
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a unit signing its replies with `key`
+    pub fn add_unit(&self, unit_id: &str, key: SigningKey, behavior: UnitBehavior) {
+        lock(&self.units).insert(
+            unit_id.to_string(),
+            SimulatedUnit {
+                key,
+                behavior,
+                deliveries: HashMap::new(),
+                executed: HashSet::new(),
+            },
+        );
+    }
+
+    /// Change how a unit answers from now on
+    pub fn set_behavior(&self, unit_id: &str, behavior: UnitBehavior) {
+        if let Some(unit) = lock(&self.units).get_mut(unit_id) {
+            unit.behavior = behavior;
+        }
+    }
+
+    /// Every message sent, with the unit it was sent to
+    pub fn sent(&self) -> Vec<(String, LinkMessage)> {
+        lock(&self.sent).clone()
+    }
+
+    /// Number of distinct commands a unit has acted on
+    pub fn executed(&self, unit_id: &str) -> usize {
+        lock(&self.units)
+            .get(unit_id)
+            .map_or(0, |unit| unit.executed.len())
+    }
+}
+
+fn current_timestamp_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64
+}
+
+#[tonic::async_trait]
+impl UnitLink for InMemoryUnitLink {
+    async fn send(&self, unit_id: &str, message: &LinkMessage) -> Result<(), LinkError> {
+        let mut units = lock(&self.units);
+        let unit = units
+            .get_mut(unit_id)
+            .ok_or_else(|| LinkError::Unreachable(unit_id.to_string()))?;
+        lock(&self.sent).push((unit_id.to_string(), message.clone()));
+        if !message.verify(&self.router_public_key, unit_id) {
+            return Ok(());
+        }
+
+        let LinkMessage::Command(envelope) = message else {
+            return Ok(());
+        };
+        let deliveries = unit
+            .deliveries
+            .entry(envelope.command_id.clone())
+            .or_default();
+        *deliveries += 1;
+
+        let kinds = match &unit.behavior {
+            UnitBehavior::Ack => vec![ReplyKind::Ack],
+            UnitBehavior::Progress(steps) => steps
+                .iter()
+                .map(|&percent| ReplyKind::Progress { percent })
+                .chain([ReplyKind::Ack])
+                .collect(),
+            UnitBehavior::Nack(reason) => vec![ReplyKind::Nack {
+                reason: reason.clone(),
+            }],
+            UnitBehavior::DropFirst(count) if *deliveries <= *count => return Ok(()),
+            UnitBehavior::DropFirst(_) => vec![ReplyKind::Ack],
+            UnitBehavior::Hold => vec![ReplyKind::Progress { percent: 0.0 }],
+            UnitBehavior::Silent => return Ok(()),
+        };
+        if kinds
+            .iter()
+            .any(|kind| !matches!(kind, ReplyKind::Nack { .. }))
+        {
+            unit.executed.insert(envelope.command_id.clone());
+        }
+        for kind in kinds {
+            self.replies.deliver(UnitReply::sign(
+                envelope.command_id.clone(),
+                unit_id,
+                kind,
+                envelope.dispatched_ns,
+                current_timestamp_ns(),
+                &unit.key,
+            ));
+        }
+        Ok(())
+    }
+}
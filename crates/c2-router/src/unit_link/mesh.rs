@@ -0,0 +1,159 @@
+//! Unit link over a mesh transport
+//!
+//! Link messages and unit replies travel as JSON, one per frame. A reply is
+//! only accepted from the address registered for the unit it names, on top
+//! of the signature check the dispatcher makes.
+
+use super::{LinkError, LinkMessage, ReplySink, UnitLink, UnitReply};
+use aethercore_core::sync;
+use aethercore_mesh::{MeshResult, MeshTransport};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, warn};
+
+/// Link to units reachable over a `MeshTransport`
+pub struct MeshUnitLink<T> {
+    transport: Arc<T>,
+    addresses: RwLock<HashMap<String, String>>,
+    replies: ReplySink,
+}
+
+impl<T: MeshTransport> MeshUnitLink<T> {
+    /// Create a link sending over `transport` and answering into `replies`
+    ///
+    /// Replies are only received while `run` is being polled.
+    pub fn new(transport: Arc<T>, replies: ReplySink) -> Self {
+        Self {
+            transport,
+            addresses: RwLock::new(HashMap::new()),
+            replies,
+        }
+    }
+
+    /// Set the transport address `unit_id` is reached at
+    pub fn set_address(&self, unit_id: &str, address: impl Into<String>) {
+        sync::write(&self.addresses).insert(unit_id.to_string(), address.into());
+    }
+
+    /// Forget a unit; it is unreachable from now on
+    pub fn remove_unit(&self, unit_id: &str) {
+        sync::write(&self.addresses).remove(unit_id);
+    }
+
+    fn address_of(&self, unit_id: &str) -> Option<String> {
+        sync::read(&self.addresses).get(unit_id).cloned()
+    }
+
+    /// Receive unit replies until the transport fails
+    pub async fn run(&self) -> MeshResult<()> {
+        loop {
+            let (address, frame) = self.transport.recv().await?;
+            let reply: UnitReply = match serde_json::from_slice(&frame) {
+                Ok(reply) => reply,
+                Err(e) => {
+                    debug!(%address, "Dropping frame that is not a unit reply: {}", e);
+                    continue;
+                }
+            };
+            if self.address_of(&reply.unit_id).as_deref() != Some(address.as_str()) {
+                warn!(
+                    %address,
+                    unit_id = %reply.unit_id,
+                    "Dropping reply from an address not registered for the unit"
+                );
+                continue;
+            }
+            self.replies.deliver(reply);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<T: MeshTransport> UnitLink for MeshUnitLink<T> {
+    async fn send(&self, unit_id: &str, message: &LinkMessage) -> Result<(), LinkError> {
+        let address = self
+            .address_of(unit_id)
+            .ok_or_else(|| LinkError::Unreachable(unit_id.to_string()))?;
+        let frame =
+            serde_json::to_vec(message).map_err(|e| LinkError::Serialization(e.to_string()))?;
+        self.transport
+            .send_to(&address, &frame)
+            .await
+            .map_err(|e| LinkError::Transport(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_types::UnitCommand;
+    use crate::unit_link::{CommandEnvelope, CommandPayload, ReplyKind};
+    use aethercore_mesh::LoopbackNetwork;
+    use ed25519_dalek::SigningKey;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_command_and_reply_over_mesh() {
+        let network = LoopbackNetwork::new();
+        let sink = ReplySink::new();
+        let link = Arc::new(MeshUnitLink::new(
+            Arc::new(network.attach("c2")),
+            sink.clone(),
+        ));
+        link.set_address("unit-1", "unit-1-radio");
+        let unit = network.attach("unit-1-radio");
+        let rogue = network.attach("rogue");
+        let receiver = tokio::spawn({
+            let link = link.clone();
+            async move { link.run().await }
+        });
+
+        let mut waiter = sink.wait_for("cmd-1", "unit-1").unwrap();
+        let message = LinkMessage::Command(CommandEnvelope::sign(
+            "cmd-1",
+            "unit-1",
+            CommandPayload::Unit(UnitCommand::Reboot { delay_secs: 5 }),
+            1,
+            1000,
+            1000,
+            &SigningKey::from_bytes(&[6u8; 32]),
+        ));
+        link.send("unit-1", &message).await.unwrap();
+        assert!(matches!(
+            link.send("unit-2", &message).await,
+            Err(LinkError::Unreachable(_))
+        ));
+
+        let (from, frame) = unit.recv().await.unwrap();
+        assert_eq!(from, "c2");
+        assert_eq!(
+            serde_json::from_slice::<LinkMessage>(&frame).unwrap(),
+            message
+        );
+
+        // A reply naming the unit from another address is dropped
+        let key = SigningKey::from_bytes(&[5u8; 32]);
+        let forged = UnitReply::sign("cmd-1", "unit-1", ReplyKind::Ack, 1000, 2000, &key);
+        rogue
+            .send_to("c2", &serde_json::to_vec(&forged).unwrap())
+            .await
+            .unwrap();
+        let reply = UnitReply::sign(
+            "cmd-1",
+            "unit-1",
+            ReplyKind::Progress { percent: 50.0 },
+            1000,
+            3000,
+            &key,
+        );
+        unit.send_to("c2", &serde_json::to_vec(&reply).unwrap())
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), waiter.recv())
+            .await
+            .unwrap();
+        assert_eq!(received, reply);
+        receiver.abort();
+    }
+}
@@ -0,0 +1,527 @@
+//! Links between the C2 router and units
+//!
+//! A `UnitLink` carries `LinkMessage`s from the dispatcher to units. Units
+//! answer each command with signed `UnitReply`s, which the link hands to the
+//! dispatcher's `ReplySink`: any number of progress reports while the
+//! command runs, then an ACK or NACK.
+//!
+//! Replies carry the `dispatched_ns` of the envelope they answer, so an
+//! answer to one dispatch cannot be passed off as an answer to another.
+//!
+//! Every `LinkMessage` is signed by the router and names the unit it is for.
+//! A unit must check it with `LinkMessage::verify` against the router's
+//! public key, provisioned with the unit, and its own unit ID, and drop
+//! messages that fail without acting on or answering them. A message
+//! forwarded to a unit it does not name is refused like a forged one.
+//!
+//! Commands that go unanswered are sent again with the same command ID and
+//! a higher attempt number, so a unit must treat a command ID it has already
+//! seen as a duplicate and answer it again without executing it twice.
+//!
+//! Links provided:
+//! - `MeshUnitLink` over any `MeshTransport`
+//! - `InMemoryUnitLink`, whose simulated units answer as told, for tests
+
+#![warn(missing_docs)]
+
+mod memory;
+mod mesh;
+
+pub use memory::{InMemoryUnitLink, UnitBehavior};
+pub use mesh::MeshUnitLink;
+
+use crate::command_types::{SwarmCommand, UnitCommand};
+use crate::quorum::hash_field;
+use aethercore_core::sync;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+/// Unit link errors
+#[derive(Debug, Error)]
+pub enum LinkError {
+    /// No route to the unit
+    #[error("Unit unreachable: {0}")]
+    Unreachable(String),
+
+    /// Transport failed to send
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    /// Message could not be encoded
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+}
+
+/// Command carried to a unit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CommandPayload {
+    /// Command for this unit alone
+    Unit(UnitCommand),
+    /// This unit's part in a swarm command
+    Swarm(SwarmCommand),
+}
+
+/// Command as sent to a unit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandEnvelope {
+    /// Command identifier, unchanged across resends
+    pub command_id: String,
+    /// Unit the command is for
+    pub unit_id: String,
+    /// Command to execute
+    pub payload: CommandPayload,
+    /// Delivery attempt, starting at 1
+    pub attempt: u32,
+    /// Command timestamp (nanoseconds since epoch)
+    pub timestamp_ns: u64,
+    /// When the router began this dispatch (nanoseconds since epoch),
+    /// unchanged across resends and echoed in the unit's replies
+    pub dispatched_ns: u64,
+    /// Router's Ed25519 signature over `signed_hash`
+    pub signature: Vec<u8>,
+}
+
+impl CommandEnvelope {
+    /// Create an envelope signed with the router's key
+    pub fn sign(
+        command_id: impl Into<String>,
+        unit_id: impl Into<String>,
+        payload: CommandPayload,
+        attempt: u32,
+        timestamp_ns: u64,
+        dispatched_ns: u64,
+        key: &SigningKey,
+    ) -> Self {
+        let mut envelope = Self {
+            command_id: command_id.into(),
+            unit_id: unit_id.into(),
+            payload,
+            attempt,
+            timestamp_ns,
+            dispatched_ns,
+            signature: Vec::new(),
+        };
+        envelope.signature = key.sign(&envelope.signed_hash()).to_bytes().to_vec();
+        envelope
+    }
+
+    /// BLAKE3 hash of the envelope fields, as signed by the router
+    ///
+    /// The payload is hashed in its JSON encoding.
+    pub fn signed_hash(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"aethercore.c2.unit-command-envelope");
+        hash_field(&mut hasher, self.command_id.as_bytes());
+        hash_field(&mut hasher, self.unit_id.as_bytes());
+        hash_field(
+            &mut hasher,
+            &serde_json::to_vec(&self.payload).unwrap_or_default(),
+        );
+        hasher.update(&self.attempt.to_le_bytes());
+        hasher.update(&self.timestamp_ns.to_le_bytes());
+        hasher.update(&self.dispatched_ns.to_le_bytes());
+        *hasher.finalize().as_bytes()
+    }
+}
+
+/// Message from the C2 router to a unit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LinkMessage {
+    /// Execute a command
+    Command(CommandEnvelope),
+    /// Stop executing a command
+    Abort {
+        /// Command to stop
+        command_id: String,
+        /// Unit told to stop
+        unit_id: String,
+        /// Reason given by the operator
+        reason: String,
+        /// Router's Ed25519 signature over `signed_hash`
+        signature: Vec<u8>,
+    },
+}
+
+impl LinkMessage {
+    /// Create an abort signed with the router's key
+    pub fn abort(
+        command_id: impl Into<String>,
+        unit_id: impl Into<String>,
+        reason: impl Into<String>,
+        key: &SigningKey,
+    ) -> Self {
+        let command_id = command_id.into();
+        let unit_id = unit_id.into();
+        let reason = reason.into();
+        let signature = key
+            .sign(&abort_hash(&command_id, &unit_id, &reason))
+            .to_bytes()
+            .to_vec();
+        LinkMessage::Abort {
+            command_id,
+            unit_id,
+            reason,
+            signature,
+        }
+    }
+
+    /// Unit the message is for
+    pub fn unit_id(&self) -> &str {
+        match self {
+            LinkMessage::Command(envelope) => &envelope.unit_id,
+            LinkMessage::Abort { unit_id, .. } => unit_id,
+        }
+    }
+
+    /// BLAKE3 hash of the message, as signed by the router
+    pub fn signed_hash(&self) -> [u8; 32] {
+        match self {
+            LinkMessage::Command(envelope) => envelope.signed_hash(),
+            LinkMessage::Abort {
+                command_id,
+                unit_id,
+                reason,
+                ..
+            } => abort_hash(command_id, unit_id, reason),
+        }
+    }
+
+    /// Check that the message names `unit_id` and is signed with the
+    /// router's key
+    ///
+    /// Units must pass their own ID and drop any message for which this
+    /// returns false.
+    pub fn verify(&self, router_public_key: &[u8; 32], unit_id: &str) -> bool {
+        if self.unit_id() != unit_id {
+            return false;
+        }
+        let signature = match self {
+            LinkMessage::Command(envelope) => &envelope.signature,
+            LinkMessage::Abort { signature, .. } => signature,
+        };
+        verify_signature(router_public_key, &self.signed_hash(), signature)
+    }
+}
+
+fn abort_hash(command_id: &str, unit_id: &str, reason: &str) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"aethercore.c2.unit-command-abort");
+    hash_field(&mut hasher, command_id.as_bytes());
+    hash_field(&mut hasher, unit_id.as_bytes());
+    hash_field(&mut hasher, reason.as_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+fn verify_signature(public_key: &[u8; 32], hash: &[u8; 32], signature: &[u8]) -> bool {
+    let Ok(key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+    key.verify(hash, &signature).is_ok()
+}
+
+/// What a unit reports about a command
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ReplyKind {
+    /// Command is being executed
+    Progress {
+        /// Completion percentage
+        percent: f32,
+    },
+    /// Command executed
+    Ack,
+    /// Command refused or failed
+    Nack {
+        /// Reason reported by the unit
+        reason: String,
+    },
+}
+
+impl ReplyKind {
+    fn hash_into(&self, hasher: &mut blake3::Hasher) {
+        match self {
+            ReplyKind::Progress { percent } => {
+                hasher.update(&[0]);
+                hasher.update(&percent.to_bits().to_le_bytes());
+            }
+            ReplyKind::Ack => {
+                hasher.update(&[1]);
+            }
+            ReplyKind::Nack { reason } => {
+                hasher.update(&[2]);
+                hash_field(hasher, reason.as_bytes());
+            }
+        }
+    }
+}
+
+/// Signed report from a unit about a command
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnitReply {
+    /// Command reported on
+    pub command_id: String,
+    /// Reporting unit
+    pub unit_id: String,
+    /// Report
+    pub kind: ReplyKind,
+    /// `dispatched_ns` of the envelope answered
+    pub dispatched_ns: u64,
+    /// Report timestamp (nanoseconds since epoch)
+    pub timestamp_ns: u64,
+    /// Ed25519 signature over `signed_hash`
+    pub signature: Vec<u8>,
+}
+
+impl UnitReply {
+    /// Create a reply signed with the unit's key
+    pub fn sign(
+        command_id: impl Into<String>,
+        unit_id: impl Into<String>,
+        kind: ReplyKind,
+        dispatched_ns: u64,
+        timestamp_ns: u64,
+        key: &SigningKey,
+    ) -> Self {
+        let mut reply = Self {
+            command_id: command_id.into(),
+            unit_id: unit_id.into(),
+            kind,
+            dispatched_ns,
+            timestamp_ns,
+            signature: Vec::new(),
+        };
+        reply.signature = key.sign(&reply.signed_hash()).to_bytes().to_vec();
+        reply
+    }
+
+    /// BLAKE3 hash of the reply fields, as signed by the unit
+    pub fn signed_hash(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"aethercore.c2.unit-reply");
+        hash_field(&mut hasher, self.command_id.as_bytes());
+        hash_field(&mut hasher, self.unit_id.as_bytes());
+        self.kind.hash_into(&mut hasher);
+        hasher.update(&self.dispatched_ns.to_le_bytes());
+        hasher.update(&self.timestamp_ns.to_le_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Check the signature against the unit's public key
+    pub fn verify(&self, public_key: &[u8; 32]) -> bool {
+        verify_signature(public_key, &self.signed_hash(), &self.signature)
+    }
+}
+
+/// Transport carrying commands to units
+///
+/// Replies travel the other way through the `ReplySink` the link was
+/// created with.
+#[tonic::async_trait]
+pub trait UnitLink: Send + Sync {
+    /// Deliver `message` to `unit_id`
+    ///
+    /// Returning `Ok` means the message was handed to the transport, not
+    /// that the unit received it.
+    async fn send(&self, unit_id: &str, message: &LinkMessage) -> Result<(), LinkError>;
+}
+
+type Waiters = HashMap<(String, String), mpsc::UnboundedSender<UnitReply>>;
+
+/// Routes unit replies to the dispatches waiting on them
+#[derive(Debug, Clone, Default)]
+pub struct ReplySink {
+    waiters: Arc<Mutex<Waiters>>,
+}
+
+impl ReplySink {
+    /// Create a sink with nobody waiting
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hand a reply to the dispatch waiting on its command and unit
+    ///
+    /// Returns false if nothing is waiting, as for a late reply to a command
+    /// that already completed or timed out. Signatures are checked by the
+    /// dispatcher, not here.
+    pub fn deliver(&self, reply: UnitReply) -> bool {
+        let key = (reply.command_id.clone(), reply.unit_id.clone());
+        match self.lock().get(&key) {
+            Some(waiter) => waiter.send(reply).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Start waiting for replies from `unit_id` about `command_id`
+    ///
+    /// Returns `None` if a dispatch is already waiting on the same pair.
+    pub(crate) fn wait_for(&self, command_id: &str, unit_id: &str) -> Option<ReplyWaiter> {
+        let key = (command_id.to_string(), unit_id.to_string());
+        let mut waiters = self.lock();
+        if waiters.contains_key(&key) {
+            return None;
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        waiters.insert(key.clone(), sender);
+        Some(ReplyWaiter {
+            sink: self.clone(),
+            key,
+            receiver,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Waiters> {
+        sync::lock(&self.waiters)
+    }
+}
+
+/// Replies for one command and unit; stops waiting when dropped
+#[derive(Debug)]
+pub(crate) struct ReplyWaiter {
+    sink: ReplySink,
+    key: (String, String),
+    receiver: mpsc::UnboundedReceiver<UnitReply>,
+}
+
+impl ReplyWaiter {
+    /// Next reply; pending forever once the sink is gone
+    pub(crate) async fn recv(&mut self) -> UnitReply {
+        match self.receiver.recv().await {
+            Some(reply) => reply,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+impl Drop for ReplyWaiter {
+    fn drop(&mut self) {
+        self.sink.lock().remove(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_signature() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let reply = UnitReply::sign(
+            "cmd-1",
+            "unit-1",
+            ReplyKind::Nack {
+                reason: "low fuel".to_string(),
+            },
+            900,
+            1000,
+            &key,
+        );
+        assert!(reply.verify(&public_key));
+        assert!(!reply.verify(
+            &SigningKey::from_bytes(&[4u8; 32])
+                .verifying_key()
+                .to_bytes()
+        ));
+
+        // Any altered field breaks the signature
+        let mut forged = reply.clone();
+        forged.kind = ReplyKind::Ack;
+        assert!(!forged.verify(&public_key));
+        let mut forged = reply.clone();
+        forged.unit_id = "unit-2".to_string();
+        assert!(!forged.verify(&public_key));
+        let mut forged = reply;
+        forged.dispatched_ns = 950;
+        assert!(!forged.verify(&public_key));
+    }
+
+    #[test]
+    fn test_link_message_signature() {
+        let router_key = SigningKey::from_bytes(&[7u8; 32]);
+        let router_public_key = router_key.verifying_key().to_bytes();
+        let other_key = SigningKey::from_bytes(&[8u8; 32])
+            .verifying_key()
+            .to_bytes();
+
+        let command = LinkMessage::Command(CommandEnvelope::sign(
+            "cmd-1",
+            "unit-1",
+            CommandPayload::Unit(UnitCommand::Reboot { delay_secs: 5 }),
+            1,
+            1000,
+            1100,
+            &router_key,
+        ));
+        assert!(command.verify(&router_public_key, "unit-1"));
+        assert!(!command.verify(&other_key, "unit-1"));
+
+        // A command for one unit is refused by any other it is forwarded to
+        assert!(!command.verify(&router_public_key, "unit-2"));
+        let LinkMessage::Command(mut readdressed) = command.clone() else {
+            unreachable!()
+        };
+        readdressed.unit_id = "unit-2".to_string();
+        assert!(!LinkMessage::Command(readdressed).verify(&router_public_key, "unit-2"));
+
+        // Any altered field breaks the signature
+        let LinkMessage::Command(mut envelope) = command else {
+            unreachable!()
+        };
+        envelope.payload = CommandPayload::Unit(UnitCommand::Reboot { delay_secs: 0 });
+        assert!(!LinkMessage::Command(envelope).verify(&router_public_key, "unit-1"));
+
+        let abort = LinkMessage::abort("swarm-1", "unit-1", "Operator abort", &router_key);
+        assert!(abort.verify(&router_public_key, "unit-1"));
+        assert!(!abort.verify(&other_key, "unit-1"));
+        assert!(!abort.verify(&router_public_key, "unit-2"));
+        let LinkMessage::Abort { signature, .. } = abort else {
+            unreachable!()
+        };
+        let forged = LinkMessage::Abort {
+            command_id: "swarm-2".to_string(),
+            unit_id: "unit-1".to_string(),
+            reason: "Operator abort".to_string(),
+            signature: signature.clone(),
+        };
+        assert!(!forged.verify(&router_public_key, "unit-1"));
+        let readdressed = LinkMessage::Abort {
+            command_id: "swarm-1".to_string(),
+            unit_id: "unit-2".to_string(),
+            reason: "Operator abort".to_string(),
+            signature,
+        };
+        assert!(!readdressed.verify(&router_public_key, "unit-2"));
+    }
+
+    #[tokio::test]
+    async fn test_sink_routes_to_waiter() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let sink = ReplySink::new();
+        let reply = UnitReply::sign("cmd-1", "unit-1", ReplyKind::Ack, 900, 1000, &key);
+        assert!(!sink.deliver(reply.clone()));
+
+        let mut waiter = sink.wait_for("cmd-1", "unit-1").unwrap();
+        assert!(sink.wait_for("cmd-1", "unit-1").is_none());
+        assert!(!sink.deliver(UnitReply::sign(
+            "cmd-1",
+            "unit-2",
+            ReplyKind::Ack,
+            900,
+            1000,
+            &key
+        )));
+        assert!(sink.deliver(reply.clone()));
+        assert_eq!(waiter.recv().await, reply);
+
+        drop(waiter);
+        assert!(!sink.deliver(reply));
+        assert!(sink.wait_for("cmd-1", "unit-1").is_some());
+    }
+}
@@ -27,6 +27,9 @@ pub enum Error {
     #[error("ZK proof error: {0}")]
     Zk(String),
 
+    #[error("ZK verification error: {0}")]
+    ZkVerification(#[from] crate::zk_trait::ZkVerificationError),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
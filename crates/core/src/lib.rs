@@ -11,6 +11,7 @@ pub mod logging;
 pub mod merkle_aggregator;
 pub mod merkle_vine;
 pub mod slashing;
+pub mod sync;
 pub mod trust_chain;
 pub mod types;
 pub mod zk_trait;
@@ -385,13 +385,40 @@ impl SlashingEngine {
             SlashingEvent::new(node_id.clone(), fault_type, slasher_public_key_id, evidence)
                 .sign(signing_key);
 
+        self.record_slashing_event(slashing_event.clone(), &signing_key.verifying_key())?;
+
+        Ok(slashing_event)
+    }
+
+    /// Record a slashing event signed outside the engine
+    ///
+    /// For slashers whose key is held by a signing service rather than
+    /// passed in as a `SigningKey`. The event must verify under
+    /// `slasher_key`; the node is then transitioned to Revoked and the event
+    /// stored exactly as `execute_slashing` would.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The node is revoked
+    /// * `Err(SlashingError)` - The signature does not verify or the node is already revoked
+    pub fn record_slashing_event(
+        &mut self,
+        event: SlashingEvent,
+        slasher_key: &VerifyingKey,
+    ) -> Result<()> {
+        event.verify(slasher_key)?;
+        if self.get_node_state(&event.node_id).is_revoked() {
+            return Err(SlashingError::NodeAlreadyRevoked {
+                node_id: event.node_id,
+            });
+        }
+
         // Transition node to Revoked state
-        self.set_node_state(node_id.clone(), NodeState::Revoked);
+        self.set_node_state(event.node_id.clone(), NodeState::Revoked);
 
         // Store slashing event
-        self.slashing_events.push(slashing_event.clone());
+        self.slashing_events.push(event);
 
-        Ok(slashing_event)
+        Ok(())
     }
 
     /// Check an event for Byzantine behavior and automatically slash if detected
@@ -625,6 +652,62 @@ mod tests {
         assert_eq!(slashing_event.node_id, "node-1");
     }
 
+    #[test]
+    fn test_record_externally_signed_slashing_event() {
+        let mut engine = SlashingEngine::new();
+        use rand::Rng;
+        let mut csprng = rand::thread_rng();
+        let secret_bytes: [u8; 32] = csprng.gen();
+        let signing_key = SigningKey::from_bytes(&secret_bytes);
+
+        let fault_type = ByzantineFaultType::Equivocation {
+            seq_no: 7,
+            hash1: vec![1, 2, 3],
+            hash2: vec![4, 5, 6],
+        };
+        let unsigned = SlashingEvent::new(
+            "node-1".to_string(),
+            fault_type,
+            "slasher-1".to_string(),
+            None,
+        );
+
+        let slasher_key = signing_key.verifying_key();
+
+        // Unsigned events are rejected
+        assert!(matches!(
+            engine.record_slashing_event(unsigned.clone(), &slasher_key),
+            Err(SlashingError::InvalidSignature(_))
+        ));
+        assert!(!engine.get_node_state("node-1").is_revoked());
+
+        // As are events signed by anyone but the slasher
+        let mut forged = unsigned.clone();
+        forged.signature = vec![0xAB; 64];
+        assert!(matches!(
+            engine.record_slashing_event(forged, &slasher_key),
+            Err(SlashingError::InvalidSignature(_))
+        ));
+        let impostor = SigningKey::from_bytes(&[9u8; 32]);
+        assert!(matches!(
+            engine.record_slashing_event(unsigned.clone().sign(&impostor), &slasher_key),
+            Err(SlashingError::InvalidSignature(_))
+        ));
+        assert!(!engine.get_node_state("node-1").is_revoked());
+
+        engine
+            .record_slashing_event(unsigned.clone().sign(&signing_key), &slasher_key)
+            .unwrap();
+        assert!(engine.get_node_state("node-1").is_revoked());
+        assert_eq!(engine.get_node_slashing_events("node-1").len(), 1);
+
+        // A node is only revoked once
+        assert!(matches!(
+            engine.record_slashing_event(unsigned.sign(&signing_key), &slasher_key),
+            Err(SlashingError::NodeAlreadyRevoked { .. })
+        ));
+    }
+
     #[test]
     fn test_cannot_slash_already_revoked_node() {
         let mut engine = SlashingEngine::new();
@@ -0,0 +1,51 @@
+//! Lock acquisition with a single poisoning policy.
+//!
+//! A lock is poisoned when a thread panics while holding it. Every lock
+//! taken through this module guards state that is changed by single
+//! collection operations or only after the backing store has been written,
+//! so a panicking holder cannot leave it half-updated. A poisoned lock is
+//! therefore logged and its guard recovered rather than propagating the
+//! panic into every later caller.
+
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Lock a mutex, recovering the guard if it was poisoned.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(recover)
+}
+
+/// Acquire a read guard, recovering it if the lock was poisoned.
+pub fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(recover)
+}
+
+/// Acquire a write guard, recovering it if the lock was poisoned.
+pub fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(recover)
+}
+
+fn recover<G>(error: PoisonError<G>) -> G {
+    tracing::error!("Recovering poisoned lock");
+    error.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_poisoned_mutex_is_recovered() {
+        let mutex = Arc::new(Mutex::new(vec![1]));
+        let poisoner = Arc::clone(&mutex);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison");
+        })
+        .join();
+
+        assert!(mutex.is_poisoned());
+        lock(&mutex).push(2);
+        assert_eq!(*lock(&mutex), vec![1, 2]);
+    }
+}
@@ -38,6 +38,9 @@ pub struct SigningServiceImpl {
     /// Map of NodeID to EventSigningService
     /// In production with TPM, each node would have its own TPM-backed key
     signing_services: Arc<Mutex<HashMap<String, EventSigningService>>>,
+    /// mTLS peer authorizer; when set, callers may only sign as themselves
+    #[cfg(feature = "mtls")]
+    peer_authorizer: Option<Arc<dyn crate::mtls::PeerAuthorizer>>,
 }
 
 #[cfg(feature = "grpc-server")]
@@ -46,9 +49,34 @@ impl SigningServiceImpl {
     pub fn new() -> Self {
         Self {
             signing_services: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "mtls")]
+            peer_authorizer: None,
         }
     }
 
+    /// Bind signing requests to the mTLS peer identity.
+    ///
+    /// `node_id` in SignMessage and CreateSignedEnvelope must then match the
+    /// client certificate.
+    #[cfg(feature = "mtls")]
+    pub fn with_peer_authorizer(
+        mut self,
+        authorizer: Arc<dyn crate::mtls::PeerAuthorizer>,
+    ) -> Self {
+        self.peer_authorizer = Some(authorizer);
+        self
+    }
+
+    /// Check that `node_id` is the mTLS peer (no-op in plaintext mode)
+    #[allow(clippy::result_large_err)] // tonic::Status is the RPC error type
+    fn bind_node_id<T>(&self, request: &Request<T>, node_id: &str) -> Result<(), Status> {
+        #[cfg(feature = "mtls")]
+        crate::mtls::bind_device_id(request, self.peer_authorizer.as_ref(), node_id)?;
+        #[cfg(not(feature = "mtls"))]
+        let _ = (request, node_id);
+        Ok(())
+    }
+
     /// Get current timestamp in milliseconds
     fn current_timestamp_ms() -> u64 {
         std::time::SystemTime::now()
@@ -95,6 +123,7 @@ impl SigningService for SigningServiceImpl {
         request: Request<SignMessageRequest>,
     ) -> Result<Response<SignMessageResponse>, Status> {
         let start = std::time::Instant::now();
+        self.bind_node_id(&request, &request.get_ref().node_id)?;
         let req = request.into_inner();
         let timestamp_ms = Self::current_timestamp_ms();
 
@@ -186,6 +215,7 @@ impl SigningService for SigningServiceImpl {
         request: Request<CreateSignedEnvelopeRequest>,
     ) -> Result<Response<CreateSignedEnvelopeResponse>, Status> {
         let start = std::time::Instant::now();
+        self.bind_node_id(&request, &request.get_ref().node_id)?;
         let req = request.into_inner();
         let timestamp_ms = req.timestamp_ms;
 
@@ -327,3 +357,25 @@ pub async fn start_grpc_server(
 
     Ok(())
 }
+
+/// Start the Signing Service gRPC server with mutual TLS
+///
+/// Clients must present a certificate accepted by `config`, and may only
+/// request signatures for their own node ID.
+#[cfg(feature = "mtls")]
+pub async fn start_grpc_server_mtls(
+    addr: std::net::SocketAddr,
+    config: crate::mtls::MtlsServerConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let service = SigningServiceImpl::new().with_peer_authorizer(config.authorizer());
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    tracing::info!("Signing Service gRPC server (mTLS) listening on {}", addr);
+
+    Server::builder()
+        .add_service(SigningServiceServer::new(service))
+        .serve_with_incoming(crate::mtls::incoming(listener, &config)?)
+        .await?;
+
+    Ok(())
+}
@@ -44,6 +44,9 @@ pub mod zk;
 #[cfg(feature = "grpc-server")]
 pub mod grpc_server;
 
+#[cfg(feature = "mtls")]
+pub mod mtls;
+
 #[cfg(test)]
 mod test_vectors;
 
@@ -61,13 +64,16 @@ pub use signing::{
 };
 
 pub use zk::{
-    ZkError, ZkPrivateInputs, ZkProof, ZkProofParams, ZkProver, ZkProverTrait, ZkPublicInputs,
-    ZkVerificationKey,
+    ZkError, ZkPrivateInputs, ZkProof, ZkProofParams, ZkProver, ZkProverAdapter, ZkProverTrait,
+    ZkPublicInputs, ZkVerificationKey,
 };
 
 #[cfg(feature = "grpc-server")]
 pub use grpc_server::{start_grpc_server as start_signing_grpc_server, SigningServiceImpl};
 
+#[cfg(feature = "mtls")]
+pub use grpc_server::start_grpc_server_mtls as start_signing_grpc_server_mtls;
+
 #[cfg(test)]
 mod tests {
     #[test]
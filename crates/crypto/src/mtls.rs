@@ -0,0 +1,352 @@
+//! Mutual TLS transport for the AetherCore gRPC servers.
+//!
+//! Servers present a CA-issued device certificate and require clients to do
+//! the same. Client chains are validated against the configured CA roots
+//! during the handshake, then handed to a [`PeerAuthorizer`] which maps the
+//! certificate to a device identity and refuses revoked peers before any RPC
+//! is served.
+//!
+//! # Identity Binding
+//!
+//! Services built with an authorizer call [`bind_device_id`] with the
+//! `device_id` / `node_id` claimed in a request. The claim must match the
+//! identity of the peer certificate. Without an authorizer (plaintext mode)
+//! binding is a no-op.
+//!
+//! # Keys
+//!
+//! Only Ed25519 device keys are used; the server key is supplied as an
+//! `ed25519_dalek::SigningKey` and encoded to PKCS#8 for rustls.
+
+use ed25519_dalek::pkcs8::EncodePrivateKey;
+use ed25519_dalek::SigningKey;
+use rustls::server::{AllowAnyAuthenticatedClient, ClientCertVerified, ClientCertVerifier};
+use rustls::{Certificate, DistinguishedName, PrivateKey, RootCertStore, ServerConfig};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Status};
+
+/// Time allowed for a client to complete the TLS handshake
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Accepted connections buffered before the server picks them up
+const ACCEPT_BACKLOG: usize = 64;
+
+/// mTLS configuration errors
+#[derive(Debug, Error)]
+pub enum MtlsError {
+    /// Server certificate chain is empty
+    #[error("Server certificate chain is empty")]
+    EmptyCertificateChain,
+
+    /// A client CA root could not be parsed
+    #[error("Invalid client CA root: {0}")]
+    InvalidCaRoot(String),
+
+    /// Server key could not be encoded
+    #[error("Invalid server key: {0}")]
+    InvalidKey(String),
+
+    /// rustls rejected the configuration
+    #[error("TLS configuration error: {0}")]
+    Tls(#[from] rustls::Error),
+}
+
+/// Maps authenticated client certificates to device identities.
+///
+/// Implementations decide which certificates are acceptable beyond chain
+/// validation, e.g. by checking revocation state.
+pub trait PeerAuthorizer: Send + Sync {
+    /// Authorize a client certificate chain (DER, end-entity first).
+    ///
+    /// Returns the peer's device identity, or the reason it is refused.
+    fn authorize_peer(&self, chain: &[&[u8]]) -> Result<String, String>;
+}
+
+/// Server-side mTLS configuration
+#[derive(Clone)]
+pub struct MtlsServerConfig {
+    /// Server certificate chain (DER, end-entity first)
+    certificate_chain: Vec<Vec<u8>>,
+    /// Server private key (PKCS#8 DER)
+    private_key: Vec<u8>,
+    /// CA roots accepted for client certificates (DER)
+    client_ca_roots: Vec<Vec<u8>>,
+    /// Peer authorization and identity mapping
+    authorizer: Arc<dyn PeerAuthorizer>,
+}
+
+impl std::fmt::Debug for MtlsServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MtlsServerConfig")
+            .field("certificate_chain", &self.certificate_chain.len())
+            .field("client_ca_roots", &self.client_ca_roots.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MtlsServerConfig {
+    /// Create a server configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificate_chain` - Server certificate chain (DER, end-entity first)
+    /// * `server_key` - Key matching the server certificate
+    /// * `client_ca_roots` - CA certificates (DER) that client chains must lead to
+    /// * `authorizer` - Maps client certificates to device identities
+    pub fn new(
+        certificate_chain: Vec<Vec<u8>>,
+        server_key: &SigningKey,
+        client_ca_roots: Vec<Vec<u8>>,
+        authorizer: Arc<dyn PeerAuthorizer>,
+    ) -> Result<Self, MtlsError> {
+        if certificate_chain.is_empty() {
+            return Err(MtlsError::EmptyCertificateChain);
+        }
+        let private_key = server_key
+            .to_pkcs8_der()
+            .map_err(|e| MtlsError::InvalidKey(e.to_string()))?
+            .as_bytes()
+            .to_vec();
+
+        Ok(Self {
+            certificate_chain,
+            private_key,
+            client_ca_roots,
+            authorizer,
+        })
+    }
+
+    /// Peer authorizer shared with the services behind this transport
+    pub fn authorizer(&self) -> Arc<dyn PeerAuthorizer> {
+        self.authorizer.clone()
+    }
+
+    /// Build the rustls server configuration
+    pub fn rustls_config(&self) -> Result<Arc<ServerConfig>, MtlsError> {
+        let mut roots = RootCertStore::empty();
+        for root in &self.client_ca_roots {
+            roots
+                .add(&Certificate(root.clone()))
+                .map_err(|e| MtlsError::InvalidCaRoot(e.to_string()))?;
+        }
+
+        let verifier = Arc::new(AuthorizingClientVerifier {
+            inner: AllowAnyAuthenticatedClient::new(roots).boxed(),
+            authorizer: self.authorizer.clone(),
+        });
+
+        let mut config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(
+                self.certificate_chain
+                    .iter()
+                    .cloned()
+                    .map(Certificate)
+                    .collect(),
+                PrivateKey(self.private_key.clone()),
+            )?;
+        config.alpn_protocols = vec![b"h2".to_vec()];
+
+        Ok(Arc::new(config))
+    }
+}
+
+/// Client certificate verifier that runs the `PeerAuthorizer` after chain validation
+struct AuthorizingClientVerifier {
+    inner: Arc<dyn ClientCertVerifier>,
+    authorizer: Arc<dyn PeerAuthorizer>,
+}
+
+impl ClientCertVerifier for AuthorizingClientVerifier {
+    fn client_auth_root_subjects(&self) -> &[DistinguishedName] {
+        self.inner.client_auth_root_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        now: SystemTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let verified = self
+            .inner
+            .verify_client_cert(end_entity, intermediates, now)?;
+
+        let chain: Vec<&[u8]> = std::iter::once(end_entity)
+            .chain(intermediates)
+            .map(|cert| cert.0.as_slice())
+            .collect();
+        match self.authorizer.authorize_peer(&chain) {
+            Ok(device_id) => {
+                tracing::debug!(device_id = %device_id, "mTLS peer authorized");
+                Ok(verified)
+            }
+            Err(reason) => {
+                tracing::warn!(reason = %reason, "mTLS peer refused at handshake");
+                Err(rustls::Error::InvalidCertificate(
+                    rustls::CertificateError::Revoked,
+                ))
+            }
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Accept mTLS connections on `listener`.
+///
+/// Returns a stream for `tonic::transport::Server::serve_with_incoming`.
+/// Handshakes run concurrently; failed handshakes are logged and dropped
+/// without ending the stream.
+pub fn incoming(
+    listener: TcpListener,
+    config: &MtlsServerConfig,
+) -> Result<ReceiverStream<Result<TlsStream<TcpStream>, std::io::Error>>, MtlsError> {
+    let acceptor = TlsAcceptor::from(config.rustls_config()?);
+    let (tx, rx) = tokio::sync::mpsc::channel(ACCEPT_BACKLOG);
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, remote_addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!("mTLS accept failed: {}", e);
+                    continue;
+                }
+            };
+
+            // Server has shut down
+            if tx.is_closed() {
+                break;
+            }
+
+            let acceptor = acceptor.clone();
+            let conn_tx = tx.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(tls_stream)) => {
+                        let _ = conn_tx.send(Ok(tls_stream)).await;
+                    }
+                    Ok(Err(e)) => {
+                        tracing::warn!(remote = %remote_addr, "mTLS handshake failed: {}", e);
+                    }
+                    Err(_) => {
+                        tracing::warn!(remote = %remote_addr, "mTLS handshake timed out");
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(ReceiverStream::new(rx))
+}
+
+/// Device identity of the mTLS peer that sent `request`.
+///
+/// The authorizer is consulted on every call, so a peer revoked after its
+/// handshake is refused on its next request.
+#[allow(clippy::result_large_err)] // tonic::Status is the RPC error type
+pub fn peer_device_id<T>(
+    request: &Request<T>,
+    authorizer: &dyn PeerAuthorizer,
+) -> Result<String, Status> {
+    let certs = request
+        .peer_certs()
+        .filter(|certs| !certs.is_empty())
+        .ok_or_else(|| Status::unauthenticated("Client certificate required"))?;
+
+    let chain: Vec<&[u8]> = certs.iter().map(|cert| cert.get_ref()).collect();
+    authorizer
+        .authorize_peer(&chain)
+        .map_err(|reason| Status::permission_denied(format!("Peer refused: {}", reason)))
+}
+
+/// Require that `claimed_device_id` is the identity of the mTLS peer.
+///
+/// No-op when `authorizer` is `None` (plaintext mode).
+#[allow(clippy::result_large_err)] // tonic::Status is the RPC error type
+pub fn bind_device_id<T>(
+    request: &Request<T>,
+    authorizer: Option<&Arc<dyn PeerAuthorizer>>,
+    claimed_device_id: &str,
+) -> Result<(), Status> {
+    let authorizer = match authorizer {
+        Some(authorizer) => authorizer,
+        None => return Ok(()),
+    };
+
+    let peer_device_id = peer_device_id(request, authorizer.as_ref())?;
+    if peer_device_id != claimed_device_id {
+        tracing::warn!(
+            peer = %peer_device_id,
+            claimed = %claimed_device_id,
+            "Request device ID does not match client certificate"
+        );
+        return Err(Status::permission_denied(
+            "Device ID does not match client certificate",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedAuthorizer;
+
+    impl PeerAuthorizer for FixedAuthorizer {
+        fn authorize_peer(&self, _chain: &[&[u8]]) -> Result<String, String> {
+            Ok("node-a".to_string())
+        }
+    }
+
+    #[test]
+    fn test_bind_is_noop_without_authorizer() {
+        let request = Request::new(());
+        assert!(bind_device_id(&request, None, "node-a").is_ok());
+    }
+
+    #[test]
+    fn test_bind_requires_client_certificate() {
+        let authorizer: Arc<dyn PeerAuthorizer> = Arc::new(FixedAuthorizer);
+        let request = Request::new(());
+
+        let status = bind_device_id(&request, Some(&authorizer), "node-a").unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_config_requires_certificate_chain() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let result = MtlsServerConfig::new(vec![], &key, vec![], Arc::new(FixedAuthorizer));
+        assert!(matches!(result, Err(MtlsError::EmptyCertificateChain)));
+    }
+}
@@ -22,17 +22,20 @@
 //! This layer protects against:
 //! - Compromise of long-term signing keys (forward secrecy)
 //! - Passive eavesdropping on session data
-//! - Replay attacks (combined with nonce tracking)
+//! - Replay and reflection of session messages (per-direction keys and counters)
 //! - Key exhaustion attacks (automatic rotation)
 //!
 //! # Key Rotation Protocol
 //!
 //! 1. **Initialization**: Both parties generate ephemeral X25519 keypairs
 //! 2. **Exchange**: Public keys are exchanged (signed with Ed25519 identity keys)
-//! 3. **Derive**: Shared secret derived via X25519 DH, used to key ChaCha20-Poly1305
-//! 4. **Epoch**: After N messages or T seconds, initiate key rotation
-//! 5. **Rotate**: Generate new ephemeral keypairs, repeat exchange
-//! 6. **Zeroize**: Old key material securely erased
+//! 3. **Derive**: Shared secret derived via X25519 DH; HKDF-SHA256 turns it into
+//!    one ChaCha20-Poly1305 key per direction
+//! 4. **Send**: Each message carries its sender's counter, used as the nonce;
+//!    receivers refuse counters outside a sliding replay window
+//! 5. **Epoch**: After N messages or T seconds, initiate key rotation
+//! 6. **Rotate**: Generate new ephemeral keypairs, repeat exchange
+//! 7. **Zeroize**: Old key material securely erased
 //!
 //! # Performance
 //!
@@ -46,10 +49,15 @@ use chacha20poly1305::{
     aead::{Aead, KeyInit},
     ChaCha20Poly1305, Nonce as ChaCha20Nonce,
 };
+use hmac::{Hmac, Mac};
 use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, SharedSecret};
+use zeroize::Zeroizing;
+
+type HmacSha256 = Hmac<Sha256>;
 
 /// Maximum number of messages before forced key rotation.
 const MAX_MESSAGES_PER_EPOCH: u64 = 10_000;
@@ -178,13 +186,129 @@ impl KeyExchangeMessage {
     }
 }
 
+/// Messages accepted out of order behind the newest one seen.
+const REPLAY_WINDOW: u64 = 64;
+
+/// HKDF info labels for the two directions of a session. The side whose
+/// ephemeral public key sorts lower sends with the first.
+const LOW_TO_HIGH_LABEL: &[u8] = b"aethercore.session.low-to-high";
+const HIGH_TO_LOW_LABEL: &[u8] = b"aethercore.session.high-to-low";
+
+/// HKDF-SHA256 (RFC 5869) yielding a single 32-byte key.
+fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut extract =
+        <HmacSha256 as Mac>::new_from_slice(salt).expect("HMAC accepts keys of any length");
+    extract.update(ikm);
+    let prk: Zeroizing<[u8; 32]> = Zeroizing::new(extract.finalize().into_bytes().into());
+
+    let mut expand =
+        <HmacSha256 as Mac>::new_from_slice(prk.as_ref()).expect("HMAC accepts keys of any length");
+    expand.update(info);
+    expand.update(&[1]);
+    Zeroizing::new(expand.finalize().into_bytes().into())
+}
+
+/// Derive the (send, receive) ciphers for one side of a session.
+///
+/// Both directions come from the same shared secret but under different
+/// labels, so a message can never be reflected back to its sender, and
+/// each key sees every nonce at most once.
+fn directional_ciphers(
+    shared_secret: &SharedSecret,
+    local_public: &X25519PublicKey,
+    peer_public: &X25519PublicKey,
+) -> SessionResult<(ChaCha20Poly1305, ChaCha20Poly1305)> {
+    let (local, peer) = (local_public.as_bytes(), peer_public.as_bytes());
+    if local == peer {
+        return Err(SessionError::KeyExchange(
+            "Peer offered our own ephemeral key".to_string(),
+        ));
+    }
+    let (low, high) = if local < peer {
+        (local, peer)
+    } else {
+        (peer, local)
+    };
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(low);
+    salt[32..].copy_from_slice(high);
+
+    let low_to_high = hkdf_sha256(&salt, shared_secret.as_bytes(), LOW_TO_HIGH_LABEL);
+    let high_to_low = hkdf_sha256(&salt, shared_secret.as_bytes(), HIGH_TO_LOW_LABEL);
+    let (send, receive) = if local < peer {
+        (low_to_high, high_to_low)
+    } else {
+        (high_to_low, low_to_high)
+    };
+    Ok((
+        ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(send.as_ref())),
+        ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(receive.as_ref())),
+    ))
+}
+
+/// Nonce for the message numbered `counter`.
+fn counter_nonce(counter: u64) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce[4..].copy_from_slice(&counter.to_le_bytes());
+    nonce
+}
+
+/// Sliding window over the message counters received in an epoch.
+#[derive(Debug, Default)]
+struct ReplayWindow {
+    /// Highest counter accepted (counters start at 1)
+    highest: u64,
+    /// Bit `i` is set if counter `highest - i` was accepted
+    seen: u64,
+}
+
+impl ReplayWindow {
+    /// Whether `counter` is new and recent enough to accept.
+    fn permits(&self, counter: u64) -> bool {
+        if counter == 0 {
+            return false;
+        }
+        if counter > self.highest {
+            return true;
+        }
+        let behind = self.highest - counter;
+        behind < REPLAY_WINDOW && self.seen & (1 << behind) == 0
+    }
+
+    /// Record `counter`, which `permits` allowed, as received.
+    fn accept(&mut self, counter: u64) {
+        if counter > self.highest {
+            let shift = counter - self.highest;
+            self.seen = if shift >= REPLAY_WINDOW {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.highest = counter;
+        } else {
+            self.seen |= 1 << (self.highest - counter);
+        }
+    }
+}
+
 /// Session cipher state managing encryption/decryption with automatic rotation.
+///
+/// Each side sends under its own key and numbers its messages; the number
+/// is the AEAD nonce, and received numbers are checked against a sliding
+/// replay window.
 pub struct SessionCipher {
-    /// Current ChaCha20-Poly1305 cipher instance
-    cipher: ChaCha20Poly1305,
+    /// ChaCha20-Poly1305 cipher for messages we send
+    send_cipher: ChaCha20Poly1305,
 
-    /// Shared secret (zeroized on rotation via Drop)
-    shared_secret: Option<SharedSecret>,
+    /// ChaCha20-Poly1305 cipher for messages the peer sends
+    receive_cipher: ChaCha20Poly1305,
+
+    /// Counter of the last message sent in this epoch
+    send_counter: u64,
+
+    /// Counters received in this epoch
+    replay_window: ReplayWindow,
 
     /// Current epoch number
     epoch: u64,
@@ -202,29 +326,32 @@ pub struct SessionCipher {
     max_epoch_duration: Duration,
 }
 
-impl Drop for SessionCipher {
-    fn drop(&mut self) {
-        // SharedSecret is already zeroized by its Drop impl
-        self.shared_secret = None;
-    }
-}
-
 impl SessionCipher {
     /// Create a new session cipher from a shared secret.
-    pub fn new(shared_secret: SharedSecret, epoch: u64) -> Self {
-        // Derive ChaCha20-Poly1305 key from shared secret
-        let key = chacha20poly1305::Key::from_slice(shared_secret.as_bytes());
-        let cipher = ChaCha20Poly1305::new(key);
+    ///
+    /// `local_public` and `peer_public` are the ephemeral keys the secret
+    /// was agreed from; they decide which direction key each side sends with.
+    /// The shared secret is zeroized once the keys are derived.
+    pub fn new(
+        shared_secret: SharedSecret,
+        local_public: &X25519PublicKey,
+        peer_public: &X25519PublicKey,
+        epoch: u64,
+    ) -> SessionResult<Self> {
+        let (send_cipher, receive_cipher) =
+            directional_ciphers(&shared_secret, local_public, peer_public)?;
 
-        Self {
-            cipher,
-            shared_secret: Some(shared_secret),
+        Ok(Self {
+            send_cipher,
+            receive_cipher,
+            send_counter: 0,
+            replay_window: ReplayWindow::default(),
             epoch,
             message_count: 0,
             epoch_start: SystemTime::now(),
             max_messages_per_epoch: MAX_MESSAGES_PER_EPOCH,
             max_epoch_duration: Duration::from_secs(MAX_EPOCH_DURATION_SECS),
-        }
+        })
     }
 
     /// Check if key rotation is required.
@@ -235,48 +362,53 @@ impl SessionCipher {
 
     /// Encrypt plaintext with authenticated encryption.
     ///
-    /// Returns (ciphertext, nonce). The nonce must be transmitted with the ciphertext.
-    pub fn encrypt(&mut self, plaintext: &[u8]) -> SessionResult<(Vec<u8>, [u8; NONCE_SIZE])> {
+    /// Returns (ciphertext, counter). The counter must be transmitted with
+    /// the ciphertext; it is the message's nonce.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> SessionResult<(Vec<u8>, u64)> {
         if self.rotation_required() {
             return Err(SessionError::RotationRequired);
         }
 
-        // Generate unique nonce for this message
-        let mut nonce_bytes = [0u8; NONCE_SIZE];
-        let mut rng = HardwareRng::new()?;
-        rng.fill_bytes(&mut nonce_bytes);
-
-        let nonce = ChaCha20Nonce::from_slice(&nonce_bytes);
+        let counter = self.send_counter + 1;
+        let nonce = counter_nonce(counter);
 
         // Encrypt with AEAD
         let ciphertext = self
-            .cipher
-            .encrypt(nonce, plaintext)
+            .send_cipher
+            .encrypt(ChaCha20Nonce::from_slice(&nonce), plaintext)
             .map_err(|e| SessionError::Encryption(e.to_string()))?;
 
+        self.send_counter = counter;
         self.message_count += 1;
 
-        Ok((ciphertext, nonce_bytes))
+        Ok((ciphertext, counter))
     }
 
     /// Decrypt ciphertext with authenticated decryption.
-    pub fn decrypt(
-        &mut self,
-        ciphertext: &[u8],
-        nonce: &[u8; NONCE_SIZE],
-    ) -> SessionResult<Vec<u8>> {
+    ///
+    /// Fails for a counter already received in this epoch, or one too far
+    /// behind the newest received to tell.
+    pub fn decrypt(&mut self, ciphertext: &[u8], counter: u64) -> SessionResult<Vec<u8>> {
         if self.rotation_required() {
             return Err(SessionError::RotationRequired);
         }
+        if !self.replay_window.permits(counter) {
+            return Err(SessionError::Decryption(format!(
+                "Replayed or stale message counter {}",
+                counter
+            )));
+        }
 
-        let nonce = ChaCha20Nonce::from_slice(nonce);
+        let nonce = counter_nonce(counter);
 
         // Decrypt with AEAD verification
         let plaintext = self
-            .cipher
-            .decrypt(nonce, ciphertext)
+            .receive_cipher
+            .decrypt(ChaCha20Nonce::from_slice(&nonce), ciphertext)
             .map_err(|e| SessionError::Decryption(e.to_string()))?;
 
+        // Only authentic messages move the window
+        self.replay_window.accept(counter);
         self.message_count += 1;
 
         Ok(plaintext)
@@ -284,18 +416,25 @@ impl SessionCipher {
 
     /// Rotate to a new session key.
     ///
-    /// The old shared secret is zeroized automatically via Drop.
-    pub fn rotate(&mut self, new_shared_secret: SharedSecret) {
-        // Derive new cipher from new shared secret
-        let key = chacha20poly1305::Key::from_slice(new_shared_secret.as_bytes());
-        let new_cipher = ChaCha20Poly1305::new(key);
-
-        // Replace cipher and shared secret (old shared_secret is zeroized via Drop)
-        self.cipher = new_cipher;
-        self.shared_secret = Some(new_shared_secret);
+    /// The new shared secret is zeroized once the keys are derived; the
+    /// old keys are dropped with their ciphers.
+    pub fn rotate(
+        &mut self,
+        new_shared_secret: SharedSecret,
+        local_public: &X25519PublicKey,
+        peer_public: &X25519PublicKey,
+    ) -> SessionResult<()> {
+        let (send_cipher, receive_cipher) =
+            directional_ciphers(&new_shared_secret, local_public, peer_public)?;
+
+        self.send_cipher = send_cipher;
+        self.receive_cipher = receive_cipher;
+        self.send_counter = 0;
+        self.replay_window = ReplayWindow::default();
         self.epoch += 1;
         self.message_count = 0;
         self.epoch_start = SystemTime::now();
+        Ok(())
     }
 
     /// Get current epoch number.
@@ -334,6 +473,12 @@ pub struct SessionManager {
 
     /// Current epoch
     epoch: u64,
+
+    /// Messages allowed per epoch before rotation is required
+    max_messages_per_epoch: u64,
+
+    /// Epoch lifetime before rotation is required
+    max_epoch_duration: Duration,
 }
 
 impl SessionManager {
@@ -344,9 +489,21 @@ impl SessionManager {
             cipher: None,
             local_id,
             epoch: 0,
+            max_messages_per_epoch: MAX_MESSAGES_PER_EPOCH,
+            max_epoch_duration: Duration::from_secs(MAX_EPOCH_DURATION_SECS),
         }
     }
 
+    /// Override the rotation limits applied to the session cipher.
+    ///
+    /// Callers on constrained links may rotate more aggressively than the
+    /// defaults; the limits must be set before the key exchange completes.
+    pub fn with_epoch_limits(mut self, max_messages: u64, max_duration: Duration) -> Self {
+        self.max_messages_per_epoch = max_messages;
+        self.max_epoch_duration = max_duration;
+        self
+    }
+
     /// Initiate a new session by generating ephemeral keypair.
     ///
     /// Returns a key exchange message that must be signed and sent to peer.
@@ -370,13 +527,19 @@ impl SessionManager {
         let shared_secret = keypair.compute_shared_secret(&peer_public)?;
 
         // Create session cipher with shared secret
-        self.cipher = Some(SessionCipher::new(shared_secret, self.epoch));
+        let mut cipher =
+            SessionCipher::new(shared_secret, &keypair.public, &peer_public, self.epoch)?;
+        cipher.max_messages_per_epoch = self.max_messages_per_epoch;
+        cipher.max_epoch_duration = self.max_epoch_duration;
+        self.cipher = Some(cipher);
 
         Ok(())
     }
 
     /// Encrypt data with current session cipher.
-    pub fn encrypt(&mut self, plaintext: &[u8]) -> SessionResult<(Vec<u8>, [u8; NONCE_SIZE])> {
+    ///
+    /// Returns (ciphertext, counter).
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> SessionResult<(Vec<u8>, u64)> {
         let cipher = self
             .cipher
             .as_mut()
@@ -385,16 +548,12 @@ impl SessionManager {
     }
 
     /// Decrypt data with current session cipher.
-    pub fn decrypt(
-        &mut self,
-        ciphertext: &[u8],
-        nonce: &[u8; NONCE_SIZE],
-    ) -> SessionResult<Vec<u8>> {
+    pub fn decrypt(&mut self, ciphertext: &[u8], counter: u64) -> SessionResult<Vec<u8>> {
         let cipher = self
             .cipher
             .as_mut()
             .ok_or_else(|| SessionError::InvalidState("No active session".to_string()))?;
-        cipher.decrypt(ciphertext, nonce)
+        cipher.decrypt(ciphertext, counter)
     }
 
     /// Check if key rotation is required.
@@ -423,14 +582,11 @@ impl SessionManager {
         let peer_public = peer_msg.to_public_key();
         let shared_secret = keypair.compute_shared_secret(&peer_public)?;
 
-        // Rotate to new key (old key is zeroized)
-        if let Some(cipher) = self.cipher.as_mut() {
-            cipher.rotate(shared_secret);
-        } else {
-            return Err(SessionError::InvalidState("No active session".to_string()));
+        // Rotate to new keys (old keys are dropped)
+        match self.cipher.as_mut() {
+            Some(cipher) => cipher.rotate(shared_secret, &keypair.public, &peer_public),
+            None => Err(SessionError::InvalidState("No active session".to_string())),
         }
-
-        Ok(())
     }
 
     /// Get current epoch number.
@@ -496,6 +652,20 @@ fn current_timestamp_us() -> u64 {
 mod tests {
     use super::*;
 
+    /// Ciphers for both ends of a fresh key exchange
+    fn cipher_pair(epoch: u64) -> (SessionCipher, SessionCipher) {
+        let mut alice_keypair = SessionKeyPair::generate().unwrap();
+        let mut bob_keypair = SessionKeyPair::generate().unwrap();
+        let (alice_public, bob_public) = (alice_keypair.public, bob_keypair.public);
+
+        let alice_secret = alice_keypair.compute_shared_secret(&bob_public).unwrap();
+        let bob_secret = bob_keypair.compute_shared_secret(&alice_public).unwrap();
+        (
+            SessionCipher::new(alice_secret, &alice_public, &bob_public, epoch).unwrap(),
+            SessionCipher::new(bob_secret, &bob_public, &alice_public, epoch).unwrap(),
+        )
+    }
+
     #[test]
     fn test_keypair_generation() {
         let keypair = SessionKeyPair::generate().unwrap();
@@ -532,34 +702,30 @@ mod tests {
 
     #[test]
     fn test_session_cipher_encrypt_decrypt() {
-        let mut alice_keypair = SessionKeyPair::generate().unwrap();
-        let mut bob_keypair = SessionKeyPair::generate().unwrap();
-
-        let bob_public = bob_keypair.public;
-        let shared_secret = alice_keypair.compute_shared_secret(&bob_public).unwrap();
-        let mut cipher = SessionCipher::new(shared_secret, 0);
+        let (mut alice, mut bob) = cipher_pair(0);
 
         let plaintext = b"Secret message for testing";
-        let (ciphertext, nonce) = cipher.encrypt(plaintext).unwrap();
-
+        let (ciphertext, counter) = alice.encrypt(plaintext).unwrap();
         assert_ne!(ciphertext, plaintext);
-        assert_eq!(nonce.len(), NONCE_SIZE);
+        assert_eq!(counter, 1);
 
-        let decrypted = cipher.decrypt(&ciphertext, &nonce).unwrap();
+        let decrypted = bob.decrypt(&ciphertext, counter).unwrap();
         assert_eq!(decrypted, plaintext);
+
+        // Each direction has its own key: a message cannot be reflected
+        // back to its sender
+        let (ciphertext, counter) = alice.encrypt(plaintext).unwrap();
+        assert_eq!(counter, 2);
+        assert!(alice.decrypt(&ciphertext, counter).is_err());
+        assert_eq!(bob.decrypt(&ciphertext, counter).unwrap(), plaintext);
     }
 
     #[test]
     fn test_session_cipher_auth_tag() {
-        let mut alice_keypair = SessionKeyPair::generate().unwrap();
-        let mut bob_keypair = SessionKeyPair::generate().unwrap();
-
-        let bob_public = bob_keypair.public;
-        let shared_secret = alice_keypair.compute_shared_secret(&bob_public).unwrap();
-        let mut cipher = SessionCipher::new(shared_secret, 0);
+        let (mut alice, mut bob) = cipher_pair(0);
 
         let plaintext = b"Secret message";
-        let (mut ciphertext, nonce) = cipher.encrypt(plaintext).unwrap();
+        let (mut ciphertext, counter) = alice.encrypt(plaintext).unwrap();
 
         // Tamper with ciphertext
         if let Some(byte) = ciphertext.first_mut() {
@@ -567,18 +733,46 @@ mod tests {
         }
 
         // Decryption should fail due to auth tag mismatch
-        let result = cipher.decrypt(&ciphertext, &nonce);
+        let result = bob.decrypt(&ciphertext, counter);
         assert!(result.is_err());
+
+        // So does claiming another counter, which changes the nonce
+        let (ciphertext, counter) = alice.encrypt(plaintext).unwrap();
+        assert!(bob.decrypt(&ciphertext, counter + 1).is_err());
+        assert!(bob.decrypt(&ciphertext, counter).is_ok());
     }
 
     #[test]
-    fn test_rotation_required_message_count() {
-        let mut alice_keypair = SessionKeyPair::generate().unwrap();
-        let mut bob_keypair = SessionKeyPair::generate().unwrap();
+    fn test_replay_window() {
+        let (mut alice, mut bob) = cipher_pair(0);
+        let sent: Vec<(Vec<u8>, u64)> = (0..70)
+            .map(|i| alice.encrypt(format!("msg {}", i).as_bytes()).unwrap())
+            .collect();
+
+        // Out of order within the window is fine, but only once
+        let (ciphertext, counter) = &sent[5];
+        assert!(bob.decrypt(ciphertext, *counter).is_ok());
+        let (ciphertext, counter) = &sent[2];
+        assert!(bob.decrypt(ciphertext, *counter).is_ok());
+        assert!(bob.decrypt(ciphertext, *counter).is_err());
+        let (ciphertext, counter) = &sent[5];
+        assert!(bob.decrypt(ciphertext, *counter).is_err());
+
+        // A forged message does not consume its counter
+        let (ciphertext, counter) = &sent[69];
+        assert!(bob.decrypt(b"forged ciphertext", *counter).is_err());
+        assert!(bob.decrypt(ciphertext, *counter).is_ok());
+
+        // Messages that fell out of the window are refused
+        let (ciphertext, counter) = &sent[3];
+        assert!(bob.decrypt(ciphertext, *counter).is_err());
+        let (ciphertext, counter) = &sent[10];
+        assert!(bob.decrypt(ciphertext, *counter).is_ok());
+    }
 
-        let bob_public = bob_keypair.public;
-        let shared_secret = alice_keypair.compute_shared_secret(&bob_public).unwrap();
-        let mut cipher = SessionCipher::new(shared_secret, 0);
+    #[test]
+    fn test_rotation_required_message_count() {
+        let (mut cipher, _) = cipher_pair(0);
         cipher.set_max_messages(5);
 
         assert!(!cipher.rotation_required());
@@ -598,12 +792,7 @@ mod tests {
 
     #[test]
     fn test_rotation_required_time() {
-        let mut alice_keypair = SessionKeyPair::generate().unwrap();
-        let mut bob_keypair = SessionKeyPair::generate().unwrap();
-
-        let bob_public = bob_keypair.public;
-        let shared_secret = alice_keypair.compute_shared_secret(&bob_public).unwrap();
-        let mut cipher = SessionCipher::new(shared_secret, 0);
+        let (mut cipher, _) = cipher_pair(0);
         cipher.set_max_duration(Duration::from_millis(50));
 
         assert!(!cipher.rotation_required());
@@ -617,32 +806,33 @@ mod tests {
 
     #[test]
     fn test_cipher_rotation() {
-        let mut alice_keypair = SessionKeyPair::generate().unwrap();
-        let mut bob_keypair = SessionKeyPair::generate().unwrap();
+        let (mut alice, mut bob) = cipher_pair(0);
 
-        let bob_public = bob_keypair.public;
-        let shared_secret1 = alice_keypair.compute_shared_secret(&bob_public).unwrap();
-        let mut cipher = SessionCipher::new(shared_secret1, 0);
-
-        let _ = cipher.encrypt(b"test").unwrap();
-        let epoch1 = cipher.epoch();
-        let _count1 = cipher.message_count();
+        let (ciphertext, counter) = alice.encrypt(b"test").unwrap();
+        bob.decrypt(&ciphertext, counter).unwrap();
+        let epoch1 = alice.epoch();
 
         // Generate new keypairs for rotation
         let mut alice_keypair2 = SessionKeyPair::generate().unwrap();
         let mut bob_keypair2 = SessionKeyPair::generate().unwrap();
-        let bob_public2 = bob_keypair2.public;
-        let shared_secret2 = alice_keypair2.compute_shared_secret(&bob_public2).unwrap();
+        let (alice_public2, bob_public2) = (alice_keypair2.public, bob_keypair2.public);
+        let alice_secret2 = alice_keypair2.compute_shared_secret(&bob_public2).unwrap();
+        let bob_secret2 = bob_keypair2.compute_shared_secret(&alice_public2).unwrap();
 
         // Rotate
-        cipher.rotate(shared_secret2);
-
-        assert_eq!(cipher.epoch(), epoch1 + 1);
-        assert_eq!(cipher.message_count(), 0);
-
-        // Should be able to encrypt with new key
-        let (ciphertext, nonce) = cipher.encrypt(b"new message").unwrap();
-        let decrypted = cipher.decrypt(&ciphertext, &nonce).unwrap();
+        alice
+            .rotate(alice_secret2, &alice_public2, &bob_public2)
+            .unwrap();
+        bob.rotate(bob_secret2, &bob_public2, &alice_public2)
+            .unwrap();
+
+        assert_eq!(alice.epoch(), epoch1 + 1);
+        assert_eq!(alice.message_count(), 0);
+
+        // Counters restart under the new keys
+        let (ciphertext, counter) = alice.encrypt(b"new message").unwrap();
+        assert_eq!(counter, 1);
+        let decrypted = bob.decrypt(&ciphertext, counter).unwrap();
         assert_eq!(decrypted, b"new message");
     }
 
@@ -665,14 +855,14 @@ mod tests {
 
         // Alice encrypts, Bob decrypts
         let plaintext = b"Hello Bob!";
-        let (ciphertext, nonce) = alice.encrypt(plaintext).unwrap();
-        let decrypted = bob.decrypt(&ciphertext, &nonce).unwrap();
+        let (ciphertext, counter) = alice.encrypt(plaintext).unwrap();
+        let decrypted = bob.decrypt(&ciphertext, counter).unwrap();
         assert_eq!(decrypted, plaintext);
 
         // Bob encrypts, Alice decrypts
         let plaintext2 = b"Hello Alice!";
-        let (ciphertext2, nonce2) = bob.encrypt(plaintext2).unwrap();
-        let decrypted2 = alice.decrypt(&ciphertext2, &nonce2).unwrap();
+        let (ciphertext2, counter2) = bob.encrypt(plaintext2).unwrap();
+        let decrypted2 = alice.decrypt(&ciphertext2, counter2).unwrap();
         assert_eq!(decrypted2, plaintext2);
     }
 
@@ -705,11 +895,39 @@ mod tests {
 
         // Should be able to communicate with new keys
         let plaintext = b"After rotation";
-        let (ciphertext, nonce) = alice.encrypt(plaintext).unwrap();
-        let decrypted = bob.decrypt(&ciphertext, &nonce).unwrap();
+        let (ciphertext, counter) = alice.encrypt(plaintext).unwrap();
+        let decrypted = bob.decrypt(&ciphertext, counter).unwrap();
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn test_session_manager_epoch_limits() {
+        let mut alice =
+            SessionManager::new("alice".to_string()).with_epoch_limits(2, Duration::from_secs(60));
+        let mut bob = SessionManager::new("bob".to_string());
+
+        let alice_msg = alice.initiate_session().unwrap();
+        let bob_msg = bob.initiate_session().unwrap();
+        alice.complete_key_exchange(&bob_msg).unwrap();
+        bob.complete_key_exchange(&alice_msg).unwrap();
+
+        alice.encrypt(b"one").unwrap();
+        assert!(!alice.rotation_required());
+        alice.encrypt(b"two").unwrap();
+        assert!(alice.rotation_required());
+        assert!(!bob.rotation_required());
+
+        // Limits carry over to the rotated cipher
+        let alice_rotate = alice.initiate_rotation().unwrap();
+        let bob_rotate = bob.initiate_rotation().unwrap();
+        alice.complete_rotation(&bob_rotate).unwrap();
+        bob.complete_rotation(&alice_rotate).unwrap();
+        assert!(!alice.rotation_required());
+        alice.encrypt(b"three").unwrap();
+        alice.encrypt(b"four").unwrap();
+        assert!(alice.rotation_required());
+    }
+
     #[test]
     fn test_different_shared_secrets() {
         // Two unrelated sessions should have different shared secrets
@@ -746,12 +964,7 @@ mod tests {
 
     #[test]
     fn test_message_count_tracking() {
-        let mut alice_keypair = SessionKeyPair::generate().unwrap();
-        let mut bob_keypair = SessionKeyPair::generate().unwrap();
-
-        let bob_public = bob_keypair.public;
-        let shared_secret = alice_keypair.compute_shared_secret(&bob_public).unwrap();
-        let mut cipher = SessionCipher::new(shared_secret, 0);
+        let (mut cipher, _) = cipher_pair(0);
 
         assert_eq!(cipher.message_count(), 0);
 
@@ -0,0 +1,334 @@
+//! R1CS circuit for device attestation and location proofs.
+//!
+//! This is a native arkworks port of the AuthynticProof.circom constraint system.
+//! The circuit proves knowledge of a witness (`ZkPrivateInputs`) such that:
+//!
+//! - `device_commitment == Poseidon(device_secret, device_salt)`
+//! - `expected_location_commitment == Poseidon(location_hash, location_nonce)`
+//! - `expected_attestation_root == Poseidon(attestation[0..4])`
+//! - `timestamp <= current_time` and `current_time - timestamp <= max_age`
+//!
+//! The Poseidon gadget uses the same circom-compatible BN254 x^5 parameters as
+//! [`super::poseidon`], so commitments computed natively are accepted in-circuit.
+//!
+//! Public inputs are allocated in `ZkPublicInputs` field order:
+//! `[device_commitment, merkle_root, current_time, expected_location_commitment,
+//! expected_attestation_root, max_age]`.
+
+use super::error::{ZkError, ZkResult};
+use super::inputs::{ZkPrivateInputs, ZkPublicInputs};
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
+};
+use light_poseidon::parameters::bn254_x5::get_poseidon_parameters;
+use light_poseidon::PoseidonParameters;
+
+/// Bit width used for timestamp range checks (Unix milliseconds fit in u64)
+const TIMESTAMP_BITS: usize = 64;
+
+/// Number of public inputs exposed by the circuit
+pub const NUM_PUBLIC_INPUTS: usize = 6;
+
+/// Convert a 32-byte big-endian value into a BN254 scalar.
+///
+/// Matches the reduction used by the native Poseidon helpers.
+pub fn bytes_to_field(bytes: &[u8; 32]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+/// Map public inputs onto the field elements expected by the verifier
+pub fn public_inputs_to_field(public_inputs: &ZkPublicInputs) -> Vec<Fr> {
+    vec![
+        bytes_to_field(&public_inputs.device_commitment),
+        bytes_to_field(&public_inputs.merkle_root),
+        Fr::from(public_inputs.current_time),
+        bytes_to_field(&public_inputs.expected_location_commitment),
+        bytes_to_field(&public_inputs.expected_attestation_root),
+        Fr::from(public_inputs.max_age),
+    ]
+}
+
+/// Witness assignment for [`AuthynticCircuit`]
+#[derive(Debug, Clone)]
+struct Assignment {
+    device_secret: Fr,
+    device_salt: Fr,
+    location_hash: Fr,
+    location_nonce: Fr,
+    timestamp: u64,
+    neighbor_attestations: [Fr; 4],
+    public: Vec<Fr>,
+    current_time: u64,
+    max_age: u64,
+}
+
+/// Device attestation circuit
+///
+/// Constructed without an assignment for key generation (`blank`) and with
+/// one for proving (`new`).
+#[derive(Debug, Clone, Default)]
+pub struct AuthynticCircuit {
+    assignment: Option<Assignment>,
+}
+
+impl AuthynticCircuit {
+    /// Create a circuit instance with a full witness assignment
+    pub fn new(private_inputs: &ZkPrivateInputs, public_inputs: &ZkPublicInputs) -> Self {
+        let atts = &private_inputs.neighbor_attestations;
+        Self {
+            assignment: Some(Assignment {
+                device_secret: bytes_to_field(&private_inputs.device_secret),
+                device_salt: bytes_to_field(&private_inputs.device_salt),
+                location_hash: bytes_to_field(&private_inputs.location_hash),
+                location_nonce: bytes_to_field(&private_inputs.location_nonce),
+                timestamp: private_inputs.timestamp,
+                neighbor_attestations: [
+                    bytes_to_field(&atts[0]),
+                    bytes_to_field(&atts[1]),
+                    bytes_to_field(&atts[2]),
+                    bytes_to_field(&atts[3]),
+                ],
+                public: public_inputs_to_field(public_inputs),
+                current_time: public_inputs.current_time,
+                max_age: public_inputs.max_age,
+            }),
+        }
+    }
+
+    /// Create a circuit instance without an assignment (for key generation)
+    pub fn blank() -> Self {
+        Self::default()
+    }
+
+    /// Synthesize the witness and check it against every constraint.
+    ///
+    /// Returns the number of constraints on success. On failure the error names
+    /// the first unsatisfied constraint, so invalid inputs are rejected before
+    /// an expensive (and silently invalid) proof is produced.
+    pub fn check_witness(&self) -> ZkResult<usize> {
+        if self.assignment.is_none() {
+            return Err(ZkError::InvalidInput(
+                "Circuit has no witness assignment".to_string(),
+            ));
+        }
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        self.clone()
+            .generate_constraints(cs.clone())
+            .map_err(|e| ZkError::ProofGenerationFailed(format!("Synthesis failed: {}", e)))?;
+
+        let satisfied = cs
+            .is_satisfied()
+            .map_err(|e| ZkError::ProofGenerationFailed(format!("Synthesis failed: {}", e)))?;
+        if !satisfied {
+            let which = cs
+                .which_is_unsatisfied()
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| "unknown".to_string());
+            return Err(ZkError::ProofGenerationFailed(format!(
+                "Witness does not satisfy circuit constraint: {}",
+                which
+            )));
+        }
+
+        Ok(cs.num_constraints())
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for AuthynticCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let a = self.assignment.as_ref();
+        let public = |i: usize| {
+            a.map(|a| a.public[i])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        // Public inputs (order must match `public_inputs_to_field`)
+        let device_commitment = FpVar::new_input(cs.clone(), || public(0))?;
+        // The merkle root is bound as a public input; membership is established
+        // by the verifier against its trusted device set.
+        let _merkle_root = FpVar::new_input(cs.clone(), || public(1))?;
+        let current_time = FpVar::new_input(cs.clone(), || public(2))?;
+        let location_commitment = FpVar::new_input(cs.clone(), || public(3))?;
+        let attestation_root = FpVar::new_input(cs.clone(), || public(4))?;
+        let max_age = FpVar::new_input(cs.clone(), || public(5))?;
+
+        let witness = |f: fn(&Assignment) -> Fr| {
+            let cs = cs.clone();
+            FpVar::new_witness(cs, move || {
+                a.map(f).ok_or(SynthesisError::AssignmentMissing)
+            })
+        };
+
+        // Private witness
+        let device_secret = witness(|a| a.device_secret)?;
+        let device_salt = witness(|a| a.device_salt)?;
+        let location_hash = witness(|a| a.location_hash)?;
+        let location_nonce = witness(|a| a.location_nonce)?;
+        let attestations = [
+            witness(|a| a.neighbor_attestations[0])?,
+            witness(|a| a.neighbor_attestations[1])?,
+            witness(|a| a.neighbor_attestations[2])?,
+            witness(|a| a.neighbor_attestations[3])?,
+        ];
+
+        // Commitment constraints
+        poseidon_gadget(&[device_secret, device_salt])?.enforce_equal(&device_commitment)?;
+        poseidon_gadget(&[location_hash, location_nonce])?.enforce_equal(&location_commitment)?;
+        poseidon_gadget(&attestations)?.enforce_equal(&attestation_root)?;
+
+        // Temporal constraints: timestamp, age and slack are all range checked,
+        // so `age = current_time - timestamp` and `slack = max_age - age` cannot wrap.
+        let timestamp = alloc_u64(cs.clone(), a.map(|a| a.timestamp))?;
+        let age = alloc_u64(
+            cs.clone(),
+            a.map(|a| a.current_time.wrapping_sub(a.timestamp)),
+        )?;
+        let slack = alloc_u64(
+            cs.clone(),
+            a.map(|a| {
+                a.max_age
+                    .wrapping_sub(a.current_time.wrapping_sub(a.timestamp))
+            }),
+        )?;
+        (&timestamp + &age).enforce_equal(&current_time)?;
+        (&age + &slack).enforce_equal(&max_age)?;
+
+        Ok(())
+    }
+}
+
+/// Allocate a witness value constrained to `TIMESTAMP_BITS` bits
+fn alloc_u64(cs: ConstraintSystemRef<Fr>, value: Option<u64>) -> Result<FpVar<Fr>, SynthesisError> {
+    let bits = (0..TIMESTAMP_BITS)
+        .map(|i| {
+            Boolean::new_witness(cs.clone(), || {
+                value
+                    .map(|v| (v >> i) & 1 == 1)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp(&bits)
+}
+
+/// In-circuit circom-compatible Poseidon hash over BN254 (x^5 S-box)
+///
+/// Mirrors the round structure of `light_poseidon::Poseidon::hash` exactly:
+/// half full rounds, partial rounds, half full rounds, each applying round
+/// constants, the S-box layer and the MDS matrix.
+fn poseidon_gadget(inputs: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+    let width = inputs.len() + 1;
+    let params: PoseidonParameters<Fr> =
+        get_poseidon_parameters(width as u8).map_err(|_| SynthesisError::Unsatisfiable)?;
+
+    let mut state: Vec<FpVar<Fr>> = Vec::with_capacity(width);
+    state.push(FpVar::zero());
+    state.extend(inputs.iter().cloned());
+
+    let half_rounds = params.full_rounds / 2;
+    let all_rounds = params.full_rounds + params.partial_rounds;
+
+    for round in 0..all_rounds {
+        for (i, s) in state.iter_mut().enumerate() {
+            *s += params.ark[round * width + i];
+        }
+
+        let full = round < half_rounds || round >= half_rounds + params.partial_rounds;
+        if full {
+            for s in state.iter_mut() {
+                *s = sbox(s)?;
+            }
+        } else {
+            state[0] = sbox(&state[0])?;
+        }
+
+        state = (0..width)
+            .map(|i| {
+                state
+                    .iter()
+                    .zip(params.mds[i].iter())
+                    .fold(FpVar::zero(), |acc, (s, m)| acc + s * *m)
+            })
+            .collect();
+    }
+
+    Ok(state.swap_remove(0))
+}
+
+/// x^5 S-box (3 multiplication constraints)
+fn sbox(x: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    let x2 = x.square()?;
+    let x4 = x2.square()?;
+    Ok(x4 * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::poseidon::{attestation_root, device_commitment, location_commitment};
+
+    fn valid_inputs() -> (ZkPrivateInputs, ZkPublicInputs) {
+        let private_inputs = ZkPrivateInputs::new(
+            [1u8; 32],
+            [2u8; 32],
+            [3u8; 32],
+            [4u8; 32],
+            1_000_000,
+            [[5u8; 32], [6u8; 32], [7u8; 32], [8u8; 32]],
+        );
+        let public_inputs = ZkPublicInputs::new(
+            device_commitment(&[1u8; 32], &[2u8; 32]).unwrap(),
+            [10u8; 32],
+            1_200_000,
+            location_commitment(&[3u8; 32], &[4u8; 32]).unwrap(),
+            attestation_root(&private_inputs.neighbor_attestations).unwrap(),
+            300_000,
+        );
+        (private_inputs, public_inputs)
+    }
+
+    #[test]
+    fn test_valid_witness_satisfies_circuit() {
+        let (private_inputs, public_inputs) = valid_inputs();
+        let constraints = AuthynticCircuit::new(&private_inputs, &public_inputs)
+            .check_witness()
+            .unwrap();
+        assert!(constraints > 0);
+    }
+
+    #[test]
+    fn test_commitment_mismatch_unsatisfied() {
+        let (private_inputs, mut public_inputs) = valid_inputs();
+        public_inputs.device_commitment = [9u8; 32];
+        let result = AuthynticCircuit::new(&private_inputs, &public_inputs).check_witness();
+        assert!(matches!(result, Err(ZkError::ProofGenerationFailed(_))));
+    }
+
+    #[test]
+    fn test_stale_timestamp_unsatisfied() {
+        let (private_inputs, mut public_inputs) = valid_inputs();
+        public_inputs.max_age = 100_000;
+        let result = AuthynticCircuit::new(&private_inputs, &public_inputs).check_witness();
+        assert!(matches!(result, Err(ZkError::ProofGenerationFailed(_))));
+    }
+
+    #[test]
+    fn test_future_timestamp_unsatisfied() {
+        let (mut private_inputs, public_inputs) = valid_inputs();
+        private_inputs.timestamp = 1_300_000;
+        let result = AuthynticCircuit::new(&private_inputs, &public_inputs).check_witness();
+        assert!(matches!(result, Err(ZkError::ProofGenerationFailed(_))));
+    }
+
+    #[test]
+    fn test_blank_circuit_has_no_witness() {
+        let result = AuthynticCircuit::blank().check_witness();
+        assert!(matches!(result, Err(ZkError::InvalidInput(_))));
+    }
+}
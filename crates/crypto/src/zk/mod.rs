@@ -3,11 +3,17 @@
 //! This module provides ZK-SNARK capabilities for privacy-preserving authentication
 //! and location proofs, ported from AuthynticOne TypeScript implementation.
 
+pub mod circuit;
 pub mod error;
 pub mod inputs;
 pub mod poseidon;
 pub mod prover;
+pub mod service;
 
+pub use circuit::AuthynticCircuit;
 pub use error::{ZkError, ZkResult};
 pub use inputs::{ZkPrivateInputs, ZkProofParams, ZkPublicInputs};
 pub use prover::{ZkProof, ZkProver, ZkProverTrait, ZkVerificationKey};
+pub use service::{
+    LocationOpening, ZkProofEnvelope, ZkProofStatement, ZkProverAdapter, DEFAULT_MAX_PROOF_AGE_MS,
+};
@@ -25,7 +25,9 @@
 //!
 //! - **ZkProver**: Main prover instance that manages circuit artifacts and generates proofs
 //! - **ZkProof**: Groth16 proof structure containing π_A, π_B, π_C components
-//! - **Circuit Artifacts**: .wasm (witness generator), .r1cs (constraints), .zkey (proving key)
+//! - **AuthynticCircuit**: Native R1CS port of AuthynticProof.circom (witness + constraints)
+//! - **Circuit Artifacts**: .zkey (proving key); .wasm/.r1cs paths are accepted for layout
+//!   compatibility but the witness is synthesized natively
 //!
 //! ## Fail-Visible Pattern
 //!
@@ -69,6 +71,12 @@
 //! # Ok::<(), aethercore_crypto::zk::ZkError>(())
 //! ```
 //!
+//! ## Development Keys
+//!
+//! `initialize_with_setup()` runs a circuit-specific setup for `AuthynticCircuit`.
+//! Export the result with `export_proving_key()` (the .zkey artifact) and
+//! `verification_key()` (published to verifiers, see `ZkVerificationKey::verify`).
+//!
 //! ## Mock Mode (for testing)
 //!
 //! ```rust
@@ -90,9 +98,16 @@
 //! - **Circuit Compatibility**: BN254 curve ensures compatibility with Ethereum and standard
 //!   Circom tooling.
 
+use super::circuit::{public_inputs_to_field, AuthynticCircuit, NUM_PUBLIC_INPUTS};
 use super::error::{ZkError, ZkResult};
 use super::inputs::{ZkPrivateInputs, ZkPublicInputs};
 use super::poseidon::{attestation_root, device_commitment, location_commitment};
+use ark_bn254::{Bn254, G1Affine, G2Affine};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -124,7 +139,10 @@ pub struct ZkProof {
 }
 
 /// Verification key for ZK proofs
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// All points use arkworks uncompressed encoding (G1: 64 bytes, G2: 128 bytes),
+/// the same encoding as the `ZkProof` components.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ZkVerificationKey {
     /// Alpha_1 point
     pub alpha_1: Vec<u8>,
@@ -138,6 +156,60 @@ pub struct ZkVerificationKey {
     pub ic: Vec<Vec<u8>>,
 }
 
+impl ZkVerificationKey {
+    /// Verify a proof against this key without access to the proving key.
+    ///
+    /// This is the entry point for third-party verifiers that only hold the
+    /// published verification key.
+    pub fn verify(&self, proof: &ZkProof, public_inputs: &ZkPublicInputs) -> ZkResult<bool> {
+        let pvk = Groth16::<Bn254>::process_vk(&self.to_ark()?)
+            .map_err(|e| ZkError::VerificationFailed(e.to_string()))?;
+        verify_with_prepared_key(&pvk, proof, public_inputs)
+    }
+
+    fn from_ark(vk: &VerifyingKey<Bn254>) -> ZkResult<Self> {
+        Ok(Self {
+            alpha_1: serialize_point(&vk.alpha_g1)?,
+            beta_2: serialize_point(&vk.beta_g2)?,
+            gamma_2: serialize_point(&vk.gamma_g2)?,
+            delta_2: serialize_point(&vk.delta_g2)?,
+            ic: vk
+                .gamma_abc_g1
+                .iter()
+                .map(serialize_point)
+                .collect::<ZkResult<_>>()?,
+        })
+    }
+
+    fn to_ark(&self) -> ZkResult<VerifyingKey<Bn254>> {
+        if self.ic.len() != NUM_PUBLIC_INPUTS + 1 {
+            return Err(ZkError::VerificationKeyNotFound(format!(
+                "Verification key has {} IC points, circuit requires {}",
+                self.ic.len(),
+                NUM_PUBLIC_INPUTS + 1
+            )));
+        }
+
+        let invalid = |e: ark_serialize::SerializationError| {
+            ZkError::VerificationKeyNotFound(format!("Malformed verification key: {}", e))
+        };
+        Ok(VerifyingKey {
+            alpha_g1: G1Affine::deserialize_uncompressed(self.alpha_1.as_slice())
+                .map_err(invalid)?,
+            beta_g2: G2Affine::deserialize_uncompressed(self.beta_2.as_slice()).map_err(invalid)?,
+            gamma_g2: G2Affine::deserialize_uncompressed(self.gamma_2.as_slice())
+                .map_err(invalid)?,
+            delta_g2: G2Affine::deserialize_uncompressed(self.delta_2.as_slice())
+                .map_err(invalid)?,
+            gamma_abc_g1: self
+                .ic
+                .iter()
+                .map(|p| G1Affine::deserialize_uncompressed(p.as_slice()).map_err(invalid))
+                .collect::<ZkResult<_>>()?,
+        })
+    }
+}
+
 /// Trait for ZK proof generation (dependency injection)
 pub trait ZkProverTrait: Send + Sync {
     /// Generate a ZK proof from private inputs
@@ -156,10 +228,12 @@ pub trait ZkProverTrait: Send + Sync {
 pub struct ZkProver {
     /// Whether the prover is initialized with keys
     initialized: bool,
-    /// Optional proving key loaded from artifacts
-    proving_key: Option<Vec<u8>>,
-    /// Optional verification key loaded from artifacts
-    verification_key: Option<Vec<u8>>,
+    /// Whether the prover emits structurally valid but meaningless proofs
+    mock_mode: bool,
+    /// Groth16 proving key for `AuthynticCircuit`
+    proving_key: Option<ProvingKey<Bn254>>,
+    /// Prepared Groth16 verification key for `AuthynticCircuit`
+    verification_key: Option<PreparedVerifyingKey<Bn254>>,
 }
 
 impl ZkProver {
@@ -167,6 +241,7 @@ impl ZkProver {
     pub fn new() -> Self {
         Self {
             initialized: false,
+            mock_mode: false,
             proving_key: None,
             verification_key: None,
         }
@@ -175,9 +250,11 @@ impl ZkProver {
     /// Initialize the prover with circuit artifacts
     ///
     /// # Arguments
-    /// * `_wasm_path` - Path to .wasm witness generator (reserved for future use)
-    /// * `_r1cs_path` - Path to .r1cs constraint system (reserved for future use)
-    /// * `zkey_path` - Path to .zkey proving key file
+    /// * `_wasm_path` - Path to .wasm witness generator (unused; the witness is
+    ///   synthesized by the native `AuthynticCircuit`)
+    /// * `_r1cs_path` - Path to .r1cs constraint system (unused; constraints are
+    ///   generated by the native `AuthynticCircuit`)
+    /// * `zkey_path` - Path to the proving key, as written by `export_proving_key`
     ///
     /// # Fail-Visible Pattern
     /// If artifacts are missing, this will return an error with clear diagnostics.
@@ -196,12 +273,39 @@ impl ZkProver {
             ))
         })?;
 
-        // For now, store the raw data. In production, this would be parsed
-        // into ProvingKey and VerifyingKey structures
-        self.proving_key = Some(zkey_data);
-        self.initialized = true;
+        let proving_key = ProvingKey::<Bn254>::deserialize_compressed(zkey_data.as_slice())
+            .map_err(|e| {
+                ZkError::ProvingKeyNotFound(format!(
+                    "CRITICAL: ZK proving key at {:?} is malformed. Deployment unsafe. Error: {}",
+                    zkey_path, e
+                ))
+            })?;
 
-        Ok(())
+        self.load_proving_key(proving_key)
+    }
+
+    /// Initialize by running a circuit-specific Groth16 setup
+    ///
+    /// The resulting keys are only as trustworthy as `rng`: whoever knows its
+    /// output can forge proofs. Use this for tests and development; deployed
+    /// keys must come from a ceremony and be loaded with `initialize()`.
+    pub fn initialize_with_setup<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> ZkResult<()> {
+        let (proving_key, _) =
+            Groth16::<Bn254>::circuit_specific_setup(AuthynticCircuit::blank(), rng)
+                .map_err(|e| ZkError::ProofGenerationFailed(format!("Setup failed: {}", e)))?;
+        self.load_proving_key(proving_key)
+    }
+
+    /// Create a verify-only instance from a published verification key
+    pub fn from_verification_key(verification_key: &ZkVerificationKey) -> ZkResult<Self> {
+        let pvk = Groth16::<Bn254>::process_vk(&verification_key.to_ark()?)
+            .map_err(|e| ZkError::VerificationKeyNotFound(e.to_string()))?;
+        Ok(Self {
+            initialized: true,
+            mock_mode: false,
+            proving_key: None,
+            verification_key: Some(pvk),
+        })
     }
 
     /// Initialize with mock keys for testing (TESTING ONLY)
@@ -209,6 +313,46 @@ impl ZkProver {
     /// This allows the prover to be initialized without circuit artifacts.
     /// It will generate proofs that are structurally valid but cryptographically meaningless.
     pub fn initialize_mock(&mut self) -> ZkResult<()> {
+        self.initialized = true;
+        self.mock_mode = true;
+        Ok(())
+    }
+
+    /// Serialize the proving key for storage as a .zkey artifact
+    pub fn export_proving_key(&self) -> ZkResult<Vec<u8>> {
+        let proving_key = self
+            .proving_key
+            .as_ref()
+            .ok_or_else(|| ZkError::ProvingKeyNotFound("No proving key loaded".to_string()))?;
+        let mut bytes = Vec::new();
+        proving_key
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| ZkError::ProvingKeyNotFound(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Export the verification key for distribution to verifiers
+    pub fn verification_key(&self) -> ZkResult<ZkVerificationKey> {
+        let pvk = self.verification_key.as_ref().ok_or_else(|| {
+            ZkError::VerificationKeyNotFound("No verification key loaded".to_string())
+        })?;
+        ZkVerificationKey::from_ark(&pvk.vk)
+    }
+
+    fn load_proving_key(&mut self, proving_key: ProvingKey<Bn254>) -> ZkResult<()> {
+        if proving_key.vk.gamma_abc_g1.len() != NUM_PUBLIC_INPUTS + 1 {
+            return Err(ZkError::ProvingKeyNotFound(format!(
+                "CRITICAL: Proving key expects {} public inputs, circuit has {}",
+                proving_key.vk.gamma_abc_g1.len().saturating_sub(1),
+                NUM_PUBLIC_INPUTS
+            )));
+        }
+
+        let pvk = Groth16::<Bn254>::process_vk(&proving_key.vk)
+            .map_err(|e| ZkError::VerificationKeyNotFound(e.to_string()))?;
+        self.verification_key = Some(pvk);
+        self.proving_key = Some(proving_key);
+        self.mock_mode = false;
         self.initialized = true;
         Ok(())
     }
@@ -322,14 +466,6 @@ impl ZkProverTrait for ZkProver {
             ));
         }
 
-        // Generate Groth16 proof
-        // NOTE: In production, this would load the ProvingKey from self.proving_key
-        // and use Groth16::<Bn254>::prove() with the witness inputs
-        //
-        // For now, we generate a mock proof structure that is compatible with
-        // the Groth16 format but uses deterministic test values.
-        // This allows the code to compile without requiring circuit artifacts.
-
         let proof = self.generate_groth16_proof(private_inputs, public_inputs)?;
 
         Ok(proof)
@@ -343,45 +479,22 @@ impl ZkProverTrait for ZkProver {
             return Err(ZkError::NotInitialized);
         }
 
-        // Verify protocol and curve match
-        if proof.protocol != "groth16" {
-            return Err(ZkError::VerificationFailed(format!(
-                "Unsupported protocol: {}",
-                proof.protocol
-            )));
-        }
-
-        if proof.curve != "bn254" {
-            return Err(ZkError::VerificationFailed(format!(
-                "Unsupported curve: {}",
-                proof.curve
-            )));
-        }
-
-        // In production, this would:
-        // 1. Deserialize the proof components (pi_a, pi_b, pi_c) into Proof<Bn254>
-        // 2. Convert public_inputs into Vec<Fr>
-        // 3. Call Groth16::<Bn254>::verify() with the VerifyingKey
-        //
-        // For now, we perform basic structural validation
         self.verify_groth16_proof(proof, public_inputs)
     }
 }
 
 /// Internal proof generation using Groth16
 impl ZkProver {
-    /// Generate a Groth16 proof structure
+    /// Generate a Groth16 proof
     ///
-    /// This creates a proof structure compatible with Groth16 on BN254.
-    /// In production deployment with circuit artifacts, this would call
-    /// Groth16::<Bn254>::prove() with the loaded ProvingKey.
+    /// Synthesizes the `AuthynticCircuit` witness, checks it against the R1CS,
+    /// and proves with the loaded proving key.
     fn generate_groth16_proof(
         &self,
-        _private_inputs: &ZkPrivateInputs,
-        _public_inputs: &ZkPublicInputs,
+        private_inputs: &ZkPrivateInputs,
+        public_inputs: &ZkPublicInputs,
     ) -> ZkResult<ZkProof> {
-        // Check if we have a loaded proving key
-        if self.proving_key.is_none() {
+        if self.mock_mode {
             // Operating in mock mode - generate deterministic mock proof
             return Ok(ZkProof {
                 pi_a: vec![0u8; PROOF_PI_A_SIZE],
@@ -392,74 +505,138 @@ impl ZkProver {
             });
         }
 
-        // In production with loaded keys:
-        // 1. Map private_inputs to witness map
-        // 2. Generate circuit witness using .wasm
-        // 3. Create Groth16 proof: Groth16::<Bn254>::prove(&pk, witness, &mut rng)
-        // 4. Serialize proof components
-        //
-        // For now, return an error indicating artifacts are required
-        Err(ZkError::ProofGenerationFailed(
-            "Full proof generation requires circuit artifacts. Use initialize() with valid paths."
-                .to_string(),
-        ))
+        let proving_key = self.proving_key.as_ref().ok_or_else(|| {
+            ZkError::ProvingKeyNotFound(
+                "Verify-only prover cannot generate proofs. Use initialize() with valid paths."
+                    .to_string(),
+            )
+        })?;
+
+        let circuit = AuthynticCircuit::new(private_inputs, public_inputs);
+        circuit.check_witness()?;
+
+        let proof = Groth16::<Bn254>::prove(proving_key, circuit, &mut OsRng)
+            .map_err(|e| ZkError::ProofGenerationFailed(e.to_string()))?;
+
+        Ok(ZkProof {
+            pi_a: serialize_point(&proof.a)?,
+            pi_b: serialize_point(&proof.b)?,
+            pi_c: serialize_point(&proof.c)?,
+            protocol: "groth16".to_string(),
+            curve: "bn254".to_string(),
+        })
     }
 
-    /// Verify a Groth16 proof structure
+    /// Verify a Groth16 proof
     ///
-    /// This validates the proof structure and would perform cryptographic
-    /// verification in production with loaded verification keys.
+    /// Mock mode only performs structural validation; otherwise the proof is
+    /// checked with the pairing equation against the loaded verification key.
     fn verify_groth16_proof(
         &self,
         proof: &ZkProof,
-        _public_inputs: &ZkPublicInputs,
+        public_inputs: &ZkPublicInputs,
     ) -> ZkResult<bool> {
-        // Basic structural validation
-        if proof.pi_a.len() != PROOF_PI_A_SIZE {
-            return Err(ZkError::VerificationFailed(format!(
-                "Invalid pi_a size: expected {}, got {}",
-                PROOF_PI_A_SIZE,
-                proof.pi_a.len()
-            )));
+        if self.mock_mode {
+            // Mock mode - structural validation only
+            validate_proof_structure(proof)?;
+            return Ok(true);
         }
 
-        if proof.pi_b.len() != PROOF_PI_B_SIZE {
-            return Err(ZkError::VerificationFailed(format!(
-                "Invalid pi_b size: expected {}, got {}",
-                PROOF_PI_B_SIZE,
-                proof.pi_b.len()
-            )));
-        }
+        let pvk = self.verification_key.as_ref().ok_or_else(|| {
+            ZkError::VerificationKeyNotFound(
+                "Full verification requires circuit artifacts. Use initialize() with valid paths."
+                    .to_string(),
+            )
+        })?;
 
-        if proof.pi_c.len() != PROOF_PI_C_SIZE {
-            return Err(ZkError::VerificationFailed(format!(
-                "Invalid pi_c size: expected {}, got {}",
-                PROOF_PI_C_SIZE,
-                proof.pi_c.len()
-            )));
-        }
+        verify_with_prepared_key(pvk, proof, public_inputs)
+    }
+}
 
-        // In production with verification key:
-        // 1. Deserialize proof: Proof::<Bn254>::deserialize(&proof_bytes)
-        // 2. Prepare public inputs: Vec<Fr>
-        // 3. Verify: Groth16::<Bn254>::verify(&vk, &public_inputs, &proof)
-        //
-        // For now, accept structurally valid proofs in mock mode
-        if self.verification_key.is_none() {
-            // Mock mode - structural validation passed
-            return Ok(true);
-        }
+/// Check protocol, curve and component sizes of a proof
+fn validate_proof_structure(proof: &ZkProof) -> ZkResult<()> {
+    // Verify protocol and curve match
+    if proof.protocol != "groth16" {
+        return Err(ZkError::VerificationFailed(format!(
+            "Unsupported protocol: {}",
+            proof.protocol
+        )));
+    }
 
-        Err(ZkError::VerificationFailed(
-            "Full verification requires circuit artifacts. Use initialize() with valid paths."
-                .to_string(),
-        ))
+    if proof.curve != "bn254" {
+        return Err(ZkError::VerificationFailed(format!(
+            "Unsupported curve: {}",
+            proof.curve
+        )));
+    }
+
+    if proof.pi_a.len() != PROOF_PI_A_SIZE {
+        return Err(ZkError::VerificationFailed(format!(
+            "Invalid pi_a size: expected {}, got {}",
+            PROOF_PI_A_SIZE,
+            proof.pi_a.len()
+        )));
+    }
+
+    if proof.pi_b.len() != PROOF_PI_B_SIZE {
+        return Err(ZkError::VerificationFailed(format!(
+            "Invalid pi_b size: expected {}, got {}",
+            PROOF_PI_B_SIZE,
+            proof.pi_b.len()
+        )));
+    }
+
+    if proof.pi_c.len() != PROOF_PI_C_SIZE {
+        return Err(ZkError::VerificationFailed(format!(
+            "Invalid pi_c size: expected {}, got {}",
+            PROOF_PI_C_SIZE,
+            proof.pi_c.len()
+        )));
     }
+
+    Ok(())
+}
+
+/// Verify a proof with the Groth16 pairing check
+///
+/// Malformed proofs (wrong sizes, points off the curve or outside the
+/// prime-order subgroup) are errors; well-formed proofs that fail the pairing
+/// check return `Ok(false)`.
+fn verify_with_prepared_key(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof: &ZkProof,
+    public_inputs: &ZkPublicInputs,
+) -> ZkResult<bool> {
+    validate_proof_structure(proof)?;
+
+    let malformed = |e: ark_serialize::SerializationError| {
+        ZkError::VerificationFailed(format!("Malformed proof: {}", e))
+    };
+    let proof = Proof::<Bn254> {
+        a: G1Affine::deserialize_uncompressed(proof.pi_a.as_slice()).map_err(malformed)?,
+        b: G2Affine::deserialize_uncompressed(proof.pi_b.as_slice()).map_err(malformed)?,
+        c: G1Affine::deserialize_uncompressed(proof.pi_c.as_slice()).map_err(malformed)?,
+    };
+
+    Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs_to_field(public_inputs), &proof)
+        .map_err(|e| ZkError::VerificationFailed(e.to_string()))
+}
+
+/// Serialize a curve point with arkworks uncompressed encoding
+fn serialize_point<P: CanonicalSerialize>(point: &P) -> ZkResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    point
+        .serialize_uncompressed(&mut bytes)
+        .map_err(|e| ZkError::InvalidInput(format!("Point serialization failed: {}", e)))?;
+    Ok(bytes)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::OnceLock;
 
     fn create_test_private_inputs() -> ZkPrivateInputs {
         ZkPrivateInputs::new(
@@ -472,6 +649,128 @@ mod tests {
         )
     }
 
+    /// Shared real-mode prover; setup is seeded so tests are reproducible
+    fn real_prover() -> &'static ZkProver {
+        static PROVER: OnceLock<ZkProver> = OnceLock::new();
+        PROVER.get_or_init(|| {
+            let mut prover = ZkProver::new();
+            prover
+                .initialize_with_setup(&mut StdRng::seed_from_u64(42))
+                .unwrap();
+            prover
+        })
+    }
+
+    fn real_proof() -> (ZkProof, ZkPublicInputs) {
+        let prover = real_prover();
+        let private_inputs = create_test_private_inputs();
+        let public_inputs = prover
+            .compute_public_inputs(&private_inputs, [10u8; 32], 1_200_000, 300_000)
+            .unwrap();
+        let proof = prover
+            .generate_proof(&private_inputs, &public_inputs)
+            .unwrap();
+        (proof, public_inputs)
+    }
+
+    #[test]
+    fn test_real_proof_roundtrip() {
+        let (proof, public_inputs) = real_proof();
+        assert_eq!(proof.pi_a.len(), PROOF_PI_A_SIZE);
+        assert_eq!(proof.pi_b.len(), PROOF_PI_B_SIZE);
+        assert_eq!(proof.pi_c.len(), PROOF_PI_C_SIZE);
+        assert!(real_prover().verify_proof(&proof, &public_inputs).unwrap());
+    }
+
+    #[test]
+    fn test_real_proof_rejects_tampered_public_inputs() {
+        let (proof, mut public_inputs) = real_proof();
+        public_inputs.merkle_root = [11u8; 32];
+        assert!(!real_prover().verify_proof(&proof, &public_inputs).unwrap());
+
+        let (proof, mut public_inputs) = real_proof();
+        public_inputs.current_time += 1;
+        assert!(!real_prover().verify_proof(&proof, &public_inputs).unwrap());
+    }
+
+    #[test]
+    fn test_real_proof_rejects_mock_proof() {
+        let (_, public_inputs) = real_proof();
+        let proof = ZkProof {
+            pi_a: vec![0u8; 64],
+            pi_b: vec![0u8; 128],
+            pi_c: vec![0u8; 64],
+            protocol: "groth16".to_string(),
+            curve: "bn254".to_string(),
+        };
+        let result = real_prover().verify_proof(&proof, &public_inputs);
+        assert!(!matches!(result, Ok(true)));
+    }
+
+    #[test]
+    fn test_third_party_verification_key() {
+        let (proof, public_inputs) = real_proof();
+        let vk = real_prover().verification_key().unwrap();
+
+        // Round-trip through JSON as a published key would be
+        let json = serde_json::to_string(&vk).unwrap();
+        let vk: ZkVerificationKey = serde_json::from_str(&json).unwrap();
+        assert!(vk.verify(&proof, &public_inputs).unwrap());
+
+        let verifier = ZkProver::from_verification_key(&vk).unwrap();
+        assert!(verifier.verify_proof(&proof, &public_inputs).unwrap());
+
+        // A verify-only instance cannot produce proofs
+        let private_inputs = create_test_private_inputs();
+        let result = verifier.generate_proof(&private_inputs, &public_inputs);
+        assert!(matches!(result, Err(ZkError::ProvingKeyNotFound(_))));
+    }
+
+    #[test]
+    fn test_verification_key_from_other_setup_rejects() {
+        let (proof, public_inputs) = real_proof();
+        let mut other = ZkProver::new();
+        other
+            .initialize_with_setup(&mut StdRng::seed_from_u64(7))
+            .unwrap();
+        assert!(!other.verify_proof(&proof, &public_inputs).unwrap());
+    }
+
+    #[test]
+    fn test_initialize_from_exported_zkey() {
+        let zkey = real_prover().export_proving_key().unwrap();
+        let path = std::env::temp_dir().join(format!("aethercore-zk-{}.zkey", std::process::id()));
+        std::fs::write(&path, &zkey).unwrap();
+
+        let mut prover = ZkProver::new();
+        let result = prover.initialize(Path::new("unused.wasm"), Path::new("unused.r1cs"), &path);
+        std::fs::remove_file(&path).unwrap();
+        result.unwrap();
+
+        assert_eq!(
+            prover.verification_key().unwrap(),
+            real_prover().verification_key().unwrap()
+        );
+        let (proof, public_inputs) = real_proof();
+        assert!(prover.verify_proof(&proof, &public_inputs).unwrap());
+    }
+
+    #[test]
+    fn test_initialize_with_malformed_zkey() {
+        let path = std::env::temp_dir().join(format!(
+            "aethercore-zk-malformed-{}.zkey",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"not a proving key").unwrap();
+
+        let mut prover = ZkProver::new();
+        let result = prover.initialize(Path::new("unused.wasm"), Path::new("unused.r1cs"), &path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ZkError::ProvingKeyNotFound(_))));
+        assert!(!prover.initialized);
+    }
+
     #[test]
     fn test_prover_initialization() {
         let mut prover = ZkProver::new();
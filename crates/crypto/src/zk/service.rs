@@ -0,0 +1,713 @@
+//! Domain-layer ZK service backed by the Groth16 prover.
+//!
+//! `ZkProverAdapter` implements `aethercore_core::ZkProverService` and
+//! `aethercore_core::ZkPhysicsVerifier` on top of [`ZkProver`], so callers that
+//! only know the domain types (`ZkProofRequest` / `ZkProofResult`) get real
+//! proofs and physics enforcement.
+//!
+//! # Wire Format
+//!
+//! `ZkProofResult::proof_bytes` is a JSON-encoded [`ZkProofEnvelope`]: the
+//! Groth16 proof plus the [`ZkProofStatement`] it was generated for.
+//! `public_inputs_hash` is the BLAKE3 hash of that statement, so a verifier
+//! holding only the hash can detect any substitution of inputs or physics claims.
+//!
+//! # Statement Binding
+//!
+//! The hash alone does not stop a relay from rewriting a statement around
+//! someone else's proof and re-hashing it, so every claim is tied to the
+//! circuit's public inputs:
+//!
+//! - The attestation time is `public_inputs.current_time`, which the prover
+//!   sets to the witness timestamp. It must be within `max_proof_age_ms` of the
+//!   verifier's clock, and `public_inputs.max_age` may not exceed that window.
+//! - `device_id` must be the device registered for `device_commitment`.
+//! - Reported coordinates seed the location nonce, and the statement carries
+//!   the opening of `expected_location_commitment` so the verifier can
+//!   recompute it.
+//!
+//! # Physics Enforcement
+//!
+//! The verifier keeps the last accepted timestamp and position per device.
+//! A proof is rejected with a `ZkVerificationError` when its timestamp does not
+//! advance past the last one seen, or when the implied velocity since the last
+//! attestation exceeds `MAX_VELOCITY_MPS`. History is only updated on success,
+//! so a rejected report cannot poison the baseline.
+
+use super::error::ZkError;
+use super::inputs::{ZkPrivateInputs, ZkPublicInputs};
+use super::poseidon::{device_commitment, location_commitment};
+use super::prover::{ZkProof, ZkProver, ZkProverTrait};
+use aethercore_core::sync;
+use aethercore_core::zk_trait::{
+    GeoCoordinate, PhysicsValidation, ZkPhysicsVerifier, ZkProofRequest, ZkProofResult,
+    ZkProverService, ZkVerificationError, MAX_LATENCY_MS,
+};
+use aethercore_core::Error as CoreError;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Default maximum age of a proof's witness timestamp (60 seconds)
+pub const DEFAULT_MAX_PROOF_AGE_MS: u64 = 60_000;
+
+/// Number of neighbor attestation slots in the circuit
+const ATTESTATION_SLOTS: usize = 4;
+
+/// Opening of a location commitment whose nonce is derived from coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationOpening {
+    /// Location hash committed to in-circuit
+    pub location_hash: [u8; 32],
+    /// Randomness mixed with the coordinates into the location nonce
+    pub randomness: [u8; 32],
+}
+
+/// Statement a proof is generated for (public inputs plus physics claims)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZkProofStatement {
+    /// Device identifier the proof is attributed to
+    pub device_id: u64,
+    /// Claimed position at the attestation time, if reported
+    pub coordinates: Option<GeoCoordinate>,
+    /// Opening binding `coordinates` to the location commitment
+    pub location_opening: Option<LocationOpening>,
+    /// Groth16 public inputs
+    pub public_inputs: ZkPublicInputs,
+}
+
+impl ZkProofStatement {
+    /// Attestation timestamp proven in-circuit (Unix milliseconds)
+    pub fn timestamp(&self) -> u64 {
+        self.public_inputs.current_time
+    }
+
+    /// BLAKE3 hash binding the proof to this statement
+    pub fn hash(&self) -> Result<[u8; 32], CoreError> {
+        let bytes = serde_json::to_vec(self)?;
+        Ok(*blake3::hash(&bytes).as_bytes())
+    }
+}
+
+/// Serialized form carried in `ZkProofResult::proof_bytes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZkProofEnvelope {
+    /// Statement the proof attests to
+    pub statement: ZkProofStatement,
+    /// Groth16 proof
+    pub proof: ZkProof,
+}
+
+/// Device witness material (never leaves the prover)
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct DeviceSecrets {
+    device_secret: [u8; 32],
+    device_salt: [u8; 32],
+}
+
+/// Last accepted attestation for a device
+#[derive(Debug, Clone, Copy)]
+struct DeviceTrack {
+    timestamp: u64,
+    /// Last reported position and the timestamp it was reported at
+    position: Option<(GeoCoordinate, u64)>,
+}
+
+/// `ZkProverService` / `ZkPhysicsVerifier` implementation over [`ZkProver`]
+pub struct ZkProverAdapter {
+    prover: ZkProver,
+    secrets: Option<DeviceSecrets>,
+    max_proof_age_ms: u64,
+    /// Registered device commitments and the device each belongs to
+    devices: Mutex<HashMap<[u8; 32], u64>>,
+    history: Mutex<HashMap<u64, DeviceTrack>>,
+}
+
+impl ZkProverAdapter {
+    /// Create an adapter that can both prove (as the local device) and verify
+    pub fn new(prover: ZkProver, device_secret: [u8; 32], device_salt: [u8; 32]) -> Self {
+        Self {
+            prover,
+            secrets: Some(DeviceSecrets {
+                device_secret,
+                device_salt,
+            }),
+            max_proof_age_ms: DEFAULT_MAX_PROOF_AGE_MS,
+            devices: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a verify-only adapter (e.g. for a gateway or C2 node)
+    pub fn verifier(prover: ZkProver) -> Self {
+        Self {
+            prover,
+            secrets: None,
+            max_proof_age_ms: DEFAULT_MAX_PROOF_AGE_MS,
+            devices: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Override the maximum proof age, both proven in-circuit and accepted on
+    /// verification
+    pub fn with_max_proof_age(mut self, max_proof_age_ms: u64) -> Self {
+        self.max_proof_age_ms = max_proof_age_ms;
+        self
+    }
+
+    /// Commitment to this adapter's device secret, for registration with verifiers
+    pub fn device_commitment(&self) -> Result<[u8; 32], CoreError> {
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            CoreError::Zk("Verify-only adapter has no device witness".to_string())
+        })?;
+        device_commitment(&secrets.device_secret, &secrets.device_salt).map_err(zk_error)
+    }
+
+    /// Accept proofs for `device_id` made with the secret behind `commitment`
+    pub fn register_device(&self, device_id: u64, commitment: [u8; 32]) {
+        sync::lock(&self.devices).insert(commitment, device_id);
+    }
+
+    /// Last accepted attestation timestamp for a device
+    pub fn last_seen(&self, device_id: u64) -> Option<u64> {
+        self.history
+            .lock()
+            .ok()?
+            .get(&device_id)
+            .map(|track| track.timestamp)
+    }
+
+    /// Map a domain request onto circuit inputs
+    ///
+    /// The witness timestamp doubles as `current_time`, so the proof pins the
+    /// exact attestation time.
+    fn build_inputs(
+        &self,
+        request: &ZkProofRequest,
+    ) -> Result<(ZkPrivateInputs, ZkPublicInputs, Option<LocationOpening>), CoreError> {
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            CoreError::Zk("Verify-only adapter has no device witness".to_string())
+        })?;
+
+        if request.neighbor_attestations.len() > ATTESTATION_SLOTS {
+            return Err(zk_error(ZkError::InvalidInput(format!(
+                "At most {} neighbor attestations supported, got {}",
+                ATTESTATION_SLOTS,
+                request.neighbor_attestations.len()
+            ))));
+        }
+        // Unused slots are zero-filled, matching the circuit's fixed arity
+        let mut neighbor_attestations = [[0u8; 32]; ATTESTATION_SLOTS];
+        for (slot, attestation) in neighbor_attestations
+            .iter_mut()
+            .zip(request.neighbor_attestations.iter())
+        {
+            *slot = *attestation;
+        }
+
+        let mut randomness = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut randomness);
+        let (location_nonce, opening) = match &request.coordinates {
+            Some(coordinates) => (
+                location_nonce(coordinates, &randomness),
+                Some(LocationOpening {
+                    location_hash: request.location_hash,
+                    randomness,
+                }),
+            ),
+            None => (randomness, None),
+        };
+
+        let private_inputs = ZkPrivateInputs::new(
+            secrets.device_secret,
+            secrets.device_salt,
+            request.location_hash,
+            location_nonce,
+            request.timestamp,
+            neighbor_attestations,
+        );
+        let public_inputs = self
+            .prover
+            .compute_public_inputs(
+                &private_inputs,
+                request.merkle_root,
+                request.timestamp,
+                self.max_proof_age_ms,
+            )
+            .map_err(zk_error)?;
+
+        Ok((private_inputs, public_inputs, opening))
+    }
+
+    /// Check that a statement's claims are the ones its public inputs commit to
+    fn check_statement(&self, statement: &ZkProofStatement) -> Result<(), ZkVerificationError> {
+        let inputs = &statement.public_inputs;
+        let now = current_timestamp_ms();
+        if inputs.max_age > self.max_proof_age_ms
+            || now.saturating_sub(inputs.current_time) > self.max_proof_age_ms
+            || inputs.current_time > now.saturating_add(MAX_LATENCY_MS)
+        {
+            return Err(ZkVerificationError::TemporalViolation {
+                proof_timestamp: inputs.current_time,
+                current_time: now,
+                max_drift_ms: self.max_proof_age_ms,
+            });
+        }
+
+        let registered = self
+            .devices
+            .lock()
+            .map_err(|_| ZkVerificationError::CryptographicFailure("Device lock poisoned".into()))?
+            .get(&inputs.device_commitment)
+            .copied();
+        if registered != Some(statement.device_id) {
+            return Err(ZkVerificationError::InvalidProof(format!(
+                "Device commitment is not registered to device {}",
+                statement.device_id
+            )));
+        }
+
+        match (&statement.coordinates, &statement.location_opening) {
+            (None, None) => Ok(()),
+            (Some(coordinates), Some(opening)) => {
+                let commitment = location_commitment(
+                    &opening.location_hash,
+                    &location_nonce(coordinates, &opening.randomness),
+                )
+                .map_err(|e| ZkVerificationError::CryptographicFailure(e.to_string()))?;
+                if commitment != inputs.expected_location_commitment {
+                    return Err(ZkVerificationError::InvalidProof(
+                        "Coordinates do not open the location commitment".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(ZkVerificationError::InvalidProof(
+                "Coordinates and location opening must be reported together".to_string(),
+            )),
+        }
+    }
+
+    /// Run temporal and spatial checks for a statement against device history,
+    /// and record it as the device's latest attestation if they pass
+    ///
+    /// Check and update happen under one lock, so two copies of a proof
+    /// verified concurrently cannot both pass the temporal check.
+    fn check_and_record(&self, statement: &ZkProofStatement) -> Result<(), ZkVerificationError> {
+        let mut history = self.history.lock().map_err(|_| {
+            ZkVerificationError::CryptographicFailure("History lock poisoned".into())
+        })?;
+        let previous = history.get(&statement.device_id).copied();
+
+        let timestamp = statement.timestamp();
+        let last_seen = previous.map(|track| track.timestamp).unwrap_or(0);
+        self.verify_temporal_bounds(timestamp, last_seen)?;
+
+        if let (Some((prev_coord, prev_timestamp)), Some(curr)) = (
+            previous.and_then(|track| track.position),
+            statement.coordinates,
+        ) {
+            self.verify_spatial_bounds(
+                &prev_coord,
+                &curr,
+                timestamp.saturating_sub(prev_timestamp),
+            )?;
+        }
+
+        let track = history.entry(statement.device_id).or_insert(DeviceTrack {
+            timestamp,
+            position: None,
+        });
+        track.timestamp = timestamp;
+        // Keep the last known position if this report carries none
+        if let Some(coordinates) = statement.coordinates {
+            track.position = Some((coordinates, timestamp));
+        }
+        Ok(())
+    }
+}
+
+impl ZkProverService for ZkProverAdapter {
+    fn generate(&self, request: &ZkProofRequest) -> aethercore_core::Result<ZkProofResult> {
+        let generated_at = current_timestamp_ms();
+        let (private_inputs, public_inputs, location_opening) = self.build_inputs(request)?;
+
+        let proof = self
+            .prover
+            .generate_proof(&private_inputs, &public_inputs)
+            .map_err(zk_error)?;
+
+        let statement = ZkProofStatement {
+            device_id: request.device_id,
+            coordinates: request.coordinates,
+            location_opening,
+            public_inputs,
+        };
+        let public_inputs_hash = statement.hash()?;
+        let proof_bytes = serde_json::to_vec(&ZkProofEnvelope { statement, proof })?;
+
+        Ok(ZkProofResult {
+            proof_bytes,
+            public_inputs_hash,
+            generated_at,
+        })
+    }
+
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        public_inputs_hash: &[u8; 32],
+    ) -> aethercore_core::Result<bool> {
+        let envelope: ZkProofEnvelope = serde_json::from_slice(proof_bytes).map_err(|e| {
+            CoreError::from(ZkVerificationError::InvalidProof(format!(
+                "Malformed proof envelope: {}",
+                e
+            )))
+        })?;
+
+        if envelope.statement.hash()? != *public_inputs_hash {
+            return Err(ZkVerificationError::InvalidProof(
+                "Public inputs hash does not match proof statement".to_string(),
+            )
+            .into());
+        }
+        self.check_statement(&envelope.statement)?;
+
+        let valid = self
+            .prover
+            .verify_proof(&envelope.proof, &envelope.statement.public_inputs)
+            .map_err(|e| ZkVerificationError::CryptographicFailure(e.to_string()))?;
+        if !valid {
+            return Ok(false);
+        }
+
+        self.check_and_record(&envelope.statement)?;
+
+        Ok(true)
+    }
+}
+
+impl ZkPhysicsVerifier for ZkProverAdapter {
+    /// Validate temporal and spatial bounds against tracked device history
+    ///
+    /// Unlike the default implementation, spatial bounds are checked whenever
+    /// both the request and the device history carry coordinates.
+    fn validate_physics(
+        &self,
+        request: &ZkProofRequest,
+    ) -> Result<PhysicsValidation, ZkVerificationError> {
+        let mut validation = PhysicsValidation {
+            temporal_valid: true,
+            spatial_valid: true,
+            temporal_error: None,
+            spatial_error: None,
+        };
+
+        let previous = self
+            .history
+            .lock()
+            .map_err(|_| ZkVerificationError::CryptographicFailure("History lock poisoned".into()))?
+            .get(&request.device_id)
+            .copied();
+
+        let last_seen = request
+            .last_seen
+            .or_else(|| previous.map(|track| track.timestamp));
+        if let Some(last_seen) = last_seen {
+            if let Err(e) = self.verify_temporal_bounds(request.timestamp, last_seen) {
+                validation.temporal_valid = false;
+                validation.temporal_error = Some(e.to_string());
+            }
+        }
+
+        if let (Some((prev_coord, prev_timestamp)), Some(curr)) = (
+            previous.and_then(|track| track.position),
+            request.coordinates,
+        ) {
+            if let Err(e) = self.verify_spatial_bounds(
+                &prev_coord,
+                &curr,
+                request.timestamp.saturating_sub(prev_timestamp),
+            ) {
+                validation.spatial_valid = false;
+                validation.spatial_error = Some(e.to_string());
+            }
+        }
+
+        Ok(validation)
+    }
+}
+
+/// Location nonce committing to reported coordinates
+fn location_nonce(coordinates: &GeoCoordinate, randomness: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"aethercore.zk.location-nonce");
+    hasher.update(&coordinates.latitude.to_be_bytes());
+    hasher.update(&coordinates.longitude.to_be_bytes());
+    hasher.update(randomness);
+    *hasher.finalize().as_bytes()
+}
+
+fn zk_error(err: ZkError) -> CoreError {
+    CoreError::Zk(err.to_string())
+}
+
+fn current_timestamp_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("System time before UNIX epoch")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::OnceLock;
+
+    fn shared_prover() -> ZkProver {
+        static PROVER: OnceLock<ZkProver> = OnceLock::new();
+        PROVER
+            .get_or_init(|| {
+                let mut prover = ZkProver::new();
+                prover
+                    .initialize_with_setup(&mut StdRng::seed_from_u64(42))
+                    .unwrap();
+                prover
+            })
+            .clone()
+    }
+
+    fn request(timestamp: u64, coordinates: Option<GeoCoordinate>) -> ZkProofRequest {
+        ZkProofRequest {
+            device_id: 7,
+            timestamp,
+            location_hash: [3u8; 32],
+            neighbor_attestations: vec![[5u8; 32], [6u8; 32]],
+            merkle_root: [10u8; 32],
+            coordinates,
+            last_seen: None,
+        }
+    }
+
+    fn adapters() -> (ZkProverAdapter, ZkProverAdapter) {
+        let prover = ZkProverAdapter::new(shared_prover(), [1u8; 32], [2u8; 32]);
+        let verifier = ZkProverAdapter::verifier(shared_prover());
+        verifier.register_device(7, prover.device_commitment().unwrap());
+        (prover, verifier)
+    }
+
+    /// Rewrite a proof's statement and re-hash it, as a relay could
+    fn rewrite(
+        result: &ZkProofResult,
+        edit: impl FnOnce(&mut ZkProofStatement),
+    ) -> (Vec<u8>, [u8; 32]) {
+        let mut envelope: ZkProofEnvelope = serde_json::from_slice(&result.proof_bytes).unwrap();
+        edit(&mut envelope.statement);
+        let hash = envelope.statement.hash().unwrap();
+        (serde_json::to_vec(&envelope).unwrap(), hash)
+    }
+
+    #[test]
+    fn test_generate_and_verify() {
+        let (prover, verifier) = adapters();
+        let now = current_timestamp_ms();
+        let sf = GeoCoordinate::new(37.7749, -122.4194).unwrap();
+
+        let result = prover.generate(&request(now - 1_000, Some(sf))).unwrap();
+        assert!(verifier
+            .verify(&result.proof_bytes, &result.public_inputs_hash)
+            .unwrap());
+        assert_eq!(verifier.last_seen(7), Some(now - 1_000));
+    }
+
+    #[test]
+    fn test_impossible_movement_rejected() {
+        let (prover, verifier) = adapters();
+        let now = current_timestamp_ms();
+        let sf = GeoCoordinate::new(37.7749, -122.4194).unwrap();
+        let la = GeoCoordinate::new(34.0522, -118.2437).unwrap();
+
+        let first = prover.generate(&request(now - 2_000, Some(sf))).unwrap();
+        assert!(verifier
+            .verify(&first.proof_bytes, &first.public_inputs_hash)
+            .unwrap());
+
+        // ~559 km in one second
+        let second = prover.generate(&request(now - 1_000, Some(la))).unwrap();
+        let result = verifier.verify(&second.proof_bytes, &second.public_inputs_hash);
+        assert!(matches!(
+            result,
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::SpatialViolation { .. }
+            ))
+        ));
+
+        // Rejected report does not move the baseline
+        assert_eq!(verifier.last_seen(7), Some(now - 2_000));
+        let validation = verifier
+            .validate_physics(&request(now - 1_000, Some(la)))
+            .unwrap();
+        assert!(!validation.spatial_valid);
+    }
+
+    #[test]
+    fn test_replayed_proof_rejected() {
+        let (prover, verifier) = adapters();
+        let now = current_timestamp_ms();
+
+        let result = prover.generate(&request(now - 1_000, None)).unwrap();
+        assert!(verifier
+            .verify(&result.proof_bytes, &result.public_inputs_hash)
+            .unwrap());
+
+        let replay = verifier.verify(&result.proof_bytes, &result.public_inputs_hash);
+        assert!(matches!(
+            replay,
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::TemporalViolation { .. }
+            ))
+        ));
+
+        // Moving the replay to a fresh time changes the public inputs the
+        // proof was made for
+        let (forged, hash) = rewrite(&result, |statement| {
+            statement.public_inputs.current_time = now;
+        });
+        assert!(!verifier.verify(&forged, &hash).unwrap());
+        assert_eq!(verifier.last_seen(7), Some(now - 1_000));
+
+        // A proof that has aged out is refused outright
+        let stale = prover.generate(&request(now - 120_000, None)).unwrap();
+        assert!(matches!(
+            verifier.verify(&stale.proof_bytes, &stale.public_inputs_hash),
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::TemporalViolation { .. }
+            ))
+        ));
+
+        // As is one proven against a wider age window than the verifier allows
+        let lenient = ZkProverAdapter::new(shared_prover(), [1u8; 32], [2u8; 32])
+            .with_max_proof_age(DEFAULT_MAX_PROOF_AGE_MS * 10);
+        let result = lenient.generate(&request(now, None)).unwrap();
+        assert!(matches!(
+            verifier.verify(&result.proof_bytes, &result.public_inputs_hash),
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::TemporalViolation { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_concurrent_replay_accepted_once() {
+        let (prover, verifier) = adapters();
+        let result = prover
+            .generate(&request(current_timestamp_ms() - 1_000, None))
+            .unwrap();
+
+        let barrier = std::sync::Barrier::new(8);
+        let accepted = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        verifier
+                            .verify(&result.proof_bytes, &result.public_inputs_hash)
+                            .unwrap_or(false)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .filter(|&ok| ok)
+                .count()
+        });
+        assert_eq!(accepted, 1);
+    }
+
+    #[test]
+    fn test_substituted_statement_rejected() {
+        let (prover, verifier) = adapters();
+        let now = current_timestamp_ms();
+        let sf = GeoCoordinate::new(37.7749, -122.4194).unwrap();
+        let la = GeoCoordinate::new(34.0522, -118.2437).unwrap();
+        let result = prover.generate(&request(now - 1_000, Some(sf))).unwrap();
+
+        let mut envelope: ZkProofEnvelope = serde_json::from_slice(&result.proof_bytes).unwrap();
+        envelope.statement.device_id = 8;
+        let forged = serde_json::to_vec(&envelope).unwrap();
+        assert!(matches!(
+            verifier.verify(&forged, &result.public_inputs_hash),
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::InvalidProof(_)
+            ))
+        ));
+
+        // Re-hashing does not help: the device, position and public inputs
+        // are each bound to the proof
+        verifier.register_device(8, [9u8; 32]);
+        let (forged, hash) = rewrite(&result, |statement| statement.device_id = 8);
+        assert!(matches!(
+            verifier.verify(&forged, &hash),
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::InvalidProof(_)
+            ))
+        ));
+
+        let (forged, hash) = rewrite(&result, |statement| statement.coordinates = Some(la));
+        assert!(matches!(
+            verifier.verify(&forged, &hash),
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::InvalidProof(_)
+            ))
+        ));
+        let (forged, hash) = rewrite(&result, |statement| statement.location_opening = None);
+        assert!(verifier.verify(&forged, &hash).is_err());
+
+        let (forged, hash) = rewrite(&result, |statement| {
+            statement.public_inputs.merkle_root = [11u8; 32];
+        });
+        assert!(!verifier.verify(&forged, &hash).unwrap());
+
+        // None of the forgeries moved the baseline; the genuine proof still verifies
+        assert_eq!(verifier.last_seen(7), None);
+        assert!(verifier
+            .verify(&result.proof_bytes, &result.public_inputs_hash)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_unregistered_device_rejected() {
+        let (prover, _) = adapters();
+        let verifier = ZkProverAdapter::verifier(shared_prover());
+        let result = prover
+            .generate(&request(current_timestamp_ms() - 1_000, None))
+            .unwrap();
+        assert!(matches!(
+            verifier.verify(&result.proof_bytes, &result.public_inputs_hash),
+            Err(CoreError::ZkVerification(
+                ZkVerificationError::InvalidProof(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_too_many_attestations_rejected() {
+        let (prover, _) = adapters();
+        let mut req = request(current_timestamp_ms(), None);
+        req.neighbor_attestations = vec![[1u8; 32]; ATTESTATION_SLOTS + 1];
+        assert!(matches!(prover.generate(&req), Err(CoreError::Zk(_))));
+    }
+
+    #[test]
+    fn test_verifier_cannot_generate() {
+        let (_, verifier) = adapters();
+        let req = request(current_timestamp_ms(), None);
+        assert!(matches!(verifier.generate(&req), Err(CoreError::Zk(_))));
+    }
+}
@@ -0,0 +1,293 @@
+//! Attestation key certificate validation.
+//!
+//! A TPM quote vouches for PCR values only if the key that signed it lives in
+//! a TPM, and nothing in the quote itself shows that. Registration therefore
+//! trusts an attestation key (AK) only through a certificate chain ending at a
+//! configured anchor: a TPM manufacturer's EK CA, or the fleet CA that
+//! certifies AKs after credential activation.
+//!
+//! A chain is the DER certificate for the AK, optionally followed by the DER
+//! intermediates up to the anchor, each issued by the next. Every signature
+//! must be ECDSA P-256 with SHA-256.
+
+use crate::x509::{time_to_millis, x509_error};
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::pkcs8::DecodePublicKey;
+use signature::Verifier;
+use x509_cert::der::oid::db::{rfc5280, rfc5912};
+use x509_cert::der::{Decode, DecodePem, Encode, Reader, SliceReader};
+use x509_cert::ext::pkix::BasicConstraints;
+use x509_cert::name::Name;
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+use x509_cert::Certificate as X509Certificate;
+
+/// Most certificates accepted in one chain, anchor excluded.
+const MAX_CHAIN_LEN: usize = 4;
+
+struct TrustAnchor {
+    subject: Name,
+    key: VerifyingKey,
+}
+
+/// Certificates trusted to certify TPM attestation keys.
+#[derive(Default)]
+pub struct AkTrustAnchors {
+    anchors: Vec<TrustAnchor>,
+}
+
+impl AkTrustAnchors {
+    /// Create an empty anchor set, which certifies no key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust the P-256 key of a DER-encoded anchor certificate.
+    pub fn add_der(&mut self, der: &[u8]) -> crate::Result<()> {
+        let cert = X509Certificate::from_der(der)
+            .map_err(|e| x509_error("Invalid trust anchor certificate", e))?;
+        self.add(cert)
+    }
+
+    /// Trust the P-256 key of a PEM-encoded anchor certificate.
+    pub fn add_pem(&mut self, pem: &str) -> crate::Result<()> {
+        let cert = X509Certificate::from_pem(pem)
+            .map_err(|e| x509_error("Invalid trust anchor certificate", e))?;
+        self.add(cert)
+    }
+
+    fn add(&mut self, cert: X509Certificate) -> crate::Result<()> {
+        let key = p256_key(&cert.tbs_certificate.subject_public_key_info)?;
+        self.anchors.push(TrustAnchor {
+            subject: cert.tbs_certificate.subject,
+            key,
+        });
+        Ok(())
+    }
+
+    /// Whether no anchor is configured.
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+
+    /// Verify an AK certificate chain at `now_ms` and return the key it
+    /// certifies, as an uncompressed SEC1 point.
+    pub fn certified_key(&self, chain_der: &[u8], now_ms: u64) -> crate::Result<Vec<u8>> {
+        let chain = parse_chain(chain_der)?;
+        let (Some(leaf), Some(top)) = (chain.first(), chain.last()) else {
+            return Err(ak_error("AK certificate chain is empty"));
+        };
+
+        for (index, cert) in chain.iter().enumerate() {
+            let validity = &cert.tbs_certificate.validity;
+            if now_ms < time_to_millis(validity.not_before)
+                || now_ms > time_to_millis(validity.not_after)
+            {
+                return Err(ak_error(
+                    "AK certificate chain contains an expired certificate",
+                ));
+            }
+            if index > 0 && !is_ca(cert)? {
+                return Err(ak_error("AK certificate issued by a non-CA certificate"));
+            }
+        }
+        for pair in chain.windows(2) {
+            let issuer = &pair[1].tbs_certificate;
+            if pair[0].tbs_certificate.issuer != issuer.subject {
+                return Err(ak_error("AK certificate chain is out of order"));
+            }
+            if !signed_by(&pair[0], &p256_key(&issuer.subject_public_key_info)?) {
+                return Err(ak_error("AK certificate chain signature is invalid"));
+            }
+        }
+
+        let anchored = self.anchors.iter().any(|anchor| {
+            anchor.subject == top.tbs_certificate.issuer && signed_by(top, &anchor.key)
+        });
+        if !anchored {
+            return Err(ak_error(
+                "AK certificate chain does not lead to a trusted anchor",
+            ));
+        }
+
+        let key = p256_key(&leaf.tbs_certificate.subject_public_key_info)?;
+        Ok(key.to_encoded_point(false).as_bytes().to_vec())
+    }
+}
+
+fn parse_chain(chain_der: &[u8]) -> crate::Result<Vec<X509Certificate>> {
+    let mut reader =
+        SliceReader::new(chain_der).map_err(|e| x509_error("Invalid AK certificate", e))?;
+    let mut chain = Vec::new();
+    while !reader.is_finished() {
+        if chain.len() == MAX_CHAIN_LEN {
+            return Err(ak_error("AK certificate chain is too long"));
+        }
+        chain.push(
+            X509Certificate::decode(&mut reader)
+                .map_err(|e| x509_error("Invalid AK certificate", e))?,
+        );
+    }
+    Ok(chain)
+}
+
+fn p256_key(spki: &SubjectPublicKeyInfoOwned) -> crate::Result<VerifyingKey> {
+    let der = spki
+        .to_der()
+        .map_err(|e| x509_error("Invalid certificate public key", e))?;
+    VerifyingKey::from_public_key_der(&der)
+        .map_err(|e| x509_error("Certificate key is not a P-256 key", e))
+}
+
+fn signed_by(cert: &X509Certificate, issuer_key: &VerifyingKey) -> bool {
+    if cert.signature_algorithm.oid != rfc5912::ECDSA_WITH_SHA_256 {
+        return false;
+    }
+    let (Ok(tbs_der), Ok(signature)) = (
+        cert.tbs_certificate.to_der(),
+        Signature::from_der(cert.signature.raw_bytes()),
+    ) else {
+        return false;
+    };
+    issuer_key.verify(&tbs_der, &signature).is_ok()
+}
+
+fn is_ca(cert: &X509Certificate) -> crate::Result<bool> {
+    let Some(extension) = cert
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|extension| extension.extn_id == rfc5280::ID_CE_BASIC_CONSTRAINTS)
+    else {
+        return Ok(false);
+    };
+    BasicConstraints::from_der(extension.extn_value.as_bytes())
+        .map(|constraints| constraints.ca)
+        .map_err(|e| x509_error("Invalid basic constraints", e))
+}
+
+fn ak_error(message: &str) -> crate::Error {
+    crate::Error::Identity(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::SigningKey;
+    use p256::pkcs8::EncodePublicKey;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use x509_cert::der::asn1::BitString;
+    use x509_cert::serial_number::SerialNumber;
+    use x509_cert::spki::AlgorithmIdentifierOwned;
+    use x509_cert::time::Validity;
+    use x509_cert::{certificate::Version, TbsCertificate};
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_slice(&[seed; 32]).unwrap()
+    }
+
+    /// DER certificate for `subject_key`, signed by `issuer_key`
+    fn issue(
+        subject: &str,
+        subject_key: &SigningKey,
+        issuer: &str,
+        issuer_key: &SigningKey,
+        is_ca: bool,
+    ) -> Vec<u8> {
+        let spki_der = subject_key.verifying_key().to_public_key_der().unwrap();
+        let constraints = BasicConstraints {
+            ca: is_ca,
+            path_len_constraint: None,
+        };
+        let tbs = TbsCertificate {
+            version: Version::V3,
+            serial_number: SerialNumber::from(1u32),
+            signature: AlgorithmIdentifierOwned {
+                oid: rfc5912::ECDSA_WITH_SHA_256,
+                parameters: None,
+            },
+            issuer: Name::from_str(&format!("CN={}", issuer)).unwrap(),
+            validity: Validity::from_now(Duration::from_secs(3600)).unwrap(),
+            subject: Name::from_str(&format!("CN={}", subject)).unwrap(),
+            subject_public_key_info: SubjectPublicKeyInfoOwned::from_der(spki_der.as_bytes())
+                .unwrap(),
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: Some(vec![x509_cert::ext::Extension {
+                extn_id: rfc5280::ID_CE_BASIC_CONSTRAINTS,
+                critical: true,
+                extn_value: x509_cert::der::asn1::OctetString::new(constraints.to_der().unwrap())
+                    .unwrap(),
+            }]),
+        };
+        let signature: Signature = issuer_key.sign(&tbs.to_der().unwrap());
+        X509Certificate {
+            tbs_certificate: tbs,
+            signature_algorithm: AlgorithmIdentifierOwned {
+                oid: rfc5912::ECDSA_WITH_SHA_256,
+                parameters: None,
+            },
+            signature: BitString::from_bytes(signature.to_der().as_bytes()).unwrap(),
+        }
+        .to_der()
+        .unwrap()
+    }
+
+    fn now_ms() -> u64 {
+        crate::pki::current_timestamp()
+    }
+
+    fn anchors(root_key: &SigningKey) -> AkTrustAnchors {
+        let mut anchors = AkTrustAnchors::new();
+        anchors
+            .add_der(&issue("ek-root", root_key, "ek-root", root_key, true))
+            .unwrap();
+        anchors
+    }
+
+    #[test]
+    fn test_certified_key_through_intermediate() {
+        let (root, intermediate, ak) = (signing_key(1), signing_key(2), signing_key(3));
+        let anchors = anchors(&root);
+
+        let mut chain = issue("node-ak", &ak, "ek-ca", &intermediate, false);
+        chain.extend(issue("ek-ca", &intermediate, "ek-root", &root, true));
+        let key = anchors.certified_key(&chain, now_ms()).unwrap();
+        assert_eq!(
+            key,
+            ak.verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec()
+        );
+
+        // Expired at the time of registration
+        assert!(anchors
+            .certified_key(&chain, now_ms() + 2 * 3600 * 1000)
+            .is_err());
+
+        // Intermediates must be CAs
+        let mut chain = issue("node-ak", &ak, "ek-ca", &intermediate, false);
+        chain.extend(issue("ek-ca", &intermediate, "ek-root", &root, false));
+        assert!(anchors.certified_key(&chain, now_ms()).is_err());
+    }
+
+    #[test]
+    fn test_self_asserted_key_rejected() {
+        let (root, ak) = (signing_key(1), signing_key(3));
+        let anchors = anchors(&root);
+
+        // Self-signed, or signed by a key merely claiming the anchor's name
+        let self_signed = issue("node-ak", &ak, "node-ak", &ak, false);
+        assert!(anchors.certified_key(&self_signed, now_ms()).is_err());
+        let forged = issue("node-ak", &ak, "ek-root", &signing_key(9), false);
+        assert!(anchors.certified_key(&forged, now_ms()).is_err());
+
+        assert!(AkTrustAnchors::new()
+            .certified_key(&issue("node-ak", &ak, "ek-root", &root, false), now_ms())
+            .is_err());
+        assert!(anchors.certified_key(&[0xBB; 64], now_ms()).is_err());
+    }
+}
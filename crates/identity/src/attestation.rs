@@ -229,6 +229,10 @@ pub struct AttestationManager {
     attestation_events: Vec<event::AttestationEvent>,
     /// Nonce counter for uniqueness
     nonce_counter: u64,
+    /// TPM holding the identity's attestation key, and the key's ID
+    tpm: Option<(crate::TpmManager, String)>,
+    /// Golden PCR values peers' quotes are checked against
+    pcr_policy: Option<crate::PcrPolicySet>,
 }
 
 impl AttestationManager {
@@ -245,9 +249,31 @@ impl AttestationManager {
             nonce_window_ms: DEFAULT_NONCE_WINDOW_MS,
             attestation_events: Vec::new(),
             nonce_counter: 0,
+            tpm: None,
+            pcr_policy: None,
         }
     }
 
+    /// Answer challenges with the TPM attestation key `key_id`.
+    ///
+    /// Challenge signatures are made with the key and responses carry a
+    /// quote over the challenge for `REQUIRED_PCRS`. The identity's public
+    /// key must be the attestation key's public key.
+    pub fn with_tpm(mut self, tpm: crate::TpmManager, key_id: impl Into<String>) -> Self {
+        self.tpm = Some((tpm, key_id.into()));
+        self
+    }
+
+    /// Require peers' TPM quotes to satisfy a PCR policy.
+    ///
+    /// Responses must then carry a quote, and the peer identity must declare
+    /// its `platform_type` and `firmware_version` in its metadata. The
+    /// matched policy name is recorded in the `ResponseVerified` event.
+    pub fn with_pcr_policy(mut self, policies: crate::PcrPolicySet) -> Self {
+        self.pcr_policy = Some(policies);
+        self
+    }
+
     /// Configure handshake timeout.
     pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
         self.handshake_timeout_ms = timeout_ms;
@@ -260,11 +286,21 @@ impl AttestationManager {
         self
     }
 
+    /// Local identity presented to peers.
+    pub fn identity(&self) -> &PlatformIdentity {
+        &self.identity
+    }
+
     /// Initiate attestation handshake with a peer.
     pub fn initiate_handshake(&mut self, peer_id: &str) -> crate::Result<AttestationRequest> {
-        // Check if handshake already in progress
+        // Check if handshake already in progress; attested peers may re-attest
         if let Some(state) = self.handshakes.get(peer_id) {
-            if !matches!(state, HandshakeState::Idle | HandshakeState::Failed { .. }) {
+            if !matches!(
+                state,
+                HandshakeState::Idle
+                    | HandshakeState::Failed { .. }
+                    | HandshakeState::Completed { .. }
+            ) {
                 return Err(crate::Error::Identity(
                     "Handshake already in progress".to_string(),
                 ));
@@ -360,12 +396,15 @@ impl AttestationManager {
         self.record_nonce(counter_challenge.clone())?;
 
         // Sign the incoming challenge
-        let challenge_signature = sign_data(&request.challenge, &self.identity);
+        let challenge_signature = self.sign_challenge(&request.challenge)?;
 
         // Generate TPM quote if using TPM attestation
-        let tpm_quote = match &self.identity.attestation {
-            Attestation::Tpm { .. } => {
-                // In production, generate real TPM quote
+        let tpm_quote = match (&self.identity.attestation, &self.tpm) {
+            (Attestation::Tpm { .. }, Some((tpm, _))) => {
+                Some(tpm.generate_quote(request.challenge.clone(), &crate::REQUIRED_PCRS)?)
+            }
+            (Attestation::Tpm { .. }, None) => {
+                // No TPM attached: placeholder quote without evidence
                 Some(crate::TpmQuote {
                     pcrs: vec![],
                     signature: vec![],
@@ -492,13 +531,44 @@ impl AttestationManager {
             ));
         }
 
-        // Verify TPM quote if present
+        // Verify TPM quote if present; it must be over our challenge, so a
+        // quote captured from an earlier handshake cannot be replayed
         if let Some(ref quote) = response.tpm_quote {
+            if quote.nonce != original_challenge {
+                return Err(crate::Error::Identity(
+                    "TPM quote does not cover the challenge".to_string(),
+                ));
+            }
             if !verify_tpm_quote(quote, &response.identity) {
                 return Err(crate::Error::Identity("Invalid TPM quote".to_string()));
             }
         }
 
+        // Compare measurements against the golden values
+        let mut additional_data = HashMap::new();
+        if let Some(policies) = &self.pcr_policy {
+            let matched = response
+                .tpm_quote
+                .as_ref()
+                .ok_or_else(|| {
+                    crate::Error::Identity("TPM quote required by PCR policy".to_string())
+                })
+                .and_then(|quote| {
+                    policies.evaluate_metadata(&response.identity.metadata, &quote.pcrs)
+                })
+                .map(str::to_string);
+            match matched {
+                Ok(name) => {
+                    additional_data
+                        .insert(crate::pcr_policy::PCR_POLICY_METADATA_KEY.to_string(), name);
+                }
+                Err(e) => {
+                    self.fail_handshake(&response.identity.id, &e.to_string());
+                    return Err(e);
+                }
+            }
+        }
+
         // Record counter-challenge nonce
         self.record_nonce(response.counter_challenge.clone())?;
 
@@ -507,7 +577,18 @@ impl AttestationManager {
             .insert(response.identity.id.clone(), response.identity.clone());
 
         // Sign the counter-challenge
-        let counter_signature = sign_data(&response.counter_challenge, &self.identity);
+        let counter_signature = self.sign_challenge(&response.counter_challenge)?;
+
+        // The responder has proven its identity; our side is complete
+        let trust_score = calculate_trust_score(&response.identity.attestation);
+        self.handshakes.insert(
+            response.identity.id.clone(),
+            HandshakeState::Completed {
+                peer_identity: response.identity.clone(),
+                completed_at: now,
+                trust_score,
+            },
+        );
 
         // Record event
         self.record_event(event::AttestationEvent {
@@ -520,9 +601,9 @@ impl AttestationManager {
                 attestation_type: format!("{:?}", response.identity.attestation),
                 cert_chain_length: response.cert_chain.len(),
                 tpm_quote_present: response.tpm_quote.is_some(),
-                trust_score: 0.0,
+                trust_score,
                 failure_reason: None,
-                additional_data: HashMap::new(),
+                additional_data,
             },
         });
 
@@ -777,6 +858,14 @@ impl AttestationManager {
         })
     }
 
+    /// Sign a challenge with the TPM attestation key, or the identity key.
+    fn sign_challenge(&self, challenge: &[u8]) -> crate::Result<Vec<u8>> {
+        match &self.tpm {
+            Some((tpm, key_id)) => tpm.sign_with_attestation_key(key_id, challenge),
+            None => Ok(sign_data(challenge, &self.identity)),
+        }
+    }
+
     fn generate_nonce(&mut self, size: usize) -> Vec<u8> {
         self.nonce_counter += 1;
 
@@ -869,31 +958,7 @@ fn verify_signature(data: &[u8], signature: &[u8], identity: &PlatformIdentity)
 
 /// Verify TPM quote.
 fn verify_tpm_quote(quote: &crate::TpmQuote, identity: &PlatformIdentity) -> bool {
-    if quote.nonce.is_empty() {
-        return false;
-    }
-
-    let verifying_key = match p256::ecdsa::VerifyingKey::from_sec1_bytes(&identity.public_key) {
-        Ok(key) => key,
-        Err(_) => return false,
-    };
-    let signature = match Signature::from_der(&quote.signature) {
-        Ok(sig) => sig,
-        Err(_) => return false,
-    };
-
-    if verifying_key
-        .verify(&quote.attestation_data, &signature)
-        .is_err()
-    {
-        return false;
-    }
-
-    if !quote
-        .attestation_data
-        .windows(quote.nonce.len())
-        .any(|window| window == quote.nonce)
-    {
+    if !crate::tpm::verify_quote_contents(quote, &identity.public_key).verified {
         return false;
     }
 
@@ -1014,6 +1079,7 @@ mod tests {
             not_after: u64::MAX,
             signature: vec![4, 5, 6],
             extensions: HashMap::new(),
+            x509_der: None,
         }
     }
 
@@ -1281,6 +1347,134 @@ mod tests {
         assert!(!verify_tpm_quote(&bad_quote, &identity));
     }
 
+    #[test]
+    fn test_handshake_with_tpm_attestation_key() {
+        let mut tpm = crate::TpmManager::new(false);
+        let ak = tpm
+            .generate_attestation_key("node-2-ak".to_string())
+            .unwrap();
+        let responder_identity = PlatformIdentity {
+            id: "node-2".to_string(),
+            public_key: ak.public_key.clone(),
+            attestation: Attestation::Tpm {
+                quote: Vec::new(),
+                pcrs: Vec::new(),
+                ak_cert: Vec::new(),
+            },
+            created_at: current_timestamp(),
+            metadata: HashMap::new(),
+        };
+
+        let mut initiator = AttestationManager::new(
+            create_test_identity(
+                "node-1",
+                Attestation::Software {
+                    certificate: vec![1, 2, 3],
+                },
+            ),
+            vec![create_test_cert("node-1")],
+        );
+        let mut responder =
+            AttestationManager::new(responder_identity, vec![create_test_cert("node-2")])
+                .with_tpm(tpm, "node-2-ak");
+
+        let request = initiator.initiate_handshake("node-2").unwrap();
+        let response = responder.handle_request(request).unwrap();
+
+        let quote = response.tpm_quote.as_ref().unwrap();
+        assert_eq!(quote.nonce, response.challenge);
+        assert_eq!(quote.pcrs.len(), crate::REQUIRED_PCRS.len());
+        let first_quote = response.tpm_quote.clone();
+        assert!(initiator.handle_response(response).is_ok());
+        assert!(initiator.is_attested("node-2"));
+        assert_eq!(initiator.get_trust_score("node-2"), Some(1.0));
+
+        // Attested peers can be re-attested, but not with an earlier quote
+        let request = initiator.initiate_handshake("node-2").unwrap();
+        let mut response = responder.handle_request(request).unwrap();
+        response.tpm_quote = first_quote;
+        assert!(initiator.handle_response(response).is_err());
+    }
+
+    #[test]
+    fn test_handshake_pcr_policy() {
+        // Stub TPMs report 0xFF for every PCR
+        let policy = |value: &str| {
+            crate::PcrPolicySet::from_toml_str(&format!(
+                "[[policy]]\nname = \"uas-release\"\nplatform_types = [\"Uas\"]\n\
+                 [[policy.measurements]]\n0 = \"{0}\"\n7 = \"{0}\"",
+                value.repeat(32)
+            ))
+            .unwrap()
+        };
+        let handshake = |policies: crate::PcrPolicySet| {
+            let mut tpm = crate::TpmManager::new(false);
+            let ak = tpm
+                .generate_attestation_key("node-2-ak".to_string())
+                .unwrap();
+            let responder_identity = PlatformIdentity {
+                id: "node-2".to_string(),
+                public_key: ak.public_key,
+                attestation: Attestation::Tpm {
+                    quote: Vec::new(),
+                    pcrs: Vec::new(),
+                    ak_cert: Vec::new(),
+                },
+                created_at: current_timestamp(),
+                metadata: HashMap::from([
+                    ("platform_type".to_string(), "Uas".to_string()),
+                    ("firmware_version".to_string(), "v2.0.0".to_string()),
+                ]),
+            };
+            let mut initiator = AttestationManager::new(
+                create_test_identity(
+                    "node-1",
+                    Attestation::Software {
+                        certificate: vec![1, 2, 3],
+                    },
+                ),
+                vec![create_test_cert("node-1")],
+            )
+            .with_pcr_policy(policies);
+            let mut responder =
+                AttestationManager::new(responder_identity, vec![create_test_cert("node-2")])
+                    .with_tpm(tpm, "node-2-ak");
+
+            let request = initiator.initiate_handshake("node-2").unwrap();
+            let response = responder.handle_request(request).unwrap();
+            let result = initiator.handle_response(response);
+            (initiator, result)
+        };
+
+        let (initiator, result) = handshake(policy("ff"));
+        assert!(result.is_ok());
+        let event = initiator.get_attestation_events().last().unwrap();
+        assert_eq!(
+            event.event_type,
+            event::AttestationEventType::ResponseVerified
+        );
+        assert_eq!(
+            event
+                .metadata
+                .additional_data
+                .get("pcr_policy")
+                .map(String::as_str),
+            Some("uas-release")
+        );
+
+        let (initiator, result) = handshake(policy("00"));
+        assert!(result.is_err());
+        assert!(!initiator.is_attested("node-2"));
+        assert_eq!(
+            initiator
+                .get_attestation_events()
+                .last()
+                .unwrap()
+                .event_type,
+            event::AttestationEventType::HandshakeFailed
+        );
+    }
+
     #[test]
     fn test_nonce_cleanup() {
         let identity = create_test_identity(
@@ -1,11 +1,20 @@
 //! Device identity management with hardware-rooted attestation.
 //!
 //! Provides unique, cryptographically-bound identities for physical platforms.
+//!
+//! `IdentityManager` keeps the registry in memory. When opened with
+//! `IdentityManager::open`, every registration and revocation is first written
+//! to an `IdentityStore` and the registry is reloaded from it on start.
 
+use crate::identity_store::{IdentityStore, RevocationRecord};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use tracing::{debug, error, info, warn};
 
+/// Reason recorded when `IdentityManager::revoke` is called without one
+const UNSPECIFIED_REVOCATION_REASON: &str = "unspecified";
+
 /// Unique platform identifier with cryptographic binding.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PlatformIdentity {
@@ -72,18 +81,62 @@ pub struct IdentityManager {
     /// Known identities indexed by ID
     identities: HashMap<String, PlatformIdentity>,
     /// Revoked identity IDs
-    revoked: std::collections::HashSet<String>,
+    revoked: HashSet<String>,
+    /// Revocation history, oldest first
+    revocation_log: Vec<RevocationRecord>,
+    /// Durable backing store (None for an in-memory registry)
+    store: Option<IdentityStore>,
 }
 
 impl IdentityManager {
-    /// Create a new identity manager.
+    /// Create a new in-memory identity manager.
     pub fn new() -> Self {
         Self {
             identities: HashMap::new(),
-            revoked: std::collections::HashSet::new(),
+            revoked: HashSet::new(),
+            revocation_log: Vec::new(),
+            store: None,
         }
     }
 
+    /// Open a persistent identity manager backed by SQLite at `path`.
+    ///
+    /// Previously registered identities and revocations are loaded on start.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let store = IdentityStore::open(path).map_err(store_error)?;
+        Self::with_store(store)
+    }
+
+    /// Create an identity manager over an already-open store.
+    pub fn with_store(store: IdentityStore) -> crate::Result<Self> {
+        let identities = store.load_identities().map_err(store_error)?;
+        let revocation_log = store.load_revocations().map_err(store_error)?;
+
+        let mut manager = Self::new();
+        manager.identities = identities
+            .into_iter()
+            .map(|identity| (identity.id.clone(), identity))
+            .collect();
+        manager.revoked = revocation_log
+            .iter()
+            .map(|record| record.identity_id.clone())
+            .collect();
+        manager.revocation_log = revocation_log;
+        manager.store = Some(store);
+
+        info!(
+            identities = manager.identities.len(),
+            revoked = manager.revoked.len(),
+            "Identity registry loaded from store"
+        );
+        Ok(manager)
+    }
+
+    /// Whether registrations and revocations are persisted.
+    pub fn is_persistent(&self) -> bool {
+        self.store.is_some()
+    }
+
     /// Register a new identity.
     #[tracing::instrument(skip(self, identity), fields(identity_id = %identity.id))]
     pub fn register(&mut self, identity: PlatformIdentity) -> crate::Result<()> {
@@ -104,6 +157,10 @@ impl IdentityManager {
             ));
         }
 
+        if let Some(store) = &self.store {
+            store.insert_identity(&identity).map_err(store_error)?;
+        }
+
         self.identities.insert(identity.id.clone(), identity);
         info!("Identity registered successfully");
         Ok(())
@@ -183,14 +240,58 @@ impl IdentityManager {
 
     /// Revoke an identity.
     pub fn revoke(&mut self, id: &str) -> crate::Result<()> {
+        self.revoke_with_reason(id, UNSPECIFIED_REVOCATION_REASON, None)
+    }
+
+    /// Revoke an identity, recording the reason and issuing authority.
+    ///
+    /// Revoking an already revoked identity is a no-op and adds no history.
+    #[tracing::instrument(skip(self, reason))]
+    pub fn revoke_with_reason(
+        &mut self,
+        id: &str,
+        reason: &str,
+        revoked_by: Option<&str>,
+    ) -> crate::Result<()> {
         if !self.identities.contains_key(id) {
             return Err(crate::Error::Identity("Identity not found".to_string()));
         }
 
+        if self.revoked.contains(id) {
+            debug!("Identity already revoked");
+            return Ok(());
+        }
+
+        let record = RevocationRecord {
+            identity_id: id.to_string(),
+            reason: reason.to_string(),
+            revoked_by: revoked_by.map(str::to_string),
+            revoked_at: current_timestamp(),
+        };
+
+        if let Some(store) = &self.store {
+            store.insert_revocation(&record).map_err(store_error)?;
+        }
+
         self.revoked.insert(id.to_string());
+        self.revocation_log.push(record);
+        warn!(reason = reason, "Identity revoked");
         Ok(())
     }
 
+    /// Revocation history for a single identity, oldest first.
+    pub fn revocation_history(&self, id: &str) -> Vec<&RevocationRecord> {
+        self.revocation_log
+            .iter()
+            .filter(|record| record.identity_id == id)
+            .collect()
+    }
+
+    /// Full revocation history, oldest first.
+    pub fn revocations(&self) -> &[RevocationRecord] {
+        &self.revocation_log
+    }
+
     /// Check if an identity is revoked.
     pub fn is_revoked(&self, id: &str) -> bool {
         self.revoked.contains(id)
@@ -213,6 +314,15 @@ impl Default for IdentityManager {
     }
 }
 
+fn store_error(err: crate::IdentityError) -> crate::Error {
+    match err {
+        crate::IdentityError::IdentityExists { .. } => {
+            crate::Error::Identity("Identity already registered".to_string())
+        }
+        other => crate::Error::Identity(format!("Identity store error: {}", other)),
+    }
+}
+
 /// Get current timestamp in milliseconds.
 fn current_timestamp() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -355,4 +465,67 @@ mod tests {
         let list = manager.list();
         assert_eq!(list.len(), 2);
     }
+
+    fn temp_db_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("aethercore-identity-{}", std::process::id()))
+            .join(format!("{}.db", name))
+    }
+
+    #[test]
+    fn test_persistent_registry_survives_restart() {
+        let path = temp_db_path("restart");
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let mut manager = IdentityManager::open(&path).unwrap();
+            assert!(manager.is_persistent());
+            manager.register(create_test_identity("test-1")).unwrap();
+            manager.register(create_test_identity("test-2")).unwrap();
+            manager
+                .revoke_with_reason("test-2", "Key compromise", Some("admin-1"))
+                .unwrap();
+        }
+
+        let mut manager = IdentityManager::open(&path).unwrap();
+        assert_eq!(manager.list().len(), 2);
+        assert!(manager.is_enrolled("test-1"));
+        assert!(manager.is_revoked("test-2"));
+        assert!(!manager.verify(&create_test_identity("test-2")).verified);
+
+        let history = manager.revocation_history("test-2");
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].reason, "Key compromise");
+        assert_eq!(history[0].revoked_by.as_deref(), Some("admin-1"));
+        assert!(history[0].revoked_at > 0);
+
+        // Duplicate detection also applies to identities loaded from disk
+        assert!(manager.register(create_test_identity("test-1")).is_err());
+
+        drop(manager);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_repeat_revocation_records_once() {
+        let mut manager = IdentityManager::new();
+        manager.register(create_test_identity("test-1")).unwrap();
+
+        manager.revoke("test-1").unwrap();
+        manager
+            .revoke_with_reason("test-1", "Second sweep", None)
+            .unwrap();
+
+        let history = manager.revocation_history("test-1");
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].reason, UNSPECIFIED_REVOCATION_REASON);
+        assert_eq!(manager.revocations().len(), 1);
+    }
+
+    #[test]
+    fn test_revoke_unknown_identity_fails() {
+        let mut manager = IdentityManager::new();
+        assert!(manager.revoke_with_reason("ghost", "test", None).is_err());
+        assert!(manager.revocations().is_empty());
+    }
 }
@@ -72,6 +72,10 @@ pub enum IdentityError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    /// Persistent storage errors
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     /// I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
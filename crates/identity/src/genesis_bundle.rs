@@ -6,6 +6,8 @@
 //! - Root and intermediate CA certificates
 //! - Bootstrap node addresses for initial mesh connectivity
 //! - Revocation checking endpoints (CRL and OCSP)
+//! - The latest CRL known to the issuer, so revocation checks work before the
+//!   platform can reach a CRL endpoint
 //!
 //! # Security Model
 //!
@@ -20,15 +22,20 @@
 //! - Directory: 0o700 (owner only)
 //! - Device certificate: 0o600 (owner read/write only)
 //! - Root CA certificate: 0o644 (world-readable)
+//! - CRL (`crl.json`): 0o644 (world-readable)
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::pki::{CertificateRevocationList, RevocationMode, TrustChainValidator};
 use crate::{Attestation, Certificate};
 
 /// Path where genesis bundles are installed.
 pub const GENESIS_BUNDLE_PATH: &str = "/etc/4mik/certs";
 
+/// File name of the installed CRL within the bundle directory.
+pub const CRL_FILE_NAME: &str = "crl.json";
+
 /// Minimum trust score required to receive a genesis bundle.
 const MIN_TRUST_SCORE: f64 = 0.7;
 
@@ -47,12 +54,69 @@ pub struct GenesisBundle {
     pub crl_endpoints: Vec<String>,
     /// OCSP endpoints for real-time revocation checking
     pub ocsp_endpoints: Vec<String>,
+    /// Latest CRL at bundle creation (refreshed from `crl_endpoints`)
+    #[serde(default)]
+    pub crl: Option<CertificateRevocationList>,
     /// When this bundle was created
     pub created_at: u64,
     /// When this bundle expires
     pub expires_at: u64,
 }
 
+impl GenesisBundle {
+    /// Replace the embedded CRL with a newer one.
+    ///
+    /// The CRL must be a full CRL signed by the root or an intermediate CA in
+    /// this bundle, and newer than the embedded CRL from the same issuer.
+    pub fn update_crl(&mut self, crl: CertificateRevocationList) -> crate::Result<()> {
+        verify_bundle_crl(
+            &self.root_ca_certificate,
+            &self.intermediate_certificates,
+            &crl,
+        )?;
+
+        if let Some(current) = &self.crl {
+            if current.issuer == crl.issuer && current.crl_number >= crl.crl_number {
+                return Err(crate::Error::Identity(format!(
+                    "CRL {} is not newer than installed CRL {}",
+                    crl.crl_number, current.crl_number
+                )));
+            }
+        }
+
+        self.crl = Some(crl);
+        Ok(())
+    }
+
+    /// Build a chain validator trusting this bundle's root CA.
+    ///
+    /// Intermediates that chain to the root and may sign CRLs are trusted as
+    /// CRL issuers. The embedded CRL, if any, is loaded and revocation
+    /// checking is enabled.
+    pub fn trust_chain_validator(&self) -> crate::Result<TrustChainValidator> {
+        let mut validator = TrustChainValidator::new();
+        validator.add_trusted_root(
+            self.root_ca_certificate.subject.clone(),
+            self.root_ca_certificate.public_key.clone(),
+        );
+        validator.set_revocation_mode(RevocationMode::BestEffort);
+        // Intermediates are ordered from the issuing CA up to the root
+        for (i, intermediate) in self.intermediate_certificates.iter().enumerate() {
+            if let Err(e) = validator.add_crl_issuer(&self.intermediate_certificates[i..]) {
+                tracing::debug!(
+                    ca = %intermediate.subject,
+                    "Intermediate not trusted to sign CRLs: {}",
+                    e
+                );
+            }
+        }
+        if let Some(crl) = &self.crl {
+            validator.add_crl(crl.clone())?;
+        }
+        Ok(validator)
+    }
+}
+
 /// Bootstrap node for initial mesh connectivity.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootstrapNode {
@@ -78,6 +142,8 @@ pub struct GenesisBundleGenerator {
     crl_endpoints: Vec<String>,
     /// OCSP endpoints
     ocsp_endpoints: Vec<String>,
+    /// Latest CRL to embed in issued bundles
+    crl: Option<CertificateRevocationList>,
     /// Bundle validity in milliseconds
     validity_ms: u64,
 }
@@ -107,10 +173,21 @@ impl GenesisBundleGenerator {
             bootstrap_nodes,
             crl_endpoints,
             ocsp_endpoints,
+            crl: None,
             validity_ms: validity_days * 24 * 60 * 60 * 1000,
         }
     }
 
+    /// Set the CRL embedded in subsequently generated bundles.
+    ///
+    /// Call after each CRL issued by the CA so new platforms start with
+    /// current revocation state.
+    pub fn set_crl(&mut self, crl: CertificateRevocationList) -> crate::Result<()> {
+        verify_bundle_crl(&self.root_ca, &self.intermediate_cas, &crl)?;
+        self.crl = Some(crl);
+        Ok(())
+    }
+
     /// Generate a genesis bundle for an attested platform.
     ///
     /// # Security Checks
@@ -165,6 +242,7 @@ impl GenesisBundleGenerator {
             mesh_bootstrap_nodes: self.bootstrap_nodes.clone(),
             crl_endpoints: self.crl_endpoints.clone(),
             ocsp_endpoints: self.ocsp_endpoints.clone(),
+            crl: self.crl.clone(),
             created_at: now,
             expires_at: now + self.validity_ms,
         })
@@ -267,6 +345,11 @@ pub fn install_genesis_bundle(
         }
     }
 
+    // Write CRL (world-readable)
+    if let Some(crl) = &bundle.crl {
+        install_crl(crl, Some(base))?;
+    }
+
     // Write bootstrap nodes configuration
     let bootstrap_path = base_dir.join("bootstrap-nodes.json");
     let bootstrap_json =
@@ -293,10 +376,96 @@ pub fn install_genesis_bundle(
     Ok(())
 }
 
-/// Convert certificate to PEM format (simplified).
+/// Install a CRL into an existing bundle directory, replacing any previous CRL.
+///
+/// Used both during bundle installation and when a newer CRL is fetched from
+/// one of the bundle's `crl_endpoints`.
+pub fn install_crl(crl: &CertificateRevocationList, base_path: Option<&str>) -> crate::Result<()> {
+    let path = Path::new(base_path.unwrap_or(GENESIS_BUNDLE_PATH)).join(CRL_FILE_NAME);
+    let crl_json = serde_json::to_string_pretty(crl)
+        .map_err(|e| crate::Error::Identity(format!("Failed to serialize CRL: {}", e)))?;
+    std::fs::write(&path, crl_json)
+        .map_err(|e| crate::Error::Identity(format!("Failed to write CRL: {}", e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(&path)
+            .map_err(|e| crate::Error::Identity(format!("Failed to get metadata: {}", e)))?
+            .permissions();
+        perms.set_mode(0o644);
+        std::fs::set_permissions(&path, perms)
+            .map_err(|e| crate::Error::Identity(format!("Failed to set permissions: {}", e)))?;
+    }
+
+    tracing::info!(
+        issuer = %crl.issuer,
+        crl_number = crl.crl_number,
+        "CRL installed"
+    );
+    Ok(())
+}
+
+/// Load the installed CRL, if one is present.
+///
+/// The CRL must be signed by the root or an intermediate CA in `bundle`; a
+/// CRL file that does not verify is an error rather than trusted.
+pub fn load_installed_crl(
+    bundle: &GenesisBundle,
+    base_path: Option<&str>,
+) -> crate::Result<Option<CertificateRevocationList>> {
+    let path = Path::new(base_path.unwrap_or(GENESIS_BUNDLE_PATH)).join(CRL_FILE_NAME);
+    let crl_json = match std::fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(crate::Error::Identity(format!("Failed to read CRL: {}", e))),
+    };
+    let crl = serde_json::from_str(&crl_json)
+        .map_err(|e| crate::Error::Identity(format!("Failed to parse CRL: {}", e)))?;
+    verify_bundle_crl(
+        &bundle.root_ca_certificate,
+        &bundle.intermediate_certificates,
+        &crl,
+    )?;
+    Ok(Some(crl))
+}
+
+/// Check that a CRL is a full CRL signed by one of the bundle's CAs.
+fn verify_bundle_crl(
+    root_ca: &Certificate,
+    intermediate_cas: &[Certificate],
+    crl: &CertificateRevocationList,
+) -> crate::Result<()> {
+    if crl.is_delta() {
+        return Err(crate::Error::Identity(
+            "Only full CRLs can be distributed in a genesis bundle".to_string(),
+        ));
+    }
+
+    let issuer = std::iter::once(root_ca)
+        .chain(intermediate_cas)
+        .find(|cert| cert.subject == crl.issuer)
+        .ok_or_else(|| {
+            crate::Error::Identity(format!("CRL issuer {} is not a bundle CA", crl.issuer))
+        })?;
+
+    if !crl.verify_signature(&issuer.public_key) {
+        return Err(crate::Error::Identity(format!(
+            "CRL signature from {} is invalid",
+            crl.issuer
+        )));
+    }
+    Ok(())
+}
+
+/// Convert certificate to PEM format.
+///
+/// X.509-backed certificates are written as standard PEM; native certificates
+/// fall back to a PEM-wrapped JSON encoding.
 fn certificate_to_pem(cert: &Certificate) -> String {
-    // In production, use proper X.509 encoding
-    // For now, create a simple PEM-like format
+    if let Ok(pem) = cert.to_x509_pem() {
+        return pem;
+    }
     let cert_json = serde_json::to_string_pretty(cert).unwrap_or_default();
     format!(
         "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
@@ -357,6 +526,7 @@ mod tests {
             not_after: u64::MAX,
             signature: vec![5, 6, 7, 8],
             extensions: HashMap::new(),
+            x509_der: None,
         }
     }
 
@@ -573,4 +743,183 @@ mod tests {
     fn test_min_trust_score_constant() {
         assert_eq!(MIN_TRUST_SCORE, 0.7);
     }
+
+    fn signing_key(seed: u8) -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn ca_with_root(seed: u8) -> (crate::CertificateAuthority, Certificate) {
+        use ed25519_dalek::Signer;
+
+        let key = signing_key(seed);
+        let public_key = key.verifying_key().to_bytes().to_vec();
+        let ca = crate::CertificateAuthority::new(
+            "root-ca",
+            public_key.clone(),
+            key.to_bytes().to_vec(),
+        );
+        let mut root = create_test_cert("root-ca");
+        root.issuer = "root-ca".to_string();
+        root.public_key = public_key;
+        root.signature = key
+            .sign(&crate::pki::certificate_tbs_bytes(&root))
+            .to_bytes()
+            .to_vec();
+        (ca, root)
+    }
+
+    fn issue_device_cert(ca: &mut crate::CertificateAuthority) -> Certificate {
+        use ed25519_dalek::Signer;
+
+        let key = signing_key(10);
+        let mut request = crate::CertificateRequest {
+            subject: "device-007".to_string(),
+            public_key: key.verifying_key().to_bytes().to_vec(),
+            signature: Vec::new(),
+            attestation: Attestation::Software {
+                certificate: vec![1],
+            },
+            pkcs10_der: None,
+        };
+        request.signature = key
+            .sign(&crate::pki::csr_tbs_bytes(&request))
+            .to_bytes()
+            .to_vec();
+        ca.issue_certificate(request, 365).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_embeds_and_installs_crl() {
+        let (mut ca, root) = ca_with_root(42);
+        let mut generator = GenesisBundleGenerator::new(root, vec![], vec![], vec![], vec![], 365);
+        generator.set_crl(ca.issue_crl(60_000).unwrap()).unwrap();
+
+        let bundle = generator
+            .generate(
+                create_test_cert("device-007"),
+                &Attestation::Software {
+                    certificate: vec![],
+                },
+                1.0,
+            )
+            .unwrap();
+        assert_eq!(bundle.crl.as_ref().unwrap().crl_number, 1);
+
+        let temp_dir = std::env::temp_dir().join("test-genesis-bundle-crl");
+        let temp_path = temp_dir.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(&temp_dir);
+
+        assert_eq!(load_installed_crl(&bundle, Some(temp_path)).unwrap(), None);
+        install_genesis_bundle(&bundle, Some(temp_path)).unwrap();
+        let installed = load_installed_crl(&bundle, Some(temp_path)).unwrap();
+
+        // A CRL swapped in on disk is not trusted
+        let (mut impostor, _) = ca_with_root(7);
+        install_crl(&impostor.issue_crl(60_000).unwrap(), Some(temp_path)).unwrap();
+        let swapped = load_installed_crl(&bundle, Some(temp_path));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        assert_eq!(installed, bundle.crl);
+        assert!(swapped.is_err());
+    }
+
+    #[test]
+    fn test_bundle_validator_loads_intermediate_crl() {
+        use ed25519_dalek::Signer;
+
+        let (mut ca, root) = ca_with_root(42);
+        let key = signing_key(43);
+        let mut request = crate::CertificateRequest {
+            subject: "issuing-ca".to_string(),
+            public_key: key.verifying_key().to_bytes().to_vec(),
+            signature: Vec::new(),
+            attestation: Attestation::Software {
+                certificate: vec![1],
+            },
+            pkcs10_der: None,
+        };
+        request.signature = key
+            .sign(&crate::pki::csr_tbs_bytes(&request))
+            .to_bytes()
+            .to_vec();
+        let intermediate_cert = ca.issue_intermediate_ca(request, 365, Some(0)).unwrap();
+        let mut intermediate = crate::CertificateAuthority::subordinate(
+            intermediate_cert.clone(),
+            key.to_bytes().to_vec(),
+        )
+        .unwrap();
+        let device_cert = issue_device_cert(&mut intermediate);
+
+        let mut generator = GenesisBundleGenerator::new(
+            root.clone(),
+            vec![intermediate_cert.clone()],
+            vec![],
+            vec![],
+            vec![],
+            365,
+        );
+        generator
+            .set_crl(intermediate.issue_crl(60_000).unwrap())
+            .unwrap();
+        let mut bundle = generator
+            .generate(
+                device_cert.clone(),
+                &Attestation::Software {
+                    certificate: vec![],
+                },
+                1.0,
+            )
+            .unwrap();
+        let chain = vec![device_cert.clone(), intermediate_cert];
+        assert!(bundle.trust_chain_validator().unwrap().verify_chain(&chain));
+
+        intermediate
+            .revoke_certificate(&device_cert.serial)
+            .unwrap();
+        bundle
+            .update_crl(intermediate.issue_crl(60_000).unwrap())
+            .unwrap();
+        assert!(!bundle.trust_chain_validator().unwrap().verify_chain(&chain));
+    }
+
+    #[test]
+    fn test_reject_untrusted_crl() {
+        let (_, root) = ca_with_root(42);
+        let (mut impostor, _) = ca_with_root(7);
+        let mut generator = GenesisBundleGenerator::new(root, vec![], vec![], vec![], vec![], 365);
+
+        assert!(generator
+            .set_crl(impostor.issue_crl(60_000).unwrap())
+            .is_err());
+        let delta = impostor.issue_delta_crl(60_000).unwrap();
+        assert!(generator.set_crl(delta).is_err());
+    }
+
+    #[test]
+    fn test_bundle_validator_rejects_revoked_device() {
+        let (mut ca, root) = ca_with_root(42);
+        let device_cert = issue_device_cert(&mut ca);
+        let mut generator =
+            GenesisBundleGenerator::new(root.clone(), vec![], vec![], vec![], vec![], 365);
+        let initial_crl = ca.issue_crl(60_000).unwrap();
+        generator.set_crl(initial_crl.clone()).unwrap();
+
+        let mut bundle = generator
+            .generate(
+                device_cert.clone(),
+                &Attestation::Software {
+                    certificate: vec![],
+                },
+                1.0,
+            )
+            .unwrap();
+
+        let chain = vec![device_cert.clone(), root];
+        assert!(bundle.trust_chain_validator().unwrap().verify_chain(&chain));
+
+        ca.revoke_certificate(&device_cert.serial).unwrap();
+        bundle.update_crl(ca.issue_crl(60_000).unwrap()).unwrap();
+        assert!(!bundle.trust_chain_validator().unwrap().verify_chain(&chain));
+        assert!(bundle.update_crl(initial_crl).is_err());
+    }
 }
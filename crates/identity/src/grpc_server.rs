@@ -26,9 +26,74 @@ use proto::identity_registry_server::{IdentityRegistry, IdentityRegistryServer};
 #[cfg(feature = "grpc-server")]
 use proto::*;
 
-use crate::{IdentityManager, PlatformIdentity, TpmManager};
+use crate::{
+    AkTrustAnchors, CertificateAuthority, IdentityManager, PcrPolicySet, PlatformIdentity,
+    RenewalManager, TpmManager,
+};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
+/// How often WatchCertificateExpiry re-checks the CA for expiring certificates
+#[cfg(feature = "grpc-server")]
+const EXPIRY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How far a RegisterNode timestamp may be from the server clock.
+///
+/// The timestamp is bound into the registration quote nonce, so accepting
+/// any value would let a captured quote be replayed indefinitely.
+pub const REGISTRATION_CLOCK_SKEW_MS: u64 = 5 * 60 * 1000;
+
+/// Qualifying data a RegisterNode TPM quote must cover.
+///
+/// Binds the quote to the registering node and its request timestamp:
+/// BLAKE3(node_id || timestamp_ms as big-endian u64).
+pub fn registration_quote_nonce(node_id: &str, timestamp_ms: u64) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(node_id.as_bytes());
+    hasher.update(&timestamp_ms.to_be_bytes());
+    hasher.finalize().as_bytes().to_vec()
+}
+
+/// Split concatenated 32-byte PCR values, indexed by `indices` or else in order.
+///
+/// Returns `None` if `indices` does not name one valid PCR per value.
+#[cfg(feature = "grpc-server")]
+fn parse_pcr_values(pcrs: &[u8], indices: &[u32]) -> Option<Vec<crate::PcrValue>> {
+    let chunk_size = 32;
+    let num_pcrs = pcrs.len().div_ceil(chunk_size);
+
+    if !indices.is_empty()
+        && (indices.len() != num_pcrs || indices.iter().any(|&index| index >= 24))
+    {
+        return None;
+    }
+
+    Some(
+        (0..num_pcrs.min(24))
+            .map(|i| {
+                let start = i * chunk_size;
+                let end = (start + chunk_size).min(pcrs.len());
+                crate::PcrValue {
+                    index: indices.get(i).map_or(i as u8, |&index| index as u8),
+                    value: pcrs[start..end].to_vec(),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Whether two SEC1 encodings, compressed or not, are the same P-256 key
+#[cfg(feature = "grpc-server")]
+fn same_p256_key(a: &[u8], b: &[u8]) -> bool {
+    match (
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(a),
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(b),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
 /// Identity Registry gRPC service implementation
 #[cfg(feature = "grpc-server")]
 pub struct IdentityRegistryService {
@@ -40,6 +105,17 @@ pub struct IdentityRegistryService {
     admin_node_ids: Arc<Mutex<Vec<String>>>,
     /// TPM enforcement enabled (default: true)
     tpm_enabled: bool,
+    /// Certificate authority watched for expiring certificates (optional)
+    certificate_authority: Option<Arc<Mutex<CertificateAuthority>>>,
+    /// Golden PCR values registering nodes must match (optional)
+    pcr_policy: Option<Arc<PcrPolicySet>>,
+    /// Anchors that attestation key certificates must chain to
+    ak_trust_anchors: Arc<AkTrustAnchors>,
+    /// Attested renewal of certificates issued by the CA (optional)
+    renewal_manager: Option<Arc<Mutex<RenewalManager>>>,
+    /// mTLS peer authorizer; when set, callers act only as their certified node
+    #[cfg(feature = "mtls")]
+    peer_authorizer: Option<Arc<dyn crate::mtls::PeerAuthorizer>>,
 }
 
 #[cfg(feature = "grpc-server")]
@@ -91,6 +167,12 @@ impl IdentityRegistryService {
             tpm_manager,
             admin_node_ids: Arc::new(Mutex::new(Vec::new())),
             tpm_enabled,
+            certificate_authority: None,
+            pcr_policy: None,
+            ak_trust_anchors: Arc::new(AkTrustAnchors::new()),
+            renewal_manager: None,
+            #[cfg(feature = "mtls")]
+            peer_authorizer: None,
         }
     }
 
@@ -113,9 +195,90 @@ impl IdentityRegistryService {
             tpm_manager,
             admin_node_ids: Arc::new(Mutex::new(admin_node_ids)),
             tpm_enabled,
+            certificate_authority: None,
+            pcr_policy: None,
+            ak_trust_anchors: Arc::new(AkTrustAnchors::new()),
+            renewal_manager: None,
+            #[cfg(feature = "mtls")]
+            peer_authorizer: None,
+        }
+    }
+
+    /// Report expiring certificates issued by `ca` via WatchCertificateExpiry
+    pub fn with_certificate_authority(mut self, ca: Arc<Mutex<CertificateAuthority>>) -> Self {
+        self.certificate_authority = Some(ca);
+        self
+    }
+
+    /// Require registering nodes' PCR values to satisfy a policy.
+    ///
+    /// Applies while TPM enforcement is enabled. The node's platform type
+    /// and firmware version select the policy, and the matched policy name
+    /// is recorded in the registered identity's `pcr_policy` metadata.
+    pub fn with_pcr_policy(mut self, policies: PcrPolicySet) -> Self {
+        self.pcr_policy = Some(Arc::new(policies));
+        self
+    }
+
+    /// Trust attestation keys certified up to one of `anchors`.
+    ///
+    /// While TPM enforcement is enabled, RegisterNode verifies quotes only
+    /// with the key its `ak_cert` chain certifies, so without anchors every
+    /// TPM registration is refused.
+    pub fn with_ak_trust_anchors(mut self, anchors: AkTrustAnchors) -> Self {
+        self.ak_trust_anchors = Arc::new(anchors);
+        self
+    }
+
+    /// Renew certificates via GetRenewalChallenge and RenewCertificate.
+    ///
+    /// Requires `with_certificate_authority`. While TPM enforcement is
+    /// enabled, RegisterNode enrolls each node's certified attestation key
+    /// with `renewals`; renewal quotes must be signed by that key. Pass a
+    /// `RenewalManager::open(..)` instance to keep those enrollments across
+    /// restarts.
+    pub fn with_renewal_manager(mut self, renewals: Arc<Mutex<RenewalManager>>) -> Self {
+        self.renewal_manager = Some(renewals);
+        self
+    }
+
+    /// Refuse an mTLS peer acting on another node's certificate
+    #[allow(clippy::result_large_err)] // tonic::Status is the RPC error type
+    fn require_subject_peer(peer: Option<String>, subject: &str) -> Result<(), Status> {
+        match peer {
+            Some(peer) if peer != subject => Err(Status::permission_denied(
+                "Certificate does not belong to client certificate's node",
+            )),
+            _ => Ok(()),
         }
     }
 
+    /// Bind requests to the mTLS peer identity.
+    ///
+    /// RegisterNode may then only register the client's own node ID, and
+    /// RevokeNode must be signed by the admin node holding the client
+    /// certificate.
+    #[cfg(feature = "mtls")]
+    pub fn with_peer_authorizer(
+        mut self,
+        authorizer: Arc<dyn crate::mtls::PeerAuthorizer>,
+    ) -> Self {
+        self.peer_authorizer = Some(authorizer);
+        self
+    }
+
+    /// Node ID of the mTLS peer, or `None` in plaintext mode
+    #[allow(clippy::result_large_err)] // tonic::Status is the RPC error type
+    fn mtls_peer<T>(&self, request: &Request<T>) -> Result<Option<String>, Status> {
+        #[cfg(feature = "mtls")]
+        if let Some(authorizer) = &self.peer_authorizer {
+            return crate::mtls::peer_device_id(request, authorizer.as_ref()).map(Some);
+        }
+        #[cfg(not(feature = "mtls"))]
+        let _ = request;
+        Ok(None)
+    }
+
     /// Get current timestamp in milliseconds
     fn current_timestamp_ms() -> u64 {
         std::time::SystemTime::now()
@@ -310,9 +473,33 @@ impl IdentityRegistry for IdentityRegistryService {
         &self,
         request: Request<RegisterNodeRequest>,
     ) -> Result<Response<RegisterNodeResponse>, Status> {
+        let peer = self.mtls_peer(&request)?;
         let req = request.into_inner();
         let timestamp_ms = Self::current_timestamp_ms();
 
+        if let Some(peer) = peer.filter(|peer| *peer != req.node_id) {
+            tracing::warn!(
+                "Registration DENIED for node {}: client certificate belongs to {}",
+                req.node_id,
+                peer
+            );
+            return Err(Status::permission_denied(
+                "Node ID does not match client certificate",
+            ));
+        }
+
+        let skew = timestamp_ms.abs_diff(req.timestamp_ms);
+        if skew > REGISTRATION_CLOCK_SKEW_MS {
+            tracing::warn!(
+                "Registration failed for node {}: timestamp {}ms from server clock",
+                req.node_id,
+                skew
+            );
+            return Err(Status::invalid_argument(
+                "Registration timestamp outside acceptable window",
+            ));
+        }
+
         // Decode public key
         let public_key = hex::decode(&req.public_key_hex).map_err(|_| {
             tracing::warn!(
@@ -348,6 +535,22 @@ impl IdentityRegistry for IdentityRegistryService {
             ));
         }
 
+        let mut metadata = std::collections::HashMap::new();
+        for (key, value) in [
+            (
+                crate::pcr_policy::PLATFORM_TYPE_METADATA_KEY,
+                &req.platform_type,
+            ),
+            (
+                crate::pcr_policy::FIRMWARE_VERSION_METADATA_KEY,
+                &req.firmware_version,
+            ),
+        ] {
+            if !value.is_empty() {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
+
         // Create attestation from TPM data
         let attestation = crate::Attestation::Tpm {
             quote: req.tpm_quote.clone(),
@@ -357,6 +560,7 @@ impl IdentityRegistry for IdentityRegistryService {
 
         // Validate TPM attestation (hardware-rooted trust)
         // When TPM_ENABLED=false, skip validation and accept empty TPM fields
+        let mut enrolled_ak = None;
         if self.tpm_enabled {
             // TPM ENABLED: Enforce hardware-rooted trust (NO GRACEFUL DEGRADATION)
             if req.tpm_quote.is_empty() {
@@ -398,43 +602,59 @@ impl IdentityRegistry for IdentityRegistryService {
                 .map_err(|e| Status::internal(format!("Lock error: {}", e)))?;
 
             // Parse PCRs from the request
-            let pcr_values = if !req.pcrs.is_empty() {
-                let chunk_size = 32;
-                let num_pcrs = (req.pcrs.len() + chunk_size - 1) / chunk_size;
-
-                (0..num_pcrs.min(24))
-                    .map(|i| {
-                        let start = i * chunk_size;
-                        let end = (start + chunk_size).min(req.pcrs.len());
-                        crate::PcrValue {
-                            index: i as u8,
-                            value: req.pcrs[start..end].to_vec(),
-                        }
-                    })
-                    .collect()
-            } else {
+            let pcr_values = parse_pcr_values(&req.pcrs, &req.pcr_indices).ok_or_else(|| {
+                tracing::warn!(
+                    "Registration failed for node {}: PCR indices do not match PCR values",
+                    req.node_id
+                );
+                Status::invalid_argument("PCR indices do not match PCR values")
+            })?;
+
+            // Genuine quotes carry the signed TPMS_ATTEST, whose qualifying
+            // data is bound to this registration
+            let nonce = if req.attestation_data.is_empty() {
                 vec![]
+            } else {
+                registration_quote_nonce(&req.node_id, req.timestamp_ms)
             };
 
             // Create TPM quote structure for verification
             let tpm_quote = crate::TpmQuote {
                 pcrs: pcr_values,
                 signature: req.tpm_quote.clone(),
-                nonce: vec![],
+                nonce,
                 timestamp: req.timestamp_ms,
-                attestation_data: vec![],
+                attestation_data: req.attestation_data.clone(),
             };
 
-            // Create attestation key from AK cert
+            // Only a key certified up to a trust anchor is known to be
+            // TPM-resident; a key the client merely names proves nothing
+            let ak_public_key = self
+                .ak_trust_anchors
+                .certified_key(&req.ak_cert, Self::current_timestamp_ms())
+                .map_err(|e| {
+                    tracing::error!(
+                        "Registration DENIED for node {}: AK certificate rejected: {}",
+                        req.node_id,
+                        e
+                    );
+                    Status::permission_denied(format!("AK certificate rejected: {}", e))
+                })?;
+            if !req.ak_public_key.is_empty() && !same_p256_key(&req.ak_public_key, &ak_public_key) {
+                tracing::error!(
+                    "Registration DENIED for node {}: AK public key does not match its certificate",
+                    req.node_id
+                );
+                return Err(Status::permission_denied(
+                    "AK public key does not match its certificate",
+                ));
+            }
             let ak = crate::AttestationKey {
                 key_id: req.node_id.clone(),
-                public_key: public_key.clone(),
-                certificate: if req.ak_cert.is_empty() {
-                    None
-                } else {
-                    Some(req.ak_cert.clone())
-                },
+                public_key: ak_public_key.clone(),
+                certificate: Some(req.ak_cert.clone()),
             };
+            enrolled_ak = Some(ak_public_key);
 
             // Verify TPM quote
             if !tpm_manager.verify_quote(&tpm_quote, &ak) {
@@ -448,6 +668,26 @@ impl IdentityRegistry for IdentityRegistryService {
             }
 
             tracing::info!("Node {} passed TPM attestation validation", req.node_id);
+
+            if let Some(policies) = &self.pcr_policy {
+                let matched = crate::pcr_policy::parse_platform_type(&req.platform_type)
+                    .and_then(|platform_type| {
+                        policies.evaluate(platform_type, &req.firmware_version, &tpm_quote.pcrs)
+                    })
+                    .map_err(|e| {
+                        tracing::error!(
+                            "Registration DENIED for node {}: PCR policy check failed: {}",
+                            req.node_id,
+                            e
+                        );
+                        Status::permission_denied(format!("PCR policy check failed: {}", e))
+                    })?;
+                tracing::info!("Node {} matched PCR policy {}", req.node_id, matched);
+                metadata.insert(
+                    crate::pcr_policy::PCR_POLICY_METADATA_KEY.to_string(),
+                    matched.to_string(),
+                );
+            }
         } else {
             // TPM DISABLED: Accept registration without TPM validation
             tracing::info!(
@@ -474,7 +714,7 @@ impl IdentityRegistry for IdentityRegistryService {
             public_key,
             attestation,
             created_at: req.timestamp_ms,
-            metadata: std::collections::HashMap::new(),
+            metadata,
         };
 
         // Register with identity manager
@@ -489,6 +729,21 @@ impl IdentityRegistry for IdentityRegistryService {
                     "Node {} successfully registered with TPM attestation",
                     req.node_id
                 );
+                // Renewal quotes must come from the key certified here
+                if let (Some(renewals), Some(ak_public_key)) = (&self.renewal_manager, enrolled_ak)
+                {
+                    if let Err(e) = renewals
+                        .lock()
+                        .map_err(|e| Status::internal(format!("Lock error: {}", e)))?
+                        .register_attestation_key(req.node_id.clone(), ak_public_key)
+                    {
+                        tracing::error!(
+                            "Failed to enroll attestation key for node {}: {}",
+                            req.node_id,
+                            e
+                        );
+                    }
+                }
                 Ok(Response::new(RegisterNodeResponse {
                     success: true,
                     error_message: String::new(),
@@ -511,6 +766,7 @@ impl IdentityRegistry for IdentityRegistryService {
         &self,
         request: Request<RevokeNodeRequest>,
     ) -> Result<Response<RevokeNodeResponse>, Status> {
+        let peer = self.mtls_peer(&request)?;
         let req = request.into_inner();
         let timestamp_ms = Self::current_timestamp_ms();
 
@@ -582,6 +838,18 @@ impl IdentityRegistry for IdentityRegistryService {
             ));
         }
 
+        if let Some(peer) = peer.filter(|peer| *peer != verifying_admin) {
+            tracing::error!(
+                "Revocation DENIED for node {}: signed by admin {} but client certificate belongs to {}",
+                req.node_id,
+                verifying_admin,
+                peer
+            );
+            return Err(Status::permission_denied(
+                "Authority signature does not match client certificate",
+            ));
+        }
+
         // Drop the read lock before acquiring write lock
         drop(manager);
 
@@ -590,7 +858,7 @@ impl IdentityRegistry for IdentityRegistryService {
             .lock()
             .map_err(|e| Status::internal(format!("Lock error: {}", e)))?;
 
-        match manager.revoke(&req.node_id) {
+        match manager.revoke_with_reason(&req.node_id, &req.reason, Some(&verifying_admin)) {
             Ok(_) => {
                 tracing::warn!(
                     "Node revoked: {} by admin {} - Reason: {}",
@@ -615,9 +883,245 @@ impl IdentityRegistry for IdentityRegistryService {
             }
         }
     }
+
+    type WatchCertificateExpiryStream =
+        tokio_stream::wrappers::ReceiverStream<Result<CertificateExpiryNotice, Status>>;
+
+    /// Stream notices for certificates approaching expiry
+    ///
+    /// Each certificate is reported once, when it first falls within the
+    /// warning window; renewed and revoked certificates are not reported.
+    async fn watch_certificate_expiry(
+        &self,
+        request: Request<WatchCertificateExpiryRequest>,
+    ) -> Result<Response<Self::WatchCertificateExpiryStream>, Status> {
+        let peer = self.mtls_peer(&request)?;
+        let req = request.into_inner();
+
+        let ca = self
+            .certificate_authority
+            .clone()
+            .ok_or_else(|| Status::failed_precondition("No certificate authority configured"))?;
+        if req.warning_window_ms == 0 {
+            return Err(Status::invalid_argument(
+                "warning_window_ms must be greater than zero",
+            ));
+        }
+
+        // Under mTLS, non-admin nodes may only watch their own certificates
+        let mut node_filter = req.node_id;
+        if let Some(peer) = peer {
+            let is_admin = self
+                .admin_node_ids
+                .lock()
+                .map_err(|e| Status::internal(format!("Lock error: {}", e)))?
+                .contains(&peer);
+            if !is_admin {
+                if !node_filter.is_empty() && node_filter != peer {
+                    return Err(Status::permission_denied(
+                        "Node ID does not match client certificate",
+                    ));
+                }
+                node_filter = peer;
+            }
+        }
+
+        let window_ms = req.warning_window_ms;
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut notified = HashSet::new();
+            loop {
+                let timestamp_ms = Self::current_timestamp_ms();
+                let expiring = ca
+                    .lock()
+                    .map(|ca| {
+                        ca.expiring_certificates(window_ms)
+                            .into_iter()
+                            .filter(|cert| node_filter.is_empty() || cert.subject == node_filter)
+                            .filter(|cert| notified.insert(cert.serial.clone()))
+                            .map(|cert| CertificateExpiryNotice {
+                                serial: cert.serial.clone(),
+                                node_id: cert.subject.clone(),
+                                issuer: cert.issuer.clone(),
+                                not_after_ms: cert.not_after,
+                                remaining_ms: cert.not_after.saturating_sub(timestamp_ms),
+                                timestamp_ms,
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .map_err(|e| Status::internal(format!("Lock error: {}", e)));
+                let notices = match expiring {
+                    Ok(notices) => notices,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                };
+
+                for notice in notices {
+                    tracing::info!(
+                        "Certificate {} for node {} expires in {} ms",
+                        notice.serial,
+                        notice.node_id,
+                        notice.remaining_ms
+                    );
+                    if tx.send(Ok(notice)).await.is_err() {
+                        return;
+                    }
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(EXPIRY_POLL_INTERVAL) => {}
+                    _ = tx.closed() => return,
+                }
+            }
+        });
+
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
+            rx,
+        )))
+    }
+
+    /// Issue a challenge for renewing a certificate
+    async fn get_renewal_challenge(
+        &self,
+        request: Request<GetRenewalChallengeRequest>,
+    ) -> Result<Response<GetRenewalChallengeResponse>, Status> {
+        let peer = self.mtls_peer(&request)?;
+        let req = request.into_inner();
+        let (Some(ca), Some(renewals)) = (&self.certificate_authority, &self.renewal_manager)
+        else {
+            return Err(Status::failed_precondition(
+                "Certificate renewal is not configured",
+            ));
+        };
+
+        let ca = ca
+            .lock()
+            .map_err(|e| Status::internal(format!("Lock error: {}", e)))?;
+        let certificate = ca.get_certificate(&req.serial).ok_or_else(|| {
+            Status::not_found(format!("Unknown certificate serial: {}", req.serial))
+        })?;
+        Self::require_subject_peer(peer, &certificate.subject)?;
+
+        let nonce = renewals
+            .lock()
+            .map_err(|e| Status::internal(format!("Lock error: {}", e)))?
+            .issue_challenge(&ca, certificate)
+            .map_err(|e| {
+                tracing::warn!("Renewal challenge refused for {}: {}", req.serial, e);
+                Status::permission_denied(format!("Renewal refused: {}", e))
+            })?;
+
+        let timestamp_ms = Self::current_timestamp_ms();
+        Ok(Response::new(GetRenewalChallengeResponse {
+            nonce,
+            expires_at_ms: timestamp_ms + crate::CHALLENGE_WINDOW_MS,
+            timestamp_ms,
+        }))
+    }
+
+    /// Renew a certificate with a TPM quote over its renewal challenge
+    async fn renew_certificate(
+        &self,
+        request: Request<RenewCertificateRequest>,
+    ) -> Result<Response<RenewCertificateResponse>, Status> {
+        let peer = self.mtls_peer(&request)?;
+        let req = request.into_inner();
+        let (Some(ca), Some(renewals)) = (&self.certificate_authority, &self.renewal_manager)
+        else {
+            return Err(Status::failed_precondition(
+                "Certificate renewal is not configured",
+            ));
+        };
+
+        let mut ca = ca
+            .lock()
+            .map_err(|e| Status::internal(format!("Lock error: {}", e)))?;
+        let certificate = ca.get_certificate(&req.serial).cloned().ok_or_else(|| {
+            Status::not_found(format!("Unknown certificate serial: {}", req.serial))
+        })?;
+        Self::require_subject_peer(peer, &certificate.subject)?;
+
+        let csr = crate::CertificateRequest::from_pkcs10_der(
+            &req.csr_der,
+            crate::Attestation::Tpm {
+                quote: req.tpm_quote.clone(),
+                pcrs: req.pcrs.clone(),
+                ak_cert: Vec::new(),
+            },
+        )
+        .map_err(|e| Status::invalid_argument(format!("Invalid CSR: {}", e)))?;
+        let pcrs = parse_pcr_values(&req.pcrs, &req.pcr_indices)
+            .ok_or_else(|| Status::invalid_argument("PCR indices do not match PCR values"))?;
+
+        let mut renewals = renewals
+            .lock()
+            .map_err(|e| Status::internal(format!("Lock error: {}", e)))?;
+        // The quote is checked against the key enrolled at registration,
+        // never one the client names
+        let attestation_key = crate::AttestationKey {
+            key_id: certificate.subject.clone(),
+            public_key: renewals
+                .attestation_key(&certificate.subject)
+                .unwrap_or_default()
+                .to_vec(),
+            certificate: None,
+        };
+        // A quote covers its signed qualifying data, which the renewal
+        // manager then matches to the outstanding challenge
+        let quote = crate::TpmQuote {
+            pcrs,
+            signature: req.tpm_quote,
+            nonce: crate::TpmsAttest::parse(&req.attestation_data)
+                .map(|attestation| attestation.extra_data)
+                .unwrap_or_default(),
+            timestamp: Self::current_timestamp_ms(),
+            attestation_data: req.attestation_data,
+        };
+        let tpm_manager = self
+            .tpm_manager
+            .lock()
+            .map_err(|e| Status::internal(format!("Lock error: {}", e)))?;
+        let renewed = renewals
+            .renew(
+                &mut ca,
+                &tpm_manager,
+                crate::RenewalRequest {
+                    certificate,
+                    csr,
+                    quote,
+                    attestation_key,
+                },
+            )
+            .map_err(|e| {
+                tracing::error!("Renewal DENIED for {}: {}", req.serial, e);
+                Status::permission_denied(format!("Renewal refused: {}", e))
+            })?;
+        let certificate_der = ca
+            .export_x509(&renewed)
+            .and_then(|x509| x509.to_x509_der())
+            .map_err(|e| Status::internal(format!("Failed to encode certificate: {}", e)))?;
+
+        tracing::info!(
+            "Certificate {} for node {} renewed as {}",
+            req.serial,
+            renewed.subject,
+            renewed.serial
+        );
+        Ok(Response::new(RenewCertificateResponse {
+            serial: renewed.serial,
+            certificate_der,
+            not_after_ms: renewed.not_after,
+            timestamp_ms: Self::current_timestamp_ms(),
+        }))
+    }
 }
 
 /// Start the Identity Registry gRPC server
+///
+/// Pass an `IdentityManager::open(..)` instance to keep enrolled nodes and
+/// revocations across restarts.
 #[cfg(feature = "grpc-server")]
 pub async fn start_grpc_server(
     addr: std::net::SocketAddr,
@@ -635,3 +1139,28 @@ pub async fn start_grpc_server(
 
     Ok(())
 }
+
+/// Start the Identity Registry gRPC server with mutual TLS
+///
+/// Clients must present a certificate accepted by `config`; see
+/// `crate::mtls::IdentityPeerAuthorizer` for refusing revoked identities.
+#[cfg(feature = "mtls")]
+pub async fn start_grpc_server_mtls(
+    addr: std::net::SocketAddr,
+    identity_manager: Arc<Mutex<IdentityManager>>,
+    tpm_manager: Arc<Mutex<TpmManager>>,
+    config: crate::mtls::MtlsServerConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let service = IdentityRegistryService::new(identity_manager, tpm_manager)
+        .with_peer_authorizer(config.authorizer());
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    tracing::info!("Identity Registry gRPC server (mTLS) listening on {}", addr);
+
+    Server::builder()
+        .add_service(IdentityRegistryServer::new(service))
+        .serve_with_incoming(crate::mtls::incoming(listener, &config)?)
+        .await?;
+
+    Ok(())
+}
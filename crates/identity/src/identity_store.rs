@@ -0,0 +1,444 @@
+//! Durable SQLite storage for the identity registry.
+//!
+//! Backs `IdentityManager` so enrolled platform identities and revocations
+//! survive restarts of the identity registry service.
+//!
+//! # Architecture
+//!
+//! - `identities`: one row per registered `PlatformIdentity`; attestation and
+//!   metadata are stored as JSON
+//! - `revocations`: append-only revocation history (reason, authority, timestamp)
+//! - `attestation_keys`: attestation key enrolled per certificate subject, used
+//!   by `RenewalManager` to check renewal quotes
+//!
+//! # Guarantees
+//!
+//! - Durability: SQLite WAL mode, same settings as the core `EventLedger`
+//! - Append-only history: revocation records are never updated or deleted
+//! - Versioned schema: migrations are tracked in `PRAGMA user_version` and applied
+//!   in order on open; a database written by a newer schema is refused
+
+use crate::device::{Attestation, PlatformIdentity};
+use crate::error::{IdentityError, IdentityResult};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use tracing::{info, warn};
+
+/// Ordered schema migrations; index `n` upgrades `user_version` from `n` to `n + 1`
+const MIGRATIONS: &[&str] = &[
+    // v1: identities and revocation history
+    r#"
+    CREATE TABLE IF NOT EXISTS identities (
+        id TEXT PRIMARY KEY,
+        public_key BLOB NOT NULL,
+        attestation TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        metadata TEXT NOT NULL,
+        registered_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000)
+    );
+
+    CREATE TABLE IF NOT EXISTS revocations (
+        revocation_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        identity_id TEXT NOT NULL REFERENCES identities(id),
+        reason TEXT NOT NULL,
+        revoked_by TEXT,
+        revoked_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_revocations_identity ON revocations(identity_id);
+    CREATE INDEX IF NOT EXISTS idx_revocations_revoked_at ON revocations(revoked_at);
+    "#,
+    // v2: attestation keys enrolled for certificate renewal
+    r#"
+    CREATE TABLE IF NOT EXISTS attestation_keys (
+        subject TEXT PRIMARY KEY,
+        public_key BLOB NOT NULL,
+        enrolled_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000)
+    );
+    "#,
+];
+
+/// Current schema version
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// A single revocation event in an identity's history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RevocationRecord {
+    /// Revoked identity ID
+    pub identity_id: String,
+    /// Reason given for the revocation
+    pub reason: String,
+    /// Authority that issued the revocation, if known
+    pub revoked_by: Option<String>,
+    /// Revocation timestamp (Unix epoch milliseconds)
+    pub revoked_at: u64,
+}
+
+/// SQLite-backed identity store
+///
+/// The connection is behind a mutex so `IdentityManager` stays `Send + Sync`.
+#[derive(Debug)]
+pub struct IdentityStore {
+    conn: Mutex<Connection>,
+}
+
+impl IdentityStore {
+    /// Create or open an identity store at the specified path
+    ///
+    /// Creates parent directories if needed and applies pending migrations.
+    pub fn open(path: impl AsRef<Path>) -> IdentityResult<Self> {
+        let path = path.as_ref();
+
+        info!(path = %path.display(), "Opening identity store");
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+                | OpenFlags::SQLITE_OPEN_CREATE
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(db_error)?;
+
+        // Enable WAL mode for better concurrency and durability
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(db_error)?;
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .map_err(db_error)?;
+        conn.pragma_update(None, "foreign_keys", "ON")
+            .map_err(db_error)?;
+
+        let store = Self {
+            conn: Mutex::new(conn),
+        };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Open a non-persistent store (for tests)
+    pub fn open_in_memory() -> IdentityResult<Self> {
+        let conn = Connection::open_in_memory().map_err(db_error)?;
+        conn.pragma_update(None, "foreign_keys", "ON")
+            .map_err(db_error)?;
+        let store = Self {
+            conn: Mutex::new(conn),
+        };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Current schema version of the open database
+    pub fn schema_version(&self) -> IdentityResult<u32> {
+        self.conn()?
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(db_error)
+    }
+
+    /// Apply pending migrations
+    fn migrate(&self) -> IdentityResult<()> {
+        let current = self.schema_version()?;
+        if current > SCHEMA_VERSION {
+            return Err(IdentityError::Config(format!(
+                "Identity store schema version {} is newer than supported version {}",
+                current, SCHEMA_VERSION
+            )));
+        }
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(db_error)?;
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            info!(
+                from = version,
+                to = version + 1,
+                "Applying identity store migration"
+            );
+            tx.execute_batch(migration).map_err(db_error)?;
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(db_error)?;
+        tx.commit().map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Persist a new identity
+    pub fn insert_identity(&self, identity: &PlatformIdentity) -> IdentityResult<()> {
+        let result = self.conn()?.execute(
+            "INSERT INTO identities (id, public_key, attestation, created_at, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                identity.id,
+                identity.public_key,
+                serde_json::to_string(&identity.attestation)?,
+                identity.created_at as i64,
+                serde_json::to_string(&identity.metadata)?,
+            ],
+        );
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                warn!(identity_id = %identity.id, "Duplicate identity in store");
+                Err(IdentityError::IdentityExists {
+                    identity_id: identity.id.clone(),
+                })
+            }
+            Err(e) => Err(db_error(e)),
+        }
+    }
+
+    /// Load a single identity by ID
+    pub fn get_identity(&self, id: &str) -> IdentityResult<Option<PlatformIdentity>> {
+        self.conn()?
+            .query_row(
+                "SELECT id, public_key, attestation, created_at, metadata
+                 FROM identities WHERE id = ?1",
+                params![id],
+                row_to_raw_identity,
+            )
+            .optional()
+            .map_err(db_error)?
+            .map(RawIdentity::decode)
+            .transpose()
+    }
+
+    /// Load all identities, ordered by registration
+    pub fn load_identities(&self) -> IdentityResult<Vec<PlatformIdentity>> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, public_key, attestation, created_at, metadata
+                 FROM identities ORDER BY registered_at, id",
+            )
+            .map_err(db_error)?;
+
+        let rows = stmt
+            .query_map([], row_to_raw_identity)
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        rows.into_iter().map(RawIdentity::decode).collect()
+    }
+
+    /// Append a revocation record
+    pub fn insert_revocation(&self, record: &RevocationRecord) -> IdentityResult<()> {
+        self.conn()?
+            .execute(
+                "INSERT INTO revocations (identity_id, reason, revoked_by, revoked_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    record.identity_id,
+                    record.reason,
+                    record.revoked_by,
+                    record.revoked_at as i64,
+                ],
+            )
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Load the full revocation history, oldest first
+    pub fn load_revocations(&self) -> IdentityResult<Vec<RevocationRecord>> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT identity_id, reason, revoked_by, revoked_at
+                 FROM revocations ORDER BY revoked_at, revocation_id",
+            )
+            .map_err(db_error)?;
+
+        let records = stmt
+            .query_map([], |row| {
+                Ok(RevocationRecord {
+                    identity_id: row.get(0)?,
+                    reason: row.get(1)?,
+                    revoked_by: row.get(2)?,
+                    revoked_at: row.get::<_, i64>(3)? as u64,
+                })
+            })
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        Ok(records)
+    }
+
+    /// Record the attestation key enrolled for `subject`, replacing any earlier one
+    pub fn upsert_attestation_key(&self, subject: &str, public_key: &[u8]) -> IdentityResult<()> {
+        self.conn()?
+            .execute(
+                "INSERT INTO attestation_keys (subject, public_key) VALUES (?1, ?2)
+                 ON CONFLICT(subject) DO UPDATE SET
+                     public_key = excluded.public_key,
+                     enrolled_at = strftime('%s', 'now') * 1000",
+                params![subject, public_key],
+            )
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Load all enrolled attestation keys as `(subject, public_key)` pairs
+    pub fn load_attestation_keys(&self) -> IdentityResult<Vec<(String, Vec<u8>)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare("SELECT subject, public_key FROM attestation_keys ORDER BY subject")
+            .map_err(db_error)?;
+
+        let keys = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        Ok(keys)
+    }
+
+    fn conn(&self) -> IdentityResult<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| IdentityError::Storage("Identity store lock poisoned".to_string()))
+    }
+}
+
+/// Undecoded identity row
+struct RawIdentity {
+    id: String,
+    public_key: Vec<u8>,
+    attestation: String,
+    created_at: i64,
+    metadata: String,
+}
+
+impl RawIdentity {
+    fn decode(self) -> IdentityResult<PlatformIdentity> {
+        let attestation: Attestation = serde_json::from_str(&self.attestation)?;
+        Ok(PlatformIdentity {
+            id: self.id,
+            public_key: self.public_key,
+            attestation,
+            created_at: self.created_at as u64,
+            metadata: serde_json::from_str(&self.metadata)?,
+        })
+    }
+}
+
+fn row_to_raw_identity(row: &rusqlite::Row<'_>) -> rusqlite::Result<RawIdentity> {
+    Ok(RawIdentity {
+        id: row.get(0)?,
+        public_key: row.get(1)?,
+        attestation: row.get(2)?,
+        created_at: row.get(3)?,
+        metadata: row.get(4)?,
+    })
+}
+
+fn db_error(err: rusqlite::Error) -> IdentityError {
+    IdentityError::Storage(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn identity(id: &str) -> PlatformIdentity {
+        let mut metadata = HashMap::new();
+        metadata.insert("platform".to_string(), "rpi".to_string());
+        PlatformIdentity {
+            id: id.to_string(),
+            public_key: vec![1, 2, 3, 4],
+            attestation: Attestation::Tpm {
+                quote: vec![1],
+                pcrs: vec![2],
+                ak_cert: vec![3],
+            },
+            created_at: 1000,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_migrations_set_schema_version() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_identity_roundtrip() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        store.insert_identity(&identity("node-1")).unwrap();
+
+        assert_eq!(
+            store.get_identity("node-1").unwrap(),
+            Some(identity("node-1"))
+        );
+        assert_eq!(store.get_identity("node-2").unwrap(), None);
+        assert_eq!(store.load_identities().unwrap(), vec![identity("node-1")]);
+    }
+
+    #[test]
+    fn test_duplicate_identity_rejected() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        store.insert_identity(&identity("node-1")).unwrap();
+        assert!(matches!(
+            store.insert_identity(&identity("node-1")),
+            Err(IdentityError::IdentityExists { .. })
+        ));
+    }
+
+    #[test]
+    fn test_revocation_requires_known_identity() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let record = RevocationRecord {
+            identity_id: "ghost".to_string(),
+            reason: "test".to_string(),
+            revoked_by: None,
+            revoked_at: 1,
+        };
+        assert!(matches!(
+            store.insert_revocation(&record),
+            Err(IdentityError::Storage(_))
+        ));
+    }
+
+    #[test]
+    fn test_attestation_key_replaced_on_reenrollment() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        store.upsert_attestation_key("node-1", &[1, 2]).unwrap();
+        store.upsert_attestation_key("node-2", &[3]).unwrap();
+        store.upsert_attestation_key("node-1", &[4, 5]).unwrap();
+
+        assert_eq!(
+            store.load_attestation_keys().unwrap(),
+            vec![
+                ("node-1".to_string(), vec![4, 5]),
+                ("node-2".to_string(), vec![3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let dir = std::env::temp_dir().join(format!("identity-store-{}", std::process::id()));
+        let path = dir.join("future.db");
+        {
+            let store = IdentityStore::open(&path).unwrap();
+            store
+                .conn()
+                .unwrap()
+                .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+                .unwrap();
+        }
+        let result = IdentityStore::open(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Err(IdentityError::Config(_))));
+    }
+}
@@ -27,6 +27,7 @@
 //! - Identity enrollment and lifecycle management APIs
 //! - Audit logging and identity event streaming
 
+pub mod ak_trust;
 pub mod attestation;
 pub mod device;
 pub mod enrollment;
@@ -34,14 +35,22 @@ pub mod enrollment_state;
 pub mod error;
 pub mod federation;
 pub mod genesis_bundle;
+pub mod identity_store;
 pub mod materia_slot;
+pub mod pcr_policy;
 pub mod pki;
+pub mod renewal;
 pub mod secure_enclave;
 pub mod tpm;
+pub mod tpm_attest;
+pub mod x509;
 
 #[cfg(feature = "grpc-server")]
 pub mod grpc_server;
+#[cfg(feature = "mtls")]
+pub mod mtls;
 
+pub use ak_trust::AkTrustAnchors;
 pub use attestation::{
     AttestationFinalize, AttestationManager, AttestationRequest, AttestationResponse,
     AttestationResult, HandshakeState, PROTOCOL_VERSION,
@@ -56,16 +65,31 @@ pub use enrollment_state::{
 pub use error::{IdentityError, IdentityResult};
 pub use federation::{FederatedIdentity, FederationRegistry, TrustLevel};
 pub use genesis_bundle::{
-    install_genesis_bundle, BootstrapNode, GenesisBundle, GenesisBundleGenerator,
-    GENESIS_BUNDLE_PATH,
+    install_crl, install_genesis_bundle, load_installed_crl, BootstrapNode, GenesisBundle,
+    GenesisBundleGenerator, CRL_FILE_NAME, GENESIS_BUNDLE_PATH,
 };
+pub use identity_store::{IdentityStore, RevocationRecord};
 pub use materia_slot::{FederatedMateriaSlot, Materia, MateriaSlot};
-pub use pki::{Certificate, CertificateAuthority, CertificateRequest, TrustChainValidator};
+pub use pcr_policy::{PcrPolicy, PcrPolicySet};
+pub use pki::{
+    CaConstraints, Certificate, CertificateAuthority, CertificateRequest,
+    CertificateRevocationList, CrlEntry, KeyUsage, RevocationMode, TrustChainValidator,
+};
+pub use renewal::{RenewalManager, RenewalRequest};
 pub use secure_enclave::{SecureEnclaveAttestor, SecureEnclaveQuote};
-pub use tpm::{AttestationKey, PcrValue, TpmManager, TpmQuote};
+pub use tpm::{
+    AttestationKey, PcrValue, QuoteVerification, QuoteVerificationFailure, TpmManager, TpmQuote,
+};
+#[cfg(feature = "tpm-simulator")]
+pub use tpm::{SimulatorConfig, SimulatorProtocol};
+pub use tpm_attest::{ClockInfo, TpmsAttest};
 
+#[cfg(feature = "mtls")]
+pub use grpc_server::start_grpc_server_mtls;
 #[cfg(feature = "grpc-server")]
 pub use grpc_server::{start_grpc_server, IdentityRegistryService};
+#[cfg(feature = "mtls")]
+pub use mtls::IdentityPeerAuthorizer;
 
 // Re-export core types for convenience
 pub use aethercore_core::{Error, Result};
@@ -0,0 +1,239 @@
+//! Mutual-TLS peer authorization backed by the AetherCore PKI.
+//!
+//! [`IdentityPeerAuthorizer`] plugs the native trust chain validator, CRLs
+//! and the identity registry into the `aethercore_crypto::mtls` transport:
+//! a client certificate is accepted only if it chains to a trusted AetherCore
+//! root, is not on a held CRL, and its subject is not a revoked identity. The
+//! certificate subject is the peer's device ID.
+//!
+//! Certificates used on the wire must be X.509-backed (see
+//! [`crate::x509`]); server certificates also need a DNS subject alternative
+//! name ([`subject_alt_name_dns`]) for clients to verify the server.
+
+use crate::pki::{Certificate, CertificateRevocationList, TrustChainValidator};
+use crate::IdentityManager;
+use aethercore_core::sync;
+use ed25519_dalek::SigningKey;
+use std::sync::{Arc, Mutex, RwLock};
+
+pub use crate::x509::subject_alt_name_dns;
+pub use aethercore_crypto::mtls::{
+    bind_device_id, incoming, peer_device_id, MtlsError, MtlsServerConfig, PeerAuthorizer,
+    HANDSHAKE_TIMEOUT,
+};
+
+/// Revocation state of registered identities.
+pub trait IdentityStatus: Send + Sync {
+    /// Whether the identity has been revoked.
+    fn is_revoked(&self, identity_id: &str) -> bool;
+}
+
+impl IdentityStatus for Mutex<IdentityManager> {
+    fn is_revoked(&self, identity_id: &str) -> bool {
+        sync::lock(self).is_revoked(identity_id)
+    }
+}
+
+impl IdentityStatus for RwLock<IdentityManager> {
+    fn is_revoked(&self, identity_id: &str) -> bool {
+        sync::read(self).is_revoked(identity_id)
+    }
+}
+
+/// Authorizes mTLS peers against the AetherCore PKI and identity registry.
+pub struct IdentityPeerAuthorizer {
+    validator: RwLock<TrustChainValidator>,
+    identities: Option<Arc<dyn IdentityStatus>>,
+}
+
+impl IdentityPeerAuthorizer {
+    /// Create an authorizer that validates client chains with `validator`.
+    pub fn new(validator: TrustChainValidator) -> Self {
+        Self {
+            validator: RwLock::new(validator),
+            identities: None,
+        }
+    }
+
+    /// Also refuse peers whose identity is revoked in `identities`.
+    pub fn with_identity_status(mut self, identities: Arc<dyn IdentityStatus>) -> Self {
+        self.identities = Some(identities);
+        self
+    }
+
+    /// Load a newer CRL into the validator; later handshakes and calls see it.
+    pub fn add_crl(&self, crl: CertificateRevocationList) -> crate::Result<()> {
+        self.validator
+            .write()
+            .map_err(|_| crate::Error::Identity("Trust chain validator lock poisoned".to_string()))?
+            .add_crl(crl)
+    }
+}
+
+impl PeerAuthorizer for IdentityPeerAuthorizer {
+    fn authorize_peer(&self, chain: &[&[u8]]) -> Result<String, String> {
+        let certificates = chain
+            .iter()
+            .map(|der| Certificate::from_x509_der(der))
+            .collect::<crate::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+        let device_id = match certificates.first() {
+            Some(leaf) => leaf.subject.clone(),
+            None => return Err("Empty certificate chain".to_string()),
+        };
+
+        let validator = self
+            .validator
+            .read()
+            .map_err(|_| "Trust chain validator lock poisoned".to_string())?;
+        if !validator.verify_chain(&certificates) {
+            return Err(format!(
+                "Certificate chain for {} is not trusted",
+                device_id
+            ));
+        }
+
+        if let Some(identities) = &self.identities {
+            if identities.is_revoked(&device_id) {
+                return Err(format!("Identity {} is revoked", device_id));
+            }
+        }
+
+        Ok(device_id)
+    }
+}
+
+/// Build an mTLS server configuration from X.509-backed certificates.
+///
+/// # Arguments
+///
+/// * `certificate_chain` - Server certificate chain, end-entity first
+/// * `server_key` - Key matching the server certificate
+/// * `client_ca_roots` - CA certificates client chains must lead to
+/// * `authorizer` - Maps client certificates to device identities
+pub fn server_config(
+    certificate_chain: &[Certificate],
+    server_key: &SigningKey,
+    client_ca_roots: &[Certificate],
+    authorizer: Arc<dyn PeerAuthorizer>,
+) -> crate::Result<MtlsServerConfig> {
+    MtlsServerConfig::new(
+        x509_ders(certificate_chain)?,
+        server_key,
+        x509_ders(client_ca_roots)?,
+        authorizer,
+    )
+    .map_err(|e| crate::Error::Identity(e.to_string()))
+}
+
+fn x509_ders(certificates: &[Certificate]) -> crate::Result<Vec<Vec<u8>>> {
+    certificates.iter().map(Certificate::to_x509_der).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::Attestation;
+    use crate::pki::{CertificateAuthority, CertificateRequest, RevocationMode};
+    use ed25519_dalek::Signer;
+    use std::collections::HashMap;
+
+    fn attestation() -> Attestation {
+        Attestation::Software {
+            certificate: vec![1, 2, 3],
+        }
+    }
+
+    fn build_ca() -> CertificateAuthority {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        CertificateAuthority::new(
+            "test-ca",
+            key.verifying_key().to_bytes().to_vec(),
+            key.to_bytes().to_vec(),
+        )
+    }
+
+    fn issue_leaf(ca: &mut CertificateAuthority, subject: &str) -> Certificate {
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let mut request = CertificateRequest {
+            subject: subject.to_string(),
+            public_key: key.verifying_key().to_bytes().to_vec(),
+            attestation: attestation(),
+            signature: Vec::new(),
+            pkcs10_der: None,
+        };
+        request.signature = key
+            .sign(&crate::pki::csr_tbs_bytes(&request))
+            .to_bytes()
+            .to_vec();
+        let native = ca
+            .issue_certificate_with_extensions(request, 30, HashMap::new())
+            .unwrap();
+        ca.export_x509(&native).unwrap()
+    }
+
+    fn authorizer(ca: &CertificateAuthority) -> IdentityPeerAuthorizer {
+        let mut validator = TrustChainValidator::new();
+        validator.add_trusted_root(ca.ca_id().to_string(), ca.public_key().to_vec());
+        validator.set_revocation_mode(RevocationMode::BestEffort);
+        IdentityPeerAuthorizer::new(validator)
+    }
+
+    #[test]
+    fn test_authorizer_maps_subject_to_device_id() {
+        let mut ca = build_ca();
+        let leaf = issue_leaf(&mut ca, "node-1");
+        let der = leaf.to_x509_der().unwrap();
+
+        let device_id = authorizer(&ca).authorize_peer(&[&der]).unwrap();
+        assert_eq!(device_id, "node-1");
+    }
+
+    #[test]
+    fn test_authorizer_refuses_revoked_identity() {
+        let mut ca = build_ca();
+        let leaf = issue_leaf(&mut ca, "node-1");
+        let der = leaf.to_x509_der().unwrap();
+
+        let mut manager = IdentityManager::new();
+        manager
+            .register(crate::PlatformIdentity {
+                id: "node-1".to_string(),
+                public_key: leaf.public_key.clone(),
+                attestation: attestation(),
+                created_at: 1000,
+                metadata: HashMap::new(),
+            })
+            .unwrap();
+        let identities = Arc::new(Mutex::new(manager));
+        let authorizer = authorizer(&ca).with_identity_status(identities.clone());
+        assert!(authorizer.authorize_peer(&[&der]).is_ok());
+
+        identities.lock().unwrap().revoke("node-1").unwrap();
+        assert!(authorizer.authorize_peer(&[&der]).is_err());
+    }
+
+    #[test]
+    fn test_authorizer_refuses_certificate_on_crl() {
+        let mut ca = build_ca();
+        let leaf = issue_leaf(&mut ca, "node-1");
+        let der = leaf.to_x509_der().unwrap();
+        let authorizer = authorizer(&ca);
+        assert!(authorizer.authorize_peer(&[&der]).is_ok());
+
+        ca.revoke_certificate(&leaf.serial).unwrap();
+        authorizer.add_crl(ca.issue_crl(60_000).unwrap()).unwrap();
+        assert!(authorizer.authorize_peer(&[&der]).is_err());
+    }
+
+    #[test]
+    fn test_authorizer_refuses_untrusted_issuer() {
+        let mut ca = build_ca();
+        let leaf = issue_leaf(&mut ca, "node-1");
+        let der = leaf.to_x509_der().unwrap();
+
+        let authorizer = IdentityPeerAuthorizer::new(TrustChainValidator::new());
+        assert!(authorizer.authorize_peer(&[&der]).is_err());
+        assert!(authorizer.authorize_peer(&[b"not a certificate"]).is_err());
+    }
+}
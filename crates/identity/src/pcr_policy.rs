@@ -0,0 +1,377 @@
+//! PCR golden-value policies.
+//!
+//! A policy set lists, per platform type, the boot measurements a node may
+//! present. Each policy names the platform types and firmware versions it
+//! applies to and one or more acceptable measurements; a measurement maps
+//! PCR indices to their expected values. A quote satisfies a policy when
+//! every PCR of at least one measurement matches.
+//!
+//! Policies are evaluated in file order and the first one satisfied is the
+//! match, so its name can be recorded alongside the attestation.
+//!
+//! # File Format
+//!
+//! ```toml
+//! [[policy]]
+//! name = "uas-flight-controller"
+//! platform_types = ["Uas", "Aerial"]
+//! # Omit to accept any firmware version
+//! firmware_versions = ["v2.0.0", "v2.0.1"]
+//!
+//! # Release image
+//! [[policy.measurements]]
+//! 0 = "3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969"
+//! 7 = "65caf8dd1e0ea7a6347b635d2b379c93b9a1351edc2afc3ecda700e534eb3068"
+//!
+//! # Image with the maintenance bootloader
+//! [[policy.measurements]]
+//! 0 = "b9e7ab3f8e8ab5ff6b6a6f3c8b8a4dd0ea3b8d71c5e2a1dbb0c3f16c2cda2b8d"
+//! 7 = "65caf8dd1e0ea7a6347b635d2b379c93b9a1351edc2afc3ecda700e534eb3068"
+//! ```
+
+use crate::enrollment::PlatformType;
+use crate::tpm::PcrValue;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Number of PCRs of a PC client TPM.
+const PCR_COUNT: u8 = 24;
+
+/// Metadata key carrying a node's platform type (e.g. `"Uas"`).
+pub const PLATFORM_TYPE_METADATA_KEY: &str = "platform_type";
+
+/// Metadata key carrying a node's firmware version.
+pub const FIRMWARE_VERSION_METADATA_KEY: &str = "firmware_version";
+
+/// Metadata key under which the matched policy name is recorded.
+pub const PCR_POLICY_METADATA_KEY: &str = "pcr_policy";
+
+/// Acceptable measurements for a class of platforms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcrPolicy {
+    /// Policy name, recorded when the policy matches
+    pub name: String,
+    /// Platform types the policy applies to
+    pub platform_types: Vec<PlatformType>,
+    /// Firmware versions the policy applies to; empty for any version
+    pub firmware_versions: Vec<String>,
+    /// Alternative golden measurements, each mapping PCR index to value
+    pub measurements: Vec<BTreeMap<u8, Vec<u8>>>,
+}
+
+impl PcrPolicy {
+    /// Whether the policy covers this platform and firmware version.
+    pub fn applies_to(&self, platform_type: PlatformType, firmware_version: &str) -> bool {
+        self.platform_types.contains(&platform_type)
+            && (self.firmware_versions.is_empty()
+                || self
+                    .firmware_versions
+                    .iter()
+                    .any(|version| version == firmware_version))
+    }
+
+    /// Whether the PCR values satisfy one of the policy's measurements.
+    pub fn is_satisfied_by(&self, pcrs: &[PcrValue]) -> bool {
+        self.measurements.iter().any(|measurement| {
+            measurement.iter().all(|(index, expected)| {
+                pcrs.iter()
+                    .any(|pcr| pcr.index == *index && pcr.value == *expected)
+            })
+        })
+    }
+}
+
+/// Ordered set of PCR policies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PcrPolicySet {
+    policies: Vec<PcrPolicy>,
+}
+
+impl PcrPolicySet {
+    /// Create a policy set from already validated policies.
+    pub fn new(policies: Vec<PcrPolicy>) -> Self {
+        Self { policies }
+    }
+
+    /// Parse a policy set from TOML.
+    pub fn from_toml_str(content: &str) -> crate::Result<Self> {
+        let file: PolicyFile = toml::from_str(content)
+            .map_err(|e| crate::Error::Config(format!("Invalid PCR policy file: {}", e)))?;
+        let policies = file
+            .policy
+            .into_iter()
+            .map(PolicyEntry::validate)
+            .collect::<crate::Result<_>>()?;
+        Ok(Self { policies })
+    }
+
+    /// Load a policy set from a TOML file.
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            crate::Error::Config(format!(
+                "Failed to read PCR policy file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_toml_str(&content)
+    }
+
+    /// Policies in evaluation order.
+    pub fn policies(&self) -> &[PcrPolicy] {
+        &self.policies
+    }
+
+    /// Name of the first policy for the platform that the PCRs satisfy.
+    ///
+    /// Fails when no policy covers the platform type and firmware version,
+    /// or when none of the covering policies' measurements match.
+    pub fn evaluate(
+        &self,
+        platform_type: PlatformType,
+        firmware_version: &str,
+        pcrs: &[PcrValue],
+    ) -> crate::Result<&str> {
+        let mut applicable = self
+            .policies
+            .iter()
+            .filter(|policy| policy.applies_to(platform_type, firmware_version))
+            .peekable();
+        if applicable.peek().is_none() {
+            return Err(crate::Error::Identity(format!(
+                "No PCR policy for {:?} firmware {}",
+                platform_type, firmware_version
+            )));
+        }
+
+        applicable
+            .find(|policy| policy.is_satisfied_by(pcrs))
+            .map(|policy| policy.name.as_str())
+            .ok_or_else(|| {
+                crate::Error::Identity(format!(
+                    "PCR values match no policy for {:?} firmware {}",
+                    platform_type, firmware_version
+                ))
+            })
+    }
+
+    /// Evaluate against a node's `platform_type` and `firmware_version`
+    /// metadata.
+    pub fn evaluate_metadata(
+        &self,
+        metadata: &std::collections::HashMap<String, String>,
+        pcrs: &[PcrValue],
+    ) -> crate::Result<&str> {
+        let platform_type = metadata
+            .get(PLATFORM_TYPE_METADATA_KEY)
+            .ok_or_else(|| crate::Error::Identity("Platform type not declared".to_string()))?;
+        let firmware_version = metadata
+            .get(FIRMWARE_VERSION_METADATA_KEY)
+            .map(String::as_str)
+            .unwrap_or_default();
+        self.evaluate(parse_platform_type(platform_type)?, firmware_version, pcrs)
+    }
+}
+
+/// Parse a platform type by its serialized name (e.g. `"GroundStation"`).
+pub fn parse_platform_type(name: &str) -> crate::Result<PlatformType> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| crate::Error::Identity(format!("Unknown platform type {}", name)))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    policy: Vec<PolicyEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyEntry {
+    name: String,
+    platform_types: Vec<PlatformType>,
+    #[serde(default)]
+    firmware_versions: Vec<String>,
+    measurements: Vec<BTreeMap<String, String>>,
+}
+
+impl PolicyEntry {
+    fn validate(self) -> crate::Result<PcrPolicy> {
+        let invalid =
+            |reason: String| crate::Error::Config(format!("PCR policy {}: {}", self.name, reason));
+
+        if self.platform_types.is_empty() {
+            return Err(invalid("no platform types".to_string()));
+        }
+        if self.measurements.is_empty() {
+            return Err(invalid("no measurements".to_string()));
+        }
+
+        let mut measurements = Vec::with_capacity(self.measurements.len());
+        for entry in &self.measurements {
+            if entry.is_empty() {
+                return Err(invalid("empty measurement".to_string()));
+            }
+            let mut measurement = BTreeMap::new();
+            for (index, value) in entry {
+                let index = index
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&index| index < PCR_COUNT)
+                    .ok_or_else(|| invalid(format!("invalid PCR index {}", index)))?;
+                let value = hex::decode(value)
+                    .ok()
+                    .filter(|value| matches!(value.len(), 32 | 48 | 64))
+                    .ok_or_else(|| invalid(format!("invalid value for PCR {}", index)))?;
+                measurement.insert(index, value);
+            }
+            measurements.push(measurement);
+        }
+
+        Ok(PcrPolicy {
+            name: self.name,
+            platform_types: self.platform_types,
+            firmware_versions: self.firmware_versions,
+            measurements,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const POLICY: &str = r#"
+        [[policy]]
+        name = "uas-release"
+        platform_types = ["Uas", "Aerial"]
+        firmware_versions = ["v2.0.0"]
+
+        [[policy.measurements]]
+        0 = "1111111111111111111111111111111111111111111111111111111111111111"
+        7 = "7777777777777777777777777777777777777777777777777777777777777777"
+
+        [[policy.measurements]]
+        0 = "2222222222222222222222222222222222222222222222222222222222222222"
+        7 = "7777777777777777777777777777777777777777777777777777777777777777"
+
+        [[policy]]
+        name = "ground-station"
+        platform_types = ["GroundStation"]
+
+        [[policy.measurements]]
+        0 = "3333333333333333333333333333333333333333333333333333333333333333"
+    "#;
+
+    fn pcrs(values: &[(u8, u8)]) -> Vec<PcrValue> {
+        values
+            .iter()
+            .map(|&(index, byte)| PcrValue {
+                index,
+                value: vec![byte; 32],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_policy_file() {
+        let set = PcrPolicySet::from_toml_str(POLICY).unwrap();
+        assert_eq!(set.policies().len(), 2);
+
+        let uas = &set.policies()[0];
+        assert_eq!(
+            uas.platform_types,
+            vec![PlatformType::Uas, PlatformType::Aerial]
+        );
+        assert_eq!(uas.measurements.len(), 2);
+        assert_eq!(uas.measurements[1][&0], vec![0x22; 32]);
+        assert!(set.policies()[1].firmware_versions.is_empty());
+    }
+
+    #[test]
+    fn test_alternative_measurements() {
+        let set = PcrPolicySet::from_toml_str(POLICY).unwrap();
+
+        for first in [0x11, 0x22] {
+            assert_eq!(
+                set.evaluate(PlatformType::Uas, "v2.0.0", &pcrs(&[(0, first), (7, 0x77)]))
+                    .unwrap(),
+                "uas-release"
+            );
+        }
+        // Mixing measurements or omitting a PCR does not match
+        assert!(set
+            .evaluate(PlatformType::Uas, "v2.0.0", &pcrs(&[(0, 0x11), (7, 0x22)]))
+            .is_err());
+        assert!(set
+            .evaluate(PlatformType::Uas, "v2.0.0", &pcrs(&[(0, 0x11)]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_platform_type_and_firmware_version_scope() {
+        let set = PcrPolicySet::from_toml_str(POLICY).unwrap();
+        let uas_pcrs = pcrs(&[(0, 0x11), (7, 0x77)]);
+
+        assert!(set
+            .evaluate(PlatformType::Uas, "v1.9.0", &uas_pcrs)
+            .is_err());
+        assert!(set
+            .evaluate(PlatformType::Usv, "v2.0.0", &uas_pcrs)
+            .is_err());
+        assert!(set
+            .evaluate(PlatformType::GroundStation, "v2.0.0", &uas_pcrs)
+            .is_err());
+        assert_eq!(
+            set.evaluate(PlatformType::GroundStation, "any", &pcrs(&[(0, 0x33)]))
+                .unwrap(),
+            "ground-station"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_metadata() {
+        let set = PcrPolicySet::from_toml_str(POLICY).unwrap();
+        let uas_pcrs = pcrs(&[(0, 0x22), (7, 0x77)]);
+        let mut metadata = HashMap::from([
+            (PLATFORM_TYPE_METADATA_KEY.to_string(), "Aerial".to_string()),
+            (
+                FIRMWARE_VERSION_METADATA_KEY.to_string(),
+                "v2.0.0".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            set.evaluate_metadata(&metadata, &uas_pcrs).unwrap(),
+            "uas-release"
+        );
+
+        metadata.insert(
+            PLATFORM_TYPE_METADATA_KEY.to_string(),
+            "Zeppelin".to_string(),
+        );
+        assert!(set.evaluate_metadata(&metadata, &uas_pcrs).is_err());
+        metadata.remove(PLATFORM_TYPE_METADATA_KEY);
+        assert!(set.evaluate_metadata(&metadata, &uas_pcrs).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_policies() {
+        let invalid = [
+            // PCR index out of range
+            "[[policy]]\nname = \"p\"\nplatform_types = [\"Uas\"]\n[[policy.measurements]]\n24 = \"00000000000000000000000000000000000000000000000000000000000000ff\"",
+            // Value is not a digest
+            "[[policy]]\nname = \"p\"\nplatform_types = [\"Uas\"]\n[[policy.measurements]]\n0 = \"abcd\"",
+            // No measurements
+            "[[policy]]\nname = \"p\"\nplatform_types = [\"Uas\"]\nmeasurements = []",
+            // Unknown platform type
+            "[[policy]]\nname = \"p\"\nplatform_types = [\"Zeppelin\"]\nmeasurements = [{ 0 = \"00\" }]",
+        ];
+        for content in invalid {
+            assert!(PcrPolicySet::from_toml_str(content).is_err(), "{}", content);
+        }
+    }
+}
@@ -1,10 +1,34 @@
 //! Public Key Infrastructure (PKI) for 4MIK identity management.
 //!
 //! Provides certificate management, key distribution, and trust hierarchy.
+//!
+//! # Revocation
+//!
+//! The CA publishes revocations as signed, numbered Certificate Revocation Lists.
+//! A full CRL lists every revoked serial; a delta CRL lists only serials revoked
+//! since the full CRL it references (`base_crl_number`). Relying parties feed
+//! CRLs into a `TrustChainValidator` and select a `RevocationMode` to have
+//! revoked certificates rejected during chain validation.
+//!
+//! # Hierarchy
+//!
+//! A root CA delegates issuance to intermediate CAs with
+//! `issue_intermediate_ca`. Intermediate certificates carry RFC 5280 basic
+//! constraints (with an optional path length) and key usage extensions, and
+//! `TrustChainValidator` rejects chains in which a certificate is issued by a
+//! non-CA certificate, a CA without `keyCertSign`, or a CA whose path length
+//! is exceeded. A self-signed root at the end of a chain is a trust anchor and
+//! needs no extensions.
+//!
+//! # Renewal
+//!
+//! `renew_certificate` reissues a certificate that has not yet expired to the
+//! same subject and key. The old certificate stays valid until its own
+//! `not_after` but is no longer reported by `expiring_certificates`.
 
 use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// X.509-like certificate for platform identities.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -25,6 +49,9 @@ pub struct Certificate {
     pub signature: Vec<u8>,
     /// Extensions
     pub extensions: HashMap<String, Vec<u8>>,
+    /// DER encoding, for certificates imported from or exported as X.509
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x509_der: Option<Vec<u8>>,
 }
 
 /// Certificate Signing Request (CSR).
@@ -38,6 +65,81 @@ pub struct CertificateRequest {
     pub signature: Vec<u8>,
     /// Attestation data
     pub attestation: crate::device::Attestation,
+    /// DER encoding, for requests imported from PKCS#10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pkcs10_der: Option<Vec<u8>>,
+}
+
+/// A single revoked certificate in a CRL.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrlEntry {
+    /// Revoked certificate serial number
+    pub serial: String,
+    /// Revocation timestamp (Unix timestamp, milliseconds)
+    pub revoked_at: u64,
+    /// Revocation reason
+    pub reason: String,
+}
+
+/// RFC 5280 basic constraints of a certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaConstraints {
+    /// Whether the subject is a CA
+    pub is_ca: bool,
+    /// Maximum number of intermediate CAs that may follow this one
+    pub path_len: Option<u8>,
+}
+
+/// Key usages understood by the PKI (RFC 5280 keyUsage bits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyUsage {
+    /// Signing data other than certificates and CRLs
+    DigitalSignature,
+    /// Signing certificates
+    KeyCertSign,
+    /// Signing CRLs
+    CrlSign,
+}
+
+/// Signed Certificate Revocation List issued by a CA.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CertificateRevocationList {
+    /// Issuing CA
+    pub issuer: String,
+    /// Monotonically increasing CRL number (shared by full and delta CRLs)
+    pub crl_number: u64,
+    /// For a delta CRL, the number of the full CRL it extends
+    pub base_crl_number: Option<u64>,
+    /// Issue time (Unix timestamp, milliseconds)
+    pub this_update: u64,
+    /// Time by which a newer CRL will be issued (Unix timestamp, milliseconds)
+    pub next_update: u64,
+    /// Revoked certificates, sorted by serial
+    pub entries: Vec<CrlEntry>,
+    /// CA signature over the CRL contents
+    pub signature: Vec<u8>,
+}
+
+impl CertificateRevocationList {
+    /// Whether this is a delta CRL.
+    pub fn is_delta(&self) -> bool {
+        self.base_crl_number.is_some()
+    }
+
+    /// Check whether a serial is listed in this CRL.
+    pub fn contains(&self, serial: &str) -> bool {
+        self.entries.iter().any(|entry| entry.serial == serial)
+    }
+
+    /// Whether the CRL is past its `next_update` time.
+    pub fn is_stale(&self, now: u64) -> bool {
+        now > self.next_update
+    }
+
+    /// Verify the CRL signature against the issuer's public key.
+    pub fn verify_signature(&self, issuer_public_key: &[u8]) -> bool {
+        verify_ed25519(issuer_public_key, &self.signature, &crl_tbs_bytes(self))
+    }
 }
 
 /// Certificate Authority managing certificate lifecycle.
@@ -48,14 +150,21 @@ pub struct CertificateAuthority {
     /// CA private key (in production, use HSM)
     ca_private_key: Vec<u8>,
     /// CA public key
-    #[allow(dead_code)]
     ca_public_key: Vec<u8>,
     /// Issued certificates indexed by serial
     certificates: HashMap<String, Certificate>,
-    /// Revoked certificate serials
-    revoked: std::collections::HashSet<String>,
+    /// Revoked certificates indexed by serial
+    revoked: HashMap<String, CrlEntry>,
+    /// Number of the last CRL issued (full or delta)
+    crl_number: u64,
+    /// Number and contents of the last full CRL, base for delta CRLs
+    last_full_crl: Option<(u64, HashSet<String>)>,
     /// Next serial number
     next_serial: u64,
+    /// This CA's own certificate, for a subordinate CA
+    ca_certificate: Option<Certificate>,
+    /// Renewed certificates: old serial -> replacement serial
+    superseded: HashMap<String, String>,
 }
 
 impl CertificateAuthority {
@@ -66,16 +175,80 @@ impl CertificateAuthority {
             ca_private_key,
             ca_public_key,
             certificates: HashMap::new(),
-            revoked: std::collections::HashSet::new(),
+            revoked: HashMap::new(),
+            crl_number: 0,
+            last_full_crl: None,
             next_serial: 1,
+            ca_certificate: None,
+            superseded: HashMap::new(),
         }
     }
 
+    /// Create a subordinate CA from its CA certificate and private key.
+    ///
+    /// The certificate must be a CA certificate permitting `keyCertSign`, and
+    /// the key must match it. Certificates issued by the subordinate never
+    /// outlive its certificate, and its path length limits the intermediates
+    /// it may issue.
+    pub fn subordinate(certificate: Certificate, ca_private_key: Vec<u8>) -> crate::Result<Self> {
+        match certificate.ca_constraints()? {
+            Some(constraints) if constraints.is_ca => {}
+            _ => {
+                return Err(crate::Error::Identity(format!(
+                    "Certificate {} is not a CA certificate",
+                    certificate.serial
+                )))
+            }
+        }
+        if !certificate.permits_key_usage(KeyUsage::KeyCertSign)? {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} does not permit certificate signing",
+                certificate.serial
+            )));
+        }
+        let secret: [u8; 32] = ca_private_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| crate::Error::Identity("Invalid CA private key".to_string()))?;
+        if SigningKey::from_bytes(&secret).verifying_key().as_bytes()
+            != certificate.public_key.as_slice()
+        {
+            return Err(crate::Error::Identity(
+                "CA private key does not match certificate".to_string(),
+            ));
+        }
+
+        let mut ca = Self::new(
+            certificate.subject.clone(),
+            certificate.public_key.clone(),
+            ca_private_key,
+        );
+        ca.ca_certificate = Some(certificate);
+        Ok(ca)
+    }
+
+    /// This CA's own certificate, for a subordinate CA.
+    pub fn certificate(&self) -> Option<&Certificate> {
+        self.ca_certificate.as_ref()
+    }
+
     /// Issue a certificate from a CSR.
     pub fn issue_certificate(
         &mut self,
         request: CertificateRequest,
         validity_days: u64,
+    ) -> crate::Result<Certificate> {
+        self.issue_certificate_with_extensions(request, validity_days, HashMap::new())
+    }
+
+    /// Issue a certificate from a CSR with the given extensions.
+    ///
+    /// Extensions that will be exported to X.509 must be keyed by OID.
+    pub fn issue_certificate_with_extensions(
+        &mut self,
+        request: CertificateRequest,
+        validity_days: u64,
+        extensions: HashMap<String, Vec<u8>>,
     ) -> crate::Result<Certificate> {
         // Verify the CSR signature
         if !self.verify_csr(&request) {
@@ -88,6 +261,10 @@ impl CertificateAuthority {
         }
 
         let now = current_timestamp();
+        let mut not_after = now.saturating_add(validity_days * 24 * 60 * 60 * 1000);
+        if let Some(ca_certificate) = &self.ca_certificate {
+            not_after = not_after.min(ca_certificate.not_after);
+        }
         let serial = self.next_serial.to_string();
         self.next_serial += 1;
 
@@ -97,9 +274,10 @@ impl CertificateAuthority {
             issuer: self.ca_id.clone(),
             public_key: request.public_key,
             not_before: now,
-            not_after: now + (validity_days * 24 * 60 * 60 * 1000),
+            not_after,
             signature: Vec::new(),
-            extensions: HashMap::new(),
+            extensions,
+            x509_der: None,
         };
         cert.signature = self.sign_certificate(&cert);
 
@@ -107,6 +285,140 @@ impl CertificateAuthority {
         Ok(cert)
     }
 
+    /// Issue an intermediate CA certificate from a CSR.
+    ///
+    /// `path_len` limits how many further intermediates may follow the new CA.
+    /// A subordinate CA can only issue intermediates with a path length below
+    /// its own.
+    pub fn issue_intermediate_ca(
+        &mut self,
+        request: CertificateRequest,
+        validity_days: u64,
+        path_len: Option<u8>,
+    ) -> crate::Result<Certificate> {
+        let own_path_len = match &self.ca_certificate {
+            Some(ca_certificate) => ca_certificate
+                .ca_constraints()?
+                .and_then(|constraints| constraints.path_len),
+            None => None,
+        };
+        let path_len = match own_path_len {
+            None => path_len,
+            Some(0) => {
+                return Err(crate::Error::Identity(format!(
+                    "CA {} may not issue intermediate CAs (path length 0)",
+                    self.ca_id
+                )))
+            }
+            Some(own) => match path_len {
+                None => Some(own - 1),
+                Some(requested) if requested < own => Some(requested),
+                Some(requested) => {
+                    return Err(crate::Error::Identity(format!(
+                        "Path length {} exceeds the {} allowed below CA {}",
+                        requested,
+                        own - 1,
+                        self.ca_id
+                    )))
+                }
+            },
+        };
+
+        let constraints = CaConstraints {
+            is_ca: true,
+            path_len,
+        };
+        let extensions = HashMap::from([
+            constraints.to_extension()?,
+            crate::x509::key_usage_extension(&[KeyUsage::KeyCertSign, KeyUsage::CrlSign])?,
+        ]);
+        self.issue_certificate_with_extensions(request, validity_days, extensions)
+    }
+
+    /// Renew a certificate issued by this CA before it expires.
+    ///
+    /// `request` must name the same subject and be signed with the same key as
+    /// `current`; the new certificate keeps its extensions. Each certificate
+    /// can be renewed once, and revoked or expired certificates cannot be
+    /// renewed.
+    pub fn renew_certificate(
+        &mut self,
+        current: &Certificate,
+        request: CertificateRequest,
+        validity_days: u64,
+    ) -> crate::Result<Certificate> {
+        if self.get_certificate(&current.serial) != Some(current) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} was not issued by {}",
+                current.serial, self.ca_id
+            )));
+        }
+        if self.is_revoked(&current.serial) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} is revoked",
+                current.serial
+            )));
+        }
+        if current_timestamp() > current.not_after {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} has expired and must be re-enrolled",
+                current.serial
+            )));
+        }
+        if let Some(replacement) = self.superseded.get(&current.serial) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} was already renewed as {}",
+                current.serial, replacement
+            )));
+        }
+        if request.subject != current.subject || request.public_key != current.public_key {
+            return Err(crate::Error::Identity(
+                "Renewal request must use the subject and key of the current certificate"
+                    .to_string(),
+            ));
+        }
+
+        let renewed = self.issue_certificate_with_extensions(
+            request,
+            validity_days,
+            current.extensions.clone(),
+        )?;
+        self.superseded
+            .insert(current.serial.clone(), renewed.serial.clone());
+        Ok(renewed)
+    }
+
+    /// Serial of the certificate that replaced `serial` on renewal.
+    pub fn superseded_by(&self, serial: &str) -> Option<&str> {
+        self.superseded.get(serial).map(String::as_str)
+    }
+
+    /// Unrevoked, unrenewed certificates expiring within `within_ms`,
+    /// soonest first.
+    pub fn expiring_certificates(&self, within_ms: u64) -> Vec<&Certificate> {
+        let now = current_timestamp();
+        let horizon = now.saturating_add(within_ms);
+        let mut expiring: Vec<&Certificate> = self
+            .certificates
+            .values()
+            .filter(|cert| cert.not_after >= now && cert.not_after <= horizon)
+            .filter(|cert| !self.is_revoked(&cert.serial))
+            .filter(|cert| !self.superseded.contains_key(&cert.serial))
+            .collect();
+        expiring.sort_by(|a, b| a.not_after.cmp(&b.not_after).then(a.serial.cmp(&b.serial)));
+        expiring
+    }
+
+    /// CA identity.
+    pub fn ca_id(&self) -> &str {
+        &self.ca_id
+    }
+
+    /// CA public key.
+    pub fn public_key(&self) -> &[u8] {
+        &self.ca_public_key
+    }
+
     /// Verify a certificate.
     pub fn verify_certificate(&self, cert: &Certificate) -> bool {
         // Check if issued by this CA
@@ -115,7 +427,7 @@ impl CertificateAuthority {
         }
 
         // Check if revoked
-        if self.revoked.contains(&cert.serial) {
+        if self.revoked.contains_key(&cert.serial) {
             return false;
         }
 
@@ -130,17 +442,67 @@ impl CertificateAuthority {
 
     /// Revoke a certificate.
     pub fn revoke_certificate(&mut self, serial: &str) -> crate::Result<()> {
+        self.revoke_certificate_with_reason(serial, "unspecified")
+    }
+
+    /// Revoke a certificate, recording the reason in subsequent CRLs.
+    ///
+    /// Revoking an already revoked certificate keeps the original entry.
+    pub fn revoke_certificate_with_reason(
+        &mut self,
+        serial: &str,
+        reason: &str,
+    ) -> crate::Result<()> {
         if !self.certificates.contains_key(serial) {
             return Err(crate::Error::Identity("Certificate not found".to_string()));
         }
 
-        self.revoked.insert(serial.to_string());
+        self.revoked
+            .entry(serial.to_string())
+            .or_insert_with(|| CrlEntry {
+                serial: serial.to_string(),
+                revoked_at: current_timestamp(),
+                reason: reason.to_string(),
+            });
         Ok(())
     }
 
     /// Check if a certificate is revoked.
     pub fn is_revoked(&self, serial: &str) -> bool {
-        self.revoked.contains(serial)
+        self.revoked.contains_key(serial)
+    }
+
+    /// Issue a full CRL listing every revoked certificate.
+    ///
+    /// The CRL becomes the base for subsequent delta CRLs.
+    pub fn issue_crl(&mut self, validity_ms: u64) -> crate::Result<CertificateRevocationList> {
+        let mut entries: Vec<CrlEntry> = self.revoked.values().cloned().collect();
+        entries.sort_by(|a, b| a.serial.cmp(&b.serial));
+
+        let crl = self.sign_crl(None, entries, validity_ms)?;
+        self.last_full_crl = Some((crl.crl_number, self.revoked.keys().cloned().collect()));
+        Ok(crl)
+    }
+
+    /// Issue a delta CRL listing certificates revoked since the last full CRL.
+    pub fn issue_delta_crl(
+        &mut self,
+        validity_ms: u64,
+    ) -> crate::Result<CertificateRevocationList> {
+        let (base_number, base_serials) = self.last_full_crl.as_ref().ok_or_else(|| {
+            crate::Error::Identity("Delta CRL requires a prior full CRL".to_string())
+        })?;
+        let base_number = *base_number;
+
+        let mut entries: Vec<CrlEntry> = self
+            .revoked
+            .values()
+            .filter(|entry| !base_serials.contains(&entry.serial))
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| a.serial.cmp(&b.serial));
+
+        self.sign_crl(Some(base_number), entries, validity_ms)
     }
 
     /// Get a certificate by serial.
@@ -156,6 +518,9 @@ impl CertificateAuthority {
     // Internal helper methods
 
     fn verify_csr(&self, request: &CertificateRequest) -> bool {
+        if request.pkcs10_der.is_some() {
+            return crate::x509::verify_pkcs10_signature(request);
+        }
         if request.public_key.len() != 32 || request.signature.len() != 64 {
             return false;
         }
@@ -181,22 +546,78 @@ impl CertificateAuthority {
     }
 
     fn sign_certificate(&self, cert: &Certificate) -> Vec<u8> {
+        self.sign_tbs(&certificate_tbs_bytes(cert))
+    }
+
+    fn sign_crl(
+        &mut self,
+        base_crl_number: Option<u64>,
+        entries: Vec<CrlEntry>,
+        validity_ms: u64,
+    ) -> crate::Result<CertificateRevocationList> {
+        let now = current_timestamp();
+        let mut crl = CertificateRevocationList {
+            issuer: self.ca_id.clone(),
+            crl_number: self.crl_number + 1,
+            base_crl_number,
+            this_update: now,
+            next_update: now + validity_ms,
+            entries,
+            signature: Vec::new(),
+        };
+        crl.signature = self.sign_tbs(&crl_tbs_bytes(&crl));
+        if crl.signature.is_empty() {
+            return Err(crate::Error::Identity(
+                "CA signing key unavailable for CRL".to_string(),
+            ));
+        }
+
+        self.crl_number = crl.crl_number;
+        Ok(crl)
+    }
+
+    pub(crate) fn sign_tbs(&self, tbs: &[u8]) -> Vec<u8> {
         if self.ca_private_key.len() != 32 {
             return Vec::new();
         }
         let mut key_bytes = [0u8; 32];
         key_bytes.copy_from_slice(&self.ca_private_key);
         let signing_key = SigningKey::from_bytes(&key_bytes);
-        let tbs = certificate_tbs_bytes(cert);
-        signing_key.sign(&tbs).to_bytes().to_vec()
+        signing_key.sign(tbs).to_bytes().to_vec()
     }
 }
 
+/// Revocation checking performed by `TrustChainValidator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RevocationMode {
+    /// No revocation checking
+    #[default]
+    Disabled,
+    /// Reject certificates listed in a known CRL; issuers without a CRL pass
+    BestEffort,
+    /// Reject certificates listed in a CRL, and reject any certificate whose
+    /// issuer has no current, correctly signed CRL
+    Strict,
+}
+
+/// Latest CRLs known for one issuer.
+#[derive(Debug, Clone)]
+struct IssuerCrls {
+    full: CertificateRevocationList,
+    delta: Option<CertificateRevocationList>,
+}
+
 /// Trust chain validator for certificate chains.
 #[derive(Debug)]
 pub struct TrustChainValidator {
     /// Trusted root CAs
     trusted_roots: HashMap<String, Vec<u8>>,
+    /// Revocation checking mode
+    revocation_mode: RevocationMode,
+    /// Keys of intermediate CAs trusted to sign CRLs, indexed by CA ID
+    crl_issuers: HashMap<String, Vec<u8>>,
+    /// Latest CRLs indexed by issuer
+    crls: HashMap<String, IssuerCrls>,
 }
 
 impl TrustChainValidator {
@@ -204,6 +625,9 @@ impl TrustChainValidator {
     pub fn new() -> Self {
         Self {
             trusted_roots: HashMap::new(),
+            revocation_mode: RevocationMode::Disabled,
+            crl_issuers: HashMap::new(),
+            crls: HashMap::new(),
         }
     }
 
@@ -212,6 +636,101 @@ impl TrustChainValidator {
         self.trusted_roots.insert(ca_id, public_key);
     }
 
+    /// Set the revocation checking mode.
+    pub fn set_revocation_mode(&mut self, mode: RevocationMode) {
+        self.revocation_mode = mode;
+    }
+
+    /// Current revocation checking mode.
+    pub fn revocation_mode(&self) -> RevocationMode {
+        self.revocation_mode
+    }
+
+    /// Trust an intermediate CA to sign CRLs.
+    ///
+    /// `issuer_chain` starts with the intermediate's certificate and must
+    /// validate up to a trusted root.
+    pub fn add_crl_issuer(&mut self, issuer_chain: &[Certificate]) -> crate::Result<()> {
+        if !self.verify_chain(issuer_chain) {
+            return Err(crate::Error::Identity(
+                "CRL issuer chain does not validate".to_string(),
+            ));
+        }
+        let issuer = &issuer_chain[0];
+        if !issuer.permits_key_usage(KeyUsage::CrlSign).unwrap_or(false) {
+            return Err(crate::Error::Identity(format!(
+                "CA {} may not sign CRLs",
+                issuer.subject
+            )));
+        }
+        self.crl_issuers
+            .insert(issuer.subject.clone(), issuer.public_key.clone());
+        Ok(())
+    }
+
+    /// Add a CRL for use during chain validation.
+    ///
+    /// The CRL must be signed by a trusted root or an intermediate added with
+    /// `add_crl_issuer`; anything else is rejected and not stored. Older CRLs
+    /// than the ones already held are ignored. A delta CRL must reference the
+    /// full CRL currently held for its issuer.
+    pub fn add_crl(&mut self, crl: CertificateRevocationList) -> crate::Result<()> {
+        let issuer_key = self
+            .trusted_roots
+            .get(&crl.issuer)
+            .or_else(|| self.crl_issuers.get(&crl.issuer))
+            .ok_or_else(|| {
+                crate::Error::Identity(format!("No trusted key for CRL issuer {}", crl.issuer))
+            })?;
+        if !crl.verify_signature(issuer_key) {
+            return Err(crate::Error::Identity(format!(
+                "CRL signature invalid for issuer {}",
+                crl.issuer
+            )));
+        }
+
+        match crl.base_crl_number {
+            None => {
+                let newer = self
+                    .crls
+                    .get(&crl.issuer)
+                    .is_none_or(|held| crl.crl_number > held.full.crl_number);
+                if newer {
+                    self.crls.insert(
+                        crl.issuer.clone(),
+                        IssuerCrls {
+                            full: crl,
+                            delta: None,
+                        },
+                    );
+                }
+                Ok(())
+            }
+            Some(base) => {
+                let held = self.crls.get_mut(&crl.issuer).ok_or_else(|| {
+                    crate::Error::Identity(format!(
+                        "No full CRL held for issuer {} to apply delta CRL",
+                        crl.issuer
+                    ))
+                })?;
+                if base != held.full.crl_number {
+                    return Err(crate::Error::Identity(format!(
+                        "Delta CRL base {} does not match held full CRL {}",
+                        base, held.full.crl_number
+                    )));
+                }
+                if held
+                    .delta
+                    .as_ref()
+                    .is_none_or(|delta| crl.crl_number > delta.crl_number)
+                {
+                    held.delta = Some(crl);
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Verify a certificate chain.
     pub fn verify_chain(&self, certificates: &[Certificate]) -> bool {
         if certificates.is_empty() {
@@ -231,6 +750,9 @@ impl TrustChainValidator {
         if !verify_certificate_signature(root, root_public_key) {
             return false;
         }
+        if !self.check_revocation(root, root_public_key) {
+            return false;
+        }
 
         // Verify each certificate in chain
         for i in 0..certificates.len() - 1 {
@@ -242,6 +764,13 @@ impl TrustChainValidator {
                 return false;
             }
 
+            // Issuer must be a CA allowed to sign certificates this deep;
+            // `i` intermediates sit between it and the end-entity certificate
+            let trust_anchor = i + 2 == certificates.len() && issuer.issuer == issuer.subject;
+            if !issuer_constraints_valid(issuer, i, trust_anchor) {
+                return false;
+            }
+
             if !is_certificate_time_valid(cert) {
                 return false;
             }
@@ -249,10 +778,64 @@ impl TrustChainValidator {
             if !verify_certificate_signature(cert, &issuer.public_key) {
                 return false;
             }
+
+            if !self.check_revocation(cert, &issuer.public_key) {
+                return false;
+            }
         }
 
         true
     }
+
+    /// Check a certificate against the CRLs held for its issuer.
+    ///
+    /// Returns false if the certificate must be rejected.
+    fn check_revocation(&self, cert: &Certificate, issuer_public_key: &[u8]) -> bool {
+        if self.revocation_mode == RevocationMode::Disabled {
+            return true;
+        }
+        let strict = self.revocation_mode == RevocationMode::Strict;
+
+        let held = match self.crls.get(&cert.issuer) {
+            Some(held) => held,
+            None => {
+                if strict {
+                    tracing::warn!(issuer = %cert.issuer, "No CRL held for issuer");
+                }
+                return !strict;
+            }
+        };
+
+        // CRLs are verified when added, so a mismatch means the chain names
+        // an issuer whose key differs from the one that signed its CRL
+        if !held.full.verify_signature(issuer_public_key) {
+            tracing::warn!(issuer = %cert.issuer, "CRL signature invalid");
+            return false;
+        }
+        // Revocation is permanent, so a stale CRL still proves revocation
+        if held.full.contains(&cert.serial) {
+            return false;
+        }
+
+        let now = current_timestamp();
+        let mut current = !held.full.is_stale(now);
+        if let Some(delta) = &held.delta {
+            if !delta.verify_signature(issuer_public_key) {
+                tracing::warn!(issuer = %cert.issuer, "Delta CRL signature invalid");
+                return false;
+            }
+            if delta.contains(&cert.serial) {
+                return false;
+            }
+            current = current || !delta.is_stale(now);
+        }
+
+        if strict && !current {
+            tracing::warn!(issuer = %cert.issuer, "CRL for issuer is stale");
+            return false;
+        }
+        true
+    }
 }
 
 impl Default for TrustChainValidator {
@@ -262,7 +845,7 @@ impl Default for TrustChainValidator {
 }
 
 /// Get current timestamp in milliseconds.
-fn current_timestamp() -> u64 {
+pub(crate) fn current_timestamp() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -270,7 +853,7 @@ fn current_timestamp() -> u64 {
         .as_millis() as u64
 }
 
-fn csr_tbs_bytes(request: &CertificateRequest) -> Vec<u8> {
+pub(crate) fn csr_tbs_bytes(request: &CertificateRequest) -> Vec<u8> {
     let mut data = Vec::new();
     write_bytes(&mut data, request.subject.as_bytes());
     write_bytes(&mut data, &request.public_key);
@@ -279,7 +862,7 @@ fn csr_tbs_bytes(request: &CertificateRequest) -> Vec<u8> {
     data
 }
 
-fn certificate_tbs_bytes(cert: &Certificate) -> Vec<u8> {
+pub(crate) fn certificate_tbs_bytes(cert: &Certificate) -> Vec<u8> {
     let mut data = Vec::new();
     write_bytes(&mut data, cert.serial.as_bytes());
     write_bytes(&mut data, cert.subject.as_bytes());
@@ -298,34 +881,106 @@ fn certificate_tbs_bytes(cert: &Certificate) -> Vec<u8> {
     data
 }
 
+fn crl_tbs_bytes(crl: &CertificateRevocationList) -> Vec<u8> {
+    let mut data = Vec::new();
+    write_bytes(&mut data, crl.issuer.as_bytes());
+    data.extend_from_slice(&crl.crl_number.to_be_bytes());
+    match crl.base_crl_number {
+        Some(base) => {
+            data.push(1);
+            data.extend_from_slice(&base.to_be_bytes());
+        }
+        None => data.push(0),
+    }
+    data.extend_from_slice(&crl.this_update.to_be_bytes());
+    data.extend_from_slice(&crl.next_update.to_be_bytes());
+    data.extend_from_slice(&(crl.entries.len() as u32).to_be_bytes());
+    for entry in &crl.entries {
+        write_bytes(&mut data, entry.serial.as_bytes());
+        data.extend_from_slice(&entry.revoked_at.to_be_bytes());
+        write_bytes(&mut data, entry.reason.as_bytes());
+    }
+    data
+}
+
 fn write_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
     let len = bytes.len() as u32;
     buffer.extend_from_slice(&len.to_be_bytes());
     buffer.extend_from_slice(bytes);
 }
 
+/// Check that `issuer` may sign a certificate with `depth` intermediates below it.
+///
+/// A trust anchor without basic constraints is accepted; any constraints it
+/// does carry are enforced.
+fn issuer_constraints_valid(issuer: &Certificate, depth: usize, trust_anchor: bool) -> bool {
+    let constraints = match issuer.ca_constraints() {
+        Ok(Some(constraints)) => constraints,
+        Ok(None) => {
+            if !trust_anchor {
+                tracing::warn!(serial = %issuer.serial, "Issuer has no basic constraints");
+            }
+            return trust_anchor;
+        }
+        Err(e) => {
+            tracing::warn!(serial = %issuer.serial, "Invalid basic constraints: {}", e);
+            return false;
+        }
+    };
+
+    if !constraints.is_ca {
+        tracing::warn!(serial = %issuer.serial, "Issuer is not a CA");
+        return false;
+    }
+    if constraints
+        .path_len
+        .is_some_and(|path_len| depth > usize::from(path_len))
+    {
+        tracing::warn!(serial = %issuer.serial, "Issuer path length exceeded");
+        return false;
+    }
+    if !issuer
+        .permits_key_usage(KeyUsage::KeyCertSign)
+        .unwrap_or(false)
+    {
+        tracing::warn!(serial = %issuer.serial, "Issuer may not sign certificates");
+        return false;
+    }
+    true
+}
+
 fn is_certificate_time_valid(cert: &Certificate) -> bool {
     let now = current_timestamp();
     now >= cert.not_before && now <= cert.not_after
 }
 
 fn verify_certificate_signature(cert: &Certificate, issuer_public_key: &[u8]) -> bool {
-    if issuer_public_key.len() != 32 || cert.signature.len() != 64 {
+    if cert.x509_der.is_some() {
+        return crate::x509::verify_x509_signature(cert, issuer_public_key);
+    }
+    verify_ed25519(
+        issuer_public_key,
+        &cert.signature,
+        &certificate_tbs_bytes(cert),
+    )
+}
+
+fn verify_ed25519(public_key: &[u8], signature: &[u8], message: &[u8]) -> bool {
+    if public_key.len() != 32 || signature.len() != 64 {
         return false;
     }
     let mut key_bytes = [0u8; 32];
-    key_bytes.copy_from_slice(issuer_public_key);
+    key_bytes.copy_from_slice(public_key);
     let verifying_key = match VerifyingKey::from_bytes(&key_bytes) {
         Ok(key) => key,
         Err(_) => return false,
     };
-    let signature_bytes: [u8; 64] = match cert.signature.as_slice().try_into() {
+    let signature_bytes: [u8; 64] = match signature.try_into() {
         Ok(bytes) => bytes,
         Err(_) => return false,
     };
     let signature = Ed25519Signature::from_bytes(&signature_bytes);
-    let tbs = certificate_tbs_bytes(cert);
-    verifying_key.verify(&tbs, &signature).is_ok()
+    verifying_key.verify(message, &signature).is_ok()
 }
 
 #[cfg(test)]
@@ -347,6 +1002,7 @@ mod tests {
             attestation: Attestation::Software {
                 certificate: vec![9, 10, 11, 12],
             },
+            pkcs10_der: None,
         };
         let tbs = csr_tbs_bytes(&request);
         request.signature = signing_key.sign(&tbs).to_bytes().to_vec();
@@ -377,6 +1033,7 @@ mod tests {
             not_after: u64::MAX,
             signature: Vec::new(),
             extensions: HashMap::new(),
+            x509_der: None,
         };
         cert.signature = sign_certificate(&cert, signing_key);
         cert
@@ -467,4 +1124,331 @@ mod tests {
         let certs = ca.list_certificates();
         assert_eq!(certs.len(), 2);
     }
+
+    fn issued_chain(ca: &mut CertificateAuthority, root_seed: u8) -> Vec<Certificate> {
+        let leaf = ca
+            .issue_certificate(
+                create_test_csr("platform-1", &signing_key_from_seed(10)),
+                365,
+            )
+            .unwrap();
+        let root = build_root_cert(&ca.ca_id, &signing_key_from_seed(root_seed));
+        vec![leaf, root]
+    }
+
+    fn crl_validator(mode: RevocationMode) -> TrustChainValidator {
+        let mut validator = TrustChainValidator::new();
+        validator.add_trusted_root(
+            "test-ca".to_string(),
+            signing_key_from_seed(42)
+                .verifying_key()
+                .to_bytes()
+                .to_vec(),
+        );
+        validator.set_revocation_mode(mode);
+        validator
+    }
+
+    #[test]
+    fn test_full_crl_signed_and_numbered() {
+        let mut ca = build_ca(42, "test-ca");
+        let chain = issued_chain(&mut ca, 42);
+        ca.revoke_certificate_with_reason(&chain[0].serial, "key compromise")
+            .unwrap();
+
+        let first = ca.issue_crl(60_000).unwrap();
+        let second = ca.issue_crl(60_000).unwrap();
+
+        assert!(!first.is_delta());
+        assert_eq!(first.crl_number, 1);
+        assert_eq!(second.crl_number, 2);
+        assert!(first.contains(&chain[0].serial));
+        assert_eq!(first.entries[0].reason, "key compromise");
+        assert!(first.verify_signature(&ca.ca_public_key));
+
+        let mut tampered = first.clone();
+        tampered.entries.clear();
+        assert!(!tampered.verify_signature(&ca.ca_public_key));
+    }
+
+    #[test]
+    fn test_delta_crl_lists_new_revocations_only() {
+        let mut ca = build_ca(42, "test-ca");
+        let csr_key = signing_key_from_seed(10);
+        let first = ca
+            .issue_certificate(create_test_csr("platform-1", &csr_key), 365)
+            .unwrap();
+        let second = ca
+            .issue_certificate(create_test_csr("platform-2", &csr_key), 365)
+            .unwrap();
+
+        assert!(ca.issue_delta_crl(60_000).is_err());
+
+        ca.revoke_certificate(&first.serial).unwrap();
+        let full = ca.issue_crl(60_000).unwrap();
+        ca.revoke_certificate(&second.serial).unwrap();
+        let delta = ca.issue_delta_crl(60_000).unwrap();
+
+        assert_eq!(delta.base_crl_number, Some(full.crl_number));
+        assert!(delta.crl_number > full.crl_number);
+        assert!(delta.contains(&second.serial));
+        assert!(!delta.contains(&first.serial));
+    }
+
+    #[test]
+    fn test_validator_rejects_revoked_chain() {
+        let mut ca = build_ca(42, "test-ca");
+        let chain = issued_chain(&mut ca, 42);
+        let mut validator = crl_validator(RevocationMode::BestEffort);
+
+        validator.add_crl(ca.issue_crl(60_000).unwrap()).unwrap();
+        assert!(validator.verify_chain(&chain));
+
+        ca.revoke_certificate(&chain[0].serial).unwrap();
+        validator
+            .add_crl(ca.issue_delta_crl(60_000).unwrap())
+            .unwrap();
+        assert!(!validator.verify_chain(&chain));
+
+        validator.set_revocation_mode(RevocationMode::Disabled);
+        assert!(validator.verify_chain(&chain));
+    }
+
+    #[test]
+    fn test_strict_mode_requires_valid_crl() {
+        let mut ca = build_ca(42, "test-ca");
+        let chain = issued_chain(&mut ca, 42);
+
+        let mut validator = crl_validator(RevocationMode::Strict);
+        assert!(!validator.verify_chain(&chain));
+
+        // CRL signed by the wrong key is refused rather than stored
+        let mut forged = build_ca(7, "test-ca");
+        forged.certificates = ca.certificates.clone();
+        assert!(validator
+            .add_crl(forged.issue_crl(60_000).unwrap())
+            .is_err());
+        assert!(!validator.verify_chain(&chain));
+
+        let mut validator = crl_validator(RevocationMode::Strict);
+        validator.add_crl(ca.issue_crl(60_000).unwrap()).unwrap();
+        assert!(validator.verify_chain(&chain));
+
+        // A forged CRL cannot displace the genuine one with a higher number
+        let mut validator = crl_validator(RevocationMode::BestEffort);
+        ca.revoke_certificate(&chain[0].serial).unwrap();
+        validator.add_crl(ca.issue_crl(60_000).unwrap()).unwrap();
+        forged.crl_number = 100;
+        assert!(validator
+            .add_crl(forged.issue_crl(60_000).unwrap())
+            .is_err());
+        assert!(!validator.verify_chain(&chain));
+    }
+
+    #[test]
+    fn test_validator_ignores_older_and_mismatched_crls() {
+        let mut ca = build_ca(42, "test-ca");
+        let chain = issued_chain(&mut ca, 42);
+        let mut validator = crl_validator(RevocationMode::BestEffort);
+
+        let stale_base = ca.issue_crl(60_000).unwrap();
+        ca.revoke_certificate(&chain[0].serial).unwrap();
+        let current = ca.issue_crl(60_000).unwrap();
+        let delta = ca.issue_delta_crl(60_000).unwrap();
+
+        validator.add_crl(current).unwrap();
+        validator.add_crl(stale_base).unwrap();
+        assert!(!validator.verify_chain(&chain));
+
+        let mut other = crl_validator(RevocationMode::BestEffort);
+        assert!(other.add_crl(delta).is_err());
+    }
+
+    /// Root CA "test-ca" (seed 42) with an intermediate "issuing-ca" (seed 43).
+    fn intermediate_hierarchy(
+        path_len: Option<u8>,
+    ) -> (CertificateAuthority, CertificateAuthority) {
+        let mut root = build_ca(42, "test-ca");
+        let intermediate_key = signing_key_from_seed(43);
+        let intermediate_cert = root
+            .issue_intermediate_ca(
+                create_test_csr("issuing-ca", &intermediate_key),
+                365,
+                path_len,
+            )
+            .unwrap();
+        let intermediate = CertificateAuthority::subordinate(
+            intermediate_cert,
+            intermediate_key.to_bytes().to_vec(),
+        )
+        .unwrap();
+        (root, intermediate)
+    }
+
+    #[test]
+    fn test_intermediate_chain_validates() {
+        let (_, mut intermediate) = intermediate_hierarchy(Some(0));
+        let leaf = intermediate
+            .issue_certificate(
+                create_test_csr("platform-1", &signing_key_from_seed(10)),
+                365,
+            )
+            .unwrap();
+        let intermediate_cert = intermediate.certificate().unwrap().clone();
+
+        assert_eq!(
+            intermediate_cert.ca_constraints().unwrap(),
+            Some(CaConstraints {
+                is_ca: true,
+                path_len: Some(0),
+            })
+        );
+        assert!(leaf.not_after <= intermediate_cert.not_after);
+
+        let root_cert = build_root_cert("test-ca", &signing_key_from_seed(42));
+        let validator = crl_validator(RevocationMode::Disabled);
+        assert!(validator.verify_chain(&[leaf, intermediate_cert, root_cert]));
+    }
+
+    #[test]
+    fn test_intermediate_crl_requires_trusted_issuer() {
+        let (_, mut intermediate) = intermediate_hierarchy(Some(0));
+        let leaf = intermediate
+            .issue_certificate(
+                create_test_csr("platform-1", &signing_key_from_seed(10)),
+                365,
+            )
+            .unwrap();
+        let intermediate_cert = intermediate.certificate().unwrap().clone();
+        let root_cert = build_root_cert("test-ca", &signing_key_from_seed(42));
+        let chain = [leaf.clone(), intermediate_cert.clone(), root_cert.clone()];
+        let mut validator = crl_validator(RevocationMode::BestEffort);
+
+        intermediate.revoke_certificate(&leaf.serial).unwrap();
+        let crl = intermediate.issue_crl(60_000).unwrap();
+        assert!(validator.add_crl(crl.clone()).is_err());
+        assert!(validator.verify_chain(&chain));
+
+        // A self-signed certificate claiming the intermediate's name is not
+        // trusted for CRLs
+        let impostor = build_root_cert("issuing-ca", &signing_key_from_seed(43));
+        assert!(validator.add_crl_issuer(&[impostor]).is_err());
+
+        validator
+            .add_crl_issuer(&[intermediate_cert, root_cert])
+            .unwrap();
+        validator.add_crl(crl).unwrap();
+        assert!(!validator.verify_chain(&chain));
+    }
+
+    #[test]
+    fn test_path_length_limits_issuance() {
+        let (_, mut intermediate) = intermediate_hierarchy(Some(0));
+        let request = create_test_csr("sub-ca", &signing_key_from_seed(44));
+        assert!(intermediate
+            .issue_intermediate_ca(request.clone(), 365, None)
+            .is_err());
+
+        let (_, mut intermediate) = intermediate_hierarchy(Some(1));
+        assert!(intermediate
+            .issue_intermediate_ca(request.clone(), 365, Some(1))
+            .is_err());
+        let sub_ca = intermediate
+            .issue_intermediate_ca(request, 365, None)
+            .unwrap();
+        assert_eq!(sub_ca.ca_constraints().unwrap().unwrap().path_len, Some(0));
+    }
+
+    #[test]
+    fn test_validator_enforces_ca_constraints() {
+        let (root, mut intermediate) = intermediate_hierarchy(Some(0));
+        let intermediate_cert = intermediate.certificate().unwrap().clone();
+        let root_cert = build_root_cert("test-ca", &signing_key_from_seed(42));
+        let validator = crl_validator(RevocationMode::Disabled);
+
+        // An intermediate that ignores its path length is rejected by relying parties
+        let sub_key = signing_key_from_seed(44);
+        let sub_cert = intermediate
+            .issue_certificate_with_extensions(
+                create_test_csr("sub-ca", &sub_key),
+                365,
+                HashMap::from([CaConstraints {
+                    is_ca: true,
+                    path_len: None,
+                }
+                .to_extension()
+                .unwrap()]),
+            )
+            .unwrap();
+        let mut sub_ca = build_ca(44, "sub-ca");
+        let leaf = sub_ca
+            .issue_certificate(
+                create_test_csr("platform-1", &signing_key_from_seed(10)),
+                365,
+            )
+            .unwrap();
+        assert!(!validator.verify_chain(&[
+            leaf,
+            sub_cert,
+            intermediate_cert.clone(),
+            root_cert.clone()
+        ]));
+
+        // A leaf certificate cannot act as an issuer
+        let device_key = signing_key_from_seed(45);
+        let device_cert = intermediate
+            .issue_certificate(create_test_csr("device-1", &device_key), 365)
+            .unwrap();
+        let mut device_as_ca = build_ca(45, "device-1");
+        let forged = device_as_ca
+            .issue_certificate(
+                create_test_csr("platform-2", &signing_key_from_seed(11)),
+                365,
+            )
+            .unwrap();
+        assert!(!validator.verify_chain(&[forged, device_cert, intermediate_cert, root_cert]));
+
+        // Subordinate CAs need a CA certificate
+        let leaf = root.get_certificate("1").unwrap().clone();
+        assert!(CertificateAuthority::subordinate(leaf, vec![0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_renewal_and_expiry_tracking() {
+        let mut ca = build_ca(42, "test-ca");
+        let csr_key = signing_key_from_seed(10);
+        let short_lived = ca
+            .issue_certificate(create_test_csr("platform-1", &csr_key), 1)
+            .unwrap();
+        let long_lived = ca
+            .issue_certificate(create_test_csr("platform-2", &csr_key), 365)
+            .unwrap();
+
+        let week_ms = 7 * 24 * 60 * 60 * 1000;
+        let expiring = ca.expiring_certificates(week_ms);
+        assert_eq!(expiring, vec![&short_lived]);
+
+        // Only the subject's current key may renew
+        let other_key = signing_key_from_seed(11);
+        assert!(ca
+            .renew_certificate(&short_lived, create_test_csr("platform-1", &other_key), 30)
+            .is_err());
+
+        let renewed = ca
+            .renew_certificate(&short_lived, create_test_csr("platform-1", &csr_key), 30)
+            .unwrap();
+        assert_eq!(
+            ca.superseded_by(&short_lived.serial),
+            Some(renewed.serial.as_str())
+        );
+        assert!(ca.expiring_certificates(week_ms).is_empty());
+        assert!(ca
+            .renew_certificate(&short_lived, create_test_csr("platform-1", &csr_key), 30)
+            .is_err());
+
+        ca.revoke_certificate(&long_lived.serial).unwrap();
+        assert!(ca
+            .renew_certificate(&long_lived, create_test_csr("platform-2", &csr_key), 30)
+            .is_err());
+    }
 }
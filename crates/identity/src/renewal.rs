@@ -0,0 +1,393 @@
+//! Attested certificate renewal.
+//!
+//! A device renews its certificate before `not_after` without re-enrolling:
+//!
+//! 1. The device asks for a renewal challenge for its current certificate
+//! 2. It signs a CSR for the same subject with its current key, and produces
+//!    a TPM quote over the challenge nonce with its enrolled attestation key
+//! 3. The `RenewalManager` checks the nonce, the attestation key and the
+//!    quote, then has the CA reissue the certificate
+//!
+//! Challenges are single-use and expire after `CHALLENGE_WINDOW_MS`. Devices
+//! reach the manager through the registry's GetRenewalChallenge and
+//! RenewCertificate RPCs, which enroll attestation keys at RegisterNode.
+//! A manager created with `RenewalManager::open` keeps those enrollments in
+//! the `IdentityStore`, so devices can still renew after a registry restart.
+
+use crate::enrollment::{CHALLENGE_WINDOW_MS, REQUIRED_PCRS};
+use crate::identity_store::IdentityStore;
+use crate::pki::{Certificate, CertificateAuthority, CertificateRequest};
+use crate::tpm::{AttestationKey, TpmManager, TpmQuote};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::info;
+
+/// Size of renewal challenge nonces in bytes.
+const NONCE_SIZE: usize = 32;
+
+/// Request to renew a certificate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewalRequest {
+    /// Certificate being renewed
+    pub certificate: Certificate,
+    /// CSR for the same subject, signed with the current key
+    pub csr: CertificateRequest,
+    /// TPM quote over the renewal challenge nonce
+    pub quote: TpmQuote,
+    /// Attestation key that produced the quote
+    pub attestation_key: AttestationKey,
+}
+
+/// Outstanding renewal challenge.
+#[derive(Debug, Clone)]
+struct PendingChallenge {
+    nonce: Vec<u8>,
+    issued_at: u64,
+}
+
+/// Issues renewal challenges and verifies attested renewal requests.
+#[derive(Debug)]
+pub struct RenewalManager {
+    /// Enrolled attestation key public keys indexed by certificate subject
+    attestation_keys: HashMap<String, Vec<u8>>,
+    /// Outstanding challenges indexed by certificate serial
+    challenges: HashMap<String, PendingChallenge>,
+    /// Validity of renewed certificates
+    validity_days: u64,
+    /// Durable store for attestation key enrollments (None when in-memory)
+    store: Option<IdentityStore>,
+}
+
+impl RenewalManager {
+    /// Create a renewal manager issuing certificates valid for `validity_days`.
+    pub fn new(validity_days: u64) -> Self {
+        Self {
+            attestation_keys: HashMap::new(),
+            challenges: HashMap::new(),
+            validity_days,
+            store: None,
+        }
+    }
+
+    /// Open a renewal manager whose attestation keys persist in SQLite at `path`.
+    ///
+    /// Previously enrolled attestation keys are loaded on start.
+    pub fn open(path: impl AsRef<Path>, validity_days: u64) -> crate::Result<Self> {
+        let store = IdentityStore::open(path).map_err(store_error)?;
+        Self::with_store(store, validity_days)
+    }
+
+    /// Create a renewal manager over an already-open store.
+    pub fn with_store(store: IdentityStore, validity_days: u64) -> crate::Result<Self> {
+        let mut manager = Self::new(validity_days);
+        manager.attestation_keys = store
+            .load_attestation_keys()
+            .map_err(store_error)?
+            .into_iter()
+            .collect();
+        manager.store = Some(store);
+
+        info!(
+            attestation_keys = manager.attestation_keys.len(),
+            "Renewal attestation keys loaded from store"
+        );
+        Ok(manager)
+    }
+
+    /// Record the attestation key enrolled for `subject`.
+    ///
+    /// Renewal quotes for the subject must be signed by this key. With a
+    /// backing store the key is persisted before it takes effect.
+    pub fn register_attestation_key(
+        &mut self,
+        subject: impl Into<String>,
+        ak_public_key: Vec<u8>,
+    ) -> crate::Result<()> {
+        let subject = subject.into();
+        if let Some(store) = &self.store {
+            store
+                .upsert_attestation_key(&subject, &ak_public_key)
+                .map_err(store_error)?;
+        }
+        self.attestation_keys.insert(subject, ak_public_key);
+        Ok(())
+    }
+
+    /// Attestation key public key enrolled for `subject`.
+    pub fn attestation_key(&self, subject: &str) -> Option<&[u8]> {
+        self.attestation_keys.get(subject).map(Vec::as_slice)
+    }
+
+    /// Issue a challenge nonce for renewing `certificate`.
+    ///
+    /// Replaces any earlier challenge for the same certificate.
+    pub fn issue_challenge(
+        &mut self,
+        ca: &CertificateAuthority,
+        certificate: &Certificate,
+    ) -> crate::Result<Vec<u8>> {
+        if ca.get_certificate(&certificate.serial) != Some(certificate) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} was not issued by {}",
+                certificate.serial,
+                ca.ca_id()
+            )));
+        }
+        if ca.is_revoked(&certificate.serial) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} is revoked",
+                certificate.serial
+            )));
+        }
+
+        let mut nonce = vec![0u8; NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        self.challenges.insert(
+            certificate.serial.clone(),
+            PendingChallenge {
+                nonce: nonce.clone(),
+                issued_at: current_timestamp(),
+            },
+        );
+        Ok(nonce)
+    }
+
+    /// Verify a renewal request and have `ca` reissue the certificate.
+    ///
+    /// Consumes the certificate's challenge whether or not renewal succeeds.
+    pub fn renew(
+        &mut self,
+        ca: &mut CertificateAuthority,
+        tpm: &TpmManager,
+        request: RenewalRequest,
+    ) -> crate::Result<Certificate> {
+        let serial = &request.certificate.serial;
+        let challenge = self.challenges.remove(serial).ok_or_else(|| {
+            crate::Error::Identity(format!("No renewal challenge issued for {}", serial))
+        })?;
+        if current_timestamp().saturating_sub(challenge.issued_at) > CHALLENGE_WINDOW_MS {
+            return Err(crate::Error::Identity(
+                "Renewal challenge expired".to_string(),
+            ));
+        }
+        if request.quote.nonce != challenge.nonce {
+            return Err(crate::Error::Identity(
+                "TPM quote does not cover the renewal challenge".to_string(),
+            ));
+        }
+
+        let enrolled_ak = self
+            .attestation_keys
+            .get(&request.certificate.subject)
+            .ok_or_else(|| {
+                crate::Error::Identity(format!(
+                    "No attestation key enrolled for {}",
+                    request.certificate.subject
+                ))
+            })?;
+        if *enrolled_ak != request.attestation_key.public_key {
+            return Err(crate::Error::Identity(
+                "Quote signed by an unenrolled attestation key".to_string(),
+            ));
+        }
+
+        let quoted_pcrs: Vec<u8> = request.quote.pcrs.iter().map(|pcr| pcr.index).collect();
+        if !REQUIRED_PCRS
+            .iter()
+            .all(|index| quoted_pcrs.contains(index))
+        {
+            return Err(crate::Error::Identity(
+                "TPM quote is missing required PCRs".to_string(),
+            ));
+        }
+        if !tpm.verify_quote(&request.quote, &request.attestation_key) {
+            return Err(crate::Error::Identity(
+                "TPM quote verification failed".to_string(),
+            ));
+        }
+
+        ca.renew_certificate(&request.certificate, request.csr, self.validity_days)
+    }
+}
+
+fn store_error(err: crate::IdentityError) -> crate::Error {
+    crate::Error::Identity(format!("Identity store error: {}", err))
+}
+
+fn current_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::Attestation;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    struct Device {
+        key: SigningKey,
+        tpm: TpmManager,
+        ak: AttestationKey,
+    }
+
+    impl Device {
+        fn new() -> Self {
+            let mut tpm = TpmManager::new(false);
+            let ak = tpm
+                .generate_attestation_key("device-ak".to_string())
+                .unwrap();
+            Self {
+                key: SigningKey::from_bytes(&[10u8; 32]),
+                tpm,
+                ak,
+            }
+        }
+
+        fn csr(&self) -> CertificateRequest {
+            let mut request = CertificateRequest {
+                subject: "platform-1".to_string(),
+                public_key: self.key.verifying_key().to_bytes().to_vec(),
+                signature: Vec::new(),
+                attestation: Attestation::Software {
+                    certificate: vec![1, 2, 3],
+                },
+                pkcs10_der: None,
+            };
+            request.signature = self
+                .key
+                .sign(&crate::pki::csr_tbs_bytes(&request))
+                .to_bytes()
+                .to_vec();
+            request
+        }
+
+        fn renewal_request(&self, certificate: &Certificate, nonce: Vec<u8>) -> RenewalRequest {
+            RenewalRequest {
+                certificate: certificate.clone(),
+                csr: self.csr(),
+                quote: self.tpm.generate_quote(nonce, &REQUIRED_PCRS).unwrap(),
+                attestation_key: self.ak.clone(),
+            }
+        }
+    }
+
+    fn setup() -> (CertificateAuthority, RenewalManager, Device, Certificate) {
+        let ca_key = SigningKey::from_bytes(&[42u8; 32]);
+        let mut ca = CertificateAuthority::new(
+            "test-ca",
+            ca_key.verifying_key().to_bytes().to_vec(),
+            ca_key.to_bytes().to_vec(),
+        );
+        let device = Device::new();
+        let certificate = ca.issue_certificate(device.csr(), 30).unwrap();
+
+        let mut renewals = RenewalManager::new(30);
+        renewals
+            .register_attestation_key("platform-1", device.ak.public_key.clone())
+            .unwrap();
+        (ca, renewals, device, certificate)
+    }
+
+    #[test]
+    fn test_renewal_with_fresh_quote() {
+        let (mut ca, mut renewals, device, certificate) = setup();
+        let verifier = TpmManager::new(false);
+
+        let nonce = renewals.issue_challenge(&ca, &certificate).unwrap();
+        let renewed = renewals
+            .renew(
+                &mut ca,
+                &verifier,
+                device.renewal_request(&certificate, nonce),
+            )
+            .unwrap();
+
+        assert_ne!(renewed.serial, certificate.serial);
+        assert_eq!(renewed.subject, certificate.subject);
+        assert_eq!(renewed.public_key, certificate.public_key);
+        assert_eq!(
+            ca.superseded_by(&certificate.serial),
+            Some(renewed.serial.as_str())
+        );
+        assert!(ca.verify_certificate(&renewed));
+    }
+
+    #[test]
+    fn test_renewal_requires_matching_challenge() {
+        let (mut ca, mut renewals, device, certificate) = setup();
+        let verifier = TpmManager::new(false);
+
+        // No challenge issued
+        let request = device.renewal_request(&certificate, vec![7u8; NONCE_SIZE]);
+        assert!(renewals.renew(&mut ca, &verifier, request).is_err());
+
+        // Quote over a different nonce
+        renewals.issue_challenge(&ca, &certificate).unwrap();
+        let request = device.renewal_request(&certificate, vec![7u8; NONCE_SIZE]);
+        assert!(renewals.renew(&mut ca, &verifier, request).is_err());
+
+        // Challenges are single-use
+        let nonce = renewals.issue_challenge(&ca, &certificate).unwrap();
+        let request = device.renewal_request(&certificate, nonce);
+        renewals.renew(&mut ca, &verifier, request.clone()).unwrap();
+        assert!(renewals.renew(&mut ca, &verifier, request).is_err());
+    }
+
+    #[test]
+    fn test_renewal_rejects_unenrolled_attestation_key() {
+        let (mut ca, mut renewals, _, certificate) = setup();
+        let verifier = TpmManager::new(false);
+        let impostor = Device::new();
+
+        let nonce = renewals.issue_challenge(&ca, &certificate).unwrap();
+        let request = impostor.renewal_request(&certificate, nonce);
+        assert!(renewals.renew(&mut ca, &verifier, request).is_err());
+    }
+
+    #[test]
+    fn test_revoked_certificate_not_renewed() {
+        let (mut ca, mut renewals, device, certificate) = setup();
+        let nonce = renewals.issue_challenge(&ca, &certificate).unwrap();
+        ca.revoke_certificate(&certificate.serial).unwrap();
+
+        let request = device.renewal_request(&certificate, nonce);
+        assert!(renewals
+            .renew(&mut ca, &TpmManager::new(false), request)
+            .is_err());
+        assert!(renewals.issue_challenge(&ca, &certificate).is_err());
+    }
+
+    #[test]
+    fn test_enrolled_attestation_key_survives_restart() {
+        let (mut ca, _, device, certificate) = setup();
+        let dir = std::env::temp_dir().join(format!("aethercore-renewal-{}", std::process::id()));
+        let path = dir.join("renewal.db");
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let mut renewals = RenewalManager::open(&path, 30).unwrap();
+            renewals
+                .register_attestation_key("platform-1", device.ak.public_key.clone())
+                .unwrap();
+        }
+
+        let mut renewals = RenewalManager::open(&path, 30).unwrap();
+        assert_eq!(
+            renewals.attestation_key("platform-1"),
+            Some(device.ak.public_key.as_slice())
+        );
+        let nonce = renewals.issue_challenge(&ca, &certificate).unwrap();
+        let renewed = renewals.renew(
+            &mut ca,
+            &TpmManager::new(false),
+            device.renewal_request(&certificate, nonce),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(renewed.is_ok());
+    }
+}
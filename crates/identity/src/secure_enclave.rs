@@ -565,7 +565,7 @@ mod apple_backend {
         {
             preflight_ios_entitlements()
         }
-        
+
         #[cfg(target_os = "macos")]
         {
             preflight_macos_entitlements()
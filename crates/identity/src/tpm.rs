@@ -1,31 +1,57 @@
 //! Trusted Platform Module (TPM) integration.
 //! Enforces "Hardware-Rooted Truth" via ECDSA signature verification.
-
+//!
+//! # Backends
+//!
+//! - Hardware (`hardware-tpm`): /dev/tpm0, or the TCTI named in the
+//!   environment
+//! - Simulator (`tpm-simulator`): a software TPM 2.0 (swtpm or the Microsoft
+//!   reference simulator) reached over its socket interface. It runs the same
+//!   TSS code path as hardware, so quotes carry genuine TPMS_ATTEST data
+//! - Stub: in-memory keys signing software-built TPMS_ATTEST structures, for
+//!   unit tests
+//!
+//! # Quote Verification
+//!
+//! Verification does not depend on the backend or a TPM stack: the signed
+//! TPMS_ATTEST is decoded with [`crate::tpm_attest`] and checked against the
+//! quote's nonce and PCR values. The manager also remembers the last clock
+//! reading of each attestation key, so a replayed older quote is rejected.
+
+use crate::tpm_attest::{self, ClockInfo, TpmsAttest, TPM_ALG_SHA256, TPM_GENERATED_VALUE};
+use aethercore_core::sync;
 use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "hardware-tpm")]
-use sha2::{Digest as ShaDigestTrait, Sha256, Sha384, Sha512};
+use sha2::{Digest as ShaDigestTrait, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
 use tracing::{error, info, warn};
 
 #[cfg(feature = "hardware-tpm")]
 use tss_esapi::{
+    attributes::ObjectAttributesBuilder,
     constants::tss::TPM2_PERSISTENT_FIRST,
     handles::{ObjectHandle, PersistentTpmHandle, TpmHandle},
     interface_types::{
         algorithm::{EccSchemeAlgorithm, HashingAlgorithm},
+        dynamic_handles::Persistent,
         ecc::EccCurve,
         resource_handles::Hierarchy,
         session_handles::AuthSession,
-        structure_tags::AttestationType,
     },
     structures::{
-        Attest, AttestInfo, Data, EccScheme, MaxBuffer, ObjectAttributesBuilder,
-        PcrSelectionListBuilder, PcrSlot, Public, PublicBuilder, PublicEccParametersBuilder,
-        Signature as TpmSignature, SignatureScheme, SymmetricDefinitionObject,
+        Data, EccScheme, MaxBuffer, PcrSelectionListBuilder, PcrSlot, Public, PublicBuilder,
+        PublicEccParametersBuilder, Signature as TpmSignature, SignatureScheme,
+        SymmetricDefinitionObject,
     },
     traits::Marshall,
     Context, Error as TssError,
 };
+#[cfg(feature = "tpm-simulator")]
+use tss_esapi::{
+    constants::{StartupType, Tss2ResponseCodeKind},
+    tcti_ldr::{NetworkTPMConfig, TctiNameConf},
+};
 
 /// TPM configuration and state.
 #[derive(Debug)]
@@ -35,6 +61,124 @@ pub struct TpmManager {
     stub_keys: std::collections::HashMap<String, Vec<u8>>,
     #[cfg(feature = "hardware-tpm")]
     hardware_keys: std::collections::HashMap<String, u32>,
+    /// Software TPM used in place of the hardware device
+    #[cfg(feature = "tpm-simulator")]
+    simulator: Option<SimulatorConfig>,
+    /// Latest verified clock reading per attestation key (SEC1 public key)
+    quote_clocks: Mutex<HashMap<Vec<u8>, ClockInfo>>,
+}
+
+/// Structured TPM quote verification failure reasons.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuoteVerificationFailure {
+    /// Attestation key is not a SEC1 P-256 public key
+    InvalidAttestationKey,
+    /// Signature is malformed or does not cover `attestation_data`
+    SignatureInvalid,
+    /// `attestation_data` is not a quote TPMS_ATTEST
+    MalformedAttestation(String),
+    /// Magic is not TPM_GENERATED_VALUE
+    BadMagic(u32),
+    /// Qualifying data differs from the quote nonce
+    NonceMismatch,
+    /// Quoted PCR selection differs from the supplied PCR values
+    PcrSelectionMismatch,
+    /// Supplied PCR values do not hash to the quoted PCR digest
+    PcrDigestMismatch,
+    /// Quote is older than one already verified for the same key
+    ClockRegression {
+        previous: ClockInfo,
+        current: ClockInfo,
+    },
+}
+
+impl std::fmt::Display for QuoteVerificationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidAttestationKey => write!(f, "invalid attestation key"),
+            Self::SignatureInvalid => write!(f, "signature mismatch"),
+            Self::MalformedAttestation(reason) => write!(f, "malformed TPMS_ATTEST: {}", reason),
+            Self::BadMagic(magic) => write!(f, "bad magic 0x{:08x}", magic),
+            Self::NonceMismatch => write!(f, "nonce mismatch in attestation data"),
+            Self::PcrSelectionMismatch => write!(f, "PCR selection mismatch"),
+            Self::PcrDigestMismatch => write!(f, "PCR digest mismatch"),
+            Self::ClockRegression { previous, current } => write!(
+                f,
+                "clock regressed from {}/{}/{} to {}/{}/{}",
+                previous.reset_count,
+                previous.restart_count,
+                previous.clock,
+                current.reset_count,
+                current.restart_count,
+                current.clock
+            ),
+        }
+    }
+}
+
+/// Detailed TPM quote verification result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteVerification {
+    pub verified: bool,
+    /// Decoded attestation, once the signature over it has been checked
+    pub attestation: Option<TpmsAttest>,
+    pub failure: Option<QuoteVerificationFailure>,
+}
+
+impl QuoteVerification {
+    fn failed(attestation: Option<TpmsAttest>, failure: QuoteVerificationFailure) -> Self {
+        Self {
+            verified: false,
+            attestation,
+            failure: Some(failure),
+        }
+    }
+}
+
+/// Socket protocol spoken by a software TPM.
+#[cfg(feature = "tpm-simulator")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatorProtocol {
+    /// swtpm (`swtpm socket --tpm2`)
+    Swtpm,
+    /// Microsoft reference simulator (ms-tpm-20-ref)
+    Mssim,
+}
+
+/// Software TPM endpoint.
+///
+/// Both simulators serve TPM commands on `port` and platform control
+/// commands on `port + 1`.
+#[cfg(feature = "tpm-simulator")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatorConfig {
+    pub host: String,
+    pub port: u16,
+    pub protocol: SimulatorProtocol,
+}
+
+#[cfg(feature = "tpm-simulator")]
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 2321,
+            protocol: SimulatorProtocol::Swtpm,
+        }
+    }
+}
+
+#[cfg(feature = "tpm-simulator")]
+impl SimulatorConfig {
+    fn tcti(&self) -> crate::Result<TctiNameConf> {
+        let network: NetworkTPMConfig = format!("host={},port={}", self.host, self.port)
+            .parse()
+            .map_err(to_identity_error)?;
+        Ok(match self.protocol {
+            SimulatorProtocol::Swtpm => TctiNameConf::Swtpm(network),
+            SimulatorProtocol::Mssim => TctiNameConf::Mssim(network),
+        })
+    }
 }
 
 /// TPM Quote for platform attestation.
@@ -77,9 +221,45 @@ impl TpmManager {
             stub_keys: std::collections::HashMap::new(),
             #[cfg(feature = "hardware-tpm")]
             hardware_keys: std::collections::HashMap::new(),
+            #[cfg(feature = "tpm-simulator")]
+            simulator: None,
+            quote_clocks: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Use a software TPM instead of the hardware device.
+    ///
+    /// Sends TPM2_Startup(CLEAR) so a freshly launched simulator is usable;
+    /// a simulator that has already been started is accepted as is. The
+    /// stub fallback is disabled.
+    #[cfg(feature = "tpm-simulator")]
+    pub fn with_simulator(config: SimulatorConfig) -> crate::Result<Self> {
+        let mut context = Context::new(config.tcti()?).map_err(to_identity_error)?;
+        if let Err(e) = context.startup(StartupType::Clear) {
+            // TPM_RC_INITIALIZE: the simulator has already been started
+            let already_started = matches!(
+                e,
+                TssError::Tss2Error(rc) if rc.kind() == Some(Tss2ResponseCodeKind::Initialize)
+            );
+            if !already_started {
+                return Err(to_identity_error(e));
+            }
+        }
+        info!(
+            "STATUS: TpmManager :: Simulated Root of Trust :: {}:{}",
+            config.host, config.port
+        );
+
+        Ok(Self {
+            hardware_available: true,
+            allow_stub: false,
+            stub_keys: std::collections::HashMap::new(),
+            hardware_keys: std::collections::HashMap::new(),
+            simulator: Some(config),
+            quote_clocks: Mutex::new(HashMap::new()),
+        })
+    }
+
     #[cfg(feature = "hardware-tpm")]
     fn detect_hardware() -> bool {
         std::path::Path::new("/dev/tpm0").exists() || std::path::Path::new("/dev/tpmrm0").exists()
@@ -115,22 +295,42 @@ impl TpmManager {
     }
 
     pub fn verify_quote(&self, quote: &TpmQuote, ak: &AttestationKey) -> bool {
-        // Verification logic is identical for stub (if simulated correctly) and hardware
-        match self.perform_verification(quote, ak) {
-            Ok(valid) => {
-                if valid {
-                    info!("TrustGate :: Quote Verified :: {:x?}", quote.nonce);
-                    true
-                } else {
-                    error!("TrustGate :: Quote REJECTED :: Invalid Signature or Data");
-                    false
+        self.verify_quote_detailed(quote, ak).verified
+    }
+
+    /// Verify a quote and return the decoded attestation and failure reason.
+    ///
+    /// On success the quote's clock becomes the minimum for later quotes by
+    /// the same attestation key.
+    pub fn verify_quote_detailed(
+        &self,
+        quote: &TpmQuote,
+        ak: &AttestationKey,
+    ) -> QuoteVerification {
+        let mut verification = verify_quote_contents(quote, &ak.public_key);
+        if let (true, Some(attestation)) = (verification.verified, &verification.attestation) {
+            let current = attestation.clock_info;
+            let mut clocks = sync::lock(&self.quote_clocks);
+            let regression = match clocks.get(&ak.public_key) {
+                Some(previous) if !current.follows(previous) => Some(*previous),
+                _ => {
+                    clocks.insert(ak.public_key.clone(), current);
+                    None
                 }
+            };
+            drop(clocks);
+            if let Some(previous) = regression {
+                verification.verified = false;
+                verification.failure =
+                    Some(QuoteVerificationFailure::ClockRegression { previous, current });
             }
-            Err(e) => {
-                error!("TrustGate :: Verification ERROR :: {}", e);
-                false
-            }
         }
+
+        match &verification.failure {
+            None => info!("TrustGate :: Quote Verified :: {:x?}", quote.nonce),
+            Some(failure) => error!("TrustGate :: Quote REJECTED :: {}", failure),
+        }
+        verification
     }
 
     /// Sign data with the TPM-resident attestation key.
@@ -146,23 +346,17 @@ impl TpmManager {
         }
     }
 
-    /// Core Cryptographic Verification Logic
-    fn perform_verification(&self, quote: &TpmQuote, ak: &AttestationKey) -> crate::Result<bool> {
-        // 1. Reconstruct Public Key
-        let verifying_key = VerifyingKey::from_sec1_bytes(&ak.public_key)
-            .map_err(|_| crate::Error::Identity("Invalid Public Key Format".into()))?;
-
-        // 2. Parse Signature
-        let signature = Signature::from_der(&quote.signature)
-            .map_err(|_| crate::Error::Identity("Invalid DER Signature".into()))?;
-
-        // 3. Verify Signature against Raw Data
-        if let Err(_) = verifying_key.verify(&quote.attestation_data, &signature) {
-            error!("TrustGate :: FAIL :: Signature Mismatch");
-            return Ok(false);
+    /// Read the current SHA-256 bank values of the selected PCRs.
+    pub fn read_pcrs(&self, pcr_selection: &[u8]) -> crate::Result<Vec<PcrValue>> {
+        if self.hardware_available {
+            self.read_pcrs_hardware(pcr_selection)
+        } else if self.allow_stub {
+            Ok(stub_pcr_values(pcr_selection))
+        } else {
+            Err(crate::Error::Identity(
+                "Hardware TPM unavailable; stub fallback is disabled".to_string(),
+            ))
         }
-
-        self.verify_attestation_contents(quote)
     }
 
     // --- Hardware Placeholders (Requires tss-esapi setup in environment) ---
@@ -171,14 +365,14 @@ impl TpmManager {
         let persistent_handle_value = persistent_handle_for_key(&key_id);
         let persistent =
             PersistentTpmHandle::new(persistent_handle_value).map_err(to_identity_error)?;
-        let mut context = create_tpm_context()?;
+        let mut context = self.create_context()?;
 
         if let Ok(existing_handle) = context.tr_from_tpm_public(TpmHandle::Persistent(persistent)) {
             let _ = context.execute_with_session(Some(AuthSession::Password), |ctx| {
                 ctx.evict_control(
                     tss_esapi::interface_types::resource_handles::Provision::Owner,
                     existing_handle,
-                    persistent,
+                    Persistent::Persistent(persistent),
                 )
             });
         }
@@ -207,7 +401,7 @@ impl TpmManager {
                 ctx.evict_control(
                     tss_esapi::interface_types::resource_handles::Provision::Owner,
                     ObjectHandle::from(ak_handle),
-                    persistent,
+                    Persistent::Persistent(persistent),
                 )
             })
             .map_err(to_identity_error)?;
@@ -235,7 +429,7 @@ impl TpmManager {
             crate::Error::Identity("No hardware attestation keys available".into())
         })?;
         let persistent = PersistentTpmHandle::new(*key_handle_value).map_err(to_identity_error)?;
-        let mut context = create_tpm_context()?;
+        let mut context = self.create_context()?;
         let key_handle = context
             .tr_from_tpm_public(TpmHandle::Persistent(persistent))
             .map_err(to_identity_error)?;
@@ -284,7 +478,7 @@ impl TpmManager {
             .get(key_id)
             .ok_or_else(|| crate::Error::Identity("Unknown hardware attestation key".into()))?;
         let persistent = PersistentTpmHandle::new(*key_handle_value).map_err(to_identity_error)?;
-        let mut context = create_tpm_context()?;
+        let mut context = self.create_context()?;
         let key_handle = context
             .tr_from_tpm_public(TpmHandle::Persistent(persistent))
             .map_err(to_identity_error)?;
@@ -318,6 +512,26 @@ impl TpmManager {
         Err(crate::Error::Identity("Hardware TPM disabled".to_string()))
     }
 
+    #[cfg(feature = "hardware-tpm")]
+    fn read_pcrs_hardware(&self, pcr_selection: &[u8]) -> crate::Result<Vec<PcrValue>> {
+        let mut context = self.create_context()?;
+        read_pcr_values(&mut context, build_pcr_selection_list(pcr_selection)?)
+    }
+
+    #[cfg(not(feature = "hardware-tpm"))]
+    fn read_pcrs_hardware(&self, _pcr_selection: &[u8]) -> crate::Result<Vec<PcrValue>> {
+        Err(crate::Error::Identity("Hardware TPM disabled".to_string()))
+    }
+
+    #[cfg(feature = "hardware-tpm")]
+    fn create_context(&self) -> crate::Result<Context> {
+        #[cfg(feature = "tpm-simulator")]
+        if let Some(simulator) = &self.simulator {
+            return Context::new(simulator.tcti()?).map_err(to_identity_error);
+        }
+        create_tpm_context()
+    }
+
     // --- Stub Implementation ---
     fn generate_ak_stub(&mut self, key_id: String) -> crate::Result<AttestationKey> {
         let secret_key = p256::SecretKey::random(&mut rand::thread_rng());
@@ -332,9 +546,6 @@ impl TpmManager {
     }
 
     fn generate_quote_stub(&self, nonce: Vec<u8>, pcr_selection: &[u8]) -> crate::Result<TpmQuote> {
-        let mut attestation_data = b"TPM_DATA_STUB_".to_vec();
-        attestation_data.extend_from_slice(&nonce);
-
         let key_bytes = self
             .stub_keys
             .values()
@@ -342,23 +553,46 @@ impl TpmManager {
             .ok_or(crate::Error::Identity("No stub keys".into()))?;
         let secret_key = p256::SecretKey::from_slice(key_bytes)
             .map_err(|_| crate::Error::Identity("Invalid stub key".into()))?;
-        let signing_key = p256::ecdsa::SigningKey::from(secret_key);
-        let signature: Signature = signature::Signer::sign(&signing_key, &attestation_data);
 
-        let mut pcrs = Vec::new();
-        for &index in pcr_selection {
-            if index < 24 {
-                pcrs.push(PcrValue {
-                    index,
-                    value: vec![0xFF; 32],
-                });
-            }
-        }
+        let mut pcrs = stub_pcr_values(pcr_selection);
+        pcrs.sort_by_key(|pcr| pcr.index);
+        pcrs.dedup_by_key(|pcr| pcr.index);
+        let pcr_digest =
+            tpm_attest::pcr_digest(TPM_ALG_SHA256, pcrs.iter().map(|pcr| pcr.value.as_slice()))
+                .unwrap_or_default();
 
-        let timestamp = std::time::SystemTime::now()
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+            .unwrap_or_default();
+        let timestamp = now.as_secs();
+
+        // TPM2B_NAME of the key: nameAlg followed by the hash of its public key
+        let mut qualified_signer = TPM_ALG_SHA256.to_be_bytes().to_vec();
+        qualified_signer
+            .extend_from_slice(&Sha256::digest(secret_key.public_key().to_sec1_bytes()));
+        let attestation_data = TpmsAttest {
+            magic: TPM_GENERATED_VALUE,
+            qualified_signer,
+            extra_data: nonce.clone(),
+            clock_info: ClockInfo {
+                clock: now.as_millis() as u64,
+                reset_count: 0,
+                restart_count: 0,
+                safe: true,
+            },
+            firmware_version: 0,
+            quote: tpm_attest::QuoteInfo {
+                pcr_selections: vec![tpm_attest::PcrSelection {
+                    hash_algorithm: TPM_ALG_SHA256,
+                    indices: pcrs.iter().map(|pcr| pcr.index).collect(),
+                }],
+                pcr_digest,
+            },
+        }
+        .to_bytes();
+
+        let signing_key = p256::ecdsa::SigningKey::from(secret_key);
+        let signature: Signature = signature::Signer::sign(&signing_key, &attestation_data);
 
         Ok(TpmQuote {
             pcrs,
@@ -382,56 +616,100 @@ impl TpmManager {
     }
 }
 
-impl TpmManager {
-    fn verify_attestation_contents(&self, quote: &TpmQuote) -> crate::Result<bool> {
-        #[cfg(feature = "hardware-tpm")]
-        {
-            let attest = Attest::unmarshall(&quote.attestation_data).map_err(to_identity_error)?;
-            if attest.attestation_type() != AttestationType::Quote {
-                error!("TrustGate :: FAIL :: Attestation type is not quote");
-                return Ok(false);
-            }
-
-            if attest.extra_data().value() != quote.nonce.as_slice() {
-                error!("TrustGate :: FAIL :: Nonce mismatch in attestation data");
-                return Ok(false);
-            }
-
-            let quote_info = match attest.attested() {
-                AttestInfo::Quote { info } => info,
-                _ => {
-                    error!("TrustGate :: FAIL :: Attestation data missing quote info");
-                    return Ok(false);
-                }
-            };
-
-            if !verify_pcr_selection_matches(quote_info.pcr_selection(), &quote.pcrs) {
-                error!("TrustGate :: FAIL :: PCR selection mismatch");
-                return Ok(false);
-            }
-
-            let expected_digest = compute_pcr_digest(quote_info.pcr_selection(), &quote.pcrs)?;
-            if quote_info.pcr_digest().value() != expected_digest.as_slice() {
-                error!("TrustGate :: FAIL :: PCR digest mismatch");
-                return Ok(false);
-            }
+/// Check a quote's signature and TPMS_ATTEST against its nonce and PCRs.
+///
+/// Clock ordering is left to the caller.
+pub(crate) fn verify_quote_contents(quote: &TpmQuote, ak_public_key: &[u8]) -> QuoteVerification {
+    let verifying_key = match VerifyingKey::from_sec1_bytes(ak_public_key) {
+        Ok(key) => key,
+        Err(_) => {
+            return QuoteVerification::failed(None, QuoteVerificationFailure::InvalidAttestationKey)
+        }
+    };
+    let signature_valid = Signature::from_der(&quote.signature)
+        .map(|signature| {
+            verifying_key
+                .verify(&quote.attestation_data, &signature)
+                .is_ok()
+        })
+        .unwrap_or(false);
+    if !signature_valid {
+        return QuoteVerification::failed(None, QuoteVerificationFailure::SignatureInvalid);
+    }
 
-            return Ok(true);
+    let attestation = match TpmsAttest::parse(&quote.attestation_data) {
+        Ok(attestation) => attestation,
+        Err(e) => {
+            return QuoteVerification::failed(
+                None,
+                QuoteVerificationFailure::MalformedAttestation(e.to_string()),
+            )
         }
+    };
+    if attestation.magic != TPM_GENERATED_VALUE {
+        let magic = attestation.magic;
+        return QuoteVerification::failed(
+            Some(attestation),
+            QuoteVerificationFailure::BadMagic(magic),
+        );
+    }
+    if quote.nonce.is_empty() || attestation.extra_data != quote.nonce {
+        return QuoteVerification::failed(
+            Some(attestation),
+            QuoteVerificationFailure::NonceMismatch,
+        );
+    }
 
-        #[cfg(not(feature = "hardware-tpm"))]
-        {
-            if !quote
-                .attestation_data
-                .windows(quote.nonce.len())
-                .any(|w| w == quote.nonce)
-            {
-                error!("TrustGate :: FAIL :: Nonce not found in signed data");
-                return Ok(false);
-            }
-            Ok(true)
+    // Quotes carry values for a single bank
+    let selection = match attestation.quote.pcr_selections.as_slice() {
+        [selection] => selection,
+        _ => {
+            return QuoteVerification::failed(
+                Some(attestation),
+                QuoteVerificationFailure::PcrSelectionMismatch,
+            )
         }
+    };
+    let mut provided: Vec<u8> = quote.pcrs.iter().map(|pcr| pcr.index).collect();
+    provided.sort_unstable();
+    if provided != selection.indices {
+        return QuoteVerification::failed(
+            Some(attestation),
+            QuoteVerificationFailure::PcrSelectionMismatch,
+        );
+    }
+    let ordered_values = selection.indices.iter().filter_map(|index| {
+        quote
+            .pcrs
+            .iter()
+            .find(|pcr| pcr.index == *index)
+            .map(|pcr| pcr.value.as_slice())
+    });
+    let digest_matches = tpm_attest::pcr_digest(selection.hash_algorithm, ordered_values)
+        .is_some_and(|digest| digest == attestation.quote.pcr_digest);
+    if !digest_matches {
+        return QuoteVerification::failed(
+            Some(attestation),
+            QuoteVerificationFailure::PcrDigestMismatch,
+        );
     }
+
+    QuoteVerification {
+        verified: true,
+        attestation: Some(attestation),
+        failure: None,
+    }
+}
+
+fn stub_pcr_values(pcr_selection: &[u8]) -> Vec<PcrValue> {
+    pcr_selection
+        .iter()
+        .filter(|&&index| index < 24)
+        .map(|&index| PcrValue {
+            index,
+            value: vec![0xFF; 32],
+        })
+        .collect()
 }
 
 #[cfg(feature = "hardware-tpm")]
@@ -490,8 +768,12 @@ fn build_attestation_public() -> crate::Result<Public> {
             PublicEccParametersBuilder::new()
                 .with_symmetric(SymmetricDefinitionObject::Null)
                 .with_ecc_scheme(
-                    EccScheme::create(EccSchemeAlgorithm::EcDsa, Some(HashingAlgorithm::Sha256))
-                        .map_err(to_identity_error)?,
+                    EccScheme::create(
+                        EccSchemeAlgorithm::EcDsa,
+                        Some(HashingAlgorithm::Sha256),
+                        None,
+                    )
+                    .map_err(to_identity_error)?,
                 )
                 .with_curve(EccCurve::NistP256)
                 .with_key_derivation_function_scheme(
@@ -561,8 +843,11 @@ fn tpm_signature_to_der(signature: &TpmSignature) -> crate::Result<Vec<u8>> {
         TpmSignature::EcDsa(ecc_sig) => {
             let r = pad_left(ecc_sig.signature_r().value(), 32);
             let s = pad_left(ecc_sig.signature_s().value(), 32);
-            let sig = Signature::from_scalars(r, s)
-                .map_err(|_| crate::Error::Identity("Invalid TPM ECDSA signature".into()))?;
+            let sig = Signature::from_scalars(
+                *p256::FieldBytes::from_slice(&r),
+                *p256::FieldBytes::from_slice(&s),
+            )
+            .map_err(|_| crate::Error::Identity("Invalid TPM ECDSA signature".into()))?;
             Ok(sig.to_der().as_bytes().to_vec())
         }
         _ => Err(crate::Error::Identity(
@@ -577,7 +862,11 @@ fn ecc_public_to_sec1(public: &Public) -> crate::Result<Vec<u8>> {
         Public::Ecc { unique, .. } => {
             let x = pad_left(unique.x().value(), 32);
             let y = pad_left(unique.y().value(), 32);
-            let encoded = p256::EncodedPoint::from_affine_coordinates(&x.into(), &y.into(), false);
+            let encoded = p256::EncodedPoint::from_affine_coordinates(
+                p256::FieldBytes::from_slice(&x),
+                p256::FieldBytes::from_slice(&y),
+                false,
+            );
             Ok(encoded.as_bytes().to_vec())
         }
         _ => Err(crate::Error::Identity(
@@ -586,62 +875,6 @@ fn ecc_public_to_sec1(public: &Public) -> crate::Result<Vec<u8>> {
     }
 }
 
-#[cfg(feature = "hardware-tpm")]
-fn verify_pcr_selection_matches(
-    selection_list: &tss_esapi::structures::PcrSelectionList,
-    pcrs: &[PcrValue],
-) -> bool {
-    let selections = selection_list.get_selections();
-    if selections.len() != 1 {
-        return false;
-    }
-    let selected_slots = selections[0].selected();
-    let selected_indices: std::collections::HashSet<u8> = selected_slots
-        .iter()
-        .map(|slot| u32::from(*slot).trailing_zeros() as u8)
-        .collect();
-    let provided_indices: std::collections::HashSet<u8> =
-        pcrs.iter().map(|pcr| pcr.index).collect();
-    selected_indices == provided_indices
-}
-
-#[cfg(feature = "hardware-tpm")]
-fn compute_pcr_digest(
-    selection_list: &tss_esapi::structures::PcrSelectionList,
-    pcrs: &[PcrValue],
-) -> crate::Result<Vec<u8>> {
-    let selections = selection_list.get_selections();
-    if selections.len() != 1 {
-        return Err(crate::Error::Identity(
-            "Unsupported PCR selection banks".to_string(),
-        ));
-    }
-    let selection = selections[0];
-    let hashing_algorithm = selection.hashing_algorithm();
-    let pcr_map: std::collections::HashMap<u8, &PcrValue> =
-        pcrs.iter().map(|pcr| (pcr.index, pcr)).collect();
-    let mut ordered_values = Vec::new();
-    for slot in selection.selected() {
-        let index = u32::from(slot).trailing_zeros() as u8;
-        let pcr_value = pcr_map.get(&index).ok_or_else(|| {
-            crate::Error::Identity(format!("Missing PCR value for index {}", index))
-        })?;
-        ordered_values.extend_from_slice(&pcr_value.value);
-    }
-
-    let digest = match hashing_algorithm {
-        HashingAlgorithm::Sha256 => Sha256::digest(&ordered_values).to_vec(),
-        HashingAlgorithm::Sha384 => Sha384::digest(&ordered_values).to_vec(),
-        HashingAlgorithm::Sha512 => Sha512::digest(&ordered_values).to_vec(),
-        _ => {
-            return Err(crate::Error::Identity(
-                "Unsupported PCR hashing algorithm".to_string(),
-            ))
-        }
-    };
-    Ok(digest)
-}
-
 #[cfg(feature = "hardware-tpm")]
 fn persistent_handle_for_key(key_id: &str) -> u32 {
     let hash = blake3::hash(key_id.as_bytes());
@@ -664,3 +897,97 @@ fn pad_left(value: &[u8], size: usize) -> Vec<u8> {
     padded.extend_from_slice(value);
     padded
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stub_with_key() -> (TpmManager, AttestationKey) {
+        let mut tpm = TpmManager::new(false);
+        let ak = tpm.generate_attestation_key("test-ak".to_string()).unwrap();
+        (tpm, ak)
+    }
+
+    /// Re-sign modified attestation data with the stub attestation key.
+    fn resign(tpm: &TpmManager, quote: &mut TpmQuote, attestation: &TpmsAttest) {
+        quote.attestation_data = attestation.to_bytes();
+        quote.signature = tpm
+            .sign_with_attestation_key("test-ak", &quote.attestation_data)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_quote_reports_attestation() {
+        let (tpm, ak) = stub_with_key();
+        let quote = tpm.generate_quote(vec![0x42; 32], &[7, 0, 2]).unwrap();
+
+        let verification = tpm.verify_quote_detailed(&quote, &ak);
+        assert!(verification.verified, "{:?}", verification.failure);
+        let attestation = verification.attestation.unwrap();
+        assert_eq!(attestation.extra_data, vec![0x42; 32]);
+        assert_eq!(attestation.quote.pcr_selections[0].indices, vec![0, 2, 7]);
+    }
+
+    #[test]
+    fn test_verify_quote_failures() {
+        let (tpm, ak) = stub_with_key();
+        let quote = tpm.generate_quote(vec![0x42; 32], &[0, 2]).unwrap();
+        let verifier = TpmManager::new(false);
+        let failure = |quote: &TpmQuote| verifier.verify_quote_detailed(quote, &ak).failure;
+
+        let mut bad = quote.clone();
+        bad.attestation_data[10] ^= 0x01;
+        assert_eq!(
+            failure(&bad),
+            Some(QuoteVerificationFailure::SignatureInvalid)
+        );
+
+        let mut bad = quote.clone();
+        bad.nonce = vec![0x43; 32];
+        assert_eq!(failure(&bad), Some(QuoteVerificationFailure::NonceMismatch));
+
+        let mut bad = quote.clone();
+        bad.pcrs[1].value = vec![0u8; 32];
+        assert_eq!(
+            failure(&bad),
+            Some(QuoteVerificationFailure::PcrDigestMismatch)
+        );
+
+        let mut bad = quote.clone();
+        bad.pcrs.pop();
+        assert_eq!(
+            failure(&bad),
+            Some(QuoteVerificationFailure::PcrSelectionMismatch)
+        );
+
+        let mut attestation = TpmsAttest::parse(&quote.attestation_data).unwrap();
+        attestation.magic = 0;
+        let mut bad = quote.clone();
+        resign(&tpm, &mut bad, &attestation);
+        assert_eq!(failure(&bad), Some(QuoteVerificationFailure::BadMagic(0)));
+
+        assert!(verifier.verify_quote(&quote, &ak));
+    }
+
+    #[test]
+    fn test_verify_quote_rejects_clock_regression() {
+        let (tpm, ak) = stub_with_key();
+        let older = tpm.generate_quote(vec![0x01; 32], &[0]).unwrap();
+
+        let mut attestation = TpmsAttest::parse(&older.attestation_data).unwrap();
+        attestation.clock_info.clock += 1_000;
+        attestation.extra_data = vec![0x02; 32];
+        let mut newer = older.clone();
+        newer.nonce = vec![0x02; 32];
+        resign(&tpm, &mut newer, &attestation);
+
+        let verifier = TpmManager::new(false);
+        assert!(verifier.verify_quote(&newer, &ak));
+        assert!(matches!(
+            verifier.verify_quote_detailed(&older, &ak).failure,
+            Some(QuoteVerificationFailure::ClockRegression { .. })
+        ));
+        // Other verifiers have not seen the newer quote
+        assert!(TpmManager::new(false).verify_quote(&older, &ak));
+    }
+}
@@ -0,0 +1,316 @@
+//! TPMS_ATTEST encoding (TPM 2.0 Library, Part 2, 10.12.12).
+//!
+//! Decodes the attestation structure a TPM signs in response to TPM2_Quote,
+//! independently of the TSS, so quotes can be verified on nodes without a
+//! TPM stack. Only quote attestations (TPM_ST_ATTEST_QUOTE) are supported.
+//! All integers are big-endian.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// TPM_GENERATED_VALUE: marks structures produced by the TPM itself
+pub const TPM_GENERATED_VALUE: u32 = 0xFF54_4347;
+
+/// Structure tag of a quote attestation
+pub const TPM_ST_ATTEST_QUOTE: u16 = 0x8018;
+
+/// TPM_ALG_ID values of the supported PCR banks
+pub const TPM_ALG_SHA256: u16 = 0x000B;
+pub const TPM_ALG_SHA384: u16 = 0x000C;
+pub const TPM_ALG_SHA512: u16 = 0x000D;
+
+/// TPMS_CLOCK_INFO: the TPM's clock when the quote was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockInfo {
+    /// Milliseconds the TPM has been powered since it was manufactured
+    pub clock: u64,
+    /// TPM Reset count
+    pub reset_count: u32,
+    /// TPM Restart/Resume count since the last reset
+    pub restart_count: u32,
+    /// Whether the TPM guarantees this clock value was never reported
+    /// before (false after an orderly shutdown was missed)
+    pub safe: bool,
+}
+
+impl ClockInfo {
+    /// Whether this clock reading is no earlier than `previous` from the
+    /// same TPM.
+    ///
+    /// The reset count only grows, the restart count only grows between
+    /// resets, and the clock only moves forward.
+    pub fn follows(&self, previous: &ClockInfo) -> bool {
+        (self.reset_count, self.restart_count, self.clock)
+            >= (previous.reset_count, previous.restart_count, previous.clock)
+    }
+}
+
+/// TPMS_PCR_SELECTION: PCRs selected in one bank.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcrSelection {
+    /// Bank hash algorithm (TPM_ALG_ID)
+    pub hash_algorithm: u16,
+    /// Selected PCR indices, ascending
+    pub indices: Vec<u8>,
+}
+
+/// TPMS_QUOTE_INFO: what the quote attests to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteInfo {
+    /// Quoted PCRs
+    pub pcr_selections: Vec<PcrSelection>,
+    /// Digest of the selected PCR values, in selection order
+    pub pcr_digest: Vec<u8>,
+}
+
+/// Decoded TPMS_ATTEST of a quote.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TpmsAttest {
+    /// TPM_GENERATED_VALUE for genuine TPM output
+    pub magic: u32,
+    /// Name of the signing key
+    pub qualified_signer: Vec<u8>,
+    /// Caller-supplied qualifying data (the quote nonce)
+    pub extra_data: Vec<u8>,
+    pub clock_info: ClockInfo,
+    /// Vendor-specific firmware version
+    pub firmware_version: u64,
+    pub quote: QuoteInfo,
+}
+
+impl TpmsAttest {
+    /// Decode a marshalled TPMS_ATTEST.
+    pub fn parse(bytes: &[u8]) -> crate::Result<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let magic = reader.u32()?;
+        let attestation_type = reader.u16()?;
+        if attestation_type != TPM_ST_ATTEST_QUOTE {
+            return Err(crate::Error::Identity(format!(
+                "Unsupported attestation type 0x{:04x}",
+                attestation_type
+            )));
+        }
+        let qualified_signer = reader.sized()?.to_vec();
+        let extra_data = reader.sized()?.to_vec();
+        let clock_info = ClockInfo {
+            clock: reader.u64()?,
+            reset_count: reader.u32()?,
+            restart_count: reader.u32()?,
+            safe: reader.u8()? != 0,
+        };
+        let firmware_version = reader.u64()?;
+
+        let count = reader.u32()?;
+        let mut pcr_selections = Vec::new();
+        for _ in 0..count {
+            let hash_algorithm = reader.u16()?;
+            let size = reader.u8()? as usize;
+            // Indices are u8
+            if size > 32 {
+                return Err(crate::Error::Identity(format!(
+                    "PCR selection of {} bytes is too large",
+                    size
+                )));
+            }
+            let bitmap = reader.take(size)?;
+            let indices = (0..size * 8)
+                .filter(|bit| bitmap[bit / 8] & (1 << (bit % 8)) != 0)
+                .map(|bit| bit as u8)
+                .collect();
+            pcr_selections.push(PcrSelection {
+                hash_algorithm,
+                indices,
+            });
+        }
+        let pcr_digest = reader.sized()?.to_vec();
+
+        if reader.offset != bytes.len() {
+            return Err(crate::Error::Identity(
+                "Trailing bytes after TPMS_ATTEST".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            magic,
+            qualified_signer,
+            extra_data,
+            clock_info,
+            firmware_version,
+            quote: QuoteInfo {
+                pcr_selections,
+                pcr_digest,
+            },
+        })
+    }
+
+    /// Marshal as a TPMS_ATTEST of type TPM_ST_ATTEST_QUOTE.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.magic.to_be_bytes());
+        out.extend_from_slice(&TPM_ST_ATTEST_QUOTE.to_be_bytes());
+        put_sized(&mut out, &self.qualified_signer);
+        put_sized(&mut out, &self.extra_data);
+        out.extend_from_slice(&self.clock_info.clock.to_be_bytes());
+        out.extend_from_slice(&self.clock_info.reset_count.to_be_bytes());
+        out.extend_from_slice(&self.clock_info.restart_count.to_be_bytes());
+        out.push(self.clock_info.safe as u8);
+        out.extend_from_slice(&self.firmware_version.to_be_bytes());
+
+        out.extend_from_slice(&(self.quote.pcr_selections.len() as u32).to_be_bytes());
+        for selection in &self.quote.pcr_selections {
+            // At least 3 bytes, the minimum for the 24 PCRs of a PC client TPM
+            let size = selection
+                .indices
+                .iter()
+                .map(|&index| index as usize / 8 + 1)
+                .max()
+                .unwrap_or(0)
+                .max(3);
+            let mut bitmap = vec![0u8; size];
+            for &index in &selection.indices {
+                bitmap[index as usize / 8] |= 1 << (index % 8);
+            }
+            out.extend_from_slice(&selection.hash_algorithm.to_be_bytes());
+            out.push(size as u8);
+            out.extend_from_slice(&bitmap);
+        }
+        put_sized(&mut out, &self.quote.pcr_digest);
+        out
+    }
+}
+
+/// Digest of concatenated PCR values as computed by TPM2_Quote.
+///
+/// Returns `None` for unsupported bank algorithms.
+pub fn pcr_digest<'a>(
+    hash_algorithm: u16,
+    values: impl IntoIterator<Item = &'a [u8]>,
+) -> Option<Vec<u8>> {
+    let concatenated: Vec<u8> = values.into_iter().flatten().copied().collect();
+    match hash_algorithm {
+        TPM_ALG_SHA256 => Some(Sha256::digest(&concatenated).to_vec()),
+        TPM_ALG_SHA384 => Some(Sha384::digest(&concatenated).to_vec()),
+        TPM_ALG_SHA512 => Some(Sha512::digest(&concatenated).to_vec()),
+        _ => None,
+    }
+}
+
+fn put_sized(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| crate::Error::Identity("Truncated TPMS_ATTEST".to_string()))?;
+        let value = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(value)
+    }
+
+    fn u8(&mut self) -> crate::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> crate::Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> crate::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> crate::Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// A TPM2B: u16 size followed by that many bytes
+    fn sized(&mut self) -> crate::Result<&'a [u8]> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TpmsAttest {
+        TpmsAttest {
+            magic: TPM_GENERATED_VALUE,
+            qualified_signer: vec![0x00, 0x0B, 1, 2, 3],
+            extra_data: vec![0x42; 32],
+            clock_info: ClockInfo {
+                clock: 123_456,
+                reset_count: 2,
+                restart_count: 1,
+                safe: true,
+            },
+            firmware_version: 0x0001_0002_0003_0004,
+            quote: QuoteInfo {
+                pcr_selections: vec![PcrSelection {
+                    hash_algorithm: TPM_ALG_SHA256,
+                    indices: vec![0, 2, 4, 7],
+                }],
+                pcr_digest: vec![0xAB; 32],
+            },
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let attest = sample();
+        let bytes = attest.to_bytes();
+        assert_eq!(&bytes[..4], &TPM_GENERATED_VALUE.to_be_bytes());
+        assert_eq!(TpmsAttest::parse(&bytes).unwrap(), attest);
+    }
+
+    #[test]
+    fn test_rejects_truncated_and_trailing_bytes() {
+        let bytes = sample().to_bytes();
+        assert!(TpmsAttest::parse(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(TpmsAttest::parse(&extended).is_err());
+    }
+
+    #[test]
+    fn test_rejects_other_attestation_types() {
+        let mut bytes = sample().to_bytes();
+        // TPM_ST_ATTEST_CERTIFY
+        bytes[4..6].copy_from_slice(&0x8017u16.to_be_bytes());
+        assert!(TpmsAttest::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_clock_ordering() {
+        let base = sample().clock_info;
+        let later = ClockInfo {
+            clock: base.clock + 1,
+            ..base
+        };
+        let after_reset = ClockInfo {
+            clock: 10,
+            reset_count: base.reset_count + 1,
+            restart_count: 0,
+            safe: true,
+        };
+
+        assert!(base.follows(&base));
+        assert!(later.follows(&base));
+        assert!(!base.follows(&later));
+        assert!(after_reset.follows(&later));
+        assert!(!later.follows(&after_reset));
+    }
+}
@@ -0,0 +1,723 @@
+//! X.509 v3 and PKCS#10 interoperability for the native PKI types.
+//!
+//! AetherCore certificates are normally signed over a compact native encoding.
+//! This module maps them to and from DER/PEM X.509 so device certificates can
+//! be handed to TLS stacks, ATAK, or an external PKI, and so externally issued
+//! Ed25519 certificates and CSRs can enter the native flow.
+//!
+//! # Mapping
+//!
+//! - `serial`: decimal (or `0x`-prefixed hex) string <-> INTEGER serial number
+//! - `subject` / `issuer`: string <-> Name with a single `CN`; imported names
+//!   with other attributes use their RFC 4514 string form
+//! - `public_key`: raw Ed25519 key <-> SubjectPublicKeyInfo (`id-Ed25519`)
+//! - `not_before` / `not_after`: Unix milliseconds <-> Time (second precision);
+//!   `u64::MAX` maps to the RFC 5280 "no expiration" time `99991231235959Z`
+//! - `extensions`: keys are dotted OIDs, values are the DER `extnValue` contents
+//!
+//! A certificate imported from X.509 keeps its DER encoding in `x509_der`, and
+//! its signature is verified over the X.509 `tbsCertificate`. Native and X.509
+//! certificates can therefore be mixed in one chain. Only Ed25519 is supported.
+
+use crate::pki::{CaConstraints, Certificate, CertificateAuthority, CertificateRequest, KeyUsage};
+use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey};
+use std::collections::HashMap;
+use std::time::Duration;
+use x509_cert::der::asn1::{
+    BitString, GeneralizedTime, Ia5String, OctetString, SetOfVec, UtcTime, Utf8StringRef,
+};
+use x509_cert::der::flagset::FlagSet;
+use x509_cert::der::oid::db::{rfc4519, rfc5280, rfc8410};
+use x509_cert::der::oid::ObjectIdentifier;
+use x509_cert::der::pem::LineEnding;
+use x509_cert::der::{Any, Decode, DecodePem, Encode, EncodePem};
+use x509_cert::ext::pkix::name::DirectoryString;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::{BasicConstraints, KeyUsages, SubjectAltName};
+use x509_cert::ext::Extension;
+use x509_cert::name::{Name, RdnSequence, RelativeDistinguishedName};
+use x509_cert::request::CertReq;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+use x509_cert::time::{Time, Validity};
+use x509_cert::{attr::AttributeTypeAndValue, certificate::Version, TbsCertificate};
+
+/// Unix seconds from which X.509 requires GeneralizedTime (2050-01-01T00:00:00Z).
+const GENERALIZED_TIME_FROM_SECS: u64 = 2_524_608_000;
+
+impl Certificate {
+    /// Whether this certificate is backed by an X.509 encoding.
+    pub fn is_x509(&self) -> bool {
+        self.x509_der.is_some()
+    }
+
+    /// Import a DER-encoded X.509 v3 certificate with an Ed25519 key.
+    pub fn from_x509_der(der: &[u8]) -> crate::Result<Self> {
+        let x509 = x509_cert::Certificate::from_der(der)
+            .map_err(|e| x509_error("Invalid X.509 certificate", e))?;
+        let tbs = &x509.tbs_certificate;
+
+        require_ed25519(&x509.signature_algorithm, "signature")?;
+        require_ed25519(&tbs.subject_public_key_info.algorithm, "public key")?;
+
+        let mut extensions = HashMap::new();
+        for extension in tbs.extensions.iter().flatten() {
+            extensions.insert(
+                extension.extn_id.to_string(),
+                extension.extn_value.as_bytes().to_vec(),
+            );
+        }
+
+        Ok(Self {
+            serial: serial_to_string(&tbs.serial_number),
+            subject: name_to_string(&tbs.subject),
+            issuer: name_to_string(&tbs.issuer),
+            public_key: tbs
+                .subject_public_key_info
+                .subject_public_key
+                .raw_bytes()
+                .to_vec(),
+            not_before: time_to_millis(tbs.validity.not_before),
+            not_after: time_to_millis(tbs.validity.not_after),
+            signature: x509.signature.raw_bytes().to_vec(),
+            extensions,
+            x509_der: Some(der.to_vec()),
+        })
+    }
+
+    /// Import a PEM-encoded X.509 certificate.
+    pub fn from_x509_pem(pem: &str) -> crate::Result<Self> {
+        let x509 = x509_cert::Certificate::from_pem(pem)
+            .map_err(|e| x509_error("Invalid X.509 PEM", e))?;
+        let der = x509
+            .to_der()
+            .map_err(|e| x509_error("Failed to encode X.509 certificate", e))?;
+        Self::from_x509_der(&der)
+    }
+
+    /// DER encoding of an X.509-backed certificate.
+    ///
+    /// Native certificates must first be re-issued with
+    /// `CertificateAuthority::export_x509`.
+    pub fn to_x509_der(&self) -> crate::Result<Vec<u8>> {
+        self.x509_der.clone().ok_or_else(|| {
+            crate::Error::Identity(format!("Certificate {} has no X.509 encoding", self.serial))
+        })
+    }
+
+    /// PEM encoding of an X.509-backed certificate.
+    pub fn to_x509_pem(&self) -> crate::Result<String> {
+        let x509 = x509_cert::Certificate::from_der(&self.to_x509_der()?)
+            .map_err(|e| x509_error("Invalid X.509 certificate", e))?;
+        x509.to_pem(LineEnding::LF)
+            .map_err(|e| x509_error("Failed to encode X.509 PEM", e))
+    }
+
+    /// Basic constraints, if the certificate carries them.
+    pub fn ca_constraints(&self) -> crate::Result<Option<CaConstraints>> {
+        let value = match self
+            .extensions
+            .get(&rfc5280::ID_CE_BASIC_CONSTRAINTS.to_string())
+        {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let decoded = BasicConstraints::from_der(value)
+            .map_err(|e| x509_error("Invalid basic constraints", e))?;
+        Ok(Some(CaConstraints {
+            is_ca: decoded.ca,
+            path_len: decoded.path_len_constraint,
+        }))
+    }
+
+    /// Whether the key may be used for `usage`; true if no key usage
+    /// extension is present.
+    pub fn permits_key_usage(&self, usage: KeyUsage) -> crate::Result<bool> {
+        let value = match self.extensions.get(&rfc5280::ID_CE_KEY_USAGE.to_string()) {
+            Some(value) => value,
+            None => return Ok(true),
+        };
+        let decoded = x509_cert::ext::pkix::KeyUsage::from_der(value)
+            .map_err(|e| x509_error("Invalid key usage", e))?;
+        Ok(decoded.0.contains(key_usage_flag(usage)))
+    }
+}
+
+impl CertificateRequest {
+    /// Import a DER-encoded PKCS#10 certification request with an Ed25519 key.
+    ///
+    /// PKCS#10 carries no platform attestation, so it is supplied separately.
+    /// The CA checks the PKCS#10 self-signature at issuance; unlike a native
+    /// CSR, that signature does not cover the attestation.
+    pub fn from_pkcs10_der(
+        der: &[u8],
+        attestation: crate::device::Attestation,
+    ) -> crate::Result<Self> {
+        let request =
+            CertReq::from_der(der).map_err(|e| x509_error("Invalid PKCS#10 request", e))?;
+
+        require_ed25519(&request.algorithm, "signature")?;
+        require_ed25519(&request.info.public_key.algorithm, "public key")?;
+
+        Ok(Self {
+            subject: name_to_string(&request.info.subject),
+            public_key: request
+                .info
+                .public_key
+                .subject_public_key
+                .raw_bytes()
+                .to_vec(),
+            signature: request.signature.raw_bytes().to_vec(),
+            attestation,
+            pkcs10_der: Some(der.to_vec()),
+        })
+    }
+
+    /// Import a PEM-encoded PKCS#10 certification request.
+    pub fn from_pkcs10_pem(
+        pem: &str,
+        attestation: crate::device::Attestation,
+    ) -> crate::Result<Self> {
+        let request = CertReq::from_pem(pem).map_err(|e| x509_error("Invalid PKCS#10 PEM", e))?;
+        let der = request
+            .to_der()
+            .map_err(|e| x509_error("Failed to encode PKCS#10 request", e))?;
+        Self::from_pkcs10_der(&der, attestation)
+    }
+}
+
+impl CertificateAuthority {
+    /// Re-issue a certificate from this CA as an X.509 v3 certificate.
+    ///
+    /// Serial, subject, validity and extensions are carried over; the result
+    /// is signed by the CA key over the X.509 `tbsCertificate`. Only certificates
+    /// recorded as issued by this CA are exported.
+    pub fn export_x509(&self, cert: &Certificate) -> crate::Result<Certificate> {
+        if self.get_certificate(&cert.serial) != Some(cert) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} was not issued by {}",
+                cert.serial,
+                self.ca_id()
+            )));
+        }
+        if self.is_revoked(&cert.serial) {
+            return Err(crate::Error::Identity(format!(
+                "Certificate {} is revoked",
+                cert.serial
+            )));
+        }
+
+        self.sign_x509(cert)
+    }
+
+    /// Self-signed X.509 certificate for this CA, for use as a trust anchor.
+    pub fn self_signed_x509(&self, validity_days: u64) -> crate::Result<Certificate> {
+        let constraints = CaConstraints {
+            is_ca: true,
+            path_len: None,
+        };
+        let extensions = HashMap::from([
+            constraints.to_extension()?,
+            key_usage_extension(&[KeyUsage::KeyCertSign, KeyUsage::CrlSign])?,
+        ]);
+
+        let now = crate::pki::current_timestamp();
+        let template = Certificate {
+            serial: "0".to_string(),
+            subject: self.ca_id().to_string(),
+            issuer: self.ca_id().to_string(),
+            public_key: self.public_key().to_vec(),
+            not_before: now,
+            not_after: now.saturating_add(validity_days * 24 * 60 * 60 * 1000),
+            signature: Vec::new(),
+            extensions,
+            x509_der: None,
+        };
+        self.sign_x509(&template)
+    }
+
+    fn sign_x509(&self, cert: &Certificate) -> crate::Result<Certificate> {
+        let algorithm = ed25519_algorithm();
+        let tbs = TbsCertificate {
+            version: Version::V3,
+            serial_number: string_to_serial(&cert.serial)?,
+            signature: algorithm.clone(),
+            issuer: string_to_name(&cert.issuer)?,
+            validity: Validity {
+                not_before: millis_to_time(cert.not_before)?,
+                not_after: millis_to_time(cert.not_after)?,
+            },
+            subject: string_to_name(&cert.subject)?,
+            subject_public_key_info: ed25519_spki(&cert.public_key)?,
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: map_to_extensions(&cert.extensions)?,
+        };
+
+        let tbs_der = tbs
+            .to_der()
+            .map_err(|e| x509_error("Failed to encode tbsCertificate", e))?;
+        let signature = self.sign_tbs(&tbs_der);
+        if signature.is_empty() {
+            return Err(crate::Error::Identity(
+                "CA signing key unavailable for X.509 export".to_string(),
+            ));
+        }
+
+        let x509 = x509_cert::Certificate {
+            tbs_certificate: tbs,
+            signature_algorithm: algorithm,
+            signature: BitString::from_bytes(&signature)
+                .map_err(|e| x509_error("Failed to encode signature", e))?,
+        };
+        let der = x509
+            .to_der()
+            .map_err(|e| x509_error("Failed to encode X.509 certificate", e))?;
+        Certificate::from_x509_der(&der)
+    }
+}
+
+impl CaConstraints {
+    /// Basic constraints extension as an `(OID, value)` pair for
+    /// `Certificate::extensions`.
+    pub fn to_extension(&self) -> crate::Result<(String, Vec<u8>)> {
+        let value = BasicConstraints {
+            ca: self.is_ca,
+            path_len_constraint: self.path_len,
+        }
+        .to_der()
+        .map_err(|e| x509_error("Failed to encode basic constraints", e))?;
+        Ok((rfc5280::ID_CE_BASIC_CONSTRAINTS.to_string(), value))
+    }
+}
+
+/// Key usage extension permitting `usages`, as an `(OID, value)` pair.
+pub fn key_usage_extension(usages: &[KeyUsage]) -> crate::Result<(String, Vec<u8>)> {
+    let flags = usages.iter().fold(FlagSet::default(), |flags, usage| {
+        flags | key_usage_flag(*usage)
+    });
+    let value = x509_cert::ext::pkix::KeyUsage(flags)
+        .to_der()
+        .map_err(|e| x509_error("Failed to encode key usage", e))?;
+    Ok((rfc5280::ID_CE_KEY_USAGE.to_string(), value))
+}
+
+fn key_usage_flag(usage: KeyUsage) -> KeyUsages {
+    match usage {
+        KeyUsage::DigitalSignature => KeyUsages::DigitalSignature,
+        KeyUsage::KeyCertSign => KeyUsages::KeyCertSign,
+        KeyUsage::CrlSign => KeyUsages::CRLSign,
+    }
+}
+
+/// Subject alternative name extension listing `dns_names`.
+///
+/// Returns the `(OID, value)` pair for `Certificate::extensions`. TLS clients
+/// match the server name against these entries, not the subject CN.
+pub fn subject_alt_name_dns(dns_names: &[&str]) -> crate::Result<(String, Vec<u8>)> {
+    let names = dns_names
+        .iter()
+        .map(|name| {
+            Ia5String::new(name)
+                .map(GeneralName::DnsName)
+                .map_err(|e| x509_error("Invalid DNS name", e))
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+    let value = SubjectAltName(names)
+        .to_der()
+        .map_err(|e| x509_error("Failed to encode subject alternative name", e))?;
+    Ok((rfc5280::ID_CE_SUBJECT_ALT_NAME.to_string(), value))
+}
+
+/// Verify an X.509-backed certificate against its issuer's Ed25519 key.
+///
+/// The native fields must match the embedded DER, so they cannot be altered
+/// independently of the signed encoding.
+pub(crate) fn verify_x509_signature(cert: &Certificate, issuer_public_key: &[u8]) -> bool {
+    let der = match &cert.x509_der {
+        Some(der) => der,
+        None => return false,
+    };
+    match Certificate::from_x509_der(der) {
+        Ok(decoded) if decoded == *cert => {}
+        _ => return false,
+    }
+    let tbs_der = match x509_cert::Certificate::from_der(der)
+        .and_then(|x509| x509.tbs_certificate.to_der())
+    {
+        Ok(tbs_der) => tbs_der,
+        Err(_) => return false,
+    };
+    verify_ed25519(issuer_public_key, &cert.signature, &tbs_der)
+}
+
+/// Verify the self-signature of a PKCS#10-backed request.
+pub(crate) fn verify_pkcs10_signature(request: &CertificateRequest) -> bool {
+    let der = match &request.pkcs10_der {
+        Some(der) => der,
+        None => return false,
+    };
+    let parsed = match CertReq::from_der(der) {
+        Ok(parsed) => parsed,
+        Err(_) => return false,
+    };
+    if name_to_string(&parsed.info.subject) != request.subject
+        || parsed.info.public_key.subject_public_key.raw_bytes() != request.public_key.as_slice()
+        || parsed.signature.raw_bytes() != request.signature.as_slice()
+    {
+        return false;
+    }
+    let info_der = match parsed.info.to_der() {
+        Ok(info_der) => info_der,
+        Err(_) => return false,
+    };
+    verify_ed25519(&request.public_key, &request.signature, &info_der)
+}
+
+fn verify_ed25519(public_key: &[u8], signature: &[u8], message: &[u8]) -> bool {
+    let key_bytes: [u8; 32] = match public_key.try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+    let signature_bytes: [u8; 64] = match signature.try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+    match VerifyingKey::from_bytes(&key_bytes) {
+        Ok(key) => key
+            .verify(message, &Ed25519Signature::from_bytes(&signature_bytes))
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn ed25519_algorithm() -> AlgorithmIdentifierOwned {
+    AlgorithmIdentifierOwned {
+        oid: rfc8410::ID_ED_25519,
+        parameters: None,
+    }
+}
+
+fn ed25519_spki(public_key: &[u8]) -> crate::Result<SubjectPublicKeyInfoOwned> {
+    if public_key.len() != 32 {
+        return Err(crate::Error::Identity(
+            "X.509 export requires a 32-byte Ed25519 public key".to_string(),
+        ));
+    }
+    Ok(SubjectPublicKeyInfoOwned {
+        algorithm: ed25519_algorithm(),
+        subject_public_key: BitString::from_bytes(public_key)
+            .map_err(|e| x509_error("Failed to encode public key", e))?,
+    })
+}
+
+fn require_ed25519(algorithm: &AlgorithmIdentifierOwned, what: &str) -> crate::Result<()> {
+    if algorithm.oid != rfc8410::ID_ED_25519 || algorithm.parameters.is_some() {
+        return Err(crate::Error::Identity(format!(
+            "Unsupported {} algorithm {}; only Ed25519 is supported",
+            what, algorithm.oid
+        )));
+    }
+    Ok(())
+}
+
+fn string_to_serial(serial: &str) -> crate::Result<SerialNumber> {
+    let invalid = || {
+        crate::Error::Identity(format!(
+            "Serial {:?} is not a decimal or 0x-prefixed hex integer",
+            serial
+        ))
+    };
+
+    if let Some(hex_digits) = serial.strip_prefix("0x") {
+        let bytes = hex::decode(hex_digits).map_err(|_| invalid())?;
+        return SerialNumber::new(&bytes).map_err(|e| x509_error("Invalid serial number", e));
+    }
+    serial
+        .parse::<u64>()
+        .map(SerialNumber::from)
+        .map_err(|_| invalid())
+}
+
+fn serial_to_string(serial: &SerialNumber) -> String {
+    let bytes = serial.as_bytes();
+    if bytes.len() <= 8 {
+        let mut buffer = [0u8; 8];
+        buffer[8 - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(buffer).to_string()
+    } else {
+        format!("0x{}", hex::encode(bytes))
+    }
+}
+
+fn string_to_name(value: &str) -> crate::Result<Name> {
+    let utf8 = Utf8StringRef::new(value).map_err(|e| x509_error("Invalid name", e))?;
+    let attribute = AttributeTypeAndValue {
+        oid: rfc4519::CN,
+        value: Any::from(utf8),
+    };
+    let rdn = SetOfVec::try_from(vec![attribute]).map_err(|e| x509_error("Invalid name", e))?;
+    Ok(RdnSequence(vec![RelativeDistinguishedName(rdn)]))
+}
+
+fn name_to_string(name: &Name) -> String {
+    if let [rdn] = name.0.as_slice() {
+        if let [attribute] = rdn.0.as_slice() {
+            if attribute.oid == rfc4519::CN {
+                let decoded = attribute
+                    .value
+                    .to_der()
+                    .and_then(|der| DirectoryString::from_der(&der));
+                match decoded {
+                    Ok(DirectoryString::Utf8String(value)) => return value,
+                    Ok(DirectoryString::PrintableString(value)) => {
+                        return value.as_str().to_string()
+                    }
+                    Ok(DirectoryString::TeletexString(value)) => return value.as_str().to_string(),
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+    name.to_string()
+}
+
+fn millis_to_time(millis: u64) -> crate::Result<Time> {
+    if millis == u64::MAX {
+        return Ok(Time::INFINITY);
+    }
+    let duration = Duration::from_secs(millis / 1000);
+    let time = if duration.as_secs() < GENERALIZED_TIME_FROM_SECS {
+        UtcTime::from_unix_duration(duration).map(Time::UtcTime)
+    } else {
+        GeneralizedTime::from_unix_duration(duration).map(Time::GeneralTime)
+    };
+    time.map_err(|e| x509_error("Certificate time out of range", e))
+}
+
+pub(crate) fn time_to_millis(time: Time) -> u64 {
+    if time.to_date_time() == Time::INFINITY.to_date_time() {
+        return u64::MAX;
+    }
+    time.to_unix_duration().as_millis() as u64
+}
+
+fn map_to_extensions(
+    extensions: &HashMap<String, Vec<u8>>,
+) -> crate::Result<Option<Vec<Extension>>> {
+    if extensions.is_empty() {
+        return Ok(None);
+    }
+
+    let mut mapped = Vec::with_capacity(extensions.len());
+    for (key, value) in extensions {
+        let extn_id = ObjectIdentifier::new(key).map_err(|_| {
+            crate::Error::Identity(format!(
+                "Extension key {:?} is not an OID and cannot be exported to X.509",
+                key
+            ))
+        })?;
+        mapped.push(Extension {
+            extn_id,
+            critical: extn_id == rfc5280::ID_CE_BASIC_CONSTRAINTS
+                || extn_id == rfc5280::ID_CE_KEY_USAGE,
+            extn_value: OctetString::new(value.clone())
+                .map_err(|e| x509_error("Invalid extension value", e))?,
+        });
+    }
+    mapped.sort_by(|a, b| a.extn_id.as_bytes().cmp(b.extn_id.as_bytes()));
+    Ok(Some(mapped))
+}
+
+pub(crate) fn x509_error(context: &str, err: impl std::fmt::Display) -> crate::Error {
+    crate::Error::Identity(format!("{}: {}", context, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::Attestation;
+    use crate::pki::TrustChainValidator;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn build_ca(seed: u8, ca_id: &str) -> CertificateAuthority {
+        let key = signing_key(seed);
+        CertificateAuthority::new(
+            ca_id,
+            key.verifying_key().to_bytes().to_vec(),
+            key.to_bytes().to_vec(),
+        )
+    }
+
+    fn attestation() -> Attestation {
+        Attestation::Software {
+            certificate: vec![1, 2, 3],
+        }
+    }
+
+    /// Build a DER PKCS#10 request signed by `key`.
+    fn pkcs10_der(subject: &str, key: &SigningKey) -> Vec<u8> {
+        let info = x509_cert::request::CertReqInfo {
+            version: x509_cert::request::Version::V1,
+            subject: string_to_name(subject).unwrap(),
+            public_key: ed25519_spki(&key.verifying_key().to_bytes()).unwrap(),
+            attributes: Default::default(),
+        };
+        let signature = key.sign(&info.to_der().unwrap()).to_bytes();
+        CertReq {
+            info,
+            algorithm: ed25519_algorithm(),
+            signature: BitString::from_bytes(&signature).unwrap(),
+        }
+        .to_der()
+        .unwrap()
+    }
+
+    fn issue_x509_leaf(ca: &mut CertificateAuthority) -> Certificate {
+        let request = CertificateRequest::from_pkcs10_der(
+            &pkcs10_der("platform-1", &signing_key(10)),
+            attestation(),
+        )
+        .unwrap();
+        let native = ca.issue_certificate(request, 365).unwrap();
+        ca.export_x509(&native).unwrap()
+    }
+
+    #[test]
+    fn test_pkcs10_request_issues_certificate() {
+        let mut ca = build_ca(42, "test-ca");
+        let pem = CertReq::from_der(&pkcs10_der("platform-1", &signing_key(10)))
+            .unwrap()
+            .to_pem(LineEnding::LF)
+            .unwrap();
+
+        let request = CertificateRequest::from_pkcs10_pem(&pem, attestation()).unwrap();
+        assert_eq!(request.subject, "platform-1");
+        assert_eq!(
+            request.public_key,
+            signing_key(10).verifying_key().to_bytes().to_vec()
+        );
+
+        let cert = ca.issue_certificate(request, 365).unwrap();
+        assert!(ca.verify_certificate(&cert));
+    }
+
+    #[test]
+    fn test_tampered_pkcs10_rejected() {
+        let mut ca = build_ca(42, "test-ca");
+        let mut request = CertificateRequest::from_pkcs10_der(
+            &pkcs10_der("platform-1", &signing_key(10)),
+            attestation(),
+        )
+        .unwrap();
+        request.subject = "platform-2".to_string();
+
+        assert!(ca.issue_certificate(request, 365).is_err());
+    }
+
+    #[test]
+    fn test_x509_export_roundtrip() {
+        let mut ca = build_ca(42, "test-ca");
+        let request = CertificateRequest::from_pkcs10_der(
+            &pkcs10_der("platform-1", &signing_key(10)),
+            attestation(),
+        )
+        .unwrap();
+        let native = ca.issue_certificate(request, 365).unwrap();
+        let x509 = ca.export_x509(&native).unwrap();
+
+        assert!(x509.is_x509());
+        assert_eq!(x509.serial, native.serial);
+        assert_eq!(x509.subject, native.subject);
+        assert_eq!(x509.issuer, "test-ca");
+        assert_eq!(x509.public_key, native.public_key);
+        assert_eq!(x509.not_before, native.not_before / 1000 * 1000);
+
+        let pem = x509.to_x509_pem().unwrap();
+        assert!(pem.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert_eq!(Certificate::from_x509_pem(&pem).unwrap(), x509);
+        assert!(ca.verify_certificate(&x509));
+        assert!(native.to_x509_der().is_err());
+
+        let mut forged = native.clone();
+        forged.subject = "platform-9".to_string();
+        assert!(ca.export_x509(&forged).is_err());
+    }
+
+    #[test]
+    fn test_extensions_mapped_by_oid() {
+        let ca = build_ca(42, "test-ca");
+        let root = ca.self_signed_x509(365).unwrap();
+
+        let (critical, constraints) =
+            x509_cert::Certificate::from_der(&root.x509_der.clone().unwrap())
+                .unwrap()
+                .tbs_certificate
+                .get::<BasicConstraints>()
+                .unwrap()
+                .unwrap();
+        assert!(critical);
+        assert!(constraints.ca);
+        assert!(root
+            .extensions
+            .contains_key(&rfc5280::ID_CE_KEY_USAGE.to_string()));
+
+        let mut native = root.clone();
+        native.x509_der = None;
+        native.extensions.insert("device-role".to_string(), vec![1]);
+        assert!(ca.sign_x509(&native).is_err());
+    }
+
+    #[test]
+    fn test_mixed_native_and_x509_chain() {
+        let mut ca = build_ca(42, "test-ca");
+        let root = ca.self_signed_x509(365).unwrap();
+        let x509_leaf = issue_x509_leaf(&mut ca);
+        let native_leaf = ca.get_certificate(&x509_leaf.serial).cloned().unwrap();
+
+        let mut validator = TrustChainValidator::new();
+        validator.add_trusted_root("test-ca".to_string(), root.public_key.clone());
+
+        assert!(validator.verify_chain(&[x509_leaf.clone(), root.clone()]));
+        assert!(validator.verify_chain(&[native_leaf, root.clone()]));
+
+        // Native fields are bound to the signed DER
+        let mut tampered = x509_leaf;
+        tampered.subject = "platform-9".to_string();
+        assert!(!validator.verify_chain(&[tampered, root]));
+    }
+
+    #[test]
+    fn test_reject_non_ed25519() {
+        let mut tbs_cert = external_tbs();
+        tbs_cert.subject_public_key_info.algorithm.oid = rfc5280::ID_CE_KEY_USAGE;
+        let x509 = x509_cert::Certificate {
+            tbs_certificate: tbs_cert,
+            signature_algorithm: ed25519_algorithm(),
+            signature: BitString::from_bytes(&[0u8; 64]).unwrap(),
+        };
+        assert!(Certificate::from_x509_der(&x509.to_der().unwrap()).is_err());
+    }
+
+    fn external_tbs() -> TbsCertificate {
+        TbsCertificate {
+            version: Version::V3,
+            serial_number: SerialNumber::from(7u64),
+            signature: ed25519_algorithm(),
+            issuer: string_to_name("external-ca").unwrap(),
+            validity: Validity {
+                not_before: millis_to_time(0).unwrap(),
+                not_after: millis_to_time(u64::MAX).unwrap(),
+            },
+            subject: string_to_name("external-device").unwrap(),
+            subject_public_key_info: ed25519_spki(&[7u8; 32]).unwrap(),
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: None,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Helpers shared by the integration tests
+
+use aethercore_identity::AkTrustAnchors;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p256::pkcs8::EncodePublicKey;
+use std::str::FromStr;
+use std::time::Duration;
+use x509_cert::der::asn1::{BitString, OctetString};
+use x509_cert::der::oid::db::{rfc5280, rfc5912};
+use x509_cert::der::{Decode, Encode};
+use x509_cert::ext::pkix::BasicConstraints;
+use x509_cert::ext::Extension;
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+use x509_cert::time::Validity;
+use x509_cert::{certificate::Version, Certificate, TbsCertificate};
+
+/// CA certifying TPM attestation keys, as a fleet or EK CA would
+pub struct TestAkCa {
+    key: SigningKey,
+}
+
+impl TestAkCa {
+    pub fn new(seed: u8) -> Self {
+        Self {
+            key: SigningKey::from_slice(&[seed; 32]).unwrap(),
+        }
+    }
+
+    /// Anchors trusting this CA
+    pub fn anchors(&self) -> AkTrustAnchors {
+        let mut anchors = AkTrustAnchors::new();
+        anchors
+            .add_der(&self.issue("test-ak-ca", self.key.verifying_key(), true))
+            .unwrap();
+        anchors
+    }
+
+    /// DER certificate for the SEC1 attestation key `ak_public_key`
+    pub fn certify(&self, ak_public_key: &[u8]) -> Vec<u8> {
+        let key = VerifyingKey::from_sec1_bytes(ak_public_key).unwrap();
+        self.issue("node-ak", &key, false)
+    }
+
+    fn issue(&self, subject: &str, subject_key: &VerifyingKey, is_ca: bool) -> Vec<u8> {
+        let algorithm = AlgorithmIdentifierOwned {
+            oid: rfc5912::ECDSA_WITH_SHA_256,
+            parameters: None,
+        };
+        let constraints = BasicConstraints {
+            ca: is_ca,
+            path_len_constraint: None,
+        };
+        let tbs = TbsCertificate {
+            version: Version::V3,
+            serial_number: SerialNumber::from(1u32),
+            signature: algorithm.clone(),
+            issuer: Name::from_str("CN=test-ak-ca").unwrap(),
+            validity: Validity::from_now(Duration::from_secs(3600)).unwrap(),
+            subject: Name::from_str(&format!("CN={}", subject)).unwrap(),
+            subject_public_key_info: SubjectPublicKeyInfoOwned::from_der(
+                subject_key.to_public_key_der().unwrap().as_bytes(),
+            )
+            .unwrap(),
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: Some(vec![Extension {
+                extn_id: rfc5280::ID_CE_BASIC_CONSTRAINTS,
+                critical: true,
+                extn_value: OctetString::new(constraints.to_der().unwrap()).unwrap(),
+            }]),
+        };
+        let signature: Signature = self.key.sign(&tbs.to_der().unwrap());
+        Certificate {
+            tbs_certificate: tbs,
+            signature_algorithm: algorithm,
+            signature: BitString::from_bytes(signature.to_der().as_bytes()).unwrap(),
+        }
+        .to_der()
+        .unwrap()
+    }
+}
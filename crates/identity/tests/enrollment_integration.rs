@@ -28,6 +28,7 @@ fn create_test_cert(subject: &str, issuer: &str) -> Certificate {
         not_after: u64::MAX,
         signature: vec![6, 7, 8, 9, 10],
         extensions: HashMap::new(),
+        x509_der: None,
     }
 }
 
@@ -490,6 +491,7 @@ fn test_certificate_authority_integration() {
             pcrs: vec![],
             ak_cert: vec![],
         },
+        pkcs10_der: None,
     };
 
     let csr_tbs = csr_tbs_bytes(&csr);
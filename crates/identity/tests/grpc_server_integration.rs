@@ -6,13 +6,23 @@
 //! - Node enrollment checks
 //! - Signature verification
 //! - Node revocation with authority signatures
+//! - Certificate expiry notifications
+//! - PCR golden-value policy enforcement
+//! - Attested certificate renewal
+
+#[cfg(feature = "grpc-server")]
+mod common;
 
 #[cfg(feature = "grpc-server")]
 mod grpc_tests {
+    use super::common::TestAkCa;
     use aethercore_identity::grpc_server::proto::identity_registry_client::IdentityRegistryClient;
     use aethercore_identity::grpc_server::proto::*;
-    use aethercore_identity::grpc_server::IdentityRegistryService;
-    use aethercore_identity::{Attestation, IdentityManager, PlatformIdentity, TpmManager};
+    use aethercore_identity::grpc_server::{registration_quote_nonce, IdentityRegistryService};
+    use aethercore_identity::{
+        Attestation, CertificateAuthority, CertificateRequest, IdentityManager, PcrPolicySet,
+        PlatformIdentity, RenewalManager, TpmManager, REQUIRED_PCRS,
+    };
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -107,6 +117,7 @@ mod grpc_tests {
             pcrs,
             ak_cert,
             timestamp_ms: current_timestamp_ms(),
+            ..Default::default()
         });
 
         let response = client.register_node(request).await.unwrap().into_inner();
@@ -118,6 +129,38 @@ mod grpc_tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_register_node_with_stale_timestamp_fails() {
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let tpm_manager = Arc::new(Mutex::new(TpmManager::new(false)));
+
+        let server_url = start_test_server_with_tpm_mode(
+            identity_manager.clone(),
+            tpm_manager.clone(),
+            Some(false),
+        )
+        .await;
+
+        let mut client = IdentityRegistryClient::connect(server_url)
+            .await
+            .expect("Failed to connect to server");
+
+        let public_key = vec![1u8; 32];
+        let node_id = hex::encode(blake3::hash(&public_key).as_bytes());
+
+        // A timestamp from an hour ago, as a replayed registration would carry
+        let request = tonic::Request::new(RegisterNodeRequest {
+            node_id: node_id.clone(),
+            public_key_hex: hex::encode(&public_key),
+            timestamp_ms: current_timestamp_ms() - 60 * 60 * 1000,
+            ..Default::default()
+        });
+
+        let err = client.register_node(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(!identity_manager.lock().unwrap().is_enrolled(&node_id));
+    }
+
     #[tokio::test]
     async fn test_register_node_without_tpm_quote_fails() {
         let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
@@ -144,6 +187,7 @@ mod grpc_tests {
             pcrs: vec![0xFF; 32],
             ak_cert: vec![0xBB; 64],
             timestamp_ms: current_timestamp_ms(),
+            ..Default::default()
         });
 
         let response = client.register_node(request).await;
@@ -176,6 +220,7 @@ mod grpc_tests {
             pcrs: vec![0xFF; 32],
             ak_cert: vec![0xBB; 64],
             timestamp_ms: current_timestamp_ms(),
+            ..Default::default()
         });
 
         let response = client.register_node(request).await;
@@ -379,6 +424,7 @@ mod grpc_tests {
             pcrs: vec![],      // Empty
             ak_cert: vec![],   // Empty
             timestamp_ms: current_timestamp_ms(),
+            ..Default::default()
         });
 
         let response = client.register_node(request).await.unwrap().into_inner();
@@ -428,6 +474,7 @@ mod grpc_tests {
             pcrs: vec![],              // Missing
             ak_cert: vec![],           // Missing
             timestamp_ms: current_timestamp_ms(),
+            ..Default::default()
         });
 
         let response = client.register_node(request).await.unwrap().into_inner();
@@ -473,4 +520,360 @@ mod grpc_tests {
         // Clean up
         std::env::remove_var("TPM_ENABLED");
     }
+
+    fn signed_csr(subject: &str, seed: u8) -> CertificateRequest {
+        use ed25519_dalek::SigningKey;
+        use x509_cert::der::Encode;
+
+        // PKCS#10 keeps the request self-contained for the integration test
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let ed25519 = x509_cert::spki::AlgorithmIdentifierOwned {
+            oid: x509_cert::der::oid::db::rfc8410::ID_ED_25519,
+            parameters: None,
+        };
+        let info = x509_cert::request::CertReqInfo {
+            version: x509_cert::request::Version::V1,
+            subject: format!("CN={}", subject).parse().unwrap(),
+            public_key: x509_cert::spki::SubjectPublicKeyInfoOwned {
+                algorithm: ed25519.clone(),
+                subject_public_key: x509_cert::der::asn1::BitString::from_bytes(
+                    key.verifying_key().as_bytes(),
+                )
+                .unwrap(),
+            },
+            attributes: Default::default(),
+        };
+        let signature = ed25519_dalek::Signer::sign(&key, &info.to_der().unwrap()).to_bytes();
+        let der = x509_cert::request::CertReq {
+            info,
+            algorithm: ed25519,
+            signature: x509_cert::der::asn1::BitString::from_bytes(&signature).unwrap(),
+        }
+        .to_der()
+        .unwrap();
+
+        CertificateRequest::from_pkcs10_der(
+            &der,
+            Attestation::Software {
+                certificate: vec![1, 2, 3],
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_watch_certificate_expiry() {
+        let ca_key = ed25519_dalek::SigningKey::from_bytes(&[42u8; 32]);
+        let mut ca = CertificateAuthority::new(
+            "test-ca",
+            ca_key.verifying_key().to_bytes().to_vec(),
+            ca_key.to_bytes().to_vec(),
+        );
+        let expiring = ca.issue_certificate(signed_csr("node-a", 1), 1).unwrap();
+        ca.issue_certificate(signed_csr("node-b", 2), 365).unwrap();
+        let ca = Arc::new(Mutex::new(ca));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        let service = IdentityRegistryService::new(
+            Arc::new(Mutex::new(IdentityManager::new())),
+            Arc::new(Mutex::new(TpmManager::new(false))),
+        )
+        .with_certificate_authority(ca.clone());
+        tokio::spawn(async move {
+            Server::builder()
+                .add_service(
+                    aethercore_identity::grpc_server::proto::identity_registry_server::IdentityRegistryServer::new(
+                        service,
+                    ),
+                )
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut client = IdentityRegistryClient::connect(server_url)
+            .await
+            .expect("Failed to connect to server");
+
+        let invalid = client
+            .watch_certificate_expiry(WatchCertificateExpiryRequest {
+                node_id: String::new(),
+                warning_window_ms: 0,
+            })
+            .await;
+        assert_eq!(invalid.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let week_ms = 7 * 24 * 60 * 60 * 1000;
+        let mut stream = client
+            .watch_certificate_expiry(WatchCertificateExpiryRequest {
+                node_id: String::new(),
+                warning_window_ms: week_ms,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        let notice = stream.message().await.unwrap().unwrap();
+        assert_eq!(notice.serial, expiring.serial);
+        assert_eq!(notice.node_id, "node-a");
+        assert_eq!(notice.not_after_ms, expiring.not_after);
+        assert!(notice.remaining_ms <= 24 * 60 * 60 * 1000);
+
+        // Only the short-lived certificate is within the window
+        let next = tokio::time::timeout(Duration::from_millis(200), stream.message()).await;
+        assert!(next.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_node_pcr_policy() {
+        std::env::set_var("TPM_ENABLED", "true");
+        // Stub TPMs report 0xFF for every PCR
+        let policies = PcrPolicySet::from_toml_str(&format!(
+            "[[policy]]\nname = \"uas-release\"\nplatform_types = [\"Uas\"]\n\
+             [[policy.measurements]]\n0 = \"{0}\"\n7 = \"{0}\"",
+            "ff".repeat(32)
+        ))
+        .unwrap();
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let ak_ca = TestAkCa::new(40);
+        let service = IdentityRegistryService::new(
+            identity_manager.clone(),
+            Arc::new(Mutex::new(TpmManager::new(false))),
+        )
+        .with_pcr_policy(policies)
+        .with_ak_trust_anchors(ak_ca.anchors());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            Server::builder()
+                .add_service(identity_registry_server::IdentityRegistryServer::new(
+                    service,
+                ))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut client = IdentityRegistryClient::connect(server_url).await.unwrap();
+
+        let mut tpm = TpmManager::new(false);
+        let ak = tpm.generate_attestation_key("node-ak".to_string()).unwrap();
+        let other_ak = TpmManager::new(false)
+            .generate_attestation_key("other-ak".to_string())
+            .unwrap();
+        let attested_request = |seed: u8, platform_type: &str| {
+            let public_key = vec![seed; 32];
+            let node_id = hex::encode(blake3::hash(&public_key).as_bytes());
+            let timestamp_ms = current_timestamp_ms();
+            let quote = tpm
+                .generate_quote(
+                    registration_quote_nonce(&node_id, timestamp_ms),
+                    &REQUIRED_PCRS,
+                )
+                .unwrap();
+            RegisterNodeRequest {
+                node_id,
+                public_key_hex: hex::encode(&public_key),
+                tpm_quote: quote.signature.clone(),
+                pcrs: quote
+                    .pcrs
+                    .iter()
+                    .flat_map(|pcr| pcr.value.clone())
+                    .collect(),
+                ak_cert: ak_ca.certify(&ak.public_key),
+                timestamp_ms,
+                attestation_data: quote.attestation_data.clone(),
+                ak_public_key: ak.public_key.clone(),
+                pcr_indices: quote.pcrs.iter().map(|pcr| pcr.index as u32).collect(),
+                platform_type: platform_type.to_string(),
+                firmware_version: "v2.0.0".to_string(),
+            }
+        };
+
+        let request = attested_request(30, "Uas");
+        let node_id = request.node_id.clone();
+        let response = client.register_node(request).await.unwrap().into_inner();
+        assert!(response.success, "{}", response.error_message);
+        {
+            let manager = identity_manager.lock().unwrap();
+            let metadata = &manager.get(&node_id).unwrap().metadata;
+            assert_eq!(metadata.get("pcr_policy").unwrap(), "uas-release");
+            assert_eq!(metadata.get("platform_type").unwrap(), "Uas");
+        }
+
+        // No policy covers this platform type
+        let err = client
+            .register_node(attested_request(31, "Usv"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("PCR policy"));
+
+        // A quote signed by a key the client chose proves nothing, whether
+        // the key is named outright or certified by an untrusted CA
+        let mut self_asserted = attested_request(32, "Uas");
+        self_asserted.ak_cert = vec![0xBB; 64];
+        let err = client.register_node(self_asserted).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("AK certificate rejected"));
+
+        let mut untrusted = attested_request(33, "Uas");
+        untrusted.ak_cert = TestAkCa::new(41).certify(&ak.public_key);
+        let err = client.register_node(untrusted).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        // Nor can a certified key vouch for another
+        let mut other_key = attested_request(34, "Uas");
+        other_key.ak_cert = ak_ca.certify(&other_ak.public_key);
+        let err = client.register_node(other_key).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("does not match"));
+    }
+
+    #[tokio::test]
+    async fn test_renew_certificate_with_enrolled_attestation_key() {
+        std::env::set_var("TPM_ENABLED", "true");
+        let ca_key = ed25519_dalek::SigningKey::from_bytes(&[42u8; 32]);
+        let ca = Arc::new(Mutex::new(CertificateAuthority::new(
+            "test-ca",
+            ca_key.verifying_key().to_bytes().to_vec(),
+            ca_key.to_bytes().to_vec(),
+        )));
+        let renewals = Arc::new(Mutex::new(RenewalManager::new(30)));
+        let ak_ca = TestAkCa::new(50);
+        let service = IdentityRegistryService::new(
+            Arc::new(Mutex::new(IdentityManager::new())),
+            Arc::new(Mutex::new(TpmManager::new(false))),
+        )
+        .with_certificate_authority(ca.clone())
+        .with_renewal_manager(renewals.clone())
+        .with_ak_trust_anchors(ak_ca.anchors());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            Server::builder()
+                .add_service(identity_registry_server::IdentityRegistryServer::new(
+                    service,
+                ))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut client = IdentityRegistryClient::connect(server_url).await.unwrap();
+
+        // Register a node with a certified attestation key, and issue it a
+        // certificate for the same Ed25519 key
+        let mut tpm = TpmManager::new(false);
+        let ak = tpm.generate_attestation_key("node-ak".to_string()).unwrap();
+        let node_key = ed25519_dalek::SigningKey::from_bytes(&[60u8; 32]);
+        let public_key = node_key.verifying_key().to_bytes().to_vec();
+        let node_id = hex::encode(blake3::hash(&public_key).as_bytes());
+        let csr_der = signed_csr(&node_id, 60).pkcs10_der.unwrap();
+        let certificate = ca
+            .lock()
+            .unwrap()
+            .issue_certificate(signed_csr(&node_id, 60), 30)
+            .unwrap();
+
+        let timestamp_ms = current_timestamp_ms();
+        let quote = tpm
+            .generate_quote(
+                registration_quote_nonce(&node_id, timestamp_ms),
+                &REQUIRED_PCRS,
+            )
+            .unwrap();
+        let response = client
+            .register_node(RegisterNodeRequest {
+                node_id: node_id.clone(),
+                public_key_hex: hex::encode(&public_key),
+                tpm_quote: quote.signature.clone(),
+                pcrs: quote
+                    .pcrs
+                    .iter()
+                    .flat_map(|pcr| pcr.value.clone())
+                    .collect(),
+                ak_cert: ak_ca.certify(&ak.public_key),
+                timestamp_ms,
+                attestation_data: quote.attestation_data.clone(),
+                ak_public_key: ak.public_key.clone(),
+                pcr_indices: quote.pcrs.iter().map(|pcr| pcr.index as u32).collect(),
+                platform_type: String::new(),
+                firmware_version: String::new(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.success, "{}", response.error_message);
+        assert!(renewals.lock().unwrap().attestation_key(&node_id).is_some());
+
+        let renew_request = |tpm: &TpmManager, nonce: Vec<u8>| {
+            let quote = tpm.generate_quote(nonce, &REQUIRED_PCRS).unwrap();
+            RenewCertificateRequest {
+                serial: certificate.serial.clone(),
+                csr_der: csr_der.clone(),
+                tpm_quote: quote.signature,
+                attestation_data: quote.attestation_data,
+                pcrs: quote
+                    .pcrs
+                    .iter()
+                    .flat_map(|pcr| pcr.value.clone())
+                    .collect(),
+                pcr_indices: quote.pcrs.iter().map(|pcr| pcr.index as u32).collect(),
+            }
+        };
+        let challenge = |serial: &str| GetRenewalChallengeRequest {
+            serial: serial.to_string(),
+        };
+
+        // A quote by any other TPM key is refused
+        let mut impostor = TpmManager::new(false);
+        impostor
+            .generate_attestation_key("impostor-ak".to_string())
+            .unwrap();
+        let nonce = client
+            .get_renewal_challenge(challenge(&certificate.serial))
+            .await
+            .unwrap()
+            .into_inner()
+            .nonce;
+        let err = client
+            .renew_certificate(renew_request(&impostor, nonce))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        let nonce = client
+            .get_renewal_challenge(challenge(&certificate.serial))
+            .await
+            .unwrap()
+            .into_inner()
+            .nonce;
+        let request = renew_request(&tpm, nonce);
+        let renewed = client
+            .renew_certificate(request.clone())
+            .await
+            .unwrap()
+            .into_inner();
+        assert_ne!(renewed.serial, certificate.serial);
+        assert!(!renewed.certificate_der.is_empty());
+        assert_eq!(
+            ca.lock().unwrap().superseded_by(&certificate.serial),
+            Some(renewed.serial.as_str())
+        );
+
+        // Challenges are single-use
+        let err = client.renew_certificate(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        let err = client
+            .get_renewal_challenge(challenge("no-such-serial"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
 }
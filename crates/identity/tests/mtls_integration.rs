@@ -0,0 +1,284 @@
+//! Integration tests for the Identity Registry gRPC server over mutual TLS
+//!
+//! Tests cover:
+//! - Registration by a client holding a CA-issued certificate
+//! - Binding of the requested NodeID to the certificate subject
+//! - Refusal of revoked identities and clients without a certificate
+
+#[cfg(feature = "mtls")]
+mod mtls_tests {
+    use aethercore_identity::grpc_server::proto::identity_registry_client::IdentityRegistryClient;
+    use aethercore_identity::grpc_server::proto::*;
+    use aethercore_identity::grpc_server::IdentityRegistryService;
+    use aethercore_identity::mtls::{self, IdentityPeerAuthorizer, IdentityStatus};
+    use aethercore_identity::{
+        Attestation, Certificate, CertificateAuthority, CertificateRequest, IdentityManager,
+        TpmManager, TrustChainValidator,
+    };
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+    use ed25519_dalek::SigningKey;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tokio::time::Duration;
+    use tonic::transport::{Channel, ClientTlsConfig, Identity, Server};
+    use x509_cert::der::pem::{self, LineEnding};
+
+    fn current_timestamp_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    fn node_id(key: &SigningKey) -> String {
+        hex::encode(blake3::hash(key.verifying_key().as_bytes()).as_bytes())
+    }
+
+    /// Issue an X.509 certificate for `key` from `ca`.
+    fn issue(
+        ca: &mut CertificateAuthority,
+        subject: &str,
+        key: &SigningKey,
+        extensions: HashMap<String, Vec<u8>>,
+    ) -> Certificate {
+        let pkcs10 = {
+            use ed25519_dalek::Signer;
+            use x509_cert::der::Encode;
+
+            let info = x509_cert::request::CertReqInfo {
+                version: x509_cert::request::Version::V1,
+                subject: format!("CN={}", subject).parse().unwrap(),
+                public_key: x509_cert::spki::SubjectPublicKeyInfoOwned {
+                    algorithm: x509_cert::spki::AlgorithmIdentifierOwned {
+                        oid: x509_cert::der::oid::db::rfc8410::ID_ED_25519,
+                        parameters: None,
+                    },
+                    subject_public_key: x509_cert::der::asn1::BitString::from_bytes(
+                        key.verifying_key().as_bytes(),
+                    )
+                    .unwrap(),
+                },
+                attributes: Default::default(),
+            };
+            let signature = key.sign(&info.to_der().unwrap()).to_bytes();
+            x509_cert::request::CertReq {
+                info,
+                algorithm: x509_cert::spki::AlgorithmIdentifierOwned {
+                    oid: x509_cert::der::oid::db::rfc8410::ID_ED_25519,
+                    parameters: None,
+                },
+                signature: x509_cert::der::asn1::BitString::from_bytes(&signature).unwrap(),
+            }
+            .to_der()
+            .unwrap()
+        };
+        let request = CertificateRequest::from_pkcs10_der(
+            &pkcs10,
+            Attestation::Software {
+                certificate: vec![1, 2, 3],
+            },
+        )
+        .unwrap();
+        let native = ca
+            .issue_certificate_with_extensions(request, 30, extensions)
+            .unwrap();
+        ca.export_x509(&native).unwrap()
+    }
+
+    fn key_pem(key: &SigningKey) -> String {
+        let der = key.to_pkcs8_der().unwrap();
+        pem::encode_string("PRIVATE KEY", LineEnding::LF, der.as_bytes()).unwrap()
+    }
+
+    struct TestPki {
+        ca: CertificateAuthority,
+        ca_root: Certificate,
+    }
+
+    impl TestPki {
+        fn new() -> Self {
+            let ca_key = SigningKey::from_bytes(&[1u8; 32]);
+            let ca = CertificateAuthority::new(
+                "aethercore-test-ca",
+                ca_key.verifying_key().to_bytes().to_vec(),
+                ca_key.to_bytes().to_vec(),
+            );
+            let ca_root = ca.self_signed_x509(30).unwrap();
+            Self { ca, ca_root }
+        }
+
+        fn client_tls(&mut self, key: Option<&SigningKey>) -> ClientTlsConfig {
+            let mut tls = ClientTlsConfig::new()
+                .ca_certificate(tonic::transport::Certificate::from_pem(
+                    self.ca_root.to_x509_pem().unwrap(),
+                ))
+                .domain_name("localhost");
+            if let Some(key) = key {
+                let cert = issue(&mut self.ca, &node_id(key), key, HashMap::new());
+                tls = tls.identity(Identity::from_pem(
+                    cert.to_x509_pem().unwrap(),
+                    key_pem(key),
+                ));
+            }
+            tls
+        }
+    }
+
+    async fn start_mtls_server(
+        pki: &mut TestPki,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+    ) -> String {
+        std::env::set_var("TPM_ENABLED", "false");
+
+        let server_key = SigningKey::from_bytes(&[2u8; 32]);
+        let (san_oid, san_value) = mtls::subject_alt_name_dns(&["localhost"]).unwrap();
+        let server_cert = issue(
+            &mut pki.ca,
+            "identity-registry",
+            &server_key,
+            HashMap::from([(san_oid, san_value)]),
+        );
+
+        let mut validator = TrustChainValidator::new();
+        validator.add_trusted_root(pki.ca.ca_id().to_string(), pki.ca.public_key().to_vec());
+        let identities: Arc<dyn IdentityStatus> = identity_manager.clone();
+        let authorizer =
+            Arc::new(IdentityPeerAuthorizer::new(validator).with_identity_status(identities));
+        let config = mtls::server_config(
+            &[server_cert],
+            &server_key,
+            std::slice::from_ref(&pki.ca_root),
+            authorizer,
+        )
+        .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+        let incoming = mtls::incoming(listener, &config).unwrap();
+
+        let service = IdentityRegistryService::new(
+            identity_manager,
+            Arc::new(Mutex::new(TpmManager::new(false))),
+        )
+        .with_peer_authorizer(config.authorizer());
+
+        tokio::spawn(async move {
+            Server::builder()
+                .add_service(identity_registry_server::IdentityRegistryServer::new(
+                    service,
+                ))
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        });
+
+        // Wait for server to start
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        format!("https://{}", local_addr)
+    }
+
+    async fn register(
+        server_url: &str,
+        tls: ClientTlsConfig,
+        key: &SigningKey,
+        node_id: String,
+    ) -> Result<RegisterNodeResponse, tonic::Status> {
+        let channel = Channel::from_shared(server_url.to_string())
+            .unwrap()
+            .tls_config(tls)
+            .unwrap()
+            .connect()
+            .await
+            .map_err(|e| tonic::Status::unavailable(e.to_string()))?;
+        let mut client = IdentityRegistryClient::new(channel);
+
+        let request = tonic::Request::new(RegisterNodeRequest {
+            node_id,
+            public_key_hex: hex::encode(key.verifying_key().as_bytes()),
+            tpm_quote: vec![],
+            pcrs: vec![],
+            ak_cert: vec![],
+            timestamp_ms: current_timestamp_ms(),
+            ..Default::default()
+        });
+        client
+            .register_node(request)
+            .await
+            .map(|response| response.into_inner())
+    }
+
+    #[tokio::test]
+    async fn test_register_with_client_certificate() {
+        let mut pki = TestPki::new();
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let server_url = start_mtls_server(&mut pki, identity_manager.clone()).await;
+
+        let key = SigningKey::from_bytes(&[10u8; 32]);
+        let tls = pki.client_tls(Some(&key));
+        let response = register(&server_url, tls, &key, node_id(&key))
+            .await
+            .unwrap();
+
+        assert!(response.success, "{}", response.error_message);
+        assert!(identity_manager.lock().unwrap().is_enrolled(&node_id(&key)));
+    }
+
+    #[tokio::test]
+    async fn test_register_other_node_id_denied() {
+        let mut pki = TestPki::new();
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let server_url = start_mtls_server(&mut pki, identity_manager.clone()).await;
+
+        let key = SigningKey::from_bytes(&[10u8; 32]);
+        let other_key = SigningKey::from_bytes(&[11u8; 32]);
+        let tls = pki.client_tls(Some(&key));
+        let err = register(&server_url, tls, &other_key, node_id(&other_key))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(!identity_manager
+            .lock()
+            .unwrap()
+            .is_enrolled(&node_id(&other_key)));
+    }
+
+    #[tokio::test]
+    async fn test_revoked_identity_refused() {
+        let mut pki = TestPki::new();
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let server_url = start_mtls_server(&mut pki, identity_manager.clone()).await;
+
+        let key = SigningKey::from_bytes(&[10u8; 32]);
+        let tls = pki.client_tls(Some(&key));
+        register(&server_url, tls.clone(), &key, node_id(&key))
+            .await
+            .unwrap();
+
+        identity_manager
+            .lock()
+            .unwrap()
+            .revoke(&node_id(&key))
+            .unwrap();
+
+        assert!(register(&server_url, tls, &key, node_id(&key))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_without_certificate_refused() {
+        let mut pki = TestPki::new();
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let server_url = start_mtls_server(&mut pki, identity_manager.clone()).await;
+
+        let key = SigningKey::from_bytes(&[10u8; 32]);
+        let tls = pki.client_tls(None);
+        assert!(register(&server_url, tls, &key, node_id(&key))
+            .await
+            .is_err());
+        assert!(!identity_manager.lock().unwrap().is_enrolled(&node_id(&key)));
+    }
+}
@@ -0,0 +1,302 @@
+//! Integration tests against a software TPM 2.0 (swtpm)
+//!
+//! Each test launches its own `swtpm socket` instance and is skipped when the
+//! swtpm binary is not installed.
+//!
+//! Tests cover:
+//! - Genuine quotes and PCR reads through TpmManager
+//! - Attestation handshake answered with a TPM-resident attestation key
+//! - Node registration with TPM enforcement enabled
+
+#[cfg(feature = "tpm-simulator-tests")]
+mod common;
+
+#[cfg(feature = "tpm-simulator-tests")]
+mod simulator_tests {
+    use super::common::TestAkCa;
+    use aethercore_identity::grpc_server::proto::identity_registry_client::IdentityRegistryClient;
+    use aethercore_identity::grpc_server::proto::*;
+    use aethercore_identity::grpc_server::{registration_quote_nonce, IdentityRegistryService};
+    use aethercore_identity::tpm_attest::TPM_GENERATED_VALUE;
+    use aethercore_identity::{
+        Attestation, AttestationKey, AttestationManager, Certificate, IdentityManager,
+        PlatformIdentity, SimulatorConfig, SimulatorProtocol, TpmManager, REQUIRED_PCRS,
+    };
+    use std::collections::HashMap;
+    use std::net::{TcpListener, TcpStream};
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+    use tonic::transport::Server;
+
+    fn current_timestamp_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    /// A running swtpm instance, stopped and cleaned up on drop.
+    struct Swtpm {
+        child: Child,
+        state_dir: PathBuf,
+        port: u16,
+    }
+
+    impl Swtpm {
+        /// Launch swtpm on free local ports, or `None` if it is not installed.
+        fn launch() -> Option<Self> {
+            let installed = Command::new("swtpm")
+                .args(["socket", "--help"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok();
+            if !installed {
+                eprintln!("Skipping TPM simulator integration test: swtpm not found.");
+                return None;
+            }
+
+            let port = free_port_pair();
+            let state_dir = std::env::temp_dir().join(format!(
+                "aethercore-swtpm-{}-{}",
+                std::process::id(),
+                port
+            ));
+            std::fs::create_dir_all(&state_dir).unwrap();
+
+            let child = Command::new("swtpm")
+                .arg("socket")
+                .arg("--tpm2")
+                .arg("--server")
+                .arg(format!("type=tcp,port={},bindaddr=127.0.0.1", port))
+                .arg("--ctrl")
+                .arg(format!("type=tcp,port={},bindaddr=127.0.0.1", port + 1))
+                .arg("--tpmstate")
+                .arg(format!("dir={}", state_dir.display()))
+                .arg("--flags")
+                .arg("not-need-init")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("Failed to launch swtpm");
+            let swtpm = Self {
+                child,
+                state_dir,
+                port,
+            };
+
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while TcpStream::connect(("127.0.0.1", port)).is_err() {
+                assert!(Instant::now() < deadline, "swtpm did not start");
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Some(swtpm)
+        }
+
+        fn config(&self) -> SimulatorConfig {
+            SimulatorConfig {
+                host: "127.0.0.1".to_string(),
+                port: self.port,
+                protocol: SimulatorProtocol::Swtpm,
+            }
+        }
+    }
+
+    impl Drop for Swtpm {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            let _ = std::fs::remove_dir_all(&self.state_dir);
+        }
+    }
+
+    /// A port whose successor is also free (server and control channels).
+    fn free_port_pair() -> u16 {
+        loop {
+            let port = TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port();
+            if port < u16::MAX && TcpListener::bind(("127.0.0.1", port + 1)).is_ok() {
+                return port;
+            }
+        }
+    }
+
+    fn simulated_tpm(swtpm: &Swtpm, key_id: &str) -> (TpmManager, AttestationKey) {
+        let mut tpm = TpmManager::with_simulator(swtpm.config()).unwrap();
+        let ak = tpm.generate_attestation_key(key_id.to_string()).unwrap();
+        (tpm, ak)
+    }
+
+    fn test_cert(subject: &str) -> Certificate {
+        Certificate {
+            serial: "1".to_string(),
+            subject: subject.to_string(),
+            issuer: "test-ca".to_string(),
+            public_key: vec![1, 2, 3],
+            not_before: 0,
+            not_after: u64::MAX,
+            signature: vec![4, 5, 6],
+            extensions: HashMap::new(),
+            x509_der: None,
+        }
+    }
+
+    #[test]
+    fn test_simulator_quote_round_trip() {
+        let Some(swtpm) = Swtpm::launch() else {
+            return;
+        };
+        let (tpm, ak) = simulated_tpm(&swtpm, "simulator-ak");
+
+        let nonce = vec![0x42; 32];
+        let quote = tpm.generate_quote(nonce, &REQUIRED_PCRS).unwrap();
+        assert!(tpm.verify_quote(&quote, &ak));
+        assert_eq!(quote.pcrs, tpm.read_pcrs(&REQUIRED_PCRS).unwrap());
+
+        // Verification needs no TPM of its own
+        let verification = TpmManager::new(false).verify_quote_detailed(&quote, &ak);
+        assert!(verification.verified, "{:?}", verification.failure);
+        let attestation = verification.attestation.unwrap();
+        assert_eq!(attestation.magic, TPM_GENERATED_VALUE);
+        assert_eq!(
+            attestation.quote.pcr_selections[0].indices,
+            REQUIRED_PCRS.to_vec()
+        );
+
+        // A later quote from the same TPM has a later clock
+        std::thread::sleep(Duration::from_millis(50));
+        let later = tpm.generate_quote(vec![0x44; 32], &REQUIRED_PCRS).unwrap();
+        assert!(tpm.verify_quote(&later, &ak));
+        assert!(!tpm.verify_quote(&quote, &ak));
+
+        let mut tampered = quote.clone();
+        tampered.pcrs[0].value[0] ^= 0x01;
+        assert!(!tpm.verify_quote(&tampered, &ak));
+
+        let mut replayed = quote.clone();
+        replayed.nonce = vec![0x43; 32];
+        assert!(!tpm.verify_quote(&replayed, &ak));
+    }
+
+    #[test]
+    fn test_handshake_with_simulated_tpm() {
+        let Some(swtpm) = Swtpm::launch() else {
+            return;
+        };
+        let (tpm, ak) = simulated_tpm(&swtpm, "tpm-node-ak");
+        let pcrs = tpm.read_pcrs(&REQUIRED_PCRS).unwrap();
+
+        let tpm_identity = PlatformIdentity {
+            id: "tpm-node".to_string(),
+            public_key: ak.public_key.clone(),
+            attestation: Attestation::Tpm {
+                quote: Vec::new(),
+                pcrs: serde_json::to_vec(&pcrs).unwrap(),
+                ak_cert: Vec::new(),
+            },
+            created_at: current_timestamp_ms(),
+            metadata: HashMap::new(),
+        };
+        let software_key = ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]);
+        let software_identity = PlatformIdentity {
+            id: "software-node".to_string(),
+            public_key: software_key.verifying_key().to_bytes().to_vec(),
+            attestation: Attestation::Software {
+                certificate: vec![1, 2, 3],
+            },
+            created_at: current_timestamp_ms(),
+            metadata: HashMap::from([
+                (
+                    "private_key_hex".to_string(),
+                    hex::encode(software_key.to_bytes()),
+                ),
+                ("key_type".to_string(), "ed25519".to_string()),
+            ]),
+        };
+
+        let mut initiator =
+            AttestationManager::new(software_identity, vec![test_cert("software-node")]);
+        let mut responder = AttestationManager::new(tpm_identity, vec![test_cert("tpm-node")])
+            .with_tpm(tpm, "tpm-node-ak");
+
+        let request = initiator.initiate_handshake("tpm-node").unwrap();
+        let response = responder.handle_request(request).unwrap();
+        assert!(response.tpm_quote.is_some());
+
+        let finalize = initiator.handle_response(response).unwrap();
+        assert!(responder.handle_finalize("software-node", finalize).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_register_node_with_simulated_quote() {
+        let Some(swtpm) = Swtpm::launch() else {
+            return;
+        };
+        let (tpm, ak) = simulated_tpm(&swtpm, "registering-node-ak");
+
+        std::env::set_var("TPM_ENABLED", "true");
+        let identity_manager = Arc::new(Mutex::new(IdentityManager::new()));
+        let ak_ca = TestAkCa::new(40);
+        let service = IdentityRegistryService::new(
+            identity_manager.clone(),
+            Arc::new(Mutex::new(TpmManager::new(false))),
+        )
+        .with_ak_trust_anchors(ak_ca.anchors());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            Server::builder()
+                .add_service(identity_registry_server::IdentityRegistryServer::new(
+                    service,
+                ))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut client = IdentityRegistryClient::connect(server_url).await.unwrap();
+
+        let public_key = vec![7u8; 32];
+        let node_id = hex::encode(blake3::hash(&public_key).as_bytes());
+        let timestamp_ms = current_timestamp_ms();
+        let quote = tpm
+            .generate_quote(
+                registration_quote_nonce(&node_id, timestamp_ms),
+                &REQUIRED_PCRS,
+            )
+            .unwrap();
+        let request = RegisterNodeRequest {
+            node_id: node_id.clone(),
+            public_key_hex: hex::encode(&public_key),
+            tpm_quote: quote.signature.clone(),
+            pcrs: quote
+                .pcrs
+                .iter()
+                .flat_map(|pcr| pcr.value.clone())
+                .collect(),
+            ak_cert: ak_ca.certify(&ak.public_key),
+            timestamp_ms,
+            attestation_data: quote.attestation_data.clone(),
+            ak_public_key: ak.public_key.clone(),
+            pcr_indices: quote.pcrs.iter().map(|pcr| pcr.index as u32).collect(),
+            ..Default::default()
+        };
+
+        // The quote is bound to the request timestamp
+        let replayed = RegisterNodeRequest {
+            timestamp_ms: timestamp_ms + 1,
+            ..request.clone()
+        };
+        let err = client.register_node(replayed).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        let response = client.register_node(request).await.unwrap().into_inner();
+        assert!(response.success, "{}", response.error_message);
+        assert!(identity_manager.lock().unwrap().is_enrolled(&node_id));
+    }
+}
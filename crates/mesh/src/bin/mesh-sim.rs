@@ -0,0 +1,272 @@
+//! Mesh Simulator CLI
+//!
+//! Runs the deterministic mesh simulator under the network health grades
+//! of the degraded RF resilience configuration, with optional partitions,
+//! jamming and Byzantine nodes, and reports convergence, overhead and
+//! trust for each grade.
+
+use aethercore_mesh::{
+    ByzantineBehavior, Jamming, Partition, Scenario, SimConfig, SimReport, Simulation,
+};
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+const DEFAULT_CONFIG: &str = "config/degraded-rf-resilience.yaml";
+
+/// Faults and sizing applied on top of every scenario
+struct Options {
+    config: PathBuf,
+    scenario: Option<String>,
+    nodes: usize,
+    seed: u64,
+    duration: Duration,
+    partitions: Vec<Partition>,
+    jamming: Vec<Jamming>,
+    byzantine: Vec<(usize, ByzantineBehavior)>,
+    json: bool,
+}
+
+fn print_usage() {
+    println!("Mesh Simulator - Deterministic multi-node mesh runs with fault injection");
+    println!();
+    println!("Usage: mesh-sim [options]");
+    println!();
+    println!("Options:");
+    println!("  --config, -c <path>         Resilience config with the network health grades");
+    println!(
+        "                              (default: {})",
+        DEFAULT_CONFIG
+    );
+    println!("  --scenario, -s <grade>      Only simulate this health grade");
+    println!("  --nodes, -n <count>         Number of nodes (default: 8)");
+    println!("  --seed <seed>               Random seed (default: 0)");
+    println!("  --duration, -d <secs>       Simulated time (default: 60)");
+    println!("  --partition <from-until>    Split the swarm in two halves, in seconds");
+    println!("  --jam <from-until:ch,...>   Jam channels, in seconds");
+    println!("  --byzantine <node:behavior> Make a node forge, equivocate or flood");
+    println!("  --json                      Output reports as JSON");
+    println!();
+    println!("Examples:");
+    println!("  mesh-sim --scenario contested --nodes 12");
+    println!("  mesh-sim --partition 10-30 --byzantine 3:equivocate --json");
+    println!("  mesh-sim --jam 5-40:0 --seed 7");
+}
+
+/// Parse `from-until` in seconds
+fn parse_span(span: &str) -> Result<(Duration, Duration), String> {
+    let (from, until) = span
+        .split_once('-')
+        .ok_or_else(|| format!("Invalid time span {}: expected from-until", span))?;
+    let seconds = |value: &str| {
+        value
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .map_err(|_| format!("Invalid seconds in {}", span))
+    };
+    Ok((seconds(from)?, seconds(until)?))
+}
+
+fn parse_jam(jam: &str) -> Result<Jamming, String> {
+    let (span, channels) = jam
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid jamming {}: expected from-until:channels", jam))?;
+    let (from, until) = parse_span(span)?;
+    let channels = channels
+        .split(',')
+        .map(|channel| {
+            channel
+                .parse()
+                .map_err(|_| format!("Invalid channel {}", channel))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Jamming {
+        from,
+        until,
+        channels,
+    })
+}
+
+fn parse_byzantine(byzantine: &str) -> Result<(usize, ByzantineBehavior), String> {
+    let (node, behavior) = byzantine.split_once(':').ok_or_else(|| {
+        format!(
+            "Invalid Byzantine node {}: expected node:behavior",
+            byzantine
+        )
+    })?;
+    let node = node
+        .parse()
+        .map_err(|_| format!("Invalid node index {}", node))?;
+    let behavior = behavior.parse().map_err(|e| format!("{}", e))?;
+    Ok((node, behavior))
+}
+
+fn parse_args() -> Result<Options, String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut options = Options {
+        config: PathBuf::from(DEFAULT_CONFIG),
+        scenario: None,
+        nodes: 8,
+        seed: 0,
+        duration: Duration::from_secs(60),
+        partitions: Vec::new(),
+        jamming: Vec::new(),
+        byzantine: Vec::new(),
+        json: false,
+    };
+    let mut partitions = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if matches!(flag, "--help" | "-h") {
+            print_usage();
+            process::exit(0);
+        }
+        if flag == "--json" {
+            options.json = true;
+            i += 1;
+            continue;
+        }
+        let value = args
+            .get(i + 1)
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        match flag {
+            "--config" | "-c" => options.config = PathBuf::from(value),
+            "--scenario" | "-s" => options.scenario = Some(value.clone()),
+            "--nodes" | "-n" => {
+                options.nodes = value
+                    .parse()
+                    .map_err(|_| format!("Invalid node count {}", value))?
+            }
+            "--seed" => {
+                options.seed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid seed {}", value))?
+            }
+            "--duration" | "-d" => {
+                options.duration = Duration::from_secs(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid duration {}", value))?,
+                )
+            }
+            "--partition" => partitions.push(parse_span(value)?),
+            "--jam" => options.jamming.push(parse_jam(value)?),
+            "--byzantine" => options.byzantine.push(parse_byzantine(value)?),
+            _ => return Err(format!("Unknown option {}", flag)),
+        }
+        i += 2;
+    }
+
+    // Partitions are applied once the swarm size is known
+    let half = options.nodes / 2;
+    options.partitions = partitions
+        .into_iter()
+        .map(|(from, until)| Partition {
+            from,
+            until,
+            groups: vec![(0..half).collect(), (half..options.nodes).collect()],
+        })
+        .collect();
+    Ok(options)
+}
+
+fn print_report(report: &SimReport) {
+    let convergence = &report.convergence;
+    let ms = |value: Option<u64>| value.map_or("-".to_string(), |ms| format!("{} ms", ms));
+    println!(
+        "== {} ({} nodes, seed {}, {} s)",
+        report.name,
+        report.nodes,
+        report.seed,
+        report.duration_ms / 1000
+    );
+    println!(
+        "  Convergence: {}/{} blocks, mean {}, p95 {}, max {}, agreed at end: {}",
+        convergence.converged,
+        convergence.blocks,
+        ms(convergence.mean_ms),
+        ms(convergence.p95_ms),
+        ms(convergence.max_ms),
+        convergence.agreed_at_end
+    );
+
+    let overhead = &report.overhead;
+    println!(
+        "  Overhead: {} frames ({} bytes), {:.1} frames/block, {} delivered",
+        overhead.frames_sent,
+        overhead.bytes_sent,
+        overhead.frames_per_block,
+        overhead.frames_delivered
+    );
+    for (kind, count) in &overhead.frames_by_kind {
+        println!("    {:<20} {}", kind, count);
+    }
+    for (cause, count) in &overhead.frames_lost {
+        println!("    lost to {:<12} {}", cause, count);
+    }
+
+    let spectral = &report.spectral;
+    println!(
+        "  Spectral: {} plans activated, {} node-epochs out of sync, agreed at end: {}",
+        spectral.plans_activated, spectral.epochs_out_of_sync, spectral.agreed_at_end
+    );
+
+    println!("  Trust:");
+    for outcome in &report.trust {
+        let role = outcome
+            .byzantine
+            .map_or("honest".to_string(), |behavior| format!("{:?}", behavior));
+        let trust = outcome
+            .mean_trust
+            .map_or("-".to_string(), |trust| format!("{:.2}", trust));
+        println!(
+            "    {:<10} {:<16} trust {:<5} revoked by {}, dropped by {}",
+            outcome.node_id, role, trust, outcome.revoked_by, outcome.dropped_by
+        );
+    }
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut scenarios = Scenario::load(&options.config)
+        .map_err(|e| format!("Cannot load {}: {}", options.config.display(), e))?;
+    if let Some(name) = &options.scenario {
+        scenarios.retain(|scenario| &scenario.name == name);
+        if scenarios.is_empty() {
+            return Err(format!("No health grade named {}", name));
+        }
+    }
+
+    let mut reports = Vec::with_capacity(scenarios.len());
+    for scenario in &scenarios {
+        let config = SimConfig {
+            seed: options.seed,
+            nodes: options.nodes,
+            duration: options.duration,
+            partitions: options.partitions.clone(),
+            jamming: options.jamming.clone(),
+            byzantine: options.byzantine.iter().copied().collect(),
+            ..SimConfig::for_scenario(scenario)
+        };
+        let report = Simulation::new(config)
+            .run()
+            .map_err(|e| format!("Simulation of {} failed: {}", scenario.name, e))?;
+        if !options.json {
+            print_report(&report);
+        }
+        reports.push(report);
+    }
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -43,6 +43,20 @@ pub struct StoredBlock {
     pub synced: bool,
 }
 
+impl StoredBlock {
+    /// Hash of the block at `height` carrying `data` on top of `parent_hash`
+    ///
+    /// The first block of a chain has an empty parent hash, so the hash of
+    /// a chain's highest block commits to every block beneath it.
+    pub fn chain_hash(parent_hash: &[u8], height: u64, data: &[u8]) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(parent_hash);
+        hasher.update(&height.to_be_bytes());
+        hasher.update(data);
+        hasher.finalize().as_bytes().to_vec()
+    }
+}
+
 /// Stored telemetry or C2 command
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredEvent {
@@ -227,6 +241,166 @@ impl BunkerMode {
         Ok((block_count as usize, event_count as usize))
     }
 
+    /// Get up to `limit` blocks above `height` (all blocks for `None`), lowest first
+    pub fn get_blocks_above(
+        &self,
+        height: Option<u64>,
+        limit: usize,
+    ) -> SqliteResult<Vec<StoredBlock>> {
+        let mut stmt = self.db.prepare(
+            "SELECT hash, height, data, timestamp, synced FROM chain_store
+             WHERE ?1 IS NULL OR height > ?1 ORDER BY height, hash LIMIT ?2",
+        )?;
+
+        let blocks = stmt
+            .query_map(rusqlite::params![height, limit as i64], |row| {
+                Ok(StoredBlock {
+                    hash: row.get(0)?,
+                    height: row.get(1)?,
+                    data: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    synced: row.get::<_, i32>(4)? != 0,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(blocks)
+    }
+
+    /// Get all blocks above `after` (from the lowest for `None`) up to and
+    /// including `up_to`, lowest first
+    pub fn get_blocks_between(
+        &self,
+        after: Option<u64>,
+        up_to: u64,
+    ) -> SqliteResult<Vec<StoredBlock>> {
+        let mut stmt = self.db.prepare(
+            "SELECT hash, height, data, timestamp, synced FROM chain_store
+             WHERE (?1 IS NULL OR height > ?1) AND height <= ?2 ORDER BY height, hash",
+        )?;
+
+        let blocks = stmt
+            .query_map(rusqlite::params![after, up_to], |row| {
+                Ok(StoredBlock {
+                    hash: row.get(0)?,
+                    height: row.get(1)?,
+                    data: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    synced: row.get::<_, i32>(4)? != 0,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(blocks)
+    }
+
+    /// Store a block received from a peer, unless its hash is already held
+    ///
+    /// Returns whether the block was added; a held block is never replaced.
+    pub fn insert_block(&mut self, block: StoredBlock) -> SqliteResult<bool> {
+        let added = self.db.execute(
+            "INSERT OR IGNORE INTO chain_store (hash, height, data, timestamp, synced)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                block.hash,
+                block.height,
+                block.data,
+                block.timestamp,
+                block.synced as i32
+            ],
+        )?;
+        Ok(added > 0)
+    }
+
+    /// Store an event received from a peer, unless its ID is already held
+    ///
+    /// Returns whether the event was added; a held event is never replaced.
+    pub fn insert_event(&mut self, event: StoredEvent) -> SqliteResult<bool> {
+        let added = self.db.execute(
+            "INSERT OR IGNORE INTO event_store (id, event_type, payload, timestamp, synced)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                event.id,
+                event.event_type,
+                event.payload,
+                event.timestamp,
+                event.synced as i32
+            ],
+        )?;
+        Ok(added > 0)
+    }
+
+    /// Hashes of the blocks stored at `height`
+    pub fn get_block_hashes_at(&self, height: u64) -> SqliteResult<Vec<Vec<u8>>> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT hash FROM chain_store WHERE height = ?1 ORDER BY hash")?;
+        let hashes = stmt
+            .query_map([height], |row| row.get(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(hashes)
+    }
+
+    /// Whether any block is stored below `height`
+    pub fn has_blocks_below(&self, height: u64) -> SqliteResult<bool> {
+        self.db.query_row(
+            "SELECT EXISTS(SELECT 1 FROM chain_store WHERE height < ?1)",
+            [height],
+            |row| row.get(0),
+        )
+    }
+
+    /// Whether a block with `hash` is stored
+    pub fn has_block(&self, hash: &[u8]) -> SqliteResult<bool> {
+        self.db.query_row(
+            "SELECT EXISTS(SELECT 1 FROM chain_store WHERE hash = ?1)",
+            [hash],
+            |row| row.get(0),
+        )
+    }
+
+    /// Remove a block, such as one on an abandoned fork
+    pub fn remove_block(&mut self, hash: &[u8]) -> SqliteResult<()> {
+        self.db
+            .execute("DELETE FROM chain_store WHERE hash = ?1", [hash])?;
+        Ok(())
+    }
+
+    /// Merkle root over all stored block hashes in height order
+    ///
+    /// Two stores holding the same blocks have the same root; an empty
+    /// store has an all-zero root.
+    pub fn get_store_root(&self) -> SqliteResult<[u8; 32]> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT hash FROM chain_store ORDER BY height, hash")?;
+        let mut level = stmt
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .map(|hash| hash.map(|hash| *blake3::hash(&hash).as_bytes()))
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        if level.is_empty() {
+            return Ok([0u8; 32]);
+        }
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => {
+                        let mut hasher = blake3::Hasher::new();
+                        hasher.update(left);
+                        hasher.update(right);
+                        *hasher.finalize().as_bytes()
+                    }
+                    // Odd node is promoted to the next level
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        Ok(level[0])
+    }
+
     /// Get latest block height from local store
     pub fn get_latest_height(&self) -> SqliteResult<Option<u64>> {
         let result: Option<u64> = self
@@ -368,6 +542,77 @@ mod tests {
         assert_eq!(bunker.get_latest_height().unwrap(), Some(150));
     }
 
+    #[test]
+    fn test_get_blocks_above() {
+        let mut bunker = BunkerMode::new(":memory:").unwrap();
+        for height in [102, 100, 101, 103] {
+            bunker.store_block(create_test_block(height)).unwrap();
+        }
+
+        let heights = |blocks: Vec<StoredBlock>| -> Vec<u64> {
+            blocks.into_iter().map(|block| block.height).collect()
+        };
+        assert_eq!(
+            heights(bunker.get_blocks_above(None, 10).unwrap()),
+            vec![100, 101, 102, 103]
+        );
+        assert_eq!(
+            heights(bunker.get_blocks_above(Some(100), 2).unwrap()),
+            vec![101, 102]
+        );
+        assert!(bunker.get_blocks_above(Some(103), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_blocks_between_and_remove() {
+        let mut bunker = BunkerMode::new(":memory:").unwrap();
+        for height in 100..=104 {
+            bunker.store_block(create_test_block(height)).unwrap();
+        }
+
+        let heights = |blocks: Vec<StoredBlock>| -> Vec<u64> {
+            blocks.into_iter().map(|block| block.height).collect()
+        };
+        assert_eq!(
+            heights(bunker.get_blocks_between(Some(101), 103).unwrap()),
+            vec![102, 103]
+        );
+        assert_eq!(
+            heights(bunker.get_blocks_between(None, 101).unwrap()),
+            vec![100, 101]
+        );
+
+        let hash = create_test_block(102).hash;
+        assert!(bunker.has_block(&hash).unwrap());
+        bunker.remove_block(&hash).unwrap();
+        assert!(!bunker.has_block(&hash).unwrap());
+        assert_eq!(
+            heights(bunker.get_blocks_between(Some(101), 103).unwrap()),
+            vec![103]
+        );
+    }
+
+    #[test]
+    fn test_store_root() {
+        let mut a = BunkerMode::new(":memory:").unwrap();
+        let mut b = BunkerMode::new(":memory:").unwrap();
+        assert_eq!(a.get_store_root().unwrap(), [0u8; 32]);
+
+        // Insertion order does not matter, and sync state is not covered
+        for height in [100, 101, 102] {
+            a.store_block(create_test_block(height)).unwrap();
+        }
+        for height in [102, 100, 101] {
+            let mut block = create_test_block(height);
+            block.synced = true;
+            b.store_block(block).unwrap();
+        }
+        assert_eq!(a.get_store_root().unwrap(), b.get_store_root().unwrap());
+
+        b.store_block(create_test_block(103)).unwrap();
+        assert_ne!(a.get_store_root().unwrap(), b.get_store_root().unwrap());
+    }
+
     #[test]
     fn test_deferred_sync_scenario() {
         let mut bunker = BunkerMode::new(":memory:").unwrap();
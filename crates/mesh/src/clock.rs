@@ -0,0 +1,99 @@
+//! Mesh Clock - Time source for the protocol state machines
+//!
+//! Timestamps, timeouts, rate windows and hop epochs throughout the mesh
+//! are read from here rather than from the system clock directly. Normally
+//! this is the system clock; the simulator installs a `VirtualClock` on its
+//! thread and advances it itself, so a run models minutes of mesh time in
+//! however long it takes to compute, and comes out the same every time.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    static VIRTUAL: Cell<Option<Virtual>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy)]
+struct Virtual {
+    /// Virtual time the clock was installed at, in Unix milliseconds
+    start_ms: u64,
+    /// Real instant standing in for `start_ms`
+    start: Instant,
+    /// Current virtual time in Unix milliseconds
+    now_ms: u64,
+}
+
+/// Current time in Unix milliseconds
+pub(crate) fn current_timestamp() -> u64 {
+    match VIRTUAL.get() {
+        Some(clock) => clock.now_ms,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+    }
+}
+
+/// Current monotonic time
+pub(crate) fn now() -> Instant {
+    match VIRTUAL.get() {
+        Some(clock) => clock.start + Duration::from_millis(clock.now_ms - clock.start_ms),
+        None => Instant::now(),
+    }
+}
+
+/// Virtual time for the mesh on the current thread, until dropped
+pub(crate) struct VirtualClock {
+    // Bound to the thread whose clock it replaced
+    _thread: std::marker::PhantomData<*const ()>,
+}
+
+impl VirtualClock {
+    /// Install a virtual clock reading `start_ms`
+    pub(crate) fn install(start_ms: u64) -> Self {
+        VIRTUAL.set(Some(Virtual {
+            start_ms,
+            start: Instant::now(),
+            now_ms: start_ms,
+        }));
+        Self {
+            _thread: std::marker::PhantomData,
+        }
+    }
+
+    /// Move the clock forward to `now_ms`; it never goes backwards
+    pub(crate) fn advance_to(&self, now_ms: u64) {
+        if let Some(mut clock) = VIRTUAL.get() {
+            clock.now_ms = clock.now_ms.max(now_ms);
+            VIRTUAL.set(Some(clock));
+        }
+    }
+}
+
+impl Drop for VirtualClock {
+    fn drop(&mut self) {
+        VIRTUAL.set(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_clock_replaces_system_clock_until_dropped() {
+        let clock = VirtualClock::install(1_000);
+        let start = now();
+        assert_eq!(current_timestamp(), 1_000);
+
+        clock.advance_to(6_000);
+        assert_eq!(current_timestamp(), 6_000);
+        assert_eq!(now().duration_since(start), Duration::from_secs(5));
+
+        clock.advance_to(2_000);
+        assert_eq!(current_timestamp(), 6_000);
+
+        drop(clock);
+        assert!(current_timestamp() > 1_000_000_000_000);
+    }
+}
@@ -0,0 +1,668 @@
+//! Peer Discovery Service - Finds and admits mesh peers
+//!
+//! Candidate peers come from the seed list and, with the `mdns` feature,
+//! from mDNS announcements on the local LAN. Each candidate is dialed with
+//! a `Hello` frame (retried with exponential backoff) and then runs the
+//! mutual `AttestationManager` handshake. A peer is only handed to the
+//! peer table once the handshake has completed; peers that go quiet are
+//! aged out by the event loop.
+//!
+//! The handshake proves possession of a key, not a right to a node ID, so
+//! a node ID must be bound to the key it presents: either derived from it
+//! (`node_id_for_key`, as identity registration does) or provisioned with
+//! `with_known_peer`. Anything else is refused before attestation starts.
+
+use crate::clock::{self, current_timestamp};
+use crate::error::{MeshError, MeshResult};
+use crate::peer::{node_id_for_key, PeerInfo};
+use crate::transport::FramePayload;
+use aethercore_identity::{
+    Attestation, AttestationFinalize, AttestationManager, AttestationRequest, AttestationResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// mDNS service type mesh nodes announce themselves under
+pub const MDNS_SERVICE_TYPE: &str = "_aethercore-mesh._udp.local.";
+
+/// Identity a node announces to prospective peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeAnnouncement {
+    /// Node ID (the platform identity ID)
+    pub node_id: String,
+    /// Public key of the platform identity
+    pub public_key: Vec<u8>,
+    /// Attestation backing the identity (`tpm`, `secure-enclave`, `software` or `none`)
+    pub attestation: String,
+}
+
+/// First frame exchanged with a candidate peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hello {
+    /// Sender's announcement
+    pub announcement: NodeAnnouncement,
+    /// Whether this answers a `Hello` (replies are not answered again)
+    pub reply: bool,
+}
+
+/// Attestation handshake step carried in a mesh frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AttestationMessage {
+    /// Step 1, sent by the initiator
+    Request(AttestationRequest),
+    /// Step 2, sent by the responder
+    Response(AttestationResponse),
+    /// Step 3, sent by the initiator
+    Finalize(AttestationFinalize),
+}
+
+/// Discovery timing configuration
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+    /// How often dials, peer aging and handshake cleanup run
+    pub tick_interval: Duration,
+    /// Delay before the first redial of an unanswered candidate
+    pub initial_backoff: Duration,
+    /// Upper bound on the redial delay
+    pub max_backoff: Duration,
+    /// Peers not heard from for this long are removed
+    pub peer_timeout: Duration,
+    /// Dial attempts before giving up on a non-seed candidate
+    pub max_attempts: u32,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval: Duration::from_secs(1),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            peer_timeout: Duration::from_secs(30),
+            max_attempts: 5,
+        }
+    }
+}
+
+/// Result of handling a discovery frame
+#[derive(Debug, Default)]
+pub struct DiscoveryOutput {
+    /// Frames to send, with the address to send each to
+    pub outgoing: Vec<(String, FramePayload)>,
+    /// Peer whose attestation just completed, ready for the peer table
+    pub admitted: Option<PeerInfo>,
+}
+
+#[derive(Debug)]
+struct DialTarget {
+    attempts: u32,
+    next_attempt: Instant,
+    /// Seed peers are redialed forever; discovered candidates give up
+    permanent: bool,
+}
+
+/// Seed dialer and attestation gate for new peers
+pub struct PeerDiscovery {
+    announcement: NodeAnnouncement,
+    attestation: AttestationManager,
+    config: DiscoveryConfig,
+    targets: HashMap<String, DialTarget>,
+    /// Provisioned node IDs not derived from their keys
+    known_peers: HashMap<String, Vec<u8>>,
+}
+
+impl PeerDiscovery {
+    /// Create a discovery service attesting with `attestation`
+    ///
+    /// The node ID announced is the attestation manager's identity ID, so it
+    /// must match the node ID of the mesh being driven.
+    pub fn new(attestation: AttestationManager) -> Self {
+        let identity = attestation.identity();
+        let announcement = NodeAnnouncement {
+            node_id: identity.id.clone(),
+            public_key: identity.public_key.clone(),
+            attestation: attestation_kind(&identity.attestation).to_string(),
+        };
+        Self {
+            announcement,
+            attestation,
+            config: DiscoveryConfig::default(),
+            targets: HashMap::new(),
+            known_peers: HashMap::new(),
+        }
+    }
+
+    /// Accept `node_id` from a peer holding `public_key`, for provisioned
+    /// identities whose node ID is not derived from their key
+    pub fn with_known_peer(mut self, node_id: impl Into<String>, public_key: Vec<u8>) -> Self {
+        self.known_peers.insert(node_id.into(), public_key);
+        self
+    }
+
+    /// Configure discovery timing
+    pub fn with_config(mut self, config: DiscoveryConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Timing configuration
+    pub fn config(&self) -> &DiscoveryConfig {
+        &self.config
+    }
+
+    /// Announcement sent to candidate peers
+    pub fn announcement(&self) -> &NodeAnnouncement {
+        &self.announcement
+    }
+
+    /// Dial `address` until it answers, retrying indefinitely
+    pub fn add_seed(&mut self, address: impl Into<String>) {
+        let target = self.target(address.into());
+        target.permanent = true;
+    }
+
+    /// Dial `address`, giving up after `max_attempts` unanswered dials
+    pub fn add_candidate(&mut self, address: impl Into<String>) {
+        self.target(address.into());
+    }
+
+    fn target(&mut self, address: String) -> &mut DialTarget {
+        self.targets.entry(address).or_insert_with(|| DialTarget {
+            attempts: 0,
+            next_attempt: clock::now(),
+            permanent: false,
+        })
+    }
+
+    /// Hello frames for candidates whose next dial is due
+    ///
+    /// Addresses in `connected` already belong to admitted peers and are
+    /// skipped; their backoff is reset so they are redialed promptly if the
+    /// peer is later lost.
+    pub fn due_dials(
+        &mut self,
+        now: Instant,
+        connected: &HashSet<String>,
+    ) -> Vec<(String, FramePayload)> {
+        let max_attempts = self.config.max_attempts;
+        self.targets
+            .retain(|_, target| target.permanent || target.attempts < max_attempts);
+
+        let hello = self.hello(false);
+        let mut dials = Vec::new();
+        for (address, target) in &mut self.targets {
+            if connected.contains(address) {
+                target.attempts = 0;
+                target.next_attempt = now;
+                continue;
+            }
+            if target.next_attempt > now {
+                continue;
+            }
+            target.attempts += 1;
+            target.next_attempt = now + backoff(&self.config, target.attempts);
+            dials.push((address.clone(), hello.clone()));
+        }
+        dials
+    }
+
+    /// Handle a `Hello` received from `address`
+    ///
+    /// `known` says whether the sender is already in the peer table. The
+    /// node with the lower node ID initiates the attestation handshake, so
+    /// exactly one side starts it when both dial each other.
+    pub fn handle_hello(&mut self, address: &str, hello: Hello, known: bool) -> DiscoveryOutput {
+        let node_id = hello.announcement.node_id;
+        if node_id == self.announcement.node_id {
+            return DiscoveryOutput::default();
+        }
+        if let Err(e) = self.check_binding(&node_id, &hello.announcement.public_key) {
+            debug!("Ignoring Hello from {}: {}", address, e);
+            return DiscoveryOutput::default();
+        }
+        if let Some(target) = self.targets.get_mut(address) {
+            target.attempts = 0;
+        }
+
+        let mut output = DiscoveryOutput::default();
+        if !hello.reply {
+            output
+                .outgoing
+                .push((address.to_string(), self.hello(true)));
+        }
+
+        // An unsolicited Hello means the sender does not have us as a peer
+        // (it only dials addresses it is not connected to), so re-attest
+        if self.announcement.node_id < node_id && (!known || !hello.reply) {
+            match self.attestation.initiate_handshake(&node_id) {
+                Ok(request) => output.outgoing.push((
+                    address.to_string(),
+                    FramePayload::Attestation(Box::new(AttestationMessage::Request(request))),
+                )),
+                Err(e) => debug!("Not attesting {}: {}", node_id, e),
+            }
+        }
+        output
+    }
+
+    /// Handle an attestation step sent by node `source` from `address`
+    pub fn handle_attestation(
+        &mut self,
+        address: &str,
+        source: &str,
+        message: AttestationMessage,
+    ) -> MeshResult<DiscoveryOutput> {
+        let mut output = DiscoveryOutput::default();
+        match message {
+            AttestationMessage::Request(request) => {
+                check_identity(source, &request.identity.id)?;
+                self.check_binding(source, &request.identity.public_key)?;
+                let response = self
+                    .attestation
+                    .handle_request(request)
+                    .map_err(attestation_error)?;
+                output.outgoing.push((
+                    address.to_string(),
+                    FramePayload::Attestation(Box::new(AttestationMessage::Response(response))),
+                ));
+            }
+            AttestationMessage::Response(response) => {
+                check_identity(source, &response.identity.id)?;
+                self.check_binding(source, &response.identity.public_key)?;
+                let public_key = response.identity.public_key.clone();
+                let finalize = self
+                    .attestation
+                    .handle_response(response)
+                    .map_err(attestation_error)?;
+                output.outgoing.push((
+                    address.to_string(),
+                    FramePayload::Attestation(Box::new(AttestationMessage::Finalize(finalize))),
+                ));
+                let trust_score = self.attestation.get_trust_score(source).ok_or_else(|| {
+                    MeshError::InvalidState(format!("Attestation of {} incomplete", source))
+                })?;
+                output.admitted = Some(attested_peer(source, address, public_key, trust_score));
+            }
+            AttestationMessage::Finalize(finalize) => {
+                let result = self
+                    .attestation
+                    .handle_finalize(source, finalize)
+                    .map_err(attestation_error)?;
+                let identity = result.peer_identity.ok_or_else(|| {
+                    MeshError::InvalidState(format!("Attestation of {} has no identity", source))
+                })?;
+                check_identity(source, &identity.id)?;
+                self.check_binding(source, &identity.public_key)?;
+                output.admitted = Some(attested_peer(
+                    source,
+                    address,
+                    identity.public_key,
+                    result.trust_score,
+                ));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Expire stale nonces and time out stuck handshakes
+    pub fn cleanup(&mut self) {
+        self.attestation.cleanup();
+    }
+
+    /// `node_id` must be derived from `public_key`, or provisioned with it
+    fn check_binding(&self, node_id: &str, public_key: &[u8]) -> MeshResult<()> {
+        let bound = match self.known_peers.get(node_id) {
+            Some(known) => known.as_slice() == public_key,
+            None => node_id_for_key(public_key) == node_id,
+        };
+        if !bound {
+            return Err(MeshError::Security(format!(
+                "Node ID {} is not bound to the key it presented",
+                node_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn hello(&self, reply: bool) -> FramePayload {
+        FramePayload::Hello(Hello {
+            announcement: self.announcement.clone(),
+            reply,
+        })
+    }
+}
+
+/// Redial delay after `attempts` unanswered dials
+fn backoff(config: &DiscoveryConfig, attempts: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+    config
+        .initial_backoff
+        .saturating_mul(factor)
+        .min(config.max_backoff)
+}
+
+fn attestation_kind(attestation: &Attestation) -> &'static str {
+    match attestation {
+        Attestation::Tpm { .. } => "tpm",
+        Attestation::SecureEnclave { .. } => "secure-enclave",
+        Attestation::Software { .. } => "software",
+        Attestation::None => "none",
+    }
+}
+
+/// Handshake identities must match the node ID the frame claims to be from
+fn check_identity(source: &str, identity_id: &str) -> MeshResult<()> {
+    if source != identity_id {
+        return Err(MeshError::Security(format!(
+            "Frame from {} carries identity {}",
+            source, identity_id
+        )));
+    }
+    Ok(())
+}
+
+fn attestation_error(e: aethercore_identity::Error) -> MeshError {
+    MeshError::Security(format!("Attestation failed: {}", e))
+}
+
+fn attested_peer(node_id: &str, address: &str, public_key: Vec<u8>, trust_score: f64) -> PeerInfo {
+    PeerInfo {
+        node_id: node_id.to_string(),
+        trust_score,
+        latency_ms: 0,
+        last_seen: current_timestamp(),
+        address: address.to_string(),
+        public_key,
+        attestation_verified: true,
+    }
+}
+
+/// mDNS announcer and browser for LAN peers
+#[cfg(feature = "mdns")]
+pub struct MdnsDiscovery {
+    daemon: mdns_sd::ServiceDaemon,
+    fullname: String,
+    node_id: String,
+    events: mdns_sd::Receiver<mdns_sd::ServiceEvent>,
+}
+
+#[cfg(feature = "mdns")]
+impl MdnsDiscovery {
+    /// Announce `announcement` on `port` and browse for other mesh nodes
+    pub fn start(announcement: &NodeAnnouncement, port: u16) -> MeshResult<Self> {
+        let daemon = mdns_sd::ServiceDaemon::new().map_err(mdns_error)?;
+
+        // DNS labels are at most 63 bytes; the full node ID travels in TXT
+        let instance: String = announcement.node_id.chars().take(63).collect();
+        let public_key = hex::encode(&announcement.public_key);
+        let properties = [
+            ("node_id", announcement.node_id.as_str()),
+            ("public_key", public_key.as_str()),
+            ("attestation", announcement.attestation.as_str()),
+        ];
+        let service = mdns_sd::ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            &instance,
+            &format!("{}.local.", instance),
+            "",
+            port,
+            &properties[..],
+        )
+        .map_err(mdns_error)?
+        .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+
+        daemon.register(service).map_err(mdns_error)?;
+        let events = daemon.browse(MDNS_SERVICE_TYPE).map_err(mdns_error)?;
+
+        Ok(Self {
+            daemon,
+            fullname,
+            node_id: announcement.node_id.clone(),
+            events,
+        })
+    }
+
+    /// Address of the next mesh node resolved on the LAN
+    ///
+    /// Returns `None` once the mDNS daemon has stopped.
+    pub async fn next_candidate(&self) -> Option<String> {
+        loop {
+            let event = self.events.recv_async().await.ok()?;
+            let mdns_sd::ServiceEvent::ServiceResolved(info) = event else {
+                continue;
+            };
+            if info.get_property_val_str("node_id") == Some(self.node_id.as_str()) {
+                continue;
+            }
+            let addresses = info.get_addresses();
+            let Some(ip) = addresses
+                .iter()
+                .find(|ip| ip.is_ipv4())
+                .or_else(|| addresses.iter().next())
+            else {
+                continue;
+            };
+            return Some(std::net::SocketAddr::new(*ip, info.get_port()).to_string());
+        }
+    }
+}
+
+#[cfg(feature = "mdns")]
+impl Drop for MdnsDiscovery {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+#[cfg(feature = "mdns")]
+fn mdns_error(e: mdns_sd::Error) -> MeshError {
+    MeshError::Config(format!("mDNS: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aethercore_identity::{Certificate, PlatformIdentity};
+    use ed25519_dalek::SigningKey;
+
+    fn signing_key(key_name: &str) -> SigningKey {
+        SigningKey::from_bytes(blake3::hash(key_name.as_bytes()).as_bytes())
+    }
+
+    fn public_key(key_name: &str) -> Vec<u8> {
+        signing_key(key_name).verifying_key().to_bytes().to_vec()
+    }
+
+    fn manager(id: &str) -> AttestationManager {
+        manager_for(id, id)
+    }
+
+    /// Manager for node `id` holding the key named `key_name`
+    fn manager_for(id: &str, key_name: &str) -> AttestationManager {
+        let signing_key = signing_key(key_name);
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "private_key_hex".to_string(),
+            hex::encode(signing_key.to_bytes()),
+        );
+        metadata.insert("key_type".to_string(), "ed25519".to_string());
+        let identity = PlatformIdentity {
+            id: id.to_string(),
+            public_key: signing_key.verifying_key().to_bytes().to_vec(),
+            attestation: Attestation::Software {
+                certificate: vec![1, 2, 3],
+            },
+            created_at: current_timestamp(),
+            metadata,
+        };
+        let cert = Certificate {
+            serial: "1".to_string(),
+            subject: id.to_string(),
+            issuer: "test-ca".to_string(),
+            public_key: identity.public_key.clone(),
+            not_before: 0,
+            not_after: u64::MAX,
+            signature: vec![4, 5, 6],
+            extensions: HashMap::new(),
+            x509_der: None,
+        };
+        AttestationManager::new(identity, vec![cert])
+    }
+
+    /// Discovery for `id` that accepts the provisioned node `known`
+    fn discovery(id: &str, known: &str) -> PeerDiscovery {
+        PeerDiscovery::new(manager(id)).with_known_peer(known, public_key(known))
+    }
+
+    fn hello_of(discovery: &PeerDiscovery, reply: bool) -> Hello {
+        Hello {
+            announcement: discovery.announcement().clone(),
+            reply,
+        }
+    }
+
+    fn attestation(payload: &FramePayload) -> AttestationMessage {
+        match payload {
+            FramePayload::Attestation(message) => (**message).clone(),
+            other => panic!("Expected attestation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let config = DiscoveryConfig {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            ..Default::default()
+        };
+        assert_eq!(backoff(&config, 1), Duration::from_secs(1));
+        assert_eq!(backoff(&config, 2), Duration::from_secs(2));
+        assert_eq!(backoff(&config, 3), Duration::from_secs(4));
+        assert_eq!(backoff(&config, 4), Duration::from_secs(5));
+        assert_eq!(backoff(&config, 100), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_due_dials_back_off_and_give_up() {
+        let mut discovery = PeerDiscovery::new(manager("node-a")).with_config(DiscoveryConfig {
+            max_attempts: 2,
+            ..Default::default()
+        });
+        discovery.add_seed("seed");
+        discovery.add_candidate("lan");
+        let connected = HashSet::new();
+        let start = Instant::now();
+
+        assert_eq!(discovery.due_dials(start, &connected).len(), 2);
+        // Nothing is due again until the backoff elapses
+        assert!(discovery.due_dials(start, &connected).is_empty());
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(discovery.due_dials(later, &connected).len(), 2);
+
+        // The candidate has used up its attempts; the seed keeps being dialed
+        let much_later = start + Duration::from_secs(600);
+        let dials = discovery.due_dials(much_later, &connected);
+        assert_eq!(dials.len(), 1);
+        assert_eq!(dials[0].0, "seed");
+
+        // Connected seeds are not dialed
+        let connected: HashSet<String> = ["seed".to_string()].into();
+        assert!(discovery
+            .due_dials(much_later + Duration::from_secs(600), &connected)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_hello_handshake_admits_both_sides() {
+        let mut a = discovery("node-a", "node-b");
+        let mut b = discovery("node-b", "node-a");
+
+        // b dials a; a answers and, as the lower node ID, starts attestation
+        let output = a.handle_hello("addr-b", hello_of(&b, false), false);
+        assert_eq!(output.outgoing.len(), 2);
+        assert!(matches!(
+            output.outgoing[0].1,
+            FramePayload::Hello(Hello { reply: true, .. })
+        ));
+        let request = attestation(&output.outgoing[1].1);
+
+        // The reply alone does not make b start a second handshake
+        let output = b.handle_hello("addr-a", hello_of(&a, true), false);
+        assert!(output.outgoing.is_empty());
+
+        let output = b.handle_attestation("addr-a", "node-a", request).unwrap();
+        assert!(output.admitted.is_none());
+        let response = attestation(&output.outgoing[0].1);
+
+        let output = a.handle_attestation("addr-b", "node-b", response).unwrap();
+        let admitted = output.admitted.unwrap();
+        assert_eq!(admitted.node_id, "node-b");
+        assert_eq!(admitted.address, "addr-b");
+        assert_eq!(admitted.public_key, b.announcement().public_key);
+        assert!(admitted.attestation_verified);
+        let finalize = attestation(&output.outgoing[0].1);
+
+        let output = b.handle_attestation("addr-a", "node-a", finalize).unwrap();
+        let admitted = output.admitted.unwrap();
+        assert_eq!(admitted.node_id, "node-a");
+        assert_eq!(admitted.trust_score, 0.7);
+    }
+
+    #[test]
+    fn test_attestation_identity_must_match_source() {
+        let mut a = discovery("node-a", "node-b");
+        let mut b = discovery("node-b", "node-a");
+
+        let output = a.handle_hello("addr-b", hello_of(&b, false), false);
+        let request = attestation(&output.outgoing[1].1);
+
+        let result = b.handle_attestation("addr-a", "node-c", request);
+        assert!(matches!(result, Err(MeshError::Security(_))));
+    }
+
+    #[test]
+    fn test_node_id_must_be_bound_to_key() {
+        // Node IDs derived from the key are accepted without provisioning
+        let derived = node_id_for_key(&public_key("node-c"));
+        let c = PeerDiscovery::new(manager_for(&derived, "node-c"));
+        let mut a = PeerDiscovery::new(manager("node-a"));
+        let output = a.handle_hello("addr-c", hello_of(&c, false), false);
+        assert!(!output.outgoing.is_empty());
+
+        // Other node IDs are ignored unless provisioned
+        let b = PeerDiscovery::new(manager("node-b"));
+        let output = a.handle_hello("addr-b", hello_of(&b, false), false);
+        assert!(output.outgoing.is_empty());
+
+        // A provisioned node ID presented with another key is refused
+        let mut a = discovery("node-a", "node-b");
+        let b = discovery("node-b", "node-a");
+        let mut impostor = PeerDiscovery::new(manager_for("node-b", "mallory"))
+            .with_known_peer("node-a", public_key("node-a"));
+        assert!(a
+            .handle_hello("addr-m", hello_of(&impostor, false), false)
+            .outgoing
+            .is_empty());
+
+        let output = a.handle_hello("addr-b", hello_of(&b, false), false);
+        let request = attestation(&output.outgoing[1].1);
+        let output = impostor
+            .handle_attestation("addr-a", "node-a", request)
+            .unwrap();
+        let response = attestation(&output.outgoing[0].1);
+        let result = a.handle_attestation("addr-m", "node-b", response);
+        assert!(matches!(result, Err(MeshError::Security(_))));
+    }
+
+    #[test]
+    fn test_own_hello_ignored() {
+        let mut a = PeerDiscovery::new(manager("node-a"));
+        let own = hello_of(&a, false);
+        let output = a.handle_hello("addr-a", own, false);
+        assert!(output.outgoing.is_empty());
+    }
+}
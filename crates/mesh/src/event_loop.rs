@@ -0,0 +1,772 @@
+//! Mesh Event Loop - Drives a TacticalMesh over a transport
+//!
+//! Receives frames from a `MeshTransport`, dispatches gossip and route
+//! advertisements to the `TacticalMesh`, relays accepted gossip to a few
+//! other peers and periodically advertises the local routing table. Peer
+//! traffic is only exchanged sealed in each link's session, which the loop
+//! establishes and rotates as links come up and age. After a spell of
+//! isolation it also reconciles the bunker store with returning peers.
+//! Divergent chains reported by gossip are resolved with the neighbor
+//! concerned, and neighbors caught equivocating are slashed. Each hop
+//! epoch it tunes to the agreed hopping pattern, proposing a new one to
+//! trusted neighbors when jamming is detected. With
+//! a `PeerDiscovery` attached it also dials candidate peers, admits them
+//! after attestation and ages out peers that go quiet.
+//! Outcomes the application must act on are reported as `MeshEvent`s.
+
+use crate::clock;
+#[cfg(feature = "mdns")]
+use crate::discovery::MdnsDiscovery;
+use crate::discovery::{DiscoveryOutput, PeerDiscovery};
+use crate::error::{MeshError, MeshResult};
+use crate::fork::ForkOutcome;
+use crate::gossip::{GossipMessage, GossipResult};
+use crate::tactical::TacticalMesh;
+use crate::transport::{FramePayload, MeshFrame, MeshTransport};
+use aethercore_core::SlashingEvent;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex, MutexGuard};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+/// Default interval between route advertisements
+pub const DEFAULT_ADVERTISEMENT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Events buffered for the application before new ones are dropped
+const EVENT_QUEUE_SIZE: usize = 1024;
+
+/// Outcome reported to the application
+#[derive(Debug)]
+pub enum MeshEvent {
+    /// A gossip message was processed
+    Gossip {
+        /// Peer the message was received from
+        from: String,
+        /// Result of processing the message
+        result: GossipResult,
+    },
+    /// Routes were learned from a neighbor's advertisement
+    RoutesUpdated {
+        /// Advertising neighbor
+        from: String,
+        /// Number of routes added or updated
+        changed: usize,
+    },
+    /// A peer completed attestation and was added to the peer table
+    PeerDiscovered {
+        /// Node ID of the new peer
+        node_id: String,
+        /// Trust score from attestation
+        trust_score: f64,
+    },
+    /// A peer was not heard from within the peer timeout and was removed
+    PeerLost {
+        /// Node ID of the removed peer
+        node_id: String,
+    },
+    /// A bunker sync session with a peer finished in both directions
+    SyncCompleted {
+        /// Node ID of the peer
+        node_id: String,
+    },
+    /// A peer's encrypted link failed and the peer was removed
+    LinkFailed {
+        /// Node ID of the removed peer
+        node_id: String,
+        /// Why the link failed
+        reason: String,
+    },
+    /// Fork resolution with a neighbor finished
+    ForkResolved {
+        /// Node ID of the neighbor
+        node_id: String,
+        /// Branch kept, or why resolution was abandoned
+        outcome: ForkOutcome,
+    },
+    /// A neighbor was caught equivocating, revoked and removed
+    PeerSlashed {
+        /// Signed slashing event, naming the revoked node
+        event: SlashingEvent,
+    },
+    /// A new hopping pattern took effect
+    HopPatternActivated {
+        /// Node that issued the pattern
+        issuer: String,
+        /// Generation of the pattern
+        generation: u64,
+    },
+    /// No trusted neighbor was heard within the sync timeout, so the node
+    /// fell back to the rendezvous channel, or one was heard again
+    HopSyncChanged {
+        /// Whether the node is back in sync
+        synced: bool,
+    },
+}
+
+/// Event loop connecting a `TacticalMesh` to a transport
+pub struct MeshEventLoop<T: MeshTransport> {
+    mesh: Arc<Mutex<TacticalMesh>>,
+    transport: Arc<T>,
+    advertisement_interval: Duration,
+    discovery: Option<PeerDiscovery>,
+    #[cfg(feature = "mdns")]
+    mdns: Option<MdnsDiscovery>,
+}
+
+impl<T: MeshTransport> MeshEventLoop<T> {
+    /// Create an event loop for `mesh` over `transport`
+    pub fn new(mesh: TacticalMesh, transport: T) -> Self {
+        Self {
+            mesh: Arc::new(Mutex::new(mesh)),
+            transport: Arc::new(transport),
+            advertisement_interval: DEFAULT_ADVERTISEMENT_INTERVAL,
+            discovery: None,
+            #[cfg(feature = "mdns")]
+            mdns: None,
+        }
+    }
+
+    /// Configure how often routes are advertised to neighbors
+    pub fn with_advertisement_interval(mut self, interval: Duration) -> Self {
+        self.advertisement_interval = interval;
+        self
+    }
+
+    /// Discover peers with `discovery`, dialing the mesh's seed peers
+    ///
+    /// Only attested peers are admitted, and any peer not heard from within
+    /// the discovery peer timeout is removed.
+    pub fn with_discovery(mut self, discovery: PeerDiscovery) -> Self {
+        self.discovery = Some(discovery);
+        self
+    }
+
+    /// Also dial mesh nodes found on the LAN by `mdns`
+    ///
+    /// Has no effect unless discovery is enabled.
+    #[cfg(feature = "mdns")]
+    pub fn with_mdns(mut self, mdns: MdnsDiscovery) -> Self {
+        self.mdns = Some(mdns);
+        self
+    }
+
+    /// Run the loop on the tokio runtime
+    pub fn spawn(self) -> MeshHandle<T> {
+        let (events_tx, events) = mpsc::channel(EVENT_QUEUE_SIZE);
+        let (shutdown, shutdown_rx) = oneshot::channel();
+        let mesh = self.mesh.clone();
+        let transport = self.transport.clone();
+        let task = tokio::spawn(self.run(events_tx, shutdown_rx));
+
+        MeshHandle {
+            mesh,
+            transport,
+            events,
+            shutdown: Some(shutdown),
+            task,
+        }
+    }
+
+    async fn run(mut self, events: mpsc::Sender<MeshEvent>, mut shutdown: oneshot::Receiver<()>) {
+        let mut advertisement = tokio::time::interval(self.advertisement_interval);
+        let tick_interval = self
+            .discovery
+            .as_ref()
+            .map_or(Duration::from_secs(1), |d| d.config().tick_interval);
+        let mut discovery_tick = tokio::time::interval(tick_interval);
+        let mesh = self.mesh.lock().await;
+        let mut link_tick = tokio::time::interval(mesh.link_config().maintenance_interval);
+        let mut sync_tick = tokio::time::interval(mesh.sync_config().tick_interval);
+        let mut fork_tick = tokio::time::interval(mesh.fork_config().tick_interval);
+        let mut hop_tick = tokio::time::interval(mesh.hop_sync_config().epoch_duration);
+        drop(mesh);
+        // A loop that fell behind should not flood its neighbors catching up
+        advertisement.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        discovery_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        link_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        sync_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        fork_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        hop_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        if let Some(discovery) = &mut self.discovery {
+            for seed in self.mesh.lock().await.seed_peers() {
+                discovery.add_seed(seed.clone());
+            }
+        }
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = advertisement.tick() => {
+                    if let Err(e) = advertise_routes(&self.mesh, self.transport.as_ref()).await {
+                        warn!("Route advertisement failed: {}", e);
+                    }
+                }
+                _ = link_tick.tick() => {
+                    self.maintain_links(&events).await;
+                }
+                _ = sync_tick.tick() => {
+                    let mut mesh = self.mesh.lock().await;
+                    match mesh.sync_tick() {
+                        Ok(messages) => {
+                            let messages = messages
+                                .into_iter()
+                                .map(|(node_id, message)| (node_id, FramePayload::Sync(message)))
+                                .collect();
+                            send_sealed(mesh, self.transport.as_ref(), messages).await;
+                        }
+                        Err(e) => warn!("Bunker sync failed: {}", e),
+                    }
+                }
+                _ = fork_tick.tick() => {
+                    let mut mesh = self.mesh.lock().await;
+                    let tick = mesh.fork_tick();
+                    let requests = tick
+                        .requests
+                        .into_iter()
+                        .map(|(node_id, request)| (node_id, FramePayload::Fork(request)))
+                        .collect();
+                    send_sealed(mesh, self.transport.as_ref(), requests).await;
+                    for (node_id, reason) in tick.abandoned {
+                        let outcome = ForkOutcome::Abandoned(reason);
+                        emit(&events, MeshEvent::ForkResolved { node_id, outcome });
+                    }
+                }
+                _ = hop_tick.tick() => {
+                    let mut mesh = self.mesh.lock().await;
+                    let epoch = mesh.current_epoch();
+                    let tick = match mesh.hop_tick(epoch) {
+                        Ok(tick) => tick,
+                        Err(e) => {
+                            warn!("Hop sync failed: {}", e);
+                            continue;
+                        }
+                    };
+                    let messages = tick
+                        .messages
+                        .into_iter()
+                        .map(|(node_id, message)| (node_id, FramePayload::HopSync(message)))
+                        .collect();
+                    send_sealed(mesh, self.transport.as_ref(), messages).await;
+                    if let Some(plan) = tick.activated {
+                        let (issuer, generation) = (plan.issuer, plan.generation);
+                        emit(&events, MeshEvent::HopPatternActivated { issuer, generation });
+                    }
+                    if let Some(synced) = tick.synced {
+                        emit(&events, MeshEvent::HopSyncChanged { synced });
+                    }
+                }
+                _ = discovery_tick.tick(), if self.discovery.is_some() => {
+                    self.run_discovery(&events).await;
+                }
+                candidate = self.next_lan_candidate() => match candidate {
+                    Some(address) => {
+                        if let Some(discovery) = &mut self.discovery {
+                            discovery.add_candidate(address);
+                        }
+                    }
+                    None => self.stop_lan_discovery(),
+                },
+                received = self.transport.recv() => match received {
+                    Ok((address, bytes)) => {
+                        if let Some(event) = self.handle_frame(&address, &bytes).await {
+                            emit(&events, event);
+                        }
+                    }
+                    // Socket errors are transient; anything else ends the link
+                    Err(crate::error::MeshError::Io(e)) => {
+                        warn!("Transport receive failed: {}", e);
+                    }
+                    Err(e) => {
+                        warn!("Transport closed: {}", e);
+                        break;
+                    }
+                },
+            }
+        }
+        debug!("Mesh event loop stopped");
+    }
+
+    /// Dial due candidates, age out quiet peers and expire handshakes
+    async fn run_discovery(&mut self, events: &mpsc::Sender<MeshEvent>) {
+        let Some(discovery) = &mut self.discovery else {
+            return;
+        };
+
+        let mut mesh = self.mesh.lock().await;
+        let lost = mesh.age_out_peers(discovery.config().peer_timeout.as_millis() as u64);
+        let connected: HashSet<String> = mesh
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.address.clone())
+            .collect();
+        let node_id = mesh.node_id().to_string();
+        drop(mesh);
+
+        discovery.cleanup();
+        let dials = discovery.due_dials(clock::now(), &connected);
+        for (address, payload) in dials {
+            send_frame(self.transport.as_ref(), &node_id, &address, payload).await;
+        }
+        if lost.is_empty() {
+            return;
+        }
+        // Tell the remaining neighbors right away rather than at the next interval
+        if let Err(e) = advertise_routes(&self.mesh, self.transport.as_ref()).await {
+            warn!("Route advertisement failed: {}", e);
+        }
+        for node_id in lost {
+            emit(events, MeshEvent::PeerLost { node_id });
+        }
+    }
+
+    /// Send due key exchanges and report peers whose link failed
+    async fn maintain_links(&self, events: &mpsc::Sender<MeshEvent>) {
+        let mut mesh = self.mesh.lock().await;
+        let maintenance = mesh.maintain_links();
+        let handshakes: Vec<(String, FramePayload)> = maintenance
+            .handshakes
+            .into_iter()
+            .filter_map(|(node_id, handshake)| {
+                let address = mesh.get_peer(&node_id)?.address.clone();
+                Some((address, FramePayload::LinkHandshake(handshake)))
+            })
+            .collect();
+        let node_id = mesh.node_id().to_string();
+        drop(mesh);
+
+        for (address, payload) in handshakes {
+            send_frame(self.transport.as_ref(), &node_id, &address, payload).await;
+        }
+        if maintenance.failed.is_empty() {
+            return;
+        }
+        if let Err(e) = advertise_routes(&self.mesh, self.transport.as_ref()).await {
+            warn!("Route advertisement failed: {}", e);
+        }
+        for (node_id, reason) in maintenance.failed {
+            emit(events, MeshEvent::LinkFailed { node_id, reason });
+        }
+    }
+
+    /// Next mesh node resolved via mDNS; never completes without mDNS
+    async fn next_lan_candidate(&self) -> Option<String> {
+        #[cfg(feature = "mdns")]
+        if let Some(mdns) = &self.mdns {
+            return mdns.next_candidate().await;
+        }
+        std::future::pending().await
+    }
+
+    fn stop_lan_discovery(&mut self) {
+        warn!("mDNS discovery stopped");
+        #[cfg(feature = "mdns")]
+        {
+            self.mdns = None;
+        }
+    }
+
+    async fn handle_frame(&mut self, address: &str, bytes: &[u8]) -> Option<MeshEvent> {
+        let frame = match MeshFrame::decode(bytes) {
+            Ok(frame) => frame,
+            Err(e) => {
+                debug!("Dropping undecodable frame from {}: {}", address, e);
+                return None;
+            }
+        };
+
+        // Peers are only marked as heard from once a frame authenticates
+        let mut mesh = self.mesh.lock().await;
+        let known = mesh.get_peer(&frame.source).is_some();
+
+        match frame.payload {
+            // Discovery frames are how unknown nodes become peers
+            FramePayload::Hello(hello) => {
+                drop(mesh);
+                if hello.announcement.node_id != frame.source {
+                    debug!("Dropping Hello from {} naming another node", frame.source);
+                    return None;
+                }
+                let discovery = self.discovery.as_mut()?;
+                let output = discovery.handle_hello(address, hello, known);
+                self.apply_discovery(&frame.source, Ok(output)).await
+            }
+            FramePayload::Attestation(message) => {
+                drop(mesh);
+                let discovery = self.discovery.as_mut()?;
+                let output = discovery.handle_attestation(address, &frame.source, *message);
+                self.apply_discovery(&frame.source, output).await
+            }
+            _ if !known => {
+                debug!(
+                    "Dropping frame from unknown node {} at {}",
+                    frame.source, address
+                );
+                None
+            }
+            FramePayload::LinkHandshake(handshake) => {
+                let reply = match mesh.process_link_handshake(&frame.source, handshake) {
+                    Ok(reply) => {
+                        mesh.touch_peer(&frame.source);
+                        reply?
+                    }
+                    Err(e) => {
+                        warn!("Rejected key exchange: {}", e);
+                        return None;
+                    }
+                };
+                let node_id = mesh.node_id().to_string();
+                drop(mesh);
+                let payload = FramePayload::LinkHandshake(reply);
+                send_frame(self.transport.as_ref(), &node_id, address, payload).await;
+                None
+            }
+            FramePayload::Sealed(sealed) => match mesh.open_payload(&frame.source, &sealed) {
+                Ok(payload) => {
+                    mesh.touch_peer(&frame.source);
+                    let epoch = mesh.current_epoch();
+                    mesh.record_hop_contact(epoch);
+                    self.handle_payload(mesh, frame.source, payload).await
+                }
+                Err(e) => {
+                    debug!("Dropping sealed frame: {}", e);
+                    None
+                }
+            },
+            FramePayload::Gossip(_)
+            | FramePayload::RouteAdvertisement(_)
+            | FramePayload::Sync(_)
+            | FramePayload::Fork(_)
+            | FramePayload::HopSync(_) => {
+                debug!("Dropping unencrypted frame from {}", frame.source);
+                None
+            }
+        }
+    }
+
+    /// Handle a payload opened from a peer's sealed frame
+    async fn handle_payload(
+        &self,
+        mut mesh: MutexGuard<'_, TacticalMesh>,
+        source: String,
+        payload: FramePayload,
+    ) -> Option<MeshEvent> {
+        match payload {
+            FramePayload::Gossip(message) => {
+                let result = mesh.process_gossip(&source, message);
+                let messages = match &result {
+                    GossipResult::Accepted {
+                        should_forward: true,
+                        message,
+                    } => mesh
+                        .gossip_targets(&source, message)
+                        .into_iter()
+                        .map(|peer| (peer, FramePayload::Gossip(message.clone())))
+                        .collect(),
+                    GossipResult::Equivocation { first, second } => {
+                        match mesh.slash_equivocation(first, second) {
+                            Ok(event) => {
+                                drop(mesh);
+                                // The slashed node may have been a neighbor
+                                if let Err(e) =
+                                    advertise_routes(&self.mesh, self.transport.as_ref()).await
+                                {
+                                    warn!("Route advertisement failed: {}", e);
+                                }
+                                return Some(MeshEvent::PeerSlashed { event });
+                            }
+                            Err(e) => {
+                                warn!("Not slashing {}: {}", first.source_node, e);
+                                Vec::new()
+                            }
+                        }
+                    }
+                    _ => mesh
+                        .begin_fork_resolution(&result)
+                        .map(|(node_id, request)| (node_id, FramePayload::Fork(request)))
+                        .into_iter()
+                        .collect(),
+                };
+                send_sealed(mesh, self.transport.as_ref(), messages).await;
+                Some(MeshEvent::Gossip {
+                    from: source,
+                    result,
+                })
+            }
+            FramePayload::RouteAdvertisement(advertisement) => {
+                let changed = match mesh.process_route_advertisement(&source, advertisement) {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        warn!("Rejected route advertisement: {}", e);
+                        return None;
+                    }
+                };
+                (changed > 0).then_some(MeshEvent::RoutesUpdated {
+                    from: source,
+                    changed,
+                })
+            }
+            FramePayload::Sync(message) => {
+                let output = match mesh.process_sync(&source, message) {
+                    Ok(output) => output,
+                    Err(e) => {
+                        warn!("Bunker sync with {} failed: {}", source, e);
+                        return None;
+                    }
+                };
+                let replies = output
+                    .replies
+                    .into_iter()
+                    .map(|reply| (source.clone(), FramePayload::Sync(reply)))
+                    .collect();
+                send_sealed(mesh, self.transport.as_ref(), replies).await;
+                output
+                    .completed
+                    .then_some(MeshEvent::SyncCompleted { node_id: source })
+            }
+            FramePayload::Fork(message) => {
+                let output = match mesh.process_fork(&source, message) {
+                    Ok(output) => output,
+                    Err(e) => {
+                        warn!("Fork resolution with {} failed: {}", source, e);
+                        return None;
+                    }
+                };
+                let replies = output
+                    .replies
+                    .into_iter()
+                    .map(|reply| (source.clone(), FramePayload::Fork(reply)))
+                    .collect();
+                send_sealed(mesh, self.transport.as_ref(), replies).await;
+                output.outcome.map(|outcome| MeshEvent::ForkResolved {
+                    node_id: source,
+                    outcome,
+                })
+            }
+            FramePayload::HopSync(message) => {
+                let epoch = mesh.current_epoch();
+                let output = match mesh.process_hop_sync(&source, message, epoch) {
+                    Ok(output) => output,
+                    Err(e) => {
+                        warn!("Hop sync with {} failed: {}", source, e);
+                        return None;
+                    }
+                };
+                let messages = output
+                    .messages
+                    .into_iter()
+                    .map(|(node_id, message)| (node_id, FramePayload::HopSync(message)))
+                    .collect();
+                send_sealed(mesh, self.transport.as_ref(), messages).await;
+                output.activated.map(|plan| MeshEvent::HopPatternActivated {
+                    issuer: plan.issuer,
+                    generation: plan.generation,
+                })
+            }
+            _ => {
+                debug!("Dropping sealed link-level frame from {}", source);
+                None
+            }
+        }
+    }
+
+    /// Send discovery replies and admit a newly attested peer
+    async fn apply_discovery(
+        &mut self,
+        source: &str,
+        output: MeshResult<DiscoveryOutput>,
+    ) -> Option<MeshEvent> {
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                warn!("Discovery with {} failed: {}", source, e);
+                return None;
+            }
+        };
+
+        let node_id = self.mesh.lock().await.node_id().to_string();
+        for (address, payload) in output.outgoing {
+            send_frame(self.transport.as_ref(), &node_id, &address, payload).await;
+        }
+
+        let peer = output.admitted?;
+        let node_id = peer.node_id.clone();
+        let trust_score = peer.trust_score;
+        if let Err(e) = self.mesh.lock().await.add_peer(peer) {
+            warn!("Attested peer {} rejected: {}", node_id, e);
+            return None;
+        }
+        Some(MeshEvent::PeerDiscovered {
+            node_id,
+            trust_score,
+        })
+    }
+}
+
+/// Handle to a running mesh event loop
+pub struct MeshHandle<T: MeshTransport> {
+    mesh: Arc<Mutex<TacticalMesh>>,
+    transport: Arc<T>,
+    events: mpsc::Receiver<MeshEvent>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl<T: MeshTransport> MeshHandle<T> {
+    /// The mesh driven by the loop
+    pub fn mesh(&self) -> &Arc<Mutex<TacticalMesh>> {
+        &self.mesh
+    }
+
+    /// The loop's transport
+    pub fn transport(&self) -> &Arc<T> {
+        &self.transport
+    }
+
+    /// Update local state and gossip it, signed, to all peers
+    ///
+    /// Returns the number of peers the message was sent to.
+    pub async fn publish_state(
+        &self,
+        merkle_root: Vec<u8>,
+        block_height: u64,
+    ) -> MeshResult<usize> {
+        let mut mesh = self.mesh.lock().await;
+        let message = mesh
+            .update_local_state(merkle_root, block_height)
+            .map_err(MeshError::Security)?;
+        self.gossip_locked(mesh, message).await
+    }
+
+    /// Gossip a message to all peers
+    pub async fn gossip(&self, message: GossipMessage) -> MeshResult<usize> {
+        let mesh = self.mesh.lock().await;
+        self.gossip_locked(mesh, message).await
+    }
+
+    async fn gossip_locked(
+        &self,
+        mesh: MutexGuard<'_, TacticalMesh>,
+        message: GossipMessage,
+    ) -> MeshResult<usize> {
+        let messages = to_peers(&mesh, &[], FramePayload::Gossip(message));
+        Ok(send_sealed(mesh, self.transport.as_ref(), messages).await)
+    }
+
+    /// Advertise routes to all neighbors now, outside the periodic schedule
+    pub async fn advertise_routes(&self) -> MeshResult<usize> {
+        advertise_routes(&self.mesh, self.transport.as_ref()).await
+    }
+
+    /// Wait for the next event, or `None` once the loop has stopped
+    pub async fn next_event(&mut self) -> Option<MeshEvent> {
+        self.events.recv().await
+    }
+
+    /// Stop the loop and wait for it to finish
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl<T: MeshTransport> Drop for MeshHandle<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn advertise_routes<T: MeshTransport>(
+    mesh: &Mutex<TacticalMesh>,
+    transport: &T,
+) -> MeshResult<usize> {
+    // Each neighbor gets its own vector (poison reverse), signed and
+    // sealed separately
+    let mut mesh = mesh.lock().await;
+    let neighbors: Vec<String> = mesh
+        .get_all_peers()
+        .into_iter()
+        .map(|peer| peer.node_id.clone())
+        .filter(|neighbor| mesh.is_link_established(neighbor))
+        .collect();
+    let mut messages = Vec::with_capacity(neighbors.len());
+    for neighbor in neighbors {
+        let advertisement = mesh
+            .route_advertisement_for(&neighbor)
+            .map_err(MeshError::Security)?;
+        messages.push((neighbor, FramePayload::RouteAdvertisement(advertisement)));
+    }
+    Ok(send_sealed(mesh, transport, messages).await)
+}
+
+fn emit(events: &mpsc::Sender<MeshEvent>, event: MeshEvent) {
+    if events.try_send(event).is_err() {
+        debug!("Mesh event queue full or closed; dropping event");
+    }
+}
+
+/// Send a single frame, logging failure; returns whether it was sent
+async fn send_frame<T: MeshTransport>(
+    transport: &T,
+    node_id: &str,
+    address: &str,
+    payload: FramePayload,
+) -> bool {
+    let result = match MeshFrame::new(node_id, payload).encode() {
+        Ok(frame) => transport.send_to(address, &frame).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = &result {
+        warn!("Send to {} failed: {}", address, e);
+    }
+    result.is_ok()
+}
+
+/// `payload` addressed to all peers except those in `exclude`
+fn to_peers(
+    mesh: &TacticalMesh,
+    exclude: &[&str],
+    payload: FramePayload,
+) -> Vec<(String, FramePayload)> {
+    mesh.get_all_peers()
+        .into_iter()
+        .filter(|peer| !exclude.contains(&peer.node_id.as_str()))
+        .map(|peer| (peer.node_id.clone(), payload.clone()))
+        .collect()
+}
+
+/// Seal each payload for its peer and send it, returning how many were sent
+///
+/// Peers without an established session are skipped. Links are
+/// best-effort: a failed send is logged and the rest proceed.
+async fn send_sealed<T: MeshTransport>(
+    mut mesh: MutexGuard<'_, TacticalMesh>,
+    transport: &T,
+    messages: Vec<(String, FramePayload)>,
+) -> usize {
+    let mut frames = Vec::with_capacity(messages.len());
+    for (peer, payload) in messages {
+        let Some(address) = mesh.get_peer(&peer).map(|info| info.address.clone()) else {
+            continue;
+        };
+        match mesh.seal_payload(&peer, &payload) {
+            Ok(sealed) => frames.push((address, FramePayload::Sealed(sealed))),
+            Err(e) => debug!("Not sending to {}: {}", peer, e),
+        }
+    }
+    let node_id = mesh.node_id().to_string();
+    drop(mesh);
+
+    let mut sent = 0;
+    for (address, payload) in frames {
+        if send_frame(transport, &node_id, &address, payload).await {
+            sent += 1;
+        }
+    }
+    sent
+}
@@ -0,0 +1,840 @@
+//! Fork Resolution - Reconciling chains that gossip shows have diverged
+//!
+//! Gossip only carries each node's latest Merkle root and height, so a
+//! `ConflictDetected` or `PeerAhead` result says that a neighbor's chain
+//! differs from ours but not where. `ForkResolver` fetches the neighbor's
+//! blocks one window of heights at a time, walking down from its tip until
+//! it reaches a block we also hold: the common ancestor. Our blocks above
+//! the ancestor that the neighbor does not hold are our side of the fork.
+//!
+//! A gossiped Merkle root is the hash of the sender's highest block, which
+//! commits to its whole chain (see `StoredBlock::chain_hash`). Before
+//! anything is decided, the fetched blocks must link from the ancestor up to
+//! the root the neighbor signed in gossip; a branch that does not is
+//! abandoned without touching the block store.
+//!
+//! If our side is empty and the neighbor is ahead, its blocks are simply
+//! taken. Otherwise the branch to keep is decided by the caller, normally
+//! with `choose_branch`, and if the neighbor's branch wins our side is
+//! removed from the block store and replaced by it.
+
+use crate::bunker::{BunkerMode, StoredBlock};
+use crate::clock;
+use crate::error::MeshResult;
+use crate::gossip::{ConsensusView, GossipResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Fork resolution step exchanged with a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ForkMessage {
+    /// Request for the receiver's blocks in a range of heights
+    SegmentRequest {
+        /// Request identifier, echoed in the answer
+        id: u64,
+        /// Heights above this one are requested (all heights for `None`)
+        after: Option<u64>,
+        /// Highest height requested
+        up_to: u64,
+    },
+    /// Blocks answering a request, lowest height first
+    Segment {
+        /// Identifier of the request answered
+        id: u64,
+        /// Lower bound actually served, which may be above the one requested
+        after: Option<u64>,
+        /// Blocks held in the range served
+        blocks: Vec<StoredBlock>,
+    },
+}
+
+/// Divergence between our state and a neighbor's, as seen in gossip
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForkConflict {
+    /// Neighbor whose chain differs from ours
+    pub peer_node: String,
+    /// Neighbor's Merkle root
+    pub peer_root: Vec<u8>,
+    /// Neighbor's block height
+    pub peer_height: u64,
+    /// Our Merkle root
+    pub local_root: Vec<u8>,
+    /// Our block height
+    pub local_height: u64,
+}
+
+impl ForkConflict {
+    /// The divergence reported by a gossip result, given our local state
+    pub fn from_gossip(result: &GossipResult, local: Option<(&[u8], u64)>) -> Option<Self> {
+        let (local_root, local_height) = local?;
+        match result {
+            GossipResult::ConflictDetected {
+                peer_root,
+                peer_height,
+                peer_node,
+                ..
+            }
+            | GossipResult::PeerAhead {
+                peer_root,
+                peer_height,
+                peer_node,
+            } => Some(Self {
+                peer_node: peer_node.clone(),
+                peer_root: peer_root.clone(),
+                peer_height: *peer_height,
+                local_root: local_root.to_vec(),
+                local_height,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Side of a fork
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    /// The branch we hold
+    Local,
+    /// The neighbor's branch
+    Peer,
+}
+
+/// Choose which side of `conflict` to keep
+///
+/// `view` is the trust-weighted consensus of the peers other than the two
+/// sides. If it holds a majority for either side's state, that side is
+/// kept. Otherwise the higher branch is kept, and between branches of the
+/// same height the one with the lower root, so both sides choose alike.
+pub fn choose_branch(view: Option<&ConsensusView>, conflict: &ForkConflict) -> Branch {
+    if let Some(view) = view.filter(|view| view.has_majority()) {
+        if view.merkle_root == conflict.peer_root && view.block_height == conflict.peer_height {
+            return Branch::Peer;
+        }
+        if view.merkle_root == conflict.local_root && view.block_height == conflict.local_height {
+            return Branch::Local;
+        }
+    }
+    let peer = (conflict.peer_height, std::cmp::Reverse(&conflict.peer_root));
+    let local = (
+        conflict.local_height,
+        std::cmp::Reverse(&conflict.local_root),
+    );
+    if peer > local {
+        Branch::Peer
+    } else {
+        Branch::Local
+    }
+}
+
+/// Pacing and search limits for fork resolution
+#[derive(Debug, Clone)]
+pub struct ForkConfig {
+    /// How often unanswered requests are checked
+    pub tick_interval: Duration,
+    /// Time to wait for a segment before asking again
+    pub retry_interval: Duration,
+    /// Requests sent for one segment before resolution is abandoned
+    pub max_attempts: u32,
+    /// Heights fetched, or served, per segment
+    pub segment_heights: u64,
+    /// Heights below the neighbor's tip searched for a common ancestor
+    pub max_depth: u64,
+}
+
+impl Default for ForkConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval: Duration::from_secs(1),
+            retry_interval: Duration::from_secs(2),
+            max_attempts: 3,
+            segment_heights: 16,
+            max_depth: 1024,
+        }
+    }
+}
+
+/// How a fork was resolved
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForkOutcome {
+    /// The neighbor was ahead on our branch and its blocks were taken
+    FastForward {
+        /// Highest block held by both sides
+        ancestor_height: Option<u64>,
+        /// Blocks stored
+        added: usize,
+    },
+    /// Our branch was kept
+    KeptLocal {
+        /// Highest block held by both sides
+        ancestor_height: Option<u64>,
+    },
+    /// Our side of the fork was replaced with the neighbor's
+    AdoptedPeer {
+        /// Highest block held by both sides
+        ancestor_height: Option<u64>,
+        /// Blocks removed
+        removed: usize,
+        /// Blocks stored
+        added: usize,
+    },
+    /// Resolution was given up
+    Abandoned(String),
+}
+
+/// Result of handling a fork message
+#[derive(Debug, Default)]
+pub struct ForkOutput {
+    /// Messages to send back to the peer
+    pub replies: Vec<ForkMessage>,
+    /// Outcome, once resolution with the peer has finished
+    pub outcome: Option<ForkOutcome>,
+}
+
+/// Requests to resend and resolutions given up, from `ForkResolver::tick`
+#[derive(Debug, Default)]
+pub struct ForkTick {
+    /// Requests to send, by peer
+    pub requests: Vec<(String, ForkMessage)>,
+    /// Peers whose resolution was abandoned, with the reason
+    pub abandoned: Vec<(String, String)>,
+}
+
+struct SegmentRequest {
+    id: u64,
+    after: Option<u64>,
+    up_to: u64,
+}
+
+impl SegmentRequest {
+    fn message(&self) -> ForkMessage {
+        ForkMessage::SegmentRequest {
+            id: self.id,
+            after: self.after,
+            up_to: self.up_to,
+        }
+    }
+}
+
+struct ForkSession {
+    conflict: ForkConflict,
+    request: SegmentRequest,
+    sent: Instant,
+    attempts: u32,
+    /// The peer's blocks fetched so far
+    blocks: Vec<StoredBlock>,
+}
+
+/// Fork resolution sessions with each neighbor
+pub struct ForkResolver {
+    config: ForkConfig,
+    sessions: HashMap<String, ForkSession>,
+    /// Root each peer was last resolved against in our favor, so the same
+    /// conflict is not fetched again every time the peer gossips it
+    kept: HashMap<String, Vec<u8>>,
+    next_id: u64,
+}
+
+impl ForkResolver {
+    /// Create with `config`
+    pub fn new(config: ForkConfig) -> Self {
+        Self {
+            config,
+            sessions: HashMap::new(),
+            kept: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &ForkConfig {
+        &self.config
+    }
+
+    /// Conflict being resolved with `node_id`, if any
+    pub fn conflict(&self, node_id: &str) -> Option<&ForkConflict> {
+        self.sessions.get(node_id).map(|session| &session.conflict)
+    }
+
+    /// Drop the session with and history of `node_id`
+    pub fn remove(&mut self, node_id: &str) {
+        self.sessions.remove(node_id);
+        self.kept.remove(node_id);
+    }
+
+    /// Start resolving `conflict`, returning the first request to send to
+    /// its peer
+    ///
+    /// Returns `None` if resolution with the peer is already under way, or
+    /// this conflict was already resolved in our favor.
+    pub fn start(&mut self, conflict: ForkConflict) -> Option<ForkMessage> {
+        if self.sessions.contains_key(&conflict.peer_node)
+            || self.kept.get(&conflict.peer_node) == Some(&conflict.peer_root)
+        {
+            return None;
+        }
+        debug!(
+            peer_id = %conflict.peer_node,
+            peer_height = conflict.peer_height,
+            local_height = conflict.local_height,
+            "Starting fork resolution"
+        );
+        let request = self.request_below(conflict.peer_height);
+        let message = request.message();
+        self.sessions.insert(
+            conflict.peer_node.clone(),
+            ForkSession {
+                conflict,
+                request,
+                sent: clock::now(),
+                attempts: 1,
+                blocks: Vec::new(),
+            },
+        );
+        Some(message)
+    }
+
+    /// Resend unanswered requests and give up on those asked too often
+    ///
+    /// Sessions with nodes no longer in `peers` are discarded.
+    pub fn tick(&mut self, peers: &[String]) -> ForkTick {
+        self.sessions.retain(|node_id, _| peers.contains(node_id));
+
+        let mut tick = ForkTick::default();
+        for (node_id, session) in &mut self.sessions {
+            if clock::now().duration_since(session.sent) < self.config.retry_interval {
+                continue;
+            }
+            if session.attempts >= self.config.max_attempts {
+                tick.abandoned
+                    .push((node_id.clone(), "Segment request unanswered".to_string()));
+                continue;
+            }
+            session.sent = clock::now();
+            session.attempts += 1;
+            tick.requests
+                .push((node_id.clone(), session.request.message()));
+        }
+        for (node_id, _) in &tick.abandoned {
+            self.sessions.remove(node_id);
+        }
+        tick
+    }
+
+    /// Apply a fork message from `node_id`
+    ///
+    /// Once the common ancestor is found and the chains have forked,
+    /// `decide` is asked which branch to keep; an error abandons resolution.
+    pub fn handle(
+        &mut self,
+        bunker: &mut BunkerMode,
+        node_id: &str,
+        message: ForkMessage,
+        decide: impl FnOnce(&ForkConflict) -> Result<Branch, String>,
+    ) -> MeshResult<ForkOutput> {
+        let mut output = ForkOutput::default();
+        match message {
+            ForkMessage::SegmentRequest { id, after, up_to } => {
+                // Serve at most one window, however much was asked for
+                let after = after.max(up_to.checked_sub(self.config.segment_heights.max(1)));
+                let blocks = bunker.get_blocks_between(after, up_to)?;
+                output
+                    .replies
+                    .push(ForkMessage::Segment { id, after, blocks });
+            }
+            ForkMessage::Segment { id, after, blocks } => {
+                let Some(session) = self
+                    .sessions
+                    .get_mut(node_id)
+                    .filter(|session| session.request.id == id)
+                else {
+                    return Ok(output);
+                };
+                let up_to = session.request.up_to;
+                let after = after.max(session.request.after);
+                if after.is_some_and(|after| after >= up_to) {
+                    self.sessions.remove(node_id);
+                    output.outcome = Some(ForkOutcome::Abandoned(
+                        "Peer served an empty segment".to_string(),
+                    ));
+                    return Ok(output);
+                }
+
+                let mut window: Vec<StoredBlock> = blocks
+                    .into_iter()
+                    .filter(|block| {
+                        block.height <= up_to && after.is_none_or(|after| block.height > after)
+                    })
+                    .collect();
+                window.sort_by_key(|block| std::cmp::Reverse(block.height));
+                let mut ancestor = None;
+                for block in &window {
+                    if bunker.has_block(&block.hash)? {
+                        ancestor = Some(block.height);
+                        break;
+                    }
+                }
+                session.blocks.extend(window);
+
+                match (ancestor, after) {
+                    (None, Some(after)) => {
+                        if session.conflict.peer_height - after >= self.config.max_depth {
+                            self.sessions.remove(node_id);
+                            output.outcome = Some(ForkOutcome::Abandoned(format!(
+                                "No common ancestor within {} heights",
+                                self.config.max_depth
+                            )));
+                            return Ok(output);
+                        }
+                        // Walk further down the peer's chain
+                        let request = self.request_below(after);
+                        output.replies.push(request.message());
+                        if let Some(session) = self.sessions.get_mut(node_id) {
+                            session.request = request;
+                            session.sent = clock::now();
+                            session.attempts = 1;
+                        }
+                    }
+                    // Found the ancestor, or reached the bottom of the chain
+                    _ => {
+                        let Some(session) = self.sessions.remove(node_id) else {
+                            return Ok(output);
+                        };
+                        let outcome =
+                            match validate_branch(&session.conflict, session.blocks, ancestor) {
+                                Ok(peer_blocks) => self.resolve(
+                                    bunker,
+                                    session.conflict,
+                                    peer_blocks,
+                                    ancestor,
+                                    decide,
+                                )?,
+                                Err(reason) => {
+                                    warn!(peer_id = %node_id, "Peer's branch rejected: {}", reason);
+                                    ForkOutcome::Abandoned(reason)
+                                }
+                            };
+                        output.outcome = Some(outcome);
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Request for the window of heights up to and including `up_to`
+    fn request_below(&mut self, up_to: u64) -> SegmentRequest {
+        let id = self.next_id;
+        self.next_id += 1;
+        SegmentRequest {
+            id,
+            after: up_to.checked_sub(self.config.segment_heights.max(1)),
+            up_to,
+        }
+    }
+
+    /// Compare both sides above `ancestor` and keep the chosen branch
+    ///
+    /// `peer_blocks` are the peer's validated blocks above `ancestor`.
+    fn resolve(
+        &mut self,
+        bunker: &mut BunkerMode,
+        conflict: ForkConflict,
+        peer_blocks: Vec<StoredBlock>,
+        ancestor: Option<u64>,
+        decide: impl FnOnce(&ForkConflict) -> Result<Branch, String>,
+    ) -> MeshResult<ForkOutcome> {
+        let peer_hashes: HashSet<&[u8]> = peer_blocks
+            .iter()
+            .map(|block| block.hash.as_slice())
+            .collect();
+        let tip = conflict.local_height.max(conflict.peer_height);
+        let local_blocks: Vec<StoredBlock> = bunker
+            .get_blocks_between(ancestor, tip)?
+            .into_iter()
+            .filter(|block| !peer_hashes.contains(block.hash.as_slice()))
+            .collect();
+
+        if local_blocks.is_empty() && conflict.peer_height > conflict.local_height {
+            debug!(peer_id = %conflict.peer_node, "Fast-forwarding to peer's chain");
+            let added = store_blocks(bunker, peer_blocks)?;
+            return Ok(ForkOutcome::FastForward {
+                ancestor_height: ancestor,
+                added,
+            });
+        }
+
+        let branch = match decide(&conflict) {
+            Ok(branch) => branch,
+            Err(reason) => return Ok(ForkOutcome::Abandoned(reason)),
+        };
+        debug!(peer_id = %conflict.peer_node, ?branch, ?ancestor, "Fork resolved");
+        match branch {
+            Branch::Local => {
+                self.kept.insert(conflict.peer_node, conflict.peer_root);
+                Ok(ForkOutcome::KeptLocal {
+                    ancestor_height: ancestor,
+                })
+            }
+            Branch::Peer => {
+                for block in &local_blocks {
+                    bunker.remove_block(&block.hash)?;
+                }
+                let added = store_blocks(bunker, peer_blocks)?;
+                Ok(ForkOutcome::AdoptedPeer {
+                    ancestor_height: ancestor,
+                    removed: local_blocks.len(),
+                    added,
+                })
+            }
+        }
+    }
+}
+
+/// The peer's blocks above `ancestor`, lowest first, once they are shown to
+/// chain from the ancestor up to the root the peer gossiped
+fn validate_branch(
+    conflict: &ForkConflict,
+    blocks: Vec<StoredBlock>,
+    ancestor: Option<u64>,
+) -> Result<Vec<StoredBlock>, String> {
+    let mut by_height = BTreeMap::new();
+    for block in blocks {
+        let height = block.height;
+        if by_height.insert(height, block).is_some() {
+            return Err(format!("Peer served two blocks at height {}", height));
+        }
+    }
+
+    // The ancestor is a block we hold, found among those the peer served
+    let mut parent = match ancestor {
+        Some(height) => by_height
+            .get(&height)
+            .map(|block| block.hash.clone())
+            .ok_or_else(|| format!("Ancestor at height {} not served", height))?,
+        None => Vec::new(),
+    };
+    let mut tip_height = ancestor;
+    let branch: Vec<StoredBlock> = by_height
+        .into_values()
+        .filter(|block| ancestor.is_none_or(|ancestor| block.height > ancestor))
+        .collect();
+    for block in &branch {
+        if tip_height.is_some_and(|tip| block.height != tip + 1) {
+            return Err(format!("Peer's branch skips to height {}", block.height));
+        }
+        if block.hash != StoredBlock::chain_hash(&parent, block.height, &block.data) {
+            return Err(format!(
+                "Block at height {} does not match its data and parent",
+                block.height
+            ));
+        }
+        parent = block.hash.clone();
+        tip_height = Some(block.height);
+    }
+
+    if tip_height != Some(conflict.peer_height) || parent != conflict.peer_root {
+        return Err("Peer's branch does not end at the root it gossiped".to_string());
+    }
+    Ok(branch)
+}
+
+/// Store blocks received from a peer, returning how many were new
+fn store_blocks(bunker: &mut BunkerMode, blocks: Vec<StoredBlock>) -> MeshResult<usize> {
+    let mut added = 0;
+    for mut block in blocks {
+        if !bunker.has_block(&block.hash)? {
+            added += 1;
+        }
+        // The peer already holds it, so there is nothing to sync
+        block.synced = true;
+        bunker.store_block(block)?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(parent: &[u8], height: u64, branch: u8) -> StoredBlock {
+        let data = vec![branch; 8];
+        StoredBlock {
+            hash: StoredBlock::chain_hash(parent, height, &data),
+            height,
+            data,
+            timestamp: height,
+            synced: true,
+        }
+    }
+
+    /// Store with blocks 1..=`shared` common to every branch, then `branch`
+    /// up to `tip`
+    fn chain(shared: u64, branch: u8, tip: u64) -> BunkerMode {
+        let mut bunker = BunkerMode::new(":memory:").unwrap();
+        let mut parent = Vec::new();
+        for height in 1..=tip {
+            let block = block(&parent, height, if height <= shared { 0 } else { branch });
+            parent = block.hash.clone();
+            bunker.store_block(block).unwrap();
+        }
+        bunker
+    }
+
+    /// Hash and height of the highest block held
+    fn tip(bunker: &BunkerMode) -> (Vec<u8>, u64) {
+        let block = bunker.get_blocks_between(None, 100).unwrap().pop().unwrap();
+        (block.hash, block.height)
+    }
+
+    /// Conflict between `local` and `peer` as their gossiped tips show it
+    fn tips(local: &BunkerMode, peer: &BunkerMode) -> ForkConflict {
+        let ((local_root, local_height), (peer_root, peer_height)) = (tip(local), tip(peer));
+        ForkConflict {
+            peer_node: "node-b".to_string(),
+            peer_root,
+            peer_height,
+            local_root,
+            local_height,
+        }
+    }
+
+    fn conflict(local: (u8, u64), peer: (u8, u64)) -> ForkConflict {
+        ForkConflict {
+            peer_node: "node-b".to_string(),
+            peer_root: vec![peer.0],
+            peer_height: peer.1,
+            local_root: vec![local.0],
+            local_height: local.1,
+        }
+    }
+
+    /// Run resolution from `a` against `b` until it finishes; returns the
+    /// outcome and the number of segments fetched
+    fn resolve(
+        a: &mut BunkerMode,
+        b: &mut BunkerMode,
+        config: ForkConfig,
+        conflict: ForkConflict,
+        branch: Branch,
+    ) -> (ForkOutcome, usize) {
+        let mut resolver = ForkResolver::new(config.clone());
+        let mut responder = ForkResolver::new(config);
+        let mut request = resolver.start(conflict).unwrap();
+        let mut segments = 0;
+        loop {
+            let reply = responder
+                .handle(b, "node-a", request, |_| unreachable!())
+                .unwrap()
+                .replies
+                .remove(0);
+            segments += 1;
+            let output = resolver.handle(a, "node-b", reply, |_| Ok(branch)).unwrap();
+            if let Some(outcome) = output.outcome {
+                return (outcome, segments);
+            }
+            request = output.replies.into_iter().next().unwrap();
+        }
+    }
+
+    fn heights(bunker: &BunkerMode, branch: u8) -> Vec<u64> {
+        bunker
+            .get_blocks_between(None, 100)
+            .unwrap()
+            .into_iter()
+            .filter(|block| block.data[0] == branch)
+            .map(|block| block.height)
+            .collect()
+    }
+
+    #[test]
+    fn test_fast_forward_to_peer_ahead() {
+        let mut a = chain(3, 0, 3);
+        let mut b = chain(6, 0, 6);
+        let config = ForkConfig {
+            segment_heights: 2,
+            ..ForkConfig::default()
+        };
+
+        // Blocks 5-6, then 3-4 where block 3 is found to be common
+        let fork = tips(&a, &b);
+        let (outcome, segments) = resolve(&mut a, &mut b, config, fork, Branch::Local);
+        assert_eq!(
+            outcome,
+            ForkOutcome::FastForward {
+                ancestor_height: Some(3),
+                added: 3
+            }
+        );
+        assert_eq!(segments, 2);
+        assert_eq!(a.get_store_root().unwrap(), b.get_store_root().unwrap());
+    }
+
+    #[test]
+    fn test_fork_replaced_with_chosen_branch() {
+        let config = ForkConfig {
+            segment_heights: 3,
+            ..ForkConfig::default()
+        };
+
+        let mut a = chain(4, 1, 6);
+        let mut b = chain(4, 2, 7);
+        let fork = tips(&a, &b);
+        let (outcome, _) = resolve(&mut a, &mut b, config.clone(), fork, Branch::Peer);
+        assert_eq!(
+            outcome,
+            ForkOutcome::AdoptedPeer {
+                ancestor_height: Some(4),
+                removed: 2,
+                added: 3
+            }
+        );
+        assert!(heights(&a, 1).is_empty());
+        assert_eq!(heights(&a, 2), vec![5, 6, 7]);
+        assert_eq!(a.get_store_root().unwrap(), b.get_store_root().unwrap());
+
+        let mut a = chain(4, 1, 6);
+        let fork = tips(&a, &b);
+        let (outcome, _) = resolve(&mut a, &mut b, config, fork, Branch::Local);
+        assert_eq!(
+            outcome,
+            ForkOutcome::KeptLocal {
+                ancestor_height: Some(4)
+            }
+        );
+        assert_eq!(heights(&a, 1), vec![5, 6]);
+        assert!(heights(&a, 2).is_empty());
+    }
+
+    #[test]
+    fn test_kept_conflict_not_resolved_again() {
+        let mut a = chain(2, 1, 3);
+        let mut b = chain(2, 2, 3);
+        let fork = tips(&a, &b);
+        let mut resolver = ForkResolver::new(ForkConfig::default());
+        let request = resolver.start(fork.clone()).unwrap();
+        assert!(resolver.start(fork.clone()).is_none());
+
+        let reply = ForkResolver::new(ForkConfig::default())
+            .handle(&mut b, "node-a", request, |_| unreachable!())
+            .unwrap()
+            .replies
+            .remove(0);
+        let output = resolver
+            .handle(&mut a, "node-b", reply, |_| Ok(Branch::Local))
+            .unwrap();
+        assert!(matches!(
+            output.outcome,
+            Some(ForkOutcome::KeptLocal { .. })
+        ));
+
+        // Only a new root from the peer starts another round
+        assert!(resolver.start(fork.clone()).is_none());
+        assert!(resolver
+            .start(ForkConflict {
+                peer_root: vec![3; 32],
+                ..fork
+            })
+            .is_some());
+    }
+
+    #[test]
+    fn test_resolution_abandoned() {
+        // Nothing in common within the search depth
+        let mut a = chain(0, 1, 10);
+        let mut b = chain(0, 2, 10);
+        let config = ForkConfig {
+            segment_heights: 2,
+            max_depth: 4,
+            ..ForkConfig::default()
+        };
+        let fork = tips(&a, &b);
+        let (outcome, segments) = resolve(&mut a, &mut b, config, fork, Branch::Peer);
+        assert!(matches!(outcome, ForkOutcome::Abandoned(_)));
+        assert_eq!(segments, 2);
+        assert_eq!(heights(&a, 1).len(), 10);
+
+        // A peer that never answers
+        let mut resolver = ForkResolver::new(ForkConfig {
+            retry_interval: Duration::ZERO,
+            max_attempts: 2,
+            ..ForkConfig::default()
+        });
+        let peers = ["node-b".to_string()];
+        resolver.start(conflict((1, 3), (2, 3))).unwrap();
+        let tick = resolver.tick(&peers);
+        assert_eq!(tick.requests.len(), 1);
+        let tick = resolver.tick(&peers);
+        assert!(tick.requests.is_empty());
+        assert_eq!(tick.abandoned.len(), 1);
+        assert!(resolver.conflict("node-b").is_none());
+    }
+
+    #[test]
+    fn test_forged_branch_abandoned() {
+        let config = ForkConfig {
+            segment_heights: 3,
+            ..ForkConfig::default()
+        };
+        let mut b = chain(4, 2, 7);
+
+        // The peer's blocks do not end at the root it gossiped
+        let mut a = chain(4, 1, 6);
+        let fork = ForkConflict {
+            peer_root: vec![2; 32],
+            ..tips(&a, &b)
+        };
+        let (outcome, _) = resolve(&mut a, &mut b, config.clone(), fork, Branch::Peer);
+        assert!(matches!(outcome, ForkOutcome::Abandoned(_)));
+        assert_eq!(heights(&a, 1), vec![5, 6]);
+
+        // A block whose data was swapped no longer matches its hash
+        let fork = tips(&a, &b);
+        let mut forged = b.get_blocks_between(Some(5), 6).unwrap().remove(0);
+        forged.data = vec![3; 8];
+        b.store_block(forged).unwrap();
+        let (outcome, _) = resolve(&mut a, &mut b, config.clone(), fork, Branch::Peer);
+        assert!(matches!(outcome, ForkOutcome::Abandoned(_)));
+        assert_eq!(heights(&a, 1), vec![5, 6]);
+        assert!(heights(&a, 3).is_empty());
+
+        // Nor is a fast-forward taken from a peer whose chain has a gap
+        let mut a = chain(4, 0, 4);
+        let mut b = chain(4, 2, 7);
+        let fork = tips(&a, &b);
+        let gap = b.get_blocks_between(Some(5), 6).unwrap().remove(0);
+        b.remove_block(&gap.hash).unwrap();
+        let (outcome, _) = resolve(&mut a, &mut b, config, fork, Branch::Local);
+        assert!(matches!(outcome, ForkOutcome::Abandoned(_)));
+        assert_eq!(tip(&a).1, 4);
+    }
+
+    #[test]
+    fn test_choose_branch() {
+        let view = |root: u8, height: u64, trust_weight: f64| ConsensusView {
+            merkle_root: vec![root],
+            block_height: height,
+            peer_count: 1,
+            total_peers: 2,
+            trust_weight,
+            total_trust: 1.0,
+        };
+
+        // A majority of the other peers decides
+        let fork = conflict((1, 5), (2, 5));
+        assert_eq!(choose_branch(Some(&view(2, 5, 0.6)), &fork), Branch::Peer);
+        assert_eq!(choose_branch(Some(&view(1, 5, 0.6)), &fork), Branch::Local);
+
+        // Without one, both sides settle on the lower root
+        assert_eq!(choose_branch(Some(&view(2, 5, 0.5)), &fork), Branch::Local);
+        assert_eq!(choose_branch(None, &conflict((2, 5), (1, 5))), Branch::Peer);
+
+        // or failing that, the higher branch
+        assert_eq!(choose_branch(None, &conflict((1, 5), (2, 6))), Branch::Peer);
+        assert_eq!(
+            choose_branch(Some(&view(3, 9, 1.0)), &conflict((2, 6), (1, 5))),
+            Branch::Local
+        );
+    }
+}
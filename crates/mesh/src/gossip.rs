@@ -2,10 +2,28 @@
 //!
 //! Lightweight gossip protocol for propagating Merkle Roots across the swarm
 //! without central coordination.
-
+//!
+//! A node that gossips two different roots for the same height has
+//! equivocated; the pair of messages is reported as evidence so the node
+//! can be slashed.
+//!
+//! Flooding is contained in three places: each neighbor may only deliver
+//! `rate_limit` messages per `rate_window`, accepted messages are relayed
+//! to `fanout` randomly chosen neighbors rather than all of them, and
+//! messages are only remembered until they would be dropped as too old
+//! anyway.
+//!
+//! Messages are deduplicated on the hash of their signed content rather
+//! than on the sender-chosen message ID, so a message reusing an ID cannot
+//! shadow the genuine one.
+
+use crate::clock::current_timestamp;
+use crate::transport::write_str;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 /// Gossip message containing state updates
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +44,58 @@ pub struct GossipMessage {
     pub hop_count: u8,
 }
 
+impl GossipMessage {
+    /// Canonical encoding of the signed fields
+    ///
+    /// The hop count is left out since relays increment it.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_str(&mut bytes, &self.msg_id);
+        write_str(&mut bytes, &self.source_node);
+        bytes.extend_from_slice(&(self.merkle_root.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.merkle_root);
+        bytes.extend_from_slice(&self.block_height.to_be_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes
+    }
+
+    /// BLAKE3 hash of `signing_bytes`, identifying the message for
+    /// deduplication
+    pub fn content_hash(&self) -> [u8; 32] {
+        *blake3::hash(&self.signing_bytes()).as_bytes()
+    }
+}
+
+/// Propagation and flood-control limits for gossip
+#[derive(Debug, Clone)]
+pub struct GossipConfig {
+    /// Maximum hops before message is dropped
+    pub max_hops: u8,
+    /// Maximum age for messages
+    pub max_message_age: Duration,
+    /// How far ahead of our clock a message timestamp may be
+    pub max_clock_skew: Duration,
+    /// Messages accepted from one neighbor per `rate_window`
+    pub rate_limit: u32,
+    /// Window over which `rate_limit` applies
+    pub rate_window: Duration,
+    /// Neighbors an accepted message is relayed to
+    pub fanout: usize,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        Self {
+            max_hops: 10,
+            max_message_age: Duration::from_secs(60),
+            max_clock_skew: Duration::from_secs(5),
+            rate_limit: 50,
+            rate_window: Duration::from_secs(1),
+            fanout: 3,
+        }
+    }
+}
+
 /// Gossip protocol manager
 #[derive(Debug)]
 pub struct AethericWhisper {
@@ -35,14 +105,18 @@ pub struct AethericWhisper {
     local_merkle_root: Option<Vec<u8>>,
     /// Current local block height
     local_block_height: u64,
-    /// Recently seen message IDs for deduplication
-    seen_messages: HashSet<String>,
+    /// Content hashes of recently seen messages, for deduplication
+    seen_messages: HashSet<[u8; 32]>,
+    /// Seen content hashes in the order they expire (expiry in milliseconds)
+    seen_expiry: VecDeque<(u64, [u8; 32])>,
     /// Peer states: node_id -> (merkle_root, block_height, timestamp)
     peer_states: HashMap<String, (Vec<u8>, u64, u64)>,
-    /// Maximum hops before message is dropped
-    max_hops: u8,
-    /// Maximum age for messages (milliseconds)
-    max_message_age_ms: u64,
+    /// Last message from each source, kept as evidence should it equivocate
+    last_messages: HashMap<String, GossipMessage>,
+    /// Messages received per neighbor: node_id -> (window start, count)
+    rates: HashMap<String, (u64, u32)>,
+    /// Propagation and flood-control limits
+    config: GossipConfig,
 }
 
 impl AethericWhisper {
@@ -53,51 +127,122 @@ impl AethericWhisper {
             local_merkle_root: None,
             local_block_height: 0,
             seen_messages: HashSet::new(),
+            seen_expiry: VecDeque::new(),
             peer_states: HashMap::new(),
-            max_hops: 10,
-            max_message_age_ms: 60000, // 60 seconds
+            last_messages: HashMap::new(),
+            rates: HashMap::new(),
+            config: GossipConfig::default(),
         }
     }
 
+    /// Use `config` for propagation and flood-control limits
+    pub fn with_config(mut self, config: GossipConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Propagation and flood-control limits
+    pub fn config(&self) -> &GossipConfig {
+        &self.config
+    }
+
     /// Update local state with new Merkle root
     pub fn update_local_state(&mut self, merkle_root: Vec<u8>, block_height: u64) {
         self.local_merkle_root = Some(merkle_root);
         self.local_block_height = block_height;
     }
 
-    /// Create a gossip message with current local state
-    pub fn create_gossip_message(&self, signature: Vec<u8>) -> Option<GossipMessage> {
+    /// Current local Merkle root and block height, once set
+    pub fn local_state(&self) -> Option<(&[u8], u64)> {
+        self.local_merkle_root
+            .as_deref()
+            .map(|root| (root, self.local_block_height))
+    }
+
+    /// Create an unsigned gossip message with current local state
+    ///
+    /// The message must be signed before it is sent, since receivers drop
+    /// gossip that does not verify against its source's key.
+    pub fn create_gossip_message(&self) -> Option<GossipMessage> {
+        let timestamp = current_timestamp();
         self.local_merkle_root.as_ref().map(|root| GossipMessage {
-            msg_id: format!("{}-{}", self.node_id, current_timestamp()),
+            msg_id: format!("{}-{}-{}", self.node_id, self.local_block_height, timestamp),
             source_node: self.node_id.clone(),
             merkle_root: root.clone(),
             block_height: self.local_block_height,
-            timestamp: current_timestamp(),
-            signature,
+            timestamp,
+            signature: Vec::new(),
             hop_count: 0,
         })
     }
 
+    /// Count a message delivered by neighbor `from` against its rate limit
+    ///
+    /// Returns false once the neighbor has used up its allowance for the
+    /// current window. Every delivery counts, duplicates included.
+    pub fn check_rate(&mut self, from: &str) -> bool {
+        let now = current_timestamp();
+        let window_ms = self.config.rate_window.as_millis() as u64;
+        let (window_start, count) = self.rates.entry(from.to_string()).or_insert((now, 0));
+        if now.saturating_sub(*window_start) >= window_ms {
+            *window_start = now;
+            *count = 0;
+        }
+        *count = count.saturating_add(1);
+        *count <= self.config.rate_limit
+    }
+
+    /// Whether `message` has been seen and not yet expired
+    pub fn has_seen(&self, message: &GossipMessage) -> bool {
+        self.seen_messages.contains(&message.content_hash())
+    }
+
     /// Process an incoming gossip message
+    ///
+    /// The caller is expected to have checked the sending neighbor's rate
+    /// and the message signature first.
     pub fn process_message(&mut self, mut message: GossipMessage) -> GossipResult {
-        // Check if message is too old
         let now = current_timestamp();
-        if now - message.timestamp > self.max_message_age_ms {
+        self.prune_expired(now);
+
+        // Check the message is neither too old nor from the future
+        if now.saturating_sub(message.timestamp) > self.config.max_message_age.as_millis() as u64 {
             return GossipResult::Dropped("Message too old".to_string());
         }
+        if message.timestamp > now + self.config.max_clock_skew.as_millis() as u64 {
+            return GossipResult::Dropped("Message timestamp in the future".to_string());
+        }
 
         // Check hop count
-        if message.hop_count >= self.max_hops {
+        if message.hop_count >= self.config.max_hops {
             return GossipResult::Dropped("Max hops exceeded".to_string());
         }
 
         // Check for duplicate
-        if self.seen_messages.contains(&message.msg_id) {
+        let content_hash = message.content_hash();
+        if self.seen_messages.contains(&content_hash) {
             return GossipResult::Duplicate;
         }
 
-        // Mark as seen
-        self.seen_messages.insert(message.msg_id.clone());
+        // Mark as seen until it would be dropped as too old
+        let expiry =
+            now + (self.config.max_message_age + self.config.max_clock_skew).as_millis() as u64;
+        self.seen_messages.insert(content_hash);
+        self.seen_expiry.push_back((expiry, content_hash));
+
+        // Check the source is not contradicting its last message
+        if let Some(previous) = self.last_messages.get(&message.source_node) {
+            if previous.block_height == message.block_height
+                && previous.merkle_root != message.merkle_root
+            {
+                return GossipResult::Equivocation {
+                    first: previous.clone(),
+                    second: message,
+                };
+            }
+        }
+        self.last_messages
+            .insert(message.source_node.clone(), message.clone());
 
         // Update peer state
         self.peer_states.insert(
@@ -165,14 +310,76 @@ impl AethericWhisper {
             block_height: consensus_state.0 .1,
             peer_count: max_count,
             total_peers: self.peer_states.len(),
+            trust_weight: max_count as f64,
+            total_trust: self.peer_states.len() as f64,
         })
     }
 
-    /// Prune old messages from seen set
-    pub fn prune_old_messages(&mut self, max_seen: usize) {
-        if self.seen_messages.len() > max_seen {
-            self.seen_messages.clear();
+    /// Get the consensus view weighting each peer by its trust score
+    ///
+    /// Only peers listed in `trust_scores` are counted. The state with the
+    /// most combined trust wins; ties go to the lowest root so that every
+    /// node picks the same state.
+    pub fn get_weighted_consensus_view(
+        &self,
+        trust_scores: &HashMap<String, f64>,
+    ) -> Option<ConsensusView> {
+        let mut tallies: HashMap<(&[u8], u64), (usize, f64)> = HashMap::new();
+        let mut total_peers = 0;
+        let mut total_trust = 0.0;
+        for (node_id, (root, height, _)) in &self.peer_states {
+            let Some(&trust) = trust_scores.get(node_id) else {
+                continue;
+            };
+            total_peers += 1;
+            total_trust += trust;
+            let tally = tallies.entry((root.as_slice(), *height)).or_default();
+            tally.0 += 1;
+            tally.1 += trust;
         }
+
+        let ((root, height), (peer_count, trust_weight)) =
+            tallies.into_iter().max_by(|(a_state, a), (b_state, b)| {
+                a.1.total_cmp(&b.1).then_with(|| b_state.cmp(a_state))
+            })?;
+        Some(ConsensusView {
+            merkle_root: root.to_vec(),
+            block_height: height,
+            peer_count,
+            total_peers,
+            trust_weight,
+            total_trust,
+        })
+    }
+
+    /// Choose up to `fanout` of `candidates` at random to relay a message to
+    pub fn select_fanout<R: Rng>(&self, candidates: &[String], rng: &mut R) -> Vec<String> {
+        candidates
+            .choose_multiple(rng, self.config.fanout)
+            .cloned()
+            .collect()
+    }
+
+    /// Forget everything heard from `node_id`, such as once it is revoked
+    pub fn forget_peer(&mut self, node_id: &str) {
+        self.peer_states.remove(node_id);
+        self.last_messages.remove(node_id);
+        self.rates.remove(node_id);
+    }
+
+    /// Drop seen message IDs and rate windows that expired before `now`
+    fn prune_expired(&mut self, now: u64) {
+        while let Some((expiry, _)) = self.seen_expiry.front() {
+            if *expiry > now {
+                break;
+            }
+            if let Some((_, content_hash)) = self.seen_expiry.pop_front() {
+                self.seen_messages.remove(&content_hash);
+            }
+        }
+        let window_ms = self.config.rate_window.as_millis() as u64;
+        self.rates
+            .retain(|_, (window_start, _)| now.saturating_sub(*window_start) < window_ms);
     }
 
     /// Get peer state
@@ -208,6 +415,13 @@ pub enum GossipResult {
         /// Peer node ID
         peer_node: String,
     },
+    /// Source gossiped two different roots for the same height
+    Equivocation {
+        /// Earlier message from the source
+        first: GossipMessage,
+        /// Contradicting message from the source
+        second: GossipMessage,
+    },
     /// Peer is ahead of us
     PeerAhead {
         /// Peer's Merkle root
@@ -230,14 +444,17 @@ pub struct ConsensusView {
     pub peer_count: usize,
     /// Total number of peers
     pub total_peers: usize,
+    /// Combined trust of the agreeing peers
+    pub trust_weight: f64,
+    /// Combined trust of all peers
+    pub total_trust: f64,
 }
 
-/// Get current timestamp in milliseconds
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
+impl ConsensusView {
+    /// Whether the agreeing peers hold more than half of the total trust
+    pub fn has_majority(&self) -> bool {
+        self.trust_weight * 2.0 > self.total_trust
+    }
 }
 
 #[cfg(test)]
@@ -249,7 +466,7 @@ mod tests {
         let mut whisper = AethericWhisper::new("node1".to_string());
         whisper.update_local_state(vec![1, 2, 3], 100);
 
-        let msg = whisper.create_gossip_message(vec![4, 5, 6]).unwrap();
+        let msg = whisper.create_gossip_message().unwrap();
         assert_eq!(msg.source_node, "node1");
         assert_eq!(msg.merkle_root, vec![1, 2, 3]);
         assert_eq!(msg.block_height, 100);
@@ -342,7 +559,7 @@ mod tests {
     #[test]
     fn test_max_hops_enforcement() {
         let mut whisper = AethericWhisper::new("node1".to_string());
-        whisper.max_hops = 3;
+        whisper.config.max_hops = 3;
 
         let msg = GossipMessage {
             msg_id: "msg1".to_string(),
@@ -360,6 +577,105 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_message_timestamp_bounds() {
+        let mut whisper = AethericWhisper::new("node1".to_string());
+        let message = |msg_id: &str, timestamp: u64| GossipMessage {
+            msg_id: msg_id.to_string(),
+            source_node: "node2".to_string(),
+            merkle_root: vec![1, 2, 3],
+            block_height: 100,
+            timestamp,
+            signature: vec![],
+            hop_count: 0,
+        };
+
+        let now = current_timestamp();
+        match whisper.process_message(message("future", now + 60_000)) {
+            GossipResult::Dropped(reason) => assert!(reason.contains("future")),
+            other => panic!("Expected Dropped, got {:?}", other),
+        }
+        match whisper.process_message(message("old", now - 120_000)) {
+            GossipResult::Dropped(reason) => assert!(reason.contains("old")),
+            other => panic!("Expected Dropped, got {:?}", other),
+        }
+        // Small clock skew is tolerated
+        assert!(matches!(
+            whisper.process_message(message("skewed", now + 1_000)),
+            GossipResult::Accepted { .. }
+        ));
+    }
+
+    #[test]
+    fn test_rate_limit_per_neighbor() {
+        let mut whisper = AethericWhisper::new("node1".to_string()).with_config(GossipConfig {
+            rate_limit: 3,
+            rate_window: Duration::from_secs(60),
+            ..GossipConfig::default()
+        });
+
+        for _ in 0..3 {
+            assert!(whisper.check_rate("node2"));
+        }
+        assert!(!whisper.check_rate("node2"));
+        // Other neighbors have their own allowance
+        assert!(whisper.check_rate("node3"));
+
+        whisper.forget_peer("node2");
+        assert!(whisper.check_rate("node2"));
+    }
+
+    #[test]
+    fn test_seen_messages_expire() {
+        let mut whisper = AethericWhisper::new("node1".to_string());
+        let msg = GossipMessage {
+            msg_id: "msg1".to_string(),
+            source_node: "node2".to_string(),
+            merkle_root: vec![1, 2, 3],
+            block_height: 100,
+            timestamp: current_timestamp(),
+            signature: vec![],
+            hop_count: 0,
+        };
+        whisper.process_message(msg.clone());
+        assert!(whisper.has_seen(&msg));
+
+        // Kept while a replay could still pass the age check
+        let ttl = (whisper.config.max_message_age + whisper.config.max_clock_skew).as_millis();
+        let now = current_timestamp();
+        whisper.prune_expired(now + ttl as u64 / 2);
+        assert!(whisper.has_seen(&msg));
+
+        whisper.prune_expired(now + ttl as u64 + 1_000);
+        assert!(!whisper.has_seen(&msg));
+        assert!(whisper.seen_expiry.is_empty());
+    }
+
+    #[test]
+    fn test_select_fanout() {
+        let whisper = AethericWhisper::new("node1".to_string()).with_config(GossipConfig {
+            fanout: 2,
+            ..GossipConfig::default()
+        });
+        let candidates: Vec<String> = (2..7).map(|n| format!("node{}", n)).collect();
+
+        let targets = whisper.select_fanout(&candidates, &mut rand::thread_rng());
+        assert_eq!(targets.len(), 2);
+        assert_ne!(targets[0], targets[1]);
+        assert!(targets.iter().all(|target| candidates.contains(target)));
+
+        // Fewer candidates than the fanout are all chosen
+        assert_eq!(
+            whisper
+                .select_fanout(&candidates[..1], &mut rand::thread_rng())
+                .len(),
+            1
+        );
+        assert!(whisper
+            .select_fanout(&[], &mut rand::thread_rng())
+            .is_empty());
+    }
+
     #[test]
     fn test_get_consensus_view() {
         let mut whisper = AethericWhisper::new("node1".to_string());
@@ -383,4 +699,77 @@ mod tests {
         assert_eq!(consensus.block_height, 100);
         assert_eq!(consensus.peer_count, 3);
     }
+
+    #[test]
+    fn test_equivocation_detection() {
+        let mut whisper = AethericWhisper::new("node1".to_string());
+        let message = |msg_id: &str, root: Vec<u8>, height: u64| GossipMessage {
+            msg_id: msg_id.to_string(),
+            source_node: "node2".to_string(),
+            merkle_root: root,
+            block_height: height,
+            timestamp: current_timestamp(),
+            signature: vec![],
+            hop_count: 0,
+        };
+
+        whisper.process_message(message("msg1", vec![1, 2, 3], 100));
+        // Same root again, then a new height, are both fine
+        assert!(matches!(
+            whisper.process_message(message("msg2", vec![1, 2, 3], 100)),
+            GossipResult::Accepted { .. }
+        ));
+        assert!(matches!(
+            whisper.process_message(message("msg3", vec![4, 5, 6], 101)),
+            GossipResult::Accepted { .. }
+        ));
+
+        match whisper.process_message(message("msg4", vec![7, 8, 9], 101)) {
+            GossipResult::Equivocation { first, second } => {
+                assert_eq!(first.msg_id, "msg3");
+                assert_eq!(second.msg_id, "msg4");
+            }
+            other => panic!("Expected Equivocation, got {:?}", other),
+        }
+        // The contradicting root is not taken as the peer's state
+        assert_eq!(whisper.get_peer_state("node2").unwrap().0, vec![4, 5, 6]);
+
+        whisper.forget_peer("node2");
+        assert!(whisper.get_peer_state("node2").is_none());
+    }
+
+    #[test]
+    fn test_get_weighted_consensus_view() {
+        let mut whisper = AethericWhisper::new("node1".to_string());
+        for (node_id, root) in [("node2", 1), ("node3", 1), ("node4", 2)] {
+            whisper
+                .peer_states
+                .insert(node_id.to_string(), (vec![root], 100, current_timestamp()));
+        }
+
+        // One trusted peer outweighs two barely trusted ones
+        let trust: HashMap<String, f64> = [("node2", 0.2), ("node3", 0.2), ("node4", 0.9)]
+            .into_iter()
+            .map(|(node_id, trust)| (node_id.to_string(), trust))
+            .collect();
+        let view = whisper.get_weighted_consensus_view(&trust).unwrap();
+        assert_eq!(view.merkle_root, vec![2]);
+        assert_eq!(view.peer_count, 1);
+        assert_eq!(view.total_peers, 3);
+        assert!(view.has_majority());
+
+        // Unlisted peers are not counted, and ties go to the lowest root
+        let trust: HashMap<String, f64> = [("node2", 0.5), ("node4", 0.5)]
+            .into_iter()
+            .map(|(node_id, trust)| (node_id.to_string(), trust))
+            .collect();
+        let view = whisper.get_weighted_consensus_view(&trust).unwrap();
+        assert_eq!(view.merkle_root, vec![1]);
+        assert_eq!(view.total_peers, 2);
+        assert!(!view.has_majority());
+
+        assert!(whisper
+            .get_weighted_consensus_view(&HashMap::new())
+            .is_none());
+    }
 }
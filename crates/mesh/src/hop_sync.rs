@@ -0,0 +1,721 @@
+//! Hop Synchronization - Swarm-wide agreement on frequency hopping patterns
+//!
+//! Every node derives its channel from a shared epoch clock (Unix time
+//! divided by `epoch_duration`) and the active `HoppingPattern`, so nodes
+//! holding the same pattern hop together without exchanging anything. A new
+//! pattern is introduced as a signed `HopPlan`: a fresh seed and the epoch,
+//! `lead_epochs` ahead, at which the swarm switches to it. Plans travel to
+//! trusted neighbors in sealed frames and are relayed onward; a neighbor
+//! that has not acknowledged a plan is sent it again every `retry_epochs`
+//! until the switch. Plans issued concurrently are settled by ordering, the
+//! higher generation first and then the lower issuer ID, so every node
+//! switches to the same one.
+//!
+//! Every `rendezvous_interval` epochs all nodes tune to the rendezvous
+//! channel and exchange the newest plan generation they hold, so a node
+//! that missed a plan catches up there. A node that hears no neighbor for
+//! `sync_timeout_epochs` has lost sync and stays on the rendezvous channel
+//! until it hears one again.
+
+use crate::error::{MeshError, MeshResult};
+use crate::spectral::{generate_hopping_pattern, ChannelId, HoppingPattern};
+use crate::transport::write_str;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
+
+/// Most channels a plan may ask a pattern to be generated with
+pub const MAX_PLAN_CHANNELS: usize = 1024;
+
+/// Hopping pattern issued to the swarm, with the epoch it takes effect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HopPlan {
+    /// Node that issued the plan
+    pub issuer: String,
+    /// One above the newest plan the issuer knew of
+    pub generation: u64,
+    /// Seed the pattern is generated from
+    pub seed: Vec<u8>,
+    /// Channels in the pattern
+    pub num_channels: usize,
+    /// Lowest and highest channel the pattern may use
+    pub channel_range: (ChannelId, ChannelId),
+    /// First epoch the pattern is in force
+    pub switch_epoch: u64,
+    /// Issuer's signature over `signing_bytes()`
+    pub signature: Vec<u8>,
+}
+
+impl HopPlan {
+    /// Canonical encoding of the signed fields
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_str(&mut bytes, &self.issuer);
+        bytes.extend_from_slice(&self.generation.to_be_bytes());
+        bytes.extend_from_slice(&(self.seed.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.seed);
+        bytes.extend_from_slice(&(self.num_channels as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.channel_range.0.to_be_bytes());
+        bytes.extend_from_slice(&self.channel_range.1.to_be_bytes());
+        bytes.extend_from_slice(&self.switch_epoch.to_be_bytes());
+        bytes
+    }
+
+    /// Whether this plan takes precedence over `other`
+    pub fn supersedes(&self, other: &HopPlan) -> bool {
+        (self.generation, Reverse(&self.issuer)) > (other.generation, Reverse(&other.issuer))
+    }
+
+    /// Whether `other` is the same plan
+    fn is(&self, other: &HopPlan) -> bool {
+        self.generation == other.generation && self.issuer == other.issuer
+    }
+
+    /// The hopping pattern generated from the plan's seed
+    pub fn pattern(&self, dwell_time_ms: u64) -> HoppingPattern {
+        let mut pattern = generate_hopping_pattern(
+            self.seed.clone(),
+            self.num_channels,
+            self.channel_range,
+            dwell_time_ms,
+        );
+        pattern.pattern_id = format!("{}-{}", self.issuer, self.generation);
+        pattern
+    }
+
+    fn validate(&self) -> MeshResult<()> {
+        if self.num_channels == 0 || self.num_channels > MAX_PLAN_CHANNELS {
+            return Err(MeshError::Spectral(format!(
+                "Plan from {} has {} channels",
+                self.issuer, self.num_channels
+            )));
+        }
+        if self.channel_range.0 > self.channel_range.1 {
+            return Err(MeshError::Spectral(format!(
+                "Plan from {} has an empty channel range",
+                self.issuer
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hop synchronization step exchanged with a neighbor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HopSyncMessage {
+    /// Plan to switch to, unless the receiver holds a newer one
+    Plan(HopPlan),
+    /// Receipt of a plan
+    Ack {
+        /// Issuer of the plan received
+        issuer: String,
+        /// Generation of the plan received
+        generation: u64,
+    },
+    /// Newest plan generation held by the sender, sent at rendezvous
+    Resync {
+        /// Generation of the sender's newest plan, 0 if it has none
+        generation: u64,
+    },
+}
+
+/// Epoch clock, rendezvous and plan distribution settings
+#[derive(Debug, Clone)]
+pub struct HopSyncConfig {
+    /// Length of one epoch, which is also each channel's dwell time
+    pub epoch_duration: Duration,
+    /// Epochs between issuing a plan and switching to it
+    pub lead_epochs: u64,
+    /// Epochs to wait for an acknowledgement before sending a plan again
+    pub retry_epochs: u64,
+    /// Channel tuned to at rendezvous and while out of sync
+    pub rendezvous_channel: ChannelId,
+    /// All nodes meet on the rendezvous channel every this many epochs
+    pub rendezvous_interval: u64,
+    /// Epochs without hearing a neighbor before sync is considered lost
+    pub sync_timeout_epochs: u64,
+    /// Channels in patterns this node issues
+    pub num_channels: usize,
+    /// Range patterns this node issues draw channels from
+    pub channel_range: (ChannelId, ChannelId),
+    /// Lowest trust score of a neighbor plans are exchanged with
+    pub min_trust: f64,
+    /// Furthest a plan's generation may run ahead of the newest one held
+    pub max_generation_jump: u64,
+}
+
+impl Default for HopSyncConfig {
+    fn default() -> Self {
+        Self {
+            epoch_duration: Duration::from_secs(1),
+            lead_epochs: 5,
+            retry_epochs: 1,
+            rendezvous_channel: 0,
+            rendezvous_interval: 10,
+            sync_timeout_epochs: 25,
+            num_channels: 16,
+            channel_range: (1, 64),
+            min_trust: 0.5,
+            max_generation_jump: 16,
+        }
+    }
+}
+
+/// Where a node is tuned for an epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tuning {
+    /// The active pattern's channel for the epoch
+    Pattern,
+    /// The rendezvous channel
+    Rendezvous,
+}
+
+/// Result of handling a hop synchronization message
+#[derive(Debug, Default)]
+pub struct HopSyncOutput {
+    /// Messages to send, by neighbor
+    pub messages: Vec<(String, HopSyncMessage)>,
+    /// Plan that took effect immediately, its switch epoch having passed
+    pub activated: Option<HopPlan>,
+}
+
+/// Messages due and changes that took effect, from `HopSync::tick`
+#[derive(Debug, Default)]
+pub struct HopTick {
+    /// Messages to send, by neighbor
+    pub messages: Vec<(String, HopSyncMessage)>,
+    /// Plan whose switch epoch was reached
+    pub activated: Option<HopPlan>,
+    /// New sync state, if sync was lost or regained since the last tick
+    pub synced: Option<bool>,
+}
+
+/// Plan sent to a neighbor and not yet acknowledged
+struct Delivery {
+    plan: HopPlan,
+    sent_epoch: u64,
+}
+
+/// Agreement on the hopping pattern with trusted neighbors
+pub struct HopSync {
+    config: HopSyncConfig,
+    /// Plan in force
+    active: Option<HopPlan>,
+    /// Plan waiting for its switch epoch
+    pending: Option<HopPlan>,
+    deliveries: HashMap<String, Delivery>,
+    /// Epoch a neighbor was last heard in
+    last_contact: Option<u64>,
+    lost: bool,
+    /// Sync state last reported by `tick`
+    reported_synced: bool,
+}
+
+impl HopSync {
+    /// Create with `config`
+    pub fn new(config: HopSyncConfig) -> Self {
+        Self {
+            config,
+            active: None,
+            pending: None,
+            deliveries: HashMap::new(),
+            last_contact: None,
+            lost: false,
+            reported_synced: true,
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &HopSyncConfig {
+        &self.config
+    }
+
+    /// Epoch containing the Unix time `timestamp_ms`
+    pub fn epoch_at(&self, timestamp_ms: u64) -> u64 {
+        timestamp_ms / self.config.epoch_duration.as_millis().max(1) as u64
+    }
+
+    /// Plan in force
+    pub fn active(&self) -> Option<&HopPlan> {
+        self.active.as_ref()
+    }
+
+    /// Plan waiting for its switch epoch
+    pub fn pending(&self) -> Option<&HopPlan> {
+        self.pending.as_ref()
+    }
+
+    /// Whether a neighbor has been heard within the sync timeout
+    pub fn is_synced(&self) -> bool {
+        !self.lost
+    }
+
+    /// Generation for the next plan this node issues
+    pub fn next_generation(&self) -> u64 {
+        self.newest().map_or(1, |plan| plan.generation + 1)
+    }
+
+    /// Whether this node may issue a plan at `epoch`
+    ///
+    /// Not while out of sync, while a plan is pending, or within
+    /// `lead_epochs` of the last switch, so a jammed swarm does not churn
+    /// through patterns faster than it can measure them.
+    pub fn may_propose(&self, epoch: u64) -> bool {
+        !self.lost
+            && self.pending.is_none()
+            && self
+                .active
+                .as_ref()
+                .is_none_or(|plan| epoch >= plan.switch_epoch + self.config.lead_epochs)
+    }
+
+    /// Where to be tuned for `epoch`
+    pub fn tuning(&self, epoch: u64) -> Tuning {
+        if self.lost
+            || self.active.is_none()
+            || epoch.is_multiple_of(self.config.rendezvous_interval.max(1))
+        {
+            Tuning::Rendezvous
+        } else {
+            Tuning::Pattern
+        }
+    }
+
+    /// Take on `plan`, issued by this node, and send it to `neighbors`
+    pub fn propose(
+        &mut self,
+        plan: HopPlan,
+        epoch: u64,
+        neighbors: &[String],
+    ) -> Vec<(String, HopSyncMessage)> {
+        self.adopt(plan.clone(), epoch);
+        self.deliver(&plan, epoch, neighbors, &[])
+    }
+
+    /// Record that a neighbor was heard in `epoch`
+    pub fn contact(&mut self, epoch: u64) {
+        self.last_contact = Some(epoch);
+        self.lost = false;
+    }
+
+    /// Forget deliveries to `node_id`
+    pub fn remove(&mut self, node_id: &str) {
+        self.deliveries.remove(node_id);
+    }
+
+    /// Switch plans, resend unacknowledged ones and check for lost sync
+    ///
+    /// `neighbors` are the trusted neighbors plans are exchanged with.
+    pub fn tick(&mut self, epoch: u64, neighbors: &[String]) -> HopTick {
+        let mut tick = HopTick::default();
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|plan| plan.switch_epoch <= epoch)
+        {
+            self.active = self.pending.take();
+            tick.activated = self.active.clone();
+        }
+
+        // Past the switch a neighbor without the plan has to catch up at
+        // rendezvous instead
+        self.deliveries.retain(|node_id, delivery| {
+            neighbors.contains(node_id) && delivery.plan.switch_epoch > epoch
+        });
+        let retry_epochs = self.config.retry_epochs.max(1);
+        for (node_id, delivery) in &mut self.deliveries {
+            if delivery.sent_epoch + retry_epochs <= epoch {
+                delivery.sent_epoch = epoch;
+                tick.messages
+                    .push((node_id.clone(), HopSyncMessage::Plan(delivery.plan.clone())));
+            }
+        }
+
+        // Alone there is nobody to lose sync with
+        let last_contact = *self.last_contact.get_or_insert(epoch);
+        if neighbors.is_empty() {
+            self.contact(epoch);
+        } else if !self.lost
+            && epoch.saturating_sub(last_contact) >= self.config.sync_timeout_epochs
+        {
+            debug!(epoch, last_contact, "Hop sync lost");
+            self.lost = true;
+        }
+
+        if self.tuning(epoch) == Tuning::Rendezvous {
+            let generation = self.newest().map_or(0, |plan| plan.generation);
+            tick.messages.extend(
+                neighbors
+                    .iter()
+                    .map(|node_id| (node_id.clone(), HopSyncMessage::Resync { generation })),
+            );
+        }
+
+        if self.is_synced() != self.reported_synced {
+            self.reported_synced = self.is_synced();
+            tick.synced = Some(self.reported_synced);
+        }
+        tick
+    }
+
+    /// Handle a message from trusted neighbor `from` in `epoch`
+    ///
+    /// Plan signatures are the caller's to check.
+    pub fn handle(
+        &mut self,
+        from: &str,
+        message: HopSyncMessage,
+        epoch: u64,
+        neighbors: &[String],
+    ) -> MeshResult<HopSyncOutput> {
+        self.contact(epoch);
+        let mut output = HopSyncOutput::default();
+        match message {
+            HopSyncMessage::Plan(plan) => {
+                plan.validate()?;
+                // A plan far ahead of ours would outrank every honest plan
+                // issued after it
+                let held = self.newest().map_or(0, |plan| plan.generation);
+                if plan.generation > held.saturating_add(self.config.max_generation_jump) {
+                    return Err(MeshError::Spectral(format!(
+                        "Plan from {} jumps to generation {} from {}",
+                        plan.issuer, plan.generation, held
+                    )));
+                }
+                let ack = HopSyncMessage::Ack {
+                    issuer: plan.issuer.clone(),
+                    generation: plan.generation,
+                };
+                match self.newest() {
+                    Some(newest) if !plan.supersedes(newest) => {
+                        let known = newest.is(&plan)
+                            || self.active.as_ref().is_some_and(|active| active.is(&plan));
+                        // A sender behind us is sent what superseded its plan
+                        let reply = if known {
+                            ack
+                        } else {
+                            HopSyncMessage::Plan(newest.clone())
+                        };
+                        output.messages.push((from.to_string(), reply));
+                    }
+                    _ => {
+                        debug!(issuer = %plan.issuer, generation = plan.generation, "Hop plan taken");
+                        output.activated = self.adopt(plan.clone(), epoch);
+                        self.deliveries
+                            .retain(|_, delivery| !plan.supersedes(&delivery.plan));
+                        if plan.switch_epoch > epoch {
+                            output.messages =
+                                self.deliver(&plan, epoch, neighbors, &[from, &plan.issuer]);
+                        }
+                        output.messages.push((from.to_string(), ack));
+                    }
+                }
+            }
+            HopSyncMessage::Ack { issuer, generation } => {
+                if self.deliveries.get(from).is_some_and(|delivery| {
+                    delivery.plan.issuer == issuer && delivery.plan.generation == generation
+                }) {
+                    self.deliveries.remove(from);
+                }
+            }
+            HopSyncMessage::Resync { generation } => {
+                let newer: Vec<&HopPlan> = [self.active.as_ref(), self.pending.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|plan| plan.generation > generation)
+                    .collect();
+                if !newer.is_empty() {
+                    output.messages.extend(
+                        newer
+                            .into_iter()
+                            .map(|plan| (from.to_string(), HopSyncMessage::Plan(plan.clone()))),
+                    );
+                } else if self.newest().map_or(0, |plan| plan.generation) < generation {
+                    // The sender is ahead, so ask for its plans in turn
+                    let generation = self.newest().map_or(0, |plan| plan.generation);
+                    output
+                        .messages
+                        .push((from.to_string(), HopSyncMessage::Resync { generation }));
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Newest plan held, pending or active
+    fn newest(&self) -> Option<&HopPlan> {
+        self.pending.as_ref().or(self.active.as_ref())
+    }
+
+    /// Hold `plan` until its switch epoch, or put it in force if that has
+    /// passed, returning it in that case
+    fn adopt(&mut self, plan: HopPlan, epoch: u64) -> Option<HopPlan> {
+        if plan.switch_epoch <= epoch {
+            self.pending = None;
+            self.active = Some(plan.clone());
+            Some(plan)
+        } else {
+            self.pending = Some(plan);
+            None
+        }
+    }
+
+    /// Send `plan` to `neighbors` other than `exclude`, awaiting their
+    /// acknowledgement
+    fn deliver(
+        &mut self,
+        plan: &HopPlan,
+        epoch: u64,
+        neighbors: &[String],
+        exclude: &[&str],
+    ) -> Vec<(String, HopSyncMessage)> {
+        neighbors
+            .iter()
+            .filter(|node_id| !exclude.contains(&node_id.as_str()))
+            .map(|node_id| {
+                self.deliveries.insert(
+                    node_id.clone(),
+                    Delivery {
+                        plan: plan.clone(),
+                        sent_epoch: epoch,
+                    },
+                );
+                (node_id.clone(), HopSyncMessage::Plan(plan.clone()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(issuer: &str, generation: u64, switch_epoch: u64) -> HopPlan {
+        HopPlan {
+            issuer: issuer.to_string(),
+            generation,
+            seed: vec![generation as u8; 32],
+            num_channels: 8,
+            channel_range: (1, 64),
+            switch_epoch,
+            signature: vec![],
+        }
+    }
+
+    fn neighbors(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn plans_sent(messages: &[(String, HopSyncMessage)]) -> usize {
+        messages
+            .iter()
+            .filter(|(_, message)| matches!(message, HopSyncMessage::Plan(_)))
+            .count()
+    }
+
+    #[test]
+    fn test_plan_ordering() {
+        assert!(plan("node2", 2, 0).supersedes(&plan("node1", 1, 0)));
+        // Same generation: lower issuer wins
+        assert!(plan("node1", 2, 0).supersedes(&plan("node2", 2, 0)));
+        assert!(!plan("node2", 2, 0).supersedes(&plan("node1", 2, 0)));
+        assert!(!plan("node1", 2, 0).supersedes(&plan("node1", 2, 0)));
+
+        // Nodes regenerate the same pattern from a plan
+        let pattern = plan("node1", 3, 0).pattern(100);
+        assert_eq!(pattern.channels, plan("node1", 3, 9).pattern(100).channels);
+        assert_eq!(pattern.pattern_id, "node1-3");
+    }
+
+    #[test]
+    fn test_plan_relayed_and_switched_at_epoch() {
+        let peers = neighbors(&["node1", "node3"]);
+        let mut hop_sync = HopSync::new(HopSyncConfig::default());
+        assert_eq!(hop_sync.tuning(1), Tuning::Rendezvous);
+
+        let output = hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", 1, 5)),
+                1,
+                &peers,
+            )
+            .unwrap();
+        assert!(output.activated.is_none());
+        // Relayed to node3, acknowledged to node1
+        assert_eq!(output.messages.len(), 2);
+        assert!(matches!(
+            &output.messages[0],
+            (node_id, HopSyncMessage::Plan(_)) if node_id == "node3"
+        ));
+        assert!(matches!(
+            &output.messages[1],
+            (node_id, HopSyncMessage::Ack { generation: 1, .. }) if node_id == "node1"
+        ));
+        assert!(!hop_sync.may_propose(1));
+
+        // Unacknowledged, so sent again
+        let tick = hop_sync.tick(2, &peers);
+        assert_eq!(plans_sent(&tick.messages), 1);
+        hop_sync
+            .handle(
+                "node3",
+                HopSyncMessage::Ack {
+                    issuer: "node1".to_string(),
+                    generation: 1,
+                },
+                3,
+                &peers,
+            )
+            .unwrap();
+        assert_eq!(plans_sent(&hop_sync.tick(3, &peers).messages), 0);
+
+        let tick = hop_sync.tick(5, &peers);
+        assert_eq!(tick.activated.unwrap().generation, 1);
+        assert_eq!(hop_sync.tuning(6), Tuning::Pattern);
+        assert_eq!(hop_sync.tuning(10), Tuning::Rendezvous);
+        assert!(!hop_sync.may_propose(6));
+        assert!(hop_sync.may_propose(10));
+        assert_eq!(hop_sync.next_generation(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_plans_converge() {
+        let peers = neighbors(&["node1", "node3"]);
+        let mut hop_sync = HopSync::new(HopSyncConfig::default());
+        assert_eq!(hop_sync.propose(plan("node2", 1, 5), 0, &peers).len(), 2);
+
+        // node1's plan of the same generation wins
+        let output = hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", 1, 5)),
+                0,
+                &peers,
+            )
+            .unwrap();
+        assert!(matches!(
+            output.messages.last(),
+            Some((_, HopSyncMessage::Ack { .. }))
+        ));
+        assert_eq!(hop_sync.pending().unwrap().issuer, "node1");
+
+        // A losing plan is answered with the winner
+        let output = hop_sync
+            .handle(
+                "node3",
+                HopSyncMessage::Plan(plan("node2", 1, 5)),
+                1,
+                &peers,
+            )
+            .unwrap();
+        match &output.messages[..] {
+            [(node_id, HopSyncMessage::Plan(winner))] => {
+                assert_eq!(node_id, "node3");
+                assert_eq!(winner.issuer, "node1");
+            }
+            other => panic!("Expected the winning plan, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lost_sync_and_resync_at_rendezvous() {
+        let config = HopSyncConfig::default();
+        let timeout = config.sync_timeout_epochs;
+        let peers = neighbors(&["node1"]);
+        let mut hop_sync = HopSync::new(config);
+        hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", 1, 1)),
+                1,
+                &peers,
+            )
+            .unwrap();
+        assert_eq!(hop_sync.tuning(2), Tuning::Pattern);
+        assert!(hop_sync.tick(2, &peers).synced.is_none());
+
+        let tick = hop_sync.tick(1 + timeout, &peers);
+        assert_eq!(tick.synced, Some(false));
+        assert_eq!(hop_sync.tuning(2 + timeout), Tuning::Rendezvous);
+        assert!(!hop_sync.may_propose(2 + timeout));
+        // Out of sync, the newest generation held is sent every tick
+        assert!(matches!(
+            hop_sync.tick(2 + timeout, &peers).messages[..],
+            [(_, HopSyncMessage::Resync { generation: 1 })]
+        ));
+
+        // node1 answers with the plan that has since taken effect
+        let epoch = 40;
+        let output = hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", 2, 30)),
+                epoch,
+                &peers,
+            )
+            .unwrap();
+        assert_eq!(output.activated.unwrap().generation, 2);
+        assert_eq!(hop_sync.tuning(epoch + 1), Tuning::Pattern);
+        assert_eq!(hop_sync.tick(epoch + 1, &peers).synced, Some(true));
+
+        // And a peer behind us is sent it
+        let output = hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Resync { generation: 1 },
+                42,
+                &peers,
+            )
+            .unwrap();
+        assert!(matches!(
+            &output.messages[..],
+            [(_, HopSyncMessage::Plan(plan))] if plan.generation == 2
+        ));
+    }
+
+    #[test]
+    fn test_invalid_plan_rejected() {
+        let mut hop_sync = HopSync::new(HopSyncConfig::default());
+        let mut empty = plan("node1", 1, 5);
+        empty.num_channels = 0;
+        assert!(hop_sync
+            .handle("node1", HopSyncMessage::Plan(empty), 0, &[])
+            .is_err());
+        let mut inverted = plan("node1", 1, 5);
+        inverted.channel_range = (10, 1);
+        assert!(hop_sync
+            .handle("node1", HopSyncMessage::Plan(inverted), 0, &[])
+            .is_err());
+        assert!(hop_sync.pending().is_none());
+
+        // Generations may only run so far ahead of the newest held
+        let jump = HopSyncConfig::default().max_generation_jump;
+        assert!(hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", jump + 1, 5)),
+                0,
+                &[]
+            )
+            .is_err());
+        assert!(hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", jump, 5)),
+                0,
+                &[]
+            )
+            .is_ok());
+        assert!(hop_sync
+            .handle(
+                "node1",
+                HopSyncMessage::Plan(plan("node1", 2 * jump, 5)),
+                0,
+                &[]
+            )
+            .is_ok());
+    }
+}
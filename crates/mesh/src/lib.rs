@@ -4,11 +4,18 @@
 //!
 //! # Core Components
 //!
-//! - **Peer Discovery**: Decentralized node discovery using mDNS and seed peers
-//! - **Aetheric Whisper**: Gossip protocol for state propagation without central coordination
+//! - **Peer Discovery**: Decentralized node discovery using mDNS and seed peers,
+//!   admitting peers only after a mutual attestation handshake
+//! - **Aetheric Whisper**: Signed, rate-limited gossip for state propagation without central
+//!   coordination; divergent chains it reports are reconciled by fork resolution, and nodes caught
+//!   equivocating are slashed
 //! - **Weaver Ant Routing**: Multi-hop routing with cost-based metrics
-//! - **Spectral Agility**: Coordinated frequency hopping for EW hardening
-//! - **Bunker Mode**: Offline-first persistence for network isolation scenarios
+//! - **Spectral Agility**: Coordinated frequency hopping for EW hardening; new patterns are
+//!   agreed swarm-wide with a switch-over epoch, with a rendezvous channel to fall back on
+//! - **Secure Links**: Per-peer encrypted sessions, re-keyed as their epochs expire
+//! - **Bunker Mode**: Offline-first persistence for network isolation scenarios, reconciled
+//!   with peers by store-and-forward sync once reconnected
+//! - **Transport**: UDP and in-process loopback links, driven by `MeshEventLoop`
 //!
 //! # Design Principles
 //!
@@ -39,23 +46,56 @@
 #![warn(missing_docs)]
 
 pub mod bunker;
+mod clock;
+pub mod discovery;
 pub mod error;
+pub mod event_loop;
+pub mod fork;
 pub mod gossip;
+pub mod hop_sync;
+pub mod link;
 pub mod network;
 pub mod peer;
 pub mod routing;
 pub mod security;
+pub mod sim;
 pub mod spectral;
+pub mod sync;
 pub mod tactical;
+pub mod transport;
 
 // Re-export main types
 pub use bunker::{BunkerMode, BunkerState, StoredBlock, StoredEvent};
+#[cfg(feature = "mdns")]
+pub use discovery::MdnsDiscovery;
+pub use discovery::{
+    AttestationMessage, DiscoveryConfig, DiscoveryOutput, Hello, NodeAnnouncement, PeerDiscovery,
+};
 pub use error::{MeshError, MeshResult};
-pub use gossip::{AethericWhisper, ConsensusView, GossipMessage, GossipResult};
-pub use peer::{PeerInfo, PeerTable};
-pub use routing::{LinkQuality, RouteEntry, RouteUpdateResult, RoutingTable};
+pub use event_loop::{MeshEvent, MeshEventLoop, MeshHandle};
+pub use fork::{
+    choose_branch, Branch, ForkConfig, ForkConflict, ForkMessage, ForkOutcome, ForkOutput,
+    ForkResolver, ForkTick,
+};
+pub use gossip::{AethericWhisper, ConsensusView, GossipConfig, GossipMessage, GossipResult};
+pub use hop_sync::{
+    HopPlan, HopSync, HopSyncConfig, HopSyncMessage, HopSyncOutput, HopTick, Tuning,
+};
+pub use link::{LinkConfig, LinkHandshake, LinkMaintenance, LinkSessions, SealedPayload};
+pub use peer::{node_id_for_key, PeerInfo, PeerTable};
+pub use routing::{
+    AdvertisedRoute, LinkQuality, RouteEntry, RouteUpdateResult, RoutingTable, MAX_HOP_COUNT,
+};
 pub use security::{MeshSecurity, SignedMessage};
+pub use sim::{
+    ByzantineBehavior, Jamming, LinkProfile, Partition, Scenario, SimConfig, SimReport, Simulation,
+};
 pub use spectral::{
     generate_hopping_pattern, FrequencyHopper, HopReason, HopResult, HoppingPattern,
 };
+pub use sync::{BunkerSync, SyncBatch, SyncConfig, SyncMessage, SyncOutput, SyncSummary};
 pub use tactical::{MeshStatus, TacticalMesh};
+pub use transport::{
+    FramePayload, LoopbackNetwork, LoopbackTransport, MeshFrame, MeshTransport, RouteAdvertisement,
+    UdpTransport,
+};
@@ -0,0 +1,570 @@
+//! Secure Links - Encrypted sessions with neighboring mesh nodes
+//!
+//! Every peer link runs an `aethercore_crypto::SessionManager` session.
+//! Once a peer is admitted both ends exchange signed ephemeral X25519 keys,
+//! and from then on all mesh traffic between them is sealed with
+//! ChaCha20-Poly1305, under a separate key for each direction. Every sealed
+//! payload carries its sender's message counter, which is the AEAD nonce;
+//! a payload whose counter was already opened, or has fallen out of the
+//! replay window, is refused. When the session cipher reaches its epoch limits the
+//! link is re-keyed with a fresh exchange; a peer that does not complete
+//! that rotation in time is reported as failed so it can be dropped.
+//!
+//! Hello, attestation and key-exchange frames travel in the clear, since
+//! they are what a session is built from. Signing and verifying the
+//! exchanges is left to the caller, which holds the identity keys.
+
+use crate::clock;
+use aethercore_crypto::{KeyExchangeMessage, SessionManager};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Key exchange step sent to a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkHandshake {
+    /// Signed ephemeral key for the session epoch
+    pub exchange: KeyExchangeMessage,
+    /// Timestamp of the peer's exchange this answers, if it is a reply
+    pub in_reply_to: Option<u64>,
+}
+
+/// Frame payload encrypted for one peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedPayload {
+    /// Session epoch the payload was encrypted in
+    pub epoch: u64,
+    /// Sender's message counter within the epoch, used as the nonce
+    pub counter: u64,
+    /// Encrypted, authenticated payload
+    pub ciphertext: Vec<u8>,
+}
+
+/// Timing and re-keying policy for peer links
+#[derive(Debug, Clone)]
+pub struct LinkConfig {
+    /// How often links are checked for due handshakes and rotations
+    pub maintenance_interval: Duration,
+    /// Time to wait for a peer's key before restarting a handshake
+    pub handshake_timeout: Duration,
+    /// Time a peer has to complete a rotation before the link fails
+    pub rotation_timeout: Duration,
+    /// Messages sealed or opened per epoch before keys are rotated
+    pub max_messages_per_epoch: u64,
+    /// Epoch lifetime before keys are rotated
+    pub max_epoch_duration: Duration,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            maintenance_interval: Duration::from_secs(1),
+            handshake_timeout: Duration::from_secs(5),
+            rotation_timeout: Duration::from_secs(10),
+            max_messages_per_epoch: 10_000,
+            max_epoch_duration: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Handshakes to send and links that failed, from `LinkSessions::maintain`
+#[derive(Debug, Default)]
+pub struct LinkMaintenance {
+    /// Key exchanges to sign and send, by peer node ID
+    pub handshakes: Vec<(String, LinkHandshake)>,
+    /// Peers whose link failed, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+enum LinkState {
+    /// Initial key sent, waiting for the peer's
+    Handshaking { since: Instant },
+    /// Session keys agreed
+    Established,
+    /// Next epoch's key sent, waiting for the peer's; the current
+    /// epoch's keys stay in use until then
+    Rotating { since: Instant },
+    /// The session can no longer be used
+    Failed(String),
+}
+
+struct PeerLink {
+    session: SessionManager,
+    state: LinkState,
+    /// Timestamp of the last exchange sent to the peer
+    sent: u64,
+}
+
+impl PeerLink {
+    fn cipher_epoch(&self) -> Option<u64> {
+        match self.state {
+            LinkState::Established | LinkState::Rotating { .. } => {
+                self.session.cipher().map(|cipher| cipher.epoch())
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `handshake` answers the exchange we are waiting on
+    fn expects(&self, handshake: &LinkHandshake) -> bool {
+        handshake
+            .in_reply_to
+            .is_none_or(|timestamp| timestamp == self.sent)
+    }
+}
+
+/// Encrypted sessions with each peer of a node
+pub struct LinkSessions {
+    node_id: String,
+    config: LinkConfig,
+    links: HashMap<String, PeerLink>,
+    /// Timestamp of the last exchange accepted from each peer
+    last_exchange: HashMap<String, u64>,
+}
+
+impl LinkSessions {
+    /// Create link sessions for `node_id`
+    pub fn new(node_id: String, config: LinkConfig) -> Self {
+        Self {
+            node_id,
+            config,
+            links: HashMap::new(),
+            last_exchange: HashMap::new(),
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &LinkConfig {
+        &self.config
+    }
+
+    /// Whether traffic to `node_id` can be sealed
+    pub fn is_established(&self, node_id: &str) -> bool {
+        self.links
+            .get(node_id)
+            .is_some_and(|link| link.cipher_epoch().is_some())
+    }
+
+    /// Current session epoch with `node_id`
+    pub fn epoch(&self, node_id: &str) -> Option<u64> {
+        self.links.get(node_id).and_then(PeerLink::cipher_epoch)
+    }
+
+    /// Forget the session with `node_id`
+    pub fn remove(&mut self, node_id: &str) {
+        self.links.remove(node_id);
+    }
+
+    /// Start handshakes with `peers` that have no session, rotate keys that
+    /// reached their epoch limits and fail links whose rotation stalled
+    ///
+    /// Sessions with nodes no longer in `peers` are discarded.
+    pub fn maintain(&mut self, peers: &[String]) -> LinkMaintenance {
+        self.links.retain(|node_id, _| peers.contains(node_id));
+
+        let mut maintenance = LinkMaintenance::default();
+        for node_id in peers {
+            let Some(link) = self.links.get_mut(node_id) else {
+                if let Some(handshake) = self.start_handshake(node_id) {
+                    maintenance.handshakes.push((node_id.clone(), handshake));
+                }
+                continue;
+            };
+            match &link.state {
+                LinkState::Handshaking { since }
+                    if clock::now().duration_since(*since) >= self.config.handshake_timeout =>
+                {
+                    debug!(peer_id = %node_id, "Key exchange timed out; restarting");
+                    if let Some(handshake) = self.start_handshake(node_id) {
+                        maintenance.handshakes.push((node_id.clone(), handshake));
+                    }
+                }
+                LinkState::Established if link.session.rotation_required() => {
+                    match link.session.initiate_rotation() {
+                        Ok(exchange) => {
+                            info!(peer_id = %node_id, epoch = exchange.epoch, "Rotating link keys");
+                            link.sent = exchange.timestamp;
+                            link.state = LinkState::Rotating {
+                                since: clock::now(),
+                            };
+                            maintenance.handshakes.push((
+                                node_id.clone(),
+                                LinkHandshake {
+                                    exchange,
+                                    in_reply_to: None,
+                                },
+                            ));
+                        }
+                        Err(e) => link.state = LinkState::Failed(e.to_string()),
+                    }
+                }
+                LinkState::Rotating { since }
+                    if clock::now().duration_since(*since) >= self.config.rotation_timeout =>
+                {
+                    link.state = LinkState::Failed("key rotation timed out".to_string());
+                }
+                _ => {}
+            }
+        }
+
+        self.links.retain(|node_id, link| match &link.state {
+            LinkState::Failed(reason) => {
+                maintenance.failed.push((node_id.clone(), reason.clone()));
+                false
+            }
+            _ => true,
+        });
+        maintenance
+    }
+
+    /// Apply a key exchange from `node_id`, whose signature the caller has
+    /// already verified
+    ///
+    /// Returns the exchange to send back, if the peer is waiting on one.
+    pub fn handle_handshake(
+        &mut self,
+        node_id: &str,
+        handshake: LinkHandshake,
+    ) -> Result<Option<LinkHandshake>, String> {
+        let exchange = &handshake.exchange;
+        if exchange.sender_id != node_id {
+            return Err(format!(
+                "Key exchange from {} relayed by {}",
+                exchange.sender_id, node_id
+            ));
+        }
+        let last = self.last_exchange.get(node_id).copied().unwrap_or(0);
+        if exchange.timestamp <= last {
+            return Err(format!("Replayed key exchange from {}", node_id));
+        }
+
+        let reply = match self.links.get_mut(node_id) {
+            Some(link) => match link.state {
+                LinkState::Handshaking { .. }
+                    if exchange.epoch == 0 && link.expects(&handshake) =>
+                {
+                    link.session
+                        .complete_key_exchange(exchange)
+                        .map_err(|e| format!("Key exchange with {} failed: {}", node_id, e))?;
+                    link.state = LinkState::Established;
+                    info!(peer_id = %node_id, "Secure link established");
+                    None
+                }
+                LinkState::Rotating { .. }
+                    if exchange.epoch == link.session.epoch() && link.expects(&handshake) =>
+                {
+                    if let Err(e) = link.session.complete_rotation(exchange) {
+                        link.state = LinkState::Failed(e.to_string());
+                        return Err(format!("Key rotation with {} failed: {}", node_id, e));
+                    }
+                    link.state = LinkState::Established;
+                    debug!(peer_id = %node_id, epoch = exchange.epoch, "Link keys rotated");
+                    None
+                }
+                LinkState::Established
+                    if exchange.epoch == link.session.epoch() + 1
+                        && handshake.in_reply_to.is_none() =>
+                {
+                    let ours = link
+                        .session
+                        .initiate_rotation()
+                        .and_then(|ours| link.session.complete_rotation(exchange).map(|()| ours));
+                    let ours = match ours {
+                        Ok(ours) => ours,
+                        Err(e) => {
+                            link.state = LinkState::Failed(e.to_string());
+                            return Err(format!("Key rotation with {} failed: {}", node_id, e));
+                        }
+                    };
+                    link.sent = ours.timestamp;
+                    debug!(peer_id = %node_id, epoch = exchange.epoch, "Link keys rotated");
+                    Some(LinkHandshake {
+                        exchange: ours,
+                        in_reply_to: Some(exchange.timestamp),
+                    })
+                }
+                // The peer lost its session with us and is starting over
+                _ if exchange.epoch == 0 && handshake.in_reply_to.is_none() => {
+                    Some(self.accept_session(node_id, exchange)?)
+                }
+                _ => {
+                    return Err(format!(
+                        "Unexpected key exchange for epoch {} from {}",
+                        exchange.epoch, node_id
+                    ))
+                }
+            },
+            None if exchange.epoch == 0 && handshake.in_reply_to.is_none() => {
+                Some(self.accept_session(node_id, exchange)?)
+            }
+            None => {
+                return Err(format!(
+                    "Key exchange for epoch {} from {} without a session",
+                    exchange.epoch, node_id
+                ))
+            }
+        };
+
+        self.last_exchange
+            .insert(node_id.to_string(), handshake.exchange.timestamp);
+        Ok(reply)
+    }
+
+    /// Encrypt `plaintext` for `node_id`
+    pub fn seal(&mut self, node_id: &str, plaintext: &[u8]) -> Result<SealedPayload, String> {
+        let link = self
+            .links
+            .get_mut(node_id)
+            .ok_or_else(|| format!("No session with {}", node_id))?;
+        let epoch = link
+            .cipher_epoch()
+            .ok_or_else(|| format!("Session with {} not established", node_id))?;
+        let (ciphertext, counter) = link
+            .session
+            .encrypt(plaintext)
+            .map_err(|e| format!("Sealing for {} failed: {}", node_id, e))?;
+        Ok(SealedPayload {
+            epoch,
+            counter,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt a payload sealed by `node_id`
+    pub fn open(&mut self, node_id: &str, sealed: &SealedPayload) -> Result<Vec<u8>, String> {
+        let link = self
+            .links
+            .get_mut(node_id)
+            .ok_or_else(|| format!("No session with {}", node_id))?;
+        let epoch = link
+            .cipher_epoch()
+            .ok_or_else(|| format!("Session with {} not established", node_id))?;
+        if sealed.epoch != epoch {
+            return Err(format!(
+                "Payload from {} sealed in epoch {}, session is in epoch {}",
+                node_id, sealed.epoch, epoch
+            ));
+        }
+        link.session
+            .decrypt(&sealed.ciphertext, sealed.counter)
+            .map_err(|e| format!("Opening payload from {} failed: {}", node_id, e))
+    }
+
+    fn new_session(&self) -> SessionManager {
+        SessionManager::new(self.node_id.clone()).with_epoch_limits(
+            self.config.max_messages_per_epoch,
+            self.config.max_epoch_duration,
+        )
+    }
+
+    /// Replace any session with `node_id` by a fresh handshake
+    fn start_handshake(&mut self, node_id: &str) -> Option<LinkHandshake> {
+        let mut session = self.new_session();
+        let exchange = match session.initiate_session() {
+            Ok(exchange) => exchange,
+            Err(e) => {
+                debug!(peer_id = %node_id, "Cannot start key exchange: {}", e);
+                return None;
+            }
+        };
+        self.links.insert(
+            node_id.to_string(),
+            PeerLink {
+                session,
+                state: LinkState::Handshaking {
+                    since: clock::now(),
+                },
+                sent: exchange.timestamp,
+            },
+        );
+        Some(LinkHandshake {
+            exchange,
+            in_reply_to: None,
+        })
+    }
+
+    /// Answer a peer's initial exchange with a new session
+    fn accept_session(
+        &mut self,
+        node_id: &str,
+        exchange: &KeyExchangeMessage,
+    ) -> Result<LinkHandshake, String> {
+        let mut session = self.new_session();
+        let ours = session
+            .initiate_session()
+            .and_then(|ours| session.complete_key_exchange(exchange).map(|()| ours))
+            .map_err(|e| format!("Key exchange with {} failed: {}", node_id, e))?;
+        self.links.insert(
+            node_id.to_string(),
+            PeerLink {
+                session,
+                state: LinkState::Established,
+                sent: ours.timestamp,
+            },
+        );
+        info!(peer_id = %node_id, "Secure link established");
+        Ok(LinkHandshake {
+            exchange: ours,
+            in_reply_to: Some(exchange.timestamp),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessions(node_id: &str, config: LinkConfig) -> LinkSessions {
+        LinkSessions::new(node_id.to_string(), config)
+    }
+
+    /// Deliver handshakes back and forth until neither side has more to say
+    fn exchange(a: &mut LinkSessions, b: &mut LinkSessions, first: Vec<(String, LinkHandshake)>) {
+        let mut pending: Vec<(bool, LinkHandshake)> = first
+            .into_iter()
+            .map(|(_, handshake)| (true, handshake))
+            .collect();
+        while let Some((to_b, handshake)) = pending.pop() {
+            let (receiver, sender) = if to_b {
+                (&mut *b, &a.node_id)
+            } else {
+                (&mut *a, &b.node_id)
+            };
+            let sender = sender.clone();
+            if let Some(reply) = receiver.handle_handshake(&sender, handshake).unwrap() {
+                pending.push((!to_b, reply));
+            }
+        }
+    }
+
+    fn connected(config: LinkConfig) -> (LinkSessions, LinkSessions) {
+        let mut a = sessions("node-a", config.clone());
+        let mut b = sessions("node-b", config);
+        let started = a.maintain(&["node-b".to_string()]);
+        assert_eq!(started.handshakes.len(), 1);
+        exchange(&mut a, &mut b, started.handshakes);
+        (a, b)
+    }
+
+    #[test]
+    fn test_handshake_and_sealing() {
+        let (mut a, mut b) = connected(LinkConfig::default());
+        assert!(a.is_established("node-b"));
+        assert!(b.is_established("node-a"));
+        assert_eq!(a.epoch("node-b"), Some(0));
+
+        let sealed = a.seal("node-b", b"state update").unwrap();
+        assert_ne!(sealed.ciphertext, b"state update");
+        assert_eq!(b.open("node-a", &sealed).unwrap(), b"state update");
+
+        // A payload opens once, and never for its own sender
+        assert!(b.open("node-a", &sealed).is_err());
+        let sealed = a.seal("node-b", b"state update").unwrap();
+        assert!(a.open("node-b", &sealed).is_err());
+        assert_eq!(b.open("node-a", &sealed).unwrap(), b"state update");
+
+        // Only the session partner can open it
+        let mut tampered = sealed.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert!(b.open("node-a", &tampered).is_err());
+        assert!(b.open("node-c", &sealed).is_err());
+        assert!(sessions("node-c", LinkConfig::default())
+            .seal("node-a", b"x")
+            .is_err());
+    }
+
+    #[test]
+    fn test_simultaneous_handshakes_agree() {
+        let mut a = sessions("node-a", LinkConfig::default());
+        let mut b = sessions("node-b", LinkConfig::default());
+        let (_, from_a) = a.maintain(&["node-b".to_string()]).handshakes.remove(0);
+        let (_, from_b) = b.maintain(&["node-a".to_string()]).handshakes.remove(0);
+
+        // Crossing initial exchanges complete both ends without replies
+        assert!(b.handle_handshake("node-a", from_a).unwrap().is_none());
+        assert!(a.handle_handshake("node-b", from_b).unwrap().is_none());
+
+        let sealed = b.seal("node-a", b"hello").unwrap();
+        assert_eq!(a.open("node-b", &sealed).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_keys_rotate_at_epoch_limit() {
+        let config = LinkConfig {
+            max_messages_per_epoch: 2,
+            ..LinkConfig::default()
+        };
+        let (mut a, mut b) = connected(config);
+        let peers = ["node-b".to_string()];
+
+        for _ in 0..2 {
+            let sealed = a.seal("node-b", b"tick").unwrap();
+            b.open("node-a", &sealed).unwrap();
+        }
+        assert!(a.seal("node-b", b"tick").is_err());
+
+        let rotation = a.maintain(&peers);
+        assert_eq!(rotation.handshakes.len(), 1);
+        assert_eq!(rotation.handshakes[0].1.exchange.epoch, 1);
+        exchange(&mut a, &mut b, rotation.handshakes);
+
+        assert_eq!(a.epoch("node-b"), Some(1));
+        assert_eq!(b.epoch("node-a"), Some(1));
+        let sealed = a.seal("node-b", b"after rotation").unwrap();
+        assert_eq!(sealed.epoch, 1);
+        assert_eq!(b.open("node-a", &sealed).unwrap(), b"after rotation");
+        assert!(a.maintain(&peers).handshakes.is_empty());
+    }
+
+    #[test]
+    fn test_stalled_rotation_fails_link() {
+        let config = LinkConfig {
+            rotation_timeout: Duration::ZERO,
+            max_messages_per_epoch: 1,
+            ..LinkConfig::default()
+        };
+        let (mut a, _b) = connected(config);
+        let peers = ["node-b".to_string()];
+        a.seal("node-b", b"tick").unwrap();
+
+        let rotation = a.maintain(&peers);
+        assert_eq!(rotation.handshakes.len(), 1);
+        assert!(rotation.failed.is_empty());
+
+        // node-b never answers
+        let maintenance = a.maintain(&peers);
+        assert_eq!(maintenance.failed.len(), 1);
+        assert_eq!(maintenance.failed[0].0, "node-b");
+        assert!(!a.is_established("node-b"));
+    }
+
+    #[test]
+    fn test_replayed_and_stale_exchanges_rejected() {
+        let mut a = sessions("node-a", LinkConfig::default());
+        let mut b = sessions("node-b", LinkConfig::default());
+        let (_, first) = a.maintain(&["node-b".to_string()]).handshakes.remove(0);
+        let reply = b
+            .handle_handshake("node-a", first.clone())
+            .unwrap()
+            .unwrap();
+
+        // A replayed initial exchange would otherwise reset the session
+        assert!(b.handle_handshake("node-a", first).is_err());
+
+        // A reply to a handshake node-a has since restarted is ignored
+        let restarted = LinkConfig {
+            handshake_timeout: Duration::ZERO,
+            ..LinkConfig::default()
+        };
+        a.config = restarted;
+        let (_, second) = a.maintain(&["node-b".to_string()]).handshakes.remove(0);
+        assert!(a.handle_handshake("node-b", reply).is_err());
+        assert!(!a.is_established("node-b"));
+
+        // Sessions are only accepted from the node that signed the exchange
+        assert!(b.handle_handshake("node-c", second.clone()).is_err());
+        assert!(b.handle_handshake("node-a", second).unwrap().is_some());
+    }
+}
@@ -3,13 +3,18 @@
 //! Implements decentralized node discovery using mDNS for local LAN discovery
 //! and seed peer lists for WAN bootstrapping.
 
+use crate::clock::current_timestamp;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Unique identifier for a node in the mesh
 pub type NodeId = String;
 
+/// Node ID bound to `public_key`: the hex BLAKE3 hash of the key
+pub fn node_id_for_key(public_key: &[u8]) -> NodeId {
+    hex::encode(blake3::hash(public_key).as_bytes())
+}
+
 /// Peer information with trust scoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeerInfo {
@@ -40,6 +45,8 @@ pub struct PeerTable {
     max_peers: usize,
     /// Minimum trust score threshold
     min_trust_score: f64,
+    /// Key each node ID was first admitted with, kept after the peer leaves
+    pinned_keys: HashMap<NodeId, Vec<u8>>,
 }
 
 impl PeerTable {
@@ -50,11 +57,26 @@ impl PeerTable {
             seed_peers,
             max_peers: 100,
             min_trust_score: 0.5,
+            pinned_keys: HashMap::new(),
         }
     }
 
     /// Add or update a peer
+    ///
+    /// A node ID is pinned to the key it is first admitted with; a peer
+    /// presenting any other key under that ID is refused.
     pub fn upsert_peer(&mut self, peer: PeerInfo) -> Result<(), String> {
+        if self
+            .pinned_keys
+            .get(&peer.node_id)
+            .is_some_and(|pinned| *pinned != peer.public_key)
+        {
+            return Err(format!(
+                "Peer {} presented a key other than the one pinned for it",
+                peer.node_id
+            ));
+        }
+
         // Validate trust score
         if peer.trust_score < self.min_trust_score {
             return Err(format!(
@@ -73,10 +95,18 @@ impl PeerTable {
             }
         }
 
+        self.pinned_keys
+            .entry(peer.node_id.clone())
+            .or_insert_with(|| peer.public_key.clone());
         self.peers.insert(peer.node_id.clone(), peer);
         Ok(())
     }
 
+    /// Key pinned for `node_id`, whether or not it is currently a peer
+    pub fn pinned_key(&self, node_id: &str) -> Option<&[u8]> {
+        self.pinned_keys.get(node_id).map(Vec::as_slice)
+    }
+
     /// Get a peer by node ID
     pub fn get_peer(&self, node_id: &str) -> Option<&PeerInfo> {
         self.peers.get(node_id)
@@ -119,6 +149,29 @@ impl PeerTable {
         }
     }
 
+    /// Record that a peer was heard from at `timestamp`
+    ///
+    /// Returns false if the peer is unknown.
+    pub fn touch(&mut self, node_id: &str, timestamp: u64) -> bool {
+        match self.peers.get_mut(node_id) {
+            Some(peer) => {
+                peer.last_seen = peer.last_seen.max(timestamp);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Node IDs of peers not heard from within `max_age_ms`
+    pub fn stale_peers(&self, max_age_ms: u64) -> Vec<NodeId> {
+        let now = current_timestamp();
+        self.peers
+            .values()
+            .filter(|p| now.saturating_sub(p.last_seen) > max_age_ms)
+            .map(|p| p.node_id.clone())
+            .collect()
+    }
+
     /// Get peer count
     pub fn peer_count(&self) -> usize {
         self.peers.len()
@@ -135,14 +188,6 @@ impl PeerTable {
     }
 }
 
-/// Get current timestamp in milliseconds
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,6 +223,23 @@ mod tests {
         assert_eq!(retrieved.node_id, "node1");
     }
 
+    #[test]
+    fn test_peer_key_pinned() {
+        let mut table = PeerTable::new(vec![]);
+        table.upsert_peer(create_test_peer("node1", 0.8)).unwrap();
+
+        let mut impostor = create_test_peer("node1", 0.9);
+        impostor.public_key = vec![5, 6, 7, 8];
+        assert!(table.upsert_peer(impostor.clone()).is_err());
+        assert_eq!(table.get_peer("node1").unwrap().trust_score, 0.8);
+
+        // The pin outlives the peer's membership
+        table.remove_peer("node1");
+        assert!(table.upsert_peer(impostor).is_err());
+        assert_eq!(table.pinned_key("node1"), Some(&[1, 2, 3, 4][..]));
+        table.upsert_peer(create_test_peer("node1", 0.8)).unwrap();
+    }
+
     #[test]
     fn test_reject_low_trust_peer() {
         let mut table = PeerTable::new(vec![]);
@@ -207,6 +269,21 @@ mod tests {
         assert!(!table.is_bunker_mode());
     }
 
+    #[test]
+    fn test_stale_peers_and_touch() {
+        let mut table = PeerTable::new(vec![]);
+        let mut peer = create_test_peer("node1", 0.8);
+        peer.last_seen = current_timestamp() - 60000;
+        table.upsert_peer(peer).unwrap();
+        table.upsert_peer(create_test_peer("node2", 0.8)).unwrap();
+
+        assert_eq!(table.stale_peers(30000), vec!["node1".to_string()]);
+
+        assert!(table.touch("node1", current_timestamp()));
+        assert!(table.stale_peers(30000).is_empty());
+        assert!(!table.touch("node3", current_timestamp()));
+    }
+
     #[test]
     fn test_get_routing_peers() {
         let mut table = PeerTable::new(vec![]);
@@ -3,9 +3,14 @@
 //! Implements cost-based routing where nodes can act as bridges when
 //! direct links are unavailable.
 
+use crate::clock::current_timestamp;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+
+/// Longest route accepted; routes that would exceed it are unreachable
+///
+/// Bounds count-to-infinity when a destination disappears.
+pub const MAX_HOP_COUNT: u8 = 15;
 
 /// Route entry in the routing table
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +27,19 @@ pub struct RouteEntry {
     pub last_update: u64,
 }
 
+/// Route as advertised to a neighbor (distance vector entry)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdvertisedRoute {
+    /// Destination node ID
+    pub destination: String,
+    /// Advertiser's cost to the destination; `None` if unreachable
+    pub cost: Option<f64>,
+    /// Advertiser's hop count to the destination
+    pub hop_count: u8,
+    /// Destination's identity key as known to the advertiser; empty if unknown
+    pub public_key: Vec<u8>,
+}
+
 /// Routing table with multi-hop capability
 #[derive(Debug)]
 pub struct RoutingTable {
@@ -136,6 +154,128 @@ impl RoutingTable {
         }
     }
 
+    /// Routes to advertise to `neighbor`
+    ///
+    /// Split horizon with poison reverse: routes whose next hop is
+    /// `neighbor` are advertised back to it as unreachable, so it never
+    /// routes through us to reach them. Destination keys are left empty
+    /// for the caller to fill in.
+    pub fn advertised_routes_for(&self, neighbor: &str) -> Vec<AdvertisedRoute> {
+        self.get_routes()
+            .into_iter()
+            .filter(|route| route.destination != neighbor)
+            .map(|route| AdvertisedRoute {
+                destination: route.destination.clone(),
+                cost: (route.next_hop != neighbor).then_some(route.cost),
+                hop_count: route.hop_count,
+                public_key: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Apply a neighbor's full route advertisement
+    ///
+    /// Each reachable route costs the advertised cost plus the cost of the
+    /// link to `from`. Routes already through `from` follow its latest
+    /// cost, better or worse; routes through `from` that are advertised as
+    /// unreachable, exceed `MAX_HOP_COUNT` or are missing from the
+    /// advertisement are withdrawn. Returns the number of routes added,
+    /// updated or withdrawn.
+    pub fn apply_advertisement(
+        &mut self,
+        from: &str,
+        routes: &[AdvertisedRoute],
+    ) -> Result<usize, String> {
+        let link_quality = self
+            .neighbors
+            .get(from)
+            .ok_or_else(|| format!("{} is not a neighbor", from))?;
+        let link_cost = link_quality.compute_cost();
+        let now = current_timestamp();
+
+        // Hearing from a neighbor keeps the direct route to it alive
+        match self.routes.get_mut(from) {
+            Some(route) if route.next_hop == from => route.last_update = now,
+            Some(_) => {}
+            None => {
+                self.routes.insert(
+                    from.to_string(),
+                    RouteEntry {
+                        destination: from.to_string(),
+                        next_hop: from.to_string(),
+                        cost: link_cost,
+                        hop_count: 1,
+                        last_update: now,
+                    },
+                );
+            }
+        }
+
+        let mut changed = 0;
+        let mut advertised = HashSet::new();
+        for route in routes {
+            if route.destination == self.node_id || route.destination == from {
+                continue;
+            }
+            advertised.insert(route.destination.as_str());
+
+            let hop_count = route.hop_count.saturating_add(1);
+            let cost = route
+                .cost
+                .filter(|_| hop_count <= MAX_HOP_COUNT)
+                .map(|cost| cost + link_cost);
+            let current = self.routes.get(&route.destination);
+            let via_from = current.is_some_and(|r| r.next_hop == from);
+
+            match cost {
+                None if via_from => {
+                    self.routes.remove(&route.destination);
+                    changed += 1;
+                }
+                None => {}
+                Some(cost) => {
+                    if let Some(current) = current {
+                        let stale = now.saturating_sub(current.last_update) > self.max_route_age_ms;
+                        if via_from {
+                            if current.hop_count != hop_count
+                                || (current.cost - cost).abs() > f64::EPSILON
+                            {
+                                changed += 1;
+                            }
+                        } else if cost >= current.cost && !stale {
+                            continue;
+                        } else {
+                            changed += 1;
+                        }
+                    } else {
+                        changed += 1;
+                    }
+                    self.routes.insert(
+                        route.destination.clone(),
+                        RouteEntry {
+                            destination: route.destination.clone(),
+                            next_hop: from.to_string(),
+                            cost,
+                            hop_count,
+                            last_update: now,
+                        },
+                    );
+                }
+            }
+        }
+
+        // Anything `from` no longer advertises is no longer reachable through it
+        let before = self.routes.len();
+        self.routes.retain(|destination, route| {
+            route.next_hop != from
+                || destination == from
+                || advertised.contains(destination.as_str())
+        });
+        changed += before - self.routes.len();
+
+        Ok(changed)
+    }
+
     /// Find the next hop for a destination
     pub fn find_next_hop(&self, destination: &str) -> Option<String> {
         // Check for stale routes
@@ -200,14 +340,6 @@ pub enum RouteUpdateResult {
     Rejected(String),
 }
 
-/// Get current timestamp in milliseconds
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,6 +442,136 @@ mod tests {
         }
     }
 
+    fn advertised(destination: &str, cost: Option<f64>, hop_count: u8) -> AdvertisedRoute {
+        AdvertisedRoute {
+            destination: destination.to_string(),
+            cost,
+            hop_count,
+            public_key: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_advertised_routes_poison_reverse() {
+        let mut table = RoutingTable::new("node1".to_string());
+        table.update_neighbor("node2".to_string(), create_test_link_quality());
+        table.update_neighbor("node3".to_string(), create_test_link_quality());
+        table
+            .apply_advertisement("node2", &[advertised("node4", Some(1.0), 1)])
+            .unwrap();
+
+        let to_node2 = table.advertised_routes_for("node2");
+        // node4 is reached through node2, so it is poisoned towards node2
+        assert!(to_node2.contains(&advertised("node4", None, 2)));
+        // A neighbor is never told about itself
+        assert!(to_node2.iter().all(|r| r.destination != "node2"));
+        assert!(to_node2
+            .iter()
+            .any(|r| r.destination == "node3" && r.cost.is_some()));
+
+        let to_node3 = table.advertised_routes_for("node3");
+        let node4 = to_node3.iter().find(|r| r.destination == "node4").unwrap();
+        assert!(node4.cost.is_some());
+        assert_eq!(node4.hop_count, 2);
+    }
+
+    #[test]
+    fn test_apply_advertisement_accumulates_cost() {
+        let mut table = RoutingTable::new("node1".to_string());
+        let link = create_test_link_quality();
+        let link_cost = link.compute_cost();
+        table.update_neighbor("node2".to_string(), link);
+
+        let changed = table
+            .apply_advertisement("node2", &[advertised("node3", Some(2.0), 1)])
+            .unwrap();
+        assert_eq!(changed, 1);
+
+        let route = &table.routes["node3"];
+        assert_eq!(route.next_hop, "node2");
+        assert_eq!(route.hop_count, 2);
+        assert!((route.cost - (2.0 + link_cost)).abs() < 1e-9);
+
+        // Re-advertising the same route is not a change
+        let changed = table
+            .apply_advertisement("node2", &[advertised("node3", Some(2.0), 1)])
+            .unwrap();
+        assert_eq!(changed, 0);
+
+        assert!(table.apply_advertisement("node9", &[]).is_err());
+    }
+
+    #[test]
+    fn test_apply_advertisement_prefers_cheaper_route() {
+        let mut table = RoutingTable::new("node1".to_string());
+        table.update_neighbor("node2".to_string(), create_test_link_quality());
+        table.update_neighbor("node3".to_string(), create_test_link_quality());
+
+        table
+            .apply_advertisement("node2", &[advertised("node4", Some(5.0), 1)])
+            .unwrap();
+        table
+            .apply_advertisement("node3", &[advertised("node4", Some(1.0), 1)])
+            .unwrap();
+        assert_eq!(table.find_next_hop("node4").unwrap(), "node3");
+
+        // A more expensive alternative does not displace the current route
+        table
+            .apply_advertisement("node2", &[advertised("node4", Some(3.0), 1)])
+            .unwrap();
+        assert_eq!(table.find_next_hop("node4").unwrap(), "node3");
+
+        // The current next hop's cost is followed even when it gets worse
+        table
+            .apply_advertisement("node3", &[advertised("node4", Some(9.0), 1)])
+            .unwrap();
+        assert_eq!(table.find_next_hop("node4").unwrap(), "node3");
+        assert!(table.routes["node4"].cost > 9.0);
+    }
+
+    #[test]
+    fn test_apply_advertisement_withdraws_routes() {
+        let mut table = RoutingTable::new("node1".to_string());
+        table.update_neighbor("node2".to_string(), create_test_link_quality());
+        table
+            .apply_advertisement(
+                "node2",
+                &[
+                    advertised("node3", Some(1.0), 1),
+                    advertised("node4", Some(2.0), 2),
+                ],
+            )
+            .unwrap();
+
+        // Poisoned route is withdrawn, missing route is withdrawn
+        let changed = table
+            .apply_advertisement("node2", &[advertised("node3", None, 1)])
+            .unwrap();
+        assert_eq!(changed, 2);
+        assert!(table.find_next_hop("node3").is_none());
+        assert!(table.find_next_hop("node4").is_none());
+        // The direct route to the advertiser survives
+        assert_eq!(table.find_next_hop("node2").unwrap(), "node2");
+    }
+
+    #[test]
+    fn test_apply_advertisement_hop_limit() {
+        let mut table = RoutingTable::new("node1".to_string());
+        table.update_neighbor("node2".to_string(), create_test_link_quality());
+
+        table
+            .apply_advertisement(
+                "node2",
+                &[
+                    advertised("near", Some(1.0), MAX_HOP_COUNT - 1),
+                    advertised("far", Some(1.0), MAX_HOP_COUNT),
+                ],
+            )
+            .unwrap();
+        assert_eq!(table.routes["near"].hop_count, MAX_HOP_COUNT);
+        assert!(table.find_next_hop("far").is_none());
+    }
+
     #[test]
     fn test_cost_calculation() {
         let good_link = LinkQuality {
@@ -2,7 +2,12 @@
 //!
 //! Integrates with aethercore-crypto for TPM-based signing and verification
 
+use crate::clock::current_timestamp;
+use crate::gossip::GossipMessage;
+use crate::hop_sync::HopPlan;
+use aethercore_core::{ByzantineFaultType, SlashingEvent};
 use aethercore_crypto::signing::EventSigningService;
+use aethercore_crypto::KeyExchangeMessage;
 use aethercore_identity::{
     Attestation, AttestationKey, Certificate, PlatformIdentity, TpmManager, TpmQuote,
     TrustChainValidator,
@@ -64,22 +69,80 @@ impl MeshSecurity {
         signature: &[u8],
         public_key: &[u8],
     ) -> Result<bool, String> {
-        if signature.len() != 64 {
-            return Err("Invalid signature length".to_string());
-        }
-        if public_key.len() != 32 {
-            return Err("Invalid public key length".to_string());
-        }
-
         let hash = blake3::hash(update);
+        verify_ed25519(hash.as_bytes(), signature, public_key)
+    }
+
+    /// Sign a session key exchange with the node's identity key
+    pub fn sign_key_exchange(&mut self, exchange: &mut KeyExchangeMessage) -> Result<(), String> {
+        let service = self
+            .signing_service
+            .as_mut()
+            .ok_or_else(|| "No signing service configured".to_string())?;
+
+        exchange.signature = service
+            .sign_message(&exchange.message_to_sign())
+            .map_err(|e| format!("Signing error: {}", e))?;
+        Ok(())
+    }
+
+    /// Verify a peer's session key exchange against its identity key
+    pub fn verify_key_exchange(
+        &self,
+        exchange: &KeyExchangeMessage,
+        public_key: &[u8],
+    ) -> Result<bool, String> {
+        verify_ed25519(&exchange.message_to_sign(), &exchange.signature, public_key)
+    }
+
+    /// Sign a gossip message originated by this node
+    pub fn sign_gossip(&mut self, message: &mut GossipMessage) -> Result<(), String> {
+        message.signature = self.sign_routing_update(&message.signing_bytes())?;
+        Ok(())
+    }
 
-        let mut key_array = [0u8; 32];
-        key_array.copy_from_slice(public_key);
-        let verifying_key = VerifyingKey::from_bytes(&key_array)
-            .map_err(|e| format!("Invalid public key: {}", e))?;
+    /// Verify a gossip message against its source's identity key
+    pub fn verify_gossip(
+        &self,
+        message: &GossipMessage,
+        public_key: &[u8],
+    ) -> Result<bool, String> {
+        self.verify_routing_update(&message.signing_bytes(), &message.signature, public_key)
+    }
 
-        let signature = Signature::from_bytes(&signature.try_into().unwrap());
-        Ok(verifying_key.verify(hash.as_bytes(), &signature).is_ok())
+    /// Sign a hopping plan issued by this node
+    pub fn sign_hop_plan(&mut self, plan: &mut HopPlan) -> Result<(), String> {
+        plan.signature = self.sign_routing_update(&plan.signing_bytes())?;
+        Ok(())
+    }
+
+    /// Verify a hopping plan against its issuer's identity key
+    pub fn verify_hop_plan(&self, plan: &HopPlan, public_key: &[u8]) -> Result<bool, String> {
+        self.verify_routing_update(&plan.signing_bytes(), &plan.signature, public_key)
+    }
+
+    /// Create a slashing event for `node_id`, signed with the node's identity key
+    pub fn slashing_event(
+        &mut self,
+        node_id: String,
+        fault_type: ByzantineFaultType,
+        evidence: Option<String>,
+    ) -> Result<SlashingEvent, String> {
+        let service = self
+            .signing_service
+            .as_mut()
+            .ok_or_else(|| "No signing service configured".to_string())?;
+
+        let mut event = SlashingEvent::new(
+            node_id,
+            fault_type,
+            service.public_key_id().to_string(),
+            evidence,
+        );
+        event.signature = service
+            .sign_message(&event.event_hash)
+            .map_err(|e| format!("Signing error: {}", e))?;
+        Ok(event)
     }
 
     /// Verify TPM attestation for a peer
@@ -242,10 +305,7 @@ pub struct SignedMessage {
 impl SignedMessage {
     /// Create a new signed message
     pub fn new(payload: Vec<u8>, security: &mut MeshSecurity) -> Result<Self, String> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let timestamp = current_timestamp();
 
         let signature = security.sign_routing_update(&payload)?;
         let public_key = security
@@ -268,15 +328,29 @@ impl SignedMessage {
 
     /// Check if message is too old (replay protection)
     pub fn is_stale(&self, max_age_ms: u64) -> bool {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let now = current_timestamp();
 
         (now - self.timestamp) > max_age_ms
     }
 }
 
+fn verify_ed25519(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, String> {
+    if signature.len() != 64 {
+        return Err("Invalid signature length".to_string());
+    }
+    if public_key.len() != 32 {
+        return Err("Invalid public key length".to_string());
+    }
+
+    let mut key_array = [0u8; 32];
+    key_array.copy_from_slice(public_key);
+    let verifying_key =
+        VerifyingKey::from_bytes(&key_array).map_err(|e| format!("Invalid public key: {}", e))?;
+
+    let signature = Signature::from_bytes(&signature.try_into().unwrap());
+    Ok(verifying_key.verify(message, &signature).is_ok())
+}
+
 fn parse_pcrs(pcrs: &[u8]) -> Result<Vec<Vec<u8>>, String> {
     if pcrs.is_empty() {
         return Err("PCR values are required".to_string());
@@ -379,6 +453,7 @@ mod tests {
             not_after: u64::MAX,
             signature: Vec::new(),
             extensions: HashMap::new(),
+            x509_der: None,
         };
         root_cert.signature = sign_certificate(&root_cert, &root_key);
 
@@ -391,6 +466,7 @@ mod tests {
             not_after: u64::MAX,
             signature: Vec::new(),
             extensions: HashMap::new(),
+            x509_der: None,
         };
         ak_cert.signature = sign_certificate(&ak_cert, &root_key);
 
@@ -408,6 +484,7 @@ mod tests {
             not_after: u64::MAX,
             signature: Vec::new(),
             extensions: HashMap::new(),
+            x509_der: None,
         };
         cert.signature = sign_certificate(&cert, &signing_key);
         let certificate = serde_json::to_vec(&cert).expect("serialize software cert");
@@ -586,6 +663,80 @@ mod tests {
         assert!(!is_valid);
     }
 
+    #[test]
+    fn test_key_exchange_signature() {
+        let signing_service = EventSigningService::new();
+        let identity = PlatformIdentity {
+            id: "test".to_string(),
+            public_key: signing_service.public_key(),
+            attestation: build_software_attestation(),
+            created_at: 1000,
+            metadata: HashMap::new(),
+        };
+        let mut security = MeshSecurity::new().with_signing(signing_service, identity);
+        let public_key = security.identity().unwrap().public_key.clone();
+
+        let mut exchange = aethercore_crypto::SessionManager::new("test".to_string())
+            .initiate_session()
+            .unwrap();
+        security.sign_key_exchange(&mut exchange).unwrap();
+        assert!(security
+            .verify_key_exchange(&exchange, &public_key)
+            .unwrap());
+
+        // The epoch is covered by the signature
+        exchange.epoch += 1;
+        assert!(!security
+            .verify_key_exchange(&exchange, &public_key)
+            .unwrap());
+        assert!(MeshSecurity::new()
+            .sign_key_exchange(&mut exchange)
+            .is_err());
+    }
+
+    #[test]
+    fn test_gossip_and_slashing_signatures() {
+        let signing_service = EventSigningService::new();
+        let identity = PlatformIdentity {
+            id: "test".to_string(),
+            public_key: signing_service.public_key(),
+            attestation: build_software_attestation(),
+            created_at: 1000,
+            metadata: HashMap::new(),
+        };
+        let mut security = MeshSecurity::new().with_signing(signing_service, identity);
+        let public_key = security.identity().unwrap().public_key.clone();
+
+        let mut message = GossipMessage {
+            msg_id: "test-1".to_string(),
+            source_node: "test".to_string(),
+            merkle_root: vec![1, 2, 3],
+            block_height: 5,
+            timestamp: 1000,
+            signature: vec![],
+            hop_count: 0,
+        };
+        security.sign_gossip(&mut message).unwrap();
+        // Relays may increment the hop count, but not change the root
+        message.hop_count = 3;
+        assert!(security.verify_gossip(&message, &public_key).unwrap());
+        message.merkle_root = vec![4, 5, 6];
+        assert!(!security.verify_gossip(&message, &public_key).unwrap());
+
+        let fault_type = ByzantineFaultType::Equivocation {
+            seq_no: 5,
+            hash1: vec![1, 2, 3],
+            hash2: vec![4, 5, 6],
+        };
+        let event = security
+            .slashing_event("node-2".to_string(), fault_type, None)
+            .unwrap();
+        let key: [u8; 32] = public_key.as_slice().try_into().unwrap();
+        assert!(event
+            .verify(&VerifyingKey::from_bytes(&key).unwrap())
+            .is_ok());
+    }
+
     #[test]
     fn test_tpm_attestation_rejects_bad_pcrs() {
         let security = MeshSecurity::new();
@@ -0,0 +1,1348 @@
+//! Mesh Simulator - Deterministic multi-node runs with fault injection
+//!
+//! Runs a swarm of `TacticalMesh` nodes over a virtual radio network on
+//! simulated time, driving each node as `MeshEventLoop` would: peer links
+//! are keyed and every frame sealed, accepted gossip is relayed, routes are
+//! advertised and hopping patterns agreed. All nodes share one band and
+//! hear each other unless the configuration says otherwise:
+//!
+//! - **Latency and loss**: each frame is delayed uniformly within the link
+//!   profile's bounds and lost with its loss probability
+//! - **Link flapping**: time is cut into flap intervals, and in each one a
+//!   link is up with the profile's uptime probability
+//! - **Partitions**: for a span of time, nodes in different groups cannot
+//!   hear each other
+//! - **Jamming**: frames arriving on a jammed channel are lost. Receivers
+//!   see the loss as packet errors, like random loss, and react by agreeing
+//!   on a new hopping pattern; a frame also only arrives if the receiver is
+//!   tuned to the sender's channel
+//! - **Byzantine nodes**: otherwise run the protocol, but forge gossip
+//!   signatures, equivocate or flood their neighbors
+//!
+//! Honest nodes take turns producing blocks. A node announces its head
+//! whenever it changes, as an application would with
+//! `MeshHandle::publish_state`, and nodes that fall behind catch up through
+//! fork resolution. The report covers how long the honest nodes took to
+//! agree on each block, what the traffic cost and what trust each node
+//! kept with the honest ones.
+//!
+//! Runs are reproducible: the mesh reads time from a virtual clock the
+//! simulator advances, and network faults as well as the nodes' own random
+//! choices are drawn from generators seeded from the configuration. Only
+//! session keys and nonces still come from the OS, which changes frame
+//! contents but not what happens to them. Link
+//! profiles for the field health grades are loaded from the degraded RF
+//! configuration with `Scenario::load`.
+
+use crate::bunker::StoredBlock;
+use crate::clock::VirtualClock;
+use crate::error::{MeshError, MeshResult};
+use crate::fork::ForkOutcome;
+use crate::gossip::{GossipConfig, GossipMessage, GossipResult};
+use crate::hop_sync::HopSyncConfig;
+use crate::peer::PeerInfo;
+use crate::security::MeshSecurity;
+use crate::spectral::ChannelId;
+use crate::tactical::TacticalMesh;
+use crate::transport::{FramePayload, MeshFrame};
+use aethercore_crypto::signing::EventSigningService;
+use aethercore_identity::{Attestation, PlatformIdentity};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Unix time, in milliseconds, at which every simulation starts
+const START_MS: u64 = 1_700_000_000_000;
+
+/// Trust each node starts with in every other
+const INITIAL_TRUST: f64 = 0.9;
+
+/// Conditions on every link between two nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkProfile {
+    /// Shortest time a frame takes to arrive
+    pub latency_min: Duration,
+    /// Longest time a frame takes to arrive
+    pub latency_max: Duration,
+    /// Probability that a frame is lost
+    pub loss: f64,
+    /// Probability that a link is up in any flap interval
+    pub uptime: f64,
+    /// Period over which a link stays up or down
+    pub flap_interval: Duration,
+}
+
+impl Default for LinkProfile {
+    fn default() -> Self {
+        Self {
+            latency_min: Duration::from_millis(5),
+            latency_max: Duration::from_millis(20),
+            loss: 0.0,
+            uptime: 1.0,
+            flap_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Network conditions of a field health grade
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    /// Health grade name, such as `contested`
+    pub name: String,
+    /// Worst link conditions the grade allows
+    pub link: LinkProfile,
+    /// Interval between gossiped state updates
+    pub block_interval: Duration,
+    /// Neighbors each gossip message is relayed to
+    pub fanout: usize,
+}
+
+/// The parts of the degraded RF resilience configuration a run uses
+#[derive(Deserialize)]
+struct ResilienceConfig {
+    network_health: NetworkHealth,
+    offline_mesh: OfflineMesh,
+}
+
+#[derive(Deserialize)]
+struct NetworkHealth {
+    health_grades: HashMap<String, HealthGrade>,
+}
+
+#[derive(Deserialize)]
+struct HealthGrade {
+    packet_loss_max_percent: f64,
+    latency_max_ms: u64,
+    uptime_min_percent: f64,
+}
+
+#[derive(Deserialize)]
+struct OfflineMesh {
+    gossip: OfflineGossip,
+}
+
+#[derive(Deserialize)]
+struct OfflineGossip {
+    interval_seconds: u64,
+    fanout: usize,
+}
+
+impl Scenario {
+    /// Load one scenario per network health grade from a degraded RF
+    /// resilience configuration file
+    pub fn load(path: impl AsRef<Path>) -> MeshResult<Vec<Self>> {
+        let yaml = std::fs::read_to_string(path)?;
+        Self::from_yaml(&yaml)
+    }
+
+    /// Parse one scenario per network health grade, best grade first
+    ///
+    /// Each grade is simulated at its limits: its maximum packet loss and
+    /// minimum uptime, with latency spread between the next better grade's
+    /// limit and its own.
+    pub fn from_yaml(yaml: &str) -> MeshResult<Vec<Self>> {
+        let config: ResilienceConfig = serde_yaml::from_str(yaml)
+            .map_err(|e| MeshError::Config(format!("Invalid resilience config: {}", e)))?;
+        let mut grades: Vec<(String, HealthGrade)> =
+            config.network_health.health_grades.into_iter().collect();
+        grades.sort_by(|(a_name, a), (b_name, b)| {
+            a.packet_loss_max_percent
+                .total_cmp(&b.packet_loss_max_percent)
+                .then(a.latency_max_ms.cmp(&b.latency_max_ms))
+                .then_with(|| a_name.cmp(b_name))
+        });
+
+        let gossip = config.offline_mesh.gossip;
+        let mut latency_floor = 0;
+        let mut scenarios = Vec::with_capacity(grades.len());
+        for (name, grade) in grades {
+            let loss = grade.packet_loss_max_percent / 100.0;
+            let uptime = grade.uptime_min_percent / 100.0;
+            if !(0.0..=1.0).contains(&loss) || !(0.0..=1.0).contains(&uptime) {
+                return Err(MeshError::Config(format!(
+                    "Health grade {} has an invalid loss or uptime percentage",
+                    name
+                )));
+            }
+            let latency_min = latency_floor.min(grade.latency_max_ms);
+            latency_floor = grade.latency_max_ms;
+            scenarios.push(Self {
+                name,
+                link: LinkProfile {
+                    latency_min: Duration::from_millis(latency_min),
+                    latency_max: Duration::from_millis(grade.latency_max_ms),
+                    loss,
+                    uptime,
+                    ..LinkProfile::default()
+                },
+                block_interval: Duration::from_secs(gossip.interval_seconds),
+                fanout: gossip.fanout,
+            });
+        }
+        Ok(scenarios)
+    }
+}
+
+/// Span of time during which groups of nodes cannot hear each other
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    /// Simulated time the partition starts
+    pub from: Duration,
+    /// Simulated time the partition heals
+    pub until: Duration,
+    /// Node indices that can still hear each other; nodes in no group are
+    /// cut off entirely
+    pub groups: Vec<Vec<usize>>,
+}
+
+impl Partition {
+    /// Whether nodes `a` and `b` are split apart at `at`
+    fn splits(&self, a: usize, b: usize, at: Duration) -> bool {
+        if at < self.from || at >= self.until {
+            return false;
+        }
+        let group = |node| self.groups.iter().position(|group| group.contains(&node));
+        group(a).is_none() || group(a) != group(b)
+    }
+}
+
+/// Span of time during which a jammer transmits on some channels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jamming {
+    /// Simulated time the jamming starts
+    pub from: Duration,
+    /// Simulated time the jamming stops
+    pub until: Duration,
+    /// Channels jammed
+    pub channels: Vec<ChannelId>,
+}
+
+/// How a Byzantine node misbehaves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByzantineBehavior {
+    /// Gossips blocks it cannot sign for, once per block interval
+    ForgeSignatures,
+    /// Gossips two different blocks for the same height, once per block
+    /// interval
+    Equivocate,
+    /// Re-announces its head every step, well beyond the gossip rate limit
+    Flood,
+}
+
+impl FromStr for ByzantineBehavior {
+    type Err = MeshError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forge" | "forge_signatures" => Ok(Self::ForgeSignatures),
+            "equivocate" => Ok(Self::Equivocate),
+            "flood" => Ok(Self::Flood),
+            _ => Err(MeshError::Config(format!(
+                "Unknown Byzantine behavior {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Everything that determines a simulation run
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// Label for the run in its report
+    pub name: String,
+    /// Seed for every random draw in the run
+    pub seed: u64,
+    /// Number of nodes
+    pub nodes: usize,
+    /// Simulated time to run for
+    pub duration: Duration,
+    /// Granularity at which timers fire and the clock advances
+    pub step: Duration,
+    /// Conditions on every link
+    pub link: LinkProfile,
+    /// Interval at which the next honest node produces a block
+    pub block_interval: Duration,
+    /// Interval between route advertisements
+    pub advertisement_interval: Duration,
+    /// Network partitions
+    pub partitions: Vec<Partition>,
+    /// Jamming
+    pub jamming: Vec<Jamming>,
+    /// Misbehaving nodes, by index
+    pub byzantine: BTreeMap<usize, ByzantineBehavior>,
+    /// Gossip limits on every node
+    pub gossip: GossipConfig,
+    /// Hopping pattern agreement on every node
+    pub hop_sync: HopSyncConfig,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            name: "baseline".to_string(),
+            seed: 0,
+            nodes: 8,
+            duration: Duration::from_secs(60),
+            step: Duration::from_millis(10),
+            link: LinkProfile::default(),
+            block_interval: Duration::from_secs(5),
+            advertisement_interval: crate::event_loop::DEFAULT_ADVERTISEMENT_INTERVAL,
+            partitions: Vec::new(),
+            jamming: Vec::new(),
+            byzantine: BTreeMap::new(),
+            gossip: GossipConfig::default(),
+            hop_sync: HopSyncConfig::default(),
+        }
+    }
+}
+
+impl SimConfig {
+    /// Run under the network conditions of `scenario`
+    pub fn for_scenario(scenario: &Scenario) -> Self {
+        Self {
+            name: scenario.name.clone(),
+            link: scenario.link.clone(),
+            block_interval: scenario.block_interval,
+            gossip: GossipConfig {
+                fanout: scenario.fanout,
+                ..GossipConfig::default()
+            },
+            ..Self::default()
+        }
+    }
+
+    fn validate(&self) -> MeshResult<()> {
+        let invalid = |reason: &str| Err(MeshError::Config(reason.to_string()));
+        if self.nodes < 2 {
+            return invalid("A simulation needs at least two nodes");
+        }
+        if self.byzantine.keys().any(|&node| node >= self.nodes) {
+            return invalid("Byzantine node index out of range");
+        }
+        if self.byzantine.len() == self.nodes {
+            return invalid("A simulation needs at least one honest node");
+        }
+        if self.step.as_millis() == 0 || self.block_interval.as_millis() == 0 {
+            return invalid("Step and block interval must be at least a millisecond");
+        }
+        if self.hop_sync.epoch_duration.as_millis() == 0 {
+            return invalid("Hop epochs must be at least a millisecond");
+        }
+        if self.link.latency_min > self.link.latency_max {
+            return invalid("Minimum latency exceeds maximum latency");
+        }
+        if !(0.0..=1.0).contains(&self.link.loss) || !(0.0..=1.0).contains(&self.link.uptime) {
+            return invalid("Loss and uptime must be probabilities");
+        }
+        if self.link.flap_interval.as_millis() == 0 {
+            return invalid("Flap interval must be at least a millisecond");
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a simulation run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimReport {
+    /// Label of the run
+    pub name: String,
+    /// Seed the run was drawn from
+    pub seed: u64,
+    /// Number of nodes
+    pub nodes: usize,
+    /// Simulated time covered, in milliseconds
+    pub duration_ms: u64,
+    /// How quickly the honest nodes agreed on new blocks
+    pub convergence: ConvergenceReport,
+    /// Traffic the run generated
+    pub overhead: OverheadReport,
+    /// Hopping pattern agreement
+    pub spectral: SpectralReport,
+    /// Trust the honest nodes ended up holding in each node
+    pub trust: Vec<TrustOutcome>,
+}
+
+/// Time from each block's production until every honest node held the same
+/// head at or above its height
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConvergenceReport {
+    /// Blocks produced
+    pub blocks: usize,
+    /// Blocks the honest nodes agreed on before the run ended
+    pub converged: usize,
+    /// Mean convergence time, in milliseconds
+    pub mean_ms: Option<u64>,
+    /// 95th percentile convergence time, in milliseconds
+    pub p95_ms: Option<u64>,
+    /// Slowest convergence time, in milliseconds
+    pub max_ms: Option<u64>,
+    /// Whether the honest nodes held the same head when the run ended
+    pub agreed_at_end: bool,
+}
+
+/// Frames the run put on the air
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverheadReport {
+    /// Frames sent
+    pub frames_sent: u64,
+    /// Bytes sent, framing and encryption included
+    ///
+    /// Frames are JSON, so this varies by a few bytes between runs of the
+    /// same seed with the session keys and nonces in them.
+    pub bytes_sent: u64,
+    /// Frames that reached their receiver
+    pub frames_delivered: u64,
+    /// Frames sent per block produced
+    pub frames_per_block: f64,
+    /// Frames sent by payload type
+    pub frames_by_kind: BTreeMap<String, u64>,
+    /// Frames lost by cause
+    pub frames_lost: BTreeMap<String, u64>,
+}
+
+/// Hopping pattern agreement among the honest nodes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectralReport {
+    /// Distinct hopping plans the honest nodes activated
+    pub plans_activated: usize,
+    /// Node-epochs honest nodes spent out of hop sync
+    pub epochs_out_of_sync: u64,
+    /// Whether the honest nodes ended on the same plan
+    pub agreed_at_end: bool,
+}
+
+/// Standing of one node with the honest nodes at the end of the run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrustOutcome {
+    /// Node ID
+    pub node_id: String,
+    /// How the node misbehaved, if it did
+    pub byzantine: Option<ByzantineBehavior>,
+    /// Mean trust score among honest nodes that still hold it as a peer
+    pub mean_trust: Option<f64>,
+    /// Honest nodes that revoked it
+    pub revoked_by: usize,
+    /// Honest nodes that dropped it as a peer without revoking it
+    pub dropped_by: usize,
+}
+
+/// Why a frame did not reach its receiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Lost {
+    Partition,
+    LinkDown,
+    Loss,
+    Jammed,
+    OffChannel,
+}
+
+impl Lost {
+    fn as_str(self) -> &'static str {
+        match self {
+            Lost::Partition => "partition",
+            Lost::LinkDown => "link_down",
+            Lost::Loss => "loss",
+            Lost::Jammed => "jammed",
+            Lost::OffChannel => "off_channel",
+        }
+    }
+}
+
+/// Frame on its way to a receiver
+struct InFlight {
+    from: usize,
+    to: usize,
+    /// Channel the sender transmitted on
+    channel: Option<ChannelId>,
+    /// Whether random loss garbled it
+    lost: bool,
+    bytes: Vec<u8>,
+}
+
+struct SimNode {
+    mesh: TacticalMesh,
+    behavior: Option<ByzantineBehavior>,
+    /// Frames heard and lost to radio errors this hop epoch
+    heard: u32,
+    garbled: u32,
+    /// Equivocators send their second block a step after the first
+    equivocating: bool,
+}
+
+/// Block produced during the run
+struct Block {
+    height: u64,
+    produced_at: u64,
+    converged_at: Option<u64>,
+}
+
+/// Interval timer on simulated time
+struct Timer {
+    interval: u64,
+    next: u64,
+}
+
+impl Timer {
+    fn new(interval: Duration, first: u64) -> Self {
+        Self {
+            interval: (interval.as_millis() as u64).max(1),
+            next: first,
+        }
+    }
+
+    fn fire(&mut self, now: u64) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.next = now + self.interval;
+        true
+    }
+}
+
+/// A simulation, ready to run
+pub struct Simulation {
+    config: SimConfig,
+}
+
+impl Simulation {
+    /// Prepare a run of `config`
+    pub fn new(config: SimConfig) -> Self {
+        Self { config }
+    }
+
+    /// Run the simulation to the end and report on it
+    ///
+    /// The mesh clock on the calling thread is simulated for the duration.
+    pub fn run(&self) -> MeshResult<SimReport> {
+        self.config.validate()?;
+        let clock = VirtualClock::install(START_MS);
+        let mut world = World::new(&self.config)?;
+        let end = self.config.duration.as_millis() as u64;
+        let step = self.config.step.as_millis() as u64;
+        let mut now = 0;
+        while now < end {
+            clock.advance_to(START_MS + now);
+            world.step(now)?;
+            now += step;
+        }
+        Ok(world.report(end))
+    }
+}
+
+/// Node ID of the node at `index`
+fn node_id(index: usize) -> String {
+    format!("node-{:02}", index)
+}
+
+/// Nodes and the network between them
+struct World<'a> {
+    config: &'a SimConfig,
+    nodes: Vec<SimNode>,
+    index: HashMap<String, usize>,
+    honest: Vec<usize>,
+    /// Frames in flight by arrival time, then send order
+    in_flight: BTreeMap<(u64, u64), InFlight>,
+    sent: u64,
+    rng: StdRng,
+    blocks: Vec<Block>,
+    epoch: Option<u64>,
+    plans: BTreeSet<(String, u64)>,
+    epochs_out_of_sync: u64,
+    frames_sent: u64,
+    bytes_sent: u64,
+    frames_delivered: u64,
+    frames_by_kind: BTreeMap<String, u64>,
+    frames_lost: BTreeMap<String, u64>,
+    link_timer: Timer,
+    sync_timer: Timer,
+    fork_timer: Timer,
+    advertisement_timer: Timer,
+    block_timer: Timer,
+    byzantine_timer: Timer,
+}
+
+impl<'a> World<'a> {
+    fn new(config: &'a SimConfig) -> MeshResult<Self> {
+        let keys: Vec<EventSigningService> = (0..config.nodes)
+            .map(|index| {
+                let mut seed = config.seed.to_be_bytes().to_vec();
+                seed.extend_from_slice(node_id(index).as_bytes());
+                EventSigningService::from_key(blake3::hash(&seed).as_bytes())
+                    .map_err(|e| MeshError::Security(e.to_string()))
+            })
+            .collect::<MeshResult<_>>()?;
+        let peers: Vec<PeerInfo> = keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| PeerInfo {
+                node_id: node_id(index),
+                trust_score: INITIAL_TRUST,
+                latency_ms: config.link.latency_max.as_millis() as u64,
+                last_seen: START_MS,
+                address: node_id(index),
+                public_key: key.public_key(),
+                attestation_verified: true,
+            })
+            .collect();
+
+        let genesis = StoredBlock {
+            hash: StoredBlock::chain_hash(&[], 0, b"genesis"),
+            height: 0,
+            data: b"genesis".to_vec(),
+            timestamp: START_MS,
+            synced: true,
+        };
+        let mut nodes = Vec::with_capacity(config.nodes);
+        for (index, key) in keys.into_iter().enumerate() {
+            let identity = PlatformIdentity {
+                id: node_id(index),
+                public_key: key.public_key(),
+                attestation: Attestation::None,
+                created_at: START_MS,
+                metadata: HashMap::new(),
+            };
+            let mut mesh = TacticalMesh::new(node_id(index), vec![], ":memory:")
+                .map_err(MeshError::BunkerStorage)?
+                .with_security(MeshSecurity::new().with_signing(key, identity))
+                .with_gossip_config(config.gossip.clone())
+                .with_hop_sync_config(config.hop_sync.clone())
+                .with_rng_seed(config.seed ^ index as u64);
+            for peer in peers.iter().filter(|peer| peer.node_id != node_id(index)) {
+                mesh.add_peer(peer.clone()).map_err(MeshError::Peer)?;
+            }
+            mesh.store_block(genesis.clone())
+                .map_err(MeshError::BunkerStorage)?;
+            mesh.update_local_state(genesis.hash.clone(), genesis.height)
+                .map_err(MeshError::Security)?;
+            nodes.push(SimNode {
+                mesh,
+                behavior: config.byzantine.get(&index).copied(),
+                heard: 0,
+                garbled: 0,
+                equivocating: false,
+            });
+        }
+
+        let block_interval = config.block_interval.as_millis() as u64;
+        let mesh = &nodes[0].mesh;
+        let link_timer = Timer::new(mesh.link_config().maintenance_interval, 0);
+        let sync_timer = Timer::new(mesh.sync_config().tick_interval, 0);
+        let fork_timer = Timer::new(mesh.fork_config().tick_interval, 0);
+        Ok(Self {
+            config,
+            index: (0..config.nodes)
+                .map(|index| (node_id(index), index))
+                .collect(),
+            honest: (0..config.nodes)
+                .filter(|index| !config.byzantine.contains_key(index))
+                .collect(),
+            nodes,
+            in_flight: BTreeMap::new(),
+            sent: 0,
+            rng: StdRng::seed_from_u64(config.seed),
+            blocks: Vec::new(),
+            epoch: None,
+            plans: BTreeSet::new(),
+            epochs_out_of_sync: 0,
+            frames_sent: 0,
+            bytes_sent: 0,
+            frames_delivered: 0,
+            frames_by_kind: BTreeMap::new(),
+            frames_lost: BTreeMap::new(),
+            link_timer,
+            sync_timer,
+            fork_timer,
+            advertisement_timer: Timer::new(config.advertisement_interval, 0),
+            block_timer: Timer::new(config.block_interval, block_interval),
+            byzantine_timer: Timer::new(config.block_interval, block_interval + block_interval / 2),
+        })
+    }
+
+    /// Fire due timers, then deliver every frame due by `now`
+    fn step(&mut self, now: u64) -> MeshResult<()> {
+        let epoch = self.nodes[0].mesh.current_epoch();
+        if self.epoch != Some(epoch) {
+            self.epoch = Some(epoch);
+            self.hop_epoch(now, epoch)?;
+        }
+        if self.link_timer.fire(now) {
+            for node in 0..self.nodes.len() {
+                self.maintain_links(now, node);
+            }
+        }
+        if self.sync_timer.fire(now) {
+            for node in 0..self.nodes.len() {
+                let messages = self.nodes[node]
+                    .mesh
+                    .sync_tick()
+                    .map_err(MeshError::BunkerStorage)?;
+                self.send_all(now, node, messages, FramePayload::Sync);
+            }
+        }
+        if self.fork_timer.fire(now) {
+            for node in 0..self.nodes.len() {
+                let tick = self.nodes[node].mesh.fork_tick();
+                self.send_all(now, node, tick.requests, FramePayload::Fork);
+            }
+        }
+        if self.advertisement_timer.fire(now) {
+            for node in 0..self.nodes.len() {
+                self.advertise_routes(now, node)?;
+            }
+        }
+        if self.block_timer.fire(now) {
+            self.produce_block(now)?;
+        }
+        let misbehave = self.byzantine_timer.fire(now);
+        for node in 0..self.nodes.len() {
+            self.misbehave(now, node, misbehave)?;
+        }
+
+        while let Some(entry) = self.in_flight.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let frame = entry.remove();
+            self.deliver(now, frame)?;
+        }
+        self.check_convergence(now);
+        Ok(())
+    }
+
+    /// Report packet errors from the last epoch, then agree and tune for
+    /// the new one
+    fn hop_epoch(&mut self, now: u64, epoch: u64) -> MeshResult<()> {
+        for node in 0..self.nodes.len() {
+            let jammed = self.jammed(self.channel(node), now);
+            let state = &mut self.nodes[node];
+            let received = state.heard + state.garbled;
+            let per = if received > 0 {
+                state.garbled as f64 / received as f64
+            } else if jammed {
+                1.0
+            } else {
+                0.0
+            };
+            state.heard = 0;
+            state.garbled = 0;
+            state.mesh.update_link_metrics(per);
+
+            let tick = state.mesh.hop_tick(epoch).map_err(MeshError::Spectral)?;
+            self.send_all(now, node, tick.messages, FramePayload::HopSync);
+        }
+
+        for &node in &self.honest {
+            let mesh = &self.nodes[node].mesh;
+            if let Some(plan) = mesh.hop_plan() {
+                self.plans.insert((plan.issuer.clone(), plan.generation));
+            }
+            if !mesh.is_hop_synced() {
+                self.epochs_out_of_sync += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn maintain_links(&mut self, now: u64, node: usize) {
+        let maintenance = self.nodes[node].mesh.maintain_links();
+        self.send_all(
+            now,
+            node,
+            maintenance.handshakes,
+            FramePayload::LinkHandshake,
+        );
+        if !maintenance.failed.is_empty() {
+            let _ = self.advertise_routes(now, node);
+        }
+    }
+
+    fn advertise_routes(&mut self, now: u64, node: usize) -> MeshResult<()> {
+        let mesh = &self.nodes[node].mesh;
+        let mut neighbors: Vec<String> = mesh
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.node_id.clone())
+            .filter(|neighbor| mesh.is_link_established(neighbor))
+            .collect();
+        neighbors.sort();
+        for neighbor in neighbors {
+            let advertisement = self.nodes[node]
+                .mesh
+                .route_advertisement_for(&neighbor)
+                .map_err(MeshError::Security)?;
+            self.send(
+                now,
+                node,
+                &neighbor,
+                FramePayload::RouteAdvertisement(advertisement),
+            );
+        }
+        Ok(())
+    }
+
+    /// Next honest node in turn appends a block to its chain and announces it
+    fn produce_block(&mut self, now: u64) -> MeshResult<()> {
+        let turn = self.blocks.len() % self.honest.len();
+        let node = self.honest[turn];
+        let mesh = &mut self.nodes[node].mesh;
+        let (parent, height) = mesh
+            .local_state()
+            .map(|(root, height)| (root.to_vec(), height))
+            .ok_or_else(|| MeshError::InvalidState("Node without a chain".to_string()))?;
+
+        let data = mesh.node_id().as_bytes().to_vec();
+        let block = StoredBlock {
+            hash: StoredBlock::chain_hash(&parent, height + 1, &data),
+            height: height + 1,
+            data,
+            timestamp: START_MS + now,
+            synced: false,
+        };
+        mesh.store_block(block.clone())
+            .map_err(MeshError::BunkerStorage)?;
+        let message = mesh
+            .update_local_state(block.hash, block.height)
+            .map_err(MeshError::Security)?;
+        self.blocks.push(Block {
+            height: block.height,
+            produced_at: now,
+            converged_at: None,
+        });
+        self.gossip_to_peers(now, node, message);
+        Ok(())
+    }
+
+    /// Re-announce `node`'s head to all its peers
+    fn announce(&mut self, now: u64, node: usize) -> MeshResult<()> {
+        let mesh = &mut self.nodes[node].mesh;
+        let Some((root, height)) = mesh
+            .local_state()
+            .map(|(root, height)| (root.to_vec(), height))
+        else {
+            return Ok(());
+        };
+        let message = mesh
+            .update_local_state(root, height)
+            .map_err(MeshError::Security)?;
+        self.gossip_to_peers(now, node, message);
+        Ok(())
+    }
+
+    fn gossip_to_peers(&mut self, now: u64, node: usize, message: GossipMessage) {
+        for peer in self.peers_of(node) {
+            self.send(now, node, &peer, FramePayload::Gossip(message.clone()));
+        }
+    }
+
+    fn misbehave(&mut self, now: u64, node: usize, due: bool) -> MeshResult<()> {
+        match self.nodes[node].behavior {
+            Some(ByzantineBehavior::ForgeSignatures) if due => {
+                let mesh = &self.nodes[node].mesh;
+                let height = mesh.local_state().map_or(0, |(_, height)| height) + 1;
+                let message = GossipMessage {
+                    msg_id: format!("{}-{}-{}", mesh.node_id(), height, START_MS + now),
+                    source_node: mesh.node_id().to_string(),
+                    merkle_root: self.rng.gen::<[u8; 32]>().to_vec(),
+                    block_height: height,
+                    timestamp: START_MS + now,
+                    signature: vec![0; 64],
+                    hop_count: 0,
+                };
+                self.gossip_to_peers(now, node, message);
+            }
+            Some(ByzantineBehavior::Equivocate) if due || self.nodes[node].equivocating => {
+                // The first block extends the chain, the second contradicts it
+                let state = &mut self.nodes[node];
+                let height = state.mesh.local_state().map_or(0, |(_, height)| height);
+                let height = if state.equivocating {
+                    height
+                } else {
+                    height + 1
+                };
+                state.equivocating = !state.equivocating;
+                let root = self.rng.gen::<[u8; 32]>().to_vec();
+                let message = self.nodes[node]
+                    .mesh
+                    .update_local_state(root, height)
+                    .map_err(MeshError::Security)?;
+                self.gossip_to_peers(now, node, message);
+            }
+            Some(ByzantineBehavior::Flood) => self.announce(now, node)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn peers_of(&self, node: usize) -> Vec<String> {
+        let mut peers: Vec<String> = self.nodes[node]
+            .mesh
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.node_id.clone())
+            .collect();
+        peers.sort();
+        peers
+    }
+
+    fn channel(&self, node: usize) -> Option<ChannelId> {
+        self.nodes[node].mesh.get_mesh_status().current_channel
+    }
+
+    fn jammed(&self, channel: Option<ChannelId>, now: u64) -> bool {
+        let now = Duration::from_millis(now);
+        channel.is_some_and(|channel| {
+            self.config.jamming.iter().any(|jamming| {
+                now >= jamming.from && now < jamming.until && jamming.channels.contains(&channel)
+            })
+        })
+    }
+
+    /// Whether the link between `a` and `b` is up in the flap interval
+    /// containing `now`
+    ///
+    /// Drawn from a hash rather than the shared generator, so a link's
+    /// state does not depend on how much traffic came before.
+    fn link_up(&self, a: usize, b: usize, now: u64) -> bool {
+        if self.config.link.uptime >= 1.0 {
+            return true;
+        }
+        let interval = now / self.config.link.flap_interval.as_millis() as u64;
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.config.seed.to_be_bytes());
+        hasher.update(&(a.min(b) as u64).to_be_bytes());
+        hasher.update(&(a.max(b) as u64).to_be_bytes());
+        hasher.update(&interval.to_be_bytes());
+        let draw = u64::from_be_bytes(hasher.finalize().as_bytes()[..8].try_into().unwrap());
+        (draw as f64 / u64::MAX as f64) < self.config.link.uptime
+    }
+
+    /// Send a batch of messages the mesh addressed to its peers
+    ///
+    /// The mesh lists peers in hash order, which differs between runs, so
+    /// the batch goes out sorted by peer to keep random draws in step.
+    fn send_all<M>(
+        &mut self,
+        now: u64,
+        from: usize,
+        mut messages: Vec<(String, M)>,
+        payload: impl Fn(M) -> FramePayload,
+    ) {
+        messages.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (peer, message) in messages {
+            self.send(now, from, &peer, payload(message));
+        }
+    }
+
+    /// Put a frame from `from` to peer `to` on the air
+    ///
+    /// Everything but link handshakes is sealed for the peer first, and
+    /// nothing is sent to a peer without an established link, as in the
+    /// event loop.
+    fn send(&mut self, now: u64, from: usize, to: &str, payload: FramePayload) {
+        let Some(&to_index) = self.index.get(to) else {
+            return;
+        };
+        let kind = payload_kind(&payload);
+        let mesh = &mut self.nodes[from].mesh;
+        let payload = match payload {
+            FramePayload::LinkHandshake(_) => payload,
+            _ => match mesh.seal_payload(to, &payload) {
+                Ok(sealed) => FramePayload::Sealed(sealed),
+                Err(_) => return,
+            },
+        };
+        let Ok(bytes) = MeshFrame::new(mesh.node_id(), payload).encode() else {
+            return;
+        };
+
+        self.frames_sent += 1;
+        self.bytes_sent += bytes.len() as u64;
+        *self.frames_by_kind.entry(kind.to_string()).or_default() += 1;
+
+        let at = Duration::from_millis(now);
+        if self
+            .config
+            .partitions
+            .iter()
+            .any(|partition| partition.splits(from, to_index, at))
+        {
+            self.lose(Lost::Partition);
+            return;
+        }
+        if !self.link_up(from, to_index, now) {
+            self.lose(Lost::LinkDown);
+            return;
+        }
+        let latency = self.rng.gen_range(
+            self.config.link.latency_min.as_millis() as u64
+                ..=self.config.link.latency_max.as_millis() as u64,
+        );
+        let lost = self.rng.gen_bool(self.config.link.loss);
+        self.sent += 1;
+        self.in_flight.insert(
+            (now + latency, self.sent),
+            InFlight {
+                from,
+                to: to_index,
+                channel: self.channel(from),
+                lost,
+                bytes,
+            },
+        );
+    }
+
+    fn lose(&mut self, cause: Lost) {
+        *self
+            .frames_lost
+            .entry(cause.as_str().to_string())
+            .or_default() += 1;
+    }
+
+    fn deliver(&mut self, now: u64, frame: InFlight) -> MeshResult<()> {
+        if self.channel(frame.to) != frame.channel {
+            self.lose(Lost::OffChannel);
+            return Ok(());
+        }
+        if self.jammed(frame.channel, now) || frame.lost {
+            self.nodes[frame.to].garbled += 1;
+            self.lose(if frame.lost { Lost::Loss } else { Lost::Jammed });
+            return Ok(());
+        }
+        self.nodes[frame.to].heard += 1;
+        self.frames_delivered += 1;
+        debug_assert_ne!(frame.from, frame.to);
+        self.handle_frame(now, frame.to, &frame.bytes)
+    }
+
+    /// Apply a frame the way `MeshEventLoop` does
+    fn handle_frame(&mut self, now: u64, node: usize, bytes: &[u8]) -> MeshResult<()> {
+        let frame = MeshFrame::decode(bytes)?;
+        let mesh = &mut self.nodes[node].mesh;
+        if !mesh.touch_peer(&frame.source) {
+            return Ok(());
+        }
+        match frame.payload {
+            FramePayload::LinkHandshake(handshake) => {
+                if let Ok(Some(reply)) = mesh.process_link_handshake(&frame.source, handshake) {
+                    self.send(now, node, &frame.source, FramePayload::LinkHandshake(reply));
+                }
+            }
+            FramePayload::Sealed(sealed) => {
+                if let Ok(payload) = mesh.open_payload(&frame.source, &sealed) {
+                    let epoch = mesh.current_epoch();
+                    mesh.record_hop_contact(epoch);
+                    self.handle_payload(now, node, &frame.source, payload)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_payload(
+        &mut self,
+        now: u64,
+        node: usize,
+        source: &str,
+        payload: FramePayload,
+    ) -> MeshResult<()> {
+        let mesh = &mut self.nodes[node].mesh;
+        match payload {
+            FramePayload::Gossip(message) => {
+                let result = mesh.process_gossip(source, message);
+                match &result {
+                    GossipResult::Accepted {
+                        should_forward: true,
+                        message,
+                    } => {
+                        for peer in mesh.gossip_targets(source, message) {
+                            self.send(now, node, &peer, FramePayload::Gossip(message.clone()));
+                        }
+                    }
+                    GossipResult::Equivocation { first, second } => {
+                        if mesh.slash_equivocation(first, second).is_ok() {
+                            self.advertise_routes(now, node)?;
+                        }
+                    }
+                    _ => {
+                        if let Some((peer, request)) = mesh.begin_fork_resolution(&result) {
+                            self.send(now, node, &peer, FramePayload::Fork(request));
+                        }
+                    }
+                }
+            }
+            FramePayload::RouteAdvertisement(advertisement) => {
+                let _ = mesh.process_route_advertisement(source, advertisement);
+            }
+            FramePayload::Sync(message) => {
+                if let Ok(output) = mesh.process_sync(source, message) {
+                    for reply in output.replies {
+                        self.send(now, node, source, FramePayload::Sync(reply));
+                    }
+                }
+            }
+            FramePayload::Fork(message) => {
+                let Ok(output) = mesh.process_fork(source, message) else {
+                    return Ok(());
+                };
+                for reply in output.replies {
+                    self.send(now, node, source, FramePayload::Fork(reply));
+                }
+                if matches!(
+                    output.outcome,
+                    Some(ForkOutcome::FastForward { .. } | ForkOutcome::AdoptedPeer { .. })
+                ) {
+                    self.announce(now, node)?;
+                }
+            }
+            FramePayload::HopSync(message) => {
+                let epoch = mesh.current_epoch();
+                if let Ok(output) = mesh.process_hop_sync(source, message, epoch) {
+                    self.send_all(now, node, output.messages, FramePayload::HopSync);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Mark blocks converged once all honest nodes hold the same head at or
+    /// above their height
+    fn check_convergence(&mut self, now: u64) {
+        let Some(height) = self.agreed_height() else {
+            return;
+        };
+        for block in self
+            .blocks
+            .iter_mut()
+            .filter(|block| block.converged_at.is_none() && block.height <= height)
+        {
+            block.converged_at = Some(now);
+        }
+    }
+
+    fn agreed_height(&self) -> Option<u64> {
+        let mut heads = self
+            .honest
+            .iter()
+            .map(|&node| self.nodes[node].mesh.local_state());
+        let first = heads.next()??;
+        heads.all(|head| head == Some(first)).then_some(first.1)
+    }
+
+    fn report(&self, end: u64) -> SimReport {
+        let mut times: Vec<u64> = self
+            .blocks
+            .iter()
+            .filter_map(|block| Some(block.converged_at? - block.produced_at))
+            .collect();
+        times.sort_unstable();
+        let mean_ms = (!times.is_empty()).then(|| times.iter().sum::<u64>() / times.len() as u64);
+        let p95_ms = (!times.is_empty()).then(|| times[(times.len() * 95).div_ceil(100) - 1]);
+
+        let plans: BTreeSet<_> = self
+            .honest
+            .iter()
+            .map(|&node| {
+                self.nodes[node]
+                    .mesh
+                    .hop_plan()
+                    .map(|plan| (plan.issuer.clone(), plan.generation))
+            })
+            .collect();
+
+        SimReport {
+            name: self.config.name.clone(),
+            seed: self.config.seed,
+            nodes: self.config.nodes,
+            duration_ms: end,
+            convergence: ConvergenceReport {
+                blocks: self.blocks.len(),
+                converged: times.len(),
+                mean_ms,
+                p95_ms,
+                max_ms: times.last().copied(),
+                agreed_at_end: self.agreed_height().is_some(),
+            },
+            overhead: OverheadReport {
+                frames_sent: self.frames_sent,
+                bytes_sent: self.bytes_sent,
+                frames_delivered: self.frames_delivered,
+                frames_per_block: self.frames_sent as f64 / self.blocks.len().max(1) as f64,
+                frames_by_kind: self.frames_by_kind.clone(),
+                frames_lost: self.frames_lost.clone(),
+            },
+            spectral: SpectralReport {
+                plans_activated: self.plans.len(),
+                epochs_out_of_sync: self.epochs_out_of_sync,
+                agreed_at_end: plans.len() == 1,
+            },
+            trust: (0..self.nodes.len())
+                .map(|node| self.trust_outcome(node))
+                .collect(),
+        }
+    }
+
+    fn trust_outcome(&self, node: usize) -> TrustOutcome {
+        let node_id = node_id(node);
+        let mut scores = Vec::new();
+        let mut revoked_by = 0;
+        let mut dropped_by = 0;
+        for &observer in self.honest.iter().filter(|&&observer| observer != node) {
+            let mesh = &self.nodes[observer].mesh;
+            match mesh.get_peer(&node_id) {
+                Some(peer) => scores.push(peer.trust_score),
+                None if mesh.is_revoked(&node_id) => revoked_by += 1,
+                None => dropped_by += 1,
+            }
+        }
+        TrustOutcome {
+            node_id,
+            byzantine: self.nodes[node].behavior,
+            mean_trust: (!scores.is_empty())
+                .then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+            revoked_by,
+            dropped_by,
+        }
+    }
+}
+
+/// Name of a payload's type in reports
+fn payload_kind(payload: &FramePayload) -> &'static str {
+    match payload {
+        FramePayload::Hello(_) => "hello",
+        FramePayload::Attestation(_) => "attestation",
+        FramePayload::LinkHandshake(_) => "link_handshake",
+        FramePayload::Sealed(_) => "sealed",
+        FramePayload::Gossip(_) => "gossip",
+        FramePayload::RouteAdvertisement(_) => "route_advertisement",
+        FramePayload::Sync(_) => "sync",
+        FramePayload::Fork(_) => "fork",
+        FramePayload::HopSync(_) => "hop_sync",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESILIENCE_YAML: &str = r#"
+network_health:
+  health_grades:
+    degraded:
+      packet_loss_max_percent: 15
+      latency_max_ms: 500
+      uptime_min_percent: 80
+    excellent:
+      packet_loss_max_percent: 1
+      latency_max_ms: 50
+      uptime_min_percent: 99
+offline_mesh:
+  gossip:
+    interval_seconds: 10
+    fanout: 3
+"#;
+
+    #[test]
+    fn test_scenarios_ordered_best_grade_first() {
+        let scenarios = Scenario::from_yaml(RESILIENCE_YAML).unwrap();
+        assert_eq!(scenarios.len(), 2);
+
+        let excellent = &scenarios[0];
+        assert_eq!(excellent.name, "excellent");
+        assert_eq!(excellent.link.latency_min, Duration::ZERO);
+        assert_eq!(excellent.link.latency_max, Duration::from_millis(50));
+        assert_eq!(excellent.block_interval, Duration::from_secs(10));
+        assert_eq!(excellent.fanout, 3);
+
+        let degraded = &scenarios[1];
+        assert_eq!(degraded.name, "degraded");
+        assert_eq!(degraded.link.latency_min, Duration::from_millis(50));
+        assert_eq!(degraded.link.latency_max, Duration::from_millis(500));
+        assert!((degraded.link.loss - 0.15).abs() < 1e-9);
+        assert!((degraded.link.uptime - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scenario_rejects_invalid_percentages() {
+        let yaml = RESILIENCE_YAML.replace(
+            "packet_loss_max_percent: 15",
+            "packet_loss_max_percent: 150",
+        );
+        assert!(Scenario::from_yaml(&yaml).is_err());
+        assert!(Scenario::from_yaml("network_health: {}").is_err());
+    }
+
+    #[test]
+    fn test_byzantine_behavior_from_str() {
+        assert_eq!(
+            "forge".parse::<ByzantineBehavior>().unwrap(),
+            ByzantineBehavior::ForgeSignatures
+        );
+        assert_eq!(
+            "equivocate".parse::<ByzantineBehavior>().unwrap(),
+            ByzantineBehavior::Equivocate
+        );
+        assert_eq!(
+            "flood".parse::<ByzantineBehavior>().unwrap(),
+            ByzantineBehavior::Flood
+        );
+        assert!("sleep".parse::<ByzantineBehavior>().is_err());
+    }
+
+    #[test]
+    fn test_partition_splits_groups_while_active() {
+        let partition = Partition {
+            from: Duration::from_secs(10),
+            until: Duration::from_secs(20),
+            groups: vec![vec![0, 1], vec![2]],
+        };
+        assert!(!partition.splits(0, 2, Duration::from_secs(5)));
+        assert!(partition.splits(0, 2, Duration::from_secs(10)));
+        assert!(!partition.splits(0, 1, Duration::from_secs(15)));
+        assert!(partition.splits(3, 3, Duration::from_secs(15)));
+        assert!(!partition.splits(0, 2, Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(SimConfig::default().validate().is_ok());
+
+        let too_small = SimConfig {
+            nodes: 1,
+            ..SimConfig::default()
+        };
+        assert!(too_small.validate().is_err());
+
+        let out_of_range = SimConfig {
+            byzantine: BTreeMap::from([(8, ByzantineBehavior::Flood)]),
+            ..SimConfig::default()
+        };
+        assert!(out_of_range.validate().is_err());
+
+        let all_byzantine = SimConfig {
+            nodes: 2,
+            byzantine: BTreeMap::from([
+                (0, ByzantineBehavior::Flood),
+                (1, ByzantineBehavior::Equivocate),
+            ]),
+            ..SimConfig::default()
+        };
+        assert!(all_byzantine.validate().is_err());
+
+        let inverted_latency = SimConfig {
+            link: LinkProfile {
+                latency_min: Duration::from_millis(30),
+                latency_max: Duration::from_millis(10),
+                ..LinkProfile::default()
+            },
+            ..SimConfig::default()
+        };
+        assert!(inverted_latency.validate().is_err());
+    }
+}
@@ -1,9 +1,10 @@
 //! Spectral Agility - Coordinated Frequency Hopping for EW Hardening
 //!
 //! Implements coordinated frequency hopping to evade jamming attacks.
+//! Agreeing on the pattern across the swarm is left to `hop_sync`.
 
+use crate::clock::current_timestamp;
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Frequency channel identifier
 pub type ChannelId = u32;
@@ -36,6 +37,8 @@ pub struct FrequencyHopper {
     current_per: f64,
     /// Is jamming detected?
     jamming_detected: bool,
+    /// Channel held outside the pattern, such as for rendezvous
+    parked: Option<ChannelId>,
 }
 
 impl FrequencyHopper {
@@ -48,6 +51,7 @@ impl FrequencyHopper {
             per_threshold,
             current_per: 0.0,
             jamming_detected: false,
+            parked: None,
         }
     }
 
@@ -56,13 +60,26 @@ impl FrequencyHopper {
         self.pattern = Some(pattern);
         self.current_index = 0;
         self.last_hop_time = current_timestamp();
+        self.parked = None;
+    }
+
+    /// Current hopping pattern
+    pub fn pattern(&self) -> Option<&HoppingPattern> {
+        self.pattern.as_ref()
+    }
+
+    /// Hold `channel` until the next hop, regardless of the pattern
+    pub fn park(&mut self, channel: ChannelId) {
+        self.parked = Some(channel);
     }
 
     /// Get the current channel
     pub fn current_channel(&self) -> Option<ChannelId> {
-        self.pattern
-            .as_ref()
-            .and_then(|p| p.channels.get(self.current_index).copied())
+        self.parked.or_else(|| {
+            self.pattern
+                .as_ref()
+                .and_then(|p| p.channels.get(self.current_index).copied())
+        })
     }
 
     /// Update Packet Error Rate measurement
@@ -95,6 +112,7 @@ impl FrequencyHopper {
         if let Some(pattern) = &self.pattern {
             self.current_index = (self.current_index + 1) % pattern.channels.len();
             self.last_hop_time = current_timestamp();
+            self.parked = None;
 
             let new_channel = pattern.channels[self.current_index];
 
@@ -118,6 +136,7 @@ impl FrequencyHopper {
             let index = (epoch % pattern.channels.len() as u64) as usize;
             self.current_index = index;
             self.last_hop_time = epoch;
+            self.parked = None;
 
             HopResult::Success {
                 new_channel: pattern.channels[index],
@@ -196,14 +215,6 @@ pub fn generate_hopping_pattern(
     }
 }
 
-/// Get current timestamp in milliseconds
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,6 +338,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_park_overrides_pattern_until_next_hop() {
+        let mut hopper = FrequencyHopper::new(0.1);
+        hopper.park(7);
+        assert_eq!(hopper.current_channel(), Some(7));
+
+        hopper.set_pattern(create_test_pattern());
+        hopper.park(7);
+        assert_eq!(hopper.current_channel(), Some(7));
+        hopper.hop_at_epoch(2);
+        assert_eq!(hopper.current_channel(), Some(30));
+    }
+
     #[test]
     fn test_no_pattern_returns_error() {
         let mut hopper = FrequencyHopper::new(0.1);
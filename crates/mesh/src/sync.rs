@@ -0,0 +1,676 @@
+//! Bunker Sync - Store-and-forward reconciliation after isolation
+//!
+//! While isolated a node keeps writing blocks and events to its
+//! `BunkerMode` store. Once peers are back each link runs a sync session:
+//! both ends exchange a summary of their block store (latest height and
+//! Merkle root), then stream what the other side is missing in batches,
+//! one at a time, each acknowledged before the next is sent.
+//!
+//! A node streams every block above the peer's height, followed by its own
+//! unsynced blocks and events. Records are marked synced when the batch
+//! carrying them is acknowledged, and records received from a peer are
+//! stored as synced, so an interrupted session picks up where it stopped
+//! when the next one starts.
+//!
+//! Received blocks are only stored if their hash chains onto a block held
+//! at the height below, and received records never replace ones already
+//! held.
+
+use crate::bunker::{BunkerMode, StoredBlock, StoredEvent};
+use crate::clock;
+use crate::error::MeshResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Sync protocol step exchanged with a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SyncMessage {
+    /// State of the sender's block store, opening a session
+    Summary(SyncSummary),
+    /// Records the receiver is missing
+    Batch(SyncBatch),
+    /// Receipt of a batch
+    Ack {
+        /// Sequence number of the batch received
+        seq: u64,
+    },
+}
+
+/// Summary of a node's block store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSummary {
+    /// Highest block height held, if any
+    pub latest_height: Option<u64>,
+    /// Merkle root over all held block hashes
+    pub store_root: [u8; 32],
+    /// Whether this answers the receiver's summary
+    pub reply: bool,
+}
+
+/// Batch of records streamed to a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncBatch {
+    /// Sequence number, never reused by the sender
+    pub seq: u64,
+    /// Blocks, lowest height first
+    pub blocks: Vec<StoredBlock>,
+    /// Events, oldest first
+    pub events: Vec<StoredEvent>,
+    /// Whether the sender has nothing more to send in this session
+    pub last: bool,
+}
+
+/// Pacing and batch sizing for sync sessions
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    /// How often sessions are started and retried
+    pub tick_interval: Duration,
+    /// Time to wait for a summary or acknowledgement before resending
+    pub retry_interval: Duration,
+    /// Records per batch
+    pub max_batch_records: usize,
+    /// Block data and event payload bytes per batch; a larger record is
+    /// sent in a batch of its own
+    pub max_batch_bytes: usize,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval: Duration::from_secs(1),
+            retry_interval: Duration::from_secs(5),
+            max_batch_records: 32,
+            // Sealed frames expand JSON-encoded bytes roughly twelvefold
+            max_batch_bytes: 4096,
+        }
+    }
+}
+
+/// Result of handling a sync message
+#[derive(Debug, Default)]
+pub struct SyncOutput {
+    /// Messages to send back to the peer
+    pub replies: Vec<SyncMessage>,
+    /// Whether this message completed the session in both directions
+    pub completed: bool,
+}
+
+struct InFlight {
+    batch: SyncBatch,
+    /// Peer's height once this batch is received
+    cursor: Option<u64>,
+    sent: Instant,
+}
+
+struct SyncSession {
+    summary_sent: Instant,
+    /// Whether the peer's summary has arrived
+    peer_ready: bool,
+    /// Height up to which the peer holds our blocks
+    cursor: Option<u64>,
+    in_flight: Option<InFlight>,
+    /// Our last batch was acknowledged
+    sent_all: bool,
+    /// The peer's last batch arrived
+    received_all: bool,
+}
+
+impl SyncSession {
+    fn new() -> Self {
+        Self {
+            summary_sent: clock::now(),
+            peer_ready: false,
+            cursor: None,
+            in_flight: None,
+            sent_all: false,
+            received_all: false,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.sent_all && self.received_all
+    }
+}
+
+/// Store-and-forward sync sessions with each peer
+pub struct BunkerSync {
+    config: SyncConfig,
+    sessions: HashMap<String, SyncSession>,
+    /// Batch sequence numbers are never reused, so a late acknowledgement
+    /// from an abandoned session cannot match a new batch
+    next_seq: u64,
+}
+
+impl BunkerSync {
+    /// Create with `config`
+    pub fn new(config: SyncConfig) -> Self {
+        Self {
+            config,
+            sessions: HashMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &SyncConfig {
+        &self.config
+    }
+
+    /// Peers whose session finished in both directions
+    pub fn completed_peers(&self) -> Vec<String> {
+        self.sessions
+            .iter()
+            .filter(|(_, session)| session.is_complete())
+            .map(|(node_id, _)| node_id.clone())
+            .collect()
+    }
+
+    /// Drop the session with `node_id`
+    pub fn remove(&mut self, node_id: &str) {
+        self.sessions.remove(node_id);
+    }
+
+    /// Drop all sessions
+    pub fn clear(&mut self) {
+        self.sessions.clear();
+    }
+
+    /// Resend unanswered summaries and unacknowledged batches, and open
+    /// sessions with `peers` that have none if `start` is set
+    ///
+    /// Sessions with nodes no longer in `peers` are discarded.
+    pub fn tick(
+        &mut self,
+        bunker: &BunkerMode,
+        peers: &[String],
+        start: bool,
+    ) -> MeshResult<Vec<(String, SyncMessage)>> {
+        self.sessions.retain(|node_id, _| peers.contains(node_id));
+
+        let mut outgoing = Vec::new();
+        for node_id in peers {
+            let Some(session) = self.sessions.get_mut(node_id) else {
+                if start {
+                    debug!(peer_id = %node_id, "Starting bunker sync");
+                    self.sessions.insert(node_id.clone(), SyncSession::new());
+                    outgoing.push((node_id.clone(), summary(bunker, false)?));
+                }
+                continue;
+            };
+            if !session.peer_ready {
+                if clock::now().duration_since(session.summary_sent) >= self.config.retry_interval {
+                    session.summary_sent = clock::now();
+                    outgoing.push((node_id.clone(), summary(bunker, false)?));
+                }
+            } else if let Some(in_flight) = &mut session.in_flight {
+                if clock::now().duration_since(in_flight.sent) >= self.config.retry_interval {
+                    debug!(peer_id = %node_id, seq = in_flight.batch.seq, "Resending sync batch");
+                    in_flight.sent = clock::now();
+                    outgoing.push((node_id.clone(), SyncMessage::Batch(in_flight.batch.clone())));
+                }
+            }
+        }
+        Ok(outgoing)
+    }
+
+    /// Apply a sync message from `node_id`
+    pub fn handle(
+        &mut self,
+        bunker: &mut BunkerMode,
+        node_id: &str,
+        message: SyncMessage,
+    ) -> MeshResult<SyncOutput> {
+        let mut output = SyncOutput::default();
+        let was_complete = self
+            .sessions
+            .get(node_id)
+            .is_some_and(SyncSession::is_complete);
+
+        match message {
+            SyncMessage::Summary(peer) => {
+                if !peer.reply {
+                    // The peer is (re)starting; our side starts over with it
+                    self.sessions
+                        .insert(node_id.to_string(), SyncSession::new());
+                    output.replies.push(summary(bunker, true)?);
+                }
+                let Some(session) = self.sessions.get_mut(node_id) else {
+                    return Ok(output);
+                };
+                if session.peer_ready {
+                    return Ok(output);
+                }
+                session.peer_ready = true;
+                session.cursor = if peer.store_root == bunker.get_store_root()? {
+                    bunker.get_latest_height()?
+                } else {
+                    peer.latest_height
+                };
+                let batch = self.next_batch(bunker, node_id)?;
+                output.replies.push(batch);
+            }
+            SyncMessage::Batch(mut batch) => {
+                // Parents are checked before children, whatever the order sent
+                batch.blocks.sort_by_key(|block| block.height);
+                let mut accepted: Vec<(u64, Vec<u8>)> = Vec::new();
+                for mut block in batch.blocks {
+                    if !links_to_parent(bunker, &accepted, &block)? {
+                        warn!(
+                            "Dropping block {} from {}: hash does not chain to a known parent",
+                            block.height, node_id
+                        );
+                        continue;
+                    }
+                    accepted.push((block.height, block.hash.clone()));
+                    block.synced = true;
+                    bunker.insert_block(block)?;
+                }
+                for mut event in batch.events {
+                    event.synced = true;
+                    bunker.insert_event(event)?;
+                }
+                output.replies.push(SyncMessage::Ack { seq: batch.seq });
+                if let Some(session) = self.sessions.get_mut(node_id) {
+                    session.received_all |= batch.last;
+                }
+            }
+            SyncMessage::Ack { seq } => {
+                let Some(session) = self.sessions.get_mut(node_id) else {
+                    return Ok(output);
+                };
+                let Some(in_flight) = session
+                    .in_flight
+                    .take_if(|in_flight| in_flight.batch.seq == seq)
+                else {
+                    return Ok(output);
+                };
+                for block in in_flight.batch.blocks.iter().filter(|b| !b.synced) {
+                    bunker.mark_block_synced(&block.hash)?;
+                }
+                for event in in_flight.batch.events.iter().filter(|e| !e.synced) {
+                    bunker.mark_event_synced(&event.id)?;
+                }
+                session.cursor = in_flight.cursor;
+                if in_flight.batch.last {
+                    session.sent_all = true;
+                } else {
+                    let batch = self.next_batch(bunker, node_id)?;
+                    output.replies.push(batch);
+                }
+            }
+        }
+
+        output.completed = !was_complete
+            && self
+                .sessions
+                .get(node_id)
+                .is_some_and(SyncSession::is_complete);
+        Ok(output)
+    }
+
+    /// Build and record the next batch for `node_id`
+    fn next_batch(&mut self, bunker: &BunkerMode, node_id: &str) -> MeshResult<SyncMessage> {
+        let max_records = self.config.max_batch_records.max(1);
+        let max_bytes = self.config.max_batch_bytes;
+        let Some(session) = self.sessions.get_mut(node_id) else {
+            return Err(crate::error::MeshError::InvalidState(format!(
+                "No sync session with {}",
+                node_id
+            )));
+        };
+
+        let mut batch = SyncBatch {
+            seq: self.next_seq,
+            blocks: Vec::new(),
+            events: Vec::new(),
+            last: false,
+        };
+        let mut cursor = session.cursor;
+        let mut records = 0;
+        let mut bytes = 0;
+        let mut fits = |size: usize| {
+            let fits = records == 0 || (records < max_records && bytes + size <= max_bytes);
+            if fits {
+                records += 1;
+                bytes += size;
+            }
+            fits
+        };
+
+        // Blocks above the peer's height, then our own blocks at or below it
+        let above = bunker.get_blocks_above(session.cursor, max_records)?;
+        let mut full = above.len() == max_records;
+        for block in above {
+            if !fits(block.data.len()) {
+                full = true;
+                break;
+            }
+            cursor = Some(block.height);
+            batch.blocks.push(block);
+        }
+        if !full {
+            let below = bunker
+                .get_unsynced_blocks()?
+                .into_iter()
+                .filter(|block| session.cursor.is_some_and(|c| block.height <= c));
+            for block in below {
+                if !fits(block.data.len()) {
+                    full = true;
+                    break;
+                }
+                batch.blocks.push(block);
+            }
+        }
+        if !full {
+            for event in bunker.get_unsynced_events()? {
+                if !fits(event.payload.len()) {
+                    full = true;
+                    break;
+                }
+                batch.events.push(event);
+            }
+        }
+        batch.last = !full;
+
+        self.next_seq += 1;
+        session.in_flight = Some(InFlight {
+            batch: batch.clone(),
+            cursor,
+            sent: clock::now(),
+        });
+        Ok(SyncMessage::Batch(batch))
+    }
+}
+
+/// Whether `block` hashes correctly onto a block held at the height below,
+/// either stored or `accepted` earlier in the same batch. A block may only
+/// start a chain if nothing is held below it.
+fn links_to_parent(
+    bunker: &BunkerMode,
+    accepted: &[(u64, Vec<u8>)],
+    block: &StoredBlock,
+) -> MeshResult<bool> {
+    let chains_on =
+        |parent: &[u8]| StoredBlock::chain_hash(parent, block.height, &block.data) == block.hash;
+    let parent_height = match block.height.checked_sub(1) {
+        Some(height) => height,
+        None => return Ok(chains_on(&[])),
+    };
+    if accepted
+        .iter()
+        .any(|(height, hash)| *height == parent_height && chains_on(hash))
+    {
+        return Ok(true);
+    }
+    if bunker
+        .get_block_hashes_at(parent_height)?
+        .iter()
+        .any(|hash| chains_on(hash))
+    {
+        return Ok(true);
+    }
+    let holds_below = bunker.has_blocks_below(block.height)?
+        || accepted.iter().any(|(height, _)| *height < block.height);
+    Ok(!holds_below && chains_on(&[]))
+}
+
+fn summary(bunker: &BunkerMode, reply: bool) -> MeshResult<SyncMessage> {
+    Ok(SyncMessage::Summary(SyncSummary {
+        latest_height: bunker.get_latest_height()?,
+        store_root: bunker.get_store_root()?,
+        reply,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Node {
+        id: &'static str,
+        bunker: BunkerMode,
+        sync: BunkerSync,
+    }
+
+    fn node(id: &'static str, config: SyncConfig) -> Node {
+        Node {
+            id,
+            bunker: BunkerMode::new(":memory:").unwrap(),
+            sync: BunkerSync::new(config),
+        }
+    }
+
+    fn block(height: u64, synced: bool) -> StoredBlock {
+        let parent = match height {
+            0 | 1 => Vec::new(),
+            _ => block(height - 1, synced).hash,
+        };
+        chained(&parent, height, vec![height as u8; 16], synced)
+    }
+
+    fn chained(parent: &[u8], height: u64, data: Vec<u8>, synced: bool) -> StoredBlock {
+        StoredBlock {
+            hash: StoredBlock::chain_hash(parent, height, &data),
+            height,
+            data,
+            timestamp: height,
+            synced,
+        }
+    }
+
+    fn event(id: &str) -> StoredEvent {
+        StoredEvent {
+            id: id.to_string(),
+            event_type: "telemetry".to_string(),
+            payload: vec![1, 2, 3],
+            timestamp: 1,
+            synced: false,
+        }
+    }
+
+    /// `a` opens a session with `b`; messages are delivered in order until
+    /// none are left or `limit` have been delivered. Returns the batches `a`
+    /// sent and whether each side saw the session complete.
+    fn run(a: &mut Node, b: &mut Node, limit: usize) -> (Vec<SyncBatch>, bool, bool) {
+        let mut pending: Vec<(bool, SyncMessage)> = a
+            .sync
+            .tick(&a.bunker, &[b.id.to_string()], true)
+            .unwrap()
+            .into_iter()
+            .map(|(_, message)| (true, message))
+            .collect();
+        let mut sent_by_a = Vec::new();
+        let (mut a_done, mut b_done) = (false, false);
+        let mut delivered = 0;
+        while !pending.is_empty() && delivered < limit {
+            let (to_b, message) = pending.remove(0);
+            delivered += 1;
+            let (receiver, sender) = if to_b {
+                (&mut *b, a.id)
+            } else {
+                (&mut *a, b.id)
+            };
+            let output = receiver
+                .sync
+                .handle(&mut receiver.bunker, sender, message)
+                .unwrap();
+            if to_b {
+                b_done |= output.completed;
+            } else {
+                a_done |= output.completed;
+            }
+            for reply in output.replies {
+                if let (false, SyncMessage::Batch(batch)) = (to_b, &reply) {
+                    sent_by_a.push(batch.clone());
+                }
+                pending.push((!to_b, reply));
+            }
+        }
+        (sent_by_a, a_done, b_done)
+    }
+
+    #[test]
+    fn test_sync_exchanges_missing_records() {
+        let mut a = node("node-a", SyncConfig::default());
+        let mut b = node("node-b", SyncConfig::default());
+        for height in 1..=3 {
+            a.bunker.store_block(block(height, true)).unwrap();
+            b.bunker.store_block(block(height, true)).unwrap();
+        }
+        // Both recorded blocks while isolated, node-a also an event
+        a.bunker.store_block(block(4, false)).unwrap();
+        a.bunker.store_block(block(5, false)).unwrap();
+        a.bunker.store_event(event("contact-report")).unwrap();
+        b.bunker
+            .store_block(chained(&block(3, true).hash, 4, vec![0xB4; 16], false))
+            .unwrap();
+
+        let (_, a_done, b_done) = run(&mut a, &mut b, usize::MAX);
+        assert!(a_done && b_done);
+
+        assert_eq!(a.bunker.get_unsynced_count().unwrap(), (0, 0));
+        assert_eq!(b.bunker.get_unsynced_count().unwrap(), (0, 0));
+        assert_eq!(a.bunker.get_latest_height().unwrap(), Some(5));
+        assert_eq!(
+            a.bunker.get_store_root().unwrap(),
+            b.bunker.get_store_root().unwrap()
+        );
+        assert_eq!(b.bunker.get_blocks_above(None, 100).unwrap().len(), 6);
+        assert_eq!(a.sync.completed_peers(), vec!["node-b".to_string()]);
+    }
+
+    #[test]
+    fn test_tampered_batch_not_stored() {
+        let mut b = node("node-b", SyncConfig::default());
+        b.bunker.store_block(block(1, true)).unwrap();
+        b.bunker.store_event(event("contact-report")).unwrap();
+
+        let mut forged = block(2, false);
+        forged.data = vec![0xFF; 16];
+        let mut rewritten = block(1, false);
+        rewritten.timestamp = 99;
+        let mut replaced = event("contact-report");
+        replaced.payload = vec![9, 9, 9];
+        let batch = SyncBatch {
+            seq: 1,
+            blocks: vec![forged, rewritten, block(3, false)],
+            events: vec![replaced],
+            last: true,
+        };
+        let output = b
+            .sync
+            .handle(&mut b.bunker, "node-a", SyncMessage::Batch(batch))
+            .unwrap();
+        assert!(matches!(output.replies[..], [SyncMessage::Ack { seq: 1 }]));
+
+        // The forged block is dropped, and its child with it
+        assert_eq!(b.bunker.get_latest_height().unwrap(), Some(1));
+        let held = b.bunker.get_blocks_above(None, 10).unwrap();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].timestamp, 1);
+
+        // The held event is neither replaced nor marked synced
+        let events = b.bunker.get_unsynced_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_batches_respect_limits() {
+        let config = SyncConfig {
+            max_batch_records: 2,
+            max_batch_bytes: 40,
+            ..SyncConfig::default()
+        };
+        let mut a = node("node-a", config);
+        let mut b = node("node-b", SyncConfig::default());
+        for height in 1..=5 {
+            a.bunker.store_block(block(height, false)).unwrap();
+        }
+
+        let (batches, _, _) = run(&mut a, &mut b, usize::MAX);
+        let sizes: Vec<usize> = batches.iter().map(|batch| batch.blocks.len()).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert!(batches.last().unwrap().last);
+
+        // Block data of 16 bytes each: only two fit in 40
+        let mut a = node(
+            "node-a",
+            SyncConfig {
+                max_batch_records: 10,
+                max_batch_bytes: 40,
+                ..SyncConfig::default()
+            },
+        );
+        let mut b = node("node-b", SyncConfig::default());
+        for height in 1..=3 {
+            a.bunker.store_block(block(height, false)).unwrap();
+        }
+        let (batches, _, _) = run(&mut a, &mut b, usize::MAX);
+        assert_eq!(batches[0].blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_interrupted_sync_resumes() {
+        let config = SyncConfig {
+            max_batch_records: 2,
+            ..SyncConfig::default()
+        };
+        let mut a = node("node-a", config);
+        let mut b = node("node-b", SyncConfig::default());
+        for height in 1..=5 {
+            a.bunker.store_block(block(height, false)).unwrap();
+        }
+
+        // The link drops once a's first batch is acknowledged, with the
+        // second on its way
+        let (batches, a_done, _) = run(&mut a, &mut b, 6);
+        assert_eq!(batches.len(), 2);
+        assert!(!a_done);
+        assert_eq!(a.bunker.get_unsynced_count().unwrap(), (3, 0));
+
+        // The next session only carries what is left
+        a.sync.clear();
+        b.sync.clear();
+        let (batches, a_done, b_done) = run(&mut a, &mut b, usize::MAX);
+        let heights: Vec<u64> = batches
+            .iter()
+            .flat_map(|batch| batch.blocks.iter().map(|block| block.height))
+            .collect();
+        assert_eq!(heights, vec![3, 4, 5]);
+        assert!(a_done && b_done);
+        assert_eq!(a.bunker.get_unsynced_count().unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_unacknowledged_batch_resent() {
+        let config = SyncConfig {
+            retry_interval: Duration::ZERO,
+            ..SyncConfig::default()
+        };
+        let mut a = node("node-a", config);
+        let mut b = node("node-b", SyncConfig::default());
+        a.bunker.store_block(block(1, false)).unwrap();
+        let peers = ["node-b".to_string()];
+
+        // Summaries exchanged, then a's batch is lost
+        let (batches, _, _) = run(&mut a, &mut b, 2);
+        assert_eq!(batches.len(), 1);
+        let resent = a.sync.tick(&a.bunker, &peers, true).unwrap();
+        match &resent[..] {
+            [(peer, SyncMessage::Batch(batch))] => {
+                assert_eq!(peer, "node-b");
+                assert_eq!(batch.seq, batches[0].seq);
+            }
+            other => panic!("Expected the batch again, got {:?}", other),
+        }
+
+        // Acknowledgements for other batches change nothing
+        a.sync
+            .handle(&mut a.bunker, "node-b", SyncMessage::Ack { seq: 99 })
+            .unwrap();
+        assert_eq!(a.bunker.get_unsynced_count().unwrap(), (1, 0));
+    }
+}
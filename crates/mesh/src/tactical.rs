@@ -4,11 +4,28 @@
 //! in contested, multi-domain environments.
 
 use crate::bunker::{BunkerMode, BunkerState, StoredBlock, StoredEvent};
-use crate::gossip::{AethericWhisper, GossipMessage, GossipResult};
-use crate::peer::{PeerInfo, PeerTable};
+use crate::clock::current_timestamp;
+use crate::fork::{
+    choose_branch, ForkConfig, ForkConflict, ForkMessage, ForkOutcome, ForkOutput, ForkResolver,
+    ForkTick,
+};
+use crate::gossip::{AethericWhisper, GossipConfig, GossipMessage, GossipResult};
+use crate::hop_sync::{
+    HopPlan, HopSync, HopSyncConfig, HopSyncMessage, HopSyncOutput, HopTick, Tuning,
+};
+use crate::link::{LinkConfig, LinkHandshake, LinkMaintenance, LinkSessions, SealedPayload};
+use crate::peer::{node_id_for_key, PeerInfo, PeerTable};
 use crate::routing::{LinkQuality, RoutingTable};
+use crate::security::MeshSecurity;
 use crate::spectral::{FrequencyHopper, HoppingPattern};
+use crate::sync::{BunkerSync, SyncConfig, SyncMessage, SyncOutput};
+use crate::transport::{FramePayload, RouteAdvertisement};
+use aethercore_core::{ByzantineFaultType, SlashingEngine, SlashingEvent};
+use ed25519_dalek::VerifyingKey;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 use tracing::{debug, info, warn};
 
@@ -26,6 +43,24 @@ pub struct TacticalMesh {
     frequency_hopper: FrequencyHopper,
     /// Offline-first persistence
     bunker_mode: BunkerMode,
+    /// Signing and verification of routing updates
+    security: MeshSecurity,
+    /// Timestamp of the last accepted route advertisement per neighbor
+    advertisement_timestamps: HashMap<String, u64>,
+    /// Identity keys of nodes beyond our neighbors, from route advertisements
+    node_keys: HashMap<String, Vec<u8>>,
+    /// Encrypted sessions with each peer
+    links: LinkSessions,
+    /// Store-and-forward sync with peers after isolation
+    sync: BunkerSync,
+    /// Reconciliation of chains that diverged from a neighbor's
+    forks: ForkResolver,
+    /// Revocation of nodes caught in Byzantine behavior
+    slashing: SlashingEngine,
+    /// Agreement with trusted neighbors on the hopping pattern
+    hop_sync: HopSync,
+    /// Source of relay target and hopping seed draws
+    rng: StdRng,
 }
 
 impl TacticalMesh {
@@ -50,24 +85,93 @@ impl TacticalMesh {
             routing_table: RoutingTable::new(node_id.clone()),
             frequency_hopper: FrequencyHopper::new(0.1), // 10% PER threshold
             bunker_mode,
+            security: MeshSecurity::new(),
+            advertisement_timestamps: HashMap::new(),
+            node_keys: HashMap::new(),
+            links: LinkSessions::new(node_id, LinkConfig::default()),
+            sync: BunkerSync::new(SyncConfig::default()),
+            forks: ForkResolver::new(ForkConfig::default()),
+            slashing: SlashingEngine::new(),
+            hop_sync: HopSync::new(HopSyncConfig::default()),
+            rng: StdRng::from_entropy(),
         })
     }
 
+    /// Sign route advertisements and link key exchanges with `security`
+    ///
+    /// Without a signing service no routes are advertised and no peer
+    /// links are encrypted, so no mesh traffic flows.
+    pub fn with_security(mut self, security: MeshSecurity) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Use `config` for peer link sessions
+    pub fn with_link_config(mut self, config: LinkConfig) -> Self {
+        self.links = LinkSessions::new(self.node_id.clone(), config);
+        self
+    }
+
+    /// Use `config` for bunker sync sessions
+    pub fn with_sync_config(mut self, config: SyncConfig) -> Self {
+        self.sync = BunkerSync::new(config);
+        self
+    }
+
+    /// Use `config` for fork resolution
+    pub fn with_fork_config(mut self, config: ForkConfig) -> Self {
+        self.forks = ForkResolver::new(config);
+        self
+    }
+
+    /// Use `config` for hopping pattern agreement
+    pub fn with_hop_sync_config(mut self, config: HopSyncConfig) -> Self {
+        self.hop_sync = HopSync::new(config);
+        self
+    }
+
+    /// Use `config` for gossip propagation and flood control
+    pub fn with_gossip_config(mut self, config: GossipConfig) -> Self {
+        self.gossip = AethericWhisper::new(self.node_id.clone()).with_config(config);
+        self
+    }
+
+    /// Seed the generator behind relay targets and hopping seeds
+    ///
+    /// Only for reproducible runs such as simulation; left alone it is
+    /// seeded from the OS.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
     /// Add or update a peer
     #[tracing::instrument(skip(self, peer), fields(peer_id = %peer.node_id, trust_score = %peer.trust_score))]
     pub fn add_peer(&mut self, peer: PeerInfo) -> Result<(), String> {
         debug!("Adding peer to mesh");
 
-        // Update peer table
+        if self.is_revoked(&peer.node_id) {
+            return Err(format!("Peer {} has been revoked", peer.node_id));
+        }
+
+        // Update peer table; the key the peer was admitted with supersedes
+        // any a neighbor advertised for it
         self.peer_table.upsert_peer(peer.clone())?;
+        self.node_keys.remove(&peer.node_id);
 
-        // Update routing table with direct link
-        // TODO: Replace hardcoded values with actual link quality measurements
+        // Update routing table with direct link, keeping what the radio
+        // layer measured; until it reports, assume a good link
+        let (snr_db, packet_error_rate) = self
+            .routing_table
+            .get_neighbor_link_quality(&peer.node_id)
+            .map_or((20.0, 0.01), |measured| {
+                (measured.snr_db, measured.packet_error_rate)
+            });
         let link_quality = LinkQuality {
-            snr_db: 20.0, // Placeholder - should be measured from radio layer
+            snr_db,
             trust_score: peer.trust_score,
             latency_ms: peer.latency_ms,
-            packet_error_rate: 0.01, // Placeholder - should be measured from radio layer
+            packet_error_rate,
             last_measured: peer.last_seen,
         };
 
@@ -93,6 +197,11 @@ impl TacticalMesh {
         warn!("Removing peer from mesh");
         self.peer_table.remove_peer(node_id);
         self.routing_table.remove_neighbor(node_id);
+        self.advertisement_timestamps.remove(node_id);
+        self.links.remove(node_id);
+        self.sync.remove(node_id);
+        self.forks.remove(node_id);
+        self.hop_sync.remove(node_id);
 
         // Check if we need to enter bunker mode
         if self.peer_table.is_bunker_mode() {
@@ -103,20 +212,479 @@ impl TacticalMesh {
         info!(peer_count = self.peer_table.peer_count(), "Peer removed");
     }
 
-    /// Process incoming gossip message
-    pub fn process_gossip(&mut self, message: GossipMessage) -> GossipResult {
+    /// Record that a peer was heard from just now
+    pub fn touch_peer(&mut self, node_id: &str) -> bool {
+        self.peer_table.touch(node_id, current_timestamp())
+    }
+
+    /// Remove peers not heard from within `max_age_ms`
+    ///
+    /// Returns the removed node IDs.
+    pub fn age_out_peers(&mut self, max_age_ms: u64) -> Vec<String> {
+        let stale = self.peer_table.stale_peers(max_age_ms);
+        for node_id in &stale {
+            info!(peer_id = %node_id, "Peer timed out");
+            self.remove_peer(node_id);
+        }
+        stale
+    }
+
+    /// Seed peer addresses for WAN bootstrapping
+    pub fn seed_peers(&self) -> &[String] {
+        self.peer_table.seed_peers()
+    }
+
+    /// Process a gossip message delivered by neighbor `from`
+    ///
+    /// Each neighbor is held to the gossip rate limit. The message must be
+    /// signed by its source, and is verified against the key the source was
+    /// admitted with if it is or was our peer, or else the key advertised
+    /// for it along with the route to it. Gossip from a source with no
+    /// known key is dropped, so nothing unverified is relayed. A neighbor
+    /// caught sending a forged message of its own has its trust halved.
+    pub fn process_gossip(&mut self, from: &str, message: GossipMessage) -> GossipResult {
+        if self.is_revoked(&message.source_node) {
+            return GossipResult::Dropped("Source node revoked".to_string());
+        }
+        if message.source_node == self.node_id {
+            return GossipResult::Dropped("Own message echoed back".to_string());
+        }
+        if !self.gossip.check_rate(from) {
+            return GossipResult::Dropped(format!("Rate limit exceeded by {}", from));
+        }
+
+        let Some(public_key) = self.identity_key(&message.source_node) else {
+            return GossipResult::Dropped(format!(
+                "No key known for source {}",
+                message.source_node
+            ));
+        };
+        let valid = self
+            .security
+            .verify_gossip(&message, public_key)
+            .unwrap_or(false);
+        if !valid {
+            if let Some(peer) = self.peer_table.get_peer(from) {
+                if from == message.source_node {
+                    let trust_score = peer.trust_score * 0.5;
+                    warn!(peer_id = %from, trust_score, "Forged gossip signature");
+                    let _ = self.peer_table.update_trust_score(from, trust_score);
+                }
+            }
+            return GossipResult::Dropped(format!(
+                "Invalid signature from {}",
+                message.source_node
+            ));
+        }
+
+        // Duplicates are recognized by their signed content
         self.gossip.process_message(message)
     }
 
-    /// Update local state and create gossip message
+    /// Identity key of `node_id`: the key it was admitted with as a peer,
+    /// or else the key a neighbor advertised for it
+    fn identity_key(&self, node_id: &str) -> Option<&[u8]> {
+        self.peer_table
+            .get_peer(node_id)
+            .map(|peer| peer.public_key.as_slice())
+            .or_else(|| self.peer_table.pinned_key(node_id))
+            .or_else(|| self.node_keys.get(node_id).map(Vec::as_slice))
+    }
+
+    /// Neighbors to relay an accepted gossip message to
+    ///
+    /// Up to the configured fanout, chosen at random among linked
+    /// neighbors other than `from` and the message's source.
+    pub fn gossip_targets(&mut self, from: &str, message: &GossipMessage) -> Vec<String> {
+        let mut candidates: Vec<String> = self
+            .peer_table
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.node_id.clone())
+            .filter(|node_id| {
+                node_id != from
+                    && *node_id != message.source_node
+                    && self.is_link_established(node_id)
+            })
+            .collect();
+        // Peer table order varies from run to run; the draw should not
+        candidates.sort();
+        self.gossip.select_fanout(&candidates, &mut self.rng)
+    }
+
+    /// Slash the source of two contradicting gossip messages
+    ///
+    /// Both messages must be signed with the key the source was admitted
+    /// with, so only neighbors can be slashed this way. The source is
+    /// revoked, removed and not admitted again. Returns the signed event.
+    pub fn slash_equivocation(
+        &mut self,
+        first: &GossipMessage,
+        second: &GossipMessage,
+    ) -> Result<SlashingEvent, String> {
+        let node_id = first.source_node.clone();
+        if second.source_node != node_id
+            || second.block_height != first.block_height
+            || second.merkle_root == first.merkle_root
+        {
+            return Err(format!("Gossip from {} does not equivocate", node_id));
+        }
+        let peer = self
+            .peer_table
+            .get_peer(&node_id)
+            .ok_or_else(|| format!("Cannot verify gossip from non-neighbor {}", node_id))?;
+        for message in [first, second] {
+            if !self.security.verify_gossip(message, &peer.public_key)? {
+                return Err(format!("Invalid gossip signature from {}", node_id));
+            }
+        }
+
+        let fault_type = ByzantineFaultType::Equivocation {
+            seq_no: first.block_height,
+            hash1: first.merkle_root.clone(),
+            hash2: second.merkle_root.clone(),
+        };
+        let evidence = serde_json::to_string(&[first, second]).map_err(|e| e.to_string())?;
+        let event = self
+            .security
+            .slashing_event(node_id.clone(), fault_type, Some(evidence))?;
+        let slasher_key = self
+            .public_key()
+            .ok_or_else(|| "No signing service configured".to_string())
+            .and_then(|key| {
+                VerifyingKey::try_from(key).map_err(|e| format!("Invalid public key: {}", e))
+            })?;
+        self.slashing
+            .record_slashing_event(event.clone(), &slasher_key)
+            .map_err(|e| e.to_string())?;
+        warn!(peer_id = %node_id, height = first.block_height, "Peer slashed for equivocation");
+
+        self.gossip.forget_peer(&node_id);
+        self.remove_peer(&node_id);
+        Ok(event)
+    }
+
+    /// Whether `node_id` has been revoked by slashing
+    pub fn is_revoked(&self, node_id: &str) -> bool {
+        self.slashing.get_node_state(node_id).is_revoked()
+    }
+
+    /// Slashing events issued by this node
+    pub fn slashing_events(&self) -> &[SlashingEvent] {
+        self.slashing.get_slashing_events()
+    }
+
+    /// Fork resolution configuration
+    pub fn fork_config(&self) -> &ForkConfig {
+        self.forks.config()
+    }
+
+    /// Start resolving the divergence reported by a gossip result
+    ///
+    /// Forks are only resolved with neighbors; a conflict gossiped from
+    /// further away is resolved once a neighbor on that chain gossips it.
+    /// Returns the neighbor and the request to send it.
+    pub fn begin_fork_resolution(
+        &mut self,
+        result: &GossipResult,
+    ) -> Option<(String, ForkMessage)> {
+        let conflict = ForkConflict::from_gossip(result, self.gossip.local_state())?;
+        self.peer_table.get_peer(&conflict.peer_node)?;
+        let node_id = conflict.peer_node.clone();
+        let request = self.forks.start(conflict)?;
+        Some((node_id, request))
+    }
+
+    /// Resend unanswered fork resolution requests
+    pub fn fork_tick(&mut self) -> ForkTick {
+        let peers: Vec<String> = self
+            .peer_table
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.node_id.clone())
+            .collect();
+        let tick = self.forks.tick(&peers);
+        for (node_id, reason) in &tick.abandoned {
+            warn!(peer_id = %node_id, "Fork resolution abandoned: {}", reason);
+        }
+        tick
+    }
+
+    /// Apply a peer's fork resolution message
+    ///
+    /// Once the chains are found to have forked, the branch to keep is
+    /// chosen from the trust-weighted consensus of the other peers. If the
+    /// peer's branch is taken, its state becomes our gossiped state.
+    pub fn process_fork(&mut self, from: &str, message: ForkMessage) -> Result<ForkOutput, String> {
+        if self.peer_table.get_peer(from).is_none() {
+            return Err(format!("Fork message from unknown peer {}", from));
+        }
+        let conflict = self.forks.conflict(from).cloned();
+        let gossip = &self.gossip;
+        let peer_table = &self.peer_table;
+        let output = self
+            .forks
+            .handle(&mut self.bunker_mode, from, message, |conflict| {
+                if gossip.local_state()
+                    != Some((conflict.local_root.as_slice(), conflict.local_height))
+                {
+                    return Err("Local state changed during resolution".to_string());
+                }
+                // The two sides of the fork are not counted
+                let trust_scores = peer_table
+                    .get_all_peers()
+                    .into_iter()
+                    .filter(|peer| peer.node_id != conflict.peer_node)
+                    .map(|peer| (peer.node_id.clone(), peer.trust_score))
+                    .collect();
+                let view = gossip.get_weighted_consensus_view(&trust_scores);
+                Ok(choose_branch(view.as_ref(), conflict))
+            })
+            .map_err(|e| e.to_string())?;
+
+        let (Some(outcome), Some(conflict)) = (&output.outcome, conflict) else {
+            return Ok(output);
+        };
+        info!(peer_id = %from, ?outcome, "Fork resolution finished");
+        if matches!(
+            outcome,
+            ForkOutcome::FastForward { .. } | ForkOutcome::AdoptedPeer { .. }
+        ) && self.gossip.local_state()
+            == Some((conflict.local_root.as_slice(), conflict.local_height))
+        {
+            self.gossip
+                .update_local_state(conflict.peer_root, conflict.peer_height);
+        }
+        Ok(output)
+    }
+
+    /// Merkle root and height of our own chain, as gossiped
+    pub fn local_state(&self) -> Option<(&[u8], u64)> {
+        self.gossip.local_state()
+    }
+
+    /// Update local state and create the signed gossip message announcing it
     pub fn update_local_state(
         &mut self,
         merkle_root: Vec<u8>,
         block_height: u64,
-        signature: Vec<u8>,
-    ) -> Option<GossipMessage> {
+    ) -> Result<GossipMessage, String> {
         self.gossip.update_local_state(merkle_root, block_height);
-        self.gossip.create_gossip_message(signature)
+        let mut message = self
+            .gossip
+            .create_gossip_message()
+            .ok_or_else(|| "No local state to gossip".to_string())?;
+        self.security.sign_gossip(&mut message)?;
+        Ok(message)
+    }
+
+    /// Node identifier
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Key this node signs with, if it has a signing identity
+    pub fn public_key(&self) -> Option<&[u8]> {
+        self.security
+            .identity()
+            .map(|identity| identity.public_key.as_slice())
+    }
+
+    /// Build the signed advertisement of this node's routes for `neighbor`
+    pub fn route_advertisement_for(
+        &mut self,
+        neighbor: &str,
+    ) -> Result<RouteAdvertisement, String> {
+        let mut routes = self.routing_table.advertised_routes_for(neighbor);
+        for route in &mut routes {
+            if let Some(public_key) = self.identity_key(&route.destination) {
+                route.public_key = public_key.to_vec();
+            }
+        }
+        let mut advertisement = RouteAdvertisement {
+            origin: self.node_id.clone(),
+            routes,
+            timestamp: current_timestamp(),
+            signature: Vec::new(),
+        };
+        advertisement.signature = self
+            .security
+            .sign_routing_update(&advertisement.signing_bytes())?;
+        Ok(advertisement)
+    }
+
+    /// Learn routes from a neighbor's advertisement
+    ///
+    /// The advertisement must come from its origin, be signed with the key
+    /// the origin was admitted with and be newer than the last one accepted
+    /// from it. Keys advertised for destinations we hold no key for are
+    /// learned, so their gossip can be verified, if the destination's node
+    /// ID is derived from the key; a key that differs from the one already
+    /// held or pinned is ignored. Returns the number of routes added,
+    /// updated or withdrawn.
+    pub fn process_route_advertisement(
+        &mut self,
+        from: &str,
+        advertisement: RouteAdvertisement,
+    ) -> Result<usize, String> {
+        if advertisement.origin != from {
+            return Err(format!(
+                "Route advertisement from {} relayed by {}",
+                advertisement.origin, from
+            ));
+        }
+        let peer = self
+            .peer_table
+            .get_peer(from)
+            .ok_or_else(|| format!("Route advertisement from unknown peer {}", from))?;
+
+        let valid = self.security.verify_routing_update(
+            &advertisement.signing_bytes(),
+            &advertisement.signature,
+            &peer.public_key,
+        )?;
+        if !valid {
+            warn!(peer_id = %from, "Route advertisement signature invalid");
+            return Err(format!(
+                "Invalid route advertisement signature from {}",
+                from
+            ));
+        }
+
+        let last = self
+            .advertisement_timestamps
+            .get(from)
+            .copied()
+            .unwrap_or(0);
+        if advertisement.timestamp < last {
+            return Err(format!("Replayed route advertisement from {}", from));
+        }
+
+        let changed = self
+            .routing_table
+            .apply_advertisement(from, &advertisement.routes)?;
+        self.advertisement_timestamps
+            .insert(from.to_string(), advertisement.timestamp);
+        for route in advertisement.routes {
+            if route.public_key.is_empty() || route.destination == self.node_id {
+                continue;
+            }
+            match self.identity_key(&route.destination) {
+                None if node_id_for_key(&route.public_key) == route.destination => {
+                    self.node_keys.insert(route.destination, route.public_key);
+                }
+                None => {
+                    warn!(
+                        peer_id = %from,
+                        destination = %route.destination,
+                        "Ignoring advertised key not bound to its node ID"
+                    );
+                }
+                Some(known) if known != route.public_key.as_slice() => {
+                    warn!(
+                        peer_id = %from,
+                        destination = %route.destination,
+                        "Ignoring advertised key that differs from the one held"
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        if changed > 0 {
+            debug!(peer_id = %from, changed, "Routes updated from advertisement");
+        }
+        Ok(changed)
+    }
+
+    /// Peer link session configuration
+    pub fn link_config(&self) -> &LinkConfig {
+        self.links.config()
+    }
+
+    /// Whether traffic to `node_id` is encrypted and can be sent
+    pub fn is_link_established(&self, node_id: &str) -> bool {
+        self.links.is_established(node_id)
+    }
+
+    /// Current session key epoch with `node_id`
+    pub fn link_epoch(&self, node_id: &str) -> Option<u64> {
+        self.links.epoch(node_id)
+    }
+
+    /// Start or rotate peer link sessions as needed
+    ///
+    /// Returned handshakes are signed and ready to send. Peers whose link
+    /// failed, such as by not completing a key rotation, are removed.
+    pub fn maintain_links(&mut self) -> LinkMaintenance {
+        let peers: Vec<String> = self
+            .peer_table
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.node_id.clone())
+            .collect();
+        let mut maintenance = self.links.maintain(&peers);
+        maintenance.handshakes.retain_mut(|(node_id, handshake)| {
+            match self.security.sign_key_exchange(&mut handshake.exchange) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!(peer_id = %node_id, "Cannot sign key exchange: {}", e);
+                    false
+                }
+            }
+        });
+        for (node_id, reason) in &maintenance.failed {
+            warn!(peer_id = %node_id, "Peer link failed: {}", reason);
+            self.remove_peer(node_id);
+        }
+        maintenance
+    }
+
+    /// Apply a peer's key exchange
+    ///
+    /// The exchange must be signed with the key the peer was admitted with.
+    /// Returns the signed exchange to send back, if the peer is waiting on one.
+    pub fn process_link_handshake(
+        &mut self,
+        from: &str,
+        handshake: LinkHandshake,
+    ) -> Result<Option<LinkHandshake>, String> {
+        let peer = self
+            .peer_table
+            .get_peer(from)
+            .ok_or_else(|| format!("Key exchange from unknown peer {}", from))?;
+        let valid = self
+            .security
+            .verify_key_exchange(&handshake.exchange, &peer.public_key)?;
+        if !valid {
+            warn!(peer_id = %from, "Key exchange signature invalid");
+            return Err(format!("Invalid key exchange signature from {}", from));
+        }
+
+        let Some(mut reply) = self.links.handle_handshake(from, handshake)? else {
+            return Ok(None);
+        };
+        self.security.sign_key_exchange(&mut reply.exchange)?;
+        Ok(Some(reply))
+    }
+
+    /// Encrypt a frame payload for `node_id`
+    pub fn seal_payload(
+        &mut self,
+        node_id: &str,
+        payload: &FramePayload,
+    ) -> Result<SealedPayload, String> {
+        let plaintext = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        self.links.seal(node_id, &plaintext)
+    }
+
+    /// Decrypt a frame payload sealed by `from`
+    pub fn open_payload(
+        &mut self,
+        from: &str,
+        sealed: &SealedPayload,
+    ) -> Result<FramePayload, String> {
+        let plaintext = self.links.open(from, sealed)?;
+        serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
     }
 
     /// Find next hop for routing to destination
@@ -129,13 +697,198 @@ impl TacticalMesh {
         self.frequency_hopper.set_pattern(pattern);
     }
 
-    /// Update link quality metrics and check for frequency hop
+    /// Update link quality metrics
+    ///
+    /// Jamming is not answered by hopping alone, which would leave the rest
+    /// of the swarm behind; the next hop tick proposes a new pattern to
+    /// trusted neighbors instead.
     pub fn update_link_metrics(&mut self, per: f64) {
         self.frequency_hopper.update_per(per);
+    }
 
-        // Check if we need to hop due to jamming
-        if self.frequency_hopper.should_hop() {
-            self.frequency_hopper.hop();
+    /// Record signal and packet error rate measured by the radio layer on
+    /// the link to a peer
+    ///
+    /// Route costs to the peer follow from the next advertisement on.
+    pub fn update_link_quality(
+        &mut self,
+        node_id: &str,
+        snr_db: f64,
+        packet_error_rate: f64,
+    ) -> Result<(), String> {
+        let peer = self
+            .peer_table
+            .get_peer(node_id)
+            .ok_or_else(|| format!("Unknown peer {}", node_id))?;
+        let link_quality = LinkQuality {
+            snr_db,
+            trust_score: peer.trust_score,
+            latency_ms: peer.latency_ms,
+            packet_error_rate: packet_error_rate.clamp(0.0, 1.0),
+            last_measured: current_timestamp(),
+        };
+        self.routing_table
+            .update_neighbor(node_id.to_string(), link_quality);
+        Ok(())
+    }
+
+    /// Link quality to a direct neighbor
+    pub fn link_quality(&self, node_id: &str) -> Option<&LinkQuality> {
+        self.routing_table.get_neighbor_link_quality(node_id)
+    }
+
+    /// Hopping pattern agreement configuration
+    pub fn hop_sync_config(&self) -> &HopSyncConfig {
+        self.hop_sync.config()
+    }
+
+    /// Hop epoch at the current time
+    pub fn current_epoch(&self) -> u64 {
+        self.hop_sync.epoch_at(current_timestamp())
+    }
+
+    /// Hopping plan in force
+    pub fn hop_plan(&self) -> Option<&HopPlan> {
+        self.hop_sync.active()
+    }
+
+    /// Whether a trusted neighbor has been heard within the sync timeout
+    pub fn is_hop_synced(&self) -> bool {
+        self.hop_sync.is_synced()
+    }
+
+    /// Issue a new hopping pattern, switching over `lead_epochs` after `epoch`
+    ///
+    /// Returns the signed plan to send, by neighbor.
+    pub fn propose_hopping_pattern(
+        &mut self,
+        epoch: u64,
+    ) -> Result<Vec<(String, HopSyncMessage)>, String> {
+        let config = self.hop_sync.config();
+        let mut plan = HopPlan {
+            issuer: self.node_id.clone(),
+            generation: self.hop_sync.next_generation(),
+            seed: self.rng.gen::<[u8; 32]>().to_vec(),
+            num_channels: config.num_channels,
+            channel_range: config.channel_range,
+            switch_epoch: epoch + config.lead_epochs,
+            signature: Vec::new(),
+        };
+        self.security.sign_hop_plan(&mut plan)?;
+        info!(
+            generation = plan.generation,
+            switch_epoch = plan.switch_epoch,
+            "Proposing hopping pattern"
+        );
+        let neighbors = self.hop_neighbors();
+        Ok(self.hop_sync.propose(plan, epoch, &neighbors))
+    }
+
+    /// Drive hopping pattern agreement for `epoch` and tune to its channel
+    ///
+    /// Proposes a new pattern if jamming has been detected.
+    pub fn hop_tick(&mut self, epoch: u64) -> Result<HopTick, String> {
+        let mut proposal = Vec::new();
+        if self.frequency_hopper.is_jamming_detected() && self.hop_sync.may_propose(epoch) {
+            warn!(
+                per = self.frequency_hopper.get_current_per(),
+                "Jamming detected"
+            );
+            proposal = self.propose_hopping_pattern(epoch)?;
+        }
+        let neighbors = self.hop_neighbors();
+        let mut tick = self.hop_sync.tick(epoch, &neighbors);
+        tick.messages.splice(0..0, proposal);
+        if let Some(plan) = &tick.activated {
+            self.activate_hop_plan(plan);
+        }
+        self.tune(epoch);
+        Ok(tick)
+    }
+
+    /// Process a hop sync message from trusted neighbor `from` in `epoch`
+    ///
+    /// Plans must carry their issuer's signature, checked against the key
+    /// the issuer was admitted with or, beyond our neighbors, the key
+    /// advertised for it; plans from issuers with no known key are refused.
+    pub fn process_hop_sync(
+        &mut self,
+        from: &str,
+        message: HopSyncMessage,
+        epoch: u64,
+    ) -> Result<HopSyncOutput, String> {
+        let peer = self
+            .peer_table
+            .get_peer(from)
+            .ok_or_else(|| format!("Hop sync from unknown peer {}", from))?;
+        if peer.trust_score < self.hop_sync.config().min_trust {
+            return Err(format!("Hop sync from untrusted peer {}", from));
+        }
+        if let HopSyncMessage::Plan(plan) = &message {
+            let public_key = self
+                .identity_key(&plan.issuer)
+                .ok_or_else(|| format!("Hopping plan from unknown issuer {}", plan.issuer))?;
+            if !self
+                .security
+                .verify_hop_plan(plan, public_key)
+                .unwrap_or(false)
+            {
+                return Err(format!(
+                    "Invalid hopping plan signature from {}",
+                    plan.issuer
+                ));
+            }
+        }
+
+        let neighbors = self.hop_neighbors();
+        let output = self
+            .hop_sync
+            .handle(from, message, epoch, &neighbors)
+            .map_err(|e| e.to_string())?;
+        if let Some(plan) = &output.activated {
+            self.activate_hop_plan(plan);
+        }
+        self.tune(epoch);
+        Ok(output)
+    }
+
+    /// Record that a neighbor was heard in `epoch`
+    pub fn record_hop_contact(&mut self, epoch: u64) {
+        self.hop_sync.contact(epoch);
+    }
+
+    /// Neighbors trusted enough to exchange hopping plans with
+    fn hop_neighbors(&self) -> Vec<String> {
+        let min_trust = self.hop_sync.config().min_trust;
+        self.peer_table
+            .get_all_peers()
+            .into_iter()
+            .filter(|peer| peer.trust_score >= min_trust)
+            .map(|peer| peer.node_id.clone())
+            .collect()
+    }
+
+    fn activate_hop_plan(&mut self, plan: &HopPlan) {
+        info!(
+            issuer = %plan.issuer,
+            generation = plan.generation,
+            "Hopping pattern in force"
+        );
+        let dwell_time_ms = self.hop_sync.config().epoch_duration.as_millis() as u64;
+        self.frequency_hopper
+            .set_pattern(plan.pattern(dwell_time_ms));
+    }
+
+    /// Tune the frequency hopper to the channel for `epoch`
+    fn tune(&mut self, epoch: u64) {
+        match self.hop_sync.tuning(epoch) {
+            Tuning::Pattern => {
+                self.frequency_hopper.hop_at_epoch(epoch);
+            }
+            Tuning::Rendezvous => {
+                let channel = self.hop_sync.config().rendezvous_channel;
+                self.frequency_hopper.park(channel);
+            }
         }
     }
 
@@ -150,6 +903,89 @@ impl TacticalMesh {
         }
     }
 
+    /// Current bunker mode state
+    pub fn bunker_state(&self) -> &BunkerState {
+        self.bunker_mode.state()
+    }
+
+    /// Bunker sync configuration
+    pub fn sync_config(&self) -> &SyncConfig {
+        self.sync.config()
+    }
+
+    /// Drive store-and-forward sync with peers
+    ///
+    /// While syncing after isolation, opens a session with each peer and
+    /// returns to the connected state once a session has completed with
+    /// nothing left unsynced. Returns the messages to send, by peer.
+    pub fn sync_tick(&mut self) -> Result<Vec<(String, SyncMessage)>, String> {
+        if self.bunker_mode.state() == &BunkerState::Syncing {
+            self.finish_sync()?;
+        }
+        let syncing = self.bunker_mode.state() == &BunkerState::Syncing;
+        let peers: Vec<String> = self
+            .peer_table
+            .get_all_peers()
+            .into_iter()
+            .map(|peer| peer.node_id.clone())
+            .collect();
+        self.sync
+            .tick(&self.bunker_mode, &peers, syncing)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Apply a peer's sync message
+    pub fn process_sync(&mut self, from: &str, message: SyncMessage) -> Result<SyncOutput, String> {
+        if self.peer_table.get_peer(from).is_none() {
+            return Err(format!("Sync message from unknown peer {}", from));
+        }
+        let output = self
+            .sync
+            .handle(&mut self.bunker_mode, from, message)
+            .map_err(|e| e.to_string())?;
+        if output.completed {
+            info!(peer_id = %from, "Bunker sync with peer complete");
+            if self.bunker_mode.state() == &BunkerState::Syncing {
+                self.finish_sync()?;
+            }
+        }
+        Ok(output)
+    }
+
+    /// Return to the connected state if a sync session has completed and
+    /// nothing is left unsynced; otherwise go another round with those peers
+    fn finish_sync(&mut self) -> Result<(), String> {
+        let completed = self.sync.completed_peers();
+        if completed.is_empty() {
+            return Ok(());
+        }
+        let (block_count, event_count) = self
+            .bunker_mode
+            .get_unsynced_count()
+            .map_err(|e| e.to_string())?;
+        if block_count == 0 && event_count == 0 {
+            info!("Bunker sync complete; returning to connected state");
+            self.bunker_mode.enter_connected_state();
+        } else {
+            // Records were stored while the session ran
+            for node_id in completed {
+                self.sync.remove(&node_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get up to `limit` stored blocks above `height`, lowest first
+    pub fn get_blocks_above(
+        &self,
+        height: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<StoredBlock>, String> {
+        self.bunker_mode
+            .get_blocks_above(height, limit)
+            .map_err(|e| e.to_string())
+    }
+
     /// Store data locally (for bunker mode)
     pub fn store_block(&mut self, block: StoredBlock) -> Result<(), String> {
         self.bunker_mode
@@ -236,6 +1072,9 @@ pub struct MeshStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aethercore_crypto::signing::EventSigningService;
+    use aethercore_identity::{Attestation, PlatformIdentity};
+    use std::time::Duration;
 
     fn create_test_peer(id: &str) -> PeerInfo {
         PeerInfo {
@@ -263,6 +1102,26 @@ mod tests {
         assert!(!status.bunker_mode);
     }
 
+    #[test]
+    fn test_measured_link_quality_survives_peer_update() {
+        let mut mesh = TacticalMesh::new("node1".to_string(), vec![], ":memory:").unwrap();
+        assert!(mesh.update_link_quality("node2", 5.0, 0.2).is_err());
+
+        mesh.add_peer(create_test_peer("node2")).unwrap();
+        let assumed = mesh.link_quality("node2").unwrap().compute_cost();
+        mesh.update_link_quality("node2", -5.0, 0.3).unwrap();
+        assert!(mesh.link_quality("node2").unwrap().compute_cost() > assumed);
+
+        // Re-adding the peer, as discovery does, keeps the measurement
+        let mut peer = create_test_peer("node2");
+        peer.trust_score = 0.5;
+        mesh.add_peer(peer).unwrap();
+        let link_quality = mesh.link_quality("node2").unwrap();
+        assert_eq!(link_quality.snr_db, -5.0);
+        assert_eq!(link_quality.packet_error_rate, 0.3);
+        assert_eq!(link_quality.trust_score, 0.5);
+    }
+
     #[test]
     fn test_add_peer_exits_bunker_mode() {
         let mut mesh = TacticalMesh::new("node1".to_string(), vec![], ":memory:").unwrap();
@@ -291,6 +1150,20 @@ mod tests {
         assert!(status.bunker_mode);
     }
 
+    #[test]
+    fn test_age_out_peers() {
+        let mut mesh = TacticalMesh::new("node1".to_string(), vec![], ":memory:").unwrap();
+        let mut stale = create_test_peer("node2");
+        stale.last_seen -= 60_000;
+        mesh.add_peer(stale).unwrap();
+        mesh.add_peer(create_test_peer("node3")).unwrap();
+
+        assert_eq!(mesh.age_out_peers(30_000), vec!["node2".to_string()]);
+        assert!(mesh.get_peer("node2").is_none());
+        assert!(mesh.find_route("node2").is_none());
+        assert!(mesh.get_peer("node3").is_some());
+    }
+
     #[test]
     fn test_routing_through_peer() {
         let mut mesh = TacticalMesh::new("node1".to_string(), vec![], ":memory:").unwrap();
@@ -301,6 +1174,201 @@ mod tests {
         assert_eq!(next_hop, Some("node2".to_string()));
     }
 
+    /// Mesh that signs advertisements, and a peer entry other meshes can use for it
+    fn create_signed_mesh(id: &str) -> (TacticalMesh, PeerInfo) {
+        signed_mesh(id, id)
+    }
+
+    /// Signed mesh whose node ID is derived from its key
+    fn create_bound_mesh(seed: &str) -> (TacticalMesh, PeerInfo) {
+        let key = EventSigningService::from_key(blake3::hash(seed.as_bytes()).as_bytes())
+            .unwrap()
+            .public_key();
+        signed_mesh(&node_id_for_key(&key), seed)
+    }
+
+    fn signed_mesh(id: &str, seed: &str) -> (TacticalMesh, PeerInfo) {
+        let signing_service =
+            EventSigningService::from_key(blake3::hash(seed.as_bytes()).as_bytes()).unwrap();
+        let mut peer = create_test_peer(id);
+        peer.public_key = signing_service.public_key();
+        let identity = PlatformIdentity {
+            id: id.to_string(),
+            public_key: peer.public_key.clone(),
+            attestation: Attestation::None,
+            created_at: 0,
+            metadata: HashMap::new(),
+        };
+        let mesh = TacticalMesh::new(id.to_string(), vec![], ":memory:")
+            .unwrap()
+            .with_security(MeshSecurity::new().with_signing(signing_service, identity));
+        (mesh, peer)
+    }
+
+    #[test]
+    fn test_route_advertisement_learns_multi_hop_routes() {
+        let (mut node1, node1_peer) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        let (_, node3_peer) = create_signed_mesh("node3");
+        node1.add_peer(node2_peer).unwrap();
+        node2.add_peer(node1_peer).unwrap();
+        node2.add_peer(node3_peer).unwrap();
+
+        // node3 is learned via node2
+        let advertisement = node2.route_advertisement_for("node1").unwrap();
+        let changed = node1
+            .process_route_advertisement("node2", advertisement)
+            .unwrap();
+        assert_eq!(changed, 1);
+        assert_eq!(node1.find_route("node3"), Some("node2".to_string()));
+
+        let route = |destination: &str| {
+            node1
+                .routing_table
+                .get_routes()
+                .into_iter()
+                .find(|route| route.destination == destination)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(route("node3").hop_count, 2);
+        assert!(route("node3").cost > route("node2").cost);
+
+        // node1 reaches node3 through node2, so tells node2 it cannot
+        let advertisement = node1.route_advertisement_for("node2").unwrap();
+        assert_eq!(advertisement.routes.len(), 1);
+        assert_eq!(advertisement.routes[0].cost, None);
+        assert_eq!(
+            node2.process_route_advertisement("node1", advertisement),
+            Ok(0)
+        );
+        assert_eq!(node2.find_route("node3"), Some("node3".to_string()));
+    }
+
+    #[test]
+    fn test_route_advertisement_verification() {
+        let (mut node1, _) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        let (_, node3_peer) = create_signed_mesh("node3");
+        node1.add_peer(node2_peer).unwrap();
+        node2.add_peer(node3_peer).unwrap();
+
+        let advertisement = node2.route_advertisement_for("node1").unwrap();
+
+        // Tampered routes no longer match the signature
+        let mut tampered = advertisement.clone();
+        tampered.routes[0].cost = Some(0.0);
+        assert!(node1
+            .process_route_advertisement("node2", tampered)
+            .is_err());
+
+        // Only neighbors' own advertisements are accepted
+        let mut relayed = advertisement.clone();
+        relayed.origin = "node4".to_string();
+        assert!(node1.process_route_advertisement("node2", relayed).is_err());
+        assert!(node1
+            .process_route_advertisement("node3", advertisement.clone())
+            .is_err());
+
+        // Unsigned meshes cannot advertise
+        let mut unsigned = TacticalMesh::new("node5".to_string(), vec![], ":memory:").unwrap();
+        assert!(unsigned.route_advertisement_for("node1").is_err());
+
+        // Older advertisements are replays once a newer one was accepted
+        let mut newer = node2.route_advertisement_for("node1").unwrap();
+        newer.timestamp = advertisement.timestamp + 1;
+        newer.signature = node2
+            .security
+            .sign_routing_update(&newer.signing_bytes())
+            .unwrap();
+        assert_eq!(node1.process_route_advertisement("node2", newer), Ok(1));
+        assert!(node1
+            .process_route_advertisement("node2", advertisement)
+            .is_err());
+    }
+
+    #[test]
+    fn test_route_advertisement_key_must_match_node_id() {
+        let (mut node1, _) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        let (_, node3_peer) = create_bound_mesh("node3");
+        let node3_id = node3_peer.node_id.clone();
+        let node2_key = node2_peer.public_key.clone();
+        node1.add_peer(node2_peer).unwrap();
+        node2.add_peer(node3_peer).unwrap();
+
+        // node2 claims node3 holds node2's own key
+        let mut advertisement = node2.route_advertisement_for("node1").unwrap();
+        let route = advertisement
+            .routes
+            .iter_mut()
+            .find(|route| route.destination == node3_id)
+            .unwrap();
+        route.public_key = node2_key;
+        advertisement.signature = node2
+            .security
+            .sign_routing_update(&advertisement.signing_bytes())
+            .unwrap();
+        node1
+            .process_route_advertisement("node2", advertisement)
+            .unwrap();
+        assert_eq!(node1.find_route(&node3_id), Some("node2".to_string()));
+        assert_eq!(node1.identity_key(&node3_id), None);
+
+        // The key the node ID is derived from is learned
+        let mut advertisement = node2.route_advertisement_for("node1").unwrap();
+        advertisement.timestamp += 1;
+        advertisement.signature = node2
+            .security
+            .sign_routing_update(&advertisement.signing_bytes())
+            .unwrap();
+        node1
+            .process_route_advertisement("node2", advertisement)
+            .unwrap();
+        let key = node1.identity_key(&node3_id).unwrap().to_vec();
+        assert_eq!(node_id_for_key(&key), node3_id);
+    }
+
+    #[test]
+    fn test_link_handshake_verification() {
+        let (mut node1, node1_peer) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        node1.add_peer(node2_peer).unwrap();
+
+        let (_, handshake) = node1.maintain_links().handshakes.remove(0);
+
+        // Exchanges are only accepted from admitted peers
+        assert!(node2
+            .process_link_handshake("node1", handshake.clone())
+            .is_err());
+        node2.add_peer(node1_peer).unwrap();
+
+        let mut tampered = handshake.clone();
+        tampered.exchange.public_key[0] ^= 1;
+        assert!(node2.process_link_handshake("node1", tampered).is_err());
+
+        let reply = node2
+            .process_link_handshake("node1", handshake)
+            .unwrap()
+            .unwrap();
+        assert!(node1
+            .process_link_handshake("node2", reply)
+            .unwrap()
+            .is_none());
+        assert!(node1.is_link_established("node2"));
+
+        let payload = FramePayload::Gossip(node1.update_local_state(vec![1; 32], 5).unwrap());
+        let sealed = node1.seal_payload("node2", &payload).unwrap();
+        match node2.open_payload("node1", &sealed).unwrap() {
+            FramePayload::Gossip(message) => assert_eq!(message.block_height, 5),
+            other => panic!("Expected Gossip, got {:?}", other),
+        }
+
+        // Removing the peer ends the session
+        node1.remove_peer("node2");
+        assert!(node1.seal_payload("node2", &payload).is_err());
+    }
+
     #[test]
     fn test_store_and_retrieve_data() {
         let mut mesh = TacticalMesh::new("node1".to_string(), vec![], ":memory:").unwrap();
@@ -319,4 +1387,301 @@ mod tests {
         assert_eq!(blocks.len(), 1);
         assert_eq!(blocks[0].height, 100);
     }
+
+    #[test]
+    fn test_bunker_sync_returns_to_connected() {
+        let mut node1 = TacticalMesh::new("node1".to_string(), vec![], ":memory:").unwrap();
+        let mut node2 = TacticalMesh::new("node2".to_string(), vec![], ":memory:").unwrap();
+        node2.add_peer(create_test_peer("node1")).unwrap();
+
+        // node1 loses its only peer and records a block while isolated
+        node1.add_peer(create_test_peer("node2")).unwrap();
+        node1.remove_peer("node2");
+        assert_eq!(node1.bunker_state(), &BunkerState::Isolated);
+        node1
+            .store_block(StoredBlock {
+                hash: StoredBlock::chain_hash(&[], 1, &[1, 2, 3]),
+                height: 1,
+                data: vec![1, 2, 3],
+                timestamp: 1000,
+                synced: false,
+            })
+            .unwrap();
+
+        node1.add_peer(create_test_peer("node2")).unwrap();
+        assert_eq!(node1.bunker_state(), &BunkerState::Syncing);
+
+        let mut to_node2 = node1.sync_tick().unwrap();
+        assert_eq!(to_node2.len(), 1);
+        let mut to_node1 = Vec::new();
+        while !to_node2.is_empty() || !to_node1.is_empty() {
+            for (_, message) in to_node2.drain(..) {
+                let output = node2.process_sync("node1", message).unwrap();
+                to_node1.extend(output.replies);
+            }
+            for message in std::mem::take(&mut to_node1) {
+                let output = node1.process_sync("node2", message).unwrap();
+                to_node2.extend(output.replies.into_iter().map(|m| ("node2".to_string(), m)));
+            }
+        }
+
+        assert_eq!(node1.bunker_state(), &BunkerState::Connected);
+        assert_eq!(node2.get_blocks_above(None, 10).unwrap().len(), 1);
+        assert!(node1.get_unsynced_data().unwrap().0.is_empty());
+        assert!(node1.sync_tick().unwrap().is_empty());
+
+        // Only peers may sync
+        assert!(node1
+            .process_sync("node3", SyncMessage::Ack { seq: 0 })
+            .is_err());
+    }
+
+    #[test]
+    fn test_gossip_signature_required() {
+        let (mut node1, _) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        let (mut node3, node3_peer) = create_bound_mesh("node3");
+        node1.add_peer(node2_peer).unwrap();
+
+        // A forged signature from the neighbor itself costs it trust
+        let mut forged = node2.update_local_state(vec![1; 32], 1).unwrap();
+        forged.signature = vec![0; 64];
+        assert!(matches!(
+            node1.process_gossip("node2", forged),
+            GossipResult::Dropped(_)
+        ));
+        assert_eq!(node1.get_peer("node2").unwrap().trust_score, 0.4);
+
+        let signed = node2.update_local_state(vec![2; 32], 2).unwrap();
+        assert!(matches!(
+            node1.process_gossip("node2", signed),
+            GossipResult::Accepted { .. }
+        ));
+
+        // node3 is beyond node2: its gossip cannot be verified, so is
+        // dropped, until node2 advertises its route to node3 with node3's key
+        let relayed = node3.update_local_state(vec![2; 32], 2).unwrap();
+        assert!(matches!(
+            node1.process_gossip("node2", relayed.clone()),
+            GossipResult::Dropped(_)
+        ));
+        node2.add_peer(node3_peer).unwrap();
+        let advertisement = node2.route_advertisement_for("node1").unwrap();
+        node1
+            .process_route_advertisement("node2", advertisement)
+            .unwrap();
+
+        // A forgery reusing the genuine message's ID neither passes nor
+        // shadows it, and does not cost the relaying neighbor trust
+        let mut forged = relayed.clone();
+        forged.merkle_root = vec![3; 32];
+        assert!(matches!(
+            node1.process_gossip("node2", forged),
+            GossipResult::Dropped(_)
+        ));
+        assert_eq!(node1.get_peer("node2").unwrap().trust_score, 0.4);
+        assert!(matches!(
+            node1.process_gossip("node2", relayed.clone()),
+            GossipResult::Accepted { .. }
+        ));
+        assert!(matches!(
+            node1.process_gossip("node2", relayed),
+            GossipResult::Duplicate
+        ));
+
+        // Our own gossip coming back is not processed
+        let echoed = node1.update_local_state(vec![2; 32], 2).unwrap();
+        assert!(matches!(
+            node1.process_gossip("node2", echoed),
+            GossipResult::Dropped(_)
+        ));
+    }
+
+    #[test]
+    fn test_gossip_rate_limited_and_fanned_out() {
+        let (node1, _) = create_signed_mesh("node1");
+        let mut node1 = node1.with_gossip_config(GossipConfig {
+            rate_limit: 2,
+            rate_window: Duration::from_secs(60),
+            fanout: 1,
+            ..GossipConfig::default()
+        });
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        node1.add_peer(node2_peer).unwrap();
+
+        let results: Vec<GossipResult> = (0..3)
+            .map(|height| {
+                let message = node2.update_local_state(vec![1; 32], height).unwrap();
+                node1.process_gossip("node2", message)
+            })
+            .collect();
+        assert!(matches!(results[0], GossipResult::Accepted { .. }));
+        assert!(matches!(results[1], GossipResult::Accepted { .. }));
+        match &results[2] {
+            GossipResult::Dropped(reason) => assert!(reason.contains("Rate limit")),
+            other => panic!("Expected Dropped, got {:?}", other),
+        }
+
+        // Without established links there is nobody to relay to
+        let message = node2.update_local_state(vec![1; 32], 3).unwrap();
+        assert!(node1.gossip_targets("node3", &message).is_empty());
+    }
+
+    #[test]
+    fn test_fork_resolved_by_trust_weighted_majority() {
+        let (mut node1, _) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        let (mut node3, node3_peer) = create_signed_mesh("node3");
+        node1.add_peer(node2_peer).unwrap();
+        node1.add_peer(node3_peer).unwrap();
+
+        let block = |parent: &[u8], height: u64, branch: u8| StoredBlock {
+            hash: StoredBlock::chain_hash(parent, height, &[branch]),
+            height,
+            data: vec![branch],
+            timestamp: 1000,
+            synced: true,
+        };
+        let genesis = block(&[], 1, 0);
+        let (tip1, tip2) = (block(&genesis.hash, 2, 1), block(&genesis.hash, 2, 2));
+        // node1's branch has the lower root, so only a majority displaces it
+        let (tip1, tip2) = if tip1.hash < tip2.hash {
+            (tip1, tip2)
+        } else {
+            (tip2, tip1)
+        };
+        for node in [&mut node1, &mut node2] {
+            node.store_block(genesis.clone()).unwrap();
+        }
+        node1.store_block(tip1.clone()).unwrap();
+        node2.store_block(tip2.clone()).unwrap();
+        node1.update_local_state(tip1.hash.clone(), 2).unwrap();
+        let from_node2 = node2.update_local_state(tip2.hash.clone(), 2).unwrap();
+
+        // node3 has taken node2's branch, outweighing node1's lower root
+        let from_node3 = node3.update_local_state(tip2.hash.clone(), 2).unwrap();
+        node1.process_gossip("node3", from_node3);
+        let result = node1.process_gossip("node2", from_node2);
+        assert!(matches!(result, GossipResult::ConflictDetected { .. }));
+
+        let (peer, request) = node1.begin_fork_resolution(&result).unwrap();
+        assert_eq!(peer, "node2");
+        // Only peers are answered
+        assert!(node2.process_fork("node1", request.clone()).is_err());
+        node2.add_peer(create_test_peer("node1")).unwrap();
+        let reply = node2
+            .process_fork("node1", request)
+            .unwrap()
+            .replies
+            .remove(0);
+        let output = node1.process_fork("node2", reply).unwrap();
+        assert_eq!(
+            output.outcome,
+            Some(ForkOutcome::AdoptedPeer {
+                ancestor_height: Some(1),
+                removed: 1,
+                added: 1
+            })
+        );
+
+        assert_eq!(node1.gossip.local_state(), Some((&tip2.hash[..], 2)));
+        let blocks = node1.get_blocks_above(None, 10).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].hash, tip2.hash);
+    }
+
+    #[test]
+    fn test_equivocating_neighbor_slashed() {
+        let (mut node1, _) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        node1.add_peer(node2_peer.clone()).unwrap();
+
+        let mut signed = |msg_id: &str, root: u8| {
+            let mut message = GossipMessage {
+                msg_id: msg_id.to_string(),
+                source_node: "node2".to_string(),
+                merkle_root: vec![root; 32],
+                block_height: 9,
+                timestamp: current_timestamp(),
+                signature: vec![],
+                hop_count: 0,
+            };
+            node2.security.sign_gossip(&mut message).unwrap();
+            message
+        };
+        let first = signed("msg1", 1);
+        let second = signed("msg2", 2);
+
+        node1.process_gossip("node2", first.clone());
+        let (first, second) = match node1.process_gossip("node2", second) {
+            GossipResult::Equivocation { first, second } => (first, second),
+            other => panic!("Expected Equivocation, got {:?}", other),
+        };
+
+        // Evidence must carry the source's own signatures
+        let mut forged = second.clone();
+        forged.signature = vec![0; 64];
+        assert!(node1.slash_equivocation(&first, &forged).is_err());
+        assert!(!node1.is_revoked("node2"));
+
+        let event = node1.slash_equivocation(&first, &second).unwrap();
+        assert_eq!(event.node_id, "node2");
+        assert_eq!(node1.slashing_events().len(), 1);
+        assert!(node1.is_revoked("node2"));
+        assert!(node1.get_peer("node2").is_none());
+
+        // A revoked node is neither readmitted nor listened to
+        assert!(node1.add_peer(node2_peer).is_err());
+        assert!(matches!(
+            node1.process_gossip("node2", first),
+            GossipResult::Dropped(_)
+        ));
+    }
+
+    #[test]
+    fn test_hop_plan_requires_trusted_sender_and_valid_signature() {
+        let (mut node1, node1_peer) = create_signed_mesh("node1");
+        let (mut node2, node2_peer) = create_signed_mesh("node2");
+        node1.add_peer(node2_peer).unwrap();
+        node2.add_peer(node1_peer).unwrap();
+
+        let proposal = |node: &mut TacticalMesh| {
+            let mut messages = node.propose_hopping_pattern(0).unwrap();
+            messages.pop().unwrap().1
+        };
+
+        // Plans from strangers are ignored outright
+        assert!(node1
+            .process_hop_sync("node3", proposal(&mut node2), 0)
+            .is_err());
+
+        // A plan relayed from an issuer whose key we do not hold is refused
+        let (mut node3, _) = create_signed_mesh("node3");
+        node3.add_peer(create_signed_mesh("node2").1).unwrap();
+        assert!(node1
+            .process_hop_sync("node2", proposal(&mut node3), 0)
+            .is_err());
+
+        // A tampered plan fails verification against the issuer's key
+        let mut forged = proposal(&mut node2);
+        if let HopSyncMessage::Plan(plan) = &mut forged {
+            plan.switch_epoch += 1;
+        }
+        assert!(node1.process_hop_sync("node2", forged, 0).is_err());
+        assert!(node1.hop_plan().is_none());
+
+        let output = node1
+            .process_hop_sync("node2", proposal(&mut node2), 0)
+            .unwrap();
+        assert!(matches!(
+            output.messages.as_slice(),
+            [(_, HopSyncMessage::Ack { .. })]
+        ));
+
+        // Below the trust floor a neighbor can no longer steer the pattern
+        node1.peer_table.update_trust_score("node2", 0.2).unwrap();
+        assert!(node1
+            .process_hop_sync("node2", proposal(&mut node2), 0)
+            .is_err());
+    }
 }
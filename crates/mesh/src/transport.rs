@@ -0,0 +1,344 @@
+//! Mesh Transport - Datagram links between mesh nodes
+//!
+//! Abstracts the medium mesh frames travel over. Each transport delivers
+//! whole frames between addresses, best-effort and unordered, so the same
+//! protocol logic runs over UDP, an in-process loopback network for tests,
+//! or a radio link.
+
+use crate::discovery::{AttestationMessage, Hello};
+use crate::error::{MeshError, MeshResult};
+use crate::fork::ForkMessage;
+use crate::gossip::GossipMessage;
+use crate::hop_sync::HopSyncMessage;
+use crate::link::{LinkHandshake, SealedPayload};
+use crate::routing::AdvertisedRoute;
+use crate::sync::SyncMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+
+/// Version of the mesh frame format
+pub const FRAME_VERSION: u8 = 1;
+
+/// Largest frame a transport must carry (maximum UDP payload over IPv4)
+pub const MAX_FRAME_SIZE: usize = 65_507;
+
+/// Frame exchanged between mesh nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshFrame {
+    /// Frame format version
+    pub version: u8,
+    /// Node ID of the sending node (the previous hop, not the originator)
+    pub source: String,
+    /// Frame contents
+    pub payload: FramePayload,
+}
+
+/// Contents of a mesh frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FramePayload {
+    /// Gossiped ledger state
+    Gossip(GossipMessage),
+    /// Routes the sender can reach
+    RouteAdvertisement(RouteAdvertisement),
+    /// Introduction to a candidate peer
+    Hello(Hello),
+    /// Attestation handshake step with a candidate peer
+    Attestation(Box<AttestationMessage>),
+    /// Session key exchange with a peer
+    LinkHandshake(LinkHandshake),
+    /// Bunker sync step with a peer
+    Sync(SyncMessage),
+    /// Fork resolution step with a peer
+    Fork(ForkMessage),
+    /// Hopping pattern agreement step with a peer
+    HopSync(HopSyncMessage),
+    /// Gossip, route advertisement, sync, fork resolution or hop sync step
+    /// encrypted for the receiving peer
+    Sealed(SealedPayload),
+}
+
+/// Routes advertised by a node to one of its neighbors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteAdvertisement {
+    /// Advertising node ID
+    pub origin: String,
+    /// The advertising node's full distance vector for this neighbor
+    pub routes: Vec<AdvertisedRoute>,
+    /// Timestamp of the advertisement
+    pub timestamp: u64,
+    /// Origin's signature over `signing_bytes()`
+    pub signature: Vec<u8>,
+}
+
+impl RouteAdvertisement {
+    /// Canonical encoding of the signed fields
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_str(&mut bytes, &self.origin);
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&(self.routes.len() as u32).to_be_bytes());
+        for route in &self.routes {
+            write_str(&mut bytes, &route.destination);
+            bytes.push(route.hop_count);
+            match route.cost {
+                Some(cost) => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&cost.to_bits().to_be_bytes());
+                }
+                None => bytes.push(0),
+            }
+            bytes.extend_from_slice(&(route.public_key.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(&route.public_key);
+        }
+        bytes
+    }
+}
+
+pub(crate) fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+impl MeshFrame {
+    /// Create a frame sent by `source`
+    pub fn new(source: impl Into<String>, payload: FramePayload) -> Self {
+        Self {
+            version: FRAME_VERSION,
+            source: source.into(),
+            payload,
+        }
+    }
+
+    /// Serialize for the wire
+    pub fn encode(&self) -> MeshResult<Vec<u8>> {
+        let bytes = serde_json::to_vec(self)?;
+        if bytes.len() > MAX_FRAME_SIZE {
+            return Err(MeshError::InvalidState(format!(
+                "Frame of {} bytes exceeds maximum of {}",
+                bytes.len(),
+                MAX_FRAME_SIZE
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Deserialize from the wire
+    pub fn decode(bytes: &[u8]) -> MeshResult<Self> {
+        let frame: Self = serde_json::from_slice(bytes)?;
+        if frame.version != FRAME_VERSION {
+            return Err(MeshError::InvalidState(format!(
+                "Unsupported frame version {}",
+                frame.version
+            )));
+        }
+        Ok(frame)
+    }
+}
+
+/// Datagram transport between mesh nodes
+///
+/// Addresses are transport-specific strings, matching `PeerInfo::address`.
+pub trait MeshTransport: Send + Sync + 'static {
+    /// Address peers reach this node at
+    fn local_address(&self) -> String;
+
+    /// Send one frame to `address`
+    fn send_to(&self, address: &str, frame: &[u8]) -> impl Future<Output = MeshResult<()>> + Send;
+
+    /// Receive the next frame and the address it came from
+    fn recv(&self) -> impl Future<Output = MeshResult<(String, Vec<u8>)>> + Send;
+}
+
+/// Mesh transport over UDP datagrams
+#[derive(Debug)]
+pub struct UdpTransport {
+    socket: UdpSocket,
+    local_address: SocketAddr,
+}
+
+impl UdpTransport {
+    /// Bind to `address` (e.g. `0.0.0.0:7400`, or port 0 for any free port)
+    pub async fn bind(address: &str) -> MeshResult<Self> {
+        let socket = UdpSocket::bind(address).await?;
+        let local_address = socket.local_addr()?;
+        Ok(Self {
+            socket,
+            local_address,
+        })
+    }
+}
+
+impl MeshTransport for UdpTransport {
+    fn local_address(&self) -> String {
+        self.local_address.to_string()
+    }
+
+    async fn send_to(&self, address: &str, frame: &[u8]) -> MeshResult<()> {
+        let address: SocketAddr = address
+            .parse()
+            .map_err(|_| MeshError::Config(format!("Invalid UDP address {}", address)))?;
+        self.socket.send_to(frame, address).await?;
+        Ok(())
+    }
+
+    async fn recv(&self) -> MeshResult<(String, Vec<u8>)> {
+        let mut buf = vec![0u8; MAX_FRAME_SIZE];
+        let (len, from) = self.socket.recv_from(&mut buf).await?;
+        buf.truncate(len);
+        Ok((from.to_string(), buf))
+    }
+}
+
+type Inbox = mpsc::UnboundedSender<(String, Vec<u8>)>;
+
+/// In-process network connecting loopback transports
+///
+/// Frames are delivered instantly and reliably between attached addresses;
+/// frames to unattached addresses are silently lost, as on a radio link.
+#[derive(Debug, Clone, Default)]
+pub struct LoopbackNetwork {
+    inboxes: Arc<Mutex<HashMap<String, Inbox>>>,
+}
+
+impl LoopbackNetwork {
+    /// Create an empty network
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach a transport at `address`, replacing any transport already there
+    pub fn attach(&self, address: impl Into<String>) -> LoopbackTransport {
+        let address = address.into();
+        let (inbox, receiver) = mpsc::unbounded_channel();
+        self.lock().insert(address.clone(), inbox);
+        LoopbackTransport {
+            network: self.clone(),
+            address,
+            receiver: tokio::sync::Mutex::new(receiver),
+        }
+    }
+
+    /// Detach the transport at `address`; frames to it are lost from now on
+    pub fn detach(&self, address: &str) {
+        self.lock().remove(address);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Inbox>> {
+        aethercore_core::sync::lock(&self.inboxes)
+    }
+}
+
+/// Transport attached to a `LoopbackNetwork`
+#[derive(Debug)]
+pub struct LoopbackTransport {
+    network: LoopbackNetwork,
+    address: String,
+    receiver: tokio::sync::Mutex<mpsc::UnboundedReceiver<(String, Vec<u8>)>>,
+}
+
+impl MeshTransport for LoopbackTransport {
+    fn local_address(&self) -> String {
+        self.address.clone()
+    }
+
+    async fn send_to(&self, address: &str, frame: &[u8]) -> MeshResult<()> {
+        if frame.len() > MAX_FRAME_SIZE {
+            return Err(MeshError::InvalidState(format!(
+                "Frame of {} bytes exceeds maximum of {}",
+                frame.len(),
+                MAX_FRAME_SIZE
+            )));
+        }
+        if let Some(inbox) = self.network.lock().get(address) {
+            // A closed inbox means the receiver was dropped: the frame is lost
+            let _ = inbox.send((self.address.clone(), frame.to_vec()));
+        }
+        Ok(())
+    }
+
+    async fn recv(&self) -> MeshResult<(String, Vec<u8>)> {
+        self.receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or_else(|| MeshError::InvalidState("Loopback network closed".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gossip_frame() -> MeshFrame {
+        MeshFrame::new(
+            "node1",
+            FramePayload::Gossip(GossipMessage {
+                msg_id: "msg1".to_string(),
+                source_node: "node1".to_string(),
+                merkle_root: vec![1, 2, 3],
+                block_height: 100,
+                timestamp: 1000,
+                signature: vec![4, 5, 6],
+                hop_count: 0,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let bytes = gossip_frame().encode().unwrap();
+        let frame = MeshFrame::decode(&bytes).unwrap();
+
+        assert_eq!(frame.source, "node1");
+        match frame.payload {
+            FramePayload::Gossip(message) => assert_eq!(message.block_height, 100),
+            _ => panic!("Expected Gossip"),
+        }
+    }
+
+    #[test]
+    fn test_reject_unknown_frame_version() {
+        let mut frame = gossip_frame();
+        frame.version = FRAME_VERSION + 1;
+        let bytes = serde_json::to_vec(&frame).unwrap();
+
+        assert!(MeshFrame::decode(&bytes).is_err());
+        assert!(MeshFrame::decode(b"not a frame").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_loopback_delivery() {
+        let network = LoopbackNetwork::new();
+        let a = network.attach("a");
+        let b = network.attach("b");
+
+        a.send_to("b", b"hello").await.unwrap();
+        let (from, frame) = b.recv().await.unwrap();
+        assert_eq!(from, "a");
+        assert_eq!(frame, b"hello");
+
+        // Frames to detached or unknown addresses are lost, not errors
+        network.detach("b");
+        a.send_to("b", b"lost").await.unwrap();
+        a.send_to("nowhere", b"lost").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_udp_delivery() {
+        let a = UdpTransport::bind("127.0.0.1:0").await.unwrap();
+        let b = UdpTransport::bind("127.0.0.1:0").await.unwrap();
+
+        a.send_to(&b.local_address(), b"hello").await.unwrap();
+        let (from, frame) = b.recv().await.unwrap();
+        assert_eq!(from, a.local_address());
+        assert_eq!(frame, b"hello");
+
+        assert!(a.send_to("not-an-address", b"hello").await.is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Peer discovery tests over the loopback network
+//!
+//! Tests cover:
+//! - Seed peers being dialed and admitted after mutual attestation
+//! - Peers that fail attestation never reaching the peer table
+//! - Quiet peers being aged out, and rediscovered once reachable again
+
+use aethercore_crypto::signing::EventSigningService;
+use aethercore_identity::{Attestation, AttestationManager, Certificate, PlatformIdentity};
+use aethercore_mesh::{
+    node_id_for_key, DiscoveryConfig, LinkConfig, LoopbackNetwork, LoopbackTransport, MeshEvent,
+    MeshEventLoop, MeshHandle, MeshSecurity, PeerDiscovery, TacticalMesh,
+};
+use ed25519_dalek::SigningKey;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn current_timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn signing_key(name: &str) -> SigningKey {
+    SigningKey::from_bytes(blake3::hash(name.as_bytes()).as_bytes())
+}
+
+/// Node ID of the node keyed from `name`
+fn node_id(name: &str) -> String {
+    node_id_for_key(signing_key(name).verifying_key().as_bytes())
+}
+
+/// Software-attested identity keyed from `name`
+fn identity(name: &str) -> PlatformIdentity {
+    let signing_key = signing_key(name);
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "private_key_hex".to_string(),
+        hex::encode(signing_key.to_bytes()),
+    );
+    metadata.insert("key_type".to_string(), "ed25519".to_string());
+    PlatformIdentity {
+        id: node_id(name),
+        public_key: signing_key.verifying_key().to_bytes().to_vec(),
+        attestation: Attestation::Software {
+            certificate: vec![1, 2, 3],
+        },
+        created_at: current_timestamp_ms(),
+        metadata,
+    }
+}
+
+/// Attestation manager whose certificate chain is valid unless `revoked`
+fn attestation_manager(identity: PlatformIdentity, revoked: bool) -> AttestationManager {
+    let cert = Certificate {
+        serial: "1".to_string(),
+        subject: identity.id.clone(),
+        issuer: "test-ca".to_string(),
+        public_key: identity.public_key.clone(),
+        not_before: 0,
+        not_after: u64::MAX,
+        signature: if revoked { vec![] } else { vec![4, 5, 6] },
+        extensions: HashMap::new(),
+        x509_der: None,
+    };
+    AttestationManager::new(identity, vec![cert])
+}
+
+fn start(
+    network: &LoopbackNetwork,
+    name: &str,
+    address: &str,
+    seeds: &[&str],
+    revoked: bool,
+) -> MeshHandle<LoopbackTransport> {
+    let seeds = seeds.iter().map(|s| s.to_string()).collect();
+    let identity = identity(name);
+    // Routes are signed with the same key the node attests with
+    let signing_service =
+        EventSigningService::from_key(blake3::hash(name.as_bytes()).as_bytes()).unwrap();
+    let security = MeshSecurity::new().with_signing(signing_service, identity.clone());
+    let mesh = TacticalMesh::new(identity.id.clone(), seeds, ":memory:")
+        .unwrap()
+        .with_security(security)
+        .with_link_config(LinkConfig {
+            maintenance_interval: Duration::from_millis(20),
+            ..LinkConfig::default()
+        });
+    let discovery =
+        PeerDiscovery::new(attestation_manager(identity, revoked)).with_config(DiscoveryConfig {
+            tick_interval: Duration::from_millis(20),
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_millis(100),
+            peer_timeout: Duration::from_millis(300),
+            max_attempts: 5,
+        });
+    MeshEventLoop::new(mesh, network.attach(address))
+        .with_advertisement_interval(Duration::from_millis(50))
+        .with_discovery(discovery)
+        .spawn()
+}
+
+/// Next peer discovered or lost, skipping gossip and route updates
+async fn next_peer_event(
+    handle: &mut MeshHandle<LoopbackTransport>,
+    timeout: Duration,
+) -> Option<MeshEvent> {
+    tokio::time::timeout(timeout, async {
+        loop {
+            match handle.next_event().await.expect("event loop stopped") {
+                event @ (MeshEvent::PeerDiscovered { .. } | MeshEvent::PeerLost { .. }) => {
+                    return event
+                }
+                _ => continue,
+            }
+        }
+    })
+    .await
+    .ok()
+}
+
+async fn expect_discovered(handle: &mut MeshHandle<LoopbackTransport>, expected: &str) {
+    match next_peer_event(handle, Duration::from_secs(2)).await {
+        Some(MeshEvent::PeerDiscovered {
+            node_id,
+            trust_score,
+        }) => {
+            assert_eq!(node_id, self::node_id(expected));
+            assert_eq!(trust_score, 0.7);
+        }
+        other => panic!("Expected discovery of {}, got {:?}", expected, other),
+    }
+}
+
+#[tokio::test]
+async fn test_seed_peer_admitted_after_attestation() {
+    let network = LoopbackNetwork::new();
+    let mut node_a = start(&network, "node-a", "radio-a", &["radio-b"], false);
+    let mut node_b = start(&network, "node-b", "radio-b", &[], false);
+
+    expect_discovered(&mut node_a, "node-b").await;
+    expect_discovered(&mut node_b, "node-a").await;
+
+    let mesh = node_a.mesh().lock().await;
+    let peer = mesh.get_peer(&node_id("node-b")).unwrap();
+    assert_eq!(peer.address, "radio-b");
+    assert!(peer.attestation_verified);
+    assert!(!mesh.get_mesh_status().bunker_mode);
+    drop(mesh);
+
+    // Admitted peers go on to establish an encrypted link
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    while !node_a
+        .mesh()
+        .lock()
+        .await
+        .is_link_established(&node_id("node-b"))
+    {
+        assert!(tokio::time::Instant::now() < deadline, "no link to node-b");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mesh = node_b.mesh().lock().await;
+    assert_eq!(
+        mesh.get_peer(&node_id("node-a")).unwrap().address,
+        "radio-a"
+    );
+}
+
+#[tokio::test]
+async fn test_unattested_peer_not_admitted() {
+    let network = LoopbackNetwork::new();
+    let mut node_a = start(&network, "node-a", "radio-a", &["radio-b"], false);
+    let mut node_b = start(&network, "node-b", "radio-b", &[], true);
+
+    assert!(next_peer_event(&mut node_a, Duration::from_millis(300))
+        .await
+        .is_none());
+    assert!(next_peer_event(&mut node_b, Duration::from_millis(50))
+        .await
+        .is_none());
+    assert_eq!(node_a.mesh().lock().await.get_mesh_status().peer_count, 0);
+    assert_eq!(node_b.mesh().lock().await.get_mesh_status().peer_count, 0);
+}
+
+#[tokio::test]
+async fn test_quiet_peer_aged_out_and_rediscovered() {
+    let network = LoopbackNetwork::new();
+    let mut node_a = start(&network, "node-a", "radio-a", &["radio-b"], false);
+    let node_b = start(&network, "node-b", "radio-b", &[], false);
+    expect_discovered(&mut node_a, "node-b").await;
+
+    // node-b drops off the air
+    node_b.shutdown().await;
+    network.detach("radio-b");
+    match next_peer_event(&mut node_a, Duration::from_secs(2)).await {
+        Some(MeshEvent::PeerLost { node_id: lost }) => assert_eq!(lost, node_id("node-b")),
+        other => panic!("Expected node-b to be lost, got {:?}", other),
+    }
+    let status = node_a.mesh().lock().await.get_mesh_status();
+    assert_eq!(status.peer_count, 0);
+    assert!(status.bunker_mode);
+
+    // The seed is still dialed, so node-b is found again when it returns
+    let _node_b = start(&network, "node-b", "radio-b", &[], false);
+    expect_discovered(&mut node_a, "node-b").await;
+}
@@ -0,0 +1,265 @@
+//! Hopping pattern agreement under simulated jamming
+//!
+//! Nodes are driven epoch by epoch rather than through the event loop. A
+//! message only gets through when sender and receiver are tuned to the same
+//! channel and that channel is not jammed.
+//!
+//! Tests cover:
+//! - Jamming answered by one pattern switch across the swarm, even when
+//!   every node proposes at once
+//! - A node that missed a switch falling back to the rendezvous channel and
+//!   rejoining once it hears a neighbor there
+
+use aethercore_crypto::signing::EventSigningService;
+use aethercore_identity::{Attestation, PlatformIdentity};
+use aethercore_mesh::spectral::ChannelId;
+use aethercore_mesh::{HopSyncConfig, HopSyncMessage, MeshSecurity, PeerInfo, TacticalMesh};
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const NODE_IDS: [&str; 3] = ["node1", "node2", "node3"];
+
+fn current_timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Deterministic signing key for a node
+fn signing_service(node_id: &str) -> EventSigningService {
+    EventSigningService::from_key(blake3::hash(node_id.as_bytes()).as_bytes()).unwrap()
+}
+
+fn peer(node_id: &str) -> PeerInfo {
+    PeerInfo {
+        node_id: node_id.to_string(),
+        trust_score: 0.9,
+        latency_ms: 10,
+        last_seen: current_timestamp_ms(),
+        address: format!("radio-{}", node_id),
+        public_key: signing_service(node_id).public_key(),
+        attestation_verified: true,
+    }
+}
+
+fn hop_sync_config() -> HopSyncConfig {
+    HopSyncConfig {
+        lead_epochs: 8,
+        num_channels: 8,
+        ..HopSyncConfig::default()
+    }
+}
+
+fn mesh(node_id: &str) -> TacticalMesh {
+    let signing_service = signing_service(node_id);
+    let identity = PlatformIdentity {
+        id: node_id.to_string(),
+        public_key: signing_service.public_key(),
+        attestation: Attestation::None,
+        created_at: current_timestamp_ms(),
+        metadata: HashMap::new(),
+    };
+    let mut mesh = TacticalMesh::new(node_id.to_string(), vec![], ":memory:")
+        .unwrap()
+        .with_security(MeshSecurity::new().with_signing(signing_service, identity))
+        .with_hop_sync_config(hop_sync_config());
+    for other in NODE_IDS.iter().filter(|other| **other != node_id) {
+        mesh.add_peer(peer(other)).unwrap();
+    }
+    mesh
+}
+
+/// Fully connected swarm sharing one simulated radio band
+struct Harness {
+    nodes: Vec<TacticalMesh>,
+    epoch: u64,
+    /// Channels the jammer is transmitting on
+    jammed: HashSet<ChannelId>,
+    /// Nodes whose radio is down
+    isolated: HashSet<usize>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self {
+            nodes: NODE_IDS.iter().map(|node_id| mesh(node_id)).collect(),
+            epoch: 0,
+            jammed: HashSet::new(),
+            isolated: HashSet::new(),
+        }
+    }
+
+    fn index(node_id: &str) -> usize {
+        NODE_IDS.iter().position(|id| *id == node_id).unwrap()
+    }
+
+    fn channel(&self, node: usize) -> Option<ChannelId> {
+        self.nodes[node].get_mesh_status().current_channel
+    }
+
+    fn can_hear(&self, a: usize, b: usize) -> bool {
+        let channel = self.channel(a);
+        channel.is_some()
+            && channel == self.channel(b)
+            && !self.jammed.contains(&channel.unwrap())
+            && !self.isolated.contains(&a)
+            && !self.isolated.contains(&b)
+    }
+
+    /// Deliver messages, and the replies they prompt, within the epoch
+    fn deliver(&mut self, mut queue: Vec<(usize, String, HopSyncMessage)>) {
+        while let Some((from, to, message)) = queue.pop() {
+            let to = Self::index(&to);
+            if !self.can_hear(from, to) {
+                continue;
+            }
+            let output = self.nodes[to]
+                .process_hop_sync(NODE_IDS[from], message, self.epoch)
+                .unwrap();
+            queue.extend(
+                output
+                    .messages
+                    .into_iter()
+                    .map(|(node_id, message)| (to, node_id, message)),
+            );
+        }
+    }
+
+    /// Advance one epoch: measure the last one, tick, beacon and deliver
+    fn step(&mut self) {
+        for node in 0..self.nodes.len() {
+            let jammed = self.channel(node).is_some_and(|c| self.jammed.contains(&c));
+            self.nodes[node].update_link_metrics(if jammed { 1.0 } else { 0.0 });
+        }
+
+        self.epoch += 1;
+        let mut queue = Vec::new();
+        for node in 0..self.nodes.len() {
+            let tick = self.nodes[node].hop_tick(self.epoch).unwrap();
+            queue.extend(
+                tick.messages
+                    .into_iter()
+                    .map(|(node_id, message)| (node, node_id, message)),
+            );
+        }
+
+        // Every node beacons, so anyone on the same clear channel hears it
+        for a in 0..self.nodes.len() {
+            for b in 0..self.nodes.len() {
+                if a != b && self.can_hear(a, b) {
+                    self.nodes[b].record_hop_contact(self.epoch);
+                }
+            }
+        }
+        self.deliver(queue);
+    }
+
+    /// Issue a plan from `node` outside of any jamming
+    fn propose(&mut self, node: usize) {
+        let messages = self.nodes[node]
+            .propose_hopping_pattern(self.epoch)
+            .unwrap();
+        let queue = messages
+            .into_iter()
+            .map(|(node_id, message)| (node, node_id, message))
+            .collect();
+        self.deliver(queue);
+    }
+
+    fn plan_of(&self, node: usize) -> Option<(String, u64)> {
+        self.nodes[node]
+            .hop_plan()
+            .map(|plan| (plan.issuer.clone(), plan.generation))
+    }
+
+    fn in_step(&self) -> bool {
+        (1..self.nodes.len()).all(|node| self.channel(node) == self.channel(0))
+    }
+}
+
+/// Swarm hopping on node1's first plan
+fn hopping_swarm() -> Harness {
+    let mut harness = Harness::new();
+    harness.step();
+    harness.propose(0);
+    for _ in 0..hop_sync_config().lead_epochs {
+        harness.step();
+    }
+    for node in 0..NODE_IDS.len() {
+        assert_eq!(harness.plan_of(node), Some(("node1".to_string(), 1)));
+    }
+    harness
+}
+
+#[test]
+fn test_jamming_triggers_one_swarm_wide_switch() {
+    let mut harness = hopping_swarm();
+    for _ in 0..20 {
+        harness.step();
+        assert!(harness.in_step(), "nodes diverged at {}", harness.epoch);
+    }
+
+    // The jammer follows the pattern, leaving one of its channels clear
+    let pattern = harness.nodes[0].hop_plan().unwrap().pattern(1000);
+    harness.jammed = pattern.channels.iter().copied().collect();
+    harness.jammed.remove(&pattern.channels[1]);
+
+    // Every node sees the jamming and proposes; node1's plan wins everywhere
+    let deadline = harness.epoch + 3 * hop_sync_config().lead_epochs;
+    while harness.plan_of(0) == Some(("node1".to_string(), 1)) {
+        assert!(
+            harness.epoch < deadline,
+            "no switch away from jammed pattern"
+        );
+        harness.step();
+    }
+    for node in 0..NODE_IDS.len() {
+        assert_eq!(harness.plan_of(node), Some(("node1".to_string(), 2)));
+    }
+
+    harness.jammed.clear();
+    for _ in 0..20 {
+        harness.step();
+        assert!(harness.in_step(), "nodes diverged at {}", harness.epoch);
+        assert!(harness.nodes.iter().all(|node| node.is_hop_synced()));
+    }
+}
+
+#[test]
+fn test_node_that_missed_switch_rejoins_at_rendezvous() {
+    let mut harness = hopping_swarm();
+    let config = hop_sync_config();
+
+    // node3's radio is down while node1 moves the swarm to a new pattern
+    harness.isolated.insert(2);
+    harness.propose(0);
+    let switch = harness.epoch + config.lead_epochs;
+    while harness.epoch <= switch {
+        harness.step();
+    }
+    assert_eq!(harness.plan_of(0), Some(("node1".to_string(), 2)));
+    assert_eq!(harness.plan_of(2), Some(("node1".to_string(), 1)));
+
+    // Hearing nobody, node3 parks on the rendezvous channel
+    while harness.nodes[2].is_hop_synced() {
+        assert!(harness.epoch < switch + 2 * config.sync_timeout_epochs);
+        harness.step();
+    }
+    for _ in 0..5 {
+        harness.step();
+        assert_eq!(harness.channel(2), Some(config.rendezvous_channel));
+    }
+
+    // Back on the air, node3 meets the others at the next rendezvous slot
+    harness.isolated.clear();
+    let deadline = harness.epoch + 2 * config.rendezvous_interval;
+    while harness.plan_of(2) != harness.plan_of(0) || !harness.nodes[2].is_hop_synced() {
+        assert!(harness.epoch < deadline, "node3 did not rejoin");
+        harness.step();
+    }
+    for _ in 0..20 {
+        harness.step();
+        assert!(harness.in_step(), "nodes diverged at {}", harness.epoch);
+    }
+}
@@ -0,0 +1,737 @@
+//! Multi-node tests running several TacticalMesh event loops in one process
+//!
+//! Tests cover:
+//! - Route advertisement and multi-hop route discovery
+//! - Distance-vector convergence to the cheapest path, failover and withdrawal
+//! - Gossip forwarding across hops with deduplication, and unsigned gossip
+//!   being dropped
+//! - Frame exchange over UDP sockets
+//! - Frames from unknown nodes being ignored
+//! - Peer links being encrypted, re-keyed, and dropped when re-keying fails
+//! - Bunker store-and-forward sync after a node is isolated
+//! - Fork resolution with a neighbor whose chain diverged, and slashing of
+//!   an equivocating neighbor
+
+use aethercore_crypto::signing::EventSigningService;
+use aethercore_identity::{Attestation, PlatformIdentity};
+use aethercore_mesh::gossip::GossipResult;
+use aethercore_mesh::{
+    node_id_for_key, BunkerState, ForkConfig, ForkOutcome, FramePayload, GossipMessage, LinkConfig,
+    LoopbackNetwork, MeshEvent, MeshEventLoop, MeshFrame, MeshHandle, MeshSecurity, MeshTransport,
+    PeerInfo, StoredBlock, StoredEvent, SyncConfig, TacticalMesh, UdpTransport,
+};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const ADVERTISEMENT_INTERVAL: Duration = Duration::from_millis(50);
+
+fn current_timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Deterministic signing key for a node
+fn signing_service(node_id: &str) -> EventSigningService {
+    EventSigningService::from_key(blake3::hash(node_id.as_bytes()).as_bytes()).unwrap()
+}
+
+/// Node ID derived from the key of `name`, so its key can be learned from
+/// route advertisements beyond its neighbors
+fn bound_id(name: &str) -> String {
+    node_id_for_key(&signing_service(name).public_key())
+}
+
+fn peer(node_id: &str, public_key: Vec<u8>, address: String, latency_ms: u64) -> PeerInfo {
+    PeerInfo {
+        node_id: node_id.to_string(),
+        trust_score: 0.9,
+        latency_ms,
+        last_seen: current_timestamp_ms(),
+        address,
+        public_key,
+        attestation_verified: true,
+    }
+}
+
+fn start<T: MeshTransport>(node_id: &str, transport: T) -> MeshHandle<T> {
+    start_with_links(node_id, transport, link_config())
+}
+
+fn link_config() -> LinkConfig {
+    LinkConfig {
+        maintenance_interval: Duration::from_millis(20),
+        ..LinkConfig::default()
+    }
+}
+
+/// Security context signing as `node_id`
+fn security(node_id: &str) -> MeshSecurity {
+    security_with_key(node_id, node_id)
+}
+
+/// Security context signing as `node_id` with the key of `name`
+fn security_with_key(node_id: &str, name: &str) -> MeshSecurity {
+    let signing_service = signing_service(name);
+    let identity = PlatformIdentity {
+        id: node_id.to_string(),
+        public_key: signing_service.public_key(),
+        attestation: Attestation::None,
+        created_at: current_timestamp_ms(),
+        metadata: HashMap::new(),
+    };
+    MeshSecurity::new().with_signing(signing_service, identity)
+}
+
+/// Start a node whose ID is bound to the key of `name`
+fn start_bound<T: MeshTransport>(name: &str, transport: T) -> MeshHandle<T> {
+    let node_id = bound_id(name);
+    start_signed(
+        &node_id,
+        security_with_key(&node_id, name),
+        transport,
+        link_config(),
+    )
+}
+
+fn start_with_links<T: MeshTransport>(
+    node_id: &str,
+    transport: T,
+    links: LinkConfig,
+) -> MeshHandle<T> {
+    start_signed(node_id, security(node_id), transport, links)
+}
+
+fn start_signed<T: MeshTransport>(
+    node_id: &str,
+    security: MeshSecurity,
+    transport: T,
+    links: LinkConfig,
+) -> MeshHandle<T> {
+    let mesh = TacticalMesh::new(node_id.to_string(), vec![], ":memory:")
+        .unwrap()
+        .with_security(security)
+        .with_link_config(links)
+        .with_sync_config(SyncConfig {
+            tick_interval: Duration::from_millis(20),
+            retry_interval: Duration::from_millis(500),
+            max_batch_records: 4,
+            ..SyncConfig::default()
+        })
+        .with_fork_config(ForkConfig {
+            tick_interval: Duration::from_millis(20),
+            retry_interval: Duration::from_millis(500),
+            segment_heights: 2,
+            ..ForkConfig::default()
+        });
+    MeshEventLoop::new(mesh, transport)
+        .with_advertisement_interval(ADVERTISEMENT_INTERVAL)
+        .spawn()
+}
+
+/// Node ID and public key of a running node
+async fn identity<T: MeshTransport>(node: &MeshHandle<T>) -> (String, Vec<u8>) {
+    let mesh = node.mesh().lock().await;
+    (
+        mesh.node_id().to_string(),
+        mesh.public_key().unwrap().to_vec(),
+    )
+}
+
+/// Make `a` and `b` neighbors of each other
+async fn link<T: MeshTransport>(a: &MeshHandle<T>, b: &MeshHandle<T>) {
+    link_with_latency(a, b, 10).await;
+}
+
+/// Make `a` and `b` neighbors over a link with the given latency, and
+/// wait for their session to be established
+async fn link_with_latency<T: MeshTransport>(
+    a: &MeshHandle<T>,
+    b: &MeshHandle<T>,
+    latency_ms: u64,
+) {
+    let (a_id, a_key) = identity(a).await;
+    let (b_id, b_key) = identity(b).await;
+    a.mesh()
+        .lock()
+        .await
+        .add_peer(peer(
+            &b_id,
+            b_key,
+            b.transport().local_address(),
+            latency_ms,
+        ))
+        .unwrap();
+    b.mesh()
+        .lock()
+        .await
+        .add_peer(peer(
+            &a_id,
+            a_key,
+            a.transport().local_address(),
+            latency_ms,
+        ))
+        .unwrap();
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let a_ready = a.mesh().lock().await.is_link_established(&b_id);
+        let b_ready = b.mesh().lock().await.is_link_established(&a_id);
+        if a_ready && b_ready {
+            return;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "link between {} and {} not established",
+            a_id,
+            b_id
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// Break the link between `a` and `b` in both directions
+async fn cut<T: MeshTransport>(a: &MeshHandle<T>, b: &MeshHandle<T>) {
+    let a_id = a.mesh().lock().await.node_id().to_string();
+    let b_id = b.mesh().lock().await.node_id().to_string();
+    a.mesh().lock().await.remove_peer(&b_id);
+    b.mesh().lock().await.remove_peer(&a_id);
+}
+
+async fn wait_for_route<T: MeshTransport>(
+    handle: &MeshHandle<T>,
+    destination: &str,
+) -> Option<String> {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while tokio::time::Instant::now() < deadline {
+        if let Some(next_hop) = handle.mesh().lock().await.find_route(destination) {
+            return Some(next_hop);
+        }
+        tokio::time::sleep(ADVERTISEMENT_INTERVAL).await;
+    }
+    None
+}
+
+/// Wait until the route to `destination` goes through `expected` (or is
+/// gone, for `None`)
+async fn wait_for_next_hop<T: MeshTransport>(
+    handle: &MeshHandle<T>,
+    destination: &str,
+    expected: Option<&str>,
+) -> bool {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while tokio::time::Instant::now() < deadline {
+        let next_hop = handle.mesh().lock().await.find_route(destination);
+        if next_hop.as_deref() == expected {
+            return true;
+        }
+        tokio::time::sleep(ADVERTISEMENT_INTERVAL).await;
+    }
+    false
+}
+
+/// Next gossip event, skipping route updates
+async fn next_gossip<T: MeshTransport>(handle: &mut MeshHandle<T>) -> (String, GossipResult) {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match handle.next_event().await.expect("event loop stopped") {
+                MeshEvent::Gossip { from, result } => return (from, result),
+                _ => continue,
+            }
+        }
+    })
+    .await
+    .expect("no gossip received")
+}
+
+#[tokio::test]
+async fn test_line_topology_converges() {
+    let network = LoopbackNetwork::new();
+    let node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+    let node3 = start("node3", network.attach("radio-3"));
+
+    // node1 <-> node2 <-> node3
+    link(&node1, &node2).await;
+    link(&node2, &node3).await;
+
+    assert_eq!(
+        wait_for_route(&node1, "node3").await.as_deref(),
+        Some("node2")
+    );
+    assert_eq!(
+        wait_for_route(&node3, "node1").await.as_deref(),
+        Some("node2")
+    );
+}
+
+#[tokio::test]
+async fn test_converges_to_cheapest_path() {
+    let network = LoopbackNetwork::new();
+    let node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+    let node3 = start("node3", network.attach("radio-3"));
+    let node4 = start("node4", network.attach("radio-4"));
+    let node5 = start("node5", network.attach("radio-5"));
+
+    // node1 reaches node5 over node2 -> node3 (cheap, 3 hops) or
+    // node4 (2 hops over slow links)
+    link(&node1, &node2).await;
+    link(&node2, &node3).await;
+    link(&node3, &node5).await;
+    link_with_latency(&node1, &node4, 2000).await;
+    link_with_latency(&node4, &node5, 2000).await;
+
+    assert!(wait_for_next_hop(&node1, "node5", Some("node2")).await);
+    assert!(wait_for_next_hop(&node5, "node1", Some("node3")).await);
+}
+
+#[tokio::test]
+async fn test_failover_to_alternate_path() {
+    let network = LoopbackNetwork::new();
+    let node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+    let node3 = start("node3", network.attach("radio-3"));
+    let node4 = start("node4", network.attach("radio-4"));
+
+    // Square: node1 reaches node4 via node2 (preferred) or node3 (slow)
+    link(&node1, &node2).await;
+    link(&node2, &node4).await;
+    link_with_latency(&node1, &node3, 500).await;
+    link(&node3, &node4).await;
+    assert!(wait_for_next_hop(&node1, "node4", Some("node2")).await);
+
+    cut(&node2, &node4).await;
+
+    assert!(wait_for_next_hop(&node1, "node4", Some("node3")).await);
+    // node2 now reaches node4 back through node1
+    assert!(wait_for_next_hop(&node2, "node4", Some("node1")).await);
+}
+
+#[tokio::test]
+async fn test_unreachable_destination_withdrawn() {
+    let network = LoopbackNetwork::new();
+    let node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+    let node3 = start("node3", network.attach("radio-3"));
+    let node4 = start("node4", network.attach("radio-4"));
+
+    // node1 <-> node2 <-> node3 <-> node4
+    link(&node1, &node2).await;
+    link(&node2, &node3).await;
+    link(&node3, &node4).await;
+    assert!(wait_for_next_hop(&node1, "node4", Some("node2")).await);
+
+    // With no other path, node4 disappears everywhere instead of the
+    // remaining nodes counting up to infinity through each other
+    cut(&node3, &node4).await;
+    assert!(wait_for_next_hop(&node1, "node4", None).await);
+    assert!(wait_for_next_hop(&node2, "node4", None).await);
+    assert!(wait_for_next_hop(&node3, "node4", None).await);
+}
+
+#[tokio::test]
+async fn test_gossip_forwarded_across_hops() {
+    let network = LoopbackNetwork::new();
+    // node4 learns node1's key from the routes it is advertised with
+    let node1 = start_bound("node1", network.attach("radio-1"));
+    let mut node2 = start("node2", network.attach("radio-2"));
+    let mut node3 = start("node3", network.attach("radio-3"));
+    let mut node4 = start("node4", network.attach("radio-4"));
+
+    // Diamond: node1 reaches node4 through both node2 and node3
+    link(&node1, &node2).await;
+    link(&node1, &node3).await;
+    link(&node2, &node4).await;
+    link(&node3, &node4).await;
+
+    let sent = node1.publish_state(vec![0xAB; 32], 100).await.unwrap();
+    assert_eq!(sent, 2);
+
+    for relay in [&mut node2, &mut node3] {
+        let (from, result) = next_gossip(relay).await;
+        assert_eq!(from, bound_id("node1"));
+        assert!(matches!(result, GossipResult::Accepted { .. }));
+    }
+
+    // node4 accepts the first copy and recognizes the second as a duplicate
+    let (_, first) = next_gossip(&mut node4).await;
+    match first {
+        GossipResult::Accepted { message, .. } => {
+            assert_eq!(message.source_node, bound_id("node1"));
+            assert_eq!(message.block_height, 100);
+            assert_eq!(message.hop_count, 2);
+        }
+        other => panic!("Expected Accepted, got {:?}", other),
+    }
+    let (_, second) = next_gossip(&mut node4).await;
+    assert!(matches!(second, GossipResult::Duplicate));
+}
+
+#[tokio::test]
+async fn test_udp_mesh() {
+    let node1 = start("node1", UdpTransport::bind("127.0.0.1:0").await.unwrap());
+    let mut node2 = start("node2", UdpTransport::bind("127.0.0.1:0").await.unwrap());
+    link(&node1, &node2).await;
+
+    node1.publish_state(vec![0xCD; 32], 7).await.unwrap();
+    let (from, result) = next_gossip(&mut node2).await;
+    assert_eq!(from, "node1");
+    assert!(matches!(result, GossipResult::Accepted { .. }));
+
+    let peer_state = node2.mesh().lock().await.get_mesh_status().peer_count;
+    assert_eq!(peer_state, 1);
+    node1.shutdown().await;
+    node2.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_frames_from_unknown_nodes_ignored() {
+    let network = LoopbackNetwork::new();
+    let node1 = start("node1", network.attach("radio-1"));
+    let mut node2 = start("node2", network.attach("radio-2"));
+
+    // Only node1 knows about node2
+    node1
+        .mesh()
+        .lock()
+        .await
+        .add_peer(peer(
+            "node2",
+            signing_service("node2").public_key(),
+            "radio-2".to_string(),
+            10,
+        ))
+        .unwrap();
+    node1.publish_state(vec![0xEF; 32], 1).await.unwrap();
+
+    let event = tokio::time::timeout(Duration::from_millis(200), node2.next_event()).await;
+    assert!(event.is_err(), "unexpected event {:?}", event);
+}
+
+#[tokio::test]
+async fn test_peer_traffic_is_encrypted() {
+    let network = LoopbackNetwork::new();
+    let mut node1 = start("node1", network.attach("radio-1"));
+    // node2 is a bare radio, so the test sees exactly what node1 transmits
+    let radio = network.attach("radio-2");
+    node1
+        .mesh()
+        .lock()
+        .await
+        .add_peer(peer(
+            "node2",
+            signing_service("node2").public_key(),
+            "radio-2".to_string(),
+            10,
+        ))
+        .unwrap();
+
+    let (_, bytes) = tokio::time::timeout(Duration::from_secs(2), radio.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let frame = MeshFrame::decode(&bytes).unwrap();
+    assert!(matches!(frame.payload, FramePayload::LinkHandshake(_)));
+
+    // Without a session nothing else is sent, and unencrypted gossip
+    // claiming to be from node2 is ignored, without counting as contact
+    assert_eq!(node1.publish_state(vec![0x12; 32], 1).await.unwrap(), 0);
+    let last_seen = |mesh: &TacticalMesh| mesh.get_peer("node2").unwrap().last_seen;
+    let first_seen = last_seen(&*node1.mesh().lock().await);
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let gossip = FramePayload::Gossip(aethercore_mesh::GossipMessage {
+        msg_id: "forged".to_string(),
+        source_node: "node2".to_string(),
+        merkle_root: vec![0; 32],
+        block_height: 1,
+        timestamp: current_timestamp_ms(),
+        signature: vec![],
+        hop_count: 0,
+    });
+    let forged = MeshFrame::new("node2", gossip).encode().unwrap();
+    radio.send_to("radio-1", &forged).await.unwrap();
+    let event = tokio::time::timeout(Duration::from_millis(200), node1.next_event()).await;
+    assert!(event.is_err(), "unexpected event {:?}", event);
+    assert_eq!(last_seen(&*node1.mesh().lock().await), first_seen);
+}
+
+#[tokio::test]
+async fn test_link_keys_rotate() {
+    let network = LoopbackNetwork::new();
+    let links = LinkConfig {
+        max_messages_per_epoch: 4,
+        ..link_config()
+    };
+    let node1 = start_with_links("node1", network.attach("radio-1"), links.clone());
+    let mut node2 = start_with_links("node2", network.attach("radio-2"), links);
+    link(&node1, &node2).await;
+
+    // Route advertisements alone use up each epoch within a few intervals
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while node1.mesh().lock().await.link_epoch("node2").unwrap_or(0) < 2 {
+        assert!(tokio::time::Instant::now() < deadline, "keys not rotated");
+        tokio::time::sleep(ADVERTISEMENT_INTERVAL).await;
+    }
+
+    // Traffic still flows on the rotated keys
+    let mut delivered = false;
+    for height in 1..=20 {
+        node1.publish_state(vec![0x34; 32], height).await.unwrap();
+        if let Ok(event) = tokio::time::timeout(Duration::from_millis(200), async {
+            loop {
+                if let Some(MeshEvent::Gossip { result, .. }) = node2.next_event().await {
+                    return result;
+                }
+            }
+        })
+        .await
+        {
+            assert!(matches!(event, GossipResult::Accepted { .. }));
+            delivered = true;
+            break;
+        }
+    }
+    assert!(delivered, "no gossip delivered after rotation");
+    assert_eq!(node2.mesh().lock().await.get_mesh_status().peer_count, 1);
+}
+
+#[tokio::test]
+async fn test_peer_dropped_when_rotation_fails() {
+    let network = LoopbackNetwork::new();
+    let links = LinkConfig {
+        max_messages_per_epoch: 4,
+        rotation_timeout: Duration::from_millis(200),
+        ..link_config()
+    };
+    let mut node1 = start_with_links("node1", network.attach("radio-1"), links.clone());
+    let node2 = start_with_links("node2", network.attach("radio-2"), links);
+    link(&node1, &node2).await;
+
+    // node2 goes silent, so node1's next rotation can never complete
+    node2.shutdown().await;
+    network.detach("radio-2");
+
+    let event = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(event @ MeshEvent::LinkFailed { .. }) = node1.next_event().await {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("link failure not reported");
+    match event {
+        MeshEvent::LinkFailed { node_id, reason } => {
+            assert_eq!(node_id, "node2");
+            assert!(reason.contains("rotation"), "{}", reason);
+        }
+        other => panic!("Expected LinkFailed, got {:?}", other),
+    }
+    let status = node1.mesh().lock().await.get_mesh_status();
+    assert_eq!(status.peer_count, 0);
+    assert!(status.bunker_mode);
+}
+
+#[tokio::test]
+async fn test_bunker_sync_after_isolation() {
+    let network = LoopbackNetwork::new();
+    let mut node1 = start("node1", network.attach("radio-1"));
+    let mut node2 = start("node2", network.attach("radio-2"));
+    link(&node1, &node2).await;
+    cut(&node1, &node2).await;
+    assert_eq!(
+        node1.mesh().lock().await.bunker_state(),
+        &BunkerState::Isolated
+    );
+
+    // node1 keeps recording while cut off, more than fits in one batch
+    {
+        let mut mesh = node1.mesh().lock().await;
+        let mut parent = Vec::new();
+        for height in 1..=10u64 {
+            let data = vec![height as u8; 64];
+            let block = StoredBlock {
+                hash: StoredBlock::chain_hash(&parent, height, &data),
+                height,
+                data,
+                timestamp: current_timestamp_ms(),
+                synced: false,
+            };
+            parent = block.hash.clone();
+            mesh.store_block(block).unwrap();
+        }
+        mesh.store_event(StoredEvent {
+            id: "contact-report".to_string(),
+            event_type: "c2".to_string(),
+            payload: vec![1, 2, 3],
+            timestamp: current_timestamp_ms(),
+            synced: false,
+        })
+        .unwrap();
+    }
+
+    link(&node1, &node2).await;
+    for handle in [&mut node1, &mut node2] {
+        let event = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(event @ MeshEvent::SyncCompleted { .. }) = handle.next_event().await {
+                    return event;
+                }
+            }
+        })
+        .await
+        .expect("sync did not complete");
+        assert!(matches!(event, MeshEvent::SyncCompleted { .. }));
+    }
+
+    let mesh = node1.mesh().lock().await;
+    assert_eq!(mesh.bunker_state(), &BunkerState::Connected);
+    let (blocks, events) = mesh.get_unsynced_data().unwrap();
+    assert!(blocks.is_empty() && events.is_empty());
+    drop(mesh);
+    let blocks = node2
+        .mesh()
+        .lock()
+        .await
+        .get_blocks_above(None, 100)
+        .unwrap();
+    assert_eq!(blocks.len(), 10);
+    assert!(blocks.iter().all(|block| block.synced));
+}
+
+#[tokio::test]
+async fn test_fork_resolved_with_neighbor() {
+    let network = LoopbackNetwork::new();
+    let mut node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+
+    // Both hold blocks 1-3; past that node1 and node2 went separate ways
+    let mut tips = Vec::new();
+    for (handle, branch, tip) in [(&node1, 1, 4), (&node2, 2, 5)] {
+        let mut mesh = handle.mesh().lock().await;
+        let mut parent = Vec::new();
+        for height in 1..=tip {
+            let data = vec![if height <= 3 { 0 } else { branch }; 16];
+            let block = StoredBlock {
+                hash: StoredBlock::chain_hash(&parent, height, &data),
+                height,
+                data,
+                timestamp: current_timestamp_ms(),
+                synced: true,
+            };
+            parent = block.hash.clone();
+            mesh.store_block(block).unwrap();
+        }
+        tips.push(parent);
+    }
+    link(&node1, &node2).await;
+
+    node1.publish_state(tips[0].clone(), 4).await.unwrap();
+    node2.publish_state(tips[1].clone(), 5).await.unwrap();
+
+    // With no other peers to weigh in, node2's longer branch wins
+    let outcome = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(MeshEvent::ForkResolved { node_id, outcome }) = node1.next_event().await {
+                assert_eq!(node_id, "node2");
+                return outcome;
+            }
+        }
+    })
+    .await
+    .expect("fork not resolved");
+    assert_eq!(
+        outcome,
+        ForkOutcome::AdoptedPeer {
+            ancestor_height: Some(3),
+            removed: 1,
+            added: 2
+        }
+    );
+
+    let blocks = node1
+        .mesh()
+        .lock()
+        .await
+        .get_blocks_above(Some(3), 10)
+        .unwrap();
+    let adopted: Vec<(u64, u8)> = blocks
+        .iter()
+        .map(|block| (block.height, block.data[0]))
+        .collect();
+    assert_eq!(adopted, vec![(4, 2), (5, 2)]);
+}
+
+#[tokio::test]
+async fn test_equivocating_neighbor_slashed() {
+    let network = LoopbackNetwork::new();
+    let mut node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+    link(&node1, &node2).await;
+
+    // node2 signs two different roots for the same height
+    let mut node2_security = security("node2");
+    for (msg_id, root) in [("claim-1", 0xAA), ("claim-2", 0xBB)] {
+        let mut message = GossipMessage {
+            msg_id: msg_id.to_string(),
+            source_node: "node2".to_string(),
+            merkle_root: vec![root; 32],
+            block_height: 12,
+            timestamp: current_timestamp_ms(),
+            signature: vec![],
+            hop_count: 0,
+        };
+        node2_security.sign_gossip(&mut message).unwrap();
+        assert_eq!(node2.gossip(message).await.unwrap(), 1);
+    }
+
+    let event = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(MeshEvent::PeerSlashed { event }) = node1.next_event().await {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("equivocation not slashed");
+    assert_eq!(event.node_id, "node2");
+
+    let mesh = node1.mesh().lock().await;
+    assert!(mesh.is_revoked("node2"));
+    assert!(mesh.get_peer("node2").is_none());
+    assert_eq!(mesh.slashing_events().len(), 1);
+}
+
+#[tokio::test]
+async fn test_unsigned_gossip_dropped() {
+    let network = LoopbackNetwork::new();
+    let mut node1 = start("node1", network.attach("radio-1"));
+    let node2 = start("node2", network.attach("radio-2"));
+    link(&node1, &node2).await;
+
+    // Sealed in a valid session, but not signed by node2's identity key
+    let message = GossipMessage {
+        msg_id: "unsigned".to_string(),
+        source_node: "node2".to_string(),
+        merkle_root: vec![0x56; 32],
+        block_height: 3,
+        timestamp: current_timestamp_ms(),
+        signature: vec![],
+        hop_count: 0,
+    };
+    assert_eq!(node2.gossip(message).await.unwrap(), 1);
+
+    let (from, result) = next_gossip(&mut node1).await;
+    assert_eq!(from, "node2");
+    assert!(matches!(result, GossipResult::Dropped(_)));
+    let trust_score = node1
+        .mesh()
+        .lock()
+        .await
+        .get_peer("node2")
+        .unwrap()
+        .trust_score;
+    assert_eq!(trust_score, 0.45);
+}
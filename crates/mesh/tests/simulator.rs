@@ -0,0 +1,172 @@
+//! Deterministic simulator runs
+//!
+//! Swarms are kept small and runs short so the suite stays quick in debug
+//! builds; `mesh-sim` covers the full-size scenarios.
+//!
+//! Tests cover:
+//! - Identical reports from identical seeds
+//! - Convergence under the field health grades of the resilience config
+//! - A partition delaying convergence until it heals
+//! - Equivocating and forging nodes losing the honest nodes' trust
+//! - Jamming answered by a hopping pattern the swarm agrees on
+
+use aethercore_mesh::{ByzantineBehavior, Jamming, Partition, Scenario, SimConfig, Simulation};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const RESILIENCE_CONFIG: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../config/degraded-rf-resilience.yaml"
+);
+
+fn scenario(name: &str) -> Scenario {
+    Scenario::load(RESILIENCE_CONFIG)
+        .unwrap()
+        .into_iter()
+        .find(|scenario| scenario.name == name)
+        .unwrap()
+}
+
+/// Small, fast run with a block every two seconds
+fn config(nodes: usize, secs: u64) -> SimConfig {
+    SimConfig {
+        nodes,
+        duration: Duration::from_secs(secs),
+        block_interval: Duration::from_secs(2),
+        ..SimConfig::default()
+    }
+}
+
+#[test]
+fn test_same_seed_same_report() {
+    let config = SimConfig {
+        seed: 7,
+        nodes: 4,
+        duration: Duration::from_secs(15),
+        ..SimConfig::for_scenario(&scenario("degraded"))
+    };
+    let first = Simulation::new(config.clone()).run().unwrap();
+    let mut second = Simulation::new(config.clone()).run().unwrap();
+    // Byte counts follow the session keys, which are not seeded
+    second.overhead.bytes_sent = first.overhead.bytes_sent;
+    assert_eq!(first, second);
+
+    let other_seed = Simulation::new(SimConfig { seed: 8, ..config })
+        .run()
+        .unwrap();
+    assert_ne!(first.overhead.frames_lost, other_seed.overhead.frames_lost);
+}
+
+#[test]
+fn test_health_grades_load_from_resilience_config() {
+    let scenarios = Scenario::load(RESILIENCE_CONFIG).unwrap();
+    let names: Vec<_> = scenarios.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["excellent", "good", "degraded", "contested"]);
+    assert!(scenarios
+        .windows(2)
+        .all(|pair| pair[0].link.loss <= pair[1].link.loss));
+}
+
+#[test]
+fn test_excellent_grade_converges() {
+    let report = Simulation::new(SimConfig {
+        nodes: 5,
+        duration: Duration::from_secs(25),
+        ..SimConfig::for_scenario(&scenario("excellent"))
+    })
+    .run()
+    .unwrap();
+
+    let convergence = &report.convergence;
+    assert!(convergence.blocks > 0);
+    assert_eq!(convergence.converged, convergence.blocks);
+    assert!(convergence.agreed_at_end);
+    assert!(convergence.max_ms.unwrap() < 1_000);
+    assert!(report.trust.iter().all(|outcome| outcome.revoked_by == 0));
+}
+
+#[test]
+fn test_partition_delays_convergence_until_healed() {
+    let baseline = Simulation::new(config(6, 20)).run().unwrap();
+    let partitioned = Simulation::new(SimConfig {
+        partitions: vec![Partition {
+            from: Duration::from_secs(3),
+            until: Duration::from_secs(10),
+            groups: vec![vec![0, 1, 2], vec![3, 4, 5]],
+        }],
+        ..config(6, 20)
+    })
+    .run()
+    .unwrap();
+
+    assert!(partitioned.overhead.frames_lost.contains_key("partition"));
+    assert!(partitioned.convergence.max_ms > baseline.convergence.max_ms);
+    // Blocks produced during the split converge once it heals
+    assert_eq!(
+        partitioned.convergence.converged,
+        partitioned.convergence.blocks
+    );
+    assert!(partitioned.convergence.agreed_at_end);
+}
+
+#[test]
+fn test_equivocator_revoked_by_honest_nodes() {
+    let report = Simulation::new(SimConfig {
+        byzantine: BTreeMap::from([(1, ByzantineBehavior::Equivocate)]),
+        ..config(4, 10)
+    })
+    .run()
+    .unwrap();
+
+    let equivocator = &report.trust[1];
+    assert_eq!(equivocator.byzantine, Some(ByzantineBehavior::Equivocate));
+    assert_eq!(equivocator.revoked_by, 3);
+    assert!(report
+        .trust
+        .iter()
+        .filter(|outcome| outcome.byzantine.is_none())
+        .all(|outcome| outcome.revoked_by == 0));
+    assert!(report.convergence.agreed_at_end);
+}
+
+#[test]
+fn test_forger_loses_trust() {
+    let report = Simulation::new(SimConfig {
+        byzantine: BTreeMap::from([(0, ByzantineBehavior::ForgeSignatures)]),
+        ..config(4, 10)
+    })
+    .run()
+    .unwrap();
+
+    let forger = &report.trust[0];
+    let honest = &report.trust[1];
+    assert!(forger.revoked_by + forger.dropped_by > 0 || forger.mean_trust < honest.mean_trust);
+    assert!(report.convergence.agreed_at_end);
+}
+
+#[test]
+fn test_jamming_moves_swarm_to_new_pattern() {
+    // Packet errors under this grade put a first pattern in force early
+    let config = SimConfig {
+        nodes: 4,
+        duration: Duration::from_secs(30),
+        ..SimConfig::for_scenario(&scenario("excellent"))
+    };
+    let quiet = Simulation::new(config.clone()).run().unwrap();
+    // Half the band, so the pattern in force is hit but a clear one exists
+    let jammed = Simulation::new(SimConfig {
+        jamming: vec![Jamming {
+            from: Duration::from_secs(8),
+            until: Duration::from_secs(30),
+            channels: (1..=32).collect(),
+        }],
+        ..config
+    })
+    .run()
+    .unwrap();
+
+    assert!(jammed.overhead.frames_lost.contains_key("jammed"));
+    assert!(jammed.spectral.plans_activated > quiet.spectral.plans_activated);
+    assert!(jammed.spectral.agreed_at_end);
+    assert_eq!(jammed.spectral.epochs_out_of_sync, 0);
+}
@@ -0,0 +1,340 @@
+//! LoRa radio driver
+//!
+//! `LoraRadio` speaks the serial protocol to a LoRa modem, splitting
+//! messages into fragments that fit a packet and reassembling them on
+//! receipt. Transmissions are charged against the duty-cycle budget first,
+//! and a message is only sent if all of its fragments fit.
+//!
+//! A background task reads the serial line: packets go to the reassembler
+//! and the link statistics, command answers to whichever call is waiting on
+//! one. The radio implements `MeshTransport`, with addresses as formatted
+//! by `format_address`.
+
+use crate::duty_cycle::DutyCycle;
+use crate::error::{RadioError, RadioResult};
+use crate::fragment::{self, FragmentHeader, Reassembler};
+use crate::link_stats::LinkStats;
+use crate::lora::{format_address, parse_address, LoraConfig, LoraSettings, BROADCAST};
+use crate::protocol::{ModemCommand, ModemEvent, SlipDecoder};
+use crate::serial::SerialPort;
+use aethercore_core::sync;
+use aethercore_mesh::{MeshError, MeshResult, MeshTransport};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// How often incomplete messages are checked for expiry
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+
+type Stats = Arc<Mutex<HashMap<u32, LinkStats>>>;
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// State of the transmit side, held for the whole of a command exchange
+struct Transmitter {
+    answers: mpsc::UnboundedReceiver<ModemEvent>,
+    duty_cycle: DutyCycle,
+    settings: LoraSettings,
+    next_message_id: u16,
+}
+
+/// Host side of a serial-attached LoRa modem
+pub struct LoraRadio {
+    config: LoraConfig,
+    port: Arc<SerialPort>,
+    transmitter: tokio::sync::Mutex<Transmitter>,
+    incoming: tokio::sync::Mutex<mpsc::UnboundedReceiver<(u32, Vec<u8>)>>,
+    stats: Stats,
+    reader: JoinHandle<()>,
+}
+
+impl LoraRadio {
+    /// Open the modem on serial device `path` and apply `config.settings`
+    pub async fn open(path: impl AsRef<Path>, config: LoraConfig) -> RadioResult<Self> {
+        config.validate()?;
+        let port = Arc::new(SerialPort::open(path.as_ref(), config.baud_rate)?);
+        let (answers_tx, answers) = mpsc::unbounded_channel();
+        let (incoming_tx, incoming) = mpsc::unbounded_channel();
+        let stats = Stats::default();
+
+        let reader = tokio::spawn(read_line(
+            port.clone(),
+            config.address,
+            Reassembler::new(config.reassembly_timeout),
+            stats.clone(),
+            answers_tx,
+            incoming_tx,
+        ));
+        let radio = Self {
+            transmitter: tokio::sync::Mutex::new(Transmitter {
+                answers,
+                duty_cycle: DutyCycle::new(config.duty_cycle, config.duty_cycle_window),
+                settings: config.settings,
+                next_message_id: 0,
+            }),
+            config,
+            port,
+            incoming: tokio::sync::Mutex::new(incoming),
+            stats,
+            reader,
+        };
+        radio.configure(radio.config.settings).await?;
+        info!(
+            address = %format_address(radio.config.address),
+            frequency_hz = radio.config.settings.frequency_hz,
+            spreading_factor = radio.config.settings.spreading_factor,
+            "LoRa radio open"
+        );
+        Ok(radio)
+    }
+
+    /// This station's address
+    pub fn address(&self) -> u32 {
+        self.config.address
+    }
+
+    /// Apply new modem settings, such as the next channel of a hopping
+    /// pattern
+    pub async fn configure(&self, settings: LoraSettings) -> RadioResult<()> {
+        settings.validate()?;
+        let mut transmitter = self.transmitter.lock().await;
+        self.command(
+            &mut transmitter,
+            ModemCommand::Configure(settings),
+            ModemEvent::Configured,
+        )
+        .await?;
+        transmitter.settings = settings;
+        Ok(())
+    }
+
+    /// Send `message` to `destination`, or to every station with `BROADCAST`
+    ///
+    /// Returns once the modem has transmitted every fragment. Fails without
+    /// transmitting anything if the duty-cycle budget cannot cover the
+    /// whole message.
+    pub async fn send(&self, destination: u32, message: &[u8]) -> RadioResult<()> {
+        let mut transmitter = self.transmitter.lock().await;
+        let message_id = transmitter.next_message_id;
+        let packets = fragment::fragment(
+            destination,
+            self.config.address,
+            message_id,
+            message,
+            self.config.max_packet,
+        )?;
+        let airtime = packets
+            .iter()
+            .map(|packet| transmitter.settings.time_on_air(packet.len()))
+            .sum();
+        transmitter.duty_cycle.reserve(airtime, Instant::now())?;
+        transmitter.next_message_id = message_id.wrapping_add(1);
+
+        debug!(
+            destination = %format_address(destination),
+            fragments = packets.len(),
+            airtime_ms = airtime.as_millis() as u64,
+            "Transmitting message"
+        );
+        for packet in packets {
+            self.command(
+                &mut transmitter,
+                ModemCommand::Transmit(packet),
+                ModemEvent::TxDone,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Receive the next complete message and the station that sent it
+    pub async fn recv(&self) -> RadioResult<(u32, Vec<u8>)> {
+        self.incoming
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(RadioError::Closed)
+    }
+
+    /// Airtime spent in the current duty-cycle window
+    pub async fn airtime_used(&self) -> Duration {
+        self.transmitter
+            .lock()
+            .await
+            .duty_cycle
+            .used(Instant::now())
+    }
+
+    /// Link measurements for every station heard, by address
+    ///
+    /// Feed these into the mesh with `TacticalMesh::update_link_quality`
+    /// for the peer at each address.
+    pub fn link_stats(&self) -> HashMap<String, LinkStats> {
+        lock(&self.stats)
+            .iter()
+            .map(|(address, stats)| (format_address(*address), stats.clone()))
+            .collect()
+    }
+
+    /// Send one command and wait for the modem's answer
+    async fn command(
+        &self,
+        transmitter: &mut Transmitter,
+        command: ModemCommand,
+        expected: ModemEvent,
+    ) -> RadioResult<()> {
+        // Answers to commands that timed out earlier are stale by now
+        while transmitter.answers.try_recv().is_ok() {}
+
+        self.port.write_all(&command.encode()).await?;
+        let answer = tokio::time::timeout(self.config.command_timeout, transmitter.answers.recv())
+            .await
+            .map_err(|_| RadioError::Timeout("Modem did not answer".to_string()))?
+            .ok_or(RadioError::Closed)?;
+        match answer {
+            ModemEvent::Error(reason) => Err(RadioError::Modem(reason)),
+            answer if answer == expected => Ok(()),
+            answer => Err(RadioError::Protocol(format!(
+                "Expected {:?} from modem, got {:?}",
+                expected, answer
+            ))),
+        }
+    }
+}
+
+impl Drop for LoraRadio {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+fn lock(stats: &Stats) -> std::sync::MutexGuard<'_, HashMap<u32, LinkStats>> {
+    sync::lock(stats)
+}
+
+/// Read modem events until the line closes
+async fn read_line(
+    port: Arc<SerialPort>,
+    address: u32,
+    mut reassembler: Reassembler,
+    stats: Stats,
+    answers: mpsc::UnboundedSender<ModemEvent>,
+    incoming: mpsc::UnboundedSender<(u32, Vec<u8>)>,
+) {
+    let mut decoder = SlipDecoder::new();
+    let mut buf = [0u8; 512];
+    let mut expiry = tokio::time::interval(EXPIRY_INTERVAL);
+    loop {
+        let read = tokio::select! {
+            read = port.read(&mut buf) => read,
+            _ = expiry.tick() => {
+                for incomplete in reassembler.expire(Instant::now()) {
+                    debug!(
+                        source = %format_address(incomplete.source),
+                        received = incomplete.received,
+                        count = incomplete.count,
+                        "Dropping incomplete message"
+                    );
+                    if let Some(stats) = lock(&stats).get_mut(&incomplete.source) {
+                        stats.record_message(incomplete.received, incomplete.count);
+                    }
+                }
+                continue;
+            }
+        };
+        let len = match read {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) => {
+                warn!("Serial read failed: {}", e);
+                break;
+            }
+        };
+
+        for frame in decoder.feed(&buf[..len]) {
+            let event = match ModemEvent::decode(&frame) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Dropping modem frame: {}", e);
+                    continue;
+                }
+            };
+            let ModemEvent::Received {
+                rssi_dbm,
+                snr_db,
+                packet,
+            } = event
+            else {
+                let _ = answers.send(event);
+                continue;
+            };
+
+            let (header, data) = match FragmentHeader::decode(&packet) {
+                Ok(fragment) => fragment,
+                Err(e) => {
+                    debug!("Dropping packet: {}", e);
+                    continue;
+                }
+            };
+            if header.source == address {
+                continue;
+            }
+            // Overheard packets still tell how well the sender is heard
+            let now_ms = current_timestamp();
+            lock(&stats)
+                .entry(header.source)
+                .and_modify(|stats| stats.record_packet(rssi_dbm as f64, snr_db as f64, now_ms))
+                .or_insert_with(|| LinkStats::new(rssi_dbm as f64, snr_db as f64, now_ms));
+            if header.destination != address && header.destination != BROADCAST {
+                continue;
+            }
+
+            if let Some(message) = reassembler.push(header, data, Instant::now()) {
+                if let Some(stats) = lock(&stats).get_mut(&header.source) {
+                    stats.record_message(header.count as usize, header.count as usize);
+                }
+                if incoming.send((header.source, message)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+    debug!("Serial line closed");
+}
+
+impl From<RadioError> for MeshError {
+    fn from(e: RadioError) -> Self {
+        match e {
+            RadioError::Io(e) => MeshError::Io(e),
+            RadioError::Config(reason) => MeshError::Config(reason),
+            RadioError::Timeout(reason) => MeshError::Timeout(reason),
+            e => MeshError::InvalidState(e.to_string()),
+        }
+    }
+}
+
+impl MeshTransport for LoraRadio {
+    fn local_address(&self) -> String {
+        format_address(self.config.address)
+    }
+
+    async fn send_to(&self, address: &str, frame: &[u8]) -> MeshResult<()> {
+        let destination = parse_address(address)?;
+        Ok(self.send(destination, frame).await?)
+    }
+
+    async fn recv(&self) -> MeshResult<(String, Vec<u8>)> {
+        let (source, message) = LoraRadio::recv(self).await?;
+        Ok((format_address(source), message))
+    }
+}
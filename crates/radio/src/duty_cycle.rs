@@ -0,0 +1,123 @@
+//! Duty-cycle budgeting
+//!
+//! Sub-GHz bands cap the share of time a station may transmit, such as 1%
+//! over an hour in much of the EU 868 MHz band. Airtime is charged against
+//! a sliding window before transmitting, and a transmission that would go
+//! over budget is refused rather than sent in part.
+
+use crate::error::{RadioError, RadioResult};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Airtime spent within a sliding window
+#[derive(Debug)]
+pub struct DutyCycle {
+    budget: Duration,
+    window: Duration,
+    /// Start and airtime of each transmission still within the window
+    spent: VecDeque<(Instant, Duration)>,
+}
+
+impl DutyCycle {
+    /// Allow transmitting `limit` of every `window`
+    pub fn new(limit: f64, window: Duration) -> Self {
+        Self {
+            budget: window.mul_f64(limit.clamp(0.0, 1.0)),
+            window,
+            spent: VecDeque::new(),
+        }
+    }
+
+    /// Airtime allowed per window
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Airtime spent in the window ending at `now`
+    pub fn used(&mut self, now: Instant) -> Duration {
+        self.forget(now);
+        self.spent.iter().map(|(_, airtime)| *airtime).sum()
+    }
+
+    /// Charge `airtime` starting at `now`, if the budget allows it
+    pub fn reserve(&mut self, airtime: Duration, now: Instant) -> RadioResult<()> {
+        if airtime > self.budget {
+            return Err(RadioError::Config(format!(
+                "Transmission of {:?} exceeds the whole duty-cycle budget of {:?}",
+                airtime, self.budget
+            )));
+        }
+        let used = self.used(now);
+        if used + airtime > self.budget {
+            // Wait for enough of the oldest transmissions to leave the window
+            let mut over = used + airtime - self.budget;
+            let mut retry_after = Duration::ZERO;
+            for (start, spent) in &self.spent {
+                retry_after = (*start + self.window).saturating_duration_since(now);
+                if *spent >= over {
+                    break;
+                }
+                over -= *spent;
+            }
+            return Err(RadioError::DutyCycle { retry_after });
+        }
+        self.spent.push_back((now, airtime));
+        Ok(())
+    }
+
+    fn forget(&mut self, now: Instant) {
+        while let Some((start, _)) = self.spent.front() {
+            if now.duration_since(*start) < self.window {
+                break;
+            }
+            self.spent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_refuses_then_recovers() {
+        let start = Instant::now();
+        let mut duty_cycle = DutyCycle::new(0.01, Duration::from_secs(100));
+        assert_eq!(duty_cycle.budget(), Duration::from_secs(1));
+
+        let airtime = Duration::from_millis(400);
+        duty_cycle.reserve(airtime, start).unwrap();
+        duty_cycle
+            .reserve(airtime, start + Duration::from_secs(10))
+            .unwrap();
+
+        // The third would make 1.2 s; the first leaves the window at 100 s
+        match duty_cycle.reserve(airtime, start + Duration::from_secs(20)) {
+            Err(RadioError::DutyCycle { retry_after }) => {
+                assert_eq!(retry_after, Duration::from_secs(80))
+            }
+            other => panic!("expected duty-cycle refusal, got {:?}", other),
+        }
+        assert_eq!(
+            duty_cycle.used(start + Duration::from_secs(20)),
+            Duration::from_millis(800)
+        );
+
+        duty_cycle
+            .reserve(airtime, start + Duration::from_secs(100))
+            .unwrap();
+        assert_eq!(
+            duty_cycle.used(start + Duration::from_secs(100)),
+            Duration::from_millis(800)
+        );
+    }
+
+    #[test]
+    fn test_transmission_larger_than_budget_rejected() {
+        let mut duty_cycle = DutyCycle::new(0.01, Duration::from_secs(10));
+        assert!(matches!(
+            duty_cycle.reserve(Duration::from_millis(200), Instant::now()),
+            Err(RadioError::Config(_))
+        ));
+    }
+}
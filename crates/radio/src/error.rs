@@ -0,0 +1,58 @@
+//! Error types for AetherCore radio drivers.
+
+use std::time::Duration;
+use thiserror::Error;
+
+/// Errors that can occur talking to a radio modem.
+#[derive(Debug, Error)]
+pub enum RadioError {
+    /// Serial port I/O errors
+    #[error("Serial I/O error: {0}")]
+    Io(
+        /// Serial I/O error details
+        #[from]
+        std::io::Error,
+    ),
+
+    /// Malformed serial frames or radio packets
+    #[error("Protocol error: {0}")]
+    Protocol(
+        /// Protocol error details
+        String,
+    ),
+
+    /// Configuration errors
+    #[error("Configuration error: {0}")]
+    Config(
+        /// Configuration error details
+        String,
+    ),
+
+    /// Modem reported a failure
+    #[error("Modem error: {0}")]
+    Modem(
+        /// Modem error details
+        String,
+    ),
+
+    /// Transmitting now would exceed the duty-cycle budget
+    #[error("Duty-cycle budget exhausted, retry after {retry_after:?}")]
+    DutyCycle {
+        /// Time until the budget allows the transmission
+        retry_after: Duration,
+    },
+
+    /// Timeout error
+    #[error("Operation timed out: {0}")]
+    Timeout(
+        /// Timeout details
+        String,
+    ),
+
+    /// The modem link has shut down
+    #[error("Radio closed")]
+    Closed,
+}
+
+/// Result type for radio operations.
+pub type RadioResult<T> = Result<T, RadioError>;
@@ -0,0 +1,269 @@
+//! Fake LoRa modems on pseudo-terminals, for tests
+//!
+//! Each `FakeModem` answers the serial protocol on the master side of a
+//! pty; `LoraRadio` opens the slave side by path as it would a USB serial
+//! device. Modems attached to the same `FakeAir` hear each other's
+//! transmissions when configured alike, with the signal readings set on
+//! the air. Transmissions can be lost on the air to exercise reassembly and
+//! error accounting.
+
+use crate::lora::LoraSettings;
+use crate::protocol::{ModemCommand, ModemEvent, SlipDecoder};
+use nix::poll::{poll, PollFd, PollFlags};
+use nix::pty::openpty;
+use nix::sys::termios;
+use nix::unistd;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+/// How long a modem thread waits for host bytes before checking its inbox
+const POLL_INTERVAL_MS: i32 = 5;
+
+struct Station {
+    settings: Option<LoraSettings>,
+    inbox: mpsc::Sender<ModemEvent>,
+}
+
+struct Air {
+    stations: Vec<Station>,
+    rssi_dbm: i16,
+    snr_db: f32,
+    /// Transmissions still to be lost
+    lose: usize,
+    transmissions: usize,
+}
+
+/// Shared medium connecting fake modems
+#[derive(Clone)]
+pub struct FakeAir {
+    air: Arc<Mutex<Air>>,
+}
+
+impl Default for FakeAir {
+    fn default() -> Self {
+        Self {
+            air: Arc::new(Mutex::new(Air {
+                stations: Vec::new(),
+                rssi_dbm: -90,
+                snr_db: 7.5,
+                lose: 0,
+                transmissions: 0,
+            })),
+        }
+    }
+}
+
+impl FakeAir {
+    /// Empty air with a clean signal
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a modem on a new pty, listening on this air
+    pub fn modem(&self) -> io::Result<FakeModem> {
+        FakeModem::spawn(self.clone())
+    }
+
+    /// Signal readings reported with every packet from now on
+    pub fn set_signal(&self, rssi_dbm: i16, snr_db: f32) {
+        let mut air = self.lock();
+        air.rssi_dbm = rssi_dbm;
+        air.snr_db = snr_db;
+    }
+
+    /// Lose the next `count` transmissions; the sending modem still reports
+    /// them sent
+    pub fn lose_next(&self, count: usize) {
+        self.lock().lose = count;
+    }
+
+    /// Packets transmitted so far, lost ones included
+    pub fn transmissions(&self) -> usize {
+        self.lock().transmissions
+    }
+
+    fn attach(&self, inbox: mpsc::Sender<ModemEvent>) -> usize {
+        let mut air = self.lock();
+        air.stations.push(Station {
+            settings: None,
+            inbox,
+        });
+        air.stations.len() - 1
+    }
+
+    fn transmit(&self, from: usize, packet: Vec<u8>) {
+        let mut air = self.lock();
+        air.transmissions += 1;
+        if air.lose > 0 {
+            air.lose -= 1;
+            return;
+        }
+        let settings = air.stations[from].settings;
+        for (index, station) in air.stations.iter().enumerate() {
+            if index == from || !same_channel(station.settings, settings) {
+                continue;
+            }
+            // A receiver that has gone away simply stops hearing
+            let _ = station.inbox.send(ModemEvent::Received {
+                rssi_dbm: air.rssi_dbm,
+                snr_db: air.snr_db,
+                packet: packet.clone(),
+            });
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Air> {
+        aethercore_core::sync::lock(&self.air)
+    }
+}
+
+/// Whether two modems can hear each other; power and preamble do not matter
+fn same_channel(a: Option<LoraSettings>, b: Option<LoraSettings>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            a.frequency_hz == b.frequency_hz
+                && a.bandwidth_hz == b.bandwidth_hz
+                && a.spreading_factor == b.spreading_factor
+                && a.coding_rate == b.coding_rate
+        }
+        _ => false,
+    }
+}
+
+/// Fake modem answering on a pty until dropped
+pub struct FakeModem {
+    path: PathBuf,
+    air: FakeAir,
+    index: usize,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeModem {
+    fn spawn(air: FakeAir) -> io::Result<Self> {
+        let pty = openpty(None, None).map_err(io::Error::from)?;
+        let path = unistd::ttyname(pty.slave).map_err(io::Error::from)?;
+        // Raw from the start, so nothing written before the host opens the
+        // port is echoed or translated
+        let mut settings = termios::tcgetattr(pty.slave).map_err(io::Error::from)?;
+        termios::cfmakeraw(&mut settings);
+        termios::tcsetattr(pty.slave, termios::SetArg::TCSANOW, &settings)
+            .map_err(io::Error::from)?;
+
+        let (inbox, events) = mpsc::channel();
+        let index = air.attach(inbox);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::spawn({
+            let air = air.clone();
+            let stop = stop.clone();
+            move || {
+                serve(pty.master, index, &air, &events, &stop);
+                let _ = unistd::close(pty.master);
+                let _ = unistd::close(pty.slave);
+            }
+        });
+        Ok(Self {
+            path,
+            air,
+            index,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Serial device to open the modem at
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Settings the host last configured
+    pub fn settings(&self) -> Option<LoraSettings> {
+        self.air.lock().stations[self.index].settings
+    }
+}
+
+impl Drop for FakeModem {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        self.air.lock().stations[self.index].settings = None;
+    }
+}
+
+/// Answer host commands and pass on packets heard, until stopped
+fn serve(
+    master: RawFd,
+    index: usize,
+    air: &FakeAir,
+    events: &mpsc::Receiver<ModemEvent>,
+    stop: &AtomicBool,
+) {
+    let mut decoder = SlipDecoder::new();
+    let mut buf = [0u8; 512];
+    while !stop.load(Ordering::Relaxed) {
+        for event in events.try_iter() {
+            if write_all(master, &event.encode()).is_err() {
+                return;
+            }
+        }
+
+        let mut fds = [PollFd::new(master, PollFlags::POLLIN)];
+        match poll(&mut fds, POLL_INTERVAL_MS) {
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(_) => return,
+        }
+        let revents = fds[0].revents().unwrap_or(PollFlags::empty());
+        if !revents.contains(PollFlags::POLLIN) {
+            // Nobody has the slave side open; wait for the host
+            std::thread::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS as u64));
+            continue;
+        }
+        let len = match unistd::read(master, &mut buf) {
+            Ok(len) => len,
+            // EIO until the host opens the slave side
+            Err(_) => continue,
+        };
+
+        for frame in decoder.feed(&buf[..len]) {
+            let answer = match ModemCommand::decode(&frame) {
+                Ok(ModemCommand::Configure(settings)) => match settings.validate() {
+                    Ok(()) => {
+                        air.lock().stations[index].settings = Some(settings);
+                        ModemEvent::Configured
+                    }
+                    Err(e) => ModemEvent::Error(e.to_string()),
+                },
+                Ok(ModemCommand::Transmit(packet)) => {
+                    if air.lock().stations[index].settings.is_none() {
+                        ModemEvent::Error("Not configured".to_string())
+                    } else if packet.len() > 255 {
+                        ModemEvent::Error("Packet too long".to_string())
+                    } else {
+                        air.transmit(index, packet);
+                        ModemEvent::TxDone
+                    }
+                }
+                Err(e) => ModemEvent::Error(e.to_string()),
+            };
+            if write_all(master, &answer.encode()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn write_all(fd: RawFd, mut bytes: &[u8]) -> nix::Result<()> {
+    while !bytes.is_empty() {
+        let written = unistd::write(fd, bytes)?;
+        bytes = &bytes[written..];
+    }
+    Ok(())
+}
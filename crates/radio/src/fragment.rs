@@ -0,0 +1,298 @@
+//! Fragmentation and reassembly over small LoRa packets
+//!
+//! Every packet starts with a 12-byte header, big-endian:
+//!
+//! ```text
+//! destination u32 | source u32 | message ID u16 | index u8 | count u8
+//! ```
+//!
+//! A message is split into at most 255 fragments. Fragments are not
+//! retransmitted; a message missing any fragment when its reassembly times
+//! out is dropped, and the loss counts towards the sender's packet error
+//! rate.
+
+use crate::error::{RadioError, RadioResult};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Length of the fragment header
+pub const HEADER_LEN: usize = 12;
+
+/// Header carried by every fragment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentHeader {
+    /// Station the message is for, or `BROADCAST`
+    pub destination: u32,
+    /// Station that sent the message
+    pub source: u32,
+    /// Sender's message counter
+    pub message_id: u16,
+    /// Position of this fragment
+    pub index: u8,
+    /// Number of fragments in the message
+    pub count: u8,
+}
+
+impl FragmentHeader {
+    /// Split a packet into its header and fragment data
+    pub fn decode(packet: &[u8]) -> RadioResult<(Self, &[u8])> {
+        if packet.len() < HEADER_LEN {
+            return Err(RadioError::Protocol(
+                "Packet shorter than header".to_string(),
+            ));
+        }
+        let header = Self {
+            destination: u32::from_be_bytes(packet[0..4].try_into().unwrap()),
+            source: u32::from_be_bytes(packet[4..8].try_into().unwrap()),
+            message_id: u16::from_be_bytes([packet[8], packet[9]]),
+            index: packet[10],
+            count: packet[11],
+        };
+        if header.count == 0 || header.index >= header.count {
+            return Err(RadioError::Protocol(format!(
+                "Fragment {} of {} out of range",
+                header.index, header.count
+            )));
+        }
+        Ok((header, &packet[HEADER_LEN..]))
+    }
+
+    fn encode(&self, packet: &mut Vec<u8>) {
+        packet.extend_from_slice(&self.destination.to_be_bytes());
+        packet.extend_from_slice(&self.source.to_be_bytes());
+        packet.extend_from_slice(&self.message_id.to_be_bytes());
+        packet.push(self.index);
+        packet.push(self.count);
+    }
+}
+
+/// Split `message` into packets of at most `max_packet` bytes
+pub fn fragment(
+    destination: u32,
+    source: u32,
+    message_id: u16,
+    message: &[u8],
+    max_packet: usize,
+) -> RadioResult<Vec<Vec<u8>>> {
+    let chunk = max_packet.saturating_sub(HEADER_LEN);
+    if chunk == 0 {
+        return Err(RadioError::Config(format!(
+            "Packets of {} bytes leave no room for data",
+            max_packet
+        )));
+    }
+    let count = message.len().div_ceil(chunk).max(1);
+    if count > u8::MAX as usize {
+        return Err(RadioError::Config(format!(
+            "Message of {} bytes needs {} fragments, more than {}",
+            message.len(),
+            count,
+            u8::MAX
+        )));
+    }
+
+    let chunks: Vec<&[u8]> = if message.is_empty() {
+        vec![&[]]
+    } else {
+        message.chunks(chunk).collect()
+    };
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, data)| {
+            let mut packet = Vec::with_capacity(HEADER_LEN + data.len());
+            FragmentHeader {
+                destination,
+                source,
+                message_id,
+                index: index as u8,
+                count: count as u8,
+            }
+            .encode(&mut packet);
+            packet.extend_from_slice(data);
+            packet
+        })
+        .collect())
+}
+
+/// Message that timed out before all its fragments arrived
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Incomplete {
+    /// Station that sent it
+    pub source: u32,
+    /// Fragments that arrived
+    pub received: usize,
+    /// Fragments it was sent in
+    pub count: usize,
+}
+
+struct Partial {
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+    started: Instant,
+}
+
+/// Collects fragments back into messages
+pub struct Reassembler {
+    timeout: Duration,
+    partial: HashMap<(u32, u16), Partial>,
+}
+
+impl Reassembler {
+    /// Keep incomplete messages for `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            partial: HashMap::new(),
+        }
+    }
+
+    /// Add a fragment, returning the message once it is complete
+    ///
+    /// Duplicates are ignored. A fragment whose count disagrees with the
+    /// ones held for the same message starts the message over, as the
+    /// sender's counter has wrapped or it restarted.
+    pub fn push(&mut self, header: FragmentHeader, data: &[u8], now: Instant) -> Option<Vec<u8>> {
+        if header.count == 1 {
+            return Some(data.to_vec());
+        }
+        let key = (header.source, header.message_id);
+        let partial = self
+            .partial
+            .entry(key)
+            .and_modify(|partial| {
+                if partial.fragments.len() != header.count as usize {
+                    *partial = Partial::new(header.count, now);
+                }
+            })
+            .or_insert_with(|| Partial::new(header.count, now));
+
+        let slot = &mut partial.fragments[header.index as usize];
+        if slot.is_none() {
+            *slot = Some(data.to_vec());
+            partial.received += 1;
+        }
+        if partial.received < partial.fragments.len() {
+            return None;
+        }
+        let partial = self.partial.remove(&key)?;
+        Some(partial.fragments.into_iter().flatten().flatten().collect())
+    }
+
+    /// Drop messages that have waited longer than the timeout
+    pub fn expire(&mut self, now: Instant) -> Vec<Incomplete> {
+        let mut expired = Vec::new();
+        self.partial.retain(|(source, _), partial| {
+            if now.duration_since(partial.started) < self.timeout {
+                return true;
+            }
+            expired.push(Incomplete {
+                source: *source,
+                received: partial.received,
+                count: partial.fragments.len(),
+            });
+            false
+        });
+        expired
+    }
+}
+
+impl Partial {
+    fn new(count: u8, started: Instant) -> Self {
+        Self {
+            fragments: vec![None; count as usize],
+            received: 0,
+            started,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(packets: &[Vec<u8>]) -> Vec<(FragmentHeader, &[u8])> {
+        packets
+            .iter()
+            .map(|packet| FragmentHeader::decode(packet).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fragment_sizes_and_headers() {
+        let message: Vec<u8> = (0..=255).collect();
+        let packets = fragment(7, 3, 42, &message, 100).unwrap();
+        assert_eq!(packets.len(), 3);
+        assert!(packets.iter().all(|packet| packet.len() <= 100));
+
+        let fragments = decode(&packets);
+        for (index, (header, _)) in fragments.iter().enumerate() {
+            assert_eq!(header.destination, 7);
+            assert_eq!(header.source, 3);
+            assert_eq!(header.message_id, 42);
+            assert_eq!(header.index as usize, index);
+            assert_eq!(header.count, 3);
+        }
+        let data: Vec<u8> = fragments
+            .iter()
+            .flat_map(|(_, data)| data.to_vec())
+            .collect();
+        assert_eq!(data, message);
+
+        assert_eq!(fragment(7, 3, 0, &[], 100).unwrap().len(), 1);
+        assert!(fragment(7, 3, 0, &vec![0; 88 * 256], 100).is_err());
+    }
+
+    #[test]
+    fn test_reassembly_out_of_order_with_duplicates() {
+        let message: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let packets = fragment(1, 2, 9, &message, 64).unwrap();
+        let fragments = decode(&packets);
+        let now = Instant::now();
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(10));
+        let (last, first) = fragments.split_last().unwrap();
+        for (header, data) in first.iter().rev() {
+            assert_eq!(reassembler.push(*header, data, now), None);
+            assert_eq!(reassembler.push(*header, data, now), None);
+        }
+        assert_eq!(reassembler.push(last.0, last.1, now), Some(message));
+        assert!(reassembler.expire(now + Duration::from_secs(60)).is_empty());
+    }
+
+    #[test]
+    fn test_incomplete_message_expires() {
+        let packets = fragment(1, 2, 9, &[0; 200], 64).unwrap();
+        let fragments = decode(&packets);
+        let start = Instant::now();
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(10));
+        for (header, data) in fragments.iter().skip(1) {
+            assert_eq!(reassembler.push(*header, data, start), None);
+        }
+        assert!(reassembler
+            .expire(start + Duration::from_secs(5))
+            .is_empty());
+        assert_eq!(
+            reassembler.expire(start + Duration::from_secs(10)),
+            vec![Incomplete {
+                source: 2,
+                received: fragments.len() - 1,
+                count: fragments.len(),
+            }]
+        );
+
+        // The missing fragment arriving late starts a new, incomplete message
+        let (header, data) = fragments[0];
+        let later = start + Duration::from_secs(11);
+        assert_eq!(reassembler.push(header, data, later), None);
+    }
+
+    #[test]
+    fn test_bad_headers_rejected() {
+        assert!(FragmentHeader::decode(&[0; 11]).is_err());
+        let mut packet = fragment(1, 2, 3, b"x", 64).unwrap().remove(0);
+        packet[10] = 1;
+        assert!(FragmentHeader::decode(&packet).is_err());
+    }
+}
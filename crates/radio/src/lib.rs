@@ -1,7 +1,27 @@
 //! AetherCore Radio
 //!
-//! Radio communication functionality.
+//! Radio communication functionality, including the host side of a
+//! serial-attached LoRa modem.
 
 #![warn(missing_docs)]
 
+pub mod duty_cycle;
+pub mod error;
+pub mod fragment;
+pub mod link_stats;
+pub mod lora;
+pub mod protocol;
 pub mod radio;
+
+#[cfg(unix)]
+mod driver;
+#[cfg(unix)]
+pub mod fake_modem;
+#[cfg(unix)]
+mod serial;
+
+#[cfg(unix)]
+pub use driver::LoraRadio;
+pub use error::{RadioError, RadioResult};
+pub use link_stats::LinkStats;
+pub use lora::{format_address, parse_address, LoraConfig, LoraSettings, BROADCAST};
@@ -0,0 +1,78 @@
+//! Per-station link measurements
+//!
+//! Signal readings are smoothed over the packets heard from a station, and
+//! the packet error rate over the messages it sent: a message counts for
+//! the share of its fragments that never arrived.
+
+use serde::{Deserialize, Serialize};
+
+/// Weight of the newest sample in the running averages
+const SMOOTHING: f64 = 0.25;
+
+/// Link measurements for one remote station
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkStats {
+    /// Received signal strength in dBm
+    pub rssi_dbm: f64,
+    /// Signal-to-noise ratio in dB
+    pub snr_db: f64,
+    /// Packet Error Rate (0.0 to 1.0)
+    pub packet_error_rate: f64,
+    /// Packets heard from the station
+    pub packets_received: u64,
+    /// Last time a packet was heard, in Unix milliseconds
+    pub last_heard: u64,
+}
+
+impl LinkStats {
+    pub(crate) fn new(rssi_dbm: f64, snr_db: f64, now_ms: u64) -> Self {
+        Self {
+            rssi_dbm,
+            snr_db,
+            packet_error_rate: 0.0,
+            packets_received: 1,
+            last_heard: now_ms,
+        }
+    }
+
+    /// Fold in the signal of another packet from the station
+    pub(crate) fn record_packet(&mut self, rssi_dbm: f64, snr_db: f64, now_ms: u64) {
+        self.rssi_dbm += SMOOTHING * (rssi_dbm - self.rssi_dbm);
+        self.snr_db += SMOOTHING * (snr_db - self.snr_db);
+        self.packets_received += 1;
+        self.last_heard = now_ms;
+    }
+
+    /// Fold in a message, of which `received` out of `count` fragments
+    /// arrived
+    pub(crate) fn record_message(&mut self, received: usize, count: usize) {
+        let lost = 1.0 - received as f64 / count.max(1) as f64;
+        self.packet_error_rate += SMOOTHING * (lost - self.packet_error_rate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_smoothed_towards_new_readings() {
+        let mut stats = LinkStats::new(-80.0, 10.0, 1);
+        stats.record_packet(-120.0, -10.0, 2);
+        assert_eq!(stats.rssi_dbm, -90.0);
+        assert_eq!(stats.snr_db, 5.0);
+        assert_eq!(stats.packets_received, 2);
+        assert_eq!(stats.last_heard, 2);
+    }
+
+    #[test]
+    fn test_packet_error_rate_follows_lost_fragments() {
+        let mut stats = LinkStats::new(-80.0, 10.0, 1);
+        stats.record_message(0, 4);
+        assert_eq!(stats.packet_error_rate, 0.25);
+        for _ in 0..50 {
+            stats.record_message(4, 4);
+        }
+        assert!(stats.packet_error_rate < 0.001);
+    }
+}
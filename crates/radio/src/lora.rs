@@ -0,0 +1,214 @@
+//! LoRa modem settings and link configuration
+
+use crate::error::{RadioError, RadioResult};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Station address every radio accepts packets for
+pub const BROADCAST: u32 = u32::MAX;
+
+/// Prefix of radio addresses in their string form
+const ADDRESS_PREFIX: &str = "lora:";
+
+/// Format a station address as used in `PeerInfo.address`, e.g. `lora:0000a1b2`
+pub fn format_address(address: u32) -> String {
+    format!("{}{:08x}", ADDRESS_PREFIX, address)
+}
+
+/// Parse a station address formatted by `format_address`
+pub fn parse_address(address: &str) -> RadioResult<u32> {
+    address
+        .strip_prefix(ADDRESS_PREFIX)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| RadioError::Config(format!("Invalid LoRa address {}", address)))
+}
+
+/// Modulation and transmit settings of a LoRa modem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoraSettings {
+    /// Carrier frequency in Hz
+    pub frequency_hz: u32,
+    /// Signal bandwidth in Hz
+    pub bandwidth_hz: u32,
+    /// Spreading factor (7 to 12)
+    pub spreading_factor: u8,
+    /// Coding rate denominator, 5 to 8 for 4/5 to 4/8
+    pub coding_rate: u8,
+    /// Transmit power in dBm
+    pub tx_power_dbm: i8,
+    /// Preamble length in symbols
+    pub preamble_len: u16,
+}
+
+impl Default for LoraSettings {
+    fn default() -> Self {
+        Self {
+            frequency_hz: 868_100_000,
+            bandwidth_hz: 125_000,
+            spreading_factor: 9,
+            coding_rate: 5,
+            tx_power_dbm: 14,
+            preamble_len: 8,
+        }
+    }
+}
+
+impl LoraSettings {
+    /// Check the settings are ones a LoRa modem supports
+    pub fn validate(&self) -> RadioResult<()> {
+        if !(7..=12).contains(&self.spreading_factor) {
+            return Err(RadioError::Config(format!(
+                "Spreading factor {} outside 7 to 12",
+                self.spreading_factor
+            )));
+        }
+        if !(5..=8).contains(&self.coding_rate) {
+            return Err(RadioError::Config(format!(
+                "Coding rate 4/{} outside 4/5 to 4/8",
+                self.coding_rate
+            )));
+        }
+        if !matches!(self.bandwidth_hz, 125_000 | 250_000 | 500_000) {
+            return Err(RadioError::Config(format!(
+                "Unsupported bandwidth {} Hz",
+                self.bandwidth_hz
+            )));
+        }
+        Ok(())
+    }
+
+    /// Time on air of a packet carrying `payload_len` bytes
+    ///
+    /// Semtech's formula for an explicit header with CRC, with low data
+    /// rate optimization on wherever symbols exceed 16 ms as modems enable
+    /// it.
+    pub fn time_on_air(&self, payload_len: usize) -> Duration {
+        let sf = self.spreading_factor as f64;
+        let symbol_s = (1u64 << self.spreading_factor) as f64 / self.bandwidth_hz as f64;
+        let low_data_rate = if symbol_s > 0.016 { 1.0 } else { 0.0 };
+
+        let bits = 8.0 * payload_len as f64 - 4.0 * sf + 28.0 + 16.0;
+        let blocks = (bits / (4.0 * (sf - 2.0 * low_data_rate))).ceil().max(0.0);
+        let payload_symbols = 8.0 + blocks * self.coding_rate as f64;
+        let preamble_symbols = self.preamble_len as f64 + 4.25;
+
+        Duration::from_secs_f64((preamble_symbols + payload_symbols) * symbol_s)
+    }
+}
+
+/// Configuration of a LoRa radio link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoraConfig {
+    /// This station's address
+    pub address: u32,
+    /// Modem settings applied when the link opens
+    pub settings: LoraSettings,
+    /// Serial line speed to the modem
+    pub baud_rate: u32,
+    /// Largest packet the modem transmits, fragment header included
+    pub max_packet: usize,
+    /// Fraction of time the regulator allows transmitting, e.g. 0.01
+    pub duty_cycle: f64,
+    /// Window the duty cycle is measured over
+    pub duty_cycle_window: Duration,
+    /// How long the modem may take to acknowledge a command
+    pub command_timeout: Duration,
+    /// How long fragments of an incomplete message are kept
+    pub reassembly_timeout: Duration,
+}
+
+impl Default for LoraConfig {
+    fn default() -> Self {
+        Self {
+            address: 0,
+            settings: LoraSettings::default(),
+            baud_rate: 115_200,
+            max_packet: 255,
+            duty_cycle: 0.01,
+            duty_cycle_window: Duration::from_secs(3600),
+            command_timeout: Duration::from_secs(5),
+            reassembly_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl LoraConfig {
+    /// Link configuration for the station at `address`
+    pub fn new(address: u32) -> Self {
+        Self {
+            address,
+            ..Self::default()
+        }
+    }
+
+    /// Check the configuration can be used
+    pub fn validate(&self) -> RadioResult<()> {
+        self.settings.validate()?;
+        if self.address == BROADCAST {
+            return Err(RadioError::Config(
+                "The broadcast address cannot be a station address".to_string(),
+            ));
+        }
+        if self.max_packet <= crate::fragment::HEADER_LEN || self.max_packet > 255 {
+            return Err(RadioError::Config(format!(
+                "Maximum packet of {} bytes must leave room for the fragment header and fit 255",
+                self.max_packet
+            )));
+        }
+        if !(self.duty_cycle > 0.0 && self.duty_cycle <= 1.0) {
+            return Err(RadioError::Config(format!(
+                "Duty cycle {} must be above 0 and at most 1",
+                self.duty_cycle
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_round_trip() {
+        assert_eq!(format_address(0xa1b2), "lora:0000a1b2");
+        assert_eq!(parse_address("lora:0000a1b2").unwrap(), 0xa1b2);
+        assert!(parse_address("10.0.0.1:7400").is_err());
+        assert!(parse_address("lora:xyz").is_err());
+    }
+
+    #[test]
+    fn test_time_on_air_matches_semtech_calculator() {
+        let sf7 = LoraSettings {
+            spreading_factor: 7,
+            ..LoraSettings::default()
+        };
+        assert_eq!(sf7.time_on_air(10).as_micros(), 41_216);
+
+        // SF12 at 125 kHz turns on low data rate optimization
+        let sf12 = LoraSettings {
+            spreading_factor: 12,
+            ..LoraSettings::default()
+        };
+        assert_eq!(sf12.time_on_air(10).as_micros(), 991_232);
+        assert!(sf12.time_on_air(255) > sf12.time_on_air(10));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(LoraConfig::new(1).validate().is_ok());
+        assert!(LoraConfig::new(BROADCAST).validate().is_err());
+
+        let mut config = LoraConfig::new(1);
+        config.settings.spreading_factor = 6;
+        assert!(config.validate().is_err());
+
+        let mut config = LoraConfig::new(1);
+        config.max_packet = 300;
+        assert!(config.validate().is_err());
+
+        let mut config = LoraConfig::new(1);
+        config.duty_cycle = 0.0;
+        assert!(config.validate().is_err());
+    }
+}
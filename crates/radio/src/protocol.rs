@@ -0,0 +1,352 @@
+//! Serial protocol between host and LoRa modem
+//!
+//! Frames are SLIP-delimited (RFC 1055). Each carries a kind byte, a body
+//! and a CRC-16/CCITT-FALSE over both, big-endian:
+//!
+//! ```text
+//! host → modem  0x01 Configure  frequency u32, bandwidth u32, SF u8, CR u8,
+//!                               power i8, preamble u16
+//!               0x02 Transmit   packet
+//! modem → host  0x81 Configured
+//!               0x82 Received   RSSI i16 (dBm), SNR i8 (quarter dB), packet
+//!               0x83 TxDone
+//!               0x84 Error      UTF-8 reason
+//! ```
+//!
+//! The modem handles one command at a time and answers each before taking
+//! the next.
+
+use crate::error::{RadioError, RadioResult};
+use crate::lora::LoraSettings;
+
+const END: u8 = 0xC0;
+const ESC: u8 = 0xDB;
+const ESC_END: u8 = 0xDC;
+const ESC_ESC: u8 = 0xDD;
+
+/// Longest frame body accepted, well above a LoRa packet plus headers
+const MAX_FRAME_LEN: usize = 1024;
+
+const CONFIGURE: u8 = 0x01;
+const TRANSMIT: u8 = 0x02;
+const CONFIGURED: u8 = 0x81;
+const RECEIVED: u8 = 0x82;
+const TX_DONE: u8 = 0x83;
+const ERROR: u8 = 0x84;
+
+/// Command from host to modem
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModemCommand {
+    /// Apply modulation and transmit settings
+    Configure(LoraSettings),
+    /// Transmit one packet
+    Transmit(Vec<u8>),
+}
+
+/// Event from modem to host
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModemEvent {
+    /// Settings applied
+    Configured,
+    /// Packet received over the air
+    Received {
+        /// Received signal strength in dBm
+        rssi_dbm: i16,
+        /// Signal-to-noise ratio in dB, in quarter dB steps
+        snr_db: f32,
+        /// Packet
+        packet: Vec<u8>,
+    },
+    /// Last packet transmitted
+    TxDone,
+    /// Last command failed
+    Error(String),
+}
+
+impl ModemCommand {
+    /// Encode as a SLIP frame
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        match self {
+            ModemCommand::Configure(settings) => {
+                body.push(CONFIGURE);
+                body.extend_from_slice(&settings.frequency_hz.to_be_bytes());
+                body.extend_from_slice(&settings.bandwidth_hz.to_be_bytes());
+                body.push(settings.spreading_factor);
+                body.push(settings.coding_rate);
+                body.push(settings.tx_power_dbm as u8);
+                body.extend_from_slice(&settings.preamble_len.to_be_bytes());
+            }
+            ModemCommand::Transmit(packet) => {
+                body.push(TRANSMIT);
+                body.extend_from_slice(packet);
+            }
+        }
+        slip_encode(&body)
+    }
+
+    /// Decode a frame body, as taken from `SlipDecoder`
+    pub fn decode(frame: &[u8]) -> RadioResult<Self> {
+        let (kind, body) = split_frame(frame)?;
+        match kind {
+            CONFIGURE => {
+                if body.len() != 13 {
+                    return Err(protocol_error("Configure frame has wrong length"));
+                }
+                Ok(ModemCommand::Configure(LoraSettings {
+                    frequency_hz: u32::from_be_bytes(body[0..4].try_into().unwrap()),
+                    bandwidth_hz: u32::from_be_bytes(body[4..8].try_into().unwrap()),
+                    spreading_factor: body[8],
+                    coding_rate: body[9],
+                    tx_power_dbm: body[10] as i8,
+                    preamble_len: u16::from_be_bytes(body[11..13].try_into().unwrap()),
+                }))
+            }
+            TRANSMIT => Ok(ModemCommand::Transmit(body.to_vec())),
+            _ => Err(protocol_error(&format!("Unknown command 0x{:02x}", kind))),
+        }
+    }
+}
+
+impl ModemEvent {
+    /// Encode as a SLIP frame
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        match self {
+            ModemEvent::Configured => body.push(CONFIGURED),
+            ModemEvent::Received {
+                rssi_dbm,
+                snr_db,
+                packet,
+            } => {
+                body.push(RECEIVED);
+                body.extend_from_slice(&rssi_dbm.to_be_bytes());
+                body.push((snr_db * 4.0).round().clamp(-128.0, 127.0) as i8 as u8);
+                body.extend_from_slice(packet);
+            }
+            ModemEvent::TxDone => body.push(TX_DONE),
+            ModemEvent::Error(reason) => {
+                body.push(ERROR);
+                body.extend_from_slice(reason.as_bytes());
+            }
+        }
+        slip_encode(&body)
+    }
+
+    /// Decode a frame body, as taken from `SlipDecoder`
+    pub fn decode(frame: &[u8]) -> RadioResult<Self> {
+        let (kind, body) = split_frame(frame)?;
+        match kind {
+            CONFIGURED => Ok(ModemEvent::Configured),
+            RECEIVED => {
+                if body.len() < 3 {
+                    return Err(protocol_error("Received frame too short"));
+                }
+                Ok(ModemEvent::Received {
+                    rssi_dbm: i16::from_be_bytes([body[0], body[1]]),
+                    snr_db: body[2] as i8 as f32 / 4.0,
+                    packet: body[3..].to_vec(),
+                })
+            }
+            TX_DONE => Ok(ModemEvent::TxDone),
+            ERROR => Ok(ModemEvent::Error(
+                String::from_utf8_lossy(body).into_owned(),
+            )),
+            _ => Err(protocol_error(&format!("Unknown event 0x{:02x}", kind))),
+        }
+    }
+}
+
+fn protocol_error(reason: &str) -> RadioError {
+    RadioError::Protocol(reason.to_string())
+}
+
+/// Check the CRC and split a frame body into kind and payload
+fn split_frame(frame: &[u8]) -> RadioResult<(u8, &[u8])> {
+    if frame.len() < 3 {
+        return Err(protocol_error("Frame too short"));
+    }
+    let (data, crc) = frame.split_at(frame.len() - 2);
+    if crc16(data) != u16::from_be_bytes([crc[0], crc[1]]) {
+        return Err(protocol_error("Frame CRC mismatch"));
+    }
+    Ok((data[0], &data[1..]))
+}
+
+/// CRC-16/CCITT-FALSE
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Append the CRC and SLIP-encode, with a leading END to flush line noise
+fn slip_encode(body: &[u8]) -> Vec<u8> {
+    let crc = crc16(body).to_be_bytes();
+    let mut frame = Vec::with_capacity(body.len() + 8);
+    frame.push(END);
+    for &byte in body.iter().chain(crc.iter()) {
+        match byte {
+            END => frame.extend_from_slice(&[ESC, ESC_END]),
+            ESC => frame.extend_from_slice(&[ESC, ESC_ESC]),
+            _ => frame.push(byte),
+        }
+    }
+    frame.push(END);
+    frame
+}
+
+/// Splits a serial byte stream into SLIP frame bodies
+#[derive(Debug, Default)]
+pub struct SlipDecoder {
+    frame: Vec<u8>,
+    escaped: bool,
+    /// Current frame is oversized or badly escaped; skip to the next END
+    discarding: bool,
+}
+
+impl SlipDecoder {
+    /// Create a decoder waiting for the first frame
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed bytes read from the line, returning the frames they complete
+    ///
+    /// Frames still need checking with `ModemCommand::decode` or
+    /// `ModemEvent::decode`.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        for &byte in bytes {
+            if byte == END {
+                if !self.discarding && !self.frame.is_empty() {
+                    frames.push(std::mem::take(&mut self.frame));
+                }
+                self.frame.clear();
+                self.escaped = false;
+                self.discarding = false;
+                continue;
+            }
+            if self.discarding {
+                continue;
+            }
+            let byte = if self.escaped {
+                self.escaped = false;
+                match byte {
+                    ESC_END => END,
+                    ESC_ESC => ESC,
+                    _ => {
+                        self.discarding = true;
+                        continue;
+                    }
+                }
+            } else if byte == ESC {
+                self.escaped = true;
+                continue;
+            } else {
+                byte
+            };
+            if self.frame.len() == MAX_FRAME_LEN {
+                self.discarding = true;
+                continue;
+            }
+            self.frame.push(byte);
+        }
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_events(bytes: &[u8]) -> Vec<RadioResult<ModemEvent>> {
+        SlipDecoder::new()
+            .feed(bytes)
+            .iter()
+            .map(|frame| ModemEvent::decode(frame))
+            .collect()
+    }
+
+    #[test]
+    fn test_crc16_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_commands_round_trip() {
+        let commands = [
+            ModemCommand::Configure(LoraSettings {
+                tx_power_dbm: -3,
+                ..LoraSettings::default()
+            }),
+            // Bytes that need escaping
+            ModemCommand::Transmit(vec![END, ESC, 0x00, ESC_END, END]),
+        ];
+        let mut decoder = SlipDecoder::new();
+        for command in commands {
+            let frames = decoder.feed(&command.encode());
+            assert_eq!(frames.len(), 1);
+            assert_eq!(ModemCommand::decode(&frames[0]).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn test_events_round_trip_across_reads() {
+        let events = [
+            ModemEvent::Configured,
+            ModemEvent::Received {
+                rssi_dbm: -117,
+                snr_db: -7.25,
+                packet: vec![1, 2, END, 3],
+            },
+            ModemEvent::TxDone,
+            ModemEvent::Error("busy".to_string()),
+        ];
+        let stream: Vec<u8> = events.iter().flat_map(|event| event.encode()).collect();
+
+        // Split the stream at an arbitrary point, as serial reads do
+        let mut decoder = SlipDecoder::new();
+        let mut frames = decoder.feed(&stream[..7]);
+        frames.extend(decoder.feed(&stream[7..]));
+        let decoded: Vec<ModemEvent> = frames
+            .iter()
+            .map(|frame| ModemEvent::decode(frame).unwrap())
+            .collect();
+        assert_eq!(decoded, events);
+    }
+
+    #[test]
+    fn test_corrupt_frames_rejected() {
+        let mut frame = ModemEvent::TxDone.encode();
+        frame[1] ^= 0x01;
+        let decoded = decode_events(&frame);
+        assert_eq!(decoded.len(), 1);
+        assert!(matches!(decoded[0], Err(RadioError::Protocol(_))));
+
+        // Line noise before a frame does not swallow it
+        let mut stream = vec![0x55, ESC, 0x42];
+        stream.extend(ModemEvent::Configured.encode());
+        let decoded = decode_events(&stream);
+        assert!(matches!(decoded.last(), Some(Ok(ModemEvent::Configured))));
+    }
+
+    #[test]
+    fn test_oversized_frame_discarded() {
+        let mut stream = vec![END];
+        stream.extend(std::iter::repeat_n(0x11, MAX_FRAME_LEN + 10));
+        stream.extend(ModemEvent::TxDone.encode());
+        let decoded = decode_events(&stream);
+        assert_eq!(decoded.len(), 1);
+        assert!(matches!(decoded[0], Ok(ModemEvent::TxDone)));
+    }
+}
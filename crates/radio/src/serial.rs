@@ -0,0 +1,86 @@
+//! Raw serial port on a Unix tty
+
+use crate::error::{RadioError, RadioResult};
+use nix::sys::termios::{self, BaudRate, SetArg};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use tokio::io::unix::AsyncFd;
+
+/// Non-blocking tty in raw mode, driven by the tokio reactor
+#[derive(Debug)]
+pub(crate) struct SerialPort {
+    fd: AsyncFd<File>,
+}
+
+impl SerialPort {
+    /// Open `path` at `baud_rate`, 8N1 with no flow control or line editing
+    ///
+    /// Must be called within a tokio runtime.
+    pub(crate) fn open(path: &Path, baud_rate: u32) -> RadioResult<Self> {
+        let speed = baud(baud_rate)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(nix::libc::O_NOCTTY | nix::libc::O_NONBLOCK)
+            .open(path)?;
+
+        let mut settings = termios::tcgetattr(file.as_raw_fd()).map_err(io::Error::from)?;
+        termios::cfmakeraw(&mut settings);
+        termios::cfsetspeed(&mut settings, speed).map_err(io::Error::from)?;
+        termios::tcsetattr(file.as_raw_fd(), SetArg::TCSANOW, &settings)
+            .map_err(io::Error::from)?;
+
+        Ok(Self {
+            fd: AsyncFd::new(file)?,
+        })
+    }
+
+    /// Read whatever bytes are available, waiting for at least one
+    ///
+    /// Returns 0 once the line has hung up.
+    pub(crate) async fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            match guard.try_io(|fd| fd.get_ref().read(buf)) {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Write all of `bytes`
+    pub(crate) async fn write_all(&self, mut bytes: &[u8]) -> io::Result<()> {
+        while !bytes.is_empty() {
+            let mut guard = self.fd.writable().await?;
+            match guard.try_io(|fd| fd.get_ref().write(bytes)) {
+                Ok(Ok(0)) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(Ok(written)) => bytes = &bytes[written..],
+                Ok(Err(e)) => return Err(e),
+                Err(_would_block) => continue,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn baud(baud_rate: u32) -> RadioResult<BaudRate> {
+    Ok(match baud_rate {
+        9_600 => BaudRate::B9600,
+        19_200 => BaudRate::B19200,
+        38_400 => BaudRate::B38400,
+        57_600 => BaudRate::B57600,
+        115_200 => BaudRate::B115200,
+        230_400 => BaudRate::B230400,
+        460_800 => BaudRate::B460800,
+        921_600 => BaudRate::B921600,
+        _ => {
+            return Err(RadioError::Config(format!(
+                "Unsupported baud rate {}",
+                baud_rate
+            )))
+        }
+    })
+}
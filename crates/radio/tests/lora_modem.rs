@@ -0,0 +1,151 @@
+//! LoRa radios talking through fake modems on pseudo-terminals
+//!
+//! Tests cover:
+//! - Messages larger than a packet fragmented and reassembled
+//! - Broadcast and addressed delivery, and modems on other settings not
+//!   hearing
+//! - RSSI, SNR and packet error rate reported per station
+//! - The duty-cycle budget refusing a message before any of it is sent
+//! - Use as a mesh transport
+
+#![cfg(unix)]
+
+use aethercore_mesh::MeshTransport;
+use aethercore_radio::fake_modem::{FakeAir, FakeModem};
+use aethercore_radio::{format_address, LoraConfig, LoraRadio, RadioError, BROADCAST};
+use std::time::Duration;
+
+const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn config(address: u32) -> LoraConfig {
+    LoraConfig {
+        reassembly_timeout: Duration::from_millis(500),
+        ..LoraConfig::new(address)
+    }
+}
+
+async fn open(air: &FakeAir, config: LoraConfig) -> (FakeModem, LoraRadio) {
+    let modem = air.modem().unwrap();
+    let radio = LoraRadio::open(modem.path(), config).await.unwrap();
+    (modem, radio)
+}
+
+async fn recv(radio: &LoraRadio) -> (u32, Vec<u8>) {
+    tokio::time::timeout(RECV_TIMEOUT, radio.recv())
+        .await
+        .expect("no message received")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_large_message_fragmented_and_reassembled() {
+    let air = FakeAir::new();
+    let (modem1, radio1) = open(&air, config(1)).await;
+    let (_modem2, radio2) = open(&air, config(2)).await;
+    assert_eq!(modem1.settings(), Some(config(1).settings));
+
+    let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+    radio1.send(2, &message).await.unwrap();
+    assert_eq!(air.transmissions(), 5);
+    assert_eq!(recv(&radio2).await, (1, message));
+
+    radio2.send(BROADCAST, b"hello").await.unwrap();
+    assert_eq!(recv(&radio1).await, (2, b"hello".to_vec()));
+}
+
+#[tokio::test]
+async fn test_only_addressed_stations_on_same_settings_receive() {
+    let air = FakeAir::new();
+    let (_modem1, radio1) = open(&air, config(1)).await;
+    let (_modem2, radio2) = open(&air, config(2)).await;
+    let (_modem3, radio3) = open(&air, config(3)).await;
+
+    let mut other_channel = config(4);
+    other_channel.settings.frequency_hz = 869_525_000;
+    let (_modem4, radio4) = open(&air, other_channel).await;
+
+    radio1.send(3, b"for three").await.unwrap();
+    radio1.send(BROADCAST, b"for all").await.unwrap();
+    assert_eq!(recv(&radio2).await, (1, b"for all".to_vec()));
+    assert_eq!(recv(&radio3).await, (1, b"for three".to_vec()));
+    assert_eq!(recv(&radio3).await, (1, b"for all".to_vec()));
+
+    // Station 2 overheard the addressed message, so it knows the link
+    assert_eq!(radio2.link_stats()[&format_address(1)].packets_received, 2);
+    assert!(radio4.link_stats().is_empty());
+
+    // Retuned, station 4 hears the rest of the swarm
+    radio4.configure(config(4).settings).await.unwrap();
+    radio1.send(BROADCAST, b"welcome").await.unwrap();
+    assert_eq!(recv(&radio4).await, (1, b"welcome".to_vec()));
+}
+
+#[tokio::test]
+async fn test_signal_and_packet_errors_reported() {
+    let air = FakeAir::new();
+    let (_modem1, radio1) = open(&air, config(1)).await;
+    let (_modem2, radio2) = open(&air, config(2)).await;
+
+    air.set_signal(-118, -9.25);
+    radio1.send(2, b"weak").await.unwrap();
+    recv(&radio2).await;
+    let stats = radio2.link_stats()[&format_address(1)].clone();
+    assert_eq!(stats.rssi_dbm, -118.0);
+    assert_eq!(stats.snr_db, -9.25);
+    assert_eq!(stats.packet_error_rate, 0.0);
+
+    // Half of a two-fragment message is lost and never completes
+    air.lose_next(1);
+    radio1.send(2, &[0; 300]).await.unwrap();
+    radio1.send(2, b"after").await.unwrap();
+    assert_eq!(recv(&radio2).await, (1, b"after".to_vec()));
+
+    tokio::time::sleep(Duration::from_millis(1600)).await;
+    let stats = radio2.link_stats()[&format_address(1)].clone();
+    assert!(stats.packet_error_rate > 0.0);
+}
+
+#[tokio::test]
+async fn test_duty_cycle_refuses_whole_message() {
+    let air = FakeAir::new();
+    let mut config = LoraConfig {
+        duty_cycle: 0.1,
+        duty_cycle_window: Duration::from_secs(10),
+        ..config(1)
+    };
+    config.settings.spreading_factor = 7;
+    let (_modem1, radio1) = open(&air, config).await;
+
+    // At SF7 the message's two packets take 0.67 s of the 1 s budget
+    radio1.send(2, &[0; 400]).await.unwrap();
+    let sent = air.transmissions();
+    match radio1.send(2, &[0; 400]).await {
+        Err(RadioError::DutyCycle { retry_after }) => {
+            assert!(retry_after > Duration::from_secs(9))
+        }
+        other => panic!("expected duty-cycle refusal, got {:?}", other),
+    }
+    assert_eq!(air.transmissions(), sent);
+    assert!(radio1.airtime_used().await > Duration::from_millis(600));
+
+    // A short message still fits
+    radio1.send(2, b"ok").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_radio_as_mesh_transport() {
+    let air = FakeAir::new();
+    let (_modem1, radio1) = open(&air, config(0xa1)).await;
+    let (_modem2, radio2) = open(&air, config(0xb2)).await;
+    assert_eq!(radio2.local_address(), "lora:000000b2");
+
+    let frame = vec![0x7b; 600];
+    radio1.send_to("lora:000000b2", &frame).await.unwrap();
+    let received = tokio::time::timeout(RECV_TIMEOUT, MeshTransport::recv(&radio2))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(received, ("lora:000000a1".to_string(), frame));
+
+    assert!(radio1.send_to("10.0.0.1:7400", b"x").await.is_err());
+}
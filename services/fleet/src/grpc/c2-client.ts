@@ -66,6 +66,7 @@ export class C2RouterClient {
     commandJson: string;
     signatures: string[];
     timestampNs: number;
+    nonce: string;
   }): Promise<any> {
     // Placeholder: In production, this would call the gRPC service
     return {
@@ -26,6 +26,7 @@ export interface SwarmCommandRequest {
   command: Record<string, unknown>;  // SwarmCommand (JSON)
   signatures: string[];
   timestampNs: number;
+  nonce: string;  // Unique per device; covered by the device signature
 }
 
 export interface SwarmCommandResponse {
@@ -87,6 +87,7 @@ test('buildUnitCommandRequest normalizes command payload shape', () => {
 
   assert.equal(request.unit_id, 'unit-99');
   assert.deepEqual(request.signatures, ['sig-xyz']);
+  assert.equal(request.nonce, '123e4567-e89b-12d3-a456-426614174000');
   assert.deepEqual(JSON.parse(request.command_json), {
     id: '123e4567-e89b-12d3-a456-426614174000',
     type: 'MARK_HOSTILE',
@@ -16,6 +16,7 @@ export type UnitCommandRequest = {
   command_json: string;
   signatures: string[];
   timestamp_ns: string;
+  nonce: string;
 };
 
 export type UnitCommandResponse = {
@@ -156,6 +157,7 @@ export function buildUnitCommandRequest(command: CommandFrame): UnitCommandReque
     }),
     signatures: [command.signature],
     timestamp_ns: Date.now().toString(),
+    nonce: command.id,
   };
 }
 
@@ -19,14 +19,18 @@
 
 use aethercore_c2_router::grpc::{C2GrpcServer, C2Router, UnitCommandRequest};
 use aethercore_c2_router::{
-    authority::AuthorityVerifier, command_types::UnitCommand, dispatcher::CommandDispatcher,
+    authority::{AuthoritySignature, AuthorityVerifier},
+    command_types::UnitCommand,
+    dispatcher::CommandDispatcher,
     quorum::QuorumGate,
+    unit_link::{InMemoryUnitLink, UnitBehavior},
 };
 use aethercore_crypto::chain::GENESIS_HASH;
 use aethercore_crypto::signing::CanonicalEvent;
 use aethercore_identity::{Attestation, IdentityManager, PlatformIdentity};
 use aethercore_trust_mesh::TrustScorer;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tonic::metadata::MetadataValue;
 use tonic::Request;
 
@@ -36,6 +40,7 @@ mod test_utils {
     use base64::engine::general_purpose;
     use base64::Engine as _;
     use ed25519_dalek::{Signer, SigningKey};
+    use std::sync::atomic::{AtomicU64, Ordering};
     use tonic::metadata::MetadataValue;
 
     /// Create a valid node identity for testing
@@ -55,7 +60,7 @@ mod test_utils {
     }
 
     /// Derive a deterministic Ed25519 key from the node ID (test-only)
-    fn derive_signing_key(node_id: &str) -> SigningKey {
+    pub fn derive_signing_key(node_id: &str) -> SigningKey {
         let hash = blake3::hash(node_id.as_bytes());
         let mut secret = [0u8; 32];
         secret.copy_from_slice(hash.as_bytes());
@@ -96,20 +101,33 @@ mod test_utils {
         device_id: &'static str,
         command: &UnitCommand,
     ) -> Request<UnitCommandRequest> {
+        static NEXT_NONCE: AtomicU64 = AtomicU64::new(1);
         let command_json = serde_json::to_string(command).unwrap();
         let timestamp_ns = current_timestamp_ns();
+        let nonce = format!("nonce-{}", NEXT_NONCE.fetch_add(1, Ordering::Relaxed));
 
         // Sign the command with the device's deterministic test key
         let signing_key = derive_signing_key(device_id);
-        let message = format!("{}:{}:{}", device_id, command_json, timestamp_ns);
+        let message = format!("{}:{}:{}:{}", device_id, command_json, timestamp_ns, nonce);
         let signature = signing_key.sign(message.as_bytes());
         let signature_b64 = general_purpose::STANDARD.encode(signature.to_bytes());
 
+        // The node also approves the command as its operator authority
+        let command_hash =
+            QuorumGate::unit_command_hash(device_id, "unit-1", &command_json, timestamp_ns, &nonce);
+        let authority = AuthoritySignature::new(
+            device_id.to_string(),
+            signing_key.sign(&command_hash).to_bytes().to_vec(),
+            signing_key.verifying_key().to_bytes(),
+            timestamp_ns,
+        );
+
         let mut request = Request::new(UnitCommandRequest {
             unit_id: "unit-1".to_string(),
             command_json,
-            signatures: vec![signature_b64.clone()],
+            signatures: vec![serde_json::to_string(&authority).unwrap()],
             timestamp_ns,
+            nonce,
         });
 
         request
@@ -126,8 +144,27 @@ mod test_utils {
 
 /// Setup a C2 server with specific nodes and trust scores
 fn setup_server_with_nodes(nodes: &[(&str, f64)]) -> C2GrpcServer {
+    // Commands go to a simulated "unit-1" that acknowledges everything
     let dispatcher = CommandDispatcher::new();
-    let verifier = AuthorityVerifier::new();
+    let unit_key = test_utils::derive_signing_key("unit-1");
+    dispatcher.register_unit_key("unit-1", unit_key.verifying_key().to_bytes());
+    let router_key = test_utils::derive_signing_key("c2-router");
+    let link = InMemoryUnitLink::new(
+        dispatcher.reply_sink(),
+        router_key.verifying_key().to_bytes(),
+    );
+    link.add_unit("unit-1", unit_key, UnitBehavior::Ack);
+    let dispatcher = dispatcher
+        .with_unit_link(Arc::new(link))
+        .with_router_key(router_key);
+
+    let mut verifier = AuthorityVerifier::new();
+    for (node_id, _) in nodes {
+        let public_key = test_utils::derive_signing_key(node_id)
+            .verifying_key()
+            .to_bytes();
+        verifier.register_authority(node_id.to_string(), public_key);
+    }
     let quorum_gate = QuorumGate::new(verifier);
 
     // Configure trust scorer with specified scores
@@ -253,6 +290,7 @@ async fn test_quarantined_node_command_rejection() {
         result1.is_ok(),
         "Command should succeed with healthy trust score"
     );
+    assert!(result1.unwrap().into_inner().success);
 
     println!("✓ Initial Command SUCCEEDED (Healthy Trust)");
 
@@ -538,7 +576,7 @@ async fn test_spoofed_signature_rejection() {
     let timestamp_ns = current_timestamp_ns();
 
     // Phase 4: Sign with spoofed key
-    let message = format!("{}:{}:{}", DEVICE_ID, command_json, timestamp_ns);
+    let message = format!("{}:{}:{}:nonce-1", DEVICE_ID, command_json, timestamp_ns);
     let spoofed_signature = spoofed_key.sign(message.as_bytes());
     let spoofed_sig_b64 = base64::encode(spoofed_signature.to_bytes());
 
@@ -548,6 +586,7 @@ async fn test_spoofed_signature_rejection() {
         command_json,
         signatures: vec![spoofed_sig_b64.clone()],
         timestamp_ns,
+        nonce: "nonce-1".to_string(),
     });
 
     request